@@ -0,0 +1,66 @@
+// This program is checked with all of the lints loaded at once. Each instruction is reported by
+// some of the lints, and must not be reported by the others. The findings are in `expected.txt`.
+use anchor_lang::prelude::*;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod e2e {
+    use super::*;
+
+    // An `allow` of one lint must not suppress the findings of the others: the `space` of
+    // `Initialize::orderbook` is still reported.
+    #[allow(account_loader_misuse)]
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+        orderbook.authority = ctx.accounts.payer.key();
+        Ok(())
+    }
+
+    pub fn update(ctx: Context<Update>, price: u64) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_init()?;
+        orderbook.price = price;
+        orderbook.updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        Ok(())
+    }
+
+    pub fn log_price(ctx: Context<LogPrice>) -> Result<()> {
+        #[allow(host_only_api)]
+        let start = Instant::now();
+        let orderbook = ctx.accounts.orderbook.load()?;
+        msg!("price: {}, elapsed: {:?}", orderbook.price, start.elapsed());
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = std::mem::size_of::<Orderbook>())]
+    orderbook: AccountLoader<'info, Orderbook>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    #[account(mut, has_one = authority)]
+    orderbook: AccountLoader<'info, Orderbook>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LogPrice<'info> {
+    orderbook: AccountLoader<'info, Orderbook>,
+}
+
+#[account(zero_copy)]
+pub struct Orderbook {
+    authority: Pubkey,
+    price: u64,
+    updated_at: u64,
+}
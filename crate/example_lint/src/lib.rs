@@ -0,0 +1,143 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::AccountsStruct;
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, ExprKind, FnDecl, HirId, Item,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    context::SolanaContextExt,
+    expansion::is_skipped_expansion,
+    sarif,
+    utils::{anchor_constraints::field_constraint_summary, visit_expr_no_bodies},
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the uses, in Anchor handlers, of the field `treasury` of an `Accounts` struct whose
+    /// key is checked neither by the constraints of the struct nor by the handler.
+    ///
+    /// This is not a lint meant to be used on Solana programs. It is an example of a lint specific
+    /// to a project, e.g., whose treasury is a fixed account, written in another library with the
+    /// public API of `solana_lints` (see `solana_lints::context`). Its tests check that the example
+    /// keeps compiling against the API.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A caller may pass any account as the treasury, e.g., to receive the fees of the program.
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item: collect the Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for every Anchor handler, excluding the functions generated by macros
+    /// - find the first use of the field `treasury` of a local struct, e.g.,
+    ///   `ctx.accounts.treasury`, whose key the handler does not compare
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each use, if the struct is an Anchor `Accounts` struct, and its constraints do not
+    ///   check the key of the field, report the use, with a note at the field
+    pub UNCHECKED_TREASURY,
+    Warn,
+    "a treasury whose key is not checked",
+    UncheckedTreasury::default()
+}
+
+const TREASURY_FIELD: &str = "treasury";
+
+#[derive(Default)]
+struct UncheckedTreasury {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    /// The uses of the treasury without a check of its key in their handler, with the struct
+    /// holding the field
+    uses: Vec<(HirId, Span, DefId)>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UncheckedTreasury {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = cx.anchor_accounts_struct(item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        if is_skipped_expansion(UNCHECKED_TREASURY, span)
+            || cx.context_accounts_struct(local_def_id).is_none()
+        {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if let ExprKind::Field(..) = expr.kind;
+                if !is_skipped_expansion(UNCHECKED_TREASURY, expr.span);
+                if let Some((def_id, field_name)) = cx.local_struct_field(expr);
+                if field_name == TREASURY_FIELD;
+                if !visit_expr_no_bodies(body.value, |other| cx.compares_key(other, expr));
+                then {
+                    self.uses.push((expr.hir_id, expr.span, def_id));
+                    return true;
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for &(hir_id, span, def_id) in &self.uses {
+            let accounts_struct = match self.anchor_accounts.get(&def_id) {
+                Some(accounts_struct) => accounts_struct,
+                None => continue,
+            };
+            if field_constraint_summary(accounts_struct, TREASURY_FIELD)
+                .map_or(false, |constraints| {
+                    constraints.has_key_check || constraints.is_key_compared
+                })
+            {
+                continue;
+            }
+            cx.report(
+                UNCHECKED_TREASURY,
+                hir_id,
+                span,
+                "the key of the treasury is not checked",
+                |diag| {
+                    if let Some(field_def) = cx.local_field_def(def_id, TREASURY_FIELD) {
+                        diag.span_note(
+                            field_def.span,
+                            "the field has no `address` or `seeds` constraint",
+                        );
+                    }
+                    diag.help("add the constraint `#[account(address = TREASURY)]` to the field");
+                },
+            );
+        }
+        sarif::write(cx, &[UNCHECKED_TREASURY]);
+    }
+}
+
+#[test]
+fn treasury() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "treasury");
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+pub const TREASURY: Pubkey = pubkey!("9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin");
+
+#[program]
+pub mod example_lint_treasury {
+    use super::*;
+
+    pub fn pay_fee(ctx: Context<PayFee>, amount: u64) -> Result<()> {
+        let treasury = ctx.accounts.treasury.to_account_info();
+        **treasury.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+
+    pub fn pay_checked_fee(ctx: Context<PayFee>, amount: u64) -> Result<()> {
+        if ctx.accounts.treasury.key() != TREASURY {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let treasury = ctx.accounts.treasury.to_account_info();
+        **treasury.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+
+    pub fn collect(ctx: Context<Collect>, amount: u64) -> Result<()> {
+        let treasury = ctx.accounts.treasury.to_account_info();
+        **treasury.try_borrow_mut_lamports()? -= amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct PayFee<'info> {
+    /// CHECK: receives the fee
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Collect<'info> {
+    /// CHECK: the treasury of the program
+    #[account(mut, address = TREASURY)]
+    pub treasury: UncheckedAccount<'info>,
+}
+
+fn main() {}
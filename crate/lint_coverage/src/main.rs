@@ -0,0 +1,347 @@
+//! Report the coverage of the lint libraries. Run with `lint_coverage [--json] [ROOT]`.
+//!
+//! For each library in `ROOT/lints`, the report lists the lints the library declares, whether it
+//! works on Anchor and non-Anchor programs, according to the "Works on" section of its
+//! documentation, and its ui tests, with the number of findings each test expects. It then lists
+//! the programs of https://github.com/coral-xyz/sealevel-attacks with the libraries covering them,
+//! i.e., the libraries whose description or README links to the program.
+//!
+//! The report is printed as markdown, or as JSON with `--json`. `ROOT` defaults to the repository
+//! the tool is built from.
+
+use serde_json::{json, Value};
+use std::{
+    env,
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+const USAGE: &str = "usage: lint_coverage [--json] [ROOT]";
+
+/// The programs of https://github.com/coral-xyz/sealevel-attacks
+const SEALEVEL_ATTACKS: &[&str] = &[
+    "0-signer-authorization",
+    "1-account-data-matching",
+    "2-owner-checks",
+    "3-type-cosplay",
+    "4-initialization",
+    "5-arbitrary-cpi",
+    "6-duplicate-mutable-accounts",
+    "7-bump-seed-canonicalization",
+    "8-pda-sharing",
+    "9-closing-accounts",
+    "10-sysvar-address-checking",
+];
+
+const SEALEVEL_ATTACKS_URL: &str =
+    "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/";
+
+struct Library {
+    name: String,
+    description: String,
+    /// The names of the lints, e.g., `missing_owner_check`
+    lints: Vec<String>,
+    anchor: bool,
+    non_anchor: bool,
+    examples: Vec<Example>,
+    /// The sealevel attacks the description or the README links to
+    sealevel_attacks: Vec<String>,
+}
+
+/// A ui test
+struct Example {
+    name: String,
+    /// The number of diagnostics in the expected output, if there is one
+    findings: Option<usize>,
+}
+
+fn main() {
+    let mut as_json = false;
+    let mut root = None;
+    for arg in env::args().skip(1) {
+        if arg == "--json" {
+            as_json = true;
+        } else if arg.starts_with('-') || root.is_some() {
+            fail(USAGE);
+        } else {
+            root = Some(PathBuf::from(arg));
+        }
+    }
+    let root = root.unwrap_or_else(default_root);
+
+    let libraries = libraries(&root);
+
+    if as_json {
+        println!("{:#}", to_json(&libraries));
+    } else {
+        print!("{}", to_markdown(&libraries));
+    }
+}
+
+/// Read the libraries in `root/lints`, in sorted order
+fn libraries(root: &Path) -> Vec<Library> {
+    let lints_dir = root.join("lints");
+    let entries = read_dir(&lints_dir).unwrap_or_else(|error| {
+        fail(&format!("{}: {error}", lints_dir.display()));
+    });
+    let mut paths = entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.join("Cargo.toml").exists())
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths.iter().map(|path| library(path)).collect()
+}
+
+fn library(path: &Path) -> Library {
+    let name = path.file_name().unwrap().to_string_lossy().into_owned();
+    let manifest = read(&path.join("Cargo.toml"));
+    let description = manifest
+        .lines()
+        .find_map(|line| string_value(line, "description"))
+        .unwrap_or_default();
+
+    let mut sources = Vec::new();
+    collect_sources(&path.join("src"), &mut sources);
+    let docs = sources
+        .iter()
+        .flat_map(|source| lint_declarations(&read(source)))
+        .collect::<Vec<_>>();
+    let works_on = |framework: &str| {
+        docs.iter()
+            .any(|(doc, _)| doc.lines().any(|line| line == format!("- [x] {framework}")))
+    };
+
+    // The description may link to a sealevel attack, and the README lists the references of the
+    // lint (see `solana_lints::references`).
+    let readme = read_to_string(path.join("README.md")).unwrap_or_default();
+    let mut sealevel_attacks = Vec::<String>::new();
+    for text in [&description, &readme] {
+        for attack in text
+            .split(SEALEVEL_ATTACKS_URL)
+            .skip(1)
+            .filter_map(|rest| rest.split(|c: char| c == ')' || c == '/').next())
+        {
+            if !sealevel_attacks.iter().any(|other| other == attack) {
+                sealevel_attacks.push(attack.to_owned());
+            }
+        }
+    }
+
+    Library {
+        examples: examples(path, &manifest),
+        anchor: works_on("Anchor"),
+        non_anchor: works_on("Non Anchor"),
+        lints: docs.into_iter().map(|(_, lint)| lint).collect(),
+        name,
+        description,
+        sealevel_attacks,
+    }
+}
+
+/// Return the documentation and the name of each lint declared in `source` with one of the
+/// `solana_lints` or `dylint_linting` macros, e.g., `declare_late_lint!`
+fn lint_declarations(source: &str) -> Vec<(String, String)> {
+    let mut declarations = Vec::new();
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        if !(line.ends_with("_lint! {")
+            && (line.starts_with("solana_lints::") || line.starts_with("dylint_linting::")))
+        {
+            continue;
+        }
+        let mut doc = String::new();
+        for line in lines.by_ref() {
+            let line = line.trim();
+            if let Some(text) = line.strip_prefix("///") {
+                doc.push_str(text.strip_prefix(' ').unwrap_or(text));
+                doc.push('\n');
+            } else if let Some(lint) = line
+                .strip_prefix("pub ")
+                .and_then(|rest| rest.strip_suffix(','))
+            {
+                declarations.push((doc, lint.to_lowercase()));
+                break;
+            }
+        }
+    }
+    declarations
+}
+
+/// Return the ui tests declared as `[[example]]`s in `manifest`, with the number of findings of
+/// each
+fn examples(path: &Path, manifest: &str) -> Vec<Example> {
+    let mut examples = Vec::new();
+    let mut name = None;
+    for line in manifest.lines() {
+        if line.starts_with('[') {
+            name = None;
+        }
+        if let Some(value) = string_value(line, "name") {
+            name = Some(value);
+        }
+        if let (Some(name), Some(source)) = (&name, string_value(line, "path")) {
+            let stderr = path.join(source).with_extension("stderr");
+            examples.push(Example {
+                name: name.clone(),
+                findings: read_to_string(stderr)
+                    .ok()
+                    .map(|stderr| count_findings(&stderr)),
+            });
+        }
+    }
+    examples
+}
+
+/// Return the number of diagnostics in the expected output of a ui test, excluding the summary,
+/// e.g., `error: aborting due to 2 previous errors`
+fn count_findings(stderr: &str) -> usize {
+    stderr
+        .lines()
+        .filter(|line| line.starts_with("error: ") || line.starts_with("warning: "))
+        .filter(|line| !line.starts_with("error: aborting due to"))
+        .filter(|line| !line.contains(" warning emitted") && !line.contains(" warnings emitted"))
+        .count()
+}
+
+/// If `line` is `key = "value"`, return `value`
+fn string_value(line: &str, key: &str) -> Option<String> {
+    let (name, value) = line.split_once('=')?;
+    if name.trim() != key {
+        return None;
+    }
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some(value.to_owned())
+}
+
+fn to_json(libraries: &[Library]) -> Value {
+    json!({
+        "libraries": libraries
+            .iter()
+            .map(|library| json!({
+                "name": library.name,
+                "description": library.description,
+                "lints": library.lints,
+                "anchor": library.anchor,
+                "non_anchor": library.non_anchor,
+                "examples": library
+                    .examples
+                    .iter()
+                    .map(|example| json!({
+                        "name": example.name,
+                        "findings": example.findings,
+                    }))
+                    .collect::<Vec<_>>(),
+                "sealevel_attacks": library.sealevel_attacks,
+            }))
+            .collect::<Vec<_>>(),
+        "sealevel_attacks": SEALEVEL_ATTACKS
+            .iter()
+            .map(|program| json!({
+                "program": program,
+                "libraries": covering(libraries, program),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+fn to_markdown(libraries: &[Library]) -> String {
+    let check = |works_on: bool| if works_on { ":heavy_check_mark:" } else { "" };
+    let mut markdown = String::from(
+        "## Libraries\n\n\
+         | Library | Lints | Anchor | Non Anchor | UI tests |\n\
+         | - | - | - | - | - |\n",
+    );
+    for library in libraries {
+        let lints = library
+            .lints
+            .iter()
+            .map(|lint| format!("`{lint}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let examples = library
+            .examples
+            .iter()
+            .map(|example| match example.findings {
+                Some(findings) => format!("`{}` ({findings})", example.name),
+                None => format!("`{}`", example.name),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let name = &library.name;
+        let anchor = check(library.anchor);
+        let non_anchor = check(library.non_anchor);
+        markdown.push_str(&format!(
+            "| [`{name}`](lints/{name}) | {lints} | {anchor} | {non_anchor} | {examples} |\n"
+        ));
+    }
+    markdown.push_str(
+        "\nThe number of findings each ui test expects is given in parentheses.\n\n\
+         ## Sealevel attacks\n\n\
+         | Program | Libraries |\n\
+         | - | - |\n",
+    );
+    for program in SEALEVEL_ATTACKS {
+        let libraries = covering(libraries, program)
+            .iter()
+            .map(|library| format!("`{library}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        markdown.push_str(&format!(
+            "| [{program}]({SEALEVEL_ATTACKS_URL}{program}) | {libraries} |\n"
+        ));
+    }
+    markdown
+}
+
+/// Return the names of the libraries covering the sealevel attack `program`
+fn covering<'a>(libraries: &'a [Library], program: &str) -> Vec<&'a str> {
+    libraries
+        .iter()
+        .filter(|library| {
+            library
+                .sealevel_attacks
+                .iter()
+                .any(|attack| attack == program)
+        })
+        .map(|library| library.name.as_str())
+        .collect()
+}
+
+/// Collect the `.rs` files in the directory `path` and its subdirectories, in sorted order
+fn collect_sources(path: &Path, sources: &mut Vec<PathBuf>) {
+    let Ok(entries) = read_dir(path) else {
+        return;
+    };
+    let mut paths = entries
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            collect_sources(&path, sources);
+        } else if path
+            .extension()
+            .map_or(false, |extension| extension == "rs")
+        {
+            sources.push(path);
+        }
+    }
+}
+
+fn read(path: &Path) -> String {
+    read_to_string(path).unwrap_or_else(|error| fail(&format!("{}: {error}", path.display())))
+}
+
+fn default_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .ancestors()
+        .nth(2)
+        .unwrap()
+        .to_path_buf()
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{msg}");
+    exit(2)
+}
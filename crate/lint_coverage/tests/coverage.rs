@@ -0,0 +1,50 @@
+use assert_cmd::Command;
+use serde_json::{json, Value};
+
+fn coverage() -> Value {
+    let output = Command::cargo_bin("lint_coverage")
+        .unwrap()
+        .arg("--json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn library_is_described() {
+    let coverage = coverage();
+    let library = coverage["libraries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|library| library["name"] == "missing_owner_check")
+        .unwrap();
+    assert_eq!(library["lints"], json!(["missing_owner_check"]));
+    assert_eq!(library["anchor"], true);
+    assert_eq!(library["non_anchor"], true);
+    assert_eq!(library["sealevel_attacks"], json!(["2-owner-checks"]));
+    assert!(library["examples"]
+        .as_array()
+        .unwrap()
+        .contains(&json!({ "name": "secure", "findings": 0 })));
+}
+
+#[test]
+fn every_library_declares_a_lint() {
+    let coverage = coverage();
+    for library in coverage["libraries"].as_array().unwrap() {
+        assert_ne!(library["lints"], json!([]), "{}", library["name"]);
+    }
+}
+
+#[test]
+fn sealevel_attacks_are_listed() {
+    Command::cargo_bin("lint_coverage")
+        .unwrap()
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "| [5-arbitrary-cpi](https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/5-arbitrary-cpi) | `arbitrary_cpi` |",
+        ));
+}
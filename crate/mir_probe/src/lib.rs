@@ -0,0 +1,142 @@
+#![feature(rustc_private)]
+#![feature(box_patterns)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+
+use clippy_utils::diagnostics::span_lint;
+use if_chain::if_chain;
+use rustc_hir::Body;
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::{
+    mir::{
+        self, BorrowKind, Local, MutBorrowKind, Operand, ProjectionElem, Rvalue, StatementKind,
+        TerminatorKind,
+    },
+    ty::TyKind,
+};
+use solana_lints::{mir_cache::BodyAnalysis, utils::dataflow::is_moved_from};
+
+dylint_linting::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Checks `solana_lints::utils::dataflow` against the MIR of the functions of the crate being
+    /// checked. Each function is expected to pass its first parameter to a function or method
+    /// named `sink`, through the form of assignment the function is named after:
+    ///
+    /// - `copy_for_deref`: `_4 = deref_copy ((*_1).0: &Key)`
+    /// - `field_temporary`: `_2 = (_3.1: u8)`, where `_3` is a temporary
+    /// - `two_phase_borrow`: `_4 = &mut _2`, a two-phase borrow
+    ///
+    /// This is not a lint meant to be used on Solana programs. It is run on a small crate, so that
+    /// a change in the MIR the toolchain generates, or in the helpers, is caught by the tests.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The lints following values through the MIR, e.g., `arbitrary_cpi` and
+    /// `bump_seed_canonicalization`, use the helpers. If the helpers do not recognize a form of
+    /// assignment, the chains of aliases they follow stop at it, and the lints silently miss or
+    /// misreport the values.
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// - For every function named after a form of assignment
+    ///   - If no statement of its MIR is an assignment of that form, report the function
+    ///   - For every call to `sink`, if `is_moved_from`, or its variant through the predecessors
+    ///     cached by `solana_lints::mir_cache::BodyAnalysis`, does not trace the first argument of
+    ///     the call back to the first parameter of the function, report the call
+    pub MIR_PROBE,
+    Deny,
+    "MIR forms of assignment which `solana_lints::utils::dataflow` does not follow"
+}
+
+impl<'tcx> LateLintPass<'tcx> for MirProbe {
+    fn check_body(&mut self, cx: &LateContext<'tcx>, body: &'tcx Body<'tcx>) {
+        let def_id = cx.tcx.hir().body_owner_def_id(body.id()).to_def_id();
+        if !cx.tcx.def_kind(def_id).is_fn_like() {
+            return;
+        }
+        let name = cx.tcx.item_name(def_id);
+        let (form, is_form): (_, fn(&mir::Body<'_>, &Rvalue<'_>) -> bool) = match name.as_str() {
+            "copy_for_deref" => ("a `deref_copy`", |_, rvalue| {
+                matches!(rvalue, Rvalue::CopyForDeref(_))
+            }),
+            "field_temporary" => ("a field of a temporary", |body, rvalue| {
+                matches!(
+                    rvalue,
+                    Rvalue::Use(Operand::Copy(place) | Operand::Move(place))
+                        if place.local.as_usize() > body.arg_count
+                            && matches!(place.projection.as_slice(), [ProjectionElem::Field(..)])
+                )
+            }),
+            "two_phase_borrow" => ("a two-phase borrow", |_, rvalue| {
+                matches!(
+                    rvalue,
+                    Rvalue::Ref(
+                        _,
+                        BorrowKind::Mut {
+                            kind: MutBorrowKind::TwoPhaseBorrow
+                        },
+                        _
+                    )
+                )
+            }),
+            _ => return,
+        };
+        let body_mir = cx.tcx.optimized_mir(def_id);
+
+        if !body_mir
+            .basic_blocks
+            .iter()
+            .flat_map(|block_data| &block_data.statements)
+            .any(|stmt| match &stmt.kind {
+                StatementKind::Assign(box (_, rvalue)) => is_form(body_mir, rvalue),
+                _ => false,
+            })
+        {
+            span_lint(
+                cx,
+                MIR_PROBE,
+                cx.tcx.def_span(def_id),
+                &format!(
+                    "the MIR of `{name}` does not contain {form}; update the probe to the MIR the toolchain generates"
+                ),
+            );
+        }
+
+        // the first parameter is `_1`, as `_0` is the return place
+        let parameter = Local::from_u32(1);
+        let analysis = BodyAnalysis::new(cx.tcx, body_mir);
+        for (block, block_data) in body_mir.basic_blocks.iter_enumerated() {
+            let terminator = block_data.terminator();
+            if_chain! {
+                if let TerminatorKind::Call {
+                    func: Operand::Constant(box func),
+                    args,
+                    ..
+                } = &terminator.kind;
+                if let TyKind::FnDef(callee, _) = func.const_.ty().kind();
+                if cx.tcx.item_name(*callee).as_str() == "sink";
+                if let Some(Operand::Copy(arg) | Operand::Move(arg)) = args.first();
+                if !is_moved_from(cx.tcx, body_mir, block, arg, &[parameter])
+                    || !analysis.is_moved_from(body_mir, block, arg, &[parameter]);
+                then {
+                    span_lint(
+                        cx,
+                        MIR_PROBE,
+                        terminator.source_info.span,
+                        &format!(
+                            "the argument of `sink` is not traced back to the parameter of `{name}`"
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn probe() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "probe");
+}
@@ -0,0 +1,45 @@
+// Each function passes its parameter to `sink`, through the form of assignment it is named after.
+// `mir_probe` checks that the MIR of each function still contains that form, and that
+// `solana_lints::utils::dataflow::is_moved_from` traces the argument of `sink` back to the
+// parameter.
+
+pub struct Key([u8; 32]);
+
+pub struct Holder<'a> {
+    pub key: &'a Key,
+}
+
+pub struct Pair {
+    pub keys: Vec<u8>,
+    pub bump: u8,
+}
+
+pub trait Sink {
+    fn sink(&mut self, len: usize);
+}
+
+impl Sink for Vec<u8> {
+    fn sink(&mut self, _: usize) {}
+}
+
+pub fn sink<T>(_: T) {}
+
+// `_4 = deref_copy ((*_1).0: &Key)`
+pub fn copy_for_deref(holder: &Holder<'_>) {
+    sink(&holder.key.0);
+}
+
+// `_3 = move _1; _2 = (_3.1: u8)`
+pub fn field_temporary(pair: Pair) {
+    let bump = { pair }.bump;
+    sink(bump);
+}
+
+// `_4 = &mut _2`, made before `keys.len()` is called
+pub fn two_phase_borrow(keys: Vec<u8>) {
+    let mut keys = keys;
+    keys.sink(keys.len());
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,122 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{def_path_res, diagnostics::span_lint, match_def_path};
+use rustc_hir::def::Res;
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::DUMMY_SP;
+use solana_lints::{known_programs, paths};
+
+dylint_linting::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the paths in `solana_lints::paths`, and the paths of the program IDs in
+    /// `solana_lints::known_programs`, which do not resolve in the crate being checked.
+    ///
+    /// This is not a lint meant to be used on Solana programs. It is run on a program which depends
+    /// on the supported versions of `anchor-lang`, `anchor-spl`, and `solana-program`, so that a path
+    /// which no longer refers to an item, e.g., because the item was moved to another module, is
+    /// caught by the tests.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The lints use the paths with `match_def_path`. If a path does not resolve, `match_def_path`
+    /// silently returns false, and the lints which use the path stop reporting anything.
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// - For every path in `solana_lints::paths::ALL` and in the `items` of
+    ///   `solana_lints::known_programs::KNOWN_PROGRAMS`
+    ///   - Resolve the path with `def_path_res`
+    ///   - If none of the resolved items is recognized by `match_def_path` with the same path,
+    ///     report the path
+    pub PATHS_PROBE,
+    Deny,
+    "paths in `solana_lints::paths` and `solana_lints::known_programs` which do not resolve"
+}
+
+impl<'tcx> LateLintPass<'tcx> for PathsProbe {
+    fn check_crate(&mut self, cx: &LateContext<'tcx>) {
+        for (name, path) in paths::ALL {
+            if !resolves(cx, path) {
+                span_lint(
+                    cx,
+                    PATHS_PROBE,
+                    DUMMY_SP,
+                    &format!("`paths::{name}` (`{}`) does not resolve", path.join("::")),
+                );
+            }
+        }
+        for program in known_programs::KNOWN_PROGRAMS {
+            for path in program.items {
+                if !resolves(cx, path) {
+                    span_lint(
+                        cx,
+                        PATHS_PROBE,
+                        DUMMY_SP,
+                        &format!(
+                            "the ID of the {} (`{}`) does not resolve",
+                            program.name,
+                            path.join("::")
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Return true if `path` resolves to an item which `match_def_path` recognizes with the same path.
+/// A path which resolves only through a re-export is not recognized.
+fn resolves(cx: &LateContext<'_>, path: &[&str]) -> bool {
+    def_path_res(cx, path).into_iter().any(|res| match res {
+        Res::Def(_, def_id) => match_def_path(cx, def_id, path),
+        _ => false,
+    })
+}
+
+#[test]
+fn probe() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "probe");
+}
+
+#[test]
+fn all_paths_are_probed() {
+    let source = include_str!("../../src/paths.rs");
+    let mut names = source
+        .lines()
+        .filter_map(|line| line.strip_prefix("pub const "))
+        .filter_map(|line| line.split(':').next())
+        .collect::<Vec<_>>();
+    let mut probed = paths::ALL.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    names.sort_unstable();
+    probed.sort_unstable();
+    assert_eq!(names, probed);
+}
+
+#[test]
+fn all_paths_are_distinct() {
+    for (i, (name, path)) in paths::ALL.iter().enumerate() {
+        assert!(
+            paths::ALL[..i].iter().all(|(other, _)| other != name),
+            "`paths::{name}` is listed twice"
+        );
+        assert!(
+            paths::ALL[..i].iter().all(|(_, other)| other != path),
+            "`paths::{name}` duplicates another path"
+        );
+    }
+}
+
+#[test]
+fn anchor_wrappers_are_listed() {
+    for wrapper in paths::AnchorWrapper::ALL {
+        assert!(
+            paths::ALL.iter().any(|(_, path)| *path == wrapper.path()),
+            "the path of `{wrapper:?}` is not listed in `paths::ALL`"
+        );
+    }
+}
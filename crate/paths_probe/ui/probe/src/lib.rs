@@ -0,0 +1,56 @@
+// This program only needs to load the crates that the paths in `solana_lints::paths` and
+// `solana_lints::known_programs` refer to: `anchor_lang`, `anchor_spl` (for `spl_token`,
+// `spl_associated_token_account`, with the `token_2022` feature, `spl_token_2022`, and, with the
+// `metadata` feature, `mpl_token_metadata`), `borsh`, `bytemuck`, `rand`, and `solana_program`.
+
+extern crate bytemuck;
+extern crate rand;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_spl::token::spl_token;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod paths_probe {
+    use super::*;
+
+    pub fn cpi(ctx: Context<Cpi>, amount: u64) -> ProgramResult {
+        let _ = Config::try_from_slice(&ctx.accounts.config.data.borrow()).unwrap();
+        let _ = Clock::get()?;
+        solana_program::program::invoke(
+            &spl_token::instruction::transfer(
+                &spl_token::ID,
+                ctx.accounts.source.key,
+                ctx.accounts.destination.key,
+                ctx.accounts.authority.key,
+                &[],
+                amount,
+            )?,
+            &[
+                ctx.accounts.source.clone(),
+                ctx.accounts.destination.clone(),
+                ctx.accounts.authority.to_account_info(),
+            ],
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct Cpi<'info> {
+    config: AccountInfo<'info>,
+    source: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: Signer<'info>,
+    token_program: Program<'info, anchor_spl::token::Token>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct Config {
+    fee: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
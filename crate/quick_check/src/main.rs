@@ -0,0 +1,127 @@
+//! Quick textual pre-checks for Solana programs. Run with `quick_check [--list] [PATH]...`.
+//!
+//! The checks parse the source files with `syn` and do not require the nightly toolchain or
+//! `rustc_private`, so they run where building the lints is impractical. They are of lower
+//! fidelity than the lints: names are not resolved, types are not known, and the contents of
+//! macro invocations other than `declare_id!` are not checked. Use the lints, with
+//! `cargo dylint`, for complete results.
+//!
+//! Each finding is printed as `path:line:column: rule: message`. The exit status is 1 if there are
+//! findings and 2 if a file cannot be read or parsed.
+
+use std::{
+    env,
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+mod rules;
+
+const USAGE: &str = "usage: quick_check [--list] [PATH]...";
+
+fn main() {
+    let mut paths = Vec::new();
+    for arg in env::args().skip(1) {
+        if arg == "--list" {
+            list_rules();
+            return;
+        } else if arg.starts_with('-') {
+            eprintln!("{USAGE}");
+            exit(2);
+        } else {
+            paths.push(PathBuf::from(arg));
+        }
+    }
+    if paths.is_empty() {
+        paths.push(PathBuf::from("."));
+    }
+
+    let mut files = Vec::new();
+    for path in &paths {
+        collect_files(path, &mut files);
+    }
+
+    let mut found = false;
+    let mut failed = false;
+    for file in files {
+        let source = match read_to_string(&file) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("{}: {error}", file.display());
+                failed = true;
+                continue;
+            }
+        };
+        let syntax = match syn::parse_file(&source) {
+            Ok(syntax) => syntax,
+            Err(error) => {
+                eprintln!("{}: failed to parse: {error}", file.display());
+                failed = true;
+                continue;
+            }
+        };
+        for finding in rules::check(&syntax) {
+            println!(
+                "{}:{}:{}: {}: {}",
+                file.display(),
+                finding.line,
+                finding.column,
+                finding.rule.name,
+                finding.msg
+            );
+            found = true;
+        }
+    }
+
+    if failed {
+        exit(2);
+    }
+    if found {
+        exit(1);
+    }
+}
+
+fn list_rules() {
+    for rule in rules::RULES {
+        println!("{}\n    {}", rule.name, rule.summary);
+        if let Some(lint) = &rule.lint {
+            println!(
+                "    The `{}` lint reports the issue with full fidelity:",
+                lint.name
+            );
+            for line in lint.description().unwrap_or_default().lines() {
+                println!("    > {line}");
+            }
+        }
+    }
+}
+
+/// Collect the `.rs` files at `path`, skipping `target` and hidden directories, in sorted order
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        files.push(path.to_path_buf());
+        return;
+    }
+    let Ok(entries) = read_dir(path) else {
+        eprintln!("{}: not a file or directory", path.display());
+        exit(2);
+    };
+    let mut paths = entries
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            if name != "target" && !name.starts_with('.') {
+                collect_files(&path, files);
+            }
+        } else if path
+            .extension()
+            .map_or(false, |extension| extension == "rs")
+        {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use syn::{
+    spanned::Spanned,
+    visit::{self, Visit},
+    Attribute, Expr, ExprCall, Lit, LitStr, Macro,
+};
+
+pub struct Rule {
+    pub name: &'static str,
+    /// What the rule reports
+    pub summary: &'static str,
+    /// The lint which reports the issue with full fidelity, if any
+    pub lint: Option<Lint>,
+}
+
+pub struct Lint {
+    pub name: &'static str,
+    readme: &'static str,
+}
+
+impl Lint {
+    /// Return the "What it does" section of the lint's README
+    pub fn description(&self) -> Option<&'static str> {
+        let (_, rest) = self.readme.split_once("**What it does:**")?;
+        let (description, _) = rest.split_once("**Why is this bad?**")?;
+        Some(description.trim())
+    }
+}
+
+pub const PLACEHOLDER_PROGRAM_ID: Rule = Rule {
+    name: "placeholder_program_id",
+    summary: "Reports `declare_id!` with a placeholder program id, e.g., the one of the Anchor \
+              templates. The program fails its program id checks, and derives other PDAs, once \
+              it is deployed at its own address.",
+    lint: None,
+};
+
+pub const EMPTY_SEEDS: Rule = Rule {
+    name: "empty_seeds",
+    summary: "Reports PDAs derived from no seeds, i.e., `seeds = []` or \
+              `Pubkey::find_program_address(&[], ..)`. Such a PDA is a single address shared by \
+              all of the users of the program.",
+    lint: None,
+};
+
+pub const TRY_FROM_SLICE_ACCOUNT_DATA: Rule = Rule {
+    name: "try_from_slice_account_data",
+    summary: "Reports account data deserialized with `try_from_slice`, which does not check the \
+              discriminator of the account type.",
+    lint: Some(Lint {
+        name: "type_cosplay",
+        readme: include_str!("../../../lints/type_cosplay/README.md"),
+    }),
+};
+
+pub const LITERAL_INSTRUCTION_INDEX: Rule = Rule {
+    name: "literal_instruction_index",
+    summary: "Reports `load_instruction_at_checked` called with a literal index. An absolute \
+              index lets the caller choose which instruction is checked by reordering the \
+              instructions of the transaction. Use an index relative to \
+              `load_current_index_checked` instead.",
+    lint: None,
+};
+
+pub const RULES: &[Rule] = &[
+    PLACEHOLDER_PROGRAM_ID,
+    EMPTY_SEEDS,
+    TRY_FROM_SLICE_ACCOUNT_DATA,
+    LITERAL_INSTRUCTION_INDEX,
+];
+
+/// The program ids of the Anchor templates and the System Program
+const PLACEHOLDER_PROGRAM_IDS: &[&str] = &[
+    "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+    "11111111111111111111111111111111",
+];
+
+pub struct Finding {
+    pub rule: &'static Rule,
+    /// 1-based line and column
+    pub line: usize,
+    pub column: usize,
+    pub msg: String,
+}
+
+/// Run the rules on the parsed file
+pub fn check(file: &syn::File) -> Vec<Finding> {
+    let mut checker = Checker::default();
+    checker.visit_file(file);
+    checker
+        .findings
+        .sort_by_key(|finding| (finding.line, finding.column));
+    checker.findings
+}
+
+#[derive(Default)]
+struct Checker {
+    findings: Vec<Finding>,
+}
+
+impl Checker {
+    fn report(&mut self, rule: &'static Rule, node: &impl Spanned, msg: String) {
+        let start = node.span().start();
+        self.findings.push(Finding {
+            rule,
+            line: start.line,
+            column: start.column + 1,
+            msg,
+        });
+    }
+}
+
+impl<'ast> Visit<'ast> for Checker {
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        if_placeholder_program_id(mac, |program_id| {
+            self.report(
+                &PLACEHOLDER_PROGRAM_ID,
+                mac,
+                format!("`declare_id!` uses the placeholder program id `{program_id}`"),
+            );
+        });
+        visit::visit_macro(self, mac);
+    }
+
+    fn visit_attribute(&mut self, attr: &'ast Attribute) {
+        if attr.path.is_ident("account") && has_empty_seeds(attr.tokens.clone()) {
+            self.report(
+                &EMPTY_SEEDS,
+                attr,
+                "the account is a PDA derived from no seeds".to_owned(),
+            );
+        }
+        visit::visit_attribute(self, attr);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        let first_arg = call.args.first();
+        match (called_fn_name(call).as_deref(), first_arg) {
+            (Some(name @ ("find_program_address" | "create_program_address")), Some(seeds))
+                if is_empty_array(seeds) =>
+            {
+                self.report(
+                    &EMPTY_SEEDS,
+                    call,
+                    format!("`{name}` is called with no seeds"),
+                );
+            }
+            (Some("try_from_slice"), Some(data)) if is_account_data(data) => {
+                self.report(
+                    &TRY_FROM_SLICE_ACCOUNT_DATA,
+                    call,
+                    "account data is deserialized with `try_from_slice`".to_owned(),
+                );
+            }
+            (Some("load_instruction_at_checked"), Some(index)) if is_int_literal(index) => {
+                self.report(
+                    &LITERAL_INSTRUCTION_INDEX,
+                    call,
+                    "`load_instruction_at_checked` is called with a literal index".to_owned(),
+                );
+            }
+            _ => {}
+        }
+        visit::visit_expr_call(self, call);
+    }
+}
+
+/// If the macro is `declare_id!` with a placeholder program id, call `f` with the program id
+fn if_placeholder_program_id(mac: &Macro, f: impl FnOnce(&str)) {
+    if !mac
+        .path
+        .segments
+        .last()
+        .map_or(false, |segment| segment.ident == "declare_id")
+    {
+        return;
+    }
+    if let Ok(lit) = mac.parse_body::<LitStr>() {
+        let program_id = lit.value();
+        if PLACEHOLDER_PROGRAM_IDS.contains(&program_id.as_str()) {
+            f(&program_id);
+        }
+    }
+}
+
+/// Return true if the tokens of an `#[account(...)]` attribute contain `seeds = []`
+fn has_empty_seeds(tokens: TokenStream) -> bool {
+    let tokens = tokens
+        .into_iter()
+        .flat_map(|tree| match tree {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Parenthesis => {
+                group.stream().into_iter().collect()
+            }
+            tree => vec![tree],
+        })
+        .collect::<Vec<_>>();
+    tokens.windows(3).any(|window| {
+        matches!(
+            window,
+            [TokenTree::Ident(ident), TokenTree::Punct(punct), TokenTree::Group(group)]
+                if ident == "seeds"
+                    && punct.as_char() == '='
+                    && group.delimiter() == Delimiter::Bracket
+                    && group.stream().is_empty()
+        )
+    })
+}
+
+/// Return the last segment of the path of the called function, e.g., `try_from_slice` for
+/// `User::try_from_slice(..)`
+fn called_fn_name(call: &ExprCall) -> Option<String> {
+    match &*call.func {
+        Expr::Path(expr_path) => expr_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Return true if the expression is `&[]`
+fn is_empty_array(expr: &Expr) -> bool {
+    match expr {
+        Expr::Reference(reference) => is_empty_array(&reference.expr),
+        Expr::Array(array) => array.elems.is_empty(),
+        _ => false,
+    }
+}
+
+/// Return true if the expression is an integer literal, possibly cast, e.g., `0` or `1 as usize`
+fn is_int_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(expr_lit) => matches!(expr_lit.lit, Lit::Int(_)),
+        Expr::Cast(cast) => is_int_literal(&cast.expr),
+        Expr::Paren(paren) => is_int_literal(&paren.expr),
+        _ => false,
+    }
+}
+
+/// Return true if the expression borrows the data of an account, e.g., `&account.data.borrow()`,
+/// `&account.data.borrow()[8..]` or `&account.try_borrow_data()?`. Other expressions, e.g., a
+/// local holding the data, are not recognized, so that instruction data is not reported.
+fn is_account_data(expr: &Expr) -> bool {
+    match expr {
+        Expr::Reference(reference) => is_account_data(&reference.expr),
+        Expr::Paren(paren) => is_account_data(&paren.expr),
+        Expr::Index(index) => is_account_data(&index.expr),
+        Expr::Try(try_expr) => is_account_data(&try_expr.expr),
+        Expr::MethodCall(method_call) => match method_call.method.to_string().as_str() {
+            "try_borrow_data" | "try_borrow_mut_data" => true,
+            "borrow" | "borrow_mut" | "try_borrow" | "try_borrow_mut" => matches!(
+                &*method_call.receiver,
+                Expr::Field(field) if matches!(&field.member, syn::Member::Named(ident) if ident == "data")
+            ),
+            _ => is_account_data(&method_call.receiver),
+        },
+        _ => false,
+    }
+}
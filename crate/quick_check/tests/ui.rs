@@ -0,0 +1,39 @@
+use assert_cmd::Command;
+use std::{fs::read_to_string, path::Path};
+
+#[test]
+fn insecure() {
+    Command::cargo_bin("quick_check")
+        .unwrap()
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg("ui/insecure.rs")
+        .assert()
+        .code(1)
+        .stdout(
+            read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("ui/insecure.stdout"))
+                .unwrap(),
+        );
+}
+
+#[test]
+fn secure() {
+    Command::cargo_bin("quick_check")
+        .unwrap()
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .arg("ui/secure.rs")
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn rules_are_listed() {
+    Command::cargo_bin("quick_check")
+        .unwrap()
+        .arg("--list")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains(
+            "The `type_cosplay` lint reports the issue with full fidelity:",
+        ));
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::load_instruction_at_checked;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod quick_check_insecure {
+    use super::*;
+
+    pub fn update(ctx: Context<Update>, value: u64) -> Result<()> {
+        let mut user = User::try_from_slice(&ctx.accounts.user.data.borrow())?;
+        user.value = value;
+        Ok(())
+    }
+
+    pub fn claim(ctx: Context<Claim>) -> Result<()> {
+        let instruction = load_instruction_at_checked(0, &ctx.accounts.instructions)?;
+        msg!("Previous program: {}", instruction.program_id);
+        let (_, _) = Pubkey::find_program_address(&[], ctx.program_id);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    user: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(mut, seeds = [], bump)]
+    state: Account<'info, State>,
+    instructions: AccountInfo<'info>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct User {
+    value: u64,
+}
+
+#[account]
+pub struct State {
+    claimed: bool,
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+declare_id!("3ZgGpKmnh9R8X4Fdx1QZyqJNcN9MW9Zjbx8Ey5yrRiXq");
+
+#[program]
+pub mod quick_check_secure {
+    use super::*;
+
+    pub fn update(ctx: Context<Update>, value: u64) -> Result<()> {
+        let mut user = User::try_deserialize(&mut &ctx.accounts.user.data.borrow()[..])?;
+        user.value = value;
+        Ok(())
+    }
+
+    pub fn claim(ctx: Context<Claim>, data: Vec<u8>) -> Result<()> {
+        let args = ClaimArgs::try_from_slice(&data)?;
+        let current_index = load_current_index_checked(&ctx.accounts.instructions)?;
+        let instruction =
+            load_instruction_at_checked(current_index as usize - 1, &ctx.accounts.instructions)?;
+        msg!("Previous program: {}", instruction.program_id);
+        let (_, _) = Pubkey::find_program_address(
+            &[b"claim", ctx.accounts.authority.key.as_ref()],
+            ctx.program_id,
+        );
+        msg!("Amount: {}", args.amount);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    user: Account<'info, User>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(mut, seeds = [b"state", authority.key().as_ref()], bump)]
+    state: Account<'info, State>,
+    authority: Signer<'info>,
+    instructions: AccountInfo<'info>,
+}
+
+#[account]
+pub struct User {
+    value: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ClaimArgs {
+    amount: u64,
+}
+
+#[account]
+pub struct State {
+    claimed: bool,
+}
@@ -0,0 +1,38 @@
+//! Writing the lock files of the lints: `discriminators.lock` (see [`crate::discriminators`]),
+//! `layouts.lock` (see [`crate::layouts`]) and `error_codes.lock` (see [`crate::error_codes`]).
+//!
+//! A lint never writes its lock file unless `SOLANA_LINTS_BLESS` is set, so that checking a
+//! package does not change it. Once set, the lints record what they find in the lock files of the
+//! packages they check, replacing the files which exist, instead of comparing with them, e.g.,
+//!
+//! ```sh
+//! SOLANA_LINTS_BLESS=1 cargo dylint --all --workspace
+//! ```
+//!
+//! The lock files should then be reviewed and committed, as they record what is on chain.
+
+use rustc_lint::LateContext;
+use std::{env, io, path::Path};
+
+pub const BLESS_VAR: &str = "SOLANA_LINTS_BLESS";
+
+/// Return true if the lints should write their lock files rather than compare with them
+pub fn is_blessing() -> bool {
+    env::var_os(BLESS_VAR).is_some()
+}
+
+/// Write the lock file `path` with `write`, warning that `what`, e.g., "layouts", could not be
+/// written if it fails
+pub fn write_lock_or_warn(
+    cx: &LateContext<'_>,
+    what: &str,
+    path: &Path,
+    write: impl FnOnce(&Path) -> io::Result<()>,
+) {
+    if let Err(error) = write(path) {
+        cx.tcx.sess.dcx().warn(format!(
+            "could not write the {what} file `{}`: {error}",
+            path.display()
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+//! An extension trait of `LateContext`, [`SolanaContextExt`], gathering the helpers of this crate
+//! which a lint of another library most likely needs: recognizing the Anchor `Accounts` structs
+//! and the handlers using them, the accounts of an expression and the checks of their keys, the IDs
+//! of well-known programs, and reporting a finding through [`crate::diagnostics`].
+//!
+//! A private, project-specific lint depends on `solana-lints` and imports the trait, e.g.,
+//!
+//! ```rust,ignore
+//! use solana_lints::context::SolanaContextExt;
+//!
+//! impl<'tcx> LateLintPass<'tcx> for UncheckedTreasury {
+//!     fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+//!         if let Some(accounts_struct) = cx.anchor_accounts_struct(item) {
+//!             self.anchor_accounts
+//!                 .insert(item.owner_id.to_def_id(), accounts_struct);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The library `crate/example_lint` of the repository is a complete example, which the tests of
+//! this crate build and run, so that the example keeps compiling against the API.
+
+use anchor_syn::AccountsStruct;
+use rustc_errors::{DiagnosticBuilder, MultiSpan};
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    Expr, FieldDef, HirId, Item,
+};
+use rustc_lint::{LateContext, Level, Lint};
+use rustc_middle::ty::Ty;
+
+use crate::{diagnostics, known_programs, utils};
+
+/// The helpers of this crate, as methods of `LateContext`. Each method calls the function of the
+/// same name, whose documentation describes it in more detail.
+pub trait SolanaContextExt<'tcx> {
+    /// The Anchor `Accounts` struct of `item`, if it is one (see
+    /// [`utils::get_anchor_accounts_struct`])
+    fn anchor_accounts_struct(&self, item: &'tcx Item<'tcx>) -> Option<AccountsStruct>;
+
+    /// The def id of `T` if the function `local_def_id` takes a `Context<T>` argument, i.e., if it
+    /// is an Anchor handler (see [`utils::context_accounts_struct`])
+    fn context_accounts_struct(&self, local_def_id: LocalDefId) -> Option<DefId>;
+
+    /// The def id of `T` if `ty` is `Account<'info, T>` or `AccountLoader<'info, T>`, or a `Box`
+    /// of them (see [`utils::account_data_type`])
+    fn account_data_type(&self, ty: Ty<'tcx>) -> Option<DefId>;
+
+    /// Return true if the crate being checked is an Anchor program (see
+    /// [`utils::is_anchor_program`])
+    fn is_anchor_program(&self) -> bool;
+
+    /// The local struct and the name of its field if `expr` is a field of a local struct, e.g.,
+    /// `ctx.accounts.vault`, or a reference to one (see [`utils::local_struct_field`])
+    fn local_struct_field(&self, expr: &'tcx Expr<'tcx>) -> Option<(DefId, String)>;
+
+    /// The definition of the field `field_name` of the local struct `def_id` (see
+    /// [`utils::local_field_def`])
+    fn local_field_def(&self, def_id: DefId, field_name: &str) -> Option<&'tcx FieldDef<'tcx>>;
+
+    /// Return true if `expr` compares the key of `account` (see [`utils::compares_key`])
+    fn compares_key(&self, expr: &'tcx Expr<'tcx>, account: &Expr<'tcx>) -> bool;
+
+    /// Return true if `expr` is the ID of a well-known program, or of a program of `extra`, e.g.,
+    /// `["my_program::ID"]` (see [`known_programs::is_known_program_id_const`])
+    fn is_known_program_id(&self, expr: &Expr<'_>, extra: &[String]) -> bool;
+
+    /// The level of `lint` at `hir_id` (see [`diagnostics::lint_level`])
+    fn lint_level(&self, lint: &'static Lint, hir_id: HirId) -> Level;
+
+    /// Report a finding of `lint` at `hir_id`, which is also recorded for the SARIF output (see
+    /// [`diagnostics::span_lint_hir_and_then`])
+    fn report(
+        &self,
+        lint: &'static Lint,
+        hir_id: HirId,
+        sp: impl Into<MultiSpan>,
+        msg: &str,
+        f: impl FnOnce(&mut DiagnosticBuilder<'_, ()>),
+    );
+}
+
+impl<'tcx> SolanaContextExt<'tcx> for LateContext<'tcx> {
+    fn anchor_accounts_struct(&self, item: &'tcx Item<'tcx>) -> Option<AccountsStruct> {
+        utils::get_anchor_accounts_struct(self, item)
+    }
+
+    fn context_accounts_struct(&self, local_def_id: LocalDefId) -> Option<DefId> {
+        utils::context_accounts_struct(self, local_def_id)
+    }
+
+    fn account_data_type(&self, ty: Ty<'tcx>) -> Option<DefId> {
+        utils::account_data_type(self, ty)
+    }
+
+    fn is_anchor_program(&self) -> bool {
+        utils::is_anchor_program(self)
+    }
+
+    fn local_struct_field(&self, expr: &'tcx Expr<'tcx>) -> Option<(DefId, String)> {
+        utils::local_struct_field(self, expr)
+    }
+
+    fn local_field_def(&self, def_id: DefId, field_name: &str) -> Option<&'tcx FieldDef<'tcx>> {
+        utils::local_field_def(self, def_id, field_name)
+    }
+
+    fn compares_key(&self, expr: &'tcx Expr<'tcx>, account: &Expr<'tcx>) -> bool {
+        utils::compares_key(self, expr, account)
+    }
+
+    fn is_known_program_id(&self, expr: &Expr<'_>, extra: &[String]) -> bool {
+        known_programs::is_known_program_id_const(self, expr, extra)
+    }
+
+    fn lint_level(&self, lint: &'static Lint, hir_id: HirId) -> Level {
+        diagnostics::lint_level(self, lint, hir_id)
+    }
+
+    fn report(
+        &self,
+        lint: &'static Lint,
+        hir_id: HirId,
+        sp: impl Into<MultiSpan>,
+        msg: &str,
+        f: impl FnOnce(&mut DiagnosticBuilder<'_, ()>),
+    ) {
+        diagnostics::span_lint_hir_and_then(self, lint, hir_id, sp, msg, f);
+    }
+}
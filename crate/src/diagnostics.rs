@@ -0,0 +1,154 @@
+//! Wrappers around `clippy_utils::diagnostics` which also record the diagnostics for SARIF output
+//! (see [`crate::sarif`]), and their machine-applicable suggestions for the patch of
+//! [`crate::fixes`]. The lints call these functions instead of the ones of `clippy_utils`.
+//!
+//! Each finding has a [`Confidence`]. The findings of [`span_lint_hir`] and
+//! [`span_lint_hir_and_then`] have high confidence; a lint whose finding rests on a heuristic uses
+//! the `_with_confidence` variants instead. The findings with a confidence lower than
+//! `min_confidence` in the `solana_lints` table of `dylint.toml` are not emitted:
+//!
+//! ```toml
+//! [solana_lints]
+//! min_confidence = "medium"
+//! ```
+//!
+//! A finding in code generated by local macros is reported at the macro call (see
+//! [`crate::expansion`]). Each diagnostic ends with a note linking to the references of its lint,
+//! if any (see [`crate::references`]).
+//!
+//! A lint reporting in `check_crate_post` reports each finding at the node it was found at, e.g.,
+//! the expression using an account, so that `#[allow(..)]` and `#[expect(..)]` on the expression
+//! or on its function apply. A lint grouping several findings into one diagnostic, e.g., at the
+//! field of the account, only groups the findings whose [`lint_level`] is the level at the field.
+
+use rustc_errors::{DiagnosticBuilder, MultiSpan};
+use rustc_hir::HirId;
+use rustc_lint::{LateContext, Level, Lint};
+use rustc_span::Span;
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+use crate::{expansion, fixes, references::references_of, sarif};
+
+/// How certain a lint is that a finding is a true positive
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// The finding is reported because the lint could not tell whether the code is safe, e.g.,
+    /// a bump seed read from a struct which may or may not be constrained
+    Low,
+    /// The lint looked for a check and found none, but the check may be where the lint does not
+    /// look, e.g., in a function of another crate
+    Medium,
+    /// The finding follows from the code, e.g., from the constraints of an Anchor `Accounts` struct
+    #[default]
+    High,
+}
+
+impl Confidence {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Config {
+    min_confidence: Option<Confidence>,
+}
+
+/// Return true if a finding with `confidence` should be emitted
+fn is_confident_enough(confidence: Confidence) -> bool {
+    static MIN_CONFIDENCE: OnceLock<Confidence> = OnceLock::new();
+    let min_confidence = MIN_CONFIDENCE.get_or_init(|| {
+        dylint_linting::config_or_default::<Config>("solana_lints")
+            .min_confidence
+            .unwrap_or(Confidence::Low)
+    });
+    confidence >= *min_confidence
+}
+
+/// Return the level of `lint` at `hir_id`, after the `#[allow(..)]`, `#[warn(..)]`, `#[deny(..)]`
+/// and `#[expect(..)]` attributes of `hir_id` and of its parents
+pub fn lint_level(cx: &LateContext<'_>, lint: &'static Lint, hir_id: HirId) -> Level {
+    cx.tcx.lint_level_at_node(lint, hir_id).0
+}
+
+pub fn span_lint_hir(
+    cx: &LateContext<'_>,
+    lint: &'static Lint,
+    hir_id: HirId,
+    sp: Span,
+    msg: &str,
+) {
+    span_lint_hir_with_confidence(cx, lint, hir_id, sp, msg, Confidence::High);
+}
+
+pub fn span_lint_hir_and_then(
+    cx: &LateContext<'_>,
+    lint: &'static Lint,
+    hir_id: HirId,
+    sp: impl Into<MultiSpan>,
+    msg: &str,
+    f: impl FnOnce(&mut DiagnosticBuilder<'_, ()>),
+) {
+    span_lint_hir_and_then_with_confidence(cx, lint, hir_id, sp, msg, Confidence::High, f);
+}
+
+pub fn span_lint_hir_with_confidence(
+    cx: &LateContext<'_>,
+    lint: &'static Lint,
+    hir_id: HirId,
+    sp: Span,
+    msg: &str,
+    confidence: Confidence,
+) {
+    if !is_confident_enough(confidence) {
+        return;
+    }
+    let sp = expansion::call_site(sp);
+    sarif::record(cx, lint, hir_id, sp, msg, confidence);
+    clippy_utils::diagnostics::span_lint_hir_and_then(cx, lint, hir_id, sp, msg, |diag| {
+        add_references(diag, lint);
+    });
+}
+
+pub fn span_lint_hir_and_then_with_confidence(
+    cx: &LateContext<'_>,
+    lint: &'static Lint,
+    hir_id: HirId,
+    sp: impl Into<MultiSpan>,
+    msg: &str,
+    confidence: Confidence,
+    f: impl FnOnce(&mut DiagnosticBuilder<'_, ()>),
+) {
+    if !is_confident_enough(confidence) {
+        return;
+    }
+    let mut sp = sp.into();
+    for primary_span in sp.primary_spans().to_vec() {
+        sp.replace(primary_span, expansion::call_site(primary_span));
+    }
+    if let Some(primary_span) = sp.primary_span() {
+        sarif::record(cx, lint, hir_id, primary_span, msg, confidence);
+    }
+    clippy_utils::diagnostics::span_lint_hir_and_then(cx, lint, hir_id, sp, msg, |diag| {
+        f(diag);
+        fixes::record(cx, lint, hir_id, diag);
+        add_references(diag, lint);
+    });
+}
+
+/// Add a "read more" note for each reference of `lint`
+fn add_references(diag: &mut DiagnosticBuilder<'_, ()>, lint: &'static Lint) {
+    for reference in references_of(&lint.name_lower()) {
+        diag.note(format!(
+            "read more in {}: {}",
+            reference.title, reference.url
+        ));
+    }
+}
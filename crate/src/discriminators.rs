@@ -0,0 +1,132 @@
+//! Anchor account discriminators, and the `discriminators.lock` file recording them.
+//!
+//! Anchor prefixes the data of an `#[account]` type with the first 8 bytes of the SHA-256 hash of
+//! `account:<name of the type>`. The discriminator depends neither on the module nor on the crate
+//! of the type, so two account types with the same name have the same discriminator, and renaming
+//! a type changes its discriminator, so that the accounts already stored can no longer be
+//! deserialized.
+//!
+//! A package can commit a `discriminators.lock` file next to its `Cargo.toml`, with one line per
+//! account type: the discriminator as 16 hexadecimal digits, and the name of the type, e.g.,
+//!
+//! ```text
+//! d308e82b02987577 Vault
+//! ```
+//!
+//! Empty lines and lines starting with `#` are ignored. The lint writes the file only if
+//! `SOLANA_LINTS_BLESS` is set (see [`crate::bless`]).
+
+use clippy_utils::get_trait_def_id;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::def_id::DefId;
+use rustc_lint::LateContext;
+use rustc_middle::ty::{self, print::with_no_trimmed_paths};
+use std::{
+    fs::{read_to_string, write},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{paths, utils::package_dir};
+
+pub const LOCK_FILE: &str = "discriminators.lock";
+
+pub type Discriminator = [u8; 8];
+
+/// Return the discriminator Anchor derives for the `#[account]` type `name`
+pub fn account_discriminator(name: &str) -> Discriminator {
+    let preimage = format!("account:{name}");
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&anchor_syn::hash::hash(preimage.as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Return the `#[account]` types of the crate and of its dependencies, i.e., the types which
+/// implement both `anchor_lang::Discriminator` and `anchor_lang::Owner`. The types generated for
+/// the instructions and the events implement only the former.
+pub fn account_types(cx: &LateContext<'_>) -> Vec<DefId> {
+    let self_types = |path: &[&str]| -> FxHashSet<DefId> {
+        get_trait_def_id(cx, path)
+            .map(|trait_id| {
+                cx.tcx
+                    .all_impls(trait_id)
+                    .filter_map(
+                        |impl_id| match cx.tcx.type_of(impl_id).skip_binder().kind() {
+                            ty::Adt(adt_def, _) => Some(adt_def.did()),
+                            _ => None,
+                        },
+                    )
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let owners = self_types(&paths::ANCHOR_LANG_OWNER);
+    let mut account_types = self_types(&paths::ANCHOR_LANG_DISCRIMINATOR)
+        .into_iter()
+        .filter(|def_id| owners.contains(def_id))
+        .collect::<Vec<_>>();
+    // the order of the impls is not stable
+    account_types.sort_by_key(|def_id| type_path(cx, *def_id));
+    account_types
+}
+
+/// Return the full path of the type, e.g., `pool::State`, which is unambiguous even if another
+/// type has the same name
+pub fn type_path(cx: &LateContext<'_>, def_id: DefId) -> String {
+    with_no_trimmed_paths!(cx.tcx.def_path_str(def_id))
+}
+
+/// Return the path of the lock file of the package being checked, i.e., the `discriminators.lock`
+/// next to the `src` directory holding the root of the crate
+pub fn lock_path(cx: &LateContext<'_>) -> Option<PathBuf> {
+    Some(package_dir(cx)?.join(LOCK_FILE))
+}
+
+/// Read the discriminators and the names of the types recorded in the lock file `path`
+pub fn read_lock(path: &Path) -> io::Result<Vec<(Discriminator, String)>> {
+    read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            parse_lock_line(line).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid line `{line}`, expected a discriminator and a type name"),
+                )
+            })
+        })
+        .collect()
+}
+
+fn parse_lock_line(line: &str) -> Option<(Discriminator, String)> {
+    let (hex, name) = line.split_once(char::is_whitespace)?;
+    let name = name.trim();
+    if hex.len() != 16 || name.is_empty() {
+        return None;
+    }
+    let mut discriminator = [0; 8];
+    for (i, byte) in discriminator.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(2 * i..2 * i + 2)?, 16).ok()?;
+    }
+    Some((discriminator, name.to_owned()))
+}
+
+/// Write the discriminators and the names of the types to the lock file `path`
+pub fn write_lock(path: &Path, entries: &[(Discriminator, String)]) -> io::Result<()> {
+    let mut contents = String::from(
+        "# The discriminators of the account types, see solana_lints::discriminators\n",
+    );
+    for (discriminator, name) in entries {
+        contents.push_str(&format!("{} {name}\n", to_hex(discriminator)));
+    }
+    write(path, contents)
+}
+
+/// Format `discriminator` as in the lock file
+pub fn to_hex(discriminator: &Discriminator) -> String {
+    discriminator
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
@@ -0,0 +1,194 @@
+//! Error codes of Anchor `#[error_code]` enums, and the `error_codes.lock` file recording them.
+//!
+//! Anchor converts a variant of an `#[error_code]` enum into the error code of the transaction
+//! failing with it: the position of the variant in the enum, or its explicit discriminant, plus
+//! the offset of the enum, i.e., 6000 unless set with `#[error_code(offset = ...)]`. The clients,
+//! the IDL and the monitors of a program identify its errors by these codes, so inserting,
+//! removing or moving a variant changes the meaning of the codes of the variants following it.
+//!
+//! A package can commit an `error_codes.lock` file next to its `Cargo.toml`, recording the codes of
+//! the variants of each enum as they were released: the name of the enum on a line, followed by
+//! its variants, one per indented line, as the code and the name of the variant, e.g.,
+//!
+//! ```text
+//! ErrorCode
+//!     6000 Unauthorized
+//!     6001 InvalidAmount
+//! ```
+//!
+//! Empty lines and lines starting with `#` are ignored. The lint writes the file only if
+//! `SOLANA_LINTS_BLESS` is set (see [`crate::bless`]).
+
+use clippy_utils::consts::{constant, Constant};
+use if_chain::if_chain;
+use rustc_hir::{def_id::DefId, BinOpKind, BodyId, ExprKind, ImplItemKind, Item, ItemKind};
+use rustc_lint::LateContext;
+use rustc_middle::ty;
+use rustc_span::{
+    hygiene::{ExpnKind, MacroKind},
+    sym, Span,
+};
+use std::{
+    fs::{read_to_string, write},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::utils::package_dir;
+
+pub const LOCK_FILE: &str = "error_codes.lock";
+
+/// The codes and the names of the variants of an error enum, in order
+pub type ErrorCodes = Vec<(u32, String)>;
+
+/// How the code of a recorded variant changed
+#[derive(Debug, Eq, PartialEq)]
+pub enum Change {
+    /// The variant has another code
+    Shifted {
+        variant: String,
+        recorded: u32,
+        current: u32,
+    },
+    /// The variant was removed, or renamed
+    Removed { variant: String, code: u32 },
+}
+
+/// If `item` is the `impl From<E> for u32` which `#[error_code]` generates for the enum `E`, return
+/// `E` and its offset, i.e., the constant the `from` function adds to the discriminants
+pub fn error_code_enum<'tcx>(
+    cx: &LateContext<'tcx>,
+    item: &'tcx Item<'tcx>,
+) -> Option<(DefId, u32)> {
+    let impl_ = match item.kind {
+        ItemKind::Impl(impl_) if is_error_code_expansion(item.span) => impl_,
+        _ => return None,
+    };
+    if_chain! {
+        if let Some(trait_id) = impl_.of_trait.and_then(|trait_ref| trait_ref.trait_def_id());
+        if cx.tcx.is_diagnostic_item(sym::From, trait_id);
+        if cx.tcx.type_of(item.owner_id).instantiate_identity() == cx.tcx.types.u32;
+        if let Some(impl_trait_ref) = cx.tcx.impl_trait_ref(item.owner_id);
+        if let ty::Adt(adt_def, _) = impl_trait_ref.instantiate_identity().args.type_at(1).kind();
+        if adt_def.is_enum();
+        if let [impl_item_ref] = impl_.items;
+        if let ImplItemKind::Fn(_, body_id) = cx.tcx.hir().impl_item(impl_item_ref.id).kind;
+        then {
+            Some((adt_def.did(), offset(cx, body_id)?))
+        } else {
+            None
+        }
+    }
+}
+
+/// Return the offset which the body of `from`, i.e., `e as u32 + offset`, adds to the discriminant
+fn offset(cx: &LateContext<'_>, body_id: BodyId) -> Option<u32> {
+    if_chain! {
+        if let ExprKind::Block(block, _) = cx.tcx.hir().body(body_id).value.kind;
+        if let Some(expr) = block.expr;
+        if let ExprKind::Binary(op, _, offset) = expr.kind;
+        if op.node == BinOpKind::Add;
+        if let Some(Constant::Int(offset)) = constant(cx, cx.tcx.typeck_body(body_id), offset);
+        then {
+            u32::try_from(offset).ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// Return true if the code at `span` is generated by the `#[error_code]` attribute
+fn is_error_code_expansion(span: Span) -> bool {
+    match span.ctxt().outer_expn_data().kind {
+        ExpnKind::Macro(MacroKind::Attr, name) => {
+            name.as_str().rsplit("::").next() == Some("error_code")
+        }
+        _ => false,
+    }
+}
+
+/// Return the codes of the variants of the error enum `def_id`, whose offset is `offset`
+pub fn error_codes(cx: &LateContext<'_>, def_id: DefId, offset: u32) -> ErrorCodes {
+    let adt_def = cx.tcx.adt_def(def_id);
+    adt_def
+        .discriminants(cx.tcx)
+        .filter_map(|(variant_idx, discr)| {
+            let code = u32::try_from(discr.val.checked_add(u128::from(offset))?).ok()?;
+            Some((code, adt_def.variant(variant_idx).name.to_string()))
+        })
+        .collect()
+}
+
+/// Compare the `current` codes of an error enum with its `recorded` codes. The variants added
+/// since the codes were recorded are not changes, unless they shift the recorded variants.
+pub fn compare(recorded: &ErrorCodes, current: &ErrorCodes) -> Vec<Change> {
+    recorded
+        .iter()
+        .filter_map(|(code, variant)| {
+            match current
+                .iter()
+                .find(|(_, current_variant)| current_variant == variant)
+            {
+                Some((current_code, _)) if current_code == code => None,
+                Some((current_code, _)) => Some(Change::Shifted {
+                    variant: variant.clone(),
+                    recorded: *code,
+                    current: *current_code,
+                }),
+                None => Some(Change::Removed {
+                    variant: variant.clone(),
+                    code: *code,
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Return the path of the lock file of the package being checked, i.e., the `error_codes.lock`
+/// next to the `src` directory holding the root of the crate
+pub fn lock_path(cx: &LateContext<'_>) -> Option<PathBuf> {
+    Some(package_dir(cx)?.join(LOCK_FILE))
+}
+
+/// Read the names of the enums and their codes recorded in the lock file `path`
+pub fn read_lock(path: &Path) -> io::Result<Vec<(String, ErrorCodes)>> {
+    let mut entries = Vec::<(String, ErrorCodes)>::new();
+    for line in read_to_string(path)?.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid line `{trimmed}`, expected an enum name or an indented variant"),
+            )
+        };
+        if line.starts_with(char::is_whitespace) {
+            let (code, variant) = trimmed
+                .split_once(char::is_whitespace)
+                .ok_or_else(invalid)?;
+            let code = code.parse().map_err(|_| invalid())?;
+            let (_, codes) = entries.last_mut().ok_or_else(invalid)?;
+            codes.push((code, variant.trim().to_owned()));
+        } else if trimmed.contains(char::is_whitespace) {
+            return Err(invalid());
+        } else {
+            entries.push((trimmed.to_owned(), ErrorCodes::new()));
+        }
+    }
+    Ok(entries)
+}
+
+/// Write the names of the enums and their codes to the lock file `path`
+pub fn write_lock(path: &Path, entries: &[(String, ErrorCodes)]) -> io::Result<()> {
+    let mut contents =
+        String::from("# The error codes of the error enums, see solana_lints::error_codes\n");
+    for (name, codes) in entries {
+        contents.push_str(&format!("{name}\n"));
+        for (code, variant) in codes {
+            contents.push_str(&format!("    {code} {variant}\n"));
+        }
+    }
+    write(path, contents)
+}
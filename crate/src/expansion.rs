@@ -0,0 +1,79 @@
+//! The lints skip the code generated by macro expansions, e.g., by Anchor's `#[program]` and
+//! `#[derive(Accounts)]`, which they analyze through the code the user wrote instead. A program
+//! may however generate its own code, e.g., its instructions, with function-like macros of its
+//! crate, e.g., `macro_rules!` macros, whose expansions are the only code the lints see. A lint
+//! analyzes these expansions if `analyze_local_macro_expansions` is set in its table of
+//! `dylint.toml`:
+//!
+//! ```toml
+//! [missing_owner_check]
+//! analyze_local_macro_expansions = true
+//! ```
+//!
+//! The findings in such expansions are reported at the outermost macro call (see [`call_site`]),
+//! as the code of the macro is shared by all of its calls.
+
+use rustc_lint::Lint;
+use rustc_span::{
+    hygiene::{ExpnKind, MacroKind},
+    Span,
+};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Config {
+    analyze_local_macro_expansions: bool,
+}
+
+/// Return true if `lint` skips the code at `span`, i.e., if the code is generated by a macro
+/// expansion, unless the lint analyzes the expansions of local macros and the code is generated by
+/// local macros only (see [`is_local_macro_expansion`])
+pub fn is_skipped_expansion(lint: &'static Lint, span: Span) -> bool {
+    span.from_expansion()
+        && !(analyzes_local_macro_expansions(lint) && is_local_macro_expansion(span))
+}
+
+/// Return true if `lint` is configured to analyze the expansions of local macros. Each library
+/// declares a single lint, so the configuration is read once per library.
+fn analyzes_local_macro_expansions(lint: &'static Lint) -> bool {
+    static ANALYZE_LOCAL_MACRO_EXPANSIONS: OnceLock<bool> = OnceLock::new();
+    *ANALYZE_LOCAL_MACRO_EXPANSIONS.get_or_init(|| {
+        dylint_linting::config_or_default::<Config>(&lint.name_lower())
+            .analyze_local_macro_expansions
+    })
+}
+
+/// Return true if the code at `span` is generated by the expansions of function-like macros
+/// defined in the local crate, possibly nested, and by no other expansion, e.g., of a derive
+/// macro, of an attribute macro or of a desugaring such as `?`
+pub fn is_local_macro_expansion(span: Span) -> bool {
+    let mut ctxt = span.ctxt();
+    if ctxt.is_root() {
+        return false;
+    }
+    while !ctxt.is_root() {
+        let expn_data = ctxt.outer_expn_data();
+        if !matches!(expn_data.kind, ExpnKind::Macro(MacroKind::Bang, _))
+            || !expn_data
+                .macro_def_id
+                .map_or(false, |def_id| def_id.is_local())
+        {
+            return false;
+        }
+        ctxt = expn_data.call_site.ctxt();
+    }
+    true
+}
+
+/// Return the span at which to report a finding at `span`: the span of the outermost macro call if
+/// the code at `span` is generated by local macros (see [`is_local_macro_expansion`]), and `span`
+/// otherwise. The code passed to the macro as arguments keeps its own spans.
+pub fn call_site(span: Span) -> Span {
+    if is_local_macro_expansion(span) {
+        span.source_callsite()
+    } else {
+        span
+    }
+}
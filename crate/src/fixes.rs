@@ -0,0 +1,307 @@
+//! A patch fixing the findings which the lints know how to fix. If `SOLANA_LINTS_FIXES` is set to a
+//! path, the suggestions of the diagnostics emitted through [`crate::diagnostics`] whose
+//! applicability is `MachineApplicable` are collected across the lints and the crates checked, and
+//! written to the file as a unified diff, to be reviewed before it is applied, e.g.,
+//!
+//! ```sh
+//! rm -f fixes.patch fixes.patch.suggestions
+//! SOLANA_LINTS_FIXES=$PWD/fixes.patch cargo dylint --all --workspace
+//! git apply fixes.patch
+//! ```
+//!
+//! The libraries loaded by `cargo dylint --all` do not share memory, so each suggestion is appended
+//! to `<path>.suggestions`, one JSON object per line, and the patch is regenerated from all of the
+//! suggestions recorded so far. As for the SARIF file (see [`crate::sarif`]), both files should be
+//! removed before the lints are run.
+//!
+//! The paths in the patch are those rustc reports, i.e., relative to the root of the workspace
+//! when the lints are run with cargo. A suggestion made twice, e.g., by two lints, is applied once,
+//! and a suggestion overlapping one recorded before it is left out. The suggestions in macro
+//! expansions are left out, as are the files with a byte order mark or `\r\n` line endings, as
+//! rustc reports the offsets of their normalized source.
+
+use rustc_errors::{Applicability, Diagnostic};
+use rustc_hir::HirId;
+use rustc_lint::{LateContext, Level, Lint};
+use rustc_span::{FileName, Pos};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{read_to_string, write, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{diagnostics::lint_level, sarif::with_lock};
+
+pub const FIXES_VAR: &str = "SOLANA_LINTS_FIXES";
+
+/// The number of unchanged lines shown around each change of the patch
+const CONTEXT: usize = 3;
+
+/// A replacement of the bytes `lo..hi` of the file `path`
+#[derive(PartialEq, Eq, Serialize, Deserialize)]
+struct Part {
+    path: String,
+    lo: usize,
+    hi: usize,
+    snippet: String,
+}
+
+impl Part {
+    fn overlaps(&self, other: &Self) -> bool {
+        self.path == other.path
+            && ((self.lo < other.hi && other.lo < self.hi) || self.lo == other.lo)
+    }
+}
+
+/// A machine-applicable suggestion of `lint`, whose parts are applied together or not at all
+#[derive(Serialize, Deserialize)]
+struct Suggestion {
+    lint: String,
+    parts: Vec<Part>,
+}
+
+/// Record the machine-applicable suggestions of `diag`, a diagnostic of `lint` emitted at
+/// `hir_id`, if the patch is enabled and the lint is not allowed there
+pub(crate) fn record(cx: &LateContext<'_>, lint: &'static Lint, hir_id: HirId, diag: &Diagnostic) {
+    let path = match env::var_os(FIXES_VAR) {
+        Some(path) => PathBuf::from(path),
+        None => return,
+    };
+    if matches!(
+        lint_level(cx, lint, hir_id),
+        Level::Allow | Level::Expect(_)
+    ) {
+        return;
+    }
+    let code_suggestions = match &diag.suggestions {
+        Ok(code_suggestions) => code_suggestions,
+        Err(_) => return,
+    };
+    let source_map = cx.tcx.sess.source_map();
+    let suggestions: Vec<Suggestion> = code_suggestions
+        .iter()
+        .filter(|code_suggestion| code_suggestion.applicability == Applicability::MachineApplicable)
+        .filter_map(|code_suggestion| {
+            let substitution = code_suggestion.substitutions.first()?;
+            let parts = substitution
+                .parts
+                .iter()
+                .map(|part| {
+                    if part.span.from_expansion() {
+                        return None;
+                    }
+                    let lo = source_map.lookup_byte_offset(part.span.lo());
+                    let hi = source_map.lookup_byte_offset(part.span.hi());
+                    let path = match &lo.sf.name {
+                        FileName::Real(name) => name.local_path()?.to_string_lossy().into_owned(),
+                        _ => return None,
+                    };
+                    Some(Part {
+                        path,
+                        lo: lo.pos.to_usize(),
+                        hi: hi.pos.to_usize(),
+                        snippet: part.snippet.clone(),
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(Suggestion {
+                lint: lint.name_lower(),
+                parts,
+            })
+        })
+        .collect();
+    if suggestions.is_empty() {
+        return;
+    }
+    if let Err(error) = with_lock(&path, || update(&path, &suggestions)) {
+        cx.tcx.sess.dcx().warn(format!(
+            "could not write the patch file `{}`: {error}",
+            path.display()
+        ));
+    }
+}
+
+/// Append `suggestions` to the suggestions recorded for the patch `path`, and regenerate the patch
+fn update(path: &Path, suggestions: &[Suggestion]) -> io::Result<()> {
+    let mut suggestions_path = PathBuf::from(path);
+    suggestions_path.as_mut_os_string().push(".suggestions");
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&suggestions_path)?;
+    for suggestion in suggestions {
+        writeln!(file, "{}", serde_json::to_string(suggestion)?)?;
+    }
+    let recorded = read_to_string(&suggestions_path)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<Suggestion>, _>>()?;
+    write(path, patch(&recorded)?)
+}
+
+/// The unified diff applying `suggestions`, skipping the suggestions made before and those
+/// overlapping a suggestion made before
+fn patch(suggestions: &[Suggestion]) -> io::Result<String> {
+    let mut files: BTreeMap<&str, Vec<&Part>> = BTreeMap::new();
+    for (index, suggestion) in suggestions.iter().enumerate() {
+        if suggestions[..index]
+            .iter()
+            .any(|other| other.parts == suggestion.parts)
+        {
+            continue;
+        }
+        let accepted = files.values().flatten();
+        if suggestion
+            .parts
+            .iter()
+            .any(|part| accepted.clone().any(|other| part.overlaps(other)))
+        {
+            continue;
+        }
+        for part in &suggestion.parts {
+            files.entry(&part.path).or_default().push(part);
+        }
+    }
+    let mut patch = String::new();
+    for (path, mut parts) in files {
+        let contents = read_to_string(path)?;
+        if contents.starts_with('\u{feff}') || contents.contains('\r') {
+            continue;
+        }
+        parts.retain(|part| {
+            part.lo <= part.hi
+                && part.hi <= contents.len()
+                && contents.is_char_boundary(part.lo)
+                && contents.is_char_boundary(part.hi)
+        });
+        parts.sort_by_key(|part| part.lo);
+        patch.push_str(&file_diff(path, &contents, &parts));
+    }
+    Ok(patch)
+}
+
+/// A change of the lines `first..=last` of a file into `new_text`
+struct Change {
+    first: usize,
+    last: usize,
+    new_text: String,
+}
+
+/// The diff of the file `path` applying `parts`, which are sorted and do not overlap
+fn file_diff(path: &str, contents: &str, parts: &[&Part]) -> String {
+    let lines: Vec<&str> = contents.split_inclusive('\n').collect();
+    if lines.is_empty() || parts.is_empty() {
+        return String::new();
+    }
+    let line_starts: Vec<usize> = lines
+        .iter()
+        .scan(0, |start, line| {
+            let line_start = *start;
+            *start += line.len();
+            Some(line_start)
+        })
+        .collect();
+    let line_of = |offset: usize| {
+        (line_starts.partition_point(|&start| start <= offset) - 1).min(lines.len() - 1)
+    };
+
+    // The parts changing the same lines make one change
+    let mut groups: Vec<(usize, usize, Vec<&Part>)> = Vec::new();
+    for &part in parts {
+        let first = line_of(part.lo);
+        let last = if part.hi > part.lo {
+            line_of(part.hi - 1)
+        } else {
+            first
+        };
+        match groups.last_mut() {
+            Some((_, group_last, group_parts)) if first <= *group_last => {
+                *group_last = (*group_last).max(last);
+                group_parts.push(part);
+            }
+            _ => groups.push((first, last, vec![part])),
+        }
+    }
+    let changes: Vec<Change> = groups
+        .into_iter()
+        .map(|(first, last, group_parts)| {
+            let end = line_starts[last] + lines[last].len();
+            let mut new_text = String::new();
+            let mut cursor = line_starts[first];
+            for part in group_parts {
+                new_text.push_str(&contents[cursor..part.lo]);
+                new_text.push_str(&part.snippet);
+                cursor = part.hi;
+            }
+            new_text.push_str(&contents[cursor..end]);
+            Change {
+                first,
+                last,
+                new_text,
+            }
+        })
+        .collect();
+
+    // The changes separated by at most twice the context make one hunk
+    let mut hunks: Vec<&[Change]> = Vec::new();
+    let mut hunk_start = 0;
+    for index in 1..=changes.len() {
+        if index == changes.len()
+            || changes[index].first - changes[index - 1].last - 1 > 2 * CONTEXT
+        {
+            hunks.push(&changes[hunk_start..index]);
+            hunk_start = index;
+        }
+    }
+
+    let mut diff = format!("--- a/{path}\n+++ b/{path}\n");
+    // The number of lines added so far, minus the number of lines removed
+    let mut offset = 0_i64;
+    for hunk in hunks {
+        let old_first = hunk[0].first.saturating_sub(CONTEXT);
+        let old_last = (hunk[hunk.len() - 1].last + CONTEXT).min(lines.len() - 1);
+        let mut body = String::new();
+        let (mut old_count, mut new_count) = (0, 0);
+        let mut line = old_first;
+        for change in hunk {
+            for context_line in &lines[line..change.first] {
+                push_line(&mut body, ' ', context_line);
+            }
+            old_count += change.first - line;
+            new_count += change.first - line;
+            for removed_line in &lines[change.first..=change.last] {
+                push_line(&mut body, '-', removed_line);
+                old_count += 1;
+            }
+            for added_line in change.new_text.split_inclusive('\n') {
+                push_line(&mut body, '+', added_line);
+                new_count += 1;
+            }
+            line = change.last + 1;
+        }
+        for context_line in &lines[line..=old_last] {
+            push_line(&mut body, ' ', context_line);
+            old_count += 1;
+            new_count += 1;
+        }
+        // A range of no lines starts at the line before it
+        let new_first = old_first as i64 + offset + i64::from(new_count != 0);
+        diff.push_str(&format!(
+            "@@ -{},{old_count} +{new_first},{new_count} @@\n{body}",
+            old_first + 1
+        ));
+        offset += new_count as i64 - old_count as i64;
+    }
+    diff
+}
+
+fn push_line(body: &mut String, prefix: char, line: &str) {
+    body.push(prefix);
+    body.push_str(line);
+    if !line.ends_with('\n') {
+        body.push_str("\n\\ No newline at end of file\n");
+    }
+}
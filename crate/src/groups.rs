@@ -0,0 +1,217 @@
+//! Lint groups, so that the lints of a tier can be allowed, warned about or denied at once:
+//!
+//! - `solana_security_critical`: the lints reporting vulnerabilities, e.g., missing signer or
+//!   owner checks, whose findings should fail CI, e.g., with `-D solana_security_critical`
+//! - `solana_best_practices`: the advisory lints, e.g., about performance, robustness or
+//!   maintainability, which are rather run locally
+//!
+//! A group applies to the lints of the libraries which are loaded, and overrides their default
+//! levels like any other lint group, e.g., `#![warn(solana_best_practices)]` also enables the
+//! lints of the group which are allowed by default.
+//!
+//! Each library is a separate dynamic library registering its own lint, and rustc requires a group
+//! to be registered once, with all of its lints. Each library therefore calls [`register_groups`]
+//! after registering its lint, and a group is registered by the last of the loaded libraries of
+//! the group to be registered. The libraries declare their lints with [`declare_late_lint!`] and
+//! [`impl_late_lint!`], which do so.
+//!
+//! [`declare_late_lint!`]: crate::declare_late_lint
+//! [`impl_late_lint!`]: crate::impl_late_lint
+
+use rustc_lint::LintStore;
+use rustc_span::Symbol;
+use std::{
+    env::{self, consts::DLL_PREFIX},
+    path::{Path, PathBuf},
+};
+
+/// The variable through which the Dylint driver passes the paths of the libraries to load, as a
+/// JSON array
+const DYLINT_LIBS_VAR: &str = "DYLINT_LIBS";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Group {
+    SecurityCritical,
+    BestPractices,
+}
+
+impl Group {
+    pub const ALL: [Self; 2] = [Self::SecurityCritical, Self::BestPractices];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SecurityCritical => "solana_security_critical",
+            Self::BestPractices => "solana_best_practices",
+        }
+    }
+}
+
+/// The group of each lint, by the name of its library, which is also the name of the lint
+pub const LINT_GROUPS: &[(&str, Group)] = &[
+    ("account_data_borrow_conflict", Group::BestPractices),
+    ("account_discriminator_collision", Group::SecurityCritical),
+    ("account_info_aggregate", Group::BestPractices),
+    ("account_info_comparison", Group::BestPractices),
+    ("account_loader_misuse", Group::BestPractices),
+    ("account_size_truncation", Group::BestPractices),
+    ("arbitrary_cpi", Group::SecurityCritical),
+    ("bump_seed_canonicalization", Group::SecurityCritical),
+    ("create_with_seed_as_pda", Group::SecurityCritical),
+    ("default_account_authority", Group::BestPractices),
+    ("degenerate_value_bypass", Group::BestPractices),
+    ("dropped_validation_error", Group::BestPractices),
+    ("funds_lockup", Group::BestPractices),
+    ("hardcoded_pubkey", Group::BestPractices),
+    ("host_only_api", Group::BestPractices),
+    ("inconsistent_pda_seeds", Group::SecurityCritical),
+    ("init_if_needed_reinit", Group::SecurityCritical),
+    ("insecure_account_close", Group::SecurityCritical),
+    ("instruction_attribute_mismatch", Group::BestPractices),
+    ("invoke_signed_seed_mismatch", Group::SecurityCritical),
+    ("loop_invariant_account_read", Group::BestPractices),
+    ("lossy_error_conversion", Group::BestPractices),
+    ("manual_instruction_dispatch", Group::BestPractices),
+    ("missing_discriminator_space", Group::BestPractices),
+    ("missing_instruction_data_validation", Group::BestPractices),
+    ("missing_mint_check", Group::SecurityCritical),
+    ("missing_mut_constraint", Group::BestPractices),
+    ("missing_owner_check", Group::SecurityCritical),
+    ("missing_rent_exempt_check", Group::BestPractices),
+    ("missing_signer_check", Group::SecurityCritical),
+    ("partial_token_account_constraints", Group::SecurityCritical),
+    ("pda_seed_collision", Group::SecurityCritical),
+    ("raw_account_info_field", Group::BestPractices),
+    ("recomputed_pda_bump", Group::BestPractices),
+    ("remaining_accounts_length_mismatch", Group::BestPractices),
+    ("self_cpi", Group::BestPractices),
+    ("self_transfer_check", Group::SecurityCritical),
+    ("shared_vault_fee_authority", Group::SecurityCritical),
+    ("shifted_error_code", Group::BestPractices),
+    ("spl_token_account_validation", Group::SecurityCritical),
+    ("stake_account_validation", Group::SecurityCritical),
+    ("static_or_leaked_state", Group::BestPractices),
+    ("system_account_authority", Group::SecurityCritical),
+    ("sysvar_get", Group::BestPractices),
+    ("token_owner_program_id_comparison", Group::BestPractices),
+    ("type_cosplay", Group::SecurityCritical),
+    ("unauthorized_account_close", Group::SecurityCritical),
+    ("unauthorized_event_emission", Group::SecurityCritical),
+    ("unauthorized_lamport_debit", Group::SecurityCritical),
+    ("unchecked_event_authority", Group::SecurityCritical),
+    ("unchecked_index", Group::BestPractices),
+    ("unchecked_instructions_sysvar", Group::BestPractices),
+    ("unchecked_token_authority", Group::SecurityCritical),
+    ("unmigrated_account_layout", Group::BestPractices),
+    ("unsafe_arithmetic", Group::BestPractices),
+    ("unsafe_realloc", Group::SecurityCritical),
+    ("unsigned_authority_change", Group::SecurityCritical),
+    ("unvalidated_cpi_accounts", Group::SecurityCritical),
+    ("use_after_close", Group::SecurityCritical),
+    ("weak_multisig_threshold", Group::BestPractices),
+];
+
+/// Return the group of the lint named `name`, if any
+pub fn group_of(name: &str) -> Option<Group> {
+    LINT_GROUPS
+        .iter()
+        .find(|(lint, _)| *lint == name)
+        .map(|&(_, group)| group)
+}
+
+/// Register each group whose lints, among those of the libraries being loaded, are all registered
+/// and which is not registered yet. Nothing is registered outside of the Dylint driver.
+pub fn register_groups(lint_store: &mut LintStore) {
+    let libraries = match loaded_libraries() {
+        Some(libraries) => libraries,
+        None => return,
+    };
+    'groups: for group in Group::ALL {
+        if lint_store.is_lint_group(Symbol::intern(group.name())) {
+            continue;
+        }
+        let mut lint_ids = Vec::new();
+        for (name, _) in LINT_GROUPS.iter().filter(|(name, lint_group)| {
+            *lint_group == group && libraries.iter().any(|library| library == name)
+        }) {
+            match lint_store.find_lints(name) {
+                Ok(ids) => lint_ids.extend(ids),
+                // a library of the group which is yet to be registered
+                Err(_) => continue 'groups,
+            }
+        }
+        if !lint_ids.is_empty() {
+            lint_store.register_group(true, group.name(), None, lint_ids);
+        }
+    }
+}
+
+/// Return the names of the libraries the Dylint driver loads, e.g., `sysvar_get` for
+/// `libsysvar_get@nightly-2023-12-28-x86_64-unknown-linux-gnu.so`
+fn loaded_libraries() -> Option<Vec<String>> {
+    let paths = env::var(DYLINT_LIBS_VAR).ok()?;
+    let paths = serde_json::from_str::<Vec<PathBuf>>(&paths).ok()?;
+    Some(paths.iter().filter_map(|path| library_name(path)).collect())
+}
+
+fn library_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let (name, _) = file_name.strip_prefix(DLL_PREFIX)?.split_once('@')?;
+    Some(name.to_owned())
+}
+
+/// Declare a lint and its lint pass, as `dylint_linting::declare_late_lint!` does, and register
+/// the old names of the lint (see [`crate::renamed`]) and the lint groups (see [`register_groups`])
+/// once the lint is registered
+#[macro_export]
+macro_rules! declare_late_lint {
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr) => {
+        $crate::paste::paste! {
+            $crate::__declare_and_register_lint! {
+                $(#[$attr])* $vis $NAME, $Level, $desc, |_| Box::new([< $NAME:camel >])
+            }
+
+            rustc_session::declare_lint_pass!([< $NAME:camel >] => [$NAME]);
+        }
+    };
+}
+
+/// Declare a lint whose lint pass has state, constructed with `$pass`, as
+/// `dylint_linting::impl_late_lint!` does, and register the old names of the lint (see
+/// [`crate::renamed`]) and the lint groups (see [`register_groups`]) once the lint is registered
+#[macro_export]
+macro_rules! impl_late_lint {
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr, $pass:expr) => {
+        $crate::__declare_and_register_lint! {
+            $(#[$attr])* $vis $NAME, $Level, $desc, |_| Box::new($pass)
+        }
+
+        $crate::paste::paste! {
+            rustc_session::impl_lint_pass!([< $NAME:camel >] => [$NAME]);
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __declare_and_register_lint {
+    ($(#[$attr:meta])* $vis:vis $NAME:ident, $Level:ident, $desc:expr, $pass:expr) => {
+        dylint_linting::dylint_library!();
+
+        extern crate rustc_lint;
+        extern crate rustc_session;
+
+        #[no_mangle]
+        pub fn register_lints(
+            sess: &rustc_session::Session,
+            lint_store: &mut rustc_lint::LintStore,
+        ) {
+            dylint_linting::init_config(sess);
+            lint_store.register_lints(&[$NAME]);
+            lint_store.register_late_pass($pass);
+            $crate::renamed::register_renamed(lint_store, $NAME);
+            $crate::groups::register_groups(lint_store);
+        }
+
+        rustc_session::declare_lint!($(#[$attr])* $vis $NAME, $Level, $desc);
+    };
+}
@@ -0,0 +1,165 @@
+//! The IDs of well-known programs, e.g., the System and Token programs. Lints use these helpers to
+//! recognize a program ID which is a constant, e.g., `spl_token::ID` or `system_program::id()`,
+//! instead of embedding the IDs themselves.
+//!
+//! Each helper also takes the paths of the IDs of other programs, e.g., `["my_program::ID"]`, which
+//! a lint reads from its configuration.
+
+use rustc_hir::{
+    def::{DefKind, Res},
+    def_id::DefId,
+    Expr, ExprKind, UnOp,
+};
+use rustc_lint::LateContext;
+use rustc_middle::{mir, ty};
+
+use clippy_utils::match_def_path;
+
+pub struct KnownProgram {
+    pub name: &'static str,
+    /// The program ID, in base58
+    pub id: &'static str,
+    /// The paths of the items holding the ID: its constant and, if any, the function returning it
+    pub items: &'static [&'static [&'static str]],
+}
+
+pub const KNOWN_PROGRAMS: &[KnownProgram] = &[
+    KnownProgram {
+        name: "System Program",
+        id: "11111111111111111111111111111111",
+        items: &[
+            &["solana_program", "system_program", "ID"],
+            &["solana_program", "system_program", "id"],
+        ],
+    },
+    KnownProgram {
+        name: "Token Program",
+        id: "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+        items: &[&["spl_token", "ID"], &["spl_token", "id"]],
+    },
+    KnownProgram {
+        name: "Token-2022 Program",
+        id: "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+        items: &[&["spl_token_2022", "ID"], &["spl_token_2022", "id"]],
+    },
+    KnownProgram {
+        name: "Associated Token Account Program",
+        id: "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+        items: &[
+            &["spl_associated_token_account", "ID"],
+            &["spl_associated_token_account", "id"],
+        ],
+    },
+    KnownProgram {
+        name: "Token Metadata Program",
+        id: "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s",
+        items: &[&["mpl_token_metadata", "programs", "MPL_TOKEN_METADATA_ID"]],
+    },
+    KnownProgram {
+        name: "Stake Program",
+        id: "Stake11111111111111111111111111111111111111",
+        items: &[
+            &["solana_program", "stake", "program", "ID"],
+            &["solana_program", "stake", "program", "id"],
+        ],
+    },
+    KnownProgram {
+        name: "Vote Program",
+        id: "Vote111111111111111111111111111111111111111",
+        items: &[
+            &["solana_program", "vote", "program", "ID"],
+            &["solana_program", "vote", "program", "id"],
+        ],
+    },
+    KnownProgram {
+        name: "BPF Loader",
+        id: "BPFLoader2111111111111111111111111111111111",
+        items: &[
+            &["solana_program", "bpf_loader", "ID"],
+            &["solana_program", "bpf_loader", "id"],
+        ],
+    },
+    KnownProgram {
+        name: "BPF Upgradeable Loader",
+        id: "BPFLoaderUpgradeab1e11111111111111111111111",
+        items: &[
+            &["solana_program", "bpf_loader_upgradeable", "ID"],
+            &["solana_program", "bpf_loader_upgradeable", "id"],
+        ],
+    },
+];
+
+/// Return the known program whose ID, in base58, is `id`
+pub fn known_program_by_id(id: &str) -> Option<&'static KnownProgram> {
+    KNOWN_PROGRAMS.iter().find(|program| program.id == id)
+}
+
+/// Return true if `def_id` is the ID constant, or the function returning the ID, of a known program,
+/// or is one of the items in `extra`
+pub fn is_known_program_id(cx: &LateContext<'_>, def_id: DefId, extra: &[String]) -> bool {
+    KNOWN_PROGRAMS
+        .iter()
+        .flat_map(|program| program.items)
+        .any(|path| match_def_path(cx, def_id, path))
+        || extra.iter().any(|path| {
+            let path = path.split("::").collect::<Vec<_>>();
+            match_def_path(cx, def_id, &path)
+        })
+}
+
+/// Return true if `expr` is the ID of a known program, i.e., a path to its ID constant or a call of
+/// the function returning it, possibly borrowed or dereferenced
+pub fn is_known_program_id_const(cx: &LateContext<'_>, expr: &Expr<'_>, extra: &[String]) -> bool {
+    match expr.kind {
+        ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) => {
+            is_known_program_id_const(cx, inner, extra)
+        }
+        ExprKind::Path(ref qpath) => matches!(
+            cx.qpath_res(qpath, expr.hir_id),
+            Res::Def(DefKind::Const, def_id) if is_known_program_id(cx, def_id, extra)
+        ),
+        ExprKind::Call(callee, []) => match callee.kind {
+            ExprKind::Path(ref qpath) => matches!(
+                cx.qpath_res(qpath, callee.hir_id),
+                Res::Def(DefKind::Fn, def_id) if is_known_program_id(cx, def_id, extra)
+            ),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Return true if `operand` is the ID constant of a known program, before it is evaluated
+pub fn is_known_program_id_operand(
+    cx: &LateContext<'_>,
+    operand: &mir::Operand<'_>,
+    extra: &[String],
+) -> bool {
+    match operand {
+        mir::Operand::Constant(constant) => match constant.const_ {
+            mir::Const::Unevaluated(unevaluated, _) => {
+                is_known_program_id(cx, unevaluated.def, extra)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Return true if `terminator` calls the function returning the ID of a known program
+pub fn is_known_program_id_call(
+    cx: &LateContext<'_>,
+    terminator: &mir::Terminator<'_>,
+    extra: &[String],
+) -> bool {
+    match &terminator.kind {
+        mir::TerminatorKind::Call {
+            func: mir::Operand::Constant(func),
+            ..
+        } => match func.const_.ty().kind() {
+            ty::FnDef(def_id, _) => is_known_program_id(cx, *def_id, extra),
+            _ => false,
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,123 @@
+//! Layouts of Anchor account types, and the `layouts.lock` file recording them.
+//!
+//! Anchor serializes the data of an `#[account]` type with Borsh, i.e., its fields in order, with
+//! neither their names nor a version. The accounts created before a program upgrade which adds
+//! fields to an account type are too short for the new layout, so that they can no longer be
+//! deserialized, unless they are reallocated and migrated. The accounts cannot be read either if a
+//! field is removed, reordered or changes type.
+//!
+//! A package can commit a `layouts.lock` file next to its `Cargo.toml`, recording the fields of
+//! each account type as they were when the accounts on chain were created: the path of the type in
+//! its crate on a line, followed by its fields, one per indented line, as `name: type`, e.g.,
+//!
+//! ```text
+//! Vault
+//!     balance: u64
+//!     bump: u8
+//! staking::Vault
+//!     staked: u64
+//! ```
+//!
+//! Empty lines and lines starting with `#` are ignored. The types of the fields are written with
+//! their full paths, e.g., `solana_program::pubkey::Pubkey`. The account types are recorded by
+//! path (see [`crate::discriminators::type_path`]), so that types with the same name in different
+//! modules have their own entries. The lint writes the file only if `SOLANA_LINTS_BLESS` is set
+//! (see [`crate::bless`]).
+
+use rustc_hir::def_id::DefId;
+use rustc_lint::LateContext;
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use std::{
+    fs::{read_to_string, write},
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::utils::package_dir;
+
+pub const LOCK_FILE: &str = "layouts.lock";
+
+/// The names and the types of the fields of an account type, in order
+pub type Layout = Vec<(String, String)>;
+
+/// How the layout of an account type changed since it was recorded
+#[derive(Debug, Eq, PartialEq)]
+pub enum Change {
+    Unchanged,
+    /// The fields were added after the recorded fields
+    Grown(Layout),
+    /// A recorded field was removed, moved, renamed, or changed type
+    Incompatible,
+}
+
+/// Return the layout of the account type `def_id`
+pub fn account_layout(cx: &LateContext<'_>, def_id: DefId) -> Layout {
+    cx.tcx
+        .adt_def(def_id)
+        .all_fields()
+        .map(|field| {
+            let ty = cx.tcx.type_of(field.did).instantiate_identity();
+            (
+                field.name.to_string(),
+                with_no_trimmed_paths!(ty.to_string()),
+            )
+        })
+        .collect()
+}
+
+/// Compare the `current` layout of an account type with its `recorded` layout
+pub fn compare(recorded: &Layout, current: &Layout) -> Change {
+    if current == recorded {
+        Change::Unchanged
+    } else if current.starts_with(recorded) {
+        Change::Grown(current[recorded.len()..].to_vec())
+    } else {
+        Change::Incompatible
+    }
+}
+
+/// Return the path of the lock file of the package being checked, i.e., the `layouts.lock` next to
+/// the `src` directory holding the root of the crate
+pub fn lock_path(cx: &LateContext<'_>) -> Option<PathBuf> {
+    Some(package_dir(cx)?.join(LOCK_FILE))
+}
+
+/// Read the paths of the types and their layouts recorded in the lock file `path`
+pub fn read_lock(path: &Path) -> io::Result<Vec<(String, Layout)>> {
+    let mut entries = Vec::<(String, Layout)>::new();
+    for line in read_to_string(path)?.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid line `{trimmed}`, expected a type path or an indented field"),
+            )
+        };
+        if line.starts_with(char::is_whitespace) {
+            let (name, ty) = trimmed.split_once(':').ok_or_else(invalid)?;
+            let (_, layout) = entries.last_mut().ok_or_else(invalid)?;
+            layout.push((name.trim().to_owned(), ty.trim().to_owned()));
+        } else if trimmed.contains(char::is_whitespace) {
+            return Err(invalid());
+        } else {
+            entries.push((trimmed.to_owned(), Layout::new()));
+        }
+    }
+    Ok(entries)
+}
+
+/// Write the paths of the types and their layouts to the lock file `path`
+pub fn write_lock(path: &Path, entries: &[(String, Layout)]) -> io::Result<()> {
+    let mut contents =
+        String::from("# The layouts of the account types, see solana_lints::layouts\n");
+    for (type_path, layout) in entries {
+        contents.push_str(&format!("{type_path}\n"));
+        for (field, ty) in layout {
+            contents.push_str(&format!("    {field}: {ty}\n"));
+        }
+    }
+    write(path, contents)
+}
@@ -1,13 +1,76 @@
+//! The helpers shared by the Solana lints of this repository, which other Dylint libraries may
+//! also use to write their own lints, e.g., lints specific to a project, without copying code from
+//! this repository:
+//!
+//! - [`context`]: an extension trait of `LateContext` with the most used helpers
+//! - [`utils`]: recognizing Anchor `Accounts` structs, accounts, key and owner checks, and the
+//!   MIR dataflow of checks (see [`utils::dataflow`])
+//! - [`mir_cache`]: the analyses of MIR bodies which a lint computes once per body
+//! - [`paths`] and [`known_programs`]: the paths of the items of Solana, Anchor and SPL, and the
+//!   IDs of well-known programs
+//! - [`diagnostics`], [`sarif`] and [`fixes`]: reporting findings, with SARIF output and a patch of
+//!   the machine-applicable suggestions
+//!
+//! The public items of these modules follow semantic versioning: a release which changes or
+//! removes one of them bumps the minor version while the version is `0.x`. A lint of another
+//! library is declared with [`declare_late_lint!`] or [`impl_late_lint!`], like the lints of this
+//! repository, and belongs to none of the groups of [`groups`].
+
 #![feature(rustc_private)]
+#![feature(box_patterns)]
 #![warn(unused_extern_crates)]
 
+extern crate rustc_ast;
+extern crate rustc_data_structures;
+extern crate rustc_errors;
 extern crate rustc_hir;
+extern crate rustc_index;
 extern crate rustc_lint;
 extern crate rustc_middle;
+extern crate rustc_span;
 
 #[allow(unused_extern_crates)]
 extern crate rustc_driver;
 
+pub mod bless;
+
+pub mod context;
+
+pub mod diagnostics;
+
+pub mod discriminators;
+
+pub mod error_codes;
+
+pub mod expansion;
+
+pub mod fixes;
+
+pub mod groups;
+
+pub mod known_programs;
+
+pub mod layouts;
+
+pub mod mir_cache;
+
 pub mod paths;
 
+pub mod references;
+
+pub mod renamed;
+
+pub mod sarif;
+
+pub mod seeds;
+
+pub mod suggestions;
+
+pub mod summaries;
+
+pub mod taint;
+
 pub mod utils;
+
+#[doc(hidden)]
+pub use paste;
@@ -0,0 +1,167 @@
+//! A cache, keyed by the def id of a body, of the analyses of its MIR which the MIR lints repeat
+//! for every call or operation they check, e.g., `arbitrary_cpi` and `bump_seed_canonicalization`
+//! following the assignments to an argument backwards. Every lint walking the MIR of a body uses
+//! it: `arbitrary_cpi`, `bump_seed_canonicalization`, `self_cpi`, `unchecked_index` and
+//! `unsafe_arithmetic`.
+//!
+//! `optimized_mir` is a query, so fetching the MIR of a body again is cheap, and `BasicBlocks`
+//! already caches the predecessor map and the dominator tree of a body
+//! (`body.basic_blocks.predecessors()` and `body.basic_blocks.dominators()`). What is not cached
+//! is what the helpers of [`crate::utils`] compute on top of them:
+//!
+//! - the predecessors of each block returned by `dataflow::backward_predecessors`, which looks for
+//!   the `Break` arms of `?` in the statements of every predecessor, at each step of a walk
+//! - the chains of aliases of a local, which `cpi::reference_locals` computes by scanning the whole
+//!   body until a fixpoint
+//! - the locals from which the value of a local is computed, which `taint::value_sources` computes
+//!   by scanning the whole body for each local it follows
+//!
+//! A lint keeps a [`MirCache`] in its lint pass, gets the [`BodyAnalysis`] of a body with
+//! [`MirCache::analysis`], and calls the methods of the analysis instead of these helpers.
+//!
+//! Every lint library links its own copy of this crate, and `cargo dylint --all` loads the
+//! libraries side by side, so the cache of a lint is not shared with the other lints: it removes
+//! the repeated work within a lint, e.g., for a body with many calls to check.
+
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::DefId;
+use rustc_index::{bit_set::BitSet, IndexVec};
+use rustc_lint::LateContext;
+use rustc_middle::{
+    mir::{BasicBlock, Body, Local, Place, StatementKind},
+    ty::TyCtxt,
+};
+
+use crate::{
+    taint,
+    utils::{cpi, dataflow},
+};
+
+/// The analyses of the MIR bodies of the crate being checked, computed the first time a lint asks
+/// for them
+#[derive(Default)]
+pub struct MirCache {
+    bodies: FxHashMap<DefId, BodyAnalysis>,
+}
+
+impl MirCache {
+    /// The analysis of the optimized MIR of `def_id`, computed on first use. The MIR of `def_id`
+    /// must be available, e.g., checked with `tcx.is_mir_available`.
+    pub fn analysis(&mut self, cx: &LateContext<'_>, def_id: DefId) -> &BodyAnalysis {
+        self.bodies
+            .entry(def_id)
+            .or_insert_with(|| BodyAnalysis::new(cx.tcx, cx.tcx.optimized_mir(def_id)))
+    }
+}
+
+/// The analyses of a MIR body. They do not borrow the body, so that a lint pass can keep them, and
+/// their methods take the body they were computed for.
+pub struct BodyAnalysis {
+    /// The predecessors of each block returned by `dataflow::backward_predecessors`
+    backward_predecessors: IndexVec<BasicBlock, Vec<BasicBlock>>,
+    /// The locals assigned from each local, or from a dereference of it, by one of the assignments
+    /// of `dataflow::rvalue_source`, e.g., `_2` for `_1` with `_2 = &_1`
+    assigned_from: IndexVec<Local, Vec<Local>>,
+    /// The locals from which each local is computed by one of its assignments, as followed by
+    /// `taint::value_sources`, e.g., `_2` and `_3` for `_1` with `_1 = Add(_2, _3)`
+    computed_from: IndexVec<Local, Vec<Local>>,
+}
+
+impl BodyAnalysis {
+    pub fn new(tcx: TyCtxt<'_>, body: &Body<'_>) -> Self {
+        let backward_predecessors = body
+            .basic_blocks
+            .indices()
+            .map(|block| dataflow::backward_predecessors(tcx, body, block))
+            .collect();
+        let mut assigned_from = IndexVec::from_elem_n(Vec::new(), body.local_decls.len());
+        let mut computed_from = IndexVec::from_elem_n(Vec::new(), body.local_decls.len());
+        for stmt in body
+            .basic_blocks
+            .iter()
+            .flat_map(|block_data| &block_data.statements)
+        {
+            if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind {
+                if_chain! {
+                    if let Some(source) = dataflow::rvalue_source(rvalue);
+                    if let Some(source) = source.local_or_deref_local();
+                    then {
+                        assigned_from[source].push(place.local);
+                    }
+                }
+                if !place.is_indirect() {
+                    computed_from[place.local].extend(taint::rvalue_value_sources(rvalue));
+                }
+            }
+        }
+        Self {
+            backward_predecessors,
+            assigned_from,
+            computed_from,
+        }
+    }
+
+    /// The predecessor of `block` returned by `dataflow::backward_predecessor`
+    pub fn backward_predecessor(&self, block: BasicBlock) -> Option<BasicBlock> {
+        self.backward_predecessors[block].first().copied()
+    }
+
+    /// The predecessors of `block` returned by `dataflow::backward_predecessors`
+    pub fn backward_predecessors(&self, block: BasicBlock) -> &[BasicBlock] {
+        &self.backward_predecessors[block]
+    }
+
+    /// `cpi::reference_locals`: `local` and the locals assigned from it, or from one of them
+    pub fn reference_locals(&self, local: Local) -> Vec<Local> {
+        let mut locals = vec![local];
+        let mut index = 0;
+        while let Some(&local) = locals.get(index) {
+            for &assigned in &self.assigned_from[local] {
+                if !locals.contains(&assigned) {
+                    locals.push(assigned);
+                }
+            }
+            index += 1;
+        }
+        locals
+    }
+
+    /// `taint::value_sources`: `local` and the locals from which its value is computed
+    pub fn value_sources(&self, local: Local) -> BitSet<Local> {
+        let mut sources = BitSet::new_empty(self.computed_from.len());
+        let mut stack = vec![local];
+        while let Some(local) = stack.pop() {
+            if sources.insert(local) {
+                stack.extend(&self.computed_from[local]);
+            }
+        }
+        sources
+    }
+
+    /// `dataflow::is_moved_from`, through the cached predecessors
+    pub fn is_moved_from<'tcx>(
+        &self,
+        body: &Body<'tcx>,
+        block: BasicBlock,
+        place: &Place<'tcx>,
+        locals: &[Local],
+    ) -> bool {
+        dataflow::is_moved_from_through(body, block, place, locals, |block| {
+            self.backward_predecessor(block)
+        })
+    }
+
+    /// `cpi::find_place_aliases`, through the cached predecessors
+    pub fn find_place_aliases<'tcx>(
+        &self,
+        cx: &LateContext<'tcx>,
+        body: &'tcx Body<'tcx>,
+        block: BasicBlock,
+        place: &Place<'tcx>,
+    ) -> Vec<Place<'tcx>> {
+        cpi::find_place_aliases_through(cx, body, block, place, |block| {
+            self.backward_predecessor(block)
+        })
+    }
+}
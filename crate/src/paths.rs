@@ -1,56 +1,694 @@
-// smoelius: Default const names to:
+//! The def paths of the items the lints recognize, e.g., with `match_def_path` or `match_type`,
+//! grouped by crate and by purpose:
+//!
+//! - `anchor_lang`: account types, account loading, traits, CPI contexts
+//! - `anchor_spl`: token CPI helpers
+//! - serialization: `borsh`, `bytemuck`
+//! - `alloc`, `core` and `std`: cells, clones, memory, host-only APIs
+//! - `rand`
+//! - `solana_program`: accounts, CPIs, PDAs, errors, the System, Stake and BPF Upgradeable Loader
+//!   programs, rent, `Pack`, return data
+//! - sysvars, including the instructions sysvar
+//! - `spl_token`, `spl_token_2022` and `mpl_token_metadata`
+//!
+//! A path is the path of the item's definition, not of a re-export, e.g.,
+//! `solana_program::account_info::AccountInfo` rather than `anchor_lang::prelude::AccountInfo`.
+//! `crate/paths_probe` checks that each path resolves (see [`ALL`]).
+//!
+//! The most common type checks have typed predicates, e.g., [`is_account_info_ty`] and
+//! [`is_anchor_wrapper_ty`], which the lints use instead of calling `match_type` with the paths.
+
+// Default const names to:
 //   crate '_' last_segment
 // all in upper snake case.
 
+use clippy_utils::ty::match_type;
+use rustc_lint::LateContext;
+use rustc_middle::ty::Ty;
+
+// anchor_lang: account types
+
+/// `Account<'info, T>`, an account of the program, deserialized and owner checked
 pub const ANCHOR_LANG_ACCOUNT: [&str; 4] = ["anchor_lang", "accounts", "account", "Account"];
+/// `AccountLoader<'info, T>`, a zero-copy account of the program
 pub const ANCHOR_LANG_ACCOUNT_LOADER: [&str; 4] =
     ["anchor_lang", "accounts", "account_loader", "AccountLoader"];
+/// `AccountLoader::load`
+pub const ANCHOR_LANG_LOAD: [&str; 5] = [
+    "anchor_lang",
+    "accounts",
+    "account_loader",
+    "AccountLoader",
+    "load",
+];
+/// `AccountLoader::load_init`
+pub const ANCHOR_LANG_LOAD_INIT: [&str; 5] = [
+    "anchor_lang",
+    "accounts",
+    "account_loader",
+    "AccountLoader",
+    "load_init",
+];
+/// `AccountLoader::load_mut`
+pub const ANCHOR_LANG_LOAD_MUT: [&str; 5] = [
+    "anchor_lang",
+    "accounts",
+    "account_loader",
+    "AccountLoader",
+    "load_mut",
+];
+/// `Program<'info, T>`, an executable account whose key is the ID of `T`
 pub const ANCHOR_LANG_PROGRAM: [&str; 4] = ["anchor_lang", "accounts", "program", "Program"];
+/// `Interface<'info, T>`, an executable account whose key is one of the IDs of `T`
 pub const ANCHOR_LANG_INTERFACE: [&str; 4] = ["anchor_lang", "accounts", "interface", "Interface"];
+/// `InterfaceAccount<'info, T>`, an account owned by one of the programs of `T`, e.g., Token or
+/// Token-2022
+pub const ANCHOR_LANG_INTERFACE_ACCOUNT: [&str; 4] = [
+    "anchor_lang",
+    "accounts",
+    "interface_account",
+    "InterfaceAccount",
+];
+/// `SystemAccount<'info>`, an account owned by the System program
 pub const ANCHOR_LANG_SYSTEM_ACCOUNT: [&str; 4] =
     ["anchor_lang", "accounts", "system_account", "SystemAccount"];
+/// `Signer<'info>`, an account which signs the instruction
+pub const ANCHOR_LANG_SIGNER: [&str; 4] = ["anchor_lang", "accounts", "signer", "Signer"];
+/// `Sysvar<'info, T>`, a sysvar account whose key is checked
+pub const ANCHOR_LANG_SYSVAR: [&str; 4] = ["anchor_lang", "accounts", "sysvar", "Sysvar"];
+/// `UncheckedAccount<'info>`, an account which Anchor does not check
+pub const ANCHOR_LANG_UNCHECKED_ACCOUNT: [&str; 4] = [
+    "anchor_lang",
+    "accounts",
+    "unchecked_account",
+    "UncheckedAccount",
+];
+
+// anchor_lang: traits and contexts
+
+/// The trait deserializing the data of accounts, e.g., with `try_deserialize`
 pub const ANCHOR_LANG_ACCOUNT_DESERIALIZE: [&str; 2] = ["anchor_lang", "AccountDeserialize"];
+/// The trait implemented by `#[derive(Accounts)]` structs
+pub const ANCHOR_LANG_ACCOUNTS: [&str; 2] = ["anchor_lang", "Accounts"];
+/// `AccountsClose::close`, which closes an account, e.g., `ctx.accounts.vault.close(destination)`
+pub const ANCHOR_LANG_ACCOUNTS_CLOSE: [&str; 3] = ["anchor_lang", "AccountsClose", "close"];
+/// `Context<'_, '_, '_, 'info, T>`, the first parameter of the instruction handlers
 pub const ANCHOR_LANG_CONTEXT: [&str; 3] = ["anchor_lang", "context", "Context"];
+/// The trait of the account types with an eight-byte discriminator
 pub const ANCHOR_LANG_DISCRIMINATOR: [&str; 2] = ["anchor_lang", "Discriminator"];
-pub const ANCHOR_LANG_SIGNER: [&str; 4] = ["anchor_lang", "accounts", "signer", "Signer"];
-pub const ANCHOR_LANG_SYSVAR: [&str; 4] = ["anchor_lang", "accounts", "sysvar", "Sysvar"];
+/// The error type of Anchor's `Result`
+pub const ANCHOR_LANG_ERROR: [&str; 3] = ["anchor_lang", "error", "Error"];
+/// The trait of the account types declaring the program which owns them
+pub const ANCHOR_LANG_OWNER: [&str; 2] = ["anchor_lang", "Owner"];
+/// `ToAccountInfo::to_account_info`
 pub const ANCHOR_LANG_TO_ACCOUNT_INFO: [&str; 3] =
     ["anchor_lang", "ToAccountInfo", "to_account_info"];
+/// `AccountDeserialize::try_deserialize`, which checks the discriminator
 pub const ANCHOR_LANG_TRY_DESERIALIZE: [&str; 3] =
     ["anchor_lang", "AccountDeserialize", "try_deserialize"];
-// key() method call path
+/// `Key::key`, e.g., `ctx.accounts.authority.key()`
 pub const ANCHOR_LANG_KEY: [&str; 3] = ["anchor_lang", "Key", "key"];
+/// The trait implemented by `#[derive(Accounts)]` structs and the account types
 pub const ANCHOR_LANG_TO_ACCOUNT_INFOS_TRAIT: [&str; 2] = ["anchor_lang", "ToAccountInfos"];
-// CpiContext::new()
+/// `Lamports::add_lamports`
+pub const ANCHOR_LANG_ADD_LAMPORTS: [&str; 3] = ["anchor_lang", "Lamports", "add_lamports"];
+/// `Lamports::get_lamports`
+pub const ANCHOR_LANG_GET_LAMPORTS: [&str; 3] = ["anchor_lang", "Lamports", "get_lamports"];
+/// `Lamports::sub_lamports`
+pub const ANCHOR_LANG_SUB_LAMPORTS: [&str; 3] = ["anchor_lang", "Lamports", "sub_lamports"];
+/// `anchor_lang::system_program::create_account`
+pub const ANCHOR_LANG_SYSTEM_PROGRAM_CREATE_ACCOUNT: [&str; 3] =
+    ["anchor_lang", "system_program", "create_account"];
+/// `CpiContext<'_, '_, '_, 'info, T>`
+pub const ANCHOR_LANG_CPI_CONTEXT: [&str; 3] = ["anchor_lang", "context", "CpiContext"];
+/// `CpiContext::new`
 pub const ANCHOR_CPI_CONTEXT_NEW: [&str; 4] = ["anchor_lang", "context", "CpiContext", "new"];
-// CpiContext::new_with_signer()
+/// `CpiContext::new_with_signer`
 pub const ANCHOR_CPI_CONTEXT_NEW_SIGNER: [&str; 4] =
     ["anchor_lang", "context", "CpiContext", "new_with_signer"];
+
+// anchor_spl
+
+/// `anchor_spl::token::burn`
+pub const ANCHOR_SPL_BURN: [&str; 3] = ["anchor_spl", "token", "burn"];
+/// `anchor_spl::token::mint_to`
+pub const ANCHOR_SPL_MINT_TO: [&str; 3] = ["anchor_spl", "token", "mint_to"];
+/// `anchor_spl::token::transfer`
+pub const ANCHOR_SPL_TRANSFER: [&str; 3] = ["anchor_spl", "token", "transfer"];
+/// `anchor_spl::token::transfer_checked`
+pub const ANCHOR_SPL_TRANSFER_CHECKED: [&str; 3] = ["anchor_spl", "token", "transfer_checked"];
+
+// serialization
+
+/// `BorshDeserialize::deserialize`
+pub const BORSH_DESERIALIZE: [&str; 4] = ["borsh", "de", "BorshDeserialize", "deserialize"];
+/// `BorshDeserialize`
+pub const BORSH_DESERIALIZE_TRAIT: [&str; 3] = ["borsh", "de", "BorshDeserialize"];
+/// `BorshDeserialize::try_from_slice`
 pub const BORSH_TRY_FROM_SLICE: [&str; 4] = ["borsh", "de", "BorshDeserialize", "try_from_slice"];
+/// `bytemuck::from_bytes`
+pub const BYTEMUCK_FROM_BYTES: [&str; 2] = ["bytemuck", "from_bytes"];
+/// `bytemuck::from_bytes_mut`
+pub const BYTEMUCK_FROM_BYTES_MUT: [&str; 2] = ["bytemuck", "from_bytes_mut"];
+/// `bytemuck::try_from_bytes`
+pub const BYTEMUCK_TRY_FROM_BYTES: [&str; 2] = ["bytemuck", "try_from_bytes"];
+/// `bytemuck::try_from_bytes_mut`
+pub const BYTEMUCK_TRY_FROM_BYTES_MUT: [&str; 2] = ["bytemuck", "try_from_bytes_mut"];
+
+// alloc and core
+
+/// `Box::leak`
+pub const ALLOC_BOXED_BOX_LEAK: [&str; 4] = ["alloc", "boxed", "Box", "leak"];
 
+/// `Try::branch`, which `x?` is desugared into
 pub const CORE_BRANCH: [&str; 5] = ["core", "ops", "try_trait", "Try", "branch"];
+/// `Ref<'_, T>`, returned by `RefCell::borrow`, e.g., of the data of an account
+pub const CORE_CELL_REF: [&str; 3] = ["core", "cell", "Ref"];
+/// `RefMut<'_, T>`, returned by `RefCell::borrow_mut`
+pub const CORE_CELL_REF_MUT: [&str; 3] = ["core", "cell", "RefMut"];
+/// `RefCell::borrow`
+pub const CORE_CELL_BORROW: [&str; 4] = ["core", "cell", "RefCell", "borrow"];
+/// `RefCell::borrow_mut`
+pub const CORE_CELL_BORROW_MUT: [&str; 4] = ["core", "cell", "RefCell", "borrow_mut"];
+/// `RefCell::try_borrow`
+pub const CORE_CELL_TRY_BORROW: [&str; 4] = ["core", "cell", "RefCell", "try_borrow"];
+/// `RefCell::try_borrow_mut`
+pub const CORE_CELL_TRY_BORROW_MUT: [&str; 4] = ["core", "cell", "RefCell", "try_borrow_mut"];
+/// `Clone::clone`
 pub const CORE_CLONE: [&str; 4] = ["core", "clone", "Clone", "clone"];
+/// `std::mem::drop`
+pub const CORE_MEM_DROP: [&str; 3] = ["core", "mem", "drop"];
+/// `std::mem::forget`
+pub const CORE_MEM_FORGET: [&str; 3] = ["core", "mem", "forget"];
+/// `std::ptr::eq`
+pub const CORE_PTR_EQ: [&str; 3] = ["core", "ptr", "eq"];
+
+// rand
+
+/// `rand::random`
+pub const RAND_RANDOM: [&str; 2] = ["rand", "random"];
+/// `rand::thread_rng`
+pub const RAND_THREAD_RNG: [&str; 4] = ["rand", "rngs", "thread", "thread_rng"];
 
+// solana_program: accounts
+
+/// `AccountInfo<'info>`, an account passed to the program, which nothing checks
 pub const SOLANA_PROGRAM_ACCOUNT_INFO: [&str; 3] =
     ["solana_program", "account_info", "AccountInfo"];
+/// `AccountInfo::assign`, which changes the owner of the account
+pub const SOLANA_PROGRAM_ASSIGN: [&str; 4] =
+    ["solana_program", "account_info", "AccountInfo", "assign"];
+/// `AccountInfo::lamports`
+pub const SOLANA_PROGRAM_LAMPORTS: [&str; 4] =
+    ["solana_program", "account_info", "AccountInfo", "lamports"];
+/// `AccountInfo::realloc`
+pub const SOLANA_PROGRAM_REALLOC: [&str; 4] =
+    ["solana_program", "account_info", "AccountInfo", "realloc"];
+/// `AccountInfo::try_borrow_data`
+pub const SOLANA_PROGRAM_TRY_BORROW_DATA: [&str; 4] = [
+    "solana_program",
+    "account_info",
+    "AccountInfo",
+    "try_borrow_data",
+];
+/// `AccountInfo::try_borrow_mut_data`
+pub const SOLANA_PROGRAM_TRY_BORROW_MUT_DATA: [&str; 4] = [
+    "solana_program",
+    "account_info",
+    "AccountInfo",
+    "try_borrow_mut_data",
+];
+/// `AccountInfo::try_borrow_mut_lamports`
+pub const SOLANA_PROGRAM_TRY_BORROW_MUT_LAMPORTS: [&str; 4] = [
+    "solana_program",
+    "account_info",
+    "AccountInfo",
+    "try_borrow_mut_lamports",
+];
+
+// solana_program: CPIs and return data
+
+/// `solana_program::program::invoke`
 pub const SOLANA_PROGRAM_INVOKE: [&str; 3] = ["solana_program", "program", "invoke"];
-// Instruction {..}
+/// `solana_program::program::invoke_signed`
+pub const SOLANA_PROGRAM_INVOKE_SIGNED: [&str; 3] = ["solana_program", "program", "invoke_signed"];
+/// `Instruction`, e.g., `Instruction { .. }`
 pub const SOLANA_PROGRAM_INSTRUCTION: [&str; 3] = ["solana_program", "instruction", "Instruction"];
+/// `Instruction::new_with_bincode`
+pub const SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BINCODE: [&str; 4] = [
+    "solana_program",
+    "instruction",
+    "Instruction",
+    "new_with_bincode",
+];
+/// `Instruction::new_with_borsh`
+pub const SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BORSH: [&str; 4] = [
+    "solana_program",
+    "instruction",
+    "Instruction",
+    "new_with_borsh",
+];
+/// `Instruction::new_with_bytes`
+pub const SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BYTES: [&str; 4] = [
+    "solana_program",
+    "instruction",
+    "Instruction",
+    "new_with_bytes",
+];
+/// `solana_program::program::get_return_data`, which returns the data set by the last CPI, and
+/// the program which set it
+pub const SOLANA_PROGRAM_GET_RETURN_DATA: [&str; 3] =
+    ["solana_program", "program", "get_return_data"];
+/// `solana_program::program::set_return_data`
+pub const SOLANA_PROGRAM_SET_RETURN_DATA: [&str; 3] =
+    ["solana_program", "program", "set_return_data"];
+
+// solana_program: addresses and errors
+
+/// `Pubkey::create_program_address`
 pub const SOLANA_PROGRAM_CREATE_PROGRAM_ADDRESS: [&str; 4] = [
     "solana_program",
     "pubkey",
     "Pubkey",
     "create_program_address",
 ];
+/// `Pubkey::find_program_address`
+pub const SOLANA_PROGRAM_FIND_PROGRAM_ADDRESS: [&str; 4] =
+    ["solana_program", "pubkey", "Pubkey", "find_program_address"];
+/// `Pubkey::create_with_seed`, which does not derive a PDA
+pub const SOLANA_PROGRAM_CREATE_WITH_SEED: [&str; 4] =
+    ["solana_program", "pubkey", "Pubkey", "create_with_seed"];
+/// `ProgramError`
+pub const SOLANA_PROGRAM_PROGRAM_ERROR: [&str; 3] =
+    ["solana_program", "program_error", "ProgramError"];
+/// `Pubkey`
+pub const SOLANA_PROGRAM_PUBKEY: [&str; 3] = ["solana_program", "pubkey", "Pubkey"];
+/// `Pubkey::new_from_array`, which `pubkey!` expands to
+pub const SOLANA_PROGRAM_PUBKEY_NEW_FROM_ARRAY: [&str; 4] =
+    ["solana_program", "pubkey", "Pubkey", "new_from_array"];
 
-pub const SPL_TOKEN_INSTRUCTION: [&str; 2] = ["spl_token", "instruction"];
+// solana_program: Stake program
+
+/// `Authorized`, the staker and withdrawer authorities of a stake account
+pub const SOLANA_PROGRAM_STAKE_AUTHORIZED: [&str; 4] =
+    ["solana_program", "stake", "state", "Authorized"];
+/// `solana_program::stake::instruction::authorize`
+pub const SOLANA_PROGRAM_STAKE_AUTHORIZE: [&str; 4] =
+    ["solana_program", "stake", "instruction", "authorize"];
+/// `solana_program::stake::instruction::deactivate_stake`
+pub const SOLANA_PROGRAM_STAKE_DEACTIVATE_STAKE: [&str; 4] =
+    ["solana_program", "stake", "instruction", "deactivate_stake"];
+/// `solana_program::stake::instruction::delegate_stake`
+pub const SOLANA_PROGRAM_STAKE_DELEGATE_STAKE: [&str; 4] =
+    ["solana_program", "stake", "instruction", "delegate_stake"];
+/// `StakeState`, the deprecated state of a stake account
+pub const SOLANA_PROGRAM_STAKE_STATE: [&str; 4] =
+    ["solana_program", "stake", "state", "StakeState"];
+/// `StakeStateV2`, the state of a stake account
+pub const SOLANA_PROGRAM_STAKE_STATE_V2: [&str; 4] =
+    ["solana_program", "stake", "state", "StakeStateV2"];
+/// `solana_program::stake::instruction::withdraw`
+pub const SOLANA_PROGRAM_STAKE_WITHDRAW: [&str; 4] =
+    ["solana_program", "stake", "instruction", "withdraw"];
+
+// solana_program: BPF Upgradeable Loader
+
+/// `UpgradeableLoaderState`, the state of the program and program data accounts, which holds the
+/// upgrade authority
+pub const SOLANA_PROGRAM_UPGRADEABLE_LOADER_STATE: [&str; 3] = [
+    "solana_program",
+    "bpf_loader_upgradeable",
+    "UpgradeableLoaderState",
+];
+/// `solana_program::bpf_loader_upgradeable::get_program_data_address`
+pub const SOLANA_PROGRAM_GET_PROGRAM_DATA_ADDRESS: [&str; 3] = [
+    "solana_program",
+    "bpf_loader_upgradeable",
+    "get_program_data_address",
+];
+
+// solana_program: rent
+
+/// `Rent::is_exempt`
+pub const SOLANA_PROGRAM_IS_EXEMPT: [&str; 4] = ["solana_program", "rent", "Rent", "is_exempt"];
+/// `Rent::minimum_balance`
+pub const SOLANA_PROGRAM_MINIMUM_BALANCE: [&str; 4] =
+    ["solana_program", "rent", "Rent", "minimum_balance"];
+
+// solana_program: System program
+
+/// `solana_program::system_instruction::allocate`
+pub const SOLANA_PROGRAM_ALLOCATE: [&str; 3] = ["solana_program", "system_instruction", "allocate"];
+/// `solana_program::system_instruction::allocate_with_seed`
+pub const SOLANA_PROGRAM_ALLOCATE_WITH_SEED: [&str; 3] =
+    ["solana_program", "system_instruction", "allocate_with_seed"];
+/// `solana_program::system_instruction::create_account`
+pub const SOLANA_PROGRAM_CREATE_ACCOUNT: [&str; 3] =
+    ["solana_program", "system_instruction", "create_account"];
+/// `solana_program::system_instruction::create_account_with_seed`
+pub const SOLANA_PROGRAM_CREATE_ACCOUNT_WITH_SEED: [&str; 3] = [
+    "solana_program",
+    "system_instruction",
+    "create_account_with_seed",
+];
+
+// solana_program: Pack
 
+/// `Pack::unpack`, which checks that the state is initialized
+pub const SOLANA_PROGRAM_UNPACK: [&str; 4] = ["solana_program", "program_pack", "Pack", "unpack"];
+/// `Pack::unpack_from_slice`
+pub const SOLANA_PROGRAM_UNPACK_FROM_SLICE: [&str; 4] = [
+    "solana_program",
+    "program_pack",
+    "Pack",
+    "unpack_from_slice",
+];
+/// `Pack::unpack_unchecked`, which does not check that the state is initialized
+pub const SOLANA_PROGRAM_UNPACK_UNCHECKED: [&str; 4] =
+    ["solana_program", "program_pack", "Pack", "unpack_unchecked"];
+
+// sysvars
+
+/// `Sysvar::from_account_info`
 pub const SYSVAR_FROM_ACCOUNT_INFO: [&str; 4] =
     ["solana_program", "sysvar", "Sysvar", "from_account_info"];
+/// `Sysvar::get`, which fails with `UnsupportedSysvar` for the sysvars not implementing it
+pub const SYSVAR_GET: [&str; 4] = ["solana_program", "sysvar", "Sysvar", "get"];
+/// `Clock`
 pub const SYSVAR_CLOCK: [&str; 3] = ["solana_program", "clock", "Clock"];
+/// `EpochRewards`
 pub const SYSVAR_EPOCH_REWARDS: [&str; 3] = ["solana_program", "epoch_rewards", "EpochRewards"];
+/// `EpochSchedule`
 pub const SYSVAR_EPOCH_SCHEDULE: [&str; 3] = ["solana_program", "epoch_schedule", "EpochSchedule"];
+/// `Fees`
 pub const SYSVAR_FEES: [&str; 3] = ["solana_program", "fees", "Fees"];
+/// `LastRestartSlot`
 pub const SYSVAR_LAST_RESTART_SLOT: [&str; 3] =
     ["solana_program", "last_restart_slot", "LastRestartSlot"];
+/// `RecentBlockhashes`, which does not implement `Sysvar::get`
+pub const SYSVAR_RECENT_BLOCKHASHES: [&str; 4] = [
+    "solana_program",
+    "sysvar",
+    "recent_blockhashes",
+    "RecentBlockhashes",
+];
+/// `Rent`
 pub const SYSVAR_RENT: [&str; 3] = ["solana_program", "rent", "Rent"];
+/// `SlotHashes`, which does not implement `Sysvar::get`
+pub const SYSVAR_SLOT_HASHES: [&str; 3] = ["solana_program", "slot_hashes", "SlotHashes"];
+/// `StakeHistory`, which does not implement `Sysvar::get`
+pub const SYSVAR_STAKE_HISTORY: [&str; 3] = ["solana_program", "stake_history", "StakeHistory"];
+/// `solana_program::sysvar::instructions::check_id`, which checks that a key is the ID of the
+/// instructions sysvar
+pub const SYSVAR_INSTRUCTIONS_CHECK_ID: [&str; 4] =
+    ["solana_program", "sysvar", "instructions", "check_id"];
+/// `solana_program::sysvar::instructions::get_instruction_relative`, which reads the instructions
+/// sysvar account it is passed
+pub const SYSVAR_INSTRUCTIONS_GET_INSTRUCTION_RELATIVE: [&str; 4] = [
+    "solana_program",
+    "sysvar",
+    "instructions",
+    "get_instruction_relative",
+];
+/// `solana_program::sysvar::instructions::load_current_index_checked`
+pub const SYSVAR_INSTRUCTIONS_LOAD_CURRENT_INDEX_CHECKED: [&str; 4] = [
+    "solana_program",
+    "sysvar",
+    "instructions",
+    "load_current_index_checked",
+];
+/// `solana_program::sysvar::instructions::load_instruction_at_checked`
+pub const SYSVAR_INSTRUCTIONS_LOAD_INSTRUCTION_AT_CHECKED: [&str; 4] = [
+    "solana_program",
+    "sysvar",
+    "instructions",
+    "load_instruction_at_checked",
+];
+
+// spl_token
+
+/// The `spl_token::instruction` module, whose functions build the instructions of the Token
+/// program
+pub const SPL_TOKEN_INSTRUCTION: [&str; 2] = ["spl_token", "instruction"];
+/// `spl_token::state::Account`, a token account
+pub const SPL_TOKEN_ACCOUNT: [&str; 3] = ["spl_token", "state", "Account"];
+/// `spl_token::state::Mint`
+pub const SPL_TOKEN_MINT: [&str; 3] = ["spl_token", "state", "Mint"];
+/// `spl_token::instruction::burn`
+pub const SPL_TOKEN_BURN: [&str; 3] = ["spl_token", "instruction", "burn"];
+/// `spl_token::instruction::mint_to`
+pub const SPL_TOKEN_MINT_TO: [&str; 3] = ["spl_token", "instruction", "mint_to"];
+/// `spl_token::instruction::transfer`
+pub const SPL_TOKEN_TRANSFER: [&str; 3] = ["spl_token", "instruction", "transfer"];
+/// `spl_token::instruction::transfer_checked`
+pub const SPL_TOKEN_TRANSFER_CHECKED: [&str; 3] = ["spl_token", "instruction", "transfer_checked"];
+
+// spl_token_2022
+
+/// `spl_token_2022::state::Account`, a Token-2022 account, without its extensions
+pub const SPL_TOKEN_2022_ACCOUNT: [&str; 3] = ["spl_token_2022", "state", "Account"];
+/// `spl_token_2022::state::Mint`
+pub const SPL_TOKEN_2022_MINT: [&str; 3] = ["spl_token_2022", "state", "Mint"];
+/// `StateWithExtensions<S>`, a Token-2022 account or mint with its extensions
+pub const SPL_TOKEN_2022_STATE_WITH_EXTENSIONS: [&str; 3] =
+    ["spl_token_2022", "extension", "StateWithExtensions"];
+/// `spl_token_2022::instruction::transfer_checked`
+pub const SPL_TOKEN_2022_TRANSFER_CHECKED: [&str; 3] =
+    ["spl_token_2022", "instruction", "transfer_checked"];
+
+// mpl_token_metadata
+
+/// `mpl_token_metadata::accounts::Metadata`, the metadata account of a mint
+pub const MPL_TOKEN_METADATA_METADATA: [&str; 3] = ["mpl_token_metadata", "accounts", "Metadata"];
+
+// std
+
+/// The `std::fs` module
+pub const STD_FS: [&str; 2] = ["std", "fs"];
+/// The `std::net` module
+pub const STD_NET: [&str; 2] = ["std", "net"];
+/// The `std::thread` module
+pub const STD_THREAD: [&str; 2] = ["std", "thread"];
+/// `Instant::now`
+pub const STD_TIME_INSTANT_NOW: [&str; 4] = ["std", "time", "Instant", "now"];
+/// `SystemTime::now`
+pub const STD_TIME_SYSTEM_TIME_NOW: [&str; 4] = ["std", "time", "SystemTime", "now"];
+
+// Every path above should be listed here. `crate/paths_probe` checks that each listed path
+// resolves, i.e., that `match_def_path` can recognize the item it refers to.
+macro_rules! all {
+    ($($path:ident),* $(,)?) => {
+        pub const ALL: &[(&str, &[&str])] = &[$((stringify!($path), &$path)),*];
+    };
+}
+
+all![
+    ANCHOR_LANG_ACCOUNT,
+    ANCHOR_LANG_ACCOUNT_LOADER,
+    ANCHOR_LANG_LOAD,
+    ANCHOR_LANG_LOAD_INIT,
+    ANCHOR_LANG_LOAD_MUT,
+    ANCHOR_LANG_PROGRAM,
+    ANCHOR_LANG_INTERFACE,
+    ANCHOR_LANG_INTERFACE_ACCOUNT,
+    ANCHOR_LANG_SYSTEM_ACCOUNT,
+    ANCHOR_LANG_SIGNER,
+    ANCHOR_LANG_SYSVAR,
+    ANCHOR_LANG_UNCHECKED_ACCOUNT,
+    ANCHOR_LANG_ACCOUNT_DESERIALIZE,
+    ANCHOR_LANG_ACCOUNTS,
+    ANCHOR_LANG_ACCOUNTS_CLOSE,
+    ANCHOR_LANG_CONTEXT,
+    ANCHOR_LANG_DISCRIMINATOR,
+    ANCHOR_LANG_ERROR,
+    ANCHOR_LANG_OWNER,
+    ANCHOR_LANG_TO_ACCOUNT_INFO,
+    ANCHOR_LANG_TRY_DESERIALIZE,
+    ANCHOR_LANG_KEY,
+    ANCHOR_LANG_TO_ACCOUNT_INFOS_TRAIT,
+    ANCHOR_LANG_ADD_LAMPORTS,
+    ANCHOR_LANG_GET_LAMPORTS,
+    ANCHOR_LANG_SUB_LAMPORTS,
+    ANCHOR_LANG_SYSTEM_PROGRAM_CREATE_ACCOUNT,
+    ANCHOR_LANG_CPI_CONTEXT,
+    ANCHOR_CPI_CONTEXT_NEW,
+    ANCHOR_CPI_CONTEXT_NEW_SIGNER,
+    ANCHOR_SPL_BURN,
+    ANCHOR_SPL_MINT_TO,
+    ANCHOR_SPL_TRANSFER,
+    ANCHOR_SPL_TRANSFER_CHECKED,
+    BORSH_DESERIALIZE,
+    BORSH_DESERIALIZE_TRAIT,
+    BORSH_TRY_FROM_SLICE,
+    BYTEMUCK_FROM_BYTES,
+    BYTEMUCK_FROM_BYTES_MUT,
+    BYTEMUCK_TRY_FROM_BYTES,
+    BYTEMUCK_TRY_FROM_BYTES_MUT,
+    ALLOC_BOXED_BOX_LEAK,
+    CORE_BRANCH,
+    CORE_CELL_REF,
+    CORE_CELL_REF_MUT,
+    CORE_CELL_BORROW,
+    CORE_CELL_BORROW_MUT,
+    CORE_CELL_TRY_BORROW,
+    CORE_CELL_TRY_BORROW_MUT,
+    CORE_CLONE,
+    CORE_MEM_DROP,
+    CORE_MEM_FORGET,
+    CORE_PTR_EQ,
+    RAND_RANDOM,
+    RAND_THREAD_RNG,
+    SOLANA_PROGRAM_ACCOUNT_INFO,
+    SOLANA_PROGRAM_ASSIGN,
+    SOLANA_PROGRAM_LAMPORTS,
+    SOLANA_PROGRAM_REALLOC,
+    SOLANA_PROGRAM_TRY_BORROW_DATA,
+    SOLANA_PROGRAM_TRY_BORROW_MUT_DATA,
+    SOLANA_PROGRAM_TRY_BORROW_MUT_LAMPORTS,
+    SOLANA_PROGRAM_INVOKE,
+    SOLANA_PROGRAM_INVOKE_SIGNED,
+    SOLANA_PROGRAM_INSTRUCTION,
+    SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BINCODE,
+    SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BORSH,
+    SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BYTES,
+    SOLANA_PROGRAM_GET_RETURN_DATA,
+    SOLANA_PROGRAM_SET_RETURN_DATA,
+    SOLANA_PROGRAM_CREATE_PROGRAM_ADDRESS,
+    SOLANA_PROGRAM_FIND_PROGRAM_ADDRESS,
+    SOLANA_PROGRAM_CREATE_WITH_SEED,
+    SOLANA_PROGRAM_PROGRAM_ERROR,
+    SOLANA_PROGRAM_PUBKEY,
+    SOLANA_PROGRAM_PUBKEY_NEW_FROM_ARRAY,
+    SOLANA_PROGRAM_STAKE_AUTHORIZED,
+    SOLANA_PROGRAM_STAKE_AUTHORIZE,
+    SOLANA_PROGRAM_STAKE_DEACTIVATE_STAKE,
+    SOLANA_PROGRAM_STAKE_DELEGATE_STAKE,
+    SOLANA_PROGRAM_STAKE_STATE,
+    SOLANA_PROGRAM_STAKE_STATE_V2,
+    SOLANA_PROGRAM_STAKE_WITHDRAW,
+    SOLANA_PROGRAM_UPGRADEABLE_LOADER_STATE,
+    SOLANA_PROGRAM_GET_PROGRAM_DATA_ADDRESS,
+    SOLANA_PROGRAM_IS_EXEMPT,
+    SOLANA_PROGRAM_MINIMUM_BALANCE,
+    SOLANA_PROGRAM_ALLOCATE,
+    SOLANA_PROGRAM_ALLOCATE_WITH_SEED,
+    SOLANA_PROGRAM_CREATE_ACCOUNT,
+    SOLANA_PROGRAM_CREATE_ACCOUNT_WITH_SEED,
+    SOLANA_PROGRAM_UNPACK,
+    SOLANA_PROGRAM_UNPACK_FROM_SLICE,
+    SOLANA_PROGRAM_UNPACK_UNCHECKED,
+    SYSVAR_FROM_ACCOUNT_INFO,
+    SYSVAR_GET,
+    SYSVAR_CLOCK,
+    SYSVAR_EPOCH_REWARDS,
+    SYSVAR_EPOCH_SCHEDULE,
+    SYSVAR_FEES,
+    SYSVAR_LAST_RESTART_SLOT,
+    SYSVAR_RECENT_BLOCKHASHES,
+    SYSVAR_RENT,
+    SYSVAR_SLOT_HASHES,
+    SYSVAR_STAKE_HISTORY,
+    SYSVAR_INSTRUCTIONS_CHECK_ID,
+    SYSVAR_INSTRUCTIONS_GET_INSTRUCTION_RELATIVE,
+    SYSVAR_INSTRUCTIONS_LOAD_CURRENT_INDEX_CHECKED,
+    SYSVAR_INSTRUCTIONS_LOAD_INSTRUCTION_AT_CHECKED,
+    SPL_TOKEN_INSTRUCTION,
+    SPL_TOKEN_ACCOUNT,
+    SPL_TOKEN_MINT,
+    SPL_TOKEN_BURN,
+    SPL_TOKEN_MINT_TO,
+    SPL_TOKEN_TRANSFER,
+    SPL_TOKEN_TRANSFER_CHECKED,
+    SPL_TOKEN_2022_ACCOUNT,
+    SPL_TOKEN_2022_MINT,
+    SPL_TOKEN_2022_STATE_WITH_EXTENSIONS,
+    SPL_TOKEN_2022_TRANSFER_CHECKED,
+    MPL_TOKEN_METADATA_METADATA,
+    STD_FS,
+    STD_NET,
+    STD_THREAD,
+    STD_TIME_INSTANT_NOW,
+    STD_TIME_SYSTEM_TIME_NOW,
+];
+
+/// Anchor's account types, which wrap the `AccountInfo` of a field of an `Accounts` struct and
+/// check it when the struct is deserialized
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AnchorWrapper {
+    Account,
+    AccountLoader,
+    Interface,
+    InterfaceAccount,
+    Program,
+    Signer,
+    SystemAccount,
+    Sysvar,
+    UncheckedAccount,
+}
+
+impl AnchorWrapper {
+    pub const ALL: [Self; 9] = [
+        Self::Account,
+        Self::AccountLoader,
+        Self::Interface,
+        Self::InterfaceAccount,
+        Self::Program,
+        Self::Signer,
+        Self::SystemAccount,
+        Self::Sysvar,
+        Self::UncheckedAccount,
+    ];
+
+    pub fn path(self) -> &'static [&'static str] {
+        match self {
+            Self::Account => &ANCHOR_LANG_ACCOUNT,
+            Self::AccountLoader => &ANCHOR_LANG_ACCOUNT_LOADER,
+            Self::Interface => &ANCHOR_LANG_INTERFACE,
+            Self::InterfaceAccount => &ANCHOR_LANG_INTERFACE_ACCOUNT,
+            Self::Program => &ANCHOR_LANG_PROGRAM,
+            Self::Signer => &ANCHOR_LANG_SIGNER,
+            Self::SystemAccount => &ANCHOR_LANG_SYSTEM_ACCOUNT,
+            Self::Sysvar => &ANCHOR_LANG_SYSVAR,
+            Self::UncheckedAccount => &ANCHOR_LANG_UNCHECKED_ACCOUNT,
+        }
+    }
+
+    /// Return true if the wrapper leaves no ambiguity about the owner of the account:
+    ///
+    /// - `Account` and `AccountLoader` require their type argument to implement
+    ///   `anchor_lang::Owner`
+    /// - `InterfaceAccount` requires its type argument to implement `anchor_lang::Owners`, and
+    ///   checks that the account is owned by one of them, e.g., Token or Token-2022
+    /// - `Program` and `Interface` check the key of the account, i.e., the program id
+    /// - `SystemAccount` checks that the account is owned by the System program
+    /// - `Signer` are mostly accounts with a private key, most of the time owned by the System
+    ///   program
+    /// - `Sysvar` checks the key of the account
+    pub fn checks_owner(self) -> bool {
+        !matches!(self, Self::UncheckedAccount)
+    }
+}
+
+/// Return true if `ty` is `AccountInfo`
+pub fn is_account_info_ty(cx: &LateContext<'_>, ty: Ty<'_>) -> bool {
+    match_type(cx, ty, &SOLANA_PROGRAM_ACCOUNT_INFO)
+}
+
+/// Return true if `ty` is one of the error types of a program: Anchor's `Error` or Solana's
+/// `ProgramError`
+pub fn is_error_ty(cx: &LateContext<'_>, ty: Ty<'_>) -> bool {
+    match_type(cx, ty, &ANCHOR_LANG_ERROR) || match_type(cx, ty, &SOLANA_PROGRAM_PROGRAM_ERROR)
+}
+
+/// Return the Anchor account type `ty` is, if any, e.g., `AnchorWrapper::Signer` for
+/// `Signer<'info>`
+pub fn anchor_wrapper(cx: &LateContext<'_>, ty: Ty<'_>) -> Option<AnchorWrapper> {
+    AnchorWrapper::ALL
+        .into_iter()
+        .find(|wrapper| match_type(cx, ty, wrapper.path()))
+}
+
+/// Return true if `ty` is one of Anchor's account types (see [`AnchorWrapper`])
+pub fn is_anchor_wrapper_ty(cx: &LateContext<'_>, ty: Ty<'_>) -> bool {
+    anchor_wrapper(cx, ty).is_some()
+}
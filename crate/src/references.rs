@@ -0,0 +1,113 @@
+//! The sections of https://github.com/coral-xyz/sealevel-attacks and
+//! https://github.com/crytic/building-secure-contracts describing the vulnerabilities the lints
+//! report. [`REFERENCES`] is the one place they are listed:
+//!
+//! - each diagnostic of a lint ends with a note linking to its references (see
+//!   [`crate::diagnostics`])
+//! - the `helpUri` of the SARIF rule of a lint is its first reference (see [`crate::sarif`])
+//! - `scripts/update_readmes.sh` lists them at the end of the README of each lint, so that table
+//!   must keep one field per line
+
+/// A section describing the vulnerability a lint reports
+pub struct Reference {
+    /// The name of the lint, which is also the name of its library
+    pub lint: &'static str,
+    pub title: &'static str,
+    pub url: &'static str,
+}
+
+/// The references of the lints, by lint, the Sealevel attacks first
+pub const REFERENCES: &[Reference] = &[
+    Reference {
+        lint: "arbitrary_cpi",
+        title: "Sealevel Attacks, 5-arbitrary-cpi",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/5-arbitrary-cpi",
+    },
+    Reference {
+        lint: "arbitrary_cpi",
+        title: "Building Secure Contracts, Arbitrary CPI",
+        url: "https://github.com/crytic/building-secure-contracts/tree/master/not-so-smart-contracts/solana/arbitrary_cpi",
+    },
+    Reference {
+        lint: "bump_seed_canonicalization",
+        title: "Sealevel Attacks, 7-bump-seed-canonicalization",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/7-bump-seed-canonicalization",
+    },
+    Reference {
+        lint: "bump_seed_canonicalization",
+        title: "Building Secure Contracts, Improper PDA validation",
+        url: "https://github.com/crytic/building-secure-contracts/tree/master/not-so-smart-contracts/solana/improper_pda_validation",
+    },
+    Reference {
+        lint: "init_if_needed_reinit",
+        title: "Sealevel Attacks, 4-initialization",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/4-initialization",
+    },
+    Reference {
+        lint: "insecure_account_close",
+        title: "Sealevel Attacks, 9-closing-accounts",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/9-closing-accounts",
+    },
+    Reference {
+        lint: "missing_mint_check",
+        title: "Sealevel Attacks, 1-account-data-matching",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/1-account-data-matching",
+    },
+    Reference {
+        lint: "missing_owner_check",
+        title: "Sealevel Attacks, 2-owner-checks",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/2-owner-checks",
+    },
+    Reference {
+        lint: "missing_owner_check",
+        title: "Building Secure Contracts, Ownership check",
+        url: "https://github.com/crytic/building-secure-contracts/tree/master/not-so-smart-contracts/solana/ownership_check",
+    },
+    Reference {
+        lint: "missing_signer_check",
+        title: "Sealevel Attacks, 0-signer-authorization",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/0-signer-authorization",
+    },
+    Reference {
+        lint: "missing_signer_check",
+        title: "Building Secure Contracts, Signer authorization",
+        url: "https://github.com/crytic/building-secure-contracts/tree/master/not-so-smart-contracts/solana/signer_authorization",
+    },
+    Reference {
+        lint: "partial_token_account_constraints",
+        title: "Sealevel Attacks, 1-account-data-matching",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/1-account-data-matching",
+    },
+    Reference {
+        lint: "self_transfer_check",
+        title: "Sealevel Attacks, 6-duplicate-mutable-accounts",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/6-duplicate-mutable-accounts",
+    },
+    Reference {
+        lint: "shared_vault_fee_authority",
+        title: "Sealevel Attacks, 8-pda-sharing",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/8-pda-sharing",
+    },
+    Reference {
+        lint: "sysvar_get",
+        title: "Sealevel Attacks, 10-sysvar-address-checking",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/10-sysvar-address-checking",
+    },
+    Reference {
+        lint: "type_cosplay",
+        title: "Sealevel Attacks, 3-type-cosplay",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/3-type-cosplay",
+    },
+    Reference {
+        lint: "unchecked_token_authority",
+        title: "Sealevel Attacks, 1-account-data-matching",
+        url: "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/1-account-data-matching",
+    },
+];
+
+/// Return the references of the lint `name`
+pub fn references_of(name: &str) -> impl Iterator<Item = &'static Reference> + '_ {
+    REFERENCES
+        .iter()
+        .filter(move |reference| reference.lint == name)
+}
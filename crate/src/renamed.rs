@@ -0,0 +1,39 @@
+//! The former names of the lints, and other names by which they are known, e.g., the names of the
+//! Sealevel Attacks they check for, so that the `#[allow(...)]` attributes and the `-A`/`-D`
+//! options referring to them keep working.
+//!
+//! Each name is registered as a renamed lint by the library of the lint it refers to (see
+//! [`register_renamed`]), which is done by [`declare_late_lint!`] and [`impl_late_lint!`]. rustc
+//! then applies the level given for the old name to the lint, and warns that the lint was renamed
+//! (`renamed_and_removed_lints`), suggesting the current name.
+//!
+//! A name must not be removed from [`RENAMED_LINTS`] once it is added, and must be listed in the
+//! README, which is checked by the `renamed_lints_are_listed_in_the_readme` test.
+//!
+//! [`declare_late_lint!`]: crate::declare_late_lint
+//! [`impl_late_lint!`]: crate::impl_late_lint
+
+use rustc_lint::{Lint, LintStore};
+
+/// The old names of the lints, and the names of the lints they refer to
+pub const RENAMED_LINTS: &[(&str, &str)] = &[
+    ("closing_accounts", "insecure_account_close"),
+    ("owner_checks", "missing_owner_check"),
+    ("signer_authorization", "missing_signer_check"),
+];
+
+/// Return the old names of the lint named `name`
+pub fn old_names(name: &str) -> impl Iterator<Item = &'static str> + '_ {
+    RENAMED_LINTS
+        .iter()
+        .filter(move |(_, new_name)| *new_name == name)
+        .map(|&(old_name, _)| old_name)
+}
+
+/// Register the old names of `lint`, which must be registered already
+pub fn register_renamed(lint_store: &mut LintStore, lint: &Lint) {
+    let name = lint.name_lower();
+    for old_name in old_names(&name) {
+        lint_store.register_renamed(old_name, &name);
+    }
+}
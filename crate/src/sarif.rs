@@ -0,0 +1,176 @@
+//! SARIF output, e.g., for GitHub code scanning. If `SOLANA_LINTS_SARIF` is set to a path, the
+//! diagnostics emitted through [`crate::diagnostics`] are recorded, and each lint writes them to
+//! the SARIF file in `check_crate_post` by calling [`write`].
+//!
+//! The file holds one run, whose tool is `solana-lints`. Each lint and each crate checked adds its
+//! rules and results to the run, so the file should be removed before the lints are run.
+
+use rustc_hir::HirId;
+use rustc_lint::{LateContext, Level, Lint};
+use rustc_span::Span;
+use serde_json::{json, Value};
+use std::{
+    env,
+    fs::{read_to_string, remove_file, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use crate::{diagnostics::Confidence, groups::group_of, references::references_of};
+
+pub const SARIF_VAR: &str = "SOLANA_LINTS_SARIF";
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// The results recorded since the last call to [`write`]
+static RESULTS: Mutex<Vec<Value>> = Mutex::new(Vec::new());
+
+/// Record a diagnostic of `lint` emitted at `hir_id` if SARIF output is enabled and the lint is
+/// not allowed there
+pub(crate) fn record(
+    cx: &LateContext<'_>,
+    lint: &'static Lint,
+    hir_id: HirId,
+    span: Span,
+    msg: &str,
+    confidence: Confidence,
+) {
+    if env::var_os(SARIF_VAR).is_none() {
+        return;
+    }
+    let level = match cx.tcx.lint_level_at_node(lint, hir_id).0 {
+        Level::Allow | Level::Expect(_) => return,
+        Level::Warn | Level::ForceWarn(_) => "warning",
+        Level::Deny | Level::Forbid => "error",
+    };
+    let source_map = cx.tcx.sess.source_map();
+    let start = source_map.lookup_char_pos(span.lo());
+    let end = source_map.lookup_char_pos(span.hi());
+    let uri = start
+        .file
+        .name
+        .prefer_local()
+        .to_string()
+        .replace('\\', "/");
+    RESULTS.lock().unwrap().push(json!({
+        "ruleId": lint.name_lower(),
+        "level": level,
+        "message": { "text": msg },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": uri },
+                "region": {
+                    "startLine": start.line,
+                    "startColumn": start.col_display + 1,
+                    "endLine": end.line,
+                    "endColumn": end.col_display + 1,
+                },
+            },
+        }],
+        "properties": { "confidence": confidence.as_str() },
+    }));
+}
+
+/// If SARIF output is enabled, add the rules of `lints` and the recorded results to the SARIF file
+pub fn write(cx: &LateContext<'_>, lints: &[&'static Lint]) {
+    if let Some(path) = env::var_os(SARIF_VAR) {
+        let path = Path::new(&path);
+        let results = std::mem::take(&mut *RESULTS.lock().unwrap());
+        if let Err(error) = with_lock(path, || update(path, lints, results)) {
+            cx.tcx.sess.dcx().warn(format!(
+                "could not write the SARIF file `{}`: {error}",
+                path.display()
+            ));
+        }
+    }
+}
+
+fn update(path: &Path, lints: &[&'static Lint], results: Vec<Value>) -> io::Result<()> {
+    let mut log = if path.exists() {
+        serde_json::from_str(&read_to_string(path)?)?
+    } else {
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "solana-lints",
+                        "informationUri": "https://github.com/crytic/solana-lints",
+                        "rules": [],
+                    },
+                },
+                "results": [],
+            }],
+        })
+    };
+    let run = log
+        .pointer_mut("/runs/0")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the SARIF file has no runs"))?;
+    if let Some(Value::Array(rules)) = run.pointer_mut("/tool/driver/rules") {
+        for lint in lints {
+            let id = lint.name_lower();
+            if !rules.iter().any(|rule| rule["id"] == id.as_str()) {
+                rules.push(rule(lint));
+            }
+        }
+    }
+    if let Some(Value::Array(run_results)) = run.get_mut("results") {
+        run_results.extend(results);
+    }
+    std::fs::write(path, serde_json::to_string_pretty(&log)?)
+}
+
+fn rule(lint: &'static Lint) -> Value {
+    let id = lint.name_lower();
+    let help_uri = references_of(&id).next().map_or_else(
+        || format!("https://github.com/crytic/solana-lints/tree/master/lints/{id}"),
+        |reference| reference.url.to_owned(),
+    );
+    let level = if lint.default_level == Level::Allow {
+        "none"
+    } else {
+        "warning"
+    };
+    let tags = group_of(&id)
+        .map(|group| vec![group.name()])
+        .unwrap_or_default();
+    json!({
+        "id": id,
+        "shortDescription": { "text": lint.desc },
+        "helpUri": help_uri,
+        "defaultConfiguration": { "level": level },
+        "properties": { "tags": tags },
+    })
+}
+
+/// Run `f` while holding `<path>.lock`, as the lints may be run on several crates concurrently. A
+/// lock which is not released within ten seconds is assumed to be stale and is removed.
+pub(crate) fn with_lock(path: &Path, f: impl FnOnce() -> io::Result<()>) -> io::Result<()> {
+    let mut lock_path = PathBuf::from(path);
+    lock_path.as_mut_os_string().push(".lock");
+    let start = Instant::now();
+    loop {
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+        {
+            Ok(_) => break,
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                if start.elapsed() > Duration::from_secs(10) {
+                    remove_file(&lock_path)?;
+                } else {
+                    sleep(Duration::from_millis(10));
+                }
+            }
+            Err(error) => return Err(error),
+        }
+    }
+    let result = f();
+    remove_file(&lock_path)?;
+    result
+}
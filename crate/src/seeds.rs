@@ -0,0 +1,163 @@
+use anchor_syn::Field;
+use clippy_utils::{fn_def_id, match_def_path};
+use if_chain::if_chain;
+use rustc_hir::{Expr, ExprKind};
+use rustc_lint::LateContext;
+
+use crate::{paths, utils::peel_addr_of};
+
+/// A normalized PDA seed
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Seed {
+    /// A byte string literal, e.g., `b"escrow"` or `"escrow".as_bytes()`
+    Bytes(Vec<u8>),
+    /// Any other expression, e.g., `maker` for `maker.key().as_ref()`
+    Expr(String),
+}
+
+/// Return the seeds of the `seeds` constraint of the Anchor field if the seeds are of the current
+/// program (no `seeds::program`) and all of them are recognized by [`seed`]
+pub fn field_seeds(field: &Field) -> Option<Vec<Seed>> {
+    if_chain! {
+        if let Some(seeds_group) = &field.constraints.seeds;
+        if seeds_group.program_seed.is_none();
+        then {
+            seeds_group.seeds.iter().map(seed).collect()
+        } else {
+            None
+        }
+    }
+}
+
+/// If `expr` is `Pubkey::find_program_address(&[seed, ...], program_id)`, return the seeds
+pub fn find_program_address_seeds<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<Vec<Seed>> {
+    if_chain! {
+        if !expr.span.from_expansion();
+        if let ExprKind::Call(_, [seeds_arg, _]) = expr.kind;
+        if let Some(def_id) = fn_def_id(cx, expr);
+        if match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_FIND_PROGRAM_ADDRESS);
+        if let ExprKind::AddrOf(_, _, seeds_array) = seeds_arg.kind;
+        if let ExprKind::Array(elements) = seeds_array.kind;
+        then {
+            elements.iter().map(|element| hir_seed(cx, element)).collect()
+        } else {
+            None
+        }
+    }
+}
+
+/// If `elements` are the elements of an array of seeds ending with a bump, e.g.,
+/// `[b"vault", authority.key.as_ref(), &[bump]]`, as passed to `Pubkey::create_program_address` or
+/// to `invoke_signed`, return the seeds without the bump. The bump is an array of one element,
+/// e.g., `&[bump]`.
+pub fn bumped_seeds<'tcx>(
+    cx: &LateContext<'tcx>,
+    elements: &'tcx [Expr<'tcx>],
+) -> Option<Vec<Seed>> {
+    if_chain! {
+        if let [seeds @ .., bump] = elements;
+        if let ExprKind::Array([_]) = peel_addr_of(bump).kind;
+        then {
+            seeds.iter().map(|element| hir_seed(cx, element)).collect()
+        } else {
+            None
+        }
+    }
+}
+
+/// Return the normalized seed of the expression, or None if the expression is not recognized. The
+/// seed is parsed from the source so that it is compared in the same way as the seeds of the
+/// `seeds` constraints.
+fn hir_seed(cx: &LateContext<'_>, expr: &Expr<'_>) -> Option<Seed> {
+    let snippet = cx.tcx.sess.source_map().span_to_snippet(expr.span).ok()?;
+    seed(&syn::parse_str(&snippet).ok()?)
+}
+
+/// Return the normalized seed of the expression, or None if the expression is not recognized
+/// - `b"x"` and `"x"` => `Bytes(b"x")`
+/// - `&x`, `x.as_ref()`, `x.as_bytes()`, `x.key()`, `x.to_account_info()` and `x.key` => the seed of `x`
+/// - `ctx.accounts.x` => `Expr("x")`
+/// - `x.y`, `x.f()` and `a::b` => `Expr("x.y")`, `Expr("x.f()")` and `Expr("a::b")`
+pub fn seed(expr: &syn::Expr) -> Option<Seed> {
+    match expr {
+        syn::Expr::Lit(expr_lit) => match &expr_lit.lit {
+            syn::Lit::ByteStr(lit_byte_str) => Some(Seed::Bytes(lit_byte_str.value())),
+            syn::Lit::Str(lit_str) => Some(Seed::Bytes(lit_str.value().into_bytes())),
+            _ => None,
+        },
+        syn::Expr::Reference(expr_reference) => seed(&expr_reference.expr),
+        syn::Expr::Paren(expr_paren) => seed(&expr_paren.expr),
+        syn::Expr::Group(expr_group) => seed(&expr_group.expr),
+        syn::Expr::MethodCall(method_call) if method_call.args.is_empty() => {
+            let method = method_call.method.to_string();
+            if matches!(
+                method.as_str(),
+                "as_ref" | "as_bytes" | "key" | "to_account_info"
+            ) {
+                seed(&method_call.receiver)
+            } else {
+                Some(Seed::Expr(format!(
+                    "{}.{method}()",
+                    seed_expr(&method_call.receiver)?
+                )))
+            }
+        }
+        syn::Expr::Field(expr_field) => {
+            let member = match &expr_field.member {
+                syn::Member::Named(ident) => ident.to_string(),
+                syn::Member::Unnamed(index) => index.index.to_string(),
+            };
+            // `AccountInfo::key`
+            if member == "key" {
+                return seed(&expr_field.base);
+            }
+            // `ctx.accounts.x`
+            if_chain! {
+                if let syn::Expr::Field(base) = &*expr_field.base;
+                if let syn::Member::Named(ident) = &base.member;
+                if ident == "accounts";
+                if let syn::Expr::Path(_) = &*base.base;
+                then {
+                    return Some(Seed::Expr(member));
+                }
+            }
+            Some(Seed::Expr(format!(
+                "{}.{member}",
+                seed_expr(&expr_field.base)?
+            )))
+        }
+        syn::Expr::Path(expr_path) => Some(Seed::Expr(
+            expr_path
+                .path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::"),
+        )),
+        _ => None,
+    }
+}
+
+/// Return the seed of the expression as an expression, e.g., the receiver of a method call
+fn seed_expr(expr: &syn::Expr) -> Option<String> {
+    match seed(expr)? {
+        Seed::Expr(expr) => Some(expr),
+        Seed::Bytes(_) => None,
+    }
+}
+
+/// Format the seeds, e.g., `[b"escrow", maker, taker]`
+pub fn display_seeds(seeds: &[Seed]) -> String {
+    let seeds = seeds
+        .iter()
+        .map(|seed| match seed {
+            Seed::Bytes(bytes) => format!("b\"{}\"", bytes.escape_ascii()),
+            Seed::Expr(expr) => expr.clone(),
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", seeds.join(", "))
+}
@@ -0,0 +1,168 @@
+//! Suggestions for the findings on the accounts of non Anchor programs. Anchor checks an account
+//! with a constraint of its `Accounts` struct, whereas a non Anchor program checks it in the code
+//! of the instruction. A finding of a non Anchor program therefore suggests both: the check to
+//! write in the instruction, and the Anchor constraint making the same check, for the programs
+//! migrating to Anchor, e.g.,
+//!
+//! ```text
+//! = help: check the owner of `vault`: `if vault.owner != &crate::ID { return Err(ProgramError::IllegalOwner); }`
+//! = help: with Anchor, the same check is the constraint `#[account(owner = crate::ID)]` on the field `vault`
+//! ```
+//!
+//! The suggestion is built from the uses of the account, e.g., the expected owner of an account
+//! unpacked into the state of the Token program is `spl_token::ID` (see [`expected_owner`]).
+
+use clippy_utils::{fn_def_id, match_any_def_paths, source::snippet, SpanlessEq};
+use if_chain::if_chain;
+use rustc_errors::DiagnosticBuilder;
+use rustc_hir::{Expr, ExprKind, QPath};
+use rustc_lint::LateContext;
+use rustc_middle::ty;
+
+use crate::{known_programs::KNOWN_PROGRAMS, paths, utils::visit_expr_no_bodies};
+
+/// A check of an account which a finding is missing
+pub enum AccountCheck {
+    /// The account is owned by the program whose ID is the expression `owner`, e.g., `crate::ID`
+    Owner(String),
+    /// The account signed the transaction
+    Signer,
+}
+
+/// The fixes of a finding on an account of a non Anchor program, with and without Anchor
+pub struct CheckSuggestion {
+    /// The expression of the account, e.g., `vault` or `accounts[0]`
+    account: String,
+    check: AccountCheck,
+}
+
+impl CheckSuggestion {
+    pub fn new(account: impl Into<String>, check: AccountCheck) -> Self {
+        Self {
+            account: account.into(),
+            check,
+        }
+    }
+
+    /// Return the suggestion for the account `account`, named after the local variable or the
+    /// field holding it, if any, and else after its source code
+    pub fn for_expr(cx: &LateContext<'_>, account: &Expr<'_>, check: AccountCheck) -> Self {
+        let account = match account.kind {
+            ExprKind::Path(QPath::Resolved(None, path)) if path.segments.len() == 1 => {
+                path.segments[0].ident.to_string()
+            }
+            ExprKind::Field(_, ident) => ident.to_string(),
+            _ => snippet(cx, account.span, "account").into_owned(),
+        };
+        Self::new(account, check)
+    }
+
+    /// The check to write in the instruction, e.g.,
+    /// `if !authority.is_signer { return Err(ProgramError::MissingRequiredSignature); }`
+    pub fn manual_check(&self) -> String {
+        match &self.check {
+            AccountCheck::Owner(owner) => format!(
+                "if {}.owner != &{owner} {{ return Err(ProgramError::IllegalOwner); }}",
+                self.account
+            ),
+            AccountCheck::Signer => format!(
+                "if !{}.is_signer {{ return Err(ProgramError::MissingRequiredSignature); }}",
+                self.account
+            ),
+        }
+    }
+
+    /// The description of the Anchor constraint making the same check as [`Self::manual_check`]
+    pub fn anchor_constraint(&self) -> String {
+        match &self.check {
+            AccountCheck::Owner(owner) => format!(
+                "the constraint `#[account(owner = {owner})]` on the field `{}`",
+                self.field_name()
+            ),
+            AccountCheck::Signer => format!(
+                "the type `Signer<'info>`, or the constraint `#[account(signer)]`, of the field `{}`",
+                self.field_name()
+            ),
+        }
+    }
+
+    /// Add the manual check and the Anchor constraint to `diag`, as help messages
+    pub fn add_to(&self, diag: &mut DiagnosticBuilder<'_, ()>) {
+        let what = match self.check {
+            AccountCheck::Owner(_) => "the owner of",
+            AccountCheck::Signer => "that the transaction is signed by",
+        };
+        diag.help(format!(
+            "check {what} `{}`: `{}`",
+            self.account,
+            self.manual_check()
+        ));
+        diag.help(format!(
+            "with Anchor, the same check is {}",
+            self.anchor_constraint()
+        ));
+    }
+
+    /// The name of the field of an Anchor `Accounts` struct holding the account: its name if it is
+    /// an identifier, e.g., `vault`, and else `account`, e.g., for `accounts[0]`
+    fn field_name(&self) -> &str {
+        if !self.account.is_empty()
+            && self
+                .account
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            &self.account
+        } else {
+            "account"
+        }
+    }
+}
+
+/// Return the ID of the program expected to own `account` in `body`: the ID of the well-known
+/// program whose state `account` is unpacked into, e.g., `spl_token::ID` for
+/// `spl_token::state::Account::unpack(&account.data.borrow())`, and else `crate::ID`, the current
+/// program
+pub fn expected_owner<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx Expr<'tcx>,
+    account: &Expr<'_>,
+) -> String {
+    visit_expr_no_bodies(body, |expr| unpacked_state_owner(cx, expr, account))
+        .unwrap_or_else(|| String::from("crate::ID"))
+}
+
+/// If `expr` unpacks `account` with `Pack::unpack`, or another function of `Pack`, into the state
+/// of a well-known program, return the path of the ID of the program
+fn unpacked_state_owner(
+    cx: &LateContext<'_>,
+    expr: &Expr<'_>,
+    account: &Expr<'_>,
+) -> Option<String> {
+    if_chain! {
+        if let ExprKind::Call(callee, [arg]) = expr.kind;
+        if let Some(def_id) = fn_def_id(cx, expr);
+        if match_any_def_paths(
+            cx,
+            def_id,
+            &[
+                &paths::SOLANA_PROGRAM_UNPACK,
+                &paths::SOLANA_PROGRAM_UNPACK_FROM_SLICE,
+                &paths::SOLANA_PROGRAM_UNPACK_UNCHECKED,
+            ],
+        )
+        .is_some();
+        if visit_expr_no_bodies(arg, |expr| SpanlessEq::new(cx).eq_expr(expr, account));
+        if let ty::Adt(adt_def, _) = cx.typeck_results().node_args(callee.hir_id).type_at(0).kind();
+        let crate_name = cx.tcx.crate_name(adt_def.did().krate);
+        then {
+            KNOWN_PROGRAMS
+                .iter()
+                .flat_map(|program| program.items)
+                .find(|path| matches!(path, [krate, "ID"] if *krate == crate_name.as_str()))
+                .map(|path| path.join("::"))
+        } else {
+            None
+        }
+    }
+}
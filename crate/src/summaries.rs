@@ -0,0 +1,110 @@
+//! Summaries of the functions of a crate, written when a lint checks the crate and read when it
+//! checks the crates depending on it, e.g., the other crates of the same workspace.
+//!
+//! A lint records, for each public function of the crate, the parameters which the function
+//! validates, e.g., whose owner it checks, so that passing an account to the function counts as a
+//! check in the crates calling it. The summary of a crate for a lint is the file
+//! `<crate name>.<lint name>.summary`, with one line per function: its path in the crate and the
+//! indexes of its validated parameters, starting from 0, e.g.,
+//!
+//! ```text
+//! ::checks::assert_token_owner 0
+//! ```
+//!
+//! The summaries are written to the directory named by `SOLANA_LINTS_SUMMARIES`, if set, and else
+//! to the output directory of the crate, e.g., `target/debug/deps`, which the crates of a workspace
+//! share. Cargo checks a crate before the crates depending on it, so its summary is written by the
+//! time they are checked.
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_lint::{LateContext, Lint};
+use rustc_span::Symbol;
+use std::{
+    env,
+    fs::{create_dir_all, read_to_string},
+    path::PathBuf,
+};
+
+pub const SUMMARIES_VAR: &str = "SOLANA_LINTS_SUMMARIES";
+
+/// The validated parameters of the functions of the crates the current crate depends on, by crate
+/// name and path, as read from their summaries
+#[derive(Default)]
+pub struct Summaries {
+    functions: FxHashMap<(Symbol, String), Vec<usize>>,
+}
+
+impl Summaries {
+    /// Read the summaries written by `lint` for the crates the current crate depends on. The crates
+    /// without a summary, e.g., those of crates.io, are ignored.
+    pub fn read(cx: &LateContext<'_>, lint: &'static Lint) -> Self {
+        let dir = summaries_dir(cx);
+        let mut functions = FxHashMap::default();
+        for &cnum in cx.tcx.crates(()) {
+            let crate_name = cx.tcx.crate_name(cnum);
+            let contents = match read_to_string(dir.join(file_name(crate_name, lint))) {
+                Ok(contents) => contents,
+                Err(_) => continue,
+            };
+            for (path, params) in contents.lines().filter_map(parse_summary_line) {
+                functions.insert((crate_name, path), params);
+            }
+        }
+        Self { functions }
+    }
+
+    /// Return the indexes of the parameters validated by `def_id`, a function of another crate
+    pub fn validated_params(&self, cx: &LateContext<'_>, def_id: DefId) -> &[usize] {
+        if def_id.is_local() {
+            return &[];
+        }
+        self.functions
+            .get(&(cx.tcx.crate_name(def_id.krate), function_path(cx, def_id)))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Write the summary of the current crate for `lint`: the functions of the crate, each with the
+/// indexes of the parameters it validates. The summary replaces the previous one, if any.
+pub fn write(cx: &LateContext<'_>, lint: &'static Lint, functions: &[(DefId, Vec<usize>)]) {
+    let dir = summaries_dir(cx);
+    let path = dir.join(file_name(cx.tcx.crate_name(LOCAL_CRATE), lint));
+    let contents: String = functions
+        .iter()
+        .map(|(def_id, params)| {
+            let params: Vec<String> = params.iter().map(ToString::to_string).collect();
+            format!("{} {}\n", function_path(cx, *def_id), params.join(" "))
+        })
+        .collect();
+    if let Err(error) = create_dir_all(&dir).and_then(|()| std::fs::write(&path, contents)) {
+        cx.tcx.sess.dcx().warn(format!(
+            "could not write the summary `{}`: {error}",
+            path.display()
+        ));
+    }
+}
+
+fn summaries_dir(cx: &LateContext<'_>) -> PathBuf {
+    env::var_os(SUMMARIES_VAR).map_or_else(
+        || cx.tcx.output_filenames(()).out_directory.clone(),
+        PathBuf::from,
+    )
+}
+
+fn file_name(crate_name: Symbol, lint: &'static Lint) -> String {
+    format!("{crate_name}.{}.summary", lint.name_lower())
+}
+
+/// The path of `def_id` in its crate, e.g., `::checks::assert_token_owner`, which is the same
+/// whether the crate is the current one or a dependency
+fn function_path(cx: &LateContext<'_>, def_id: DefId) -> String {
+    cx.tcx.def_path(def_id).to_string_no_crate_verbose()
+}
+
+fn parse_summary_line(line: &str) -> Option<(String, Vec<usize>)> {
+    let mut words = line.split_whitespace();
+    let path = words.next()?;
+    let params = words.map(str::parse).collect::<Result<_, _>>().ok()?;
+    Some((path.to_owned(), params))
+}
@@ -0,0 +1,155 @@
+//! Taint tracking over MIR, for lints which check how the values supplied by the caller of an
+//! instruction are used. The tracking is intraprocedural and flow-insensitive: a local is tainted
+//! if it is assigned anywhere in the function from a tainted local, or is the result of a call
+//! with a tainted argument.
+
+use clippy_utils::ty::match_type;
+use rustc_index::bit_set::BitSet;
+use rustc_lint::LateContext;
+use rustc_middle::{
+    mir::{
+        visit::{PlaceContext, Visitor},
+        Body, Local, Location, Operand, Rvalue, StatementKind, TerminatorKind,
+    },
+    ty,
+};
+
+use crate::paths;
+
+/// Return the arguments of `body` which hold instruction arguments:
+/// - if the first argument is an Anchor `Context`, i.e., `body` is an instruction handler, the
+///   other arguments
+/// - otherwise, the arguments of type `&[u8]`, i.e., the instruction data
+pub fn instruction_argument_locals<'tcx>(cx: &LateContext<'tcx>, body: &Body<'tcx>) -> Vec<Local> {
+    let mut args = body.args_iter();
+    if let Some(first) = args.next() {
+        if match_type(cx, body.local_decls[first].ty, &paths::ANCHOR_LANG_CONTEXT) {
+            return args.collect();
+        }
+    }
+    body.args_iter()
+        .filter(|&arg| {
+            matches!(
+                body.local_decls[arg].ty.kind(),
+                ty::Ref(_, inner, _) if matches!(
+                    inner.kind(),
+                    ty::Slice(elem) if matches!(elem.kind(), ty::Uint(ty::UintTy::U8))
+                )
+            )
+        })
+        .collect()
+}
+
+/// Return the locals of `body` whose values are derived from `sources`, including `sources`
+pub fn tainted_locals(body: &Body<'_>, sources: &[Local]) -> BitSet<Local> {
+    let mut tainted = BitSet::new_empty(body.local_decls.len());
+    for &source in sources {
+        tainted.insert(source);
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (block, block_data) in body.basic_blocks.iter_enumerated() {
+            for (statement_index, stmt) in block_data.statements.iter().enumerate() {
+                if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind {
+                    let location = Location {
+                        block,
+                        statement_index,
+                    };
+                    let mut visitor = UsesTainted {
+                        tainted: &tainted,
+                        found: false,
+                    };
+                    visitor.visit_rvalue(rvalue, location);
+                    if visitor.found {
+                        changed |= tainted.insert(place.local);
+                    }
+                }
+            }
+            if let TerminatorKind::Call {
+                args, destination, ..
+            } = &block_data.terminator().kind
+            {
+                let location = Location {
+                    block,
+                    statement_index: block_data.statements.len(),
+                };
+                let mut visitor = UsesTainted {
+                    tainted: &tainted,
+                    found: false,
+                };
+                for arg in args {
+                    visitor.visit_operand(arg, location);
+                }
+                if visitor.found {
+                    changed |= tainted.insert(destination.local);
+                }
+            }
+        }
+    }
+    tainted
+}
+
+/// Return the locals from which the value of `local` is computed, including `local`, following
+/// copies, moves, casts, and the operands of binary operations and aggregates, e.g., the bounds of
+/// a range. The trace stops at other rvalues, and at calls.
+pub fn value_sources(body: &Body<'_>, local: Local) -> BitSet<Local> {
+    let mut sources = BitSet::new_empty(body.local_decls.len());
+    let mut stack = vec![local];
+    while let Some(local) = stack.pop() {
+        if !sources.insert(local) {
+            continue;
+        }
+        for stmt in body
+            .basic_blocks
+            .iter()
+            .flat_map(|block_data| &block_data.statements)
+        {
+            if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind {
+                if place.local == local && !place.is_indirect() {
+                    stack.extend(rvalue_value_sources(rvalue));
+                }
+            }
+        }
+    }
+    sources
+}
+
+/// Return the locals from which `rvalue` computes its value, as followed by [`value_sources`]
+pub fn rvalue_value_sources(rvalue: &Rvalue<'_>) -> Vec<Local> {
+    match rvalue {
+        Rvalue::Use(operand) | Rvalue::Cast(_, operand, _) => {
+            operand_local(operand).into_iter().collect()
+        }
+        Rvalue::BinaryOp(_, box (left, right)) | Rvalue::CheckedBinaryOp(_, box (left, right)) => {
+            [left, right]
+                .into_iter()
+                .filter_map(operand_local)
+                .collect()
+        }
+        Rvalue::Aggregate(_, operands) => operands.iter().filter_map(operand_local).collect(),
+        Rvalue::CopyForDeref(place) => vec![place.local],
+        _ => Vec::new(),
+    }
+}
+
+fn operand_local(operand: &Operand<'_>) -> Option<Local> {
+    match operand {
+        Operand::Copy(place) | Operand::Move(place) => Some(place.local),
+        Operand::Constant(_) => None,
+    }
+}
+
+/// Finds whether a tainted local is used, including in a projection, e.g., `_1[_2]`
+struct UsesTainted<'a> {
+    tainted: &'a BitSet<Local>,
+    found: bool,
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for UsesTainted<'a> {
+    fn visit_local(&mut self, local: Local, _: PlaceContext, _: Location) {
+        if self.tainted.contains(local) {
+            self.found = true;
+        }
+    }
+}
@@ -0,0 +1,351 @@
+//! The checks made by the constraints of the fields of Anchor `Accounts` structs. Lints which
+//! exempt a field because of its constraints, e.g., from an owner check, query a
+//! [`ConstraintSummary`] rather than the `anchor_syn::ConstraintGroup`, so that the meaning of the
+//! constraints is defined in one place.
+
+use anchor_syn::{
+    AccountField, AccountsStruct, ConstraintGroup, ConstraintInitGroup, Field, InitKind,
+    Ty as FieldTy,
+};
+
+use crate::seeds::{field_seeds, seed, Seed};
+
+/// The field of the event authority in the `Accounts` structs of the instructions calling
+/// `emit_cpi!`: the PDA of the program signing the self-CPI which carries the event. `#[event_cpi]`
+/// adds it to the struct, with the constraints `seeds = [b"__event_authority"], bump`.
+pub const EVENT_AUTHORITY_FIELD: &str = "event_authority";
+
+/// The seed of the event authority
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// The field of the program invoked by the self-CPI of `emit_cpi!`, which `#[event_cpi]` adds to
+/// the struct next to [`EVENT_AUTHORITY_FIELD`]
+pub const EVENT_CPI_PROGRAM_FIELD: &str = "program";
+
+/// What the constraints of a field check, or do to the account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConstraintSummary {
+    /// `signer`: the account signs the instruction
+    pub is_signer: bool,
+    /// `mut`
+    pub is_mutable: bool,
+    /// `init`, `init_if_needed` or `zero`: the instruction initializes the account
+    pub is_initialized: bool,
+    /// `init_if_needed`: the account is initialized by the instruction, unless it already exists
+    pub is_init_if_needed: bool,
+    /// `close`
+    pub is_closed: bool,
+    /// `realloc`
+    pub is_reallocated: bool,
+    /// `owner = ...`, or, for the summaries of [`field_constraint_summary`], a `constraint`
+    /// comparing the owner of the account, e.g., `constraint = data.owner == &crate::ID`
+    pub has_owner_check: bool,
+    /// `address = ...` or `seeds = ...`: the key of the account is checked
+    pub has_key_check: bool,
+    /// `executable`: the account is a program, owned by a BPF loader
+    pub is_executable: bool,
+    /// `has_one = ...` or `constraint = ...`: the account is checked against the other accounts or
+    /// values, e.g., its authority
+    pub has_relation_check: bool,
+    /// For the summaries of [`field_constraint_summary`], `has_one = {field}` on another field, or
+    /// a `constraint` comparing the key of the account, e.g.,
+    /// `constraint = vault.authority == authority.key()`: the key is checked against the data of
+    /// the other accounts or other values
+    pub is_key_compared: bool,
+}
+
+impl ConstraintSummary {
+    pub fn new(constraints: &ConstraintGroup) -> Self {
+        let init_if_needed = constraints
+            .init
+            .as_ref()
+            .map_or(false, |init| init.if_needed);
+        Self {
+            is_signer: constraints.signer.is_some(),
+            is_mutable: constraints.mutable.is_some(),
+            is_initialized: constraints.init.is_some() || constraints.zeroed.is_some(),
+            is_init_if_needed: init_if_needed,
+            is_closed: constraints.close.is_some(),
+            is_reallocated: constraints.realloc.is_some(),
+            has_owner_check: constraints.owner.is_some(),
+            has_key_check: constraints.address.is_some() || constraints.seeds.is_some(),
+            is_executable: constraints.executable.is_some(),
+            has_relation_check: !constraints.has_one.is_empty() || !constraints.raw.is_empty(),
+            is_key_compared: false,
+        }
+    }
+
+    /// Return true if the account is writable: `mut`, or `init`, `init_if_needed`, `zero`, `close`
+    /// and `realloc`, which Anchor requires to be mutable
+    pub fn is_writable(&self) -> bool {
+        self.is_mutable || self.is_initialized || self.is_closed || self.is_reallocated
+    }
+
+    /// Return true if the constraints exempt the account from an owner check:
+    /// - `signer`: signers are assumed to be EOA accounts
+    /// - `init_if_needed`: Anchor checks the owner of an account which already exists
+    /// - `address` and `seeds`: the key of the account is checked
+    /// - `owner`, or a `constraint` comparing the owner
+    /// - `executable`: all executables are owned by BPF loaders
+    /// - `has_one` on another field or a `constraint` comparing the key, as for `address`
+    pub fn exempts_owner_check(&self) -> bool {
+        self.is_signer
+            || self.is_init_if_needed
+            || self.has_key_check
+            || self.has_owner_check
+            || self.is_executable
+            || self.is_key_compared
+    }
+}
+
+/// Return the summary of the constraints of the field `field_name` of `accounts_struct`, which can
+/// be a composite field.
+///
+/// The constraints of all the fields which compare the key or the owner of the account are included
+/// (see `ConstraintSummary::is_key_compared` and `ConstraintSummary::has_owner_check`), e.g.,
+/// `has_one = authority` on the field `vault` checks the key of `authority` against
+/// `vault.authority`.
+pub fn field_constraint_summary(
+    accounts_struct: &AccountsStruct,
+    field_name: &str,
+) -> Option<ConstraintSummary> {
+    let mut summary = accounts_struct
+        .fields
+        .iter()
+        .find_map(|account_field| match account_field {
+            AccountField::Field(field) if field.ident == field_name => Some(&field.constraints),
+            AccountField::CompositeField(field) if field.ident == field_name => {
+                Some(&field.constraints)
+            }
+            _ => None,
+        })
+        .map(ConstraintSummary::new)?;
+    let compared = compared_expressions(accounts_struct);
+    summary.is_key_compared = compared.contains(&Seed::Expr(field_name.to_owned()));
+    summary.has_owner_check |= compared.contains(&Seed::Expr(format!("{field_name}.owner")));
+    Some(summary)
+}
+
+/// Return the expressions which the constraints of the fields of `accounts_struct` compare,
+/// normalized as seeds: the targets of the `has_one` constraints, whose keys are compared, and the
+/// operands of the `==` comparisons of the `constraint`s, including those combined with `&&`,
+/// e.g., `authority` and `vault.owner` for `constraint = vault.owner == authority.key()`
+fn compared_expressions(accounts_struct: &AccountsStruct) -> Vec<Seed> {
+    let mut compared = Vec::new();
+    for account_field in &accounts_struct.fields {
+        let constraints = match account_field {
+            AccountField::Field(field) => &field.constraints,
+            AccountField::CompositeField(field) => &field.constraints,
+        };
+        compared.extend(
+            constraints
+                .has_one
+                .iter()
+                .filter_map(|has_one| seed(&has_one.join_target)),
+        );
+        for raw in &constraints.raw {
+            equality_operands(&raw.raw, &mut compared);
+        }
+    }
+    compared
+}
+
+/// Push to `operands` the operands of the `==` comparisons of `expr`, a conjunction of conditions
+fn equality_operands(expr: &syn::Expr, operands: &mut Vec<Seed>) {
+    match expr {
+        syn::Expr::Binary(binary) if matches!(binary.op, syn::BinOp::And(_)) => {
+            equality_operands(&binary.left, operands);
+            equality_operands(&binary.right, operands);
+        }
+        syn::Expr::Binary(binary) if matches!(binary.op, syn::BinOp::Eq(_)) => {
+            operands.extend(seed(peel_derefs(&binary.left)));
+            operands.extend(seed(peel_derefs(&binary.right)));
+        }
+        syn::Expr::Paren(expr_paren) => equality_operands(&expr_paren.expr, operands),
+        _ => {}
+    }
+}
+
+/// Return true if a `constraint` of the fields of `accounts_struct` compares the keys of the
+/// fields `a` and `b` with each other, including in a conjunction of conditions, e.g.,
+/// `constraint = source.key() != destination.key()`
+pub fn compares_field_keys(accounts_struct: &AccountsStruct, a: &str, b: &str) -> bool {
+    let (a, b) = (Seed::Expr(a.to_owned()), Seed::Expr(b.to_owned()));
+    accounts_struct.fields.iter().any(|account_field| {
+        let constraints = match account_field {
+            AccountField::Field(field) => &field.constraints,
+            AccountField::CompositeField(field) => &field.constraints,
+        };
+        constraints.raw.iter().any(|raw| {
+            let mut pairs = Vec::new();
+            compared_pairs(&raw.raw, &mut pairs);
+            pairs
+                .iter()
+                .any(|(left, right)| (*left == a && *right == b) || (*left == b && *right == a))
+        })
+    })
+}
+
+/// Push to `pairs` the operands of the `==` and `!=` comparisons of `expr`, a conjunction of
+/// conditions
+fn compared_pairs(expr: &syn::Expr, pairs: &mut Vec<(Seed, Seed)>) {
+    match expr {
+        syn::Expr::Binary(binary) if matches!(binary.op, syn::BinOp::And(_)) => {
+            compared_pairs(&binary.left, pairs);
+            compared_pairs(&binary.right, pairs);
+        }
+        syn::Expr::Binary(binary) if matches!(binary.op, syn::BinOp::Eq(_) | syn::BinOp::Ne(_)) => {
+            if let (Some(left), Some(right)) = (
+                seed(peel_derefs(&binary.left)),
+                seed(peel_derefs(&binary.right)),
+            ) {
+                pairs.push((left, right));
+            }
+        }
+        syn::Expr::Paren(expr_paren) => compared_pairs(&expr_paren.expr, pairs),
+        _ => {}
+    }
+}
+
+/// Return the operand of the derefs of `expr`, e.g., `authority.key` for `*authority.key`
+fn peel_derefs(mut expr: &syn::Expr) -> &syn::Expr {
+    while let syn::Expr::Unary(syn::ExprUnary {
+        op: syn::UnOp::Deref(_),
+        expr: inner,
+        ..
+    }) = expr
+    {
+        expr = inner;
+    }
+    expr
+}
+
+/// Return the field named `name` of `accounts_struct`, unless it is a composite field
+pub fn anchor_field<'a>(accounts_struct: &'a AccountsStruct, name: &str) -> Option<&'a Field> {
+    accounts_struct
+        .fields
+        .iter()
+        .find_map(|account_field| match account_field {
+            AccountField::Field(field) if field.ident == name => Some(field),
+            _ => None,
+        })
+}
+
+/// Return the `space` of an `init` constraint which creates a program account
+pub fn init_space(init: &ConstraintInitGroup) -> Option<&syn::Expr> {
+    if let InitKind::Program { .. } = init.kind {
+        init.space.as_ref()
+    } else {
+        None
+    }
+}
+
+/// Return true if the `seeds` constraint of the field derives the event authority of the current
+/// program, i.e., is `seeds = [b"__event_authority"], bump`
+pub fn has_event_authority_seeds(field: &Field) -> bool {
+    field_seeds(field).map_or(false, |seeds| {
+        seeds == [Seed::Bytes(EVENT_AUTHORITY_SEED.to_vec())]
+    })
+}
+
+/// Return true if the field is an `Account<'info, TokenAccount>` or an
+/// `InterfaceAccount<'info, TokenAccount>`
+pub fn is_token_account(field: &Field) -> bool {
+    let account_type_path = match &field.ty {
+        FieldTy::Account(account_ty) => &account_ty.account_type_path,
+        FieldTy::InterfaceAccount(interface_account_ty) => &interface_account_ty.account_type_path,
+        _ => return false,
+    };
+    account_type_path
+        .path
+        .segments
+        .last()
+        .map_or(false, |segment| segment.ident == "TokenAccount")
+}
+
+/// Return the expressions which the constraints of the token account check its mint against,
+/// normalized as seeds, e.g., `mint` for `token::mint = mint` or
+/// `constraint = source.mint == mint.key()`
+pub fn token_account_mints(field: &Field) -> Vec<Seed> {
+    let constraints = &field.constraints;
+    let mut mints = Vec::new();
+    if let Some(token_account) = &constraints.token_account {
+        mints.extend(token_account.mint.as_ref().and_then(seed));
+    }
+    if let Some(associated_token) = &constraints.associated_token {
+        mints.extend(seed(&associated_token.mint));
+    }
+    if let Some(init) = &constraints.init {
+        if let InitKind::Token { mint, .. } | InitKind::AssociatedToken { mint, .. } = &init.kind {
+            mints.extend(seed(mint));
+        }
+    }
+    // `has_one = mint` compares the field `mint` of the account with the key of `mint`
+    mints.extend(has_one_targets(constraints, "mint"));
+    mints.extend(raw_comparisons(field, "mint"));
+    mints
+}
+
+/// Return the expressions which the constraints of the token account check its owner against,
+/// normalized as seeds, e.g., `authority` for `token::authority = authority` or
+/// `constraint = vault.owner == authority.key()`
+pub fn token_account_authorities(field: &Field) -> Vec<Seed> {
+    let constraints = &field.constraints;
+    let mut authorities = Vec::new();
+    if let Some(token_account) = &constraints.token_account {
+        authorities.extend(token_account.authority.as_ref().and_then(seed));
+    }
+    if let Some(associated_token) = &constraints.associated_token {
+        authorities.extend(seed(&associated_token.wallet));
+    }
+    if let Some(init) = &constraints.init {
+        if let InitKind::Token { owner, .. } | InitKind::AssociatedToken { owner, .. } = &init.kind
+        {
+            authorities.extend(seed(owner));
+        }
+    }
+    // `has_one = owner` compares the field `owner` of the account with the key of `owner`
+    authorities.extend(has_one_targets(constraints, "owner"));
+    authorities.extend(raw_comparisons(field, "owner"));
+    authorities
+}
+
+/// Return the expressions which the `has_one` constraints and the `constraint`s of the field check
+/// its member `member` against, normalized as seeds, e.g., `authority` for `has_one = authority`
+/// or `constraint = config.authority == authority.key()`
+pub fn member_targets(field: &Field, member: &str) -> Vec<Seed> {
+    let mut targets = has_one_targets(&field.constraints, member);
+    targets.extend(raw_comparisons(field, member));
+    targets
+}
+
+/// Return the targets of the `has_one` constraints named `name`
+fn has_one_targets(constraints: &ConstraintGroup, name: &str) -> Vec<Seed> {
+    constraints
+        .has_one
+        .iter()
+        .filter_map(|has_one| match seed(&has_one.join_target) {
+            Some(Seed::Expr(target)) if target == name => Some(Seed::Expr(target)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Return the expressions which the `constraint`s of the field compare `{field}.{member}` with,
+/// e.g., `x` for `constraint = {field}.{member} == x`
+fn raw_comparisons(field: &Field, member: &str) -> Vec<Seed> {
+    let field_member = Seed::Expr(format!("{}.{member}", field.ident));
+    let mut values = Vec::new();
+    for raw in &field.constraints.raw {
+        if let syn::Expr::Binary(binary) = &raw.raw {
+            if matches!(binary.op, syn::BinOp::Eq(_)) {
+                match (seed(&binary.left), seed(&binary.right)) {
+                    (Some(left), Some(right)) if left == field_member => values.push(right),
+                    (Some(left), Some(right)) if right == field_member => values.push(left),
+                    _ => {}
+                }
+            }
+        }
+    }
+    values
+}
@@ -0,0 +1,217 @@
+//! Borsh layouts of types, i.e., the shapes of their serializations, to decide whether the data of a
+//! value of a type may be deserialized as a value of another type.
+//!
+//! A layout is the set of the shapes a serialization may have, one per combination of the variants
+//! of the enums it contains. A shape is a sequence of atoms: plain bytes, e.g., of an integer or a
+//! `Pubkey`, the tags of the variants of the enums, and the values of variable length, e.g., of a
+//! `Vec`. The newtypes, the tuples and the nested structs are flattened, and the adjacent plain bytes
+//! are merged, so that `struct A(Pubkey, u64)` and `struct B { key: Key, amount: (u32, u32) }`, where
+//! `struct Key(Pubkey)`, have the same layout.
+
+use clippy_utils::match_def_path;
+use rustc_lint::LateContext;
+use rustc_middle::ty::{self, Ty, VariantDef};
+use rustc_span::sym;
+
+use super::borsh_size;
+use crate::paths;
+
+/// The maximum number of shapes of a layout, past which the layout is not computed
+const MAX_SHAPES: usize = 256;
+
+/// The maximum depth of the nested types of a layout, past which the layout is not computed, e.g.,
+/// of a recursive enum
+const MAX_DEPTH: usize = 16;
+
+/// A part of a serialization
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Atom {
+    /// `n` bytes which may hold any value
+    Bytes(u64),
+    /// The byte holding the index of the variant of an enum, `Option` or `bool`
+    Tag(u8),
+    /// A value of variable length, e.g., a `Vec` or a `String`, or of a type defined outside of the
+    /// current crate, identified by its type
+    Opaque(String),
+}
+
+/// A sequence of atoms
+pub type Shape = Vec<Atom>;
+
+/// The shapes the serialization of a type may have
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Layout {
+    pub shapes: Vec<Shape>,
+}
+
+impl Atom {
+    fn width(&self) -> u64 {
+        match self {
+            Atom::Bytes(n) => *n,
+            Atom::Tag(_) | Atom::Opaque(_) => 1,
+        }
+    }
+
+    /// Return true if some bytes may be read as both `self` and `other`
+    fn matches(&self, other: &Atom) -> bool {
+        match (self, other) {
+            (Atom::Bytes(_), Atom::Bytes(_) | Atom::Tag(_)) | (Atom::Tag(_), Atom::Bytes(_)) => {
+                true
+            }
+            (Atom::Tag(tag), Atom::Tag(other_tag)) => tag == other_tag,
+            (Atom::Opaque(ty), Atom::Opaque(other_ty)) => ty == other_ty,
+            _ => false,
+        }
+    }
+}
+
+impl Layout {
+    /// Return true if some data may be deserialized both as a value of `self` and as a value of
+    /// `other`, i.e., if a shape of `self` and a shape of `other` have the same length, and atoms
+    /// matching at each offset
+    pub fn overlaps(&self, other: &Layout) -> bool {
+        self.shapes.iter().any(|shape| {
+            other
+                .shapes
+                .iter()
+                .any(|other_shape| shapes_overlap(shape, other_shape))
+        })
+    }
+}
+
+/// Return the Borsh layout of `ty`, or None if it cannot be computed, e.g., if `ty` has generic
+/// parameters or too many shapes
+/// - integers, floats and `char`: their bytes, as in `borsh_size`
+/// - `bool`: the tags 0 and 1
+/// - `Pubkey`: 32 bytes
+/// - arrays, tuples and structs defined in the current crate: the concatenation of the layouts of
+///   their elements
+/// - enums defined in the current crate and `Option`: for each variant, its tag followed by the
+///   concatenation of the layouts of its fields
+/// - `Box<T>`: the layout of `T`
+/// - Else, e.g., `Vec` and `String`, an opaque atom
+pub fn borsh_layout<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>) -> Option<Layout> {
+    layout(cx, ty, 0).map(|shapes| Layout { shapes })
+}
+
+fn layout<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>, depth: usize) -> Option<Vec<Shape>> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let opaque = || Some(vec![vec![Atom::Opaque(ty.to_string())]]);
+    match ty.kind() {
+        ty::Bool => Some(vec![vec![Atom::Tag(0)], vec![Atom::Tag(1)]]),
+        ty::Char | ty::Int(_) | ty::Uint(_) | ty::Float(_) => {
+            Some(vec![vec![Atom::Bytes(borsh_size(cx, ty)?)]])
+        }
+        ty::Array(elem_ty, len) => {
+            let len = len.try_eval_target_usize(cx.tcx, cx.param_env)?;
+            let elem_shapes = layout(cx, *elem_ty, depth + 1)?;
+            concat((0..len).map(|_| Some(elem_shapes.clone())))
+        }
+        ty::Tuple(tys) => concat(tys.iter().map(|ty| layout(cx, ty, depth + 1))),
+        ty::Adt(adt_def, args) => {
+            if match_def_path(cx, adt_def.did(), &paths::SOLANA_PROGRAM_PUBKEY) {
+                return Some(vec![vec![Atom::Bytes(32)]]);
+            }
+            if ty.is_box() {
+                return layout(cx, ty.boxed_ty(), depth + 1);
+            }
+            // The fields of the other types, e.g., `Vec`, do not reflect their serialization.
+            if !adt_def.did().is_local() && !cx.tcx.is_diagnostic_item(sym::Option, adt_def.did()) {
+                return opaque();
+            }
+            let variant_shapes = |variant: &VariantDef| {
+                concat(
+                    variant
+                        .fields
+                        .iter()
+                        .map(|field| layout(cx, field.ty(cx.tcx, args), depth + 1)),
+                )
+            };
+            if adt_def.is_struct() {
+                variant_shapes(adt_def.non_enum_variant())
+            } else if adt_def.is_enum() {
+                let mut shapes = Vec::new();
+                for (index, variant) in adt_def.variants().iter().enumerate() {
+                    let tag = u8::try_from(index).ok()?;
+                    for shape in variant_shapes(variant)? {
+                        let mut tagged = vec![Atom::Tag(tag)];
+                        extend(&mut tagged, shape);
+                        shapes.push(tagged);
+                    }
+                }
+                if shapes.len() > MAX_SHAPES {
+                    return None;
+                }
+                Some(shapes)
+            } else {
+                None
+            }
+        }
+        ty::Param(_) | ty::Alias(..) => None,
+        _ => opaque(),
+    }
+}
+
+/// Return the shapes of the concatenation of values of the layouts `parts`, i.e., the concatenations
+/// of a shape of each part
+fn concat(parts: impl Iterator<Item = Option<Vec<Shape>>>) -> Option<Vec<Shape>> {
+    let mut shapes = vec![Shape::new()];
+    for part in parts {
+        let part = part?;
+        if shapes.len().checked_mul(part.len())? > MAX_SHAPES {
+            return None;
+        }
+        shapes = shapes
+            .iter()
+            .flat_map(|shape| {
+                part.iter().map(move |part_shape| {
+                    let mut shape = shape.clone();
+                    extend(&mut shape, part_shape.clone());
+                    shape
+                })
+            })
+            .collect();
+    }
+    Some(shapes)
+}
+
+/// Append the atoms of `other` to `shape`, merging the adjacent plain bytes
+fn extend(shape: &mut Shape, other: Shape) {
+    for atom in other {
+        match (shape.last_mut(), &atom) {
+            (_, Atom::Bytes(0)) => {}
+            (Some(Atom::Bytes(n)), Atom::Bytes(m)) => *n += m,
+            _ => shape.push(atom),
+        }
+    }
+}
+
+/// Return true if some data may be read as both `shape` and `other`
+fn shapes_overlap(shape: &[Atom], other: &[Atom]) -> bool {
+    let (mut atoms, mut other_atoms) = (shape.iter(), other.iter());
+    // the current atom of each shape, and the width of it which is left to compare
+    let (mut current, mut other_current) = (None, None);
+    loop {
+        if current.is_none() {
+            current = atoms.next().map(|atom| (atom, atom.width()));
+        }
+        if other_current.is_none() {
+            other_current = other_atoms.next().map(|atom| (atom, atom.width()));
+        }
+        match (current, other_current) {
+            (None, None) => return true,
+            (Some((atom, width)), Some((other_atom, other_width))) => {
+                if !atom.matches(other_atom) {
+                    return false;
+                }
+                let compared = width.min(other_width);
+                current = (width > compared).then(|| (atom, width - compared));
+                other_current =
+                    (other_width > compared).then(|| (other_atom, other_width - compared));
+            }
+            _ => return false,
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! The CPIs made by the functions of the crate: the `Instruction`s they build, the calls of
+//! `invoke` with them, and the places which the program IDs of the `Instruction`s and the program
+//! accounts of the `CpiContext`s are assigned from. Shared by `arbitrary_cpi`, which checks that
+//! the program is validated, and `self_cpi`, which checks that it is not the current program.
+
+use clippy_utils::{match_any_def_paths, match_def_path};
+use if_chain::if_chain;
+use rustc_lint::LateContext;
+use rustc_middle::{
+    mir::{
+        self, AggregateKind, BasicBlock, Local, Operand, Place, ProjectionElem, Rvalue, Statement,
+        StatementKind, TerminatorKind,
+    },
+    ty::TyKind,
+};
+
+use super::dataflow::{backward_predecessor, rvalue_source};
+use crate::paths;
+
+/// Return the place of the Instruction and the place of program id if the statement initializes
+/// Instruction i.e stmt is _x = Instruction {...}
+pub fn is_instruction_init_stmt<'tcx>(
+    cx: &LateContext,
+    stmt: &Statement<'tcx>,
+) -> Option<(Place<'tcx>, Place<'tcx>)> {
+    if_chain! {
+        if let StatementKind::Assign(box (instruction_place, rvalue)) = &stmt.kind;
+        // The MIR generated for the `insecure-2` and other programs shows that the entire struct is initialized at once.
+        // Note: Its unknown in what cases the struct initialization is deaggregated. Assuming here that
+        // the struct is initialized at once till a counter example is found.
+        if let Rvalue::Aggregate(box AggregateKind::Adt(def_id, variant_idx, _, _, _), fields) =
+            rvalue;
+        // The Adt is a struct
+        if variant_idx.index() == 0;
+        // The struct is `solana_program::instruction::Instruction`
+        if match_def_path(cx, *def_id, &paths::SOLANA_PROGRAM_INSTRUCTION);
+        // program id is the first field. Assuming its operand is at the start of the fields IndexVec.
+        if let Some(Operand::Move(pl) | Operand::Copy(pl)) = fields.iter().next();
+        then {
+            Some((*instruction_place, *pl))
+        } else {
+            None
+        }
+    }
+}
+
+/// Return the blocks of `body` whose terminator is a call to `invoke` or `invoke_signed` with the
+/// `Instruction` in one of `instruction_locals`
+pub fn instruction_invoke_blocks(
+    cx: &LateContext<'_>,
+    body: &mir::Body<'_>,
+    instruction_locals: &[Local],
+) -> Vec<BasicBlock> {
+    body.basic_blocks
+        .iter_enumerated()
+        .filter_map(|(block, block_data)| {
+            if_chain! {
+                if let TerminatorKind::Call {
+                    func: Operand::Constant(box func),
+                    args,
+                    ..
+                } = &block_data.terminator().kind;
+                if let TyKind::FnDef(def_id, _) = func.const_.ty().kind();
+                if match_any_def_paths(
+                    cx,
+                    *def_id,
+                    &[&paths::SOLANA_PROGRAM_INVOKE, &paths::SOLANA_PROGRAM_INVOKE_SIGNED],
+                )
+                .is_some();
+                if let Some(Operand::Copy(arg) | Operand::Move(arg)) = args.first();
+                if instruction_locals.contains(&arg.local);
+                then {
+                    Some(block)
+                } else {
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Return `local` and the locals of `body` assigned from it, or from one of them, by a move, a copy,
+/// a cast or a borrow (see `rvalue_source`), e.g., `_2 = &_1` or `_3 = &(*_2)`
+pub fn reference_locals(body: &mir::Body<'_>, local: Local) -> Vec<Local> {
+    let mut locals = vec![local];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for stmt in body
+            .basic_blocks
+            .iter()
+            .flat_map(|block_data| &block_data.statements)
+        {
+            if_chain! {
+                if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind;
+                if let Some(source) = rvalue_source(rvalue);
+                if let Some(source) = source.local_or_deref_local();
+                if locals.contains(&source);
+                if !locals.contains(&place.local);
+                then {
+                    locals.push(place.local);
+                    changed = true;
+                }
+            }
+        }
+    }
+    locals
+}
+
+/// Given a place, find other places which are an alias to this place
+///
+/// Starting from `block`, the assignments to the place are followed backwards, through the
+/// predecessors returned by `backward_predecessor`, so that the
+/// blocks of the `?` operator and of the early returns do not end the walk. For an assignment
+/// to the place (or to an alias found earlier), the assigned value is an alias as well:
+/// - `place = x` or `place = &x`: `x` is an alias
+/// - `place = S { .., f: x, .. }` and the place being followed is `place.f`: `x` is an alias
+/// - `place = x.clone()` or `place = x.to_account_info()` where `x` is an `AccountInfo`: `x` is an alias.
+///   These calls are terminators of the predecessor blocks.
+pub fn find_place_aliases<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    block: BasicBlock,
+    id_arg: &Place<'tcx>,
+) -> Vec<Place<'tcx>> {
+    find_place_aliases_through(cx, body, block, id_arg, |block| {
+        backward_predecessor(cx.tcx, body, block)
+    })
+}
+
+/// `find_place_aliases`, following the assignments backwards through the predecessors returned by
+/// `predecessor`, e.g., those cached by `crate::mir_cache::BodyAnalysis`
+pub(crate) fn find_place_aliases_through<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    block: BasicBlock,
+    id_arg: &Place<'tcx>,
+    predecessor: impl Fn(BasicBlock) -> Option<BasicBlock>,
+) -> Vec<Place<'tcx>> {
+    let mut cur_block = block;
+    let mut id_arg = *id_arg;
+    let mut likely_program_id_aliases = Vec::<Place>::new();
+    likely_program_id_aliases.push(id_arg);
+    loop {
+        // The terminator of the starting block is the call being checked. The terminators of the
+        // predecessors are executed after their statements, so check them first.
+        if cur_block != block {
+            if let Some(pl) = call_alias(cx, body, cur_block, &id_arg) {
+                id_arg = pl;
+                likely_program_id_aliases.push(pl);
+            }
+        }
+        // check every stmt
+        for stmt in body.basic_blocks[cur_block].statements.iter().rev() {
+            // if the statement assigns to `id_arg`, update `id_arg` to the rhs
+            if let StatementKind::Assign(box (assign_place, rvalue)) = &stmt.kind {
+                if let Some(pl) = assignment_alias(assign_place, rvalue, &id_arg) {
+                    id_arg = pl;
+                    likely_program_id_aliases.push(pl);
+                }
+            }
+        }
+        match predecessor(cur_block) {
+            Some(predecessor) => cur_block = predecessor,
+            None => {
+                break;
+            }
+        }
+    }
+    likely_program_id_aliases
+}
+
+/// Return the alias of `place` if the statement `assign_place = rvalue` assigns to `place`
+fn assignment_alias<'tcx>(
+    assign_place: &Place<'tcx>,
+    rvalue: &Rvalue<'tcx>,
+    place: &Place<'tcx>,
+) -> Option<Place<'tcx>> {
+    // `place` is a field of a struct which is initialized at once: `_x = S { .., f: _y, .. }`
+    if_chain! {
+        if let [ProjectionElem::Field(field_idx, _)] = place.projection.as_ref();
+        if assign_place.local == place.local && assign_place.projection.is_empty();
+        if let Rvalue::Aggregate(box AggregateKind::Adt(..) | box AggregateKind::Tuple, fields) =
+            rvalue;
+        if let Some(Operand::Copy(pl) | Operand::Move(pl)) = fields.get(*field_idx);
+        then {
+            return Some(*pl);
+        }
+    }
+    if_chain! {
+        if is_same_place(assign_place, place);
+        if let Some(pl) = rvalue_source(rvalue);
+        then {
+            Some(*pl)
+        } else {
+            None
+        }
+    }
+}
+
+/// Return the alias of `place` if the terminator of `block` is a call assigning to `place` which returns
+/// the same account as its argument: `place = x.clone()` or `place = x.to_account_info()` where `x`
+/// is an `AccountInfo`
+fn call_alias<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    block: BasicBlock,
+    place: &Place<'tcx>,
+) -> Option<Place<'tcx>> {
+    if_chain! {
+        if let Some(t) = &body.basic_blocks[block].terminator;
+        if let TerminatorKind::Call {
+            func: mir::Operand::Constant(box func),
+            args,
+            destination,
+            ..
+        } = &t.kind;
+        if is_same_place(destination, place);
+        if let TyKind::FnDef(def_id, _) = func.const_.ty().kind();
+        if let Some(Operand::Copy(arg0_pl) | Operand::Move(arg0_pl)) = args.first();
+        if match_def_path(cx, *def_id, &paths::CORE_CLONE)
+            || (match_def_path(cx, *def_id, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO)
+                && paths::is_account_info_ty(cx, arg0_pl.ty(body, cx.tcx).ty.peel_refs()));
+        then {
+            Some(*arg0_pl)
+        } else {
+            None
+        }
+    }
+}
+
+/// Return true if the two places are the same or refer to the same local (`_x` and `*_x`)
+pub fn is_same_place<'tcx>(place: &Place<'tcx>, other: &Place<'tcx>) -> bool {
+    place == other
+        || (place.local_or_deref_local().is_some()
+            && place.local_or_deref_local() == other.local_or_deref_local())
+}
@@ -0,0 +1,228 @@
+//! Dataflow helpers over MIR bodies.
+//!
+//! `CheckedBlocks` is a forward dataflow analysis over the control flow graph of a body, computing
+//! whether a check, e.g., a comparison of a program ID with an expected ID, is made on every path
+//! from the start of the body to a location. A lint should suppress a warning about a call only if
+//! the call is checked on every path: a check in one branch of an `if` does not protect a call after
+//! the `if`, while checks in both branches do, although neither of them dominates the call.
+//!
+//! `rvalue_source` and `is_moved_from` follow the values of places through the assignments which
+//! copy them, move them, cast them or borrow them. They cover the forms the MIR of the supported
+//! toolchain uses for these assignments, which `crate/mir_probe` checks:
+//!
+//! - `_4 = deref_copy ((*_1).0: &Key)`, a copy of a reference read through another reference,
+//!   e.g., for `&holder.key.0`
+//! - `_2 = (_3.1: u8)`, a field of a temporary, e.g., for `let bump = make().bump`
+//! - `_4 = &mut _2`, the two-phase borrow of the receiver of a method call, which is made before
+//!   the arguments are evaluated, e.g., for `keys.push(keys.len())`
+//!
+//! The assignments are followed backwards through `backward_predecessor`, which skips the blocks
+//! of the control flow graph not leading to the use of the value: the cleanup blocks, the `Break`
+//! arms of the `?` operator, and the back edges of loops.
+
+use if_chain::if_chain;
+use rustc_hir::LangItem;
+use rustc_index::IndexVec;
+use rustc_middle::{
+    mir::{
+        AggregateKind, BasicBlock, Body, BorrowKind, Local, Location, Operand, Place, Rvalue,
+        StatementKind, START_BLOCK,
+    },
+    ty::TyCtxt,
+};
+
+/// The blocks of a body which are only reached through a check
+pub struct CheckedBlocks {
+    /// Whether a check is made on every path from the start of the body to the start of the block
+    on_entry: IndexVec<BasicBlock, bool>,
+    checks: Vec<Location>,
+}
+
+impl CheckedBlocks {
+    /// Compute the blocks of `body` which are only reached through one of `checks`, the locations
+    /// of the statements or terminators making a check
+    pub fn new(body: &Body<'_>, checks: Vec<Location>) -> Self {
+        let is_check_block = |block: BasicBlock| checks.iter().any(|check| check.block == block);
+        let predecessors = body.basic_blocks.predecessors();
+        // A block is checked on entry if every predecessor is checked on entry or makes a check.
+        // Start with every block but the start block checked, and remove blocks until a fixpoint
+        // is reached, so that the paths through loops are handled. The cleanup blocks, which are
+        // only run when unwinding, are not on the paths to the checked locations.
+        let mut on_entry = IndexVec::from_elem_n(true, body.basic_blocks.len());
+        on_entry[START_BLOCK] = false;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in body.basic_blocks.reverse_postorder() {
+                if block == START_BLOCK || !on_entry[block] {
+                    continue;
+                }
+                if !predecessors[block].iter().all(|&predecessor| {
+                    on_entry[predecessor]
+                        || is_check_block(predecessor)
+                        || body.basic_blocks[predecessor].is_cleanup
+                }) {
+                    on_entry[block] = false;
+                    changed = true;
+                }
+            }
+        }
+        Self { on_entry, checks }
+    }
+
+    /// Return true if one of the checks is made on every path from the start of the body to
+    /// `location`
+    pub fn is_checked_at(&self, location: Location) -> bool {
+        self.on_entry[location.block]
+            || self.checks.iter().any(|check| {
+                check.block == location.block && check.statement_index < location.statement_index
+            })
+    }
+}
+
+/// If `rvalue` is the value of a place, or a reference to it, return the place. The value is
+/// copied, moved, cast, copied through a reference (`deref_copy`), or borrowed, including the
+/// two-phase borrows of method receivers, e.g., `_4 = &mut _2`. Fake borrows, which only exist
+/// for the borrow checker, are not.
+pub fn rvalue_source<'a, 'tcx>(rvalue: &'a Rvalue<'tcx>) -> Option<&'a Place<'tcx>> {
+    match rvalue {
+        Rvalue::Use(Operand::Copy(place) | Operand::Move(place))
+        | Rvalue::Cast(_, Operand::Copy(place) | Operand::Move(place), _)
+        | Rvalue::Ref(_, BorrowKind::Shared | BorrowKind::Mut { .. }, place)
+        | Rvalue::AddressOf(_, place)
+        | Rvalue::CopyForDeref(place) => Some(place),
+        _ => None,
+    }
+}
+
+/// Return true if `place`, used in `block`, is derived from one of `locals`, i.e., its base local
+/// is one of them, e.g., `_3`, `(*_3)` or `(_3.1: u8)`, or it is assigned from such a place through
+/// a chain of the assignments of `rvalue_source`.
+///
+/// The assignments are followed backwards from `block`, through the predecessors returned by
+/// `backward_predecessor`. An assignment to the base local of the place being followed, e.g.,
+/// `_3 = move _2` for `(_3.1: u8)`, continues the chain, so that the fields of temporaries are
+/// followed.
+pub fn is_moved_from<'tcx>(
+    tcx: TyCtxt<'_>,
+    body: &Body<'tcx>,
+    block: BasicBlock,
+    place: &Place<'tcx>,
+    locals: &[Local],
+) -> bool {
+    is_moved_from_through(body, block, place, locals, |block| {
+        backward_predecessor(tcx, body, block)
+    })
+}
+
+/// `is_moved_from`, following the assignments backwards through the predecessors returned by
+/// `predecessor`, e.g., those cached by `crate::mir_cache::BodyAnalysis`
+pub(crate) fn is_moved_from_through<'tcx>(
+    body: &Body<'tcx>,
+    block: BasicBlock,
+    place: &Place<'tcx>,
+    locals: &[Local],
+    predecessor: impl Fn(BasicBlock) -> Option<BasicBlock>,
+) -> bool {
+    let mut place = *place;
+    let mut block = block;
+    let mut visited = Vec::new();
+    loop {
+        if locals.contains(&place.local) {
+            return true;
+        }
+        if visited.contains(&block) {
+            return false;
+        }
+        visited.push(block);
+        for stmt in body.basic_blocks[block].statements.iter().rev() {
+            if_chain! {
+                if let StatementKind::Assign(box (assign_place, rvalue)) = &stmt.kind;
+                if assigns_to(assign_place, &place);
+                if let Some(source) = rvalue_source(rvalue);
+                then {
+                    place = *source;
+                    if locals.contains(&place.local) {
+                        return true;
+                    }
+                }
+            }
+        }
+        match predecessor(block) {
+            Some(predecessor) => block = predecessor,
+            None => return false,
+        }
+    }
+}
+
+/// Return the predecessor of `block` through which the assignments to a place used in `block` are
+/// followed backwards, i.e., the first of its [`backward_predecessors`], or None if there is none,
+/// e.g., for the start block.
+pub fn backward_predecessor(
+    tcx: TyCtxt<'_>,
+    body: &Body<'_>,
+    block: BasicBlock,
+) -> Option<BasicBlock> {
+    backward_predecessors(tcx, body, block).first().copied()
+}
+
+/// Return the predecessors of `block` which may lead to `block` without returning first, i.e., the
+/// predecessors through which a value used in `block` may have been assigned.
+///
+/// The following predecessors are skipped:
+/// - the cleanup blocks, which are only run when unwinding
+/// - the `Break` arms of the `?` operator, which assign `ControlFlow::Break(..)` before returning
+///   early. Once `Try::branch` is inlined, e.g., in release builds, they precede the block
+///   switching on the `ControlFlow` along with the `Continue` arms.
+/// - the sources of the back edges of loops, i.e., the predecessors dominated by `block`, so that
+///   a walk does not cycle through a loop and misses the path entering it
+pub fn backward_predecessors(
+    tcx: TyCtxt<'_>,
+    body: &Body<'_>,
+    block: BasicBlock,
+) -> Vec<BasicBlock> {
+    let dominators = body.basic_blocks.dominators();
+    let is_back_edge = |predecessor: BasicBlock| {
+        dominators.is_reachable(predecessor) && dominators.dominates(block, predecessor)
+    };
+    body.basic_blocks.predecessors()[block]
+        .iter()
+        .copied()
+        .filter(|&predecessor| {
+            !body.basic_blocks[predecessor].is_cleanup
+                && !is_break_arm(tcx, body, predecessor)
+                && !is_back_edge(predecessor)
+        })
+        .collect()
+}
+
+/// Return true if `block` assigns a `ControlFlow::Break(..)`, i.e., it is the arm of the `?`
+/// operator returning early, once `Try::branch` is inlined
+fn is_break_arm(tcx: TyCtxt<'_>, body: &Body<'_>, block: BasicBlock) -> bool {
+    let break_variant = match tcx.lang_items().get(LangItem::ControlFlowBreak) {
+        Some(break_variant) => break_variant,
+        None => return false,
+    };
+    body.basic_blocks[block].statements.iter().any(|stmt| {
+        if_chain! {
+            if let StatementKind::Assign(box (_, Rvalue::Aggregate(kind, _))) = &stmt.kind;
+            if let AggregateKind::Adt(def_id, variant_idx, ..) = **kind;
+            then {
+                tcx.adt_def(def_id).variant(variant_idx).def_id == break_variant
+            } else {
+                false
+            }
+        }
+    })
+}
+
+/// Return true if an assignment to `assigned` writes the value of `place`: both are the same
+/// local, possibly dereferenced (`_3` and `(*_3)`), or `assigned` is the base local of `place`,
+/// e.g., `_3` for `(_3.1: u8)`
+pub fn assigns_to<'tcx>(assigned: &Place<'tcx>, place: &Place<'tcx>) -> bool {
+    assigned == place
+        || (assigned.local == place.local
+            && (assigned.projection.is_empty()
+                || (assigned.local_or_deref_local().is_some()
+                    && place.local_or_deref_local().is_some())))
+}
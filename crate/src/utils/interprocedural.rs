@@ -0,0 +1,164 @@
+//! Propagation of checks through the functions of the crate being checked. A lint which looks for
+//! a comparison of a value, e.g., of a program ID with an expected ID, can use [`calls_comparing`]
+//! to also accept a call to a helper, e.g., `validate_program_id(&program_id)?`, which compares it.
+
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::{def::DefKind, def_id::DefId};
+use rustc_lint::LateContext;
+use rustc_middle::{
+    mir::{self, BinOp, Local, Location, Operand, Place, Rvalue, StatementKind, TerminatorKind},
+    ty::TyKind,
+};
+
+use super::dataflow::rvalue_source;
+
+/// How many calls deep the functions called by a function are searched for a comparison
+pub const MAX_DEPTH: usize = 3;
+
+/// Return the locations of the calls, in the body of `def_id`, to functions of the crate which
+/// compare the value of `place`. A function compares the value if it receives the value, or a
+/// reference to it, as an argument, and
+/// - compares it with `==`, `!=`, `<`, `<=`, `>` or `>=`, or
+/// - passes it to a function of the crate which compares it, up to [`MAX_DEPTH`] calls deep
+pub fn calls_comparing<'tcx>(
+    cx: &LateContext<'tcx>,
+    def_id: DefId,
+    place: Place<'tcx>,
+) -> Vec<Location> {
+    let body = cx.tcx.optimized_mir(def_id);
+    let locals = value_locals(body, place.local);
+    let mut visited = FxHashSet::default();
+    visited.insert(def_id);
+    local_calls(cx, body, &locals)
+        .into_iter()
+        .filter(|&(_, callee, param)| compares_param(cx, callee, param, MAX_DEPTH, &mut visited))
+        .map(|(location, _, _)| location)
+        .collect()
+}
+
+/// Return true if the function `def_id` compares the value of its parameter `param`, itself or
+/// through the functions it calls, up to `depth` calls deep
+fn compares_param(
+    cx: &LateContext<'_>,
+    def_id: DefId,
+    param: Local,
+    depth: usize,
+    visited: &mut FxHashSet<DefId>,
+) -> bool {
+    if depth == 0 || !visited.insert(def_id) {
+        return false;
+    }
+    let body = cx.tcx.optimized_mir(def_id);
+    let locals = value_locals(body, param);
+    let compares = compares_locals(cx, body, &locals)
+        || local_calls(cx, body, &locals)
+            .into_iter()
+            .any(|(_, callee, param)| compares_param(cx, callee, param, depth - 1, visited));
+    visited.remove(&def_id);
+    compares
+}
+
+/// Return the locals of `body` holding the value of `local` or a reference to it, i.e., `local` and
+/// the locals assigned from them by copies, moves, casts and borrows (see [`rvalue_source`])
+fn value_locals(body: &mir::Body<'_>, local: Local) -> FxHashSet<Local> {
+    let mut locals = FxHashSet::default();
+    locals.insert(local);
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for stmt in body
+            .basic_blocks
+            .iter()
+            .flat_map(|block_data| &block_data.statements)
+        {
+            if let StatementKind::Assign(box (assign_place, rvalue)) = &stmt.kind {
+                let source = match rvalue_source(rvalue) {
+                    Some(place) => place.local,
+                    None => continue,
+                };
+                if locals.contains(&source) {
+                    changed |= locals.insert(assign_place.local);
+                }
+            }
+        }
+    }
+    locals
+}
+
+/// Return the calls in `body` to functions of the crate which are passed one of `locals`, with the
+/// called function and its parameter receiving the local
+fn local_calls(
+    cx: &LateContext<'_>,
+    body: &mir::Body<'_>,
+    locals: &FxHashSet<Local>,
+) -> Vec<(Location, DefId, Local)> {
+    let mut calls = Vec::new();
+    for (block, block_data) in body.basic_blocks.iter_enumerated() {
+        if_chain! {
+            if let TerminatorKind::Call {
+                func: Operand::Constant(box func),
+                args,
+                ..
+            } = &block_data.terminator().kind;
+            if let TyKind::FnDef(callee, _) = func.const_.ty().kind();
+            if callee.is_local();
+            if matches!(cx.tcx.def_kind(callee), DefKind::Fn | DefKind::AssocFn);
+            if cx.tcx.is_mir_available(callee);
+            then {
+                let location = Location {
+                    block,
+                    statement_index: block_data.statements.len(),
+                };
+                for (i, arg) in args.iter().enumerate() {
+                    if let Operand::Copy(place) | Operand::Move(place) = arg {
+                        if locals.contains(&place.local) {
+                            calls.push((location, *callee, Local::from_usize(i + 1)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    calls
+}
+
+/// Return true if `body` compares one of `locals`, with a binary operation or by calling
+/// `PartialEq` or `PartialOrd` methods
+fn compares_locals(cx: &LateContext<'_>, body: &mir::Body<'_>, locals: &FxHashSet<Local>) -> bool {
+    let is_local = |operand: &Operand<'_>| match operand {
+        Operand::Copy(place) | Operand::Move(place) => locals.contains(&place.local),
+        Operand::Constant(_) => false,
+    };
+    body.basic_blocks.iter().any(|block_data| {
+        block_data.statements.iter().any(|stmt| {
+            matches!(
+                &stmt.kind,
+                StatementKind::Assign(box (
+                    _,
+                    Rvalue::BinaryOp(
+                        BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge,
+                        box (left, right),
+                    ),
+                )) if is_local(left) || is_local(right)
+            )
+        }) || match &block_data.terminator().kind {
+            TerminatorKind::Call {
+                func: Operand::Constant(box func),
+                args,
+                ..
+            } => match func.const_.ty().kind() {
+                TyKind::FnDef(def_id, _) => {
+                    let lang_items = cx.tcx.lang_items();
+                    let trait_def_id = cx.tcx.trait_of_item(*def_id);
+                    trait_def_id.is_some()
+                        && (trait_def_id == lang_items.eq_trait()
+                            || trait_def_id == lang_items.partial_ord_trait())
+                        && args.iter().any(is_local)
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    })
+}
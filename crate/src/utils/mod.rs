@@ -0,0 +1,859 @@
+use anchor_syn::parser::accounts as accounts_parser;
+use anchor_syn::AccountsStruct;
+use clippy_utils::{
+    get_trait_def_id,
+    higher::ForLoop,
+    match_def_path, path_to_local, path_to_local_id,
+    ty::{implements_trait, match_type},
+    SpanlessEq,
+};
+use if_chain::if_chain;
+use rustc_ast::ast::{LitIntType, LitKind};
+use rustc_hir::{
+    def::{DefKind, Res},
+    def_id::{DefId, LocalDefId, CRATE_DEF_ID},
+    intravisit::{walk_expr, Visitor},
+    BinOpKind, Expr, ExprKind, FieldDef, HirId, Item, ItemKind, MatchSource, Node, Pat, StmtKind,
+    UnOp,
+};
+use rustc_lint::LateContext;
+use rustc_middle::{
+    mir,
+    ty::{self, GenericArgKind, Ty, VariantDef},
+};
+use rustc_span::sym;
+use std::path::PathBuf;
+use syn::{parse_str, ItemStruct};
+
+use crate::paths::{self, anchor_wrapper, AnchorWrapper};
+
+pub mod anchor_constraints;
+
+pub mod borsh_layout;
+
+pub mod cpi;
+
+pub mod dataflow;
+
+pub mod interprocedural;
+
+pub mod owners;
+
+pub mod signers;
+
+pub trait Conclusive: Default {
+    fn concluded(&self) -> bool;
+}
+
+impl<T> Conclusive for Option<T> {
+    fn concluded(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl Conclusive for bool {
+    fn concluded(&self) -> bool {
+        *self
+    }
+}
+
+pub fn visit_expr_no_bodies<'tcx, T>(
+    expr: &'tcx Expr<'tcx>,
+    f: impl FnMut(&'tcx Expr<'tcx>) -> T,
+) -> T
+where
+    T: Conclusive,
+{
+    let mut v = V {
+        f,
+        result: T::default(),
+    };
+    v.visit_expr(expr);
+    v.result
+}
+
+struct V<F, T> {
+    f: F,
+    result: T,
+}
+
+impl<'tcx, F, T> Visitor<'tcx> for V<F, T>
+where
+    F: FnMut(&'tcx Expr<'tcx>) -> T,
+    T: Conclusive,
+{
+    fn visit_expr(&mut self, expr: &'tcx Expr<'tcx>) {
+        if !self.result.concluded() {
+            self.result = (self.f)(expr);
+
+            if !self.result.concluded() {
+                walk_expr(self, expr);
+            }
+        }
+    }
+}
+
+/// Return `Some(accounts_struct)` if the item is an Anchor Accounts struct derived using `#[derive(Accounts)]` macro else None
+/// - If Item is a Struct and implements `anchor_lang::ToAccountInfos` trait.
+///     - Get the pre-expansion source code and parse it using anchor's accounts parser
+///     - If parsing succeeds then
+///         - Return `Some(anchor_syn::AccountsStruct)`
+///     - Else return None
+/// - Else return None
+pub fn get_anchor_accounts_struct<'tcx>(
+    cx: &LateContext<'tcx>,
+    item: &'tcx Item<'tcx>,
+) -> Option<AccountsStruct> {
+    // identify Anchor structs:
+    // `#[derive(Accounts)]` macro implements `anchor_lang::ToAccountInfos` trait for the struct
+    if_chain! {
+        // Anchor generated IDL structs also implement these traits. Check if the item is defined in this crate.
+        if !item.span.from_expansion();
+        if let ItemKind::Struct(_, _) = item.kind;
+        // Accounts structs implement `anchor_lang::ToAccountInfos` trait.
+        // see: https://github.com/coral-xyz/anchor/blob/8eee184938f71e3b85414d469db55fd882b380b2/lang/syn/src/codegen/accounts/mod.rs#L19
+        if let Some(trait_id) = get_trait_def_id(cx, &paths::ANCHOR_LANG_TO_ACCOUNT_INFOS_TRAIT);
+        // `implements_trait` takes generic arguments. providing empty args or dummy args works fine outside tests but
+        // fails in tests because of a debug assertion. ToAccountInfos is used instead of Accounts trait because Accounts trait
+        // takes a type generic argument. It is not possible to find the generic args in `check_item`, have to use `check_impl` probably.
+        // Assumption: Accounts structs have a lifetime argument and it should be same for the trait implementations as well.
+        if let ty::Adt(_, substs) = cx
+            .tcx
+            .type_of(item.owner_id.to_def_id())
+            .skip_binder()
+            .kind();
+        if let Some(lifetime_arg) = substs
+            .iter()
+            .find(|arg| matches!(arg.unpack(), GenericArgKind::Lifetime(_)));
+        if implements_trait(
+            cx,
+            cx.tcx.type_of(item.owner_id.to_def_id()).skip_binder(),
+            trait_id,
+            &[lifetime_arg],
+        );
+        // Get the pre-expansion source code of the struct and parse it using anchor's parser.
+        if let Ok(struct_str) = cx.tcx.sess.source_map().span_to_snippet(item.span);
+        if let Ok(syn_struct) = parse_str::<ItemStruct>(&struct_str);
+        if let Ok(accounts_struct) = accounts_parser::parse(&syn_struct);
+        then {
+            Some(accounts_struct)
+        } else {
+            None
+        }
+    }
+}
+
+/// Return the def id of `T` if the function takes a `Context<T>` argument
+pub fn context_accounts_struct(cx: &LateContext<'_>, local_def_id: LocalDefId) -> Option<DefId> {
+    let fn_sig = cx
+        .tcx
+        .fn_sig(local_def_id.to_def_id())
+        .skip_binder()
+        .skip_binder();
+    if_chain! {
+        if let Some(context_ty) = fn_sig
+            .inputs()
+            .iter()
+            .find(|ty| match_type(cx, **ty, &paths::ANCHOR_LANG_CONTEXT));
+        if let ty::Adt(_, args) = context_ty.kind();
+        if let Some(accounts_ty) = args.types().next();
+        if let ty::Adt(accounts_adt_def, _) = accounts_ty.kind();
+        then {
+            Some(accounts_adt_def.did())
+        } else {
+            None
+        }
+    }
+}
+
+/// If `ty` is `Account<'info, T>` or `AccountLoader<'info, T>`, or a `Box` of them, return the def
+/// id of `T`
+pub fn account_data_type<'tcx>(cx: &LateContext<'tcx>, mut ty: Ty<'tcx>) -> Option<DefId> {
+    if ty.is_box() {
+        ty = ty.boxed_ty();
+    }
+    if_chain! {
+        if matches!(
+            anchor_wrapper(cx, ty),
+            Some(AnchorWrapper::Account | AnchorWrapper::AccountLoader)
+        );
+        if let ty::Adt(_, args) = ty.kind();
+        if let Some(data_ty) = args.types().next();
+        if let ty::Adt(data_adt_def, _) = data_ty.kind();
+        then {
+            Some(data_adt_def.did())
+        } else {
+            None
+        }
+    }
+}
+
+/// Return true if `ty` is the type of the current program generated by `#[program]`, i.e., a struct
+/// of the module `program` at the root of the crate
+pub fn is_self_program_ty(cx: &LateContext<'_>, ty: Ty<'_>) -> bool {
+    if_chain! {
+        if let ty::Adt(adt_def, _) = ty.kind();
+        if adt_def.did().is_local();
+        if let Some(module) = cx.tcx.opt_parent(adt_def.did());
+        if cx.tcx.opt_parent(module) == Some(CRATE_DEF_ID.to_def_id());
+        then {
+            cx.tcx
+                .opt_item_name(module)
+                .map_or(false, |name| name.as_str() == "program")
+        } else {
+            false
+        }
+    }
+}
+
+/// Return true if `ty` is `Program<'info, T>`, where `T` is the type of the current program (see
+/// [`is_self_program_ty`])
+pub fn is_self_program_account_ty(cx: &LateContext<'_>, ty: Ty<'_>) -> bool {
+    if_chain! {
+        if let Some(AnchorWrapper::Program) = anchor_wrapper(cx, ty);
+        if let ty::Adt(_, args) = ty.kind();
+        if let Some(program_ty) = args.types().next();
+        then {
+            is_self_program_ty(cx, program_ty)
+        } else {
+            false
+        }
+    }
+}
+
+/// Return the directory of the package being checked, i.e., the directory of the `src` directory
+/// holding the root of the crate, or the directory of the root if it is not in a `src` directory
+pub fn package_dir(cx: &LateContext<'_>) -> Option<PathBuf> {
+    let root = cx.tcx.sess.local_crate_source_file()?;
+    let mut dir = root.parent()?;
+    if dir.file_name().map_or(false, |name| name == "src") {
+        dir = dir.parent()?;
+    }
+    Some(dir.to_path_buf())
+}
+
+/// Return true if the current program is an anchor program
+///
+/// Anchor generated programs will have
+/// mod instruction {}
+/// mod program {}
+/// mod __private { mod __global {} mod __idl}
+/// fn dispatch
+/// fn entry
+///
+/// `is_anchor_program` uses the presence of `mod __private { mod __global {} mod __idl}` to determine if
+/// the Solana program is written using Anchor
+/// - If the root module has `__private` module and it contains `__global`, `__idl` modules
+///   - return True
+/// - else false
+pub fn is_anchor_program(cx: &LateContext<'_>) -> bool {
+    let hir_map = cx.tcx.hir();
+    hir_map
+        .root_module()
+        .item_ids
+        .iter()
+        .map(|item_id| hir_map.item(*item_id))
+        .any(|item| {
+            if_chain! {
+                if let ItemKind::Mod(private_mod) = item.kind;
+                if item.ident.as_str() == "__private";
+                if private_mod
+                    .item_ids
+                    .iter()
+                    .map(|inner_item_id| hir_map.item(*inner_item_id))
+                    .any(|inner_item| {
+                        matches!(inner_item.kind, ItemKind::Mod(_))
+                            && inner_item.ident.as_str() == "__global"
+                    });
+                if private_mod
+                    .item_ids
+                    .iter()
+                    .map(|inner_item_id| hir_map.item(*inner_item_id))
+                    .any(|inner_item| {
+                        matches!(inner_item.kind, ItemKind::Mod(_))
+                            && inner_item.ident.as_str() == "__idl"
+                    });
+                then {
+                    true
+                } else {
+                    false
+                }
+            }
+        })
+}
+
+/// Return the function which the `entrypoint` function of the crate, e.g., defined by
+/// `solana_program::entrypoint!(process_instruction)`, passes the instruction to, if it is defined
+/// in the crate
+///
+/// - Find a function named `entrypoint`
+/// - Return the first function or associated function of the crate called in its body with three
+///   arguments, i.e., the program id, the accounts and the instruction data
+pub fn entrypoint_process_instruction(cx: &LateContext<'_>) -> Option<LocalDefId> {
+    let hir_map = cx.tcx.hir();
+    hir_map.items().find_map(|item_id| {
+        let item = hir_map.item(item_id);
+        if_chain! {
+            if let ItemKind::Fn(_, _, body_id) = item.kind;
+            if item.ident.as_str() == "entrypoint";
+            then {
+                let typeck_results = cx.tcx.typeck_body(body_id);
+                visit_expr_no_bodies(hir_map.body(body_id).value, |expr| {
+                    if_chain! {
+                        if let ExprKind::Call(callee, [_, _, _]) = expr.kind;
+                        if let ExprKind::Path(qpath) = &callee.kind;
+                        if let Res::Def(DefKind::Fn | DefKind::AssocFn, def_id) =
+                            typeck_results.qpath_res(qpath, callee.hir_id);
+                        then {
+                            def_id.as_local()
+                        } else {
+                            None
+                        }
+                    }
+                })
+            } else {
+                None
+            }
+        }
+    })
+}
+
+/// if `expr` is a method call of `def_path` return the receiver else None
+pub fn is_expr_method_call<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &Expr<'tcx>,
+    def_path: &[&str],
+) -> Option<&'tcx Expr<'tcx>> {
+    if_chain! {
+        if let ExprKind::MethodCall(_, recv, _, _) = expr.kind;
+        if let Some(def_id) = cx.typeck_results().type_dependent_def_id(expr.hir_id);
+        if match_def_path(cx, def_id, def_path);
+        then {
+            Some(recv)
+        } else {
+            None
+        }
+    }
+}
+
+/// Return true if `expr` is a comparison which checks the key of the account `account_expr`
+/// - `expr` is a comparison expression (`==` or `!=`) and either side accesses the key of `account_expr`:
+///   `{account_expr}.key()` (Anchor) or `{account_expr}.key` (Solana `AccountInfo`), possibly dereferenced or
+///   borrowed
+/// - The other side is the key of another account or a `Pubkey`, e.g., `*account.key == *other.key` or
+///   `account.key() != expected`
+///
+/// Comparisons of the `AccountInfo`s themselves, e.g., `std::ptr::eq(account, other)` (see
+/// `account_info_comparison`), do not compare their keys and are not key checks.
+pub fn compares_key<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    account_expr: &Expr<'tcx>,
+) -> bool {
+    if_chain! {
+        // check if the expr is comparison expression
+        if let ExprKind::Binary(op, lhs, rhs) = expr.kind;
+        // == or !=
+        if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne);
+        let lhs = peel_derefs_and_borrows(lhs);
+        let rhs = peel_derefs_and_borrows(rhs);
+        // check if lhs or rhs accesses key of `account_expr`
+        then {
+            accesses_key(cx, lhs, account_expr) || accesses_key(cx, rhs, account_expr)
+        } else {
+            false
+        }
+    }
+}
+
+/// Return true if `expr` compares the keys of the accounts `a` and `b` with each other: `expr` is a
+/// comparison expression (`==` or `!=`) of the key of `a` with the key of `b`, e.g.,
+/// `a.key() != b.key()` or `*a.key == *b.key`. As for `compares_key`, comparisons of the
+/// `AccountInfo`s themselves are not key checks.
+pub fn compares_keys_of<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    a: &Expr<'tcx>,
+    b: &Expr<'tcx>,
+) -> bool {
+    if_chain! {
+        if let ExprKind::Binary(op, lhs, rhs) = expr.kind;
+        if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne);
+        let lhs = peel_derefs_and_borrows(lhs);
+        let rhs = peel_derefs_and_borrows(rhs);
+        if (accesses_key(cx, lhs, a) && accesses_key(cx, rhs, b))
+            || (accesses_key(cx, lhs, b) && accesses_key(cx, rhs, a));
+        then {
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Remove the `*` and `&` around the expression: `*x.key` => `x.key`
+pub fn peel_derefs_and_borrows<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    while let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = expr.kind {
+        expr = inner;
+    }
+    expr
+}
+
+/// Return true if `expr` accesses the key of `account_expr`: `{account_expr}.key()` or `{account_expr}.key`
+pub fn accesses_key<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &Expr<'tcx>,
+    account_expr: &Expr<'tcx>,
+) -> bool {
+    let mut spanless_eq = SpanlessEq::new(cx);
+    // Anchor AccountInfo: `.key()` and Solana AccountInfo: `.key` field.
+    if let Some(recv) = is_expr_method_call(cx, expr, &paths::ANCHOR_LANG_KEY) {
+        return spanless_eq.eq_expr(account_expr, recv);
+    }
+    if_chain! {
+        if let ExprKind::Field(object, field_name) = expr.kind;
+        if field_name.as_str() == "key";
+        then {
+            spanless_eq.eq_expr(account_expr, object)
+        } else {
+            false
+        }
+    }
+}
+
+/// If `expr` compares two `AccountInfo`s directly instead of comparing their keys, return the two
+/// `AccountInfo` expressions.
+/// - `std::ptr::eq(a, b)` where `a` and `b` are `AccountInfo` references. This compares the addresses of the
+///   `AccountInfo` structs, not the accounts.
+/// - `a.lamports == b.lamports` or `a.data == b.data` (or `!=`) where `a` and `b` are `AccountInfo`s. This compares
+///   the balances or the contents of the accounts, not the accounts.
+pub fn account_info_comparison<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<(&'tcx Expr<'tcx>, &'tcx Expr<'tcx>)> {
+    match expr.kind {
+        ExprKind::Call(func, [lhs, rhs]) => {
+            if_chain! {
+                if let ExprKind::Path(qpath) = &func.kind;
+                if let Some(def_id) = cx.qpath_res(qpath, func.hir_id).opt_def_id();
+                if match_def_path(cx, def_id, &paths::CORE_PTR_EQ);
+                let lhs = peel_addr_of(lhs);
+                let rhs = peel_addr_of(rhs);
+                if is_account_info(cx, lhs) && is_account_info(cx, rhs);
+                then {
+                    Some((lhs, rhs))
+                } else {
+                    None
+                }
+            }
+        }
+        ExprKind::Binary(op, lhs, rhs) => {
+            if_chain! {
+                if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne);
+                if let ExprKind::Field(lhs_object, lhs_field) = lhs.kind;
+                if let ExprKind::Field(rhs_object, rhs_field) = rhs.kind;
+                if lhs_field == rhs_field;
+                if matches!(lhs_field.as_str(), "lamports" | "data");
+                if is_account_info(cx, lhs_object) && is_account_info(cx, rhs_object);
+                then {
+                    Some((lhs_object, rhs_object))
+                } else {
+                    None
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Return true if the type of `expr` (ignoring references) is Solana's `AccountInfo`
+fn is_account_info<'tcx>(cx: &LateContext<'tcx>, expr: &Expr<'tcx>) -> bool {
+    let ty = cx.typeck_results().expr_ty(expr).peel_refs();
+    paths::is_account_info_ty(cx, ty)
+}
+
+/// Remove the `&` around the expression: `&x` => `x`
+pub fn peel_addr_of<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    while let ExprKind::AddrOf(_, _, inner) = expr.kind {
+        expr = inner;
+    }
+    expr
+}
+
+/// Return the `for` loops over `AccountInfo`s in `expr`, e.g., `for account in ctx.remaining_accounts`.
+/// For each loop, return the bindings of the loop pattern which are `AccountInfo`s (or references to them)
+/// along with the iterated expression and the body of the loop.
+/// - `for account in accounts` => `account`
+/// - `for (i, account) in accounts.iter().enumerate()` => `account`
+///
+/// Each iteration of such a loop accesses a different account. The checks on the accounts of the loop are
+/// the checks applied to the bindings within the body of the loop.
+pub fn account_info_loops<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Vec<(HirId, &'tcx Expr<'tcx>, &'tcx Expr<'tcx>)> {
+    let mut loops = Vec::new();
+    visit_expr_no_bodies(expr, |expr| {
+        if let Some(for_loop) = ForLoop::hir(expr) {
+            for_loop.pat.each_binding(|_, hir_id, _, _| {
+                let ty = cx.typeck_results().node_type(hir_id).peel_refs();
+                if paths::is_account_info_ty(cx, ty) {
+                    loops.push((hir_id, for_loop.arg, for_loop.body));
+                }
+            });
+        }
+        false
+    });
+    loops
+}
+
+/// Return the local variables in `expr` which are bound to an `AccountInfo` (or a reference to one)
+/// taken from `remaining_accounts` by a function or a method:
+/// - `let account = ctx.remaining_accounts.get(0).unwrap();` => `account`
+/// - `if let Some(account) = ctx.remaining_accounts.first()` => `account`
+/// - `let account = next_account_info(&mut ctx.remaining_accounts.iter())?;` => `account`
+///
+/// The bindings of `for` loops (see `account_info_loops`) and of the accounts themselves, e.g.,
+/// `let account = &ctx.remaining_accounts[0];`, are not returned.
+pub fn remaining_account_bindings<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Vec<HirId> {
+    let mut bindings = Vec::new();
+    let mut push_bindings = |pat: &Pat<'_>, init: &'tcx Expr<'tcx>| {
+        let init_ty = cx.typeck_results().expr_ty(peel_addr_of(init));
+        if paths::is_account_info_ty(cx, init_ty) || !uses_remaining_accounts(cx, init) {
+            return;
+        }
+        pat.each_binding(|_, hir_id, _, _| {
+            let ty = cx.typeck_results().node_type(hir_id).peel_refs();
+            if paths::is_account_info_ty(cx, ty) {
+                bindings.push(hir_id);
+            }
+        });
+    };
+    visit_expr_no_bodies(expr, |expr| {
+        match expr.kind {
+            ExprKind::Block(block, _) => {
+                for stmt in block.stmts {
+                    if_chain! {
+                        if let StmtKind::Local(local) = stmt.kind;
+                        if let Some(init) = local.init;
+                        then {
+                            push_bindings(local.pat, init);
+                        }
+                    }
+                }
+            }
+            ExprKind::Let(let_expr) => push_bindings(let_expr.pat, let_expr.init),
+            _ => {}
+        }
+        false
+    });
+    bindings
+}
+
+/// Return true if `expr` is the `remaining_accounts` of an Anchor `Context`, e.g., `ctx.remaining_accounts`
+pub fn is_remaining_accounts<'tcx>(cx: &LateContext<'tcx>, expr: &Expr<'tcx>) -> bool {
+    if_chain! {
+        if let ExprKind::Field(object, field_name) = expr.kind;
+        if field_name.as_str() == "remaining_accounts";
+        let object_ty = cx.typeck_results().expr_ty_adjusted(object).peel_refs();
+        if match_type(cx, object_ty, &paths::ANCHOR_LANG_CONTEXT);
+        then {
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Return true if `remaining_accounts` is accessed in `expr`, e.g., `ctx.remaining_accounts[0]` or
+/// `ctx.remaining_accounts.iter()`
+pub fn uses_remaining_accounts<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> bool {
+    visit_expr_no_bodies(expr, |expr| is_remaining_accounts(cx, expr))
+}
+
+/// Return the first use of the local variable `hir_id` in `expr`
+pub fn first_use_of_local<'tcx>(expr: &'tcx Expr<'tcx>, hir_id: HirId) -> Option<&'tcx Expr<'tcx>> {
+    visit_expr_no_bodies(expr, |expr| {
+        if path_to_local_id(expr, hir_id) {
+            Some(expr)
+        } else {
+            None
+        }
+    })
+}
+
+/// If `expr` is a field of a local struct, e.g., `ctx.accounts.config`, or a local variable
+/// initialized with a reference to one, e.g., `config` in `let config = &mut ctx.accounts.config`,
+/// return the struct and the name of the field
+pub fn local_struct_field<'tcx>(
+    cx: &LateContext<'tcx>,
+    mut expr: &'tcx Expr<'tcx>,
+) -> Option<(DefId, String)> {
+    loop {
+        while let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = expr.kind {
+            expr = inner;
+        }
+        match path_to_local(expr).and_then(|local| reference_init(cx, local)) {
+            Some(init) => expr = init,
+            None => break,
+        }
+    }
+    if_chain! {
+        if let ExprKind::Field(object, ident) = expr.kind;
+        let object_ty = cx.typeck_results().expr_ty_adjusted(object).peel_refs();
+        if let ty::Adt(adt_def, _) = object_ty.kind();
+        if adt_def.did().is_local();
+        then {
+            Some((adt_def.did(), ident.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Return the initializer of the local variable `local` if the variable is a reference
+fn reference_init<'tcx>(cx: &LateContext<'tcx>, local: HirId) -> Option<&'tcx Expr<'tcx>> {
+    if !cx.typeck_results().node_type(local).is_ref() {
+        return None;
+    }
+    match cx.tcx.hir().parent_iter(local).next() {
+        Some((_, Node::Local(local))) => local.init,
+        _ => None,
+    }
+}
+
+/// Return the definition of the field `field_name` of the local struct `def_id`
+pub fn local_field_def<'tcx>(
+    cx: &LateContext<'tcx>,
+    def_id: DefId,
+    field_name: &str,
+) -> Option<&'tcx FieldDef<'tcx>> {
+    if_chain! {
+        if let Some(local_def_id) = def_id.as_local();
+        if let ItemKind::Struct(variant, _) = cx.tcx.hir().expect_item(local_def_id).kind;
+        then {
+            variant
+                .fields()
+                .iter()
+                .find(|field| field.ident.as_str() == field_name)
+        } else {
+            None
+        }
+    }
+}
+
+/// If `account` is a field of a local struct, e.g., `ctx.accounts.source` in
+/// `ctx.accounts.source.to_account_info()` or `ctx.accounts.source.key`, or a local variable
+/// initialized with one, return the struct and the name of the field
+pub fn account_struct_field<'tcx>(
+    cx: &LateContext<'tcx>,
+    mut account: &'tcx Expr<'tcx>,
+) -> Option<(DefId, String)> {
+    loop {
+        account = match account.kind {
+            ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) => inner,
+            ExprKind::MethodCall(segment, receiver, [], _)
+                if matches!(
+                    segment.ident.as_str(),
+                    "to_account_info" | "clone" | "key" | "as_ref"
+                ) =>
+            {
+                receiver
+            }
+            ExprKind::Field(object, ident) if ident.as_str() == "key" => object,
+            ExprKind::Path(_) => {
+                match path_to_local(account).and_then(|local| local_init(cx, local)) {
+                    Some(init) => init,
+                    None => return None,
+                }
+            }
+            _ => break,
+        };
+    }
+    if_chain! {
+        if let ExprKind::Field(object, ident) = account.kind;
+        let object_ty = cx.typeck_results().expr_ty_adjusted(object).peel_refs();
+        if let ty::Adt(adt_def, _) = object_ty.kind();
+        if adt_def.did().is_local();
+        then {
+            Some((adt_def.did(), ident.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Return the initializer of the local variable `local`
+pub fn local_init<'tcx>(cx: &LateContext<'tcx>, local: HirId) -> Option<&'tcx Expr<'tcx>> {
+    match cx.tcx.hir().parent_iter(local).next() {
+        Some((_, Node::Local(local))) => local.init,
+        _ => None,
+    }
+}
+
+/// Return true if `name` is the name of a field holding an authority, i.e., `authority`, `owner`
+/// or `admin`, or a name ending with one of them, e.g., `fee_authority`
+pub fn is_authority_name(name: &str) -> bool {
+    ["authority", "owner", "admin"]
+        .iter()
+        .any(|authority| name == *authority || name.ends_with(&format!("_{authority}")))
+}
+
+/// If `expr` assigns `0` to the lamports of an account, i.e., `**x.lamports.borrow_mut() = 0` or
+/// `**x.try_borrow_mut_lamports()? = 0`, return `x`
+pub fn closed_account<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<&'tcx Expr<'tcx>> {
+    if_chain! {
+        if let ExprKind::Assign(place, value, _) = expr.kind;
+        if let ExprKind::Lit(lit) = value.kind;
+        if let LitKind::Int(0, LitIntType::Unsuffixed) = lit.node;
+        then {
+            lamports_account(cx, place)
+        } else {
+            None
+        }
+    }
+}
+
+/// If `expr` is the lamports of an account, i.e., `**x.lamports.borrow_mut()` or
+/// `**x.try_borrow_mut_lamports()?`, return `x`
+pub fn lamports_account<'tcx>(
+    cx: &LateContext<'tcx>,
+    mut expr: &'tcx Expr<'tcx>,
+) -> Option<&'tcx Expr<'tcx>> {
+    while let ExprKind::Unary(UnOp::Deref, inner) = expr.kind {
+        expr = inner;
+    }
+    // `x?` is desugared into `match Try::branch(x) { .. }`
+    if_chain! {
+        if let ExprKind::Match(scrutinee, _, MatchSource::TryDesugar(_)) = expr.kind;
+        if let ExprKind::Call(_, [inner]) = scrutinee.kind;
+        then {
+            return is_expr_method_call(cx, inner, &paths::SOLANA_PROGRAM_TRY_BORROW_MUT_LAMPORTS);
+        }
+    }
+    if_chain! {
+        if let Some(receiver) = is_expr_method_call(cx, expr, &paths::CORE_CELL_BORROW_MUT);
+        if let ExprKind::Field(account, ident) = receiver.kind;
+        if ident.as_str() == "lamports";
+        let ty = cx.typeck_results().expr_ty_adjusted(account).peel_refs();
+        if paths::is_account_info_ty(cx, ty);
+        then {
+            Some(account)
+        } else {
+            None
+        }
+    }
+}
+
+/// Return `account` and the accounts it is obtained from, following borrows, derefs, calls to
+/// `to_account_info` and the initializers of local `AccountInfo` variables, e.g., from `source` to
+/// `ctx.accounts.source.to_account_info()` and `ctx.accounts.source` for
+/// `let source = ctx.accounts.source.to_account_info()`
+pub fn followed_accounts<'tcx>(
+    cx: &LateContext<'tcx>,
+    mut account: &'tcx Expr<'tcx>,
+) -> Vec<&'tcx Expr<'tcx>> {
+    let mut accounts = Vec::new();
+    loop {
+        while let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = account.kind
+        {
+            account = inner;
+        }
+        accounts.push(account);
+        account = if let Some(receiver) =
+            is_expr_method_call(cx, account, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO)
+        {
+            receiver
+        } else if let Some(init) =
+            path_to_local(account).and_then(|local| account_info_init(cx, local))
+        {
+            init
+        } else {
+            return accounts;
+        };
+    }
+}
+
+/// Return the initializer of the local variable `local` if the variable is an `AccountInfo`, or a
+/// reference to one
+fn account_info_init<'tcx>(cx: &LateContext<'tcx>, local: HirId) -> Option<&'tcx Expr<'tcx>> {
+    let ty = cx.typeck_results().node_type(local).peel_refs();
+    if !paths::is_account_info_ty(cx, ty) {
+        return None;
+    }
+    match cx.tcx.hir().parent_iter(local).next() {
+        Some((_, Node::Local(local))) => local.init,
+        _ => None,
+    }
+}
+
+/// Return the `HirId` whose lint attributes apply to the MIR statement or terminator with `source_info`, i.e., the
+/// innermost node with lint attributes containing it. Reporting a lint at this `HirId`, instead of at the function,
+/// makes `#[allow(..)]` and `#[expect(..)]` attributes on the statements of the function apply to the lint.
+pub fn mir_lint_root(body: &mir::Body<'_>, source_info: mir::SourceInfo) -> HirId {
+    body.source_scopes[source_info.scope]
+        .local_data
+        .as_ref()
+        .assert_crate_local()
+        .lint_root
+}
+
+/// Return the size in bytes of a value of type `ty` serialized with Borsh, or None if the size is not fixed or
+/// cannot be estimated
+/// - integers, floats, `bool` and `char`: their size (`usize` and `isize` are serialized as 64-bit integers)
+/// - arrays and tuples: the sum of the sizes of their elements
+/// - `Pubkey`: 32 bytes
+/// - structs defined in the current crate: the sum of the sizes of their fields
+/// - enums defined in the current crate and `Option`: 1 byte for the variant + the size of the largest variant
+/// - Else, e.g., `Vec`, `String` and `Box`, return None
+pub fn borsh_size<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>) -> Option<u64> {
+    match ty.kind() {
+        ty::Bool => Some(1),
+        ty::Char => Some(4),
+        ty::Int(int_ty) => Some(int_ty.bit_width().unwrap_or(64) / 8),
+        ty::Uint(uint_ty) => Some(uint_ty.bit_width().unwrap_or(64) / 8),
+        ty::Float(float_ty) => Some(float_ty.bit_width() / 8),
+        ty::Array(elem_ty, len) => {
+            let len = len.try_eval_target_usize(cx.tcx, cx.param_env)?;
+            borsh_size(cx, *elem_ty)?.checked_mul(len)
+        }
+        ty::Tuple(tys) => tys.iter().map(|ty| borsh_size(cx, ty)).sum(),
+        ty::Adt(adt_def, args) => {
+            if match_def_path(cx, adt_def.did(), &paths::SOLANA_PROGRAM_PUBKEY) {
+                return Some(32);
+            }
+            // The fields of the other types, e.g., `Vec`, do not reflect their serialized size.
+            if !adt_def.did().is_local() && !cx.tcx.is_diagnostic_item(sym::Option, adt_def.did()) {
+                return None;
+            }
+            let variant_size = |variant: &VariantDef| {
+                variant
+                    .fields
+                    .iter()
+                    .map(|field| borsh_size(cx, field.ty(cx.tcx, args)))
+                    .sum::<Option<u64>>()
+            };
+            if adt_def.is_struct() {
+                variant_size(adt_def.non_enum_variant())
+            } else if adt_def.is_enum() {
+                adt_def
+                    .variants()
+                    .iter()
+                    .map(variant_size)
+                    .collect::<Option<Vec<_>>>()?
+                    .into_iter()
+                    .max()
+                    .map(|size| size + 1)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,116 @@
+//! Detection of owner and key checks. An account is checked if its `owner` is accessed, e.g.,
+//! `account.owner != program_id`, or if its key is compared, e.g., `account.key() == expected`,
+//! including through a local variable holding the key.
+
+use clippy_utils::{path_to_local, SpanlessEq};
+use if_chain::if_chain;
+use rustc_hir::{BinOpKind, Expr, ExprKind, Local, Node, UnOp};
+use rustc_lint::LateContext;
+
+use super::{accesses_key, compares_key, visit_expr_no_bodies};
+
+/// Check if `owner` is accessed on `account_expr` or the key of `account_expr` is compared in `scope`
+pub fn is_owner_checked<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    account_expr: &Expr<'tcx>,
+) -> bool {
+    contains_owner_use(cx, scope, account_expr) || contains_key_check(cx, scope, account_expr)
+}
+
+/// Check if any of the expressions in `scope` is `{account_expr}.owner`
+fn contains_owner_use<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    account_expr: &Expr<'tcx>,
+) -> bool {
+    visit_expr_no_bodies(scope, |expr| {
+        uses_given_field(cx, expr, account_expr, "owner")
+    })
+}
+
+/// Check if the key of account returned by `account_expr` is compared in `scope`, directly or
+/// through a local variable holding the key
+fn contains_key_check<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    account_expr: &Expr<'tcx>,
+) -> bool {
+    visit_expr_no_bodies(scope, |expr| {
+        compares_key(cx, expr, account_expr) || compares_key_local(cx, expr, account_expr)
+    })
+}
+
+/// Return true if `expr` is a comparison (`==` or `!=`) of a local variable holding the key of
+/// `account_expr`, e.g., `key != expected_pda` where `let key = account.key();`. The expected key
+/// is usually derived with `Pubkey::find_program_address` and compared with such a local.
+fn compares_key_local<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    account_expr: &Expr<'tcx>,
+) -> bool {
+    if_chain! {
+        if let ExprKind::Binary(op, lhs, rhs) = expr.kind;
+        if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne);
+        then {
+            [lhs, rhs].iter().any(|operand| {
+                local_init(cx, operand).map_or(false, |init| accesses_key(cx, init, account_expr))
+            })
+        } else {
+            false
+        }
+    }
+}
+
+/// If `expr` is a local variable, return its initializer, following derefs, borrows and the
+/// initializers of other local variables, e.g., `account.key()` for `*key` where
+/// `let key = &account.key();`
+fn local_init<'tcx>(
+    cx: &LateContext<'tcx>,
+    mut expr: &'tcx Expr<'tcx>,
+) -> Option<&'tcx Expr<'tcx>> {
+    let mut init = None;
+    loop {
+        while let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = expr.kind {
+            expr = inner;
+        }
+        let local = match path_to_local(expr) {
+            Some(local) => local,
+            None => return init,
+        };
+        match cx.tcx.hir().parent_iter(local).next() {
+            Some((
+                _,
+                Node::Local(Local {
+                    init: Some(local_init),
+                    ..
+                }),
+            )) => {
+                expr = local_init;
+                init = Some(expr);
+            }
+            _ => return init,
+        }
+    }
+}
+
+/// Checks if `expr` is references `field` on `account_expr`
+pub fn uses_given_field<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &Expr<'tcx>,
+    account_expr: &Expr<'tcx>,
+    field: &str,
+) -> bool {
+    if_chain! {
+        if let ExprKind::Field(object, field_name) = expr.kind;
+        // TODO: add check for key, is_signer
+        if field_name.as_str() == field;
+        let mut spanless_eq = SpanlessEq::new(cx);
+        if spanless_eq.eq_expr(account_expr, object);
+        then {
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Detection of signer checks. A function checks that an account signs the instruction if it takes
+//! a `Context<T>` where `T` has a `Signer` field, or if it reads `x.is_signer` for some
+//! `AccountInfo` `x`.
+
+use clippy_utils::ty::match_type;
+use if_chain::if_chain;
+use rustc_hir::{def_id::LocalDefId, Expr, ExprKind};
+use rustc_lint::LateContext;
+use rustc_middle::ty::{self, GenericArg, GenericArgKind};
+
+use super::visit_expr_no_bodies;
+use crate::paths::{self, anchor_wrapper, AnchorWrapper};
+
+/// Given def id of a function return true if
+/// - function takes a Context<T> type argument and
+/// - T has only one variant (T is a struct) and
+/// - T has a Signer type field
+pub fn context_contains_signer_field(cx: &LateContext<'_>, local_def_id: LocalDefId) -> bool {
+    let fn_sig = cx
+        .tcx
+        .fn_sig(local_def_id.to_def_id())
+        .skip_binder()
+        .skip_binder();
+    if_chain! {
+        // iterate over the arguments and find Context<> type argument
+        if let Some(ty) = fn_sig
+            .inputs()
+            .iter()
+            .find(|ty| match_type(cx, **ty, &paths::ANCHOR_LANG_CONTEXT));
+        if let ty::Adt(_, substs) = ty.kind();
+        // Context takes T as generic arg. iterate over the type arguments and
+        // check any of them is a type arg and has `Signer` type field.
+        if substs.iter().any(|arg| arg_contains_signer_field(cx, arg));
+        then {
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Given a generic type argument, return true if its a struct that contains `Signer` type field.
+fn arg_contains_signer_field<'tcx>(cx: &LateContext<'tcx>, arg: GenericArg<'tcx>) -> bool {
+    if_chain! {
+        // GenericArg is a type argument (not lifetime)
+        if let GenericArgKind::Type(ty) = arg.unpack();
+        if let ty::Adt(adt_def, substs) = ty.kind();
+        if let [variant] = adt_def.variants().iter().collect::<Vec<_>>().as_slice();
+        // iterate over the fields and check if any of the field's type is `Signer`
+        if variant.fields.iter().any(|field_def| {
+            anchor_wrapper(cx, field_def.ty(cx.tcx, substs)) == Some(AnchorWrapper::Signer)
+        });
+        then {
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Return true if any of expressions in `expr` are `x.is_signer` where `x`'s type is `AccountInfo`.
+/// The bodies of closures are searched as well: the accounts iterated over by a loop, e.g.,
+/// `for account in ctx.remaining_accounts`, are often checked using iterator adapters instead,
+/// e.g., `ctx.remaining_accounts.iter().filter(|account| account.is_signer)`.
+pub fn contains_is_signer_use<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> bool {
+    visit_expr_no_bodies(expr, |expr| {
+        if let ExprKind::Closure(closure) = expr.kind {
+            contains_is_signer_use(cx, cx.tcx.hir().body(closure.body).value)
+        } else {
+            is_is_signer_use(cx, expr)
+        }
+    })
+}
+
+/// Return true if the `expr` is `x.is_signer` where `x`'s type is `AccountInfo`.
+fn is_is_signer_use<'tcx>(cx: &LateContext<'tcx>, expr: &Expr<'tcx>) -> bool {
+    if_chain! {
+        // `expr` is `x.{field_name}`
+        if let ExprKind::Field(object, field_name) = expr.kind;
+        if field_name.as_str() == "is_signer";
+        // type of `x` is AccountInfo
+        let ty = cx.typeck_results().expr_ty(object).peel_refs();
+        if paths::is_account_info_ty(cx, ty);
+        then {
+            true
+        } else {
+            false
+        }
+    }
+}
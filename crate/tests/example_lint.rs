@@ -0,0 +1,18 @@
+// The `example_lint` library is a lint written in the way another library would write one, with
+// the public API of `solana_lints` only (see `solana_lints::context`). Building and testing it
+// checks that a change to the API which breaks the libraries depending on it is noticed.
+
+use assert_cmd::prelude::*;
+use std::path::Path;
+
+#[test]
+fn example_lint() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("example_lint");
+
+    std::process::Command::new("cargo")
+        .current_dir(path)
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .args(["test"])
+        .assert()
+        .success();
+}
@@ -0,0 +1,37 @@
+// The purpose of this test is to check that the lints write their machine-applicable suggestions
+// to the patch named by `SOLANA_LINTS_FIXES`. `sysvar_get`'s `insecure` ui test is run with the
+// variable set; its suggestions replacing `from_account_info` calls are machine applicable.
+
+use assert_cmd::prelude::*;
+use std::{fs::read_to_string, path::Path};
+
+#[test]
+fn fixes() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let patch_path = tempdir.path().join("fixes.patch");
+
+    std::process::Command::new("cargo")
+        .current_dir(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("..")
+                .join("lints")
+                .join("sysvar_get"),
+        )
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .env("SOLANA_LINTS_FIXES", &patch_path)
+        .args(["test", "--", "--exact", "insecure"])
+        .assert()
+        .success();
+
+    let patch = read_to_string(&patch_path).unwrap();
+    assert!(patch.starts_with("--- a/"));
+    assert!(
+        patch.contains("\n-        let _clock = Clock::from_account_info(&ctx.accounts.clock);\n")
+    );
+    assert!(patch.contains("\n+        let _clock = Clock::get();\n"));
+    assert!(patch.contains("\n+        let _rent = Rent::get();\n"));
+    let added_lines = patch
+        .lines()
+        .filter(|line| line.starts_with('+') && !line.starts_with("+++"));
+    assert_eq!(added_lines.count(), 5);
+}
@@ -1,37 +1,39 @@
 use assert_cmd::prelude::*;
-use std::{
-    fs::read_dir,
-    io::{stderr, Write},
-    path::Path,
-};
+use std::path::Path;
 
+// The lint libraries' tests are run by `cargo xtask test`, which builds them in a shared
+// target directory and runs their ui tests in parallel. See `xtask/src/main.rs`.
 #[test]
 fn lints() {
-    let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
-        .join("..")
-        .join("lints");
-
-    for (index, entry) in read_dir(dir).unwrap().enumerate() {
-        let entry = entry.unwrap();
-        let path = entry.path();
-
-        #[allow(clippy::explicit_write)]
-        writeln!(stderr(), "{:?}", path.canonicalize().unwrap()).unwrap();
+    std::process::Command::new("cargo")
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(".."))
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .args(["xtask", "test"])
+        .assert()
+        .success();
+}
 
-        std::process::Command::new("cargo")
-            .current_dir(path.clone())
-            .env_remove("RUSTUP_TOOLCHAIN")
-            .env("CARGO_TARGET_DIR", format!("target_{index}"))
-            .args(["test"])
-            .assert()
-            .success();
+// The libraries are loaded at once to check a fixture, as they are by `cargo dylint --all`. See
+// `xtask/src/main.rs`.
+#[test]
+fn e2e() {
+    std::process::Command::new("cargo")
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(".."))
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .args(["xtask", "e2e"])
+        .assert()
+        .success();
+}
 
-        std::process::Command::new("cargo")
-            .current_dir(path)
-            .env_remove("RUSTUP_TOOLCHAIN")
-            .env("CARGO_TARGET_DIR", format!("target_{index}"))
-            .args(["clean"])
-            .assert()
-            .success();
-    }
+// Random programs are checked with some of the libraries before and after a mutation, i.e., adding
+// a constraint or removing a check, which must not introduce or remove findings, respectively. See
+// `xtask/src/metamorphic.rs`.
+#[test]
+fn metamorphic() {
+    std::process::Command::new("cargo")
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(".."))
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .args(["xtask", "metamorphic"])
+        .assert()
+        .success();
 }
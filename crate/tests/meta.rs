@@ -29,6 +29,16 @@ fn one_toolchain_is_used_throughout() {
 
         assert_eq!(channel, toolchain_channel(&path));
     }
+
+    assert_eq!(
+        channel,
+        toolchain_channel(&root.join("crate").join("paths_probe"))
+    );
+
+    assert_eq!(
+        channel,
+        toolchain_channel(&root.join("crate").join("example_lint"))
+    );
 }
 
 fn toolchain_channel(path: &Path) -> String {
@@ -95,3 +105,56 @@ second package: {path_curr:?}"
         }
     }
 }
+
+#[test]
+fn every_lint_is_in_a_group() {
+    for entry in read_dir("../lints").unwrap() {
+        let entry = entry.unwrap();
+        let path = entry.path();
+        let name = path.file_name().and_then(OsStr::to_str).unwrap();
+
+        let group = solana_lints::groups::group_of(name)
+            .unwrap_or_else(|| panic!("`{name}` is not in `solana_lints::groups::LINT_GROUPS`"));
+
+        let lib_rs = read_to_string(path.join("src").join("lib.rs")).unwrap();
+        assert!(
+            lib_rs.contains(&format!("    /// `{}`, e.g., ", group.name())),
+            "the documentation of `{name}` does not name its group, `{}`",
+            group.name()
+        );
+    }
+}
+
+#[test]
+fn renamed_lints_are_listed_in_the_readme() {
+    let readme = read_to_string("../README.md").unwrap();
+
+    for &(old_name, new_name) in solana_lints::renamed::RENAMED_LINTS {
+        assert!(
+            Path::new("../lints").join(new_name).is_dir(),
+            "`{old_name}` is renamed to `{new_name}`, which is not a lint"
+        );
+        assert!(
+            !Path::new("../lints").join(old_name).exists(),
+            "`{old_name}` is both the old name of `{new_name}` and the name of a lint"
+        );
+        assert!(
+            readme.contains(&format!("- `{old_name}`: `{new_name}`\n")),
+            "the README does not list `{old_name}` as an old name of `{new_name}`"
+        );
+    }
+}
+
+#[test]
+fn references_are_listed_in_the_readmes() {
+    for reference in solana_lints::references::REFERENCES {
+        let readme = read_to_string(Path::new("../lints").join(reference.lint).join("README.md"))
+            .unwrap_or_else(|_| panic!("`{}` is not a lint", reference.lint));
+        assert!(
+            readme.contains(&format!("- [{}]({})", reference.title, reference.url)),
+            "the README of `{}` does not list `{}`; run `scripts/update_readmes.sh`",
+            reference.lint,
+            reference.url
+        );
+    }
+}
@@ -0,0 +1,20 @@
+// The purpose of this test is to check that the helpers in `solana_lints::utils::dataflow` follow
+// the MIR the supported toolchain generates, e.g., its `deref_copy`s, fields of temporaries and
+// two-phase borrows. A form of assignment which the helpers do not follow silently breaks the
+// chains of aliases that the lints trace.
+//   The check is performed by the `mir_probe` library, which is run on a small crate.
+
+use assert_cmd::prelude::*;
+use std::path::Path;
+
+#[test]
+fn mir_forms_are_followed() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("mir_probe");
+
+    std::process::Command::new("cargo")
+        .current_dir(path)
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .args(["test"])
+        .assert()
+        .success();
+}
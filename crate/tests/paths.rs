@@ -0,0 +1,19 @@
+// The purpose of this test is to check that every path in `solana_lints::paths` resolves against
+// the supported versions of the Solana and Anchor crates. A path that does not resolve causes
+// `match_def_path` to return false, and the lints that use the path to silently stop firing.
+//   The check is performed by the `paths_probe` library, which is run on a small Anchor program.
+
+use assert_cmd::prelude::*;
+use std::path::Path;
+
+#[test]
+fn paths_resolve() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("paths_probe");
+
+    std::process::Command::new("cargo")
+        .current_dir(path)
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .args(["test"])
+        .assert()
+        .success();
+}
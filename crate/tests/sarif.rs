@@ -0,0 +1,79 @@
+// The purpose of these tests is to check that the lints write their diagnostics to the file named
+// by `SOLANA_LINTS_SARIF`. One lint's `insecure` ui test is run with the variable set.
+
+use assert_cmd::prelude::*;
+use serde_json::Value;
+use std::{fs::read_to_string, path::Path};
+
+#[test]
+fn sarif() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let sarif_path = tempdir.path().join("results.sarif");
+
+    std::process::Command::new("cargo")
+        .current_dir(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("..")
+                .join("lints")
+                .join("missing_owner_check"),
+        )
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .env("SOLANA_LINTS_SARIF", &sarif_path)
+        .args(["test", "--", "--exact", "insecure"])
+        .assert()
+        .success();
+
+    let log: Value = serde_json::from_str(&read_to_string(&sarif_path).unwrap()).unwrap();
+    let run = &log["runs"][0];
+
+    assert_eq!(
+        run["tool"]["driver"]["rules"][0]["id"],
+        "missing_owner_check"
+    );
+    assert_eq!(
+        run["tool"]["driver"]["rules"][0]["helpUri"],
+        "https://github.com/coral-xyz/sealevel-attacks/tree/master/programs/2-owner-checks"
+    );
+    assert_eq!(
+        run["tool"]["driver"]["rules"][0]["properties"]["tags"][0],
+        "solana_security_critical"
+    );
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "missing_owner_check");
+    assert_eq!(results[0]["level"], "error");
+    assert_eq!(results[0]["properties"]["confidence"], "high");
+    let region = &results[0]["locations"][0]["physicalLocation"]["region"];
+    assert_eq!(region["startLine"], 25);
+    assert_eq!(region["startColumn"], 5);
+}
+
+// The findings of `arbitrary_cpi` on `Instruction`s have medium confidence, so they are still
+// emitted, and tagged as such, when `min_confidence` is `medium`.
+#[test]
+fn sarif_confidence() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let sarif_path = tempdir.path().join("results.sarif");
+
+    std::process::Command::new("cargo")
+        .current_dir(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("..")
+                .join("lints")
+                .join("arbitrary_cpi"),
+        )
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .env("SOLANA_LINTS_SARIF", &sarif_path)
+        .env("DYLINT_TOML", "[solana_lints]\nmin_confidence = \"medium\"")
+        .args(["test", "--", "--exact", "insecure_3"])
+        .assert()
+        .success();
+
+    let log: Value = serde_json::from_str(&read_to_string(&sarif_path).unwrap()).unwrap();
+    let results = log["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results
+        .iter()
+        .all(|result| result["properties"]["confidence"] == "medium"));
+}
@@ -0,0 +1,317 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{
+    fn_def_id, match_any_def_paths, match_def_path, path_to_local_id, ty::match_type, SpanlessEq,
+};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::LocalDefId, intravisit::FnKind, Body, Expr, ExprKind, FnDecl, HirId, Local, Node,
+    PatKind, StmtKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, paths, sarif,
+    utils::visit_expr_no_bodies,
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports a borrow of the data of an account, e.g., `account.try_borrow_mut_data()` or
+    /// `account.data.borrow()`, while a conflicting borrow of the data of the same account is alive
+    /// in the function, i.e., when one of the two borrows is mutable.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The data of an `AccountInfo` is stored in a `RefCell`, so the borrows are checked at
+    /// runtime. `borrow` and `borrow_mut` panic on a conflicting borrow, and `try_borrow_data` and
+    /// `try_borrow_mut_data` return an error. Either way the instruction fails, on every input
+    /// which reaches the second borrow. A borrow bound to a local variable, e.g.,
+    /// `let data = account.try_borrow_mut_data()?;`, is alive until the end of the block, even if
+    /// the variable is not used anymore.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Two borrows are of the same account if the expressions of the accounts are the same, e.g.,
+    /// `ctx.accounts.vault`. Two `AccountInfo`s of the same account, e.g., an `AccountInfo` and its
+    /// clone, are not recognized as the same account.
+    ///
+    /// The lifetimes of the borrows are approximated: a borrow bound to a local variable is alive
+    /// until the end of the enclosing block, or until the variable is passed to `drop`; any other
+    /// borrow is alive until the end of the enclosing statement, `if` condition, `if` or loop body,
+    /// or `match` arm. Borrows which are moved, e.g., returned from the function, are not followed.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// let data = ctx.accounts.vault.try_borrow_data()?;
+    /// let amount = u64::from_le_bytes(data[..8].try_into().unwrap());
+    /// let mut data = ctx.accounts.vault.try_borrow_mut_data()?;
+    /// data[..8].copy_from_slice(&(amount + 1).to_le_bytes());
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// let amount = {
+    ///     let data = ctx.accounts.vault.try_borrow_data()?;
+    ///     u64::from_le_bytes(data[..8].try_into().unwrap())
+    /// };
+    /// let mut data = ctx.accounts.vault.try_borrow_mut_data()?;
+    /// data[..8].copy_from_slice(&(amount + 1).to_le_bytes());
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - collect the borrows of the data of an account:
+    ///   - `x.try_borrow_data()` and `x.try_borrow_mut_data()` on an `AccountInfo` `x`
+    ///   - `x.data.borrow()`, `x.data.borrow_mut()`, `x.data.try_borrow()` and
+    ///     `x.data.try_borrow_mut()` on an `AccountInfo` `x`
+    /// - for each borrow, compute the span in which it is alive:
+    ///   - if the borrow initializes a local variable of type `Ref` or `RefMut`, from the `let`
+    ///     statement to the end of the enclosing block, or to the first `drop` of the variable
+    ///   - else, the span of the enclosing statement, `if` condition, `if` or loop body, or `match`
+    ///     arm
+    /// - for each borrow `b`, find a borrow `a` before it such that
+    ///   - the accounts of `a` and `b` are the same, using `SpanlessEq`
+    ///   - `a` or `b` is mutable
+    ///   - `b` is in the span in which `a` is alive
+    /// - if there is such a borrow `a`, report `b` and note `a`
+    pub ACCOUNT_DATA_BORROW_CONFLICT,
+    Warn,
+    "borrows of the data of an account while a conflicting borrow of it is alive"
+}
+
+impl<'tcx> LateLintPass<'tcx> for AccountDataBorrowConflict {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(ACCOUNT_DATA_BORROW_CONFLICT, span) {
+            return;
+        }
+        let borrows = data_borrows(cx, body.value);
+        for (i, borrow) in borrows.iter().enumerate() {
+            let previous = match borrows[..i]
+                .iter()
+                .find(|previous| conflicts(cx, previous, borrow))
+            {
+                Some(previous) => previous,
+                None => continue,
+            };
+            let msg = format!(
+                "the data of this account is borrowed{} while it is already borrowed{}",
+                if borrow.mutable { " mutably" } else { "" },
+                if previous.mutable { " mutably" } else { "" },
+            );
+            span_lint_hir_and_then(
+                cx,
+                ACCOUNT_DATA_BORROW_CONFLICT,
+                borrow.expr.hir_id,
+                borrow.expr.span,
+                &msg,
+                |diag| {
+                    diag.span_note(previous.expr.span, "the previous borrow is here");
+                    diag.help(
+                        "end the previous borrow first, e.g., by moving it into a block or by passing it to `drop`",
+                    );
+                },
+            );
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[ACCOUNT_DATA_BORROW_CONFLICT]);
+    }
+}
+
+/// A borrow of the data of an account
+struct DataBorrow<'tcx> {
+    /// The account, e.g., `ctx.accounts.vault` in `ctx.accounts.vault.try_borrow_data()`
+    account: &'tcx Expr<'tcx>,
+    /// The borrow call
+    expr: &'tcx Expr<'tcx>,
+    mutable: bool,
+    /// The span in which the borrow is alive
+    alive: Span,
+}
+
+/// Return the borrows of the data of an account in `scope`, in the order they are found
+fn data_borrows<'tcx>(cx: &LateContext<'tcx>, scope: &'tcx Expr<'tcx>) -> Vec<DataBorrow<'tcx>> {
+    let mut borrows = Vec::new();
+    visit_expr_no_bodies(scope, |expr| {
+        if let Some((account, mutable)) = data_borrow(cx, expr) {
+            borrows.push(DataBorrow {
+                account,
+                expr,
+                mutable,
+                alive: alive_span(cx, expr),
+            });
+        }
+        false
+    });
+    borrows
+}
+
+/// Return true if `borrow` is made while `previous` is alive, and one of them is mutable
+fn conflicts<'tcx>(
+    cx: &LateContext<'tcx>,
+    previous: &DataBorrow<'tcx>,
+    borrow: &DataBorrow<'tcx>,
+) -> bool {
+    (previous.mutable || borrow.mutable)
+        && previous.expr.span.hi() <= borrow.expr.span.lo()
+        && previous.alive.contains(borrow.expr.span)
+        && SpanlessEq::new(cx).eq_expr(previous.account, borrow.account)
+}
+
+/// If `expr` borrows the data of an account, return the account and whether the borrow is mutable
+fn data_borrow<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<(&'tcx Expr<'tcx>, bool)> {
+    if let ExprKind::MethodCall(_, recv, _, _) = expr.kind {
+        let def_id = cx.typeck_results().type_dependent_def_id(expr.hir_id)?;
+        if match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_TRY_BORROW_DATA) {
+            return Some((recv, false));
+        }
+        if match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_TRY_BORROW_MUT_DATA) {
+            return Some((recv, true));
+        }
+        if_chain! {
+            if let Some(i) = match_any_def_paths(
+                cx,
+                def_id,
+                &[
+                    &paths::CORE_CELL_BORROW,
+                    &paths::CORE_CELL_TRY_BORROW,
+                    &paths::CORE_CELL_BORROW_MUT,
+                    &paths::CORE_CELL_TRY_BORROW_MUT,
+                ],
+            );
+            if let ExprKind::Field(account, ident) = recv.kind;
+            if ident.as_str() == "data";
+            let ty = cx.typeck_results().expr_ty_adjusted(account).peel_refs();
+            if paths::is_account_info_ty(cx, ty);
+            then {
+                return Some((account, i >= 2));
+            }
+        }
+    }
+    None
+}
+
+/// Return the span in which the borrow `expr` is alive
+fn alive_span(cx: &LateContext<'_>, expr: &Expr<'_>) -> Span {
+    let hir_map = cx.tcx.hir();
+    let mut child = expr.hir_id;
+    let mut child_span = expr.span;
+    for (parent_id, node) in hir_map.parent_iter(expr.hir_id) {
+        match node {
+            Node::Local(local) => {
+                return guard_alive_span(cx, local).unwrap_or(local.span);
+            }
+            Node::Stmt(stmt) => return stmt.span,
+            Node::Arm(arm) => return arm.span,
+            Node::Expr(parent) => match parent.kind {
+                // The conditions of `if` and `while` are wrapped in `DropTemps`
+                ExprKind::DropTemps(_) => return parent.span,
+                ExprKind::If(_, then, els)
+                    if then.hir_id == child || els.map_or(false, |els| els.hir_id == child) =>
+                {
+                    return child_span;
+                }
+                ExprKind::Loop(..) | ExprKind::Closure(_) => return child_span,
+                _ => {}
+            },
+            Node::Block(_) => {}
+            _ => return child_span,
+        }
+        child = parent_id;
+        child_span = hir_map.span(parent_id);
+    }
+    child_span
+}
+
+/// If `local` binds the borrow to a variable of type `Ref` or `RefMut`, e.g.,
+/// `let data = account.try_borrow_data()?;`, return the span from `local` to the end of the
+/// enclosing block, or to the first `drop` of the variable
+fn guard_alive_span(cx: &LateContext<'_>, local: &Local<'_>) -> Option<Span> {
+    if_chain! {
+        if let PatKind::Binding(_, hir_id, _, None) = local.pat.kind;
+        let ty = cx.typeck_results().node_type(hir_id).peel_refs();
+        if match_type(cx, ty, &paths::CORE_CELL_REF)
+            || match_type(cx, ty, &paths::CORE_CELL_REF_MUT);
+        if let Some(block) = cx.tcx.hir().parent_iter(local.hir_id).find_map(|(_, node)| {
+            if let Node::Block(block) = node {
+                Some(block)
+            } else {
+                None
+            }
+        });
+        then {
+            // A `drop` in a nested block, e.g., in one branch of an `if`, does not end the borrow
+            let end = block
+                .stmts
+                .iter()
+                .filter(|stmt| stmt.span.lo() > local.span.hi())
+                .find_map(|stmt| match stmt.kind {
+                    StmtKind::Expr(expr) | StmtKind::Semi(expr) if is_drop_of(cx, expr, hir_id) => {
+                        Some(stmt.span.lo())
+                    }
+                    _ => None,
+                })
+                .unwrap_or_else(|| block.span.hi());
+            Some(local.span.with_hi(end))
+        } else {
+            None
+        }
+    }
+}
+
+/// Return true if `expr` is `drop(local)`
+fn is_drop_of(cx: &LateContext<'_>, expr: &Expr<'_>, local: HirId) -> bool {
+    if_chain! {
+        if let ExprKind::Call(_, [arg]) = expr.kind;
+        if let Some(def_id) = fn_def_id(cx, expr);
+        if match_def_path(cx, def_id, &paths::CORE_MEM_DROP);
+        then {
+            path_to_local_id(arg, local)
+        } else {
+            false
+        }
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod account_data_borrow_conflict_insecure {
+    use super::*;
+
+    pub fn increment(ctx: Context<Update>) -> Result<()> {
+        let data = ctx.accounts.counter.try_borrow_data()?;
+        let count = data[0];
+        let mut data = ctx.accounts.counter.try_borrow_mut_data()?;
+        data[0] = count + 1;
+        Ok(())
+    }
+
+    pub fn reset(ctx: Context<Update>) -> Result<()> {
+        let mut data = ctx.accounts.counter.data.borrow_mut();
+        if data[1] != 0 {
+            let flags = ctx.accounts.counter.data.borrow()[1];
+            msg!("flags: {}", flags);
+        }
+        data[0] = 0;
+        Ok(())
+    }
+
+    pub fn copy_count(ctx: Context<Update>) -> Result<()> {
+        ctx.accounts.counter.data.borrow_mut()[2..4]
+            .copy_from_slice(&ctx.accounts.counter.data.borrow()[..2]);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    /// CHECK: the data is read and written by hand
+    #[account(mut)]
+    counter: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
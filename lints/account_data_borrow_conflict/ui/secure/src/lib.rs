@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod account_data_borrow_conflict_secure {
+    use super::*;
+
+    pub fn increment(ctx: Context<Update>) -> Result<()> {
+        let count = {
+            let data = ctx.accounts.counter.try_borrow_data()?;
+            data[0]
+        };
+        let mut data = ctx.accounts.counter.try_borrow_mut_data()?;
+        data[0] = count + 1;
+        Ok(())
+    }
+
+    pub fn reset(ctx: Context<Update>) -> Result<()> {
+        let data = ctx.accounts.counter.data.borrow();
+        let flags = data[1];
+        drop(data);
+        if flags != 0 {
+            ctx.accounts.counter.data.borrow_mut()[0] = 0;
+        }
+        Ok(())
+    }
+
+    pub fn log_count(ctx: Context<Update>) -> Result<()> {
+        // Two shared borrows do not conflict
+        let data = ctx.accounts.counter.data.borrow();
+        let same_data = ctx.accounts.counter.try_borrow_data()?;
+        msg!("count: {} {}", data[0], same_data[0]);
+        Ok(())
+    }
+
+    pub fn copy_count(ctx: Context<Update>) -> Result<()> {
+        // The temporary borrows are dropped at the end of each statement
+        let count = ctx.accounts.counter.data.borrow()[0];
+        ctx.accounts.counter.data.borrow_mut()[2] = count;
+        // The borrows are of different accounts
+        ctx.accounts.backup.data.borrow_mut()[..2]
+            .copy_from_slice(&ctx.accounts.counter.data.borrow()[..2]);
+        Ok(())
+    }
+
+    pub fn toggle(ctx: Context<Update>) -> Result<()> {
+        // The borrow in the condition is dropped before the body is executed
+        if ctx.accounts.counter.data.borrow()[1] == 0 {
+            ctx.accounts.counter.data.borrow_mut()[1] = 1;
+        } else {
+            ctx.accounts.counter.data.borrow_mut()[1] = 0;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    /// CHECK: the data is read and written by hand
+    #[account(mut)]
+    counter: AccountInfo<'info>,
+    /// CHECK: the data is written by hand
+    #[account(mut)]
+    backup: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,256 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use rustc_hir::def_id::DefId;
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Symbol;
+use solana_lints::{
+    bless::{is_blessing, write_lock_or_warn},
+    diagnostics::span_lint_hir_and_then,
+    discriminators::{
+        account_discriminator, account_types, lock_path, read_lock, to_hex, type_path, write_lock,
+        Discriminator, LOCK_FILE,
+    },
+    sarif,
+};
+use std::collections::{BTreeMap, HashSet};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the `#[account]` types of the crate whose discriminator is the same as the
+    /// discriminator of another account type of the crate or of its dependencies, i.e., the types
+    /// with the same name.
+    ///
+    /// If the package has a `discriminators.lock` file next to its `Cargo.toml`, with one line per
+    /// account type holding the discriminator as hexadecimal digits and the name of the type, e.g.,
+    /// `d308e82b02987577 Vault`, the lint also reports the account types which are not recorded in
+    /// the file while a type recorded in the file no longer exists, i.e., the renamed types. If
+    /// `SOLANA_LINTS_BLESS` is set (see `solana_lints::bless`), the lint records the account types
+    /// of the crate in the file instead.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Anchor derives the discriminator of an account type from the name of the type only. Two
+    /// account types with the same name, e.g., `pool::State` and `user::State`, cannot be told
+    /// apart, so one can be passed where the other is expected. Renaming an account type changes
+    /// its discriminator, so that the accounts already stored on chain can no longer be
+    /// deserialized.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The discriminators are computed from the names of the types as Anchor 0.29 computes them.
+    /// The types of the dependencies are checked only if the crate depends on them, e.g., through
+    /// the `cpi` feature of another program. A type removed from the crate is not reported unless
+    /// another type is added to it.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub mod pool {
+    ///     #[account]
+    ///     pub struct State { ... }
+    /// }
+    ///
+    /// pub mod user {
+    ///     #[account]
+    ///     pub struct State { ... }
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[account]
+    /// pub struct PoolState { ... }
+    ///
+    /// #[account]
+    /// pub struct UserState { ... }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_crate_post:
+    ///
+    /// - collect the types implementing both `anchor_lang::Discriminator` and `anchor_lang::Owner`
+    ///   in the crate and its dependencies, and compute their discriminators from their names
+    /// - report each type of the crate whose discriminator is the discriminator of another type
+    /// - if `SOLANA_LINTS_BLESS` is set, write the discriminators of the types of the crate to
+    ///   `discriminators.lock`
+    /// - else, if `discriminators.lock` exists and records a type which is not defined in the
+    ///   crate, report each type of the crate which is not recorded in it
+    pub ACCOUNT_DISCRIMINATOR_COLLISION,
+    Warn,
+    "Anchor account types whose discriminators collide or differ from `discriminators.lock`"
+}
+
+impl<'tcx> LateLintPass<'tcx> for AccountDiscriminatorCollision {
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        let account_types = account_types(cx);
+
+        let mut by_discriminator = BTreeMap::<Discriminator, Vec<DefId>>::new();
+        for &def_id in &account_types {
+            by_discriminator
+                .entry(account_discriminator(cx.tcx.item_name(def_id).as_str()))
+                .or_default()
+                .push(def_id);
+        }
+        for &def_id in &account_types {
+            let others = by_discriminator
+                [&account_discriminator(cx.tcx.item_name(def_id).as_str())]
+                .iter()
+                .copied()
+                .filter(|other| *other != def_id)
+                .collect::<Vec<_>>();
+            if def_id.is_local() && !others.is_empty() {
+                report_collision(cx, def_id, &others);
+            }
+        }
+
+        if let Some(path) = lock_path(cx) {
+            if is_blessing() {
+                let entries = account_types
+                    .iter()
+                    .filter(|def_id| def_id.is_local())
+                    .map(|def_id| {
+                        let name = cx.tcx.item_name(*def_id);
+                        (account_discriminator(name.as_str()), name.to_string())
+                    })
+                    .collect::<Vec<_>>();
+                if !entries.is_empty() {
+                    write_lock_or_warn(cx, "discriminators", &path, |path| {
+                        write_lock(path, &entries)
+                    });
+                }
+            } else if path.exists() {
+                match read_lock(&path) {
+                    Ok(entries) => check_lock(cx, &account_types, &entries),
+                    Err(error) => cx.tcx.sess.dcx().warn(format!(
+                        "could not read the discriminators file `{}`: {error}",
+                        path.display()
+                    )),
+                }
+            }
+        }
+
+        sarif::write(cx, &[ACCOUNT_DISCRIMINATOR_COLLISION]);
+    }
+}
+
+fn report_collision(cx: &LateContext<'_>, def_id: DefId, others: &[DefId]) {
+    let local_def_id = def_id.expect_local();
+    let paths = others
+        .iter()
+        .map(|other| format!("`{}`", type_path(cx, *other)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    span_lint_hir_and_then(
+        cx,
+        ACCOUNT_DISCRIMINATOR_COLLISION,
+        cx.tcx.local_def_id_to_hir_id(local_def_id),
+        cx.tcx.def_span(def_id),
+        &format!(
+            "the account type `{}` has the same discriminator as {paths}",
+            type_path(cx, def_id)
+        ),
+        |diag| {
+            for &other in others {
+                if other.is_local() {
+                    diag.span_note(
+                        cx.tcx.def_span(other),
+                        format!("`{}` is defined here", type_path(cx, other)),
+                    );
+                } else {
+                    diag.note(format!(
+                        "`{}` is defined in the crate `{}`",
+                        type_path(cx, other),
+                        cx.tcx.crate_name(other.krate)
+                    ));
+                }
+            }
+            diag.help(
+                "rename the types so that their names differ, as Anchor derives the discriminator from the name of the type only",
+            );
+        },
+    );
+}
+
+/// Report the account types of the crate which are not recorded in the lock file, if a type
+/// recorded in the lock file is not defined in the crate
+fn check_lock(cx: &LateContext<'_>, account_types: &[DefId], entries: &[(Discriminator, String)]) {
+    let local_types = account_types
+        .iter()
+        .copied()
+        .filter(|def_id| def_id.is_local())
+        .collect::<Vec<_>>();
+    let local_names = local_types
+        .iter()
+        .map(|def_id| cx.tcx.item_name(*def_id))
+        .collect::<HashSet<Symbol>>();
+    let missing = entries
+        .iter()
+        .filter(|(_, name)| !local_names.contains(&Symbol::intern(name)))
+        .collect::<Vec<_>>();
+    if missing.is_empty() {
+        return;
+    }
+    let recorded = entries
+        .iter()
+        .map(|(_, name)| name.as_str())
+        .collect::<HashSet<_>>();
+    for def_id in local_types {
+        let name = cx.tcx.item_name(def_id);
+        if recorded.contains(name.as_str()) {
+            continue;
+        }
+        span_lint_hir_and_then(
+            cx,
+            ACCOUNT_DISCRIMINATOR_COLLISION,
+            cx.tcx.local_def_id_to_hir_id(def_id.expect_local()),
+            cx.tcx.def_span(def_id),
+            &format!("the account type `{name}` is not recorded in `{LOCK_FILE}`"),
+            |diag| {
+                for (discriminator, missing_name) in &missing {
+                    diag.note(format!(
+                        "`{LOCK_FILE}` records `{missing_name}`, with discriminator `{}`, which is not defined",
+                        to_hex(discriminator)
+                    ));
+                }
+                diag.note(format!(
+                    "the discriminator of `{name}` is `{}`",
+                    to_hex(&account_discriminator(name.as_str()))
+                ));
+                diag.help(format!(
+                    "if a type was renamed to `{name}`, restore its name or migrate the accounts stored with its discriminator; otherwise, add `{name}` to `{LOCK_FILE}`"
+                ));
+            },
+        );
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn insecure_lock() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-lock");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
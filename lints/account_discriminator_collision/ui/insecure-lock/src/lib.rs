@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod account_discriminator_collision_insecure_lock {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        require_keys_eq!(ctx.accounts.config.admin, ctx.accounts.authority.key());
+        ctx.accounts.vault.balance += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    config: Account<'info, Config>,
+    #[account(mut)]
+    vault: Account<'info, UserVault>,
+    authority: Signer<'info>,
+}
+
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+}
+
+#[account]
+pub struct UserVault {
+    pub balance: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod account_discriminator_collision_insecure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        ctx.accounts.pool.total += amount;
+        ctx.accounts.user.balance += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pool: Account<'info, pool::State>,
+    #[account(mut, has_one = authority)]
+    user: Account<'info, user::State>,
+    authority: Signer<'info>,
+}
+
+pub mod pool {
+    use super::*;
+
+    #[account]
+    pub struct State {
+        pub total: u64,
+    }
+}
+
+pub mod user {
+    use super::*;
+
+    #[account]
+    pub struct State {
+        pub authority: Pubkey,
+        pub balance: u64,
+    }
+}
+
+#[allow(dead_code)]
+fn main() {}
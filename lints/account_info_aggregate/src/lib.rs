@@ -0,0 +1,372 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    fn_def_id, get_trait_def_id, is_expn_of, match_def_path, path_to_local, path_to_local_id,
+    ty::{implements_trait, is_type_diagnostic_item, match_type},
+};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::LocalDefId, intravisit::FnKind, Body, Expr, ExprKind, FnDecl, HirId, Node, PatKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, GenericArgKind, Ty};
+use rustc_span::{sym, Span, Symbol};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths::{self, anchor_wrapper, AnchorWrapper},
+    sarif,
+    utils::{context_accounts_struct, visit_expr_no_bodies},
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the `AccountInfo`s of validated accounts, e.g.,
+    /// `ctx.accounts.vault.to_account_info()` for an `Account<'info, Vault>`, which an instruction
+    /// collects into a helper struct, an array or a `Vec`, and passes to a helper function.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The constraints of an Anchor account, e.g., its owner, its type, `has_one` or `seeds`, are
+    /// attached to the typed wrapper. Once the account is passed around as a bare `AccountInfo`,
+    /// the helpers cannot tell which checks were done, accounts are easily confused with each
+    /// other, and the lints which follow the accounts, e.g., `missing_owner_check`, lose track of
+    /// them. Passing the typed accounts, or the `Accounts` struct, keeps the checks visible.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Only `to_account_info()` calls on the fields of `ctx.accounts` in the instruction handler
+    /// are followed, and only into the first helper the collection is passed to. Structs deriving
+    /// `Accounts`, e.g., the accounts of a CPI, and collections passed to functions of other
+    /// crates, e.g., `invoke`, are not reported.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    ///     pub fn transfer(ctx: Context<Transfer>, amount: u64) -> Result<()> {
+    ///         let accounts = TransferAccounts {
+    ///             vault: ctx.accounts.vault.to_account_info(),
+    ///             authority: ctx.accounts.authority.to_account_info(),
+    ///         };
+    ///         process_transfer(&accounts, amount)
+    ///     }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    ///     pub fn transfer(ctx: Context<Transfer>, amount: u64) -> Result<()> {
+    ///         process_transfer(&ctx.accounts.vault, &ctx.accounts.authority, amount)
+    ///     }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// - For every function which takes a `Context<T>` argument, excluding the functions generated by macros
+    ///   - For every `ctx.accounts.x.to_account_info()` call where `x` is not an `AccountInfo` or an `UncheckedAccount`
+    ///     - If the call is a field of a struct expression, and the struct is defined in the crate and does not
+    ///       implement `ToAccountInfos`, the collection is the struct expression
+    ///     - If the call is an element of an array expression, e.g., of `vec![...]`, the collection is the array
+    ///     - If the call is the argument of `v.push(..)` for a local `Vec` `v`, the collection is `v`
+    ///   - For each collection, if it is bound to a local which is passed to a function of the crate, or it is
+    ///     passed to such a function directly, report the collection and the call
+    pub ACCOUNT_INFO_AGGREGATE,
+    Warn,
+    "`AccountInfo`s of validated accounts collected into helper structs or `Vec`s"
+}
+
+/// A collection of `AccountInfo`s of validated accounts
+struct Aggregate {
+    /// Where the first `AccountInfo` is stored, e.g., the struct expression or the `push` call
+    hir_id: HirId,
+    span: Span,
+    /// A description of the collection, e.g., "`TransferAccounts`" or "a `Vec`"
+    desc: String,
+    flow: Flow,
+    /// The fields of `ctx.accounts` whose `AccountInfo`s are stored
+    accounts: Vec<Symbol>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Flow {
+    /// The collection is bound to the local
+    Local(HirId),
+    /// The collection is passed directly to the call
+    Call(HirId),
+}
+
+impl<'tcx> LateLintPass<'tcx> for AccountInfoAggregate {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        fn_kind: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        if is_skipped_expansion(ACCOUNT_INFO_AGGREGATE, span)
+            || matches!(fn_kind, FnKind::Closure)
+            || context_accounts_struct(cx, local_def_id).is_none()
+        {
+            return;
+        }
+
+        let mut aggregates: Vec<Aggregate> = Vec::new();
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if let Some(account) = validated_account_info(cx, expr);
+                if let Some((storage, desc, flow)) = storage(cx, expr);
+                then {
+                    if let Some(aggregate) =
+                        aggregates.iter_mut().find(|aggregate| aggregate.flow == flow)
+                    {
+                        aggregate.accounts.push(account);
+                    } else {
+                        aggregates.push(Aggregate {
+                            hir_id: storage.hir_id,
+                            span: is_expn_of(storage.span, "vec").unwrap_or(storage.span),
+                            desc,
+                            flow,
+                            accounts: vec![account],
+                        });
+                    }
+                }
+            }
+            false
+        });
+
+        for aggregate in aggregates {
+            if_chain! {
+                if let Some(call) = helper_call(cx, body, aggregate.flow);
+                if let Some(helper) = fn_def_id(cx, call);
+                then {
+                    span_lint_hir_and_then(
+                        cx,
+                        ACCOUNT_INFO_AGGREGATE,
+                        aggregate.hir_id,
+                        aggregate.span,
+                        &format!(
+                            "`AccountInfo`s of validated accounts are stored in {}, which is passed to `{}`",
+                            aggregate.desc,
+                            cx.tcx.item_name(helper)
+                        ),
+                        |diag| {
+                            diag.span_note(call.span, "the helper is called here");
+                            diag.note(format!(
+                                "the `AccountInfo`s are of {}",
+                                aggregate
+                                    .accounts
+                                    .iter()
+                                    .map(|account| format!("`{account}`"))
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            ));
+                            diag.help(
+                                "pass the typed accounts, e.g., `&Account<'info, T>`, or the `Accounts` struct instead",
+                            );
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[ACCOUNT_INFO_AGGREGATE]);
+    }
+}
+
+/// If `expr` is `ctx.accounts.x.to_account_info()` and `x` is neither an `AccountInfo` nor an
+/// `UncheckedAccount`, return `x`
+fn validated_account_info<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> Option<Symbol> {
+    if_chain! {
+        if let ExprKind::MethodCall(_, receiver, [], _) = expr.kind;
+        if let Some(def_id) = cx.typeck_results().type_dependent_def_id(expr.hir_id);
+        if match_def_path(cx, def_id, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO);
+        if let ExprKind::Field(accounts, account) = receiver.kind;
+        if let ExprKind::Field(ctx, accounts_ident) = accounts.kind;
+        if accounts_ident.as_str() == "accounts";
+        if match_type(
+            cx,
+            cx.typeck_results().expr_ty(ctx).peel_refs(),
+            &paths::ANCHOR_LANG_CONTEXT,
+        );
+        let account_ty = cx.typeck_results().expr_ty(receiver).peel_refs();
+        if !paths::is_account_info_ty(cx, account_ty);
+        if anchor_wrapper(cx, account_ty) != Some(AnchorWrapper::UncheckedAccount);
+        then {
+            Some(account.name)
+        } else {
+            None
+        }
+    }
+}
+
+/// Return the expression which stores the `AccountInfo` returned by `call` in a collection, a
+/// description of the collection, and where the collection flows
+fn storage<'tcx>(
+    cx: &LateContext<'tcx>,
+    call: &'tcx Expr<'tcx>,
+) -> Option<(&'tcx Expr<'tcx>, String, Flow)> {
+    let hir_map = cx.tcx.hir();
+    let mut parents = hir_map.parent_iter(call.hir_id).map(|(_, node)| node);
+    match parents.next()? {
+        Node::ExprField(_) => {
+            if_chain! {
+                if let Some(Node::Expr(struct_expr)) = parents.next();
+                let struct_ty = cx.typeck_results().expr_ty(struct_expr);
+                if let ty::Adt(adt_def, _) = struct_ty.kind();
+                // Structs deriving `Accounts`, e.g., the accounts of a CPI, are not helper structs.
+                if adt_def.did().is_local() && !is_accounts_struct(cx, struct_ty);
+                then {
+                    let desc = format!("`{}`", cx.tcx.item_name(adt_def.did()));
+                    Some((struct_expr, desc, flow(cx, struct_expr)?))
+                } else {
+                    None
+                }
+            }
+        }
+        Node::Expr(
+            array @ Expr {
+                kind: ExprKind::Array(_),
+                ..
+            },
+        ) => {
+            let desc = if is_expn_of(array.span, "vec").is_some() {
+                "a `Vec`"
+            } else {
+                "an array"
+            };
+            Some((array, desc.to_owned(), flow(cx, array)?))
+        }
+        Node::Expr(
+            push @ Expr {
+                kind: ExprKind::MethodCall(method, vec, [_], _),
+                ..
+            },
+        ) if method.ident.as_str() == "push"
+            && is_type_diagnostic_item(
+                cx,
+                cx.typeck_results().expr_ty(vec).peel_refs(),
+                sym::Vec,
+            ) =>
+        {
+            Some((push, "a `Vec`".to_owned(), Flow::Local(path_to_local(vec)?)))
+        }
+        _ => None,
+    }
+}
+
+/// Return where the collection `expr` flows: the local it is bound to, e.g., with
+/// `let accounts = ...`, or the call it is passed to
+fn flow<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> Option<Flow> {
+    let mut child = expr.hir_id;
+    for (parent_id, node) in cx.tcx.hir().parent_iter(expr.hir_id) {
+        match node {
+            // `&collection`, or the expansion of `vec![...]`
+            Node::Expr(parent)
+                if matches!(parent.kind, ExprKind::AddrOf(..))
+                    || (parent.span.from_expansion()
+                        && is_expn_of(parent.span, "vec").is_some()) =>
+            {
+                child = parent_id;
+            }
+            Node::Expr(parent) => {
+                return match parent.kind {
+                    ExprKind::Call(_, args) | ExprKind::MethodCall(_, _, args, _)
+                        if args.iter().any(|arg| arg.hir_id == child) =>
+                    {
+                        Some(Flow::Call(parent_id))
+                    }
+                    _ => None,
+                };
+            }
+            Node::Local(local) => {
+                return if let PatKind::Binding(_, hir_id, _, None) = local.pat.kind {
+                    Some(Flow::Local(hir_id))
+                } else {
+                    None
+                };
+            }
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Return the first call of a function of the crate which the collection flows into
+fn helper_call<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx Body<'tcx>,
+    flow: Flow,
+) -> Option<&'tcx Expr<'tcx>> {
+    let is_helper_call =
+        |expr: &Expr<'_>| fn_def_id(cx, expr).map_or(false, |def_id| def_id.is_local());
+    match flow {
+        Flow::Call(hir_id) => {
+            let call = cx.tcx.hir().expect_expr(hir_id);
+            is_helper_call(call).then_some(call)
+        }
+        Flow::Local(local) => visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if let ExprKind::Call(_, args) | ExprKind::MethodCall(_, _, args, _) = expr.kind;
+                if args.iter().any(|arg| {
+                    let arg = if let ExprKind::AddrOf(_, _, inner) = arg.kind {
+                        inner
+                    } else {
+                        arg
+                    };
+                    path_to_local_id(arg, local)
+                });
+                if is_helper_call(expr);
+                then {
+                    Some(expr)
+                } else {
+                    None
+                }
+            }
+        }),
+    }
+}
+
+/// Return true if `ty` implements `anchor_lang::ToAccountInfos`, i.e., it derives `Accounts`
+fn is_accounts_struct<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>) -> bool {
+    if_chain! {
+        if let ty::Adt(_, args) = ty.kind();
+        if let Some(trait_id) = get_trait_def_id(cx, &paths::ANCHOR_LANG_TO_ACCOUNT_INFOS_TRAIT);
+        if let Some(lifetime_arg) = args
+            .iter()
+            .find(|arg| matches!(arg.unpack(), GenericArgKind::Lifetime(_)));
+        then {
+            implements_trait(cx, ty, trait_id, &[lifetime_arg])
+        } else {
+            false
+        }
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
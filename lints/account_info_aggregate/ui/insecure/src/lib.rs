@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod account_info_aggregate_insecure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let accounts = WithdrawAccounts {
+            vault: ctx.accounts.vault.to_account_info(),
+            destination: ctx.accounts.destination.to_account_info(),
+        };
+        process_withdraw(&accounts, amount)
+    }
+
+    pub fn close(ctx: Context<Withdraw>) -> Result<()> {
+        let accounts = vec![
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+        ];
+        process_close(&accounts)
+    }
+
+    pub fn sweep(ctx: Context<Withdraw>) -> Result<()> {
+        let mut accounts = Vec::new();
+        accounts.push(ctx.accounts.vault.to_account_info());
+        accounts.push(ctx.accounts.destination.to_account_info());
+        process_close(&accounts)
+    }
+}
+
+pub struct WithdrawAccounts<'info> {
+    vault: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+}
+
+fn process_withdraw(accounts: &WithdrawAccounts, amount: u64) -> Result<()> {
+    **accounts.vault.try_borrow_mut_lamports()? -= amount;
+    **accounts.destination.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+fn process_close(accounts: &[AccountInfo]) -> Result<()> {
+    let amount = accounts[0].lamports();
+    **accounts[0].try_borrow_mut_lamports()? -= amount;
+    **accounts[1].try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, has_one = authority)]
+    vault: Account<'info, Vault>,
+    authority: Signer<'info>,
+    #[account(mut)]
+    destination: SystemAccount<'info>,
+}
+
+#[account]
+pub struct Vault {
+    authority: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod account_info_aggregate_secure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        process_withdraw(&ctx.accounts.vault, &ctx.accounts.destination, amount)
+    }
+
+    pub fn close(ctx: Context<Withdraw>) -> Result<()> {
+        process_close(ctx.accounts)
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        let accounts = system_program::Transfer {
+            from: ctx.accounts.depositor.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+        };
+        system_program::transfer(
+            CpiContext::new(ctx.accounts.system_program.to_account_info(), accounts),
+            amount,
+        )
+    }
+}
+
+fn process_withdraw(
+    vault: &Account<Vault>,
+    destination: &SystemAccount,
+    amount: u64,
+) -> Result<()> {
+    **vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **destination.to_account_info().try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+fn process_close(accounts: &Withdraw) -> Result<()> {
+    let amount = accounts.vault.to_account_info().lamports();
+    process_withdraw(&accounts.vault, &accounts.destination, amount)
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, has_one = authority)]
+    vault: Account<'info, Vault>,
+    authority: Signer<'info>,
+    #[account(mut)]
+    destination: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    #[account(mut)]
+    depositor: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct Vault {
+    authority: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,121 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_errors;
+extern crate rustc_hir;
+
+use clippy_utils::source::snippet_with_applicability;
+use rustc_errors::Applicability;
+use rustc_hir::{BinOpKind, Expr, ExprKind};
+use rustc_lint::{LateContext, LateLintPass};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, sarif,
+    utils::account_info_comparison,
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports comparisons of two `AccountInfo`s which do not compare the keys of the accounts:
+    ///
+    /// - `std::ptr::eq(a, b)` where `a` and `b` are `AccountInfo` references
+    /// - `a.lamports == b.lamports` and `a.data == b.data` (or `!=`)
+    ///
+    /// **Why is this bad?**
+    ///
+    /// These comparisons are often written to check that two accounts are the same account, but they
+    /// do not compare the keys. `std::ptr::eq` compares the addresses of the `AccountInfo` structs,
+    /// which differ for every `AccountInfo` value, e.g., for a clone, even if the accounts are the same.
+    /// Comparing the `lamports` or `data` fields compares the balances or the contents of the accounts,
+    /// which an attacker can make equal for two different accounts.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Comparisons of the balances or the contents of two accounts are reported even if they are
+    /// intended.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    ///     if std::ptr::eq(source, destination) {
+    ///         return Err(ProgramError::InvalidArgument);
+    ///     }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    ///     if source.key == destination.key {
+    ///         return Err(ProgramError::InvalidArgument);
+    ///     }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// - For every expression
+    ///   - If the expression is `std::ptr::eq(a, b)` and `a`, `b` are of type `AccountInfo` (ignoring references)
+    ///   - Or if the expression is `a.f == b.f` or `a.f != b.f` where `f` is `lamports` or `data` and `a`, `b` are of
+    ///     type `AccountInfo`
+    ///     - report the expression and suggest comparing `a.key` and `b.key`
+    ///
+    /// The reported comparisons are not key checks, so the other lints, e.g., `missing_owner_check`, may
+    /// also report the accounts involved.
+    pub ACCOUNT_INFO_COMPARISON,
+    Warn,
+    "comparing `AccountInfo`s without comparing their keys"
+}
+
+impl<'tcx> LateLintPass<'tcx> for AccountInfoComparison {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if is_skipped_expansion(ACCOUNT_INFO_COMPARISON, expr.span) {
+            return;
+        }
+        if let Some((lhs, rhs)) = account_info_comparison(cx, expr) {
+            let op = match expr.kind {
+                ExprKind::Binary(op, _, _) if op.node == BinOpKind::Ne => "!=",
+                _ => "==",
+            };
+            let mut applicability = Applicability::MaybeIncorrect;
+            let lhs_snippet = snippet_with_applicability(cx, lhs.span, "..", &mut applicability);
+            let rhs_snippet = snippet_with_applicability(cx, rhs.span, "..", &mut applicability);
+            span_lint_hir_and_then(
+                cx,
+                ACCOUNT_INFO_COMPARISON,
+                expr.hir_id,
+                expr.span,
+                "this comparison does not compare the keys of the accounts",
+                |diag| {
+                    diag.span_suggestion(
+                        expr.span,
+                        "compare the keys instead",
+                        format!("{lhs_snippet}.key {op} {rhs_snippet}.key"),
+                        applicability,
+                    );
+                },
+            );
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[ACCOUNT_INFO_COMPARISON]);
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
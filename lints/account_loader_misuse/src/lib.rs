@@ -0,0 +1,359 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use anchor_syn::{AccountField, Ty as FieldTy};
+use clippy_utils::match_def_path;
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, Expr, ExprKind, FnDecl, HirId, Item, ItemKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty;
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{
+        anchor_constraints::ConstraintSummary, get_anchor_accounts_struct, visit_expr_no_bodies,
+    },
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the `AccountLoader` method calls which do not match the constraints of the account:
+    ///
+    /// - `load_init` on an account which does not have the `init` or the `zero` constraint.
+    /// - `load_mut` on an account which does not have the `mut` constraint.
+    /// - `load_mut` or `load` on an account which has the `init` or the `zero` constraint, without
+    ///   a prior `load_init` in the instruction.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// `load_init` only checks that the discriminator of the account is not set, i.e., that the
+    /// account is not initialized. Calling it in an instruction which does not initialize the
+    /// account skips the discriminator check of `load_mut`, and the instruction fails for every
+    /// initialized account. Writing through `load_mut` to an account which is not `mut` succeeds,
+    /// but the writes are not persisted, or the transaction fails, as the account is not writable.
+    /// An account with the `init` or the `zero` constraint does not have a discriminator until the
+    /// instruction ends, so `load_mut` and `load` fail on it.
+    ///
+    /// All of these calls compile fine and only fail, or corrupt the state of the program, at
+    /// runtime.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The lint only checks the method calls in the instruction handlers, on a field of the
+    /// `Accounts` struct, e.g., `ctx.accounts.orderbook.load_init()`. Calls in other functions, or
+    /// on an `AccountLoader` which is passed around, are not checked.
+    ///
+    /// A `load_init` call is prior to a `load_mut` call if it comes first in the source of the
+    /// function; the branches of the function are not taken into account.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn update(ctx: Context<Update>, price: u64) -> Result<()> {
+    ///     let mut orderbook = ctx.accounts.orderbook.load_init()?;
+    ///     orderbook.price = price;
+    ///     Ok(())
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct Update<'info> {
+    ///     orderbook: AccountLoader<'info, Orderbook>,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// pub fn update(ctx: Context<Update>, price: u64) -> Result<()> {
+    ///     let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+    ///     orderbook.price = price;
+    ///     Ok(())
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct Update<'info> {
+    ///     #[account(mut)]
+    ///     orderbook: AccountLoader<'info, Orderbook>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - For each Anchor `Accounts` struct
+    ///   - For each field of type `AccountLoader<'info, T>`, record whether the field has the `init`
+    ///     or the `zero` constraint, and whether it is mutable (`mut`, `init` or `zero`)
+    ///
+    /// check_fn:
+    ///
+    /// - For every function, excluding the functions generated by macros and the closures
+    ///   - record each `load`, `load_init` and `load_mut` call whose receiver is a field of a struct,
+    ///     e.g., `ctx.accounts.orderbook`
+    ///
+    /// check_crate_post:
+    ///
+    /// - For each recorded call on a recorded field
+    ///   - If the call is `load_init` and the field does not have the `init` or the `zero`
+    ///     constraint, report the call
+    ///   - If the call is `load_mut` and the field is not mutable, report the call
+    ///   - If the call is `load_mut` or `load`, the field has the `init` or the `zero` constraint,
+    ///     and there is no `load_init` call on the field before it in the same function, report
+    ///     the call
+    pub ACCOUNT_LOADER_MISUSE,
+    Warn,
+    "`AccountLoader` method calls which do not match the constraints of the account",
+    AccountLoaderMisuse::default()
+}
+
+#[derive(Default)]
+struct AccountLoaderMisuse {
+    /// The `AccountLoader` fields of the `Accounts` structs, by their def ids
+    fields: FxHashMap<DefId, LoaderField>,
+    /// The `AccountLoader` method calls, in the order they are found
+    calls: Vec<LoaderCall>,
+}
+
+struct LoaderField {
+    span: Span,
+    /// Whether the field has the `init` or the `zero` constraint
+    init: bool,
+    /// Whether the account is writable: `mut`, `init` or `zero`
+    mutable: bool,
+}
+
+struct LoaderCall {
+    /// The function containing the call
+    fn_def_id: LocalDefId,
+    /// The def id of the field the method is called on
+    field: DefId,
+    method: Method,
+    hir_id: HirId,
+    span: Span,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Method {
+    Load,
+    LoadInit,
+    LoadMut,
+}
+
+impl Method {
+    fn name(self) -> &'static str {
+        match self {
+            Method::Load => "load",
+            Method::LoadInit => "load_init",
+            Method::LoadMut => "load_mut",
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for AccountLoaderMisuse {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                for (item_field, anchor_field) in
+                    variant.fields().iter().zip(accounts_struct.fields.iter())
+                {
+                    if_chain! {
+                        if let AccountField::Field(field) = anchor_field;
+                        if let FieldTy::AccountLoader(_) = field.ty;
+                        then {
+                            let constraints = ConstraintSummary::new(&field.constraints);
+                            self.fields.insert(
+                                item_field.def_id.to_def_id(),
+                                LoaderField {
+                                    span: item_field.span,
+                                    init: constraints.is_initialized,
+                                    mutable: constraints.is_writable(),
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        fn_kind: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        if is_skipped_expansion(ACCOUNT_LOADER_MISUSE, span) || matches!(fn_kind, FnKind::Closure) {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if let ExprKind::MethodCall(_, recv, _, _) = expr.kind;
+                if let Some(method) = loader_method(cx, expr);
+                if let Some(field) = field_def_id(cx, recv);
+                then {
+                    self.calls.push(LoaderCall {
+                        fn_def_id: local_def_id,
+                        field,
+                        method,
+                        hir_id: expr.hir_id,
+                        span: expr.span,
+                    });
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for call in &self.calls {
+            let field = match self.fields.get(&call.field) {
+                Some(field) => field,
+                None => continue,
+            };
+            let field_name = cx.tcx.item_name(call.field);
+            match call.method {
+                Method::LoadInit if !field.init => {
+                    span_lint_hir_and_then(
+                        cx,
+                        ACCOUNT_LOADER_MISUSE,
+                        call.hir_id,
+                        call.span,
+                        &format!(
+                            "`load_init` is called on `{field_name}`, which is not initialized by the instruction"
+                        ),
+                        |diag| {
+                            diag.span_note(
+                                field.span,
+                                format!("`{field_name}` does not have the `init` or the `zero` constraint"),
+                            );
+                            diag.help("use `load_mut` or `load` to access an initialized account");
+                        },
+                    );
+                }
+                Method::LoadMut if !field.mutable => {
+                    span_lint_hir_and_then(
+                        cx,
+                        ACCOUNT_LOADER_MISUSE,
+                        call.hir_id,
+                        call.span,
+                        &format!("`load_mut` is called on `{field_name}`, which is not mutable"),
+                        |diag| {
+                            diag.span_note(
+                                field.span,
+                                format!("`{field_name}` does not have the `mut` constraint"),
+                            );
+                            diag.help(format!(
+                                "add `#[account(mut)]` to `{field_name}`, or use `load` to read it"
+                            ));
+                        },
+                    );
+                }
+                Method::Load | Method::LoadMut if field.init && !self.initialized_before(call) => {
+                    span_lint_hir_and_then(
+                        cx,
+                        ACCOUNT_LOADER_MISUSE,
+                        call.hir_id,
+                        call.span,
+                        &format!(
+                            "`{}` is called on `{field_name}` without a prior `load_init`",
+                            call.method.name()
+                        ),
+                        |diag| {
+                            diag.span_note(
+                                field.span,
+                                format!(
+                                    "`{field_name}` is initialized by the instruction, so its discriminator is not set yet"
+                                ),
+                            );
+                            diag.help(
+                                "use `load_init` to access an account which is being initialized",
+                            );
+                        },
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        sarif::write(cx, &[ACCOUNT_LOADER_MISUSE]);
+    }
+}
+
+impl AccountLoaderMisuse {
+    /// Return true if there is a `load_init` call on the field of `call` before `call`, in the
+    /// same function
+    fn initialized_before(&self, call: &LoaderCall) -> bool {
+        self.calls.iter().any(|other| {
+            other.method == Method::LoadInit
+                && other.fn_def_id == call.fn_def_id
+                && other.field == call.field
+                && other.span.lo() < call.span.lo()
+        })
+    }
+}
+
+/// If `expr` is a call of `AccountLoader::load`, `load_init` or `load_mut`, return the method
+fn loader_method(cx: &LateContext<'_>, expr: &Expr<'_>) -> Option<Method> {
+    let def_id = cx.typeck_results().type_dependent_def_id(expr.hir_id)?;
+    if match_def_path(cx, def_id, &paths::ANCHOR_LANG_LOAD) {
+        Some(Method::Load)
+    } else if match_def_path(cx, def_id, &paths::ANCHOR_LANG_LOAD_INIT) {
+        Some(Method::LoadInit)
+    } else if match_def_path(cx, def_id, &paths::ANCHOR_LANG_LOAD_MUT) {
+        Some(Method::LoadMut)
+    } else {
+        None
+    }
+}
+
+/// If `expr` is a field access, e.g., `ctx.accounts.orderbook`, return the def id of the field
+fn field_def_id(cx: &LateContext<'_>, expr: &Expr<'_>) -> Option<DefId> {
+    if_chain! {
+        if let ExprKind::Field(base, _) = expr.kind;
+        if let ty::Adt(adt_def, _) = cx.typeck_results().expr_ty_adjusted(base).peel_refs().kind();
+        if adt_def.is_struct();
+        then {
+            let index = cx.typeck_results().field_index(expr.hir_id);
+            Some(adt_def.non_enum_variant().fields[index].did)
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod account_loader_misuse_insecure {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+        orderbook.authority = ctx.accounts.payer.key();
+        Ok(())
+    }
+
+    pub fn update(ctx: Context<Update>, price: u64) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_init()?;
+        orderbook.price = price;
+        Ok(())
+    }
+
+    pub fn set_price(ctx: Context<SetPrice>, price: u64) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+        orderbook.price = price;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + std::mem::size_of::<Orderbook>())]
+    orderbook: AccountLoader<'info, Orderbook>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    #[account(mut, has_one = authority)]
+    orderbook: AccountLoader<'info, Orderbook>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    #[account(has_one = authority)]
+    orderbook: AccountLoader<'info, Orderbook>,
+    authority: Signer<'info>,
+}
+
+#[account(zero_copy)]
+pub struct Orderbook {
+    authority: Pubkey,
+    price: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
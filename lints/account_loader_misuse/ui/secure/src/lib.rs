@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod account_loader_misuse_secure {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_init()?;
+        orderbook.authority = ctx.accounts.payer.key();
+        Ok(())
+    }
+
+    pub fn initialize_zeroed(ctx: Context<InitializeZeroed>) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_init()?;
+        orderbook.authority = ctx.accounts.authority.key();
+        Ok(())
+    }
+
+    pub fn update(ctx: Context<Update>, price: u64) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+        orderbook.price = price;
+        Ok(())
+    }
+
+    pub fn get_price(ctx: Context<GetPrice>) -> Result<u64> {
+        let orderbook = ctx.accounts.orderbook.load()?;
+        Ok(orderbook.price)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + std::mem::size_of::<Orderbook>())]
+    orderbook: AccountLoader<'info, Orderbook>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeZeroed<'info> {
+    #[account(zero)]
+    orderbook: AccountLoader<'info, Orderbook>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    #[account(mut, has_one = authority)]
+    orderbook: AccountLoader<'info, Orderbook>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetPrice<'info> {
+    orderbook: AccountLoader<'info, Orderbook>,
+}
+
+#[account(zero_copy)]
+pub struct Orderbook {
+    authority: Pubkey,
+    price: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
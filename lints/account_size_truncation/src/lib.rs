@@ -0,0 +1,501 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use anchor_syn::AccountField;
+use clippy_utils::{
+    consts::{constant, Constant},
+    fn_def_id, match_any_def_paths,
+};
+use if_chain::if_chain;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::{
+    def::{DefKind, Res},
+    def_id::LocalDefId,
+    BodyId, Expr, ExprKind, HirId, ImplItem, ImplItemKind, Item, ItemKind, QPath, TyKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty, TypeckResults};
+use rustc_span::{Span, Symbol};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{
+        anchor_constraints::init_space, get_anchor_accounts_struct, is_expr_method_call,
+        local_init, visit_expr_no_bodies,
+    },
+};
+use syn::visit::Visit;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the integer casts which may truncate the size of the data of an account:
+    /// - casts to `u8`, `u16`, `u32`, `i8`, `i16` or `i32` in the `space` (and `realloc`)
+    ///   constraints of Anchor `Accounts` structs, e.g., `space = 8 + len as u16 as usize`, and
+    ///   the truncating casts of the constants of the crate these constraints use
+    /// - casts to a narrower integer type in the size argument of `create_account`,
+    ///   `create_account_with_seed`, `allocate` and `allocate_with_seed` (of
+    ///   `system_instruction` or, for `create_account`, of `anchor_lang::system_program`), and of
+    ///   `Rent::minimum_balance`, following the local variables and the constants of the crate the
+    ///   argument uses, e.g., `let space = len as u32 as u64;`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A cast to a narrower integer type keeps the low bits of the value only, e.g., `300 as u8`
+    /// is `44`. A truncated size under-allocates the account, so that its data does not fit in
+    /// it, and a truncated size passed to `minimum_balance` underfunds it, so that it is not rent
+    /// exempt. As the sizes often come from the instruction data, e.g., the length of a name, a
+    /// caller choosing them can create accounts which later instructions fail to serialize or
+    /// resize.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// `usize` and `isize` are considered 64 bits wide, as on the SBF target, so casts between
+    /// them and `u64` or `i64`, e.g., `usize as u64`, are not reported. The types of the values in
+    /// a `space` constraint are not known, so every cast to a type narrower than 64 bits is
+    /// reported there, except the casts of integer literals which fit in the type. A cast in a
+    /// function called by the size expression is not found.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// #[instruction(len: u64)]
+    /// pub struct Initialize<'info> {
+    ///     #[account(init, payer = payer, space = 8 + 4 + len as u16 as usize)]
+    ///     log: Account<'info, Log>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// #[instruction(len: u16)]
+    /// pub struct Initialize<'info> {
+    ///     #[account(init, payer = payer, space = 8 + 4 + usize::from(len))]
+    ///     log: Account<'info, Log>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item, check_impl_item:
+    ///
+    /// - For each constant, record the truncating casts of its value (see below), by name
+    /// - For each Anchor `Accounts` struct
+    ///   - For each field with `#[account(init, space = ...)]` or `#[account(realloc = ...)]`
+    ///     constraint, record the types the expression casts values to which are narrower than
+    ///     64 bits, and the paths the expression uses, e.g., `NAME` or `Type::NAME`
+    ///
+    /// check_expr:
+    ///
+    /// - For each call of one of the functions above, excluding the calls generated by macros
+    ///   - Visit the size argument, the initializers of the local variables it uses, and the values
+    ///     of the constants of the crate it uses, recursively
+    ///   - Report each cast whose operand and type are integers, and the operand is wider than the
+    ///     type, unless the operand is a constant which fits in the type
+    ///
+    /// check_crate_post:
+    ///
+    /// - For each recorded `space` expression, report the field if the expression casts values to
+    ///   narrow types, or uses constants with truncating casts
+    pub ACCOUNT_SIZE_TRUNCATION,
+    Warn,
+    "casts which may truncate the size of the data of an account",
+    AccountSizeTruncation::default()
+}
+
+#[derive(Default)]
+struct AccountSizeTruncation {
+    /// Truncating casts of the values of the constants, by name (`NAME` or `Type::NAME` for
+    /// associated constants)
+    constants: FxHashMap<String, Vec<Span>>,
+    /// `space` expressions of the fields of Anchor `Accounts` structs
+    spaces: Vec<Space>,
+    /// Casts already reported, as the sizes of several calls may use the same cast
+    reported: FxHashSet<HirId>,
+}
+
+struct Space {
+    field_def_id: LocalDefId,
+    field_name: String,
+    span: Span,
+    /// The types narrower than 64 bits the expression casts values to, e.g., `u16` for
+    /// `len as u16 as usize`
+    narrow_casts: Vec<String>,
+    /// The paths the expression uses, which may be constants
+    paths: Vec<String>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for AccountSizeTruncation {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        match item.kind {
+            ItemKind::Const(_, _, body_id) => {
+                self.record_constant(cx, item.ident.to_string(), body_id);
+            }
+            ItemKind::Struct(variant, _) => {
+                if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+                    for (item_field, anchor_field) in
+                        variant.fields().iter().zip(accounts_struct.fields.iter())
+                    {
+                        if let AccountField::Field(field) = anchor_field {
+                            let spaces = field
+                                .constraints
+                                .init
+                                .as_ref()
+                                .and_then(init_space)
+                                .into_iter()
+                                .chain(
+                                    field
+                                        .constraints
+                                        .realloc
+                                        .as_ref()
+                                        .map(|realloc| &realloc.space),
+                                );
+                            for space in spaces {
+                                let mut visitor = SpaceVisitor::default();
+                                visitor.visit_expr(space);
+                                self.spaces.push(Space {
+                                    field_def_id: item_field.def_id,
+                                    field_name: field.ident.to_string(),
+                                    span: item_field.span,
+                                    narrow_casts: visitor.narrow_casts,
+                                    paths: visitor.paths,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_impl_item(&mut self, cx: &LateContext<'tcx>, impl_item: &'tcx ImplItem<'tcx>) {
+        if_chain! {
+            if let ImplItemKind::Const(_, body_id) = impl_item.kind;
+            let parent = cx.tcx.hir().get_parent_item(impl_item.hir_id());
+            if let ItemKind::Impl(impl_) = cx.tcx.hir().expect_item(parent.def_id).kind;
+            if let TyKind::Path(QPath::Resolved(None, path)) = impl_.self_ty.kind;
+            if let Some(segment) = path.segments.last();
+            then {
+                let name = format!("{}::{}", segment.ident, impl_item.ident);
+                self.record_constant(cx, name, body_id);
+            }
+        }
+    }
+
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if is_skipped_expansion(ACCOUNT_SIZE_TRUNCATION, expr.span) {
+            return;
+        }
+        let (callee, size) = match size_argument(cx, expr) {
+            Some(size_argument) => size_argument,
+            None => return,
+        };
+        let mut casts = Vec::new();
+        truncating_casts(
+            cx,
+            cx.typeck_results(),
+            size,
+            &mut casts,
+            &mut FxHashSet::default(),
+        );
+        for (hir_id, span) in casts {
+            if self.reported.insert(hir_id) {
+                report_cast(cx, hir_id, span, callee, size.span);
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for space in &self.spaces {
+            let constant_casts = space
+                .paths
+                .iter()
+                .filter_map(|path| Some((path, self.constants.get(path)?)))
+                .flat_map(|(path, spans)| spans.iter().map(move |span| (path, *span)))
+                .collect::<Vec<_>>();
+            if !space.narrow_casts.is_empty() || !constant_casts.is_empty() {
+                report_space(cx, space, &constant_casts);
+            }
+        }
+
+        sarif::write(cx, &[ACCOUNT_SIZE_TRUNCATION]);
+    }
+}
+
+impl AccountSizeTruncation {
+    /// Record the truncating casts of the value of the constant `name`, if any
+    fn record_constant(&mut self, cx: &LateContext<'_>, name: String, body_id: BodyId) {
+        let mut casts = Vec::new();
+        truncating_casts(
+            cx,
+            cx.tcx.typeck_body(body_id),
+            cx.tcx.hir().body(body_id).value,
+            &mut casts,
+            &mut FxHashSet::default(),
+        );
+        if !casts.is_empty() {
+            self.constants
+                .insert(name, casts.into_iter().map(|(_, span)| span).collect());
+        }
+    }
+}
+
+/// Finds the casts to types narrower than 64 bits and the paths of a `space` expression
+#[derive(Default)]
+struct SpaceVisitor {
+    narrow_casts: Vec<String>,
+    paths: Vec<String>,
+}
+
+impl<'ast> Visit<'ast> for SpaceVisitor {
+    fn visit_expr_cast(&mut self, cast: &'ast syn::ExprCast) {
+        if_chain! {
+            if let syn::Type::Path(type_path) = &*cast.ty;
+            if let Some(ident) = type_path.path.get_ident();
+            if let Some(bits) = narrow_type_bits(&ident.to_string());
+            if !is_literal_within(&cast.expr, bits);
+            then {
+                self.narrow_casts.push(ident.to_string());
+            }
+        }
+        syn::visit::visit_expr_cast(self, cast);
+    }
+
+    fn visit_expr_path(&mut self, expr_path: &'ast syn::ExprPath) {
+        // `NAME` or `Type::NAME`
+        let segments = &expr_path.path.segments;
+        let path = segments
+            .iter()
+            .skip(segments.len().saturating_sub(2))
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::");
+        self.paths.push(path);
+        syn::visit::visit_expr_path(self, expr_path);
+    }
+}
+
+/// Return the width of the integer type named `name` if it is narrower than 64 bits
+fn narrow_type_bits(name: &str) -> Option<u32> {
+    match name {
+        "u8" | "i8" => Some(8),
+        "u16" | "i16" => Some(16),
+        "u32" | "i32" => Some(32),
+        _ => None,
+    }
+}
+
+/// Return true if `expr` is an integer literal whose value fits in `bits` bits
+fn is_literal_within(expr: &syn::Expr, bits: u32) -> bool {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit_int),
+            ..
+        }) => lit_int
+            .base10_parse::<u128>()
+            .map_or(false, |value| value >> bits == 0),
+        syn::Expr::Paren(paren) => is_literal_within(&paren.expr, bits),
+        _ => false,
+    }
+}
+
+/// If `expr` allocates the data of an account or computes its rent, return the name of the
+/// function and the size argument:
+/// - `create_account(from, to, lamports, space, owner)` of `system_instruction`
+/// - `create_account(ctx, lamports, space, owner)` of `anchor_lang::system_program`
+/// - `create_account_with_seed(from, to, base, seed, lamports, space, owner)`
+/// - `allocate(pubkey, space)` and `allocate_with_seed(address, base, seed, space, owner)`
+/// - `rent.minimum_balance(data_len)`
+fn size_argument<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<(Symbol, &'tcx Expr<'tcx>)> {
+    match expr.kind {
+        ExprKind::Call(_, args) => {
+            let def_id = fn_def_id(cx, expr)?;
+            let index = match match_any_def_paths(
+                cx,
+                def_id,
+                &[
+                    &paths::SOLANA_PROGRAM_CREATE_ACCOUNT,
+                    &paths::ANCHOR_LANG_SYSTEM_PROGRAM_CREATE_ACCOUNT,
+                    &paths::SOLANA_PROGRAM_CREATE_ACCOUNT_WITH_SEED,
+                    &paths::SOLANA_PROGRAM_ALLOCATE,
+                    &paths::SOLANA_PROGRAM_ALLOCATE_WITH_SEED,
+                ],
+            )? {
+                0 | 4 => 3,
+                1 => 2,
+                2 => 5,
+                _ => 1,
+            };
+            Some((cx.tcx.item_name(def_id), args.get(index)?))
+        }
+        ExprKind::MethodCall(method, _, [data_len], _) => {
+            is_expr_method_call(cx, expr, &paths::SOLANA_PROGRAM_MINIMUM_BALANCE)
+                .map(|_| (method.ident.name, data_len))
+        }
+        _ => None,
+    }
+}
+
+/// Collect the casts of `expr` which may truncate an integer, i.e., whose operand is wider than
+/// their type, following the local variables and the constants of the crate `expr` uses, with
+/// their `HirId` and their span. `usize` and `isize` are considered 64 bits wide, as on SBF.
+fn truncating_casts<'tcx>(
+    cx: &LateContext<'tcx>,
+    typeck_results: &'tcx TypeckResults<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    casts: &mut Vec<(HirId, Span)>,
+    visited: &mut FxHashSet<HirId>,
+) {
+    visit_expr_no_bodies(expr, |expr| {
+        match expr.kind {
+            ExprKind::Cast(operand, _) => {
+                if is_truncating(cx, typeck_results, operand, typeck_results.expr_ty(expr)) {
+                    casts.push((expr.hir_id, expr.span));
+                }
+            }
+            ExprKind::Path(ref qpath) => match typeck_results.qpath_res(qpath, expr.hir_id) {
+                Res::Local(local) => {
+                    if_chain! {
+                        if visited.insert(local);
+                        if let Some(init) = local_init(cx, local);
+                        then {
+                            truncating_casts(cx, typeck_results, init, casts, visited);
+                        }
+                    }
+                }
+                Res::Def(DefKind::Const | DefKind::AssocConst, def_id) => {
+                    if_chain! {
+                        if let Some(local_def_id) = def_id.as_local();
+                        if visited.insert(cx.tcx.local_def_id_to_hir_id(local_def_id));
+                        if let Some(body_id) = cx.tcx.hir().maybe_body_owned_by(local_def_id);
+                        then {
+                            truncating_casts(
+                                cx,
+                                cx.tcx.typeck_body(body_id),
+                                cx.tcx.hir().body(body_id).value,
+                                casts,
+                                visited,
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        false
+    });
+}
+
+/// Return true if casting `operand` to `ty` may truncate it, i.e., if both are integers, the
+/// operand is wider than `ty`, and the operand is not a constant which fits in `ty`
+fn is_truncating<'tcx>(
+    cx: &LateContext<'tcx>,
+    typeck_results: &'tcx TypeckResults<'tcx>,
+    operand: &'tcx Expr<'tcx>,
+    ty: Ty<'tcx>,
+) -> bool {
+    match (int_bits(typeck_results.expr_ty(operand)), int_bits(ty)) {
+        (Some(operand_bits), Some(bits)) if operand_bits > bits => !matches!(
+            constant(cx, typeck_results, operand),
+            Some(Constant::Int(value)) if value >> bits == 0
+        ),
+        _ => false,
+    }
+}
+
+/// Return the width of the integer type `ty`, 64 for `usize` and `isize`
+fn int_bits(ty: Ty<'_>) -> Option<u64> {
+    match ty.kind() {
+        ty::Int(int_ty) => Some(int_ty.bit_width().unwrap_or(64)),
+        ty::Uint(uint_ty) => Some(uint_ty.bit_width().unwrap_or(64)),
+        _ => None,
+    }
+}
+
+fn report_cast(cx: &LateContext<'_>, hir_id: HirId, span: Span, callee: Symbol, size_span: Span) {
+    span_lint_hir_and_then(
+        cx,
+        ACCOUNT_SIZE_TRUNCATION,
+        hir_id,
+        span,
+        &format!("this cast may truncate the size passed to `{callee}`"),
+        |diag| {
+            if !size_span.contains(span) {
+                diag.span_note(
+                    size_span,
+                    "the size is computed from the truncated value here",
+                );
+            }
+            diag.note(
+                "a cast to a narrower integer type keeps the low bits of the value only, so the account may be smaller than its data, or funded for a smaller size",
+            );
+            diag.help(
+                "cast to a type which holds every size, e.g., `u64` or `usize`, or convert with `try_from` and return an error if the size does not fit",
+            );
+        },
+    );
+}
+
+fn report_space(cx: &LateContext<'_>, space: &Space, constant_casts: &[(&String, Span)]) {
+    span_lint_hir_and_then(
+        cx,
+        ACCOUNT_SIZE_TRUNCATION,
+        cx.tcx.local_def_id_to_hir_id(space.field_def_id),
+        space.span,
+        &format!(
+            "the `space` of `{}` is computed with casts which may truncate it",
+            space.field_name
+        ),
+        |diag| {
+            for (name, span) in constant_casts {
+                diag.span_note(
+                    *span,
+                    format!("the constant `{name}` is computed with this truncating cast"),
+                );
+            }
+            for ty in &space.narrow_casts {
+                diag.note(format!(
+                    "the `space` expression casts a value to `{ty}`, which keeps its low bits only"
+                ));
+            }
+            diag.help(
+                "compute the size in `usize`, converting the narrower values with `usize::from`",
+            );
+        },
+    );
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
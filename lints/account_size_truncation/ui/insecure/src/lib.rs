@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke_signed, system_instruction};
+use anchor_lang::system_program::{self, CreateAccount};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const MAX_NAME_LEN: u64 = 300;
+const NAME_SPACE: usize = 4 + MAX_NAME_LEN as u8 as usize;
+
+#[program]
+pub mod account_size_truncation_insecure {
+    use super::*;
+
+    pub fn initialize(_ctx: Context<Initialize>, _len: u64) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn create_buffer(ctx: Context<CreateBuffer>, len: u64, bump: u8) -> Result<()> {
+        let space = len as u32 as u64;
+        let lamports = Rent::get()?.minimum_balance(len as usize);
+        let ix = system_instruction::create_account(
+            ctx.accounts.payer.key,
+            ctx.accounts.buffer.key,
+            lamports,
+            space,
+            ctx.program_id,
+        );
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.buffer.to_account_info(),
+            ],
+            &[&[b"buffer", &[bump]]],
+        )?;
+        Ok(())
+    }
+
+    pub fn create_log(ctx: Context<CreateLog>, len: u128) -> Result<()> {
+        let lamports = Rent::get()?.minimum_balance(len as usize);
+        system_program::create_account(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                CreateAccount {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.log.to_account_info(),
+                },
+            ),
+            lamports,
+            u64::from(len as u32),
+            ctx.program_id,
+        )
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(len: u64)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + 4 + len as u16 as usize)]
+    log: Account<'info, Log>,
+    #[account(init, payer = payer, space = 8 + NAME_SPACE)]
+    profile: Account<'info, Profile>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBuffer<'info> {
+    /// CHECK: created by the instruction
+    #[account(mut)]
+    buffer: UncheckedAccount<'info>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLog<'info> {
+    #[account(mut)]
+    log: Signer<'info>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct Log {
+    entries: Vec<u8>,
+}
+
+#[account]
+pub struct Profile {
+    name: String,
+}
+
+#[allow(dead_code)]
+fn main() {}
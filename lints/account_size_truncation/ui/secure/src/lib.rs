@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke_signed, system_instruction};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const MAX_NAME_LEN: u64 = 300;
+const NAME_SPACE: usize = 4 + MAX_NAME_LEN as usize;
+const TAG: u8 = 255u64 as u8;
+
+#[program]
+pub mod account_size_truncation_secure {
+    use super::*;
+
+    pub fn initialize(_ctx: Context<Initialize>, _len: u16) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn create_buffer(ctx: Context<CreateBuffer>, len: usize, bump: u8) -> Result<()> {
+        let space = len as u64;
+        let lamports = Rent::get()?.minimum_balance(len + usize::from(TAG));
+        let ix = system_instruction::create_account(
+            ctx.accounts.payer.key,
+            ctx.accounts.buffer.key,
+            lamports,
+            space,
+            ctx.program_id,
+        );
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.payer.to_account_info(),
+                ctx.accounts.buffer.to_account_info(),
+            ],
+            &[&[b"buffer", &[bump]]],
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(len: u16)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + 4 + usize::from(len))]
+    log: Account<'info, Log>,
+    #[account(init, payer = payer, space = 8 + NAME_SPACE + 32 as u8 as usize)]
+    profile: Account<'info, Profile>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBuffer<'info> {
+    /// CHECK: created by the instruction
+    #[account(mut)]
+    buffer: UncheckedAccount<'info>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct Log {
+    entries: Vec<u8>,
+}
+
+#[account]
+pub struct Profile {
+    name: String,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -2,24 +2,38 @@
 #![feature(box_patterns)]
 #![warn(unused_extern_crates)]
 
-use clippy_utils::{diagnostics::span_lint, match_any_def_paths, match_def_path};
+use clippy_utils::{match_any_def_paths, match_def_path};
 use if_chain::if_chain;
 use rustc_hir::Body;
 use rustc_lint::{LateContext, LateLintPass};
 use rustc_middle::{
     mir,
     mir::{
-        AggregateKind, BasicBlock, Local, Operand, Place, Rvalue, Statement, StatementKind,
+        BasicBlock, Local, Location, Operand, Place, ProjectionElem, Rvalue, StatementKind,
         TerminatorKind,
     },
-    ty::{self, TyKind},
+    ty::TyKind,
+};
+use serde::Deserialize;
+use solana_lints::{
+    diagnostics::{span_lint_hir_with_confidence, Confidence},
+    expansion::is_skipped_expansion,
+    known_programs::{is_known_program_id_call, is_known_program_id_operand},
+    mir_cache::{BodyAnalysis, MirCache},
+    paths::{self, anchor_wrapper, AnchorWrapper},
+    sarif,
+    utils::{
+        cpi::{instruction_invoke_blocks, is_instruction_init_stmt, is_same_place},
+        dataflow::{assigns_to, rvalue_source, CheckedBlocks},
+        interprocedural::calls_comparing,
+        mir_lint_root,
+    },
 };
-use solana_lints::paths;
 
 extern crate rustc_hir;
 extern crate rustc_middle;
 
-dylint_linting::declare_late_lint! {
+solana_lints::impl_late_lint! {
     /// **What it does:**
     /// Finds uses of solana_program::program::invoke that do not check the program_id
     ///
@@ -31,11 +45,28 @@ dylint_linting::declare_late_lint! {
     /// - [x] Anchor
     /// - [x] Non Anchor
     ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
     /// **Known problems:**
-    /// False positives, since the program_id check may be within some other function (does not
-    /// trace through function calls)
-    /// False negatives, since our analysis is not path-sensitive (the program_id check may not
-    /// occur in all possible execution paths)
+    /// False positives, since the program_id check may be within some other function which is not
+    /// defined in the crate, or which is more than three calls deep (see
+    /// `solana_lints::utils::interprocedural`). Hence the findings on `Instruction`s have medium
+    /// confidence. The findings on `CpiContext`s have low confidence, as the lint does not look
+    /// for checks of the program account passed to `CpiContext::new` (see
+    /// `solana_lints::diagnostics::Confidence`).
+    ///
+    /// **Configuration:**
+    ///
+    /// A program_id which is the ID of a well-known program (see `solana_lints::known_programs`),
+    /// e.g., `spl_token::ID` or `system_program::id()`, is safe. The IDs of other programs can be
+    /// added in `dylint.toml`:
+    ///
+    /// ```toml
+    /// [arbitrary_cpi]
+    /// known_programs = ["my_program::ID", "my_program::id"]
+    /// ```
     ///
     /// **Example:**
     ///
@@ -70,30 +101,59 @@ dylint_linting::declare_late_lint! {
     /// - For every function
     ///   - For every statement in the function initializing `Instruction {..}`
     ///     - Get the place being assigned to `program_id` field
+    ///     - Follow the assignments to `program_id` backwards, on every path to the statement. If
+    ///       on every path it is assigned the ID of a known program, a constant or the result of
+    ///       calling its `id` function, ignore the call to `invoke`.
     ///     - find all the aliases of `program_id`. Use the rhs of the assignment as initial
     ///       alias and look for all assignments assigning to the locals recursively.
-    ///     - If `program_id` is compared using any of aliases ignore the call to `invoke`.
-    ///       - Look for calls to `core::cmp::PartialEq{ne, eq}` where one of arg is moved
-    ///         from an alias.
-    ///       - If one of the arg accesses `program_id` and if the basic block containing the
-    ///         comparison dominates the basic block containing call to `invoke` ensuring the
-    ///         `program_id` is checked in all execution paths Then ignore the call to `invoke`.
-    ///       - Else report the statement initializing `Instruction`.
-    ///     - Else report the statement initializing `Instruction`.
+    ///     - The `program_id` field of the `Instruction`, e.g., `&ix.program_id`, is an alias as well.
+    ///     - Find the calls to `invoke` or `invoke_signed` with the `Instruction` which the
+    ///       statement dominates. If there is none, use the statement instead.
+    ///     - Find the checks of `program_id`: the calls to `core::cmp::PartialEq{ne, eq}` where
+    ///       one of arg is moved from an alias, and the calls passing one of the aliases to a
+    ///       function of the crate which compares it.
+    ///     - A call to `invoke` is checked if a check is made on every execution path to the call
+    ///       (see `solana_lints::utils::dataflow`), e.g., a check in only one branch of an `if`
+    ///       before the call is not enough, but checks in both branches are.
+    ///     - If every call to `invoke` is checked, ignore the statement, else report it.
     ///   - For every call to `CpiContext::new` or `CpiContext::new_with_signer`
     ///     - Get the place of the first argument (program's account info)
     ///     - find all aliases of `program's` place.
+    ///       - follow assignments, struct fields initialized with the program (`S { program: x, .. }.program`),
+    ///         and calls returning the same account (`x.clone()`, `x.to_account_info()` on an `AccountInfo`)
     ///     - If the `program` is a result of calling `to_account_info` on Anchor `Program`/`Interface`
     ///       - continue
     ///     - Else report the call to `CpiContext::new`/`CpiContext::new_with_signer`
     pub ARBITRARY_CPI,
     Warn,
-    "Finds unconstrained inter-contract calls"
+    "Finds unconstrained inter-contract calls",
+    ArbitraryCpi::new()
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Config {
+    /// The paths of the IDs of programs, in addition to `solana_lints::known_programs`
+    known_programs: Vec<String>,
+}
+
+struct ArbitraryCpi {
+    config: Config,
+    mir_cache: MirCache,
+}
+
+impl ArbitraryCpi {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+            mir_cache: MirCache::default(),
+        }
+    }
 }
 
 impl<'tcx> LateLintPass<'tcx> for ArbitraryCpi {
     fn check_body(&mut self, cx: &LateContext<'tcx>, body: &'tcx Body<'tcx>) {
-        if body.value.span.from_expansion() {
+        if is_skipped_expansion(ARBITRARY_CPI, body.value.span) {
             return;
         }
         let hir_map = cx.tcx.hir();
@@ -104,6 +164,7 @@ impl<'tcx> LateLintPass<'tcx> for ArbitraryCpi {
             return;
         }
         let body_mir = cx.tcx.optimized_mir(body_did);
+        let analysis = self.mir_cache.analysis(cx, body_did);
         // list of block id and the terminator of the basic blocks in the CFG
         for (block_id, block_data) in body_mir.basic_blocks.iter_enumerated() {
             // find the Instruction {...} initialization statements and check if program id is validated
@@ -111,16 +172,30 @@ impl<'tcx> LateLintPass<'tcx> for ArbitraryCpi {
             // the lint will not report when the `Instruction` of `invoke` calls is returned by a function defined in a dependency.
             // However, if the `Instruction` is returned by a function defined in this crate then that function will get checked by the
             // lint and the statement initializing the `Instruction` will be reported.
-            for stmt in &block_data.statements {
+            for (statement_index, stmt) in block_data.statements.iter().enumerate() {
                 if_chain! {
-                    if let Some(program_id_place) = is_instruction_init_stmt(cx, stmt);
-                    if !is_program_id_verified(cx, body_mir, block_id, &program_id_place);
+                    if let Some((instruction_place, program_id_place)) =
+                        is_instruction_init_stmt(cx, stmt);
+                    if !is_program_id_verified(
+                        cx,
+                        body_mir,
+                        analysis,
+                        Location {
+                            block: block_id,
+                            statement_index,
+                        },
+                        &program_id_place,
+                        &instruction_place,
+                        &self.config.known_programs,
+                    );
                     then {
-                        span_lint(
+                        span_lint_hir_with_confidence(
                             cx,
                             ARBITRARY_CPI,
+                            mir_lint_root(body_mir, stmt.source_info),
                             stmt.source_info.span,
                             "program_id may not be checked",
+                            Confidence::Medium,
                         )
                     }
                 }
@@ -147,41 +222,24 @@ impl<'tcx> LateLintPass<'tcx> for ArbitraryCpi {
                     )
                     .is_some();
                     if let Operand::Move(program_place) = &args[0];
-                    if !is_program_safe_account_info(cx, body_mir, block_id, program_place);
+                    if !is_program_safe_account_info(cx, body_mir, analysis, block_id, program_place);
                     then {
-                        span_lint(
+                        span_lint_hir_with_confidence(
                             cx,
                             ARBITRARY_CPI,
+                            mir_lint_root(body_mir, t.source_info),
                             t.source_info.span,
                             "program_id may not be checked",
+                            Confidence::Low,
                         )
                     }
                 }
             }
         }
     }
-}
 
-/// Return the place of program id if the statement initializes Instruction i.e stmt is _x = Instruction {...}
-fn is_instruction_init_stmt<'tcx>(cx: &LateContext, stmt: &Statement<'tcx>) -> Option<Place<'tcx>> {
-    if_chain! {
-        if let StatementKind::Assign(box (_, rvalue)) = &stmt.kind;
-        // The MIR generated for the `insecure-2` and other programs shows that the entire struct is initialized at once.
-        // Note: Its unknown in what cases the struct initialization is deaggregated. Assuming here that
-        // the struct is initialized at once till a counter example is found.
-        if let Rvalue::Aggregate(box AggregateKind::Adt(def_id, variant_idx, _, _, _), fields) =
-            rvalue;
-        // The Adt is a struct
-        if variant_idx.index() == 0;
-        // The struct is `solana_program::instruction::Instruction`
-        if match_def_path(cx, *def_id, &paths::SOLANA_PROGRAM_INSTRUCTION);
-        // program id is the first field. Assuming its operand is at the start of the fields IndexVec.
-        if let Some(Operand::Move(pl) | Operand::Copy(pl)) = fields.iter().next();
-        then {
-            Some(*pl)
-        } else {
-            None
-        }
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[ARBITRARY_CPI]);
     }
 }
 
@@ -189,52 +247,204 @@ fn is_instruction_init_stmt<'tcx>(cx: &LateContext, stmt: &Statement<'tcx>) -> O
 ///
 /// The `program_id` is the place of operand used to initialize `Instruction`:
 ///   - `let _x = Instruction { program_id: program_id_place, accounts: _, data: _ }`
+///
+/// The program id must be checked on every path to every call to `invoke` or `invoke_signed` with
+/// the `Instruction` which the initialization dominates, and not only before the initialization,
+/// as it can be checked in between. If there is no such call, e.g., the `Instruction` is returned,
+/// it must be checked on every path to the initialization.
 fn is_program_id_verified<'tcx>(
-    cx: &LateContext,
+    cx: &LateContext<'tcx>,
     body: &'tcx mir::Body<'tcx>,
-    block_id: BasicBlock,
+    analysis: &BodyAnalysis,
+    location: Location,
     program_id_place: &Place<'tcx>,
+    instruction_place: &Place<'tcx>,
+    known_programs: &[String],
 ) -> bool {
-    let program_id_aliases = find_place_aliases(body, block_id, program_id_place);
+    if is_known_program_id_value(
+        cx,
+        body,
+        analysis,
+        location,
+        program_id_place,
+        known_programs,
+    ) {
+        return true;
+    }
+    let program_id_aliases =
+        analysis.find_place_aliases(cx, body, location.block, program_id_place);
+    let instruction_locals = if instruction_place.projection.is_empty() {
+        analysis.reference_locals(instruction_place.local)
+    } else {
+        Vec::new()
+    };
+    // The program id can also be checked through the `Instruction`, e.g., `ix.program_id`
+    let program_id_places: Vec<Place<'tcx>> = program_id_aliases
+        .into_iter()
+        .chain(
+            instruction_program_id_locals(body, &instruction_locals)
+                .into_iter()
+                .map(Place::from),
+        )
+        .collect();
     let likely_program_id_locals: Vec<Local> =
-        program_id_aliases.iter().map(|pl| pl.local).collect();
-    is_programid_checked(cx, body, block_id, likely_program_id_locals.as_ref())
+        program_id_places.iter().map(|pl| pl.local).collect();
+    let dominators = body.basic_blocks.dominators();
+    let mut invoke_locations: Vec<Location> =
+        instruction_invoke_blocks(cx, body, &instruction_locals)
+            .into_iter()
+            .filter(|&invoke_block| dominators.dominates(location.block, invoke_block))
+            .map(|invoke_block| body.terminator_loc(invoke_block))
+            .collect();
+    if invoke_locations.is_empty() {
+        invoke_locations.push(location);
+    }
+    // The program id is compared in the function, or passed to a function of the crate which
+    // compares it, e.g., `validate_program_id(&program_id)?` or
+    // `assert_program_id(&ix.program_id)?`
+    let mut checks = programid_checks(cx, body, analysis, likely_program_id_locals.as_ref());
+    checks.extend(
+        program_id_places
+            .iter()
+            .flat_map(|place| calls_comparing(cx, body.source.def_id(), *place)),
+    );
+    let checked_blocks = CheckedBlocks::new(body, checks);
+    invoke_locations
+        .iter()
+        .all(|&invoke_location| checked_blocks.is_checked_at(invoke_location))
+}
+
+/// Return the locals of `body` assigned the `program_id` field of the `Instruction` in one of
+/// `instruction_locals`, or a reference to it, e.g., `_5 = &(_3.0)` or `_5 = &((*_4).0)`
+fn instruction_program_id_locals(body: &mir::Body<'_>, instruction_locals: &[Local]) -> Vec<Local> {
+    // `ix.program_id` or `(*ix_ref).program_id`; `program_id` is the first field of `Instruction`
+    let is_program_id_field = |place: &Place<'_>| {
+        instruction_locals.contains(&place.local)
+            && matches!(
+                place.projection.as_slice(),
+                [ProjectionElem::Field(field, _)]
+                    | [ProjectionElem::Deref, ProjectionElem::Field(field, _)]
+                    if field.index() == 0
+            )
+    };
+    body.basic_blocks
+        .iter()
+        .flat_map(|block_data| &block_data.statements)
+        .filter_map(|stmt| match &stmt.kind {
+            StatementKind::Assign(box (place, rvalue))
+                if rvalue_source(rvalue).map_or(false, is_program_id_field) =>
+            {
+                Some(place.local)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Return true if the value of `program_id_place` used at `location`, the initialization of an
+/// `Instruction`, is the ID of a known program on every path to `location`, i.e., it is assigned
+/// the ID constant of the program or the result of calling its `id` function (see
+/// `solana_lints::known_programs`), possibly through copies, moves and borrows
+///
+/// The assignments are followed backwards through the predecessors returned by
+/// `dataflow::backward_predecessors`, cached by `analysis`. A path along which the value comes from
+/// anything else, e.g., an argument of the function or an account, makes the value unknown, so
+/// that a known ID assigned in one branch of an `if` only does not make the `Instruction` safe.
+fn is_known_program_id_value<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    analysis: &BodyAnalysis,
+    location: Location,
+    program_id_place: &Place<'tcx>,
+    known_programs: &[String],
+) -> bool {
+    let mut worklist = vec![(location.block, location.statement_index, *program_id_place)];
+    let mut visited = Vec::new();
+    'paths: while let Some((block, end, mut place)) = worklist.pop() {
+        for stmt in body.basic_blocks[block].statements[..end].iter().rev() {
+            if_chain! {
+                if let StatementKind::Assign(box (assigned, rvalue)) = &stmt.kind;
+                if assigns_to(assigned, &place);
+                then {
+                    if let Rvalue::Use(operand) = rvalue {
+                        if is_known_program_id_operand(cx, operand, known_programs) {
+                            continue 'paths;
+                        }
+                    }
+                    place = match rvalue_source(rvalue) {
+                        Some(source) => *source,
+                        None => return false,
+                    };
+                }
+            }
+        }
+        let predecessors = analysis.backward_predecessors(block);
+        // The start of the body: the value is an argument of the function, or derived from one
+        if predecessors.is_empty() {
+            return false;
+        }
+        for &predecessor in predecessors {
+            let terminator = body.basic_blocks[predecessor].terminator();
+            if let TerminatorKind::Call { destination, .. } = &terminator.kind {
+                if assigns_to(destination, &place) {
+                    if is_known_program_id_call(cx, terminator, known_programs) {
+                        continue;
+                    }
+                    return false;
+                }
+            }
+            if !visited.contains(&(predecessor, place)) {
+                visited.push((predecessor, place));
+                worklist.push((
+                    predecessor,
+                    body.basic_blocks[predecessor].statements.len(),
+                    place,
+                ));
+            }
+        }
+    }
+    true
 }
 
 /// Given the place corresponding to `program` account info, return true if the `AccountInfo` is of a `Program`.
 fn is_program_safe_account_info<'tcx>(
     cx: &LateContext<'tcx>,
     body: &'tcx mir::Body<'tcx>,
+    analysis: &BodyAnalysis,
     block_id: BasicBlock,
     program_place: &Place<'tcx>,
 ) -> bool {
-    let program_aliases = find_place_aliases(body, block_id, program_place);
+    let program_aliases = analysis.find_place_aliases(cx, body, block_id, program_place);
     // This function at the moment only checks if the program is a result of calling `to_account_info`.
     // The aliases returned by `find_place_aliases` are of form where there is an assignment statement `alias[i] = alias[i+1]`.
     // As we are only looking for `to_account_info` calls, it is sufficient to check for assignment to the last alias.
-    let program = program_aliases.last().unwrap();
+    let program = match program_aliases.last() {
+        Some(program) => program,
+        None => return false,
+    };
 
-    for (_, block_data) in body.basic_blocks.iter_enumerated() {
-        match &block_data.terminator.as_ref().unwrap().kind {
+    for block_data in body.basic_blocks.iter() {
+        let terminator = match &block_data.terminator {
+            Some(terminator) => terminator,
+            None => continue,
+        };
+        match &terminator.kind {
             TerminatorKind::Call {
                 func: mir::Operand::Constant(box func),
                 destination: dest,
                 args,
                 ..
-            } if dest.local_or_deref_local() == program.local_or_deref_local() => {
+            } if is_same_place(dest, program) => {
                 if_chain! {
                     // the func is a call to `.to_account_info()` on type `Program` or `Interface`
                     if let TyKind::FnDef(def_id, _) = func.const_.ty().kind();
                     if match_def_path(cx, *def_id, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO);
                     if !args.is_empty();
                     if let Operand::Copy(arg0_pl) | Operand::Move(arg0_pl) = &args[0];
-                    if let ty::Adt(adt_def, _) = arg0_pl.ty(body, cx.tcx).ty.peel_refs().kind();
-                    if match_any_def_paths(
-                        cx,
-                        adt_def.did(),
-                        &[&paths::ANCHOR_LANG_PROGRAM, &paths::ANCHOR_LANG_INTERFACE],
-                    )
-                    .is_some();
+                    if matches!(
+                        anchor_wrapper(cx, arg0_pl.ty(body, cx.tcx).ty.peel_refs()),
+                        Some(AnchorWrapper::Program | AnchorWrapper::Interface)
+                    );
                     then {
                         // The program is a result of calling `to_account_info` on `Program` or `Interface`
                         return true;
@@ -247,59 +457,19 @@ fn is_program_safe_account_info<'tcx>(
     false
 }
 
-/// Given a place, find other places which are an alias to this place
-fn find_place_aliases<'tcx>(
-    body: &'tcx mir::Body<'tcx>,
-    block: BasicBlock,
-    mut id_arg: &Place<'tcx>,
-) -> Vec<Place<'tcx>> {
-    let preds = body.basic_blocks.predecessors();
-    let mut cur_block = block;
-    let mut likely_program_id_aliases = Vec::<Place>::new();
-    likely_program_id_aliases.push(*id_arg);
-    loop {
-        // check every stmt
-        for stmt in body.basic_blocks[cur_block].statements.iter().rev() {
-            match &stmt.kind {
-                // if the statement assigns to `inst_arg`, update `inst_arg` to the rhs
-                StatementKind::Assign(box (assign_place, rvalue))
-                    if assign_place.local_or_deref_local() == id_arg.local_or_deref_local() =>
-                {
-                    if let Rvalue::Use(Operand::Copy(pl) | Operand::Move(pl))
-                    | Rvalue::Ref(_, _, pl) = rvalue
-                    {
-                        id_arg = pl;
-                        likely_program_id_aliases.push(*pl);
-                    }
-                }
-                _ => {}
-            }
-        }
-        match preds.get(cur_block) {
-            Some(cur_preds) if !cur_preds.is_empty() => cur_block = cur_preds[0],
-            _ => {
-                break;
-            }
-        }
-    }
-    likely_program_id_aliases
-}
-
-// This function takes the list of programid_locals and a starting block, and searches for a
-// check elsewhere in the Body that would compare the program_id with something else.
-fn is_programid_checked<'tcx>(
+/// Return the locations of the calls to `core::cmp::PartialEq{ne, eq}` in `body` comparing one of
+/// `programid_locals` with something else
+fn programid_checks<'tcx>(
     cx: &LateContext,
     body: &'tcx mir::Body<'tcx>,
-    block: BasicBlock,
+    analysis: &BodyAnalysis,
     programid_locals: &[Local],
-) -> bool {
-    let preds = body.basic_blocks.predecessors();
-    let mut cur_block = block;
-    loop {
-        // check every statement
+) -> Vec<Location> {
+    let mut checks = Vec::new();
+    for (block_id, block_data) in body.basic_blocks.iter_enumerated() {
         if_chain! {
             // is terminator a call `core::cmp::PartialEq{ne, eq}`?
-            if let Some(t) = &body.basic_blocks[cur_block].terminator;
+            if let Some(t) = &block_data.terminator;
             if let TerminatorKind::Call {
                 func: func_operand,
                 args,
@@ -312,78 +482,16 @@ fn is_programid_checked<'tcx>(
             // check if any of the args accesses program_id
             if let Operand::Copy(arg0_pl) | Operand::Move(arg0_pl) = args[0];
             if let Operand::Copy(arg1_pl) | Operand::Move(arg1_pl) = args[1];
+            // if either arg0 or arg1 came from one of the programid_locals, then we know
+            // this eq/ne check was operating on the program_id.
+            if analysis.is_moved_from(body, block_id, &arg0_pl, programid_locals)
+                || analysis.is_moved_from(body, block_id, &arg1_pl, programid_locals);
             then {
-                // if either arg0 or arg1 came from one of the programid_locals, then we know
-                // this eq/ne check was operating on the program_id.
-                if is_moved_from(cx, body, cur_block, &arg0_pl, programid_locals)
-                    || is_moved_from(cx, body, cur_block, &arg1_pl, programid_locals)
-                {
-                    // we found the check. if it dominates the call to invoke, then the check
-                    // is assumed to be sufficient!
-                    return body.basic_blocks.dominators().dominates(cur_block, block);
-                }
-            }
-        }
-
-        match preds.get(cur_block) {
-            Some(cur_preds) if !cur_preds.is_empty() => cur_block = cur_preds[0],
-            _ => {
-                break;
+                checks.push(body.terminator_loc(block_id));
             }
         }
     }
-    false
-}
-
-// helper function
-// Given the Place search_place, check if it was defined using one of the locals in search_list
-fn is_moved_from<'tcx>(
-    _: &LateContext,
-    body: &'tcx mir::Body<'tcx>,
-    block: BasicBlock,
-    mut search_place: &Place<'tcx>,
-    search_list: &[Local],
-) -> bool {
-    let preds = body.basic_blocks.predecessors();
-    let mut cur_block = block;
-    if let Some(search_loc) = search_place.local_or_deref_local() {
-        if search_list.contains(&search_loc) {
-            return true;
-        }
-    }
-    // look for chain of assign statements whose value is eventually assigned to the `search_place` and
-    // see if any of the intermediate local is in the search_list.
-    loop {
-        for stmt in body.basic_blocks[cur_block].statements.iter().rev() {
-            match &stmt.kind {
-                StatementKind::Assign(box (assign_place, rvalue))
-                    if assign_place.local_or_deref_local()
-                        == search_place.local_or_deref_local() =>
-                {
-                    match rvalue {
-                        Rvalue::Use(Operand::Copy(rvalue_place) | Operand::Move(rvalue_place))
-                        | Rvalue::Ref(_, _, rvalue_place) => {
-                            search_place = rvalue_place;
-                            if let Some(search_loc) = search_place.local_or_deref_local() {
-                                if search_list.contains(&search_loc) {
-                                    return true;
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-                _ => {}
-            }
-        }
-        match preds.get(cur_block) {
-            Some(cur_preds) if !cur_preds.is_empty() => cur_block = cur_preds[0],
-            _ => {
-                break;
-            }
-        }
-    }
-    false
+    checks
 }
 
 // We do not test the sealevel-attacks 'insecure' example, because it calls
@@ -395,11 +503,56 @@ fn insecure_2() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-2");
 }
 
+#[test]
+fn insecure_3() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-3");
+}
+
+#[test]
+fn insecure_4() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-4");
+}
+
 #[test]
 fn secure() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
 }
 
+#[test]
+fn secure_2() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-2");
+}
+
+#[test]
+fn secure_3() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-3");
+}
+
+#[test]
+fn secure_4() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-4");
+}
+
+#[test]
+fn secure_5() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-5");
+}
+
+#[test]
+fn secure_6() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-6");
+}
+
+#[test]
+fn secure_7() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-7");
+}
+
+#[test]
+fn secure_8() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-8");
+}
+
 #[test]
 fn recommended() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "recommended");
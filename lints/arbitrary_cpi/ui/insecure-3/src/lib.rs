@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod arbitrary_cpi_insecure_3 {
+    use super::*;
+
+    pub fn cpi(ctx: Context<Cpi>) -> ProgramResult {
+        let ix = Instruction {
+            program_id: *ctx.accounts.token_program.key,
+            accounts: vec![],
+            data: vec![],
+        };
+        // `log_program_id` does not compare the program ID
+        log_program_id(&ix.program_id);
+        solana_program::program::invoke(&ix, &[ctx.accounts.source.clone()])
+    }
+
+    pub fn cpi_checked_after(ctx: Context<Cpi>) -> ProgramResult {
+        let ix = Instruction {
+            program_id: *ctx.accounts.token_program.key,
+            accounts: vec![],
+            data: vec![],
+        };
+        // The program ID is compared after the call to `invoke`
+        solana_program::program::invoke(&ix, &[ctx.accounts.source.clone()])?;
+        assert_program_id(&ix.program_id)
+    }
+}
+
+fn log_program_id(program_id: &Pubkey) {
+    msg!("invoking {}", program_id);
+}
+
+fn assert_program_id(program_id: &Pubkey) -> ProgramResult {
+    if *program_id != spl_token::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Cpi<'info> {
+    source: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
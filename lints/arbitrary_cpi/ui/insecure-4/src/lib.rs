@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod arbitrary_cpi_insecure_4 {
+    use super::*;
+
+    pub fn cpi(ctx: Context<Cpi>, strict: bool) -> ProgramResult {
+        let program_id = ctx.accounts.token_program.key;
+        let ix = Instruction {
+            program_id: *program_id,
+            accounts: vec![],
+            data: vec![],
+        };
+        // The program ID is only compared on one path to `invoke`
+        if strict && *program_id != spl_token::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        solana_program::program::invoke(&ix, &[ctx.accounts.source.clone()])
+    }
+
+    pub fn cpi_checked_after(ctx: Context<Cpi>) -> ProgramResult {
+        let ix = Instruction {
+            program_id: *ctx.accounts.token_program.key,
+            accounts: vec![],
+            data: vec![],
+        };
+        solana_program::program::invoke(&ix, &[ctx.accounts.source.clone()])?;
+        // The program ID of the instruction is compared after `invoke`
+        if ix.program_id != spl_token::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Ok(())
+    }
+
+    pub fn cpi_known_on_one_path(ctx: Context<Cpi>, native: bool) -> ProgramResult {
+        // The program ID is the ID of a known program on one path to the `Instruction` only
+        let program_id = if native {
+            spl_token::ID
+        } else {
+            *ctx.accounts.token_program.key
+        };
+        let ix = Instruction {
+            program_id,
+            accounts: vec![],
+            data: vec![],
+        };
+        solana_program::program::invoke(&ix, &[ctx.accounts.source.clone()])
+    }
+}
+
+#[derive(Accounts)]
+pub struct Cpi<'info> {
+    source: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
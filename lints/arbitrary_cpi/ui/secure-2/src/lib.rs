@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod arbitrary_cpi_secure_2 {
+    use super::*;
+
+    pub fn pay(ctx: Context<Pay>, amount: u64) -> Result<()> {
+        // The program is cloned before it is passed to `CpiContext::new`
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let cpi_ctx = CpiContext::new(
+            system_program.clone(),
+            Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.recipient.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, amount)?;
+
+        // The program is stored in a local struct before it is passed to `CpiContext::new`
+        let programs = Programs { system_program };
+        let cpi_ctx = CpiContext::new(
+            programs.system_program,
+            Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.recipient.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, amount)
+    }
+}
+
+struct Programs<'info> {
+    system_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Pay<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut)]
+    recipient: SystemAccount<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[allow(dead_code)]
+fn main() {}
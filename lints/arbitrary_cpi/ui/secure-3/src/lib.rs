@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    system_program,
+};
+use anchor_spl::token::spl_token;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod arbitrary_cpi_secure_3 {
+    use super::*;
+
+    pub fn sync_native(ctx: Context<SyncNative>) -> Result<()> {
+        // The program ID is returned by the `id` function of a known program
+        let ix = Instruction {
+            program_id: spl_token::id(),
+            accounts: vec![AccountMeta::new(ctx.accounts.native.key(), false)],
+            data: vec![17],
+        };
+        invoke(&ix, &[ctx.accounts.native.to_account_info()])?;
+
+        // The program ID is the ID constant of a known program
+        let program_id = system_program::ID;
+        let ix = Instruction {
+            program_id,
+            accounts: vec![AccountMeta::new(ctx.accounts.payer.key(), true)],
+            data: vec![0; 12],
+        };
+        invoke(&ix, &[ctx.accounts.payer.to_account_info()])?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SyncNative<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(mut)]
+    native: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
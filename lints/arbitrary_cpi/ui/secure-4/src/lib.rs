@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod arbitrary_cpi_secure_4 {
+    use super::*;
+
+    pub fn cpi(ctx: Context<Cpi>) -> ProgramResult {
+        let program_id = *ctx.accounts.token_program.key;
+        // The program ID is compared by a function called by `validate_program_id`
+        validate_program_id(&program_id)?;
+        let ins = Instruction {
+            program_id,
+            accounts: vec![],
+            data: vec![],
+        };
+        solana_program::program::invoke(&ins, &[ctx.accounts.source.clone()])
+    }
+}
+
+fn validate_program_id(program_id: &Pubkey) -> ProgramResult {
+    check_token_program(program_id)
+}
+
+fn check_token_program(program_id: &Pubkey) -> ProgramResult {
+    if *program_id != spl_token::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Cpi<'info> {
+    source: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod arbitrary_cpi_secure_6 {
+    use super::*;
+
+    pub fn cpi(ctx: Context<Cpi>) -> ProgramResult {
+        let program_id = ctx.accounts.token_program.key;
+        let ix = Instruction {
+            program_id: *program_id,
+            accounts: vec![],
+            data: vec![],
+        };
+        // The program ID is compared after the construction of the instruction, before `invoke`
+        if *program_id != spl_token::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        solana_program::program::invoke(&ix, &[ctx.accounts.source.clone()])
+    }
+
+    pub fn cpi_instruction_program_id(ctx: Context<Cpi>) -> ProgramResult {
+        let ix = Instruction {
+            program_id: *ctx.accounts.token_program.key,
+            accounts: vec![],
+            data: vec![],
+        };
+        // The program ID of the instruction is compared before `invoke`
+        if ix.program_id != spl_token::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        solana_program::program::invoke(&ix, &[ctx.accounts.source.clone()])
+    }
+}
+
+#[derive(Accounts)]
+pub struct Cpi<'info> {
+    source: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
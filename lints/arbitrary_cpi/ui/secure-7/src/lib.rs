@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod arbitrary_cpi_secure_7 {
+    use super::*;
+
+    pub fn cpi(ctx: Context<Cpi>, strict: bool) -> ProgramResult {
+        let program_id = ctx.accounts.token_program.key;
+        // The program ID is compared in both branches, so on every path to `invoke`, although
+        // neither comparison dominates it
+        if strict {
+            if *program_id != spl_token::ID {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+        } else {
+            msg!("checking the token program");
+            if *program_id != spl_token::ID {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+        }
+        let ix = Instruction {
+            program_id: *program_id,
+            accounts: vec![],
+            data: vec![],
+        };
+        solana_program::program::invoke(&ix, &[ctx.accounts.source.clone()])
+    }
+}
+
+#[derive(Accounts)]
+pub struct Cpi<'info> {
+    source: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
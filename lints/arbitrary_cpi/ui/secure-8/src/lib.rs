@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+use std::convert::TryFrom;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod arbitrary_cpi_secure_8 {
+    use super::*;
+
+    pub fn cpi(ctx: Context<Cpi>, amount: u64) -> ProgramResult {
+        let program_id = ctx.accounts.token_program.key;
+        if *program_id != spl_token::ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        // The `?` operators between the check and `invoke` return early
+        let amount = amount.checked_mul(2).ok_or(ProgramError::InvalidArgument)?;
+        let len = ctx.accounts.source.try_borrow_data()?.len();
+        let ix = Instruction {
+            program_id: *program_id,
+            accounts: vec![],
+            data: [amount.to_le_bytes(), (len as u64).to_le_bytes()].concat(),
+        };
+        solana_program::program::invoke(&ix, &[ctx.accounts.source.clone()])?;
+        Ok(())
+    }
+
+    pub fn cpi_checked_by_function(ctx: Context<Cpi>, amount: u64) -> ProgramResult {
+        let program_id = ctx.accounts.token_program.key;
+        check_token_program(program_id)?;
+        let amount = u32::try_from(amount).map_err(|_| ProgramError::InvalidArgument)?;
+        let ix = Instruction {
+            program_id: *program_id,
+            accounts: vec![],
+            data: amount.to_le_bytes().to_vec(),
+        };
+        solana_program::program::invoke(&ix, &[ctx.accounts.source.clone()])
+    }
+}
+
+fn check_token_program(program_id: &Pubkey) -> ProgramResult {
+    if *program_id != spl_token::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Cpi<'info> {
+    source: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
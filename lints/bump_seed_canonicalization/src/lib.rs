@@ -2,33 +2,44 @@
 #![feature(box_patterns)]
 #![warn(unused_extern_crates)]
 
-use clippy_utils::{
-    diagnostics::span_lint, get_trait_def_id, match_def_path, ty::implements_trait,
-};
+use clippy_utils::{get_trait_def_id, match_def_path, ty::implements_trait};
 use if_chain::if_chain;
 use rustc_hir::Body;
 use rustc_lint::{LateContext, LateLintPass};
 use rustc_middle::{
     mir,
     mir::{
-        AggregateKind, BasicBlock, BinOp, Local, Operand, Place, ProjectionElem, Rvalue,
+        AggregateKind, BasicBlock, BinOp, Local, Location, Operand, Place, ProjectionElem, Rvalue,
         StatementKind, TerminatorKind,
     },
     ty::Ty,
     ty::TyKind,
 };
 use rustc_target::abi::FieldIdx;
-use solana_lints::paths;
+use solana_lints::{
+    diagnostics::{span_lint_hir_with_confidence, Confidence},
+    mir_cache::{BodyAnalysis, MirCache},
+    paths, sarif,
+    utils::{
+        dataflow::{assigns_to, rvalue_source, CheckedBlocks},
+        interprocedural::calls_comparing,
+        mir_lint_root,
+    },
+};
 
 extern crate rustc_hir;
 extern crate rustc_middle;
 extern crate rustc_target;
 
-dylint_linting::declare_late_lint! {
+solana_lints::impl_late_lint! {
     /// **What it does:**
     ///
     /// Finds uses of solana_program::pubkey::PubKey::create_program_address that do not check the bump_seed
     ///
+    /// A bump returned by `Pubkey::find_program_address` is canonical, and is not reported. A bump
+    /// supplied by the user is reported even if the function calls `find_program_address`, e.g.,
+    /// when the returned bump is discarded.
+    ///
     /// **Why is this bad?**
     ///
     /// Generally for every seed there should be a canonical address, so the user should not be
@@ -41,14 +52,18 @@ dylint_linting::declare_late_lint! {
     /// - [ ] Anchor
     /// - [x] Non Anchor
     ///
-    /// **Known problems:**
+    /// **Group:**
     ///
-    /// False positives, since the bump_seed check may be within some other function (does not
-    /// trace through function calls). The bump seed may be also be safely stored in an account but
-    /// passed from another function.
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
     ///
-    /// False negatives, since our analysis is not path-sensitive (the bump_seed check may not
-    /// occur in all possible execution paths)
+    /// **Known problems:**
+    ///
+    /// False positives, since the bump_seed check may be within some other function which is not
+    /// defined in the crate, or which is more than three calls deep (see
+    /// `solana_lints::utils::interprocedural`). The bump seed may be also be safely stored in an
+    /// account but passed from another function. Hence a bump seed which is not checked has medium
+    /// confidence, and a bump seed read from a struct, which the lint does not look for checks of,
+    /// has low confidence (see `solana_lints::diagnostics::Confidence`).
     ///
     /// **Example:**
     ///
@@ -66,14 +81,24 @@ dylint_linting::declare_late_lint! {
     ///     `&[seed1, seed2, ..., &[bump]]` e.g `&[b"vault", &[bump]]`.
     ///   - find the locations of bump.
     ///   - If bump is assigned by accessing a struct field
+    ///     - if the struct is the tuple returned by `Pubkey::find_program_address`, i.e., the bump is
+    ///       canonical; do not report
     ///     - if bump is assigned from a struct implementing `AnchorDeserialize` trait
     ///       - report a warning to use `#[account(...)` macro
     ///     - else report "bump may not be constrainted" warning
-    ///   - else if the bump is checked using a comparison operation; do not report
+    ///   - else if on every execution path to the call (see `solana_lints::utils::dataflow`), the
+    ///     bump is checked using a comparison operation, or passed to a function of the crate which
+    ///     compares it; do not report
     ///   - else report a warning
     pub BUMP_SEED_CANONICALIZATION,
     Warn,
-    "Finds calls to create_program_address that do not check the bump_seed"
+    "Finds calls to create_program_address that do not check the bump_seed",
+    BumpSeedCanonicalization::default()
+}
+
+#[derive(Default)]
+struct BumpSeedCanonicalization {
+    mir_cache: MirCache,
 }
 
 impl<'tcx> LateLintPass<'tcx> for BumpSeedCanonicalization {
@@ -114,12 +139,13 @@ impl<'tcx> LateLintPass<'tcx> for BumpSeedCanonicalization {
                     ) {
                         // get the seeds argument; seeds is the first argument
                         let seed_arg = &args[0];
-                        if let Operand::Move(p) = seed_arg {
+                        if let Operand::Copy(p) | Operand::Move(p) = seed_arg {
+                            let analysis = self.mir_cache.analysis(cx, body_did);
                             // find all alias of bump in the seeds array: &[seed1, ..., &[bump]].
                             let (dataflow_state, likely_bump_places): (
                                 BackwardDataflowState,
                                 Vec<Place>,
-                            ) = Self::find_bump_seed_for_seed_array(cx, body_mir, block_id, p);
+                            ) = Self::find_bump_seed_for_seed_array(cx, body_mir, analysis, block_id, p);
                             let likely_bump_locals: Vec<Local> =
                                 likely_bump_places.iter().map(|pl| pl.local).collect();
                             match dataflow_state {
@@ -128,16 +154,24 @@ impl<'tcx> LateLintPass<'tcx> for BumpSeedCanonicalization {
                                     // If the bump seed is just passed in but didn't come from a
                                     // structure, look for equality checks that might show that
                                     // they try to constrain it.
-                                    if !Self::is_bump_seed_checked(
-                                        cx,
+                                    let mut checks = Self::bump_seed_checks(
                                         body_mir,
+                                        analysis,
                                         likely_bump_locals.as_ref(),
-                                    ) {
-                                        span_lint(
+                                    );
+                                    checks.extend(likely_bump_places.iter().flat_map(|place| {
+                                        calls_comparing(cx, body_did, *place)
+                                    }));
+                                    if !CheckedBlocks::new(body_mir, checks)
+                                        .is_checked_at(body_mir.terminator_loc(block_id))
+                                    {
+                                        span_lint_hir_with_confidence(
                                             cx,
                                             BUMP_SEED_CANONICALIZATION,
+                                            mir_lint_root(body_mir, t.source_info),
                                             t.source_info.span,
                                             "Bump seed may not be constrained. If stored in an account, use anchor's #[account(seed=..., bump=...)] macro instead",
+                                            Confidence::Medium,
                                         );
                                     }
                                 }
@@ -147,11 +181,13 @@ impl<'tcx> LateLintPass<'tcx> for BumpSeedCanonicalization {
                                     // Value came from a non-anchor struct. We will warn here
                                     // just to be safe, since we can't tell if this bump seed
                                     // is checked or not.
-                                    span_lint(
+                                    span_lint_hir_with_confidence(
                                             cx,
                                             BUMP_SEED_CANONICALIZATION,
+                                            mir_lint_root(body_mir, t.source_info),
                                             t.source_info.span,
                                             "Bump seed comes from structure, ensure it is constrained to a single value and not user-controlled.",
+                                            Confidence::Low,
                                         );
                                 }
                                 // TODO: Should we report this???
@@ -160,11 +196,13 @@ impl<'tcx> LateLintPass<'tcx> for BumpSeedCanonicalization {
                                 BackwardDataflowState::AnchorStructContainingBump => {
                                     // Value came from an anchor struct. They should be using
                                     // the account macro for this.
-                                    span_lint(
+                                    span_lint_hir_with_confidence(
                                             cx,
                                             BUMP_SEED_CANONICALIZATION,
+                                            mir_lint_root(body_mir, t.source_info),
                                             t.source_info.span,
                                             "Bump seed comes from anchor Account, use anchor's #[account(seed=..., bump=...)] macro instead",
+                                            Confidence::Low,
                                         );
                                 }
                                 _ => {}
@@ -175,6 +213,10 @@ impl<'tcx> LateLintPass<'tcx> for BumpSeedCanonicalization {
             }
         }
     }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[BUMP_SEED_CANONICALIZATION]);
+    }
 }
 
 /// Return true if the `deser_ty` implements `anchor::AccountDeserialize` trait else false
@@ -186,11 +228,57 @@ fn is_anchor_account_struct<'tcx>(cx: &LateContext<'tcx>, deser_ty: Ty<'tcx>) ->
     account_deserialize
 }
 
+/// Return true if `local` holds the result of a `Pubkey::find_program_address` call, i.e., it is
+/// the destination of the call or is moved or copied from it
+fn is_find_program_address_result<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    local: Local,
+) -> bool {
+    let mut visited = Vec::new();
+    let mut stack = vec![local];
+    while let Some(local) = stack.pop() {
+        if visited.contains(&local) {
+            continue;
+        }
+        visited.push(local);
+        for block in body.basic_blocks.iter() {
+            if_chain! {
+                if let TerminatorKind::Call {
+                    func: mir::Operand::Constant(box func),
+                    destination,
+                    ..
+                } = &block.terminator().kind;
+                if destination.local == local;
+                if let TyKind::FnDef(def_id, _) = func.const_.ty().kind();
+                if match_def_path(cx, *def_id, &paths::SOLANA_PROGRAM_FIND_PROGRAM_ADDRESS);
+                then {
+                    return true;
+                }
+            }
+            for stmt in &block.statements {
+                if_chain! {
+                    if let StatementKind::Assign(box (assign_place, rvalue)) = &stmt.kind;
+                    if assign_place.local == local && assign_place.projection.is_empty();
+                    if let Some(rvalue_place) = rvalue_source(rvalue);
+                    if rvalue_place.projection.is_empty();
+                    then {
+                        stack.push(rvalue_place.local);
+                    }
+                }
+            }
+        }
+    }
+    false
+}
+
 #[derive(Eq, PartialEq)]
 enum BackwardDataflowState {
     SeedsArray,
     FirstSeed,
     Bump,
+    /// The bump is returned by `find_program_address`
+    CanonicalBump,
     AnchorStructContainingBump,
     NonAnchorStructContainingBump,
 }
@@ -201,10 +289,10 @@ impl BumpSeedCanonicalization {
     fn find_bump_seed_for_seed_array<'tcx>(
         cx: &LateContext<'tcx>,
         body: &'tcx mir::Body<'tcx>,
+        analysis: &BodyAnalysis,
         block: BasicBlock,
         mut seeds_arg: &Place<'tcx>,
     ) -> (BackwardDataflowState, Vec<Place<'tcx>>) {
-        let preds = body.basic_blocks.predecessors();
         let mut cur_block = block;
         let mut state = BackwardDataflowState::SeedsArray;
         let mut likely_bump_seed_aliases = Vec::<Place>::new();
@@ -213,18 +301,10 @@ impl BumpSeedCanonicalization {
             for stmt in body.basic_blocks[cur_block].statements.iter().rev() {
                 if let StatementKind::Assign(box (assign_place, rvalue)) = &stmt.kind {
                     // trace assignments so we have a list of locals that contain the bump_seed
-                    if assign_place.local_or_deref_local() == seeds_arg.local_or_deref_local() {
+                    if assigns_to(assign_place, seeds_arg) {
                         // println!("match: {:?}", stmt);
-                        match rvalue {
-                            Rvalue::Use(
-                                Operand::Copy(rvalue_place) | Operand::Move(rvalue_place),
-                            )
-                            | Rvalue::Ref(_, _, rvalue_place)
-                            | Rvalue::Cast(
-                                _,
-                                Operand::Copy(rvalue_place) | Operand::Move(rvalue_place),
-                                _,
-                            ) => {
+                        match (rvalue_source(rvalue), rvalue) {
+                            (Some(rvalue_place), _) => {
                                 // if seed_arg = x then trace for assignments of x
                                 seeds_arg = rvalue_place;
                                 // state is Bump => seed_arg stores the bump
@@ -243,8 +323,15 @@ impl BumpSeedCanonicalization {
                                         });
                                     if let ProjectionElem::Field(_, _) = proj;
                                     then {
-                                        // if the bump is accessed from a Anchor struct (representing program state)
-                                        state = if is_anchor_account_struct(
+                                        // if the bump is returned by `find_program_address`, it is canonical.
+                                        // else if the bump is accessed from a Anchor struct (representing program state)
+                                        state = if is_find_program_address_result(
+                                            cx,
+                                            body,
+                                            rvalue_place.local,
+                                        ) {
+                                            BackwardDataflowState::CanonicalBump
+                                        } else if is_anchor_account_struct(
                                             cx,
                                             Place::ty_from(rvalue_place.local, &[], body, cx.tcx)
                                                 .ty
@@ -258,110 +345,60 @@ impl BumpSeedCanonicalization {
                                 }
                             }
                             // rhs is array
-                            Rvalue::Aggregate(box AggregateKind::Array(_), elements) => match state
-                            {
-                                BackwardDataflowState::SeedsArray if elements.len() > 1 => {
-                                    // if seeds_arg stores the `seeds` location, find the location of bump
-                                    // bump is the last element: [seed1, seed2, ..., bump]
-                                    if let Operand::Move(pl) = elements.into_iter().last().unwrap()
-                                    {
-                                        // update the seeds_arg to point to pl and update the state
-                                        seeds_arg = pl;
-                                        state = BackwardDataflowState::FirstSeed;
+                            (None, Rvalue::Aggregate(box AggregateKind::Array(_), elements)) => {
+                                match state {
+                                    BackwardDataflowState::SeedsArray if elements.len() > 1 => {
+                                        // if seeds_arg stores the `seeds` location, find the location of bump
+                                        // bump is the last element: [seed1, seed2, ..., bump]
+                                        if let Operand::Copy(pl) | Operand::Move(pl) =
+                                            elements.into_iter().last().unwrap()
+                                        {
+                                            // update the seeds_arg to point to pl and update the state
+                                            seeds_arg = pl;
+                                            state = BackwardDataflowState::FirstSeed;
+                                        }
                                     }
-                                }
-                                BackwardDataflowState::FirstSeed if elements.len() == 1 => {
-                                    // seeds_arg points to bump array [ seed1, ..., &[bump]. seeds_arg stores
-                                    // the location of &[bump]. update it to store the location of bump.
-                                    if let Operand::Move(pl) = &elements[FieldIdx::from_u32(0)] {
-                                        // store the location of bump
-                                        seeds_arg = &pl;
-                                        likely_bump_seed_aliases.push(*seeds_arg);
-                                        // seeds_arg is a location of bump
-                                        state = BackwardDataflowState::Bump;
-                                    }
-                                }
-                                _ => {}
-                            },
-                            _ => {}
-                        }
-                    }
-                }
-            }
-            match preds.get(cur_block) {
-                Some(cur_preds) if !cur_preds.is_empty() => cur_block = cur_preds[0],
-                _ => {
-                    break;
-                }
-            }
-        }
-        (state, likely_bump_seed_aliases)
-    }
-
-    // helper function
-    // Given the Place search_place, check if it was defined using one of the locals in search_list
-    fn is_moved_from<'tcx>(
-        _: &LateContext,
-        body: &'tcx mir::Body<'tcx>,
-        block: BasicBlock,
-        mut search_place: &Place<'tcx>,
-        search_list: &[Local],
-    ) -> bool {
-        let preds = body.basic_blocks.predecessors();
-        let mut cur_block = block;
-        if let Some(search_loc) = search_place.local_or_deref_local() {
-            if search_list.contains(&search_loc) {
-                return true;
-            }
-        }
-        // look for chain of assign statements whose value is eventually assigned to the `search_place` and
-        // see if any of the intermediate local is in the search_list.
-        // TODO: move this and ArbitraryCPI::is_moved_from to utils.
-        loop {
-            for stmt in body.basic_blocks[cur_block].statements.iter().rev() {
-                match &stmt.kind {
-                    StatementKind::Assign(box (assign_place, rvalue))
-                        if assign_place.local_or_deref_local()
-                            == search_place.local_or_deref_local() =>
-                    {
-                        match rvalue {
-                            Rvalue::Use(
-                                Operand::Copy(rvalue_place) | Operand::Move(rvalue_place),
-                            )
-                            | Rvalue::Ref(_, _, rvalue_place) => {
-                                // println!("Found assignment {:?}", stmt);
-                                search_place = rvalue_place;
-                                if let Some(search_loc) = search_place.local_or_deref_local() {
-                                    if search_list.contains(&search_loc) {
-                                        return true;
+                                    BackwardDataflowState::FirstSeed if elements.len() == 1 => {
+                                        // seeds_arg points to bump array [ seed1, ..., &[bump]. seeds_arg stores
+                                        // the location of &[bump]. update it to store the location of bump.
+                                        if let Operand::Copy(pl) | Operand::Move(pl) =
+                                            &elements[FieldIdx::from_u32(0)]
+                                        {
+                                            // store the location of bump
+                                            seeds_arg = &pl;
+                                            likely_bump_seed_aliases.push(*seeds_arg);
+                                            // seeds_arg is a location of bump
+                                            state = BackwardDataflowState::Bump;
+                                        }
                                     }
+                                    _ => {}
                                 }
                             }
                             _ => {}
                         }
                     }
-                    _ => {}
                 }
             }
-            match preds.get(cur_block) {
-                Some(cur_preds) if !cur_preds.is_empty() => cur_block = cur_preds[0],
-                _ => {
+            match analysis.backward_predecessor(cur_block) {
+                Some(predecessor) => cur_block = predecessor,
+                None => {
                     break;
                 }
             }
         }
-        false
+        (state, likely_bump_seed_aliases)
     }
 
-    // This function takes the list of bump_locals and a starting block, and searches for a
-    // check elsewhere in the Body that would compare the program_id with something else.
-    fn is_bump_seed_checked<'tcx>(
-        cx: &LateContext,
+    // This function takes the list of bump_locals, and returns the locations of the checks in the
+    // Body that compare the bump with something else.
+    fn bump_seed_checks<'tcx>(
         body: &'tcx mir::Body<'tcx>,
+        analysis: &BodyAnalysis,
         bump_locals: &[Local],
-    ) -> bool {
+    ) -> Vec<Location> {
+        let mut checks = Vec::new();
         for (block_id, block) in body.basic_blocks.iter_enumerated() {
-            for stmt in &block.statements {
+            for (statement_index, stmt) in block.statements.iter().enumerate() {
                 if_chain! {
                     // look for assign statements
                     if let StatementKind::Assign(box (_, rvalue)) = &stmt.kind;
@@ -371,17 +408,20 @@ impl BumpSeedCanonicalization {
                     if let Operand::Copy(arg1_pl) | Operand::Move(arg1_pl) = op1;
                     then {
                         // Check if one of the args in comparison came from a local of bump
-                        if Self::is_moved_from(cx, body, block_id, arg0_pl, bump_locals)
-                            || Self::is_moved_from(cx, body, block_id, arg1_pl, bump_locals)
+                        if analysis.is_moved_from(body, block_id, arg0_pl, bump_locals)
+                            || analysis.is_moved_from(body, block_id, arg1_pl, bump_locals)
                         {
-                            // we found the check
-                            return true;
+                            // we found a check
+                            checks.push(Location {
+                                block: block_id,
+                                statement_index,
+                            });
                         }
                     }
                 }
             }
         }
-        false
+        checks
     }
 }
 
@@ -395,6 +435,11 @@ fn secure() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
 }
 
+#[test]
+fn secure_2() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-2");
+}
+
 #[test]
 fn insecure_2() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-2");
@@ -405,7 +450,37 @@ fn insecure_3() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-3");
 }
 
+#[test]
+fn insecure_4() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-4");
+}
+
+#[test]
+fn secure_3() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-3");
+}
+
 #[test]
 fn recommended() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "recommended");
 }
+
+#[test]
+fn expect() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "expect");
+}
+
+#[test]
+fn insecure_5() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-5");
+}
+
+#[test]
+fn secure_4() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-4");
+}
+
+#[test]
+fn secure_5() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-5");
+}
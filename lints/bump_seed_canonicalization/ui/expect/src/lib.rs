@@ -0,0 +1,60 @@
+#![feature(lint_reasons)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod bump_seed_canonicalization_expect {
+    use super::*;
+
+    pub fn set_value(ctx: Context<BumpSeed>, key: u64, new_value: u64, bump: u8) -> ProgramResult {
+        // The expectation is fulfilled: `bump` is not checked to be the canonical bump.
+        #[expect(bump_seed_canonicalization)]
+        let address =
+            Pubkey::create_program_address(&[key.to_le_bytes().as_ref(), &[bump]], ctx.program_id)?;
+        if address != ctx.accounts.data.key() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        ctx.accounts.data.value = new_value;
+
+        Ok(())
+    }
+
+    // The expectation is unfulfilled: `bump` is checked against the canonical bump.
+    #[expect(bump_seed_canonicalization)]
+    pub fn set_value_secure(
+        ctx: Context<BumpSeed>,
+        key: u64,
+        new_value: u64,
+        bump: u8,
+    ) -> ProgramResult {
+        let (address, expected_bump) =
+            Pubkey::find_program_address(&[key.to_le_bytes().as_ref()], ctx.program_id);
+
+        if address != ctx.accounts.data.key() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if expected_bump != bump {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        ctx.accounts.data.value = new_value;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct BumpSeed<'info> {
+    data: Account<'info, Data>,
+}
+
+#[account]
+pub struct Data {
+    value: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod bump_seed_canonicalization_insecure_4 {
+    use super::*;
+
+    pub fn set_value(ctx: Context<BumpSeed>, key: u64, new_value: u64, bump: u8) -> ProgramResult {
+        // The canonical bump is discarded, and the bump supplied by the user is used instead
+        let (_, canonical_bump) =
+            Pubkey::find_program_address(&[key.to_le_bytes().as_ref()], ctx.program_id);
+        msg!("canonical bump: {}", canonical_bump);
+        let address =
+            Pubkey::create_program_address(&[key.to_le_bytes().as_ref(), &[bump]], ctx.program_id)?;
+        if address != ctx.accounts.data.key() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        ctx.accounts.data.value = new_value;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct BumpSeed<'info> {
+    data: Account<'info, Data>,
+}
+
+#[account]
+pub struct Data {
+    value: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
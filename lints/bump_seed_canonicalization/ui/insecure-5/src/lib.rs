@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod bump_seed_canonicalization_insecure_5 {
+    use super::*;
+
+    pub fn set_value(ctx: Context<BumpSeed>, key: u64, new_value: u64, bump: u8) -> ProgramResult {
+        // The bump is only checked when a new value is set
+        if new_value != 0 {
+            let (_, canonical_bump) =
+                Pubkey::find_program_address(&[key.to_le_bytes().as_ref()], ctx.program_id);
+            if bump != canonical_bump {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+        let address =
+            Pubkey::create_program_address(&[key.to_le_bytes().as_ref(), &[bump]], ctx.program_id)?;
+        if address != ctx.accounts.data.key() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        ctx.accounts.data.value = new_value;
+
+        Ok(())
+    }
+
+    pub fn set_late(ctx: Context<BumpSeed>, key: u64, new_value: u64, bump: u8) -> ProgramResult {
+        let address =
+            Pubkey::create_program_address(&[key.to_le_bytes().as_ref(), &[bump]], ctx.program_id)?;
+        if address != ctx.accounts.data.key() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        // The bump is checked after the address is created
+        let (_, canonical_bump) =
+            Pubkey::find_program_address(&[key.to_le_bytes().as_ref()], ctx.program_id);
+        if bump != canonical_bump {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        ctx.accounts.data.value = new_value;
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct BumpSeed<'info> {
+    data: Account<'info, Data>,
+}
+
+#[account]
+pub struct Data {
+    value: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
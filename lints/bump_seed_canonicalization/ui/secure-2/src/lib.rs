@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod bump_seed_canonicalization_secure_2 {
+    use super::*;
+
+    pub fn set_value(ctx: Context<BumpSeed>, key: u64, new_value: u64, bump: u8) -> ProgramResult {
+        // The bump is compared with the canonical bump by `check_bump`
+        check_bump(key, bump, ctx.program_id)?;
+        let address =
+            Pubkey::create_program_address(&[key.to_le_bytes().as_ref(), &[bump]], ctx.program_id)?;
+        if address != ctx.accounts.data.key() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        ctx.accounts.data.value = new_value;
+
+        Ok(())
+    }
+}
+
+fn check_bump(key: u64, bump: u8, program_id: &Pubkey) -> ProgramResult {
+    let (_, expected_bump) =
+        Pubkey::find_program_address(&[key.to_le_bytes().as_ref()], program_id);
+    if bump != expected_bump {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct BumpSeed<'info> {
+    data: Account<'info, Data>,
+}
+
+#[account]
+pub struct Data {
+    value: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
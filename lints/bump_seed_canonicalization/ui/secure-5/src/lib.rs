@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use std::convert::TryFrom;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod bump_seed_canonicalization_secure_5 {
+    use super::*;
+
+    pub fn set_value(ctx: Context<BumpSeed>, key: u64, new_value: u64, bump: u8) -> ProgramResult {
+        let (_, canonical_bump) =
+            Pubkey::find_program_address(&[key.to_le_bytes().as_ref()], ctx.program_id);
+        if bump != canonical_bump {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        // The `?` operators between the check and `create_program_address` return early
+        let new_value = new_value
+            .checked_add(1)
+            .ok_or(ProgramError::InvalidArgument)?;
+        let seed = u32::try_from(key).map_err(|_| ProgramError::InvalidArgument)?;
+        let address = Pubkey::create_program_address(
+            &[seed.to_le_bytes().as_ref(), &[bump]],
+            ctx.program_id,
+        )?;
+        if address != ctx.accounts.data.key() {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        ctx.accounts.data.value = new_value;
+
+        Ok(())
+    }
+
+    pub fn check_values(ctx: Context<BumpSeed>, keys: Vec<u64>, bump: u8) -> ProgramResult {
+        let (_, canonical_bump) = Pubkey::find_program_address(&[b"values"], ctx.program_id);
+        if bump != canonical_bump {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        // The check is made before the loop, whose body returns early with `?`
+        for key in keys {
+            let seed = u32::try_from(key).map_err(|_| ProgramError::InvalidArgument)?;
+            let address = Pubkey::create_program_address(
+                &[seed.to_le_bytes().as_ref(), &[bump]],
+                ctx.program_id,
+            )?;
+            if address != ctx.accounts.data.key() {
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct BumpSeed<'info> {
+    data: Account<'info, Data>,
+}
+
+#[account]
+pub struct Data {
+    value: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
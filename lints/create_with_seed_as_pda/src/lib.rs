@@ -0,0 +1,321 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{fn_def_id, match_def_path, path_to_local, SpanlessEq};
+use if_chain::if_chain;
+use rustc_hir::{
+    def::DefKind, def_id::LocalDefId, intravisit::FnKind, BinOpKind, Body, Expr, ExprKind, FnDecl,
+    HirId, MatchSource, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::{span_lint_hir_and_then, span_lint_hir_and_then_with_confidence, Confidence},
+    expansion::is_skipped_expansion,
+    paths::{self, anchor_wrapper, AnchorWrapper},
+    sarif,
+    utils::{is_authority_name, is_expr_method_call, local_init, visit_expr_no_bodies},
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the addresses derived with `Pubkey::create_with_seed` which are used as if they
+    /// were PDAs of the program:
+    ///
+    /// - an account whose key is compared with such an address, and which is not required to
+    ///   sign, i.e., whose `is_signer` is not read
+    /// - such an address passed as an authority or an owner, e.g., as the `authority_pubkey` of
+    ///   `spl_token::instruction::transfer`
+    /// - such an address stored in an authority field, e.g., `vault.authority = address`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// `Pubkey::create_with_seed` does not derive a PDA. The account at the address is controlled
+    /// by the holder of the base key, who signs the `*_with_seed` instructions of the System
+    /// program, e.g., `transfer_with_seed`, and the program cannot sign for it with
+    /// `invoke_signed`. Checking that an account is at such an address does not show that the
+    /// program controls it, and an authority at such an address either belongs to the holder of
+    /// the base key or can never sign.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Each function is analyzed on its own. The address is followed through `?`, `unwrap()`,
+    /// `expect(..)`, borrows and local variables only. The comparisons are not reported in the
+    /// functions which pass a seeded address to a `*_with_seed` function, e.g.,
+    /// `system_instruction::create_account_with_seed`, as they manage the accounts with the base
+    /// key, as intended. An account compared with an address may be required to sign by the
+    /// caller of the function, so these findings have medium confidence.
+    /// The authorities are recognized by their names (see `unsigned_authority_change`).
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// let vault_authority = Pubkey::create_with_seed(base.key, "vault", program_id)?;
+    /// if vault_authority_info.key != &vault_authority {
+    ///     return Err(ProgramError::InvalidSeeds);
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// let (vault_authority, _) =
+    ///     Pubkey::find_program_address(&[b"vault", base.key.as_ref()], program_id);
+    /// if vault_authority_info.key != &vault_authority {
+    ///     return Err(ProgramError::InvalidSeeds);
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for each function, excluding the functions generated by macros
+    /// - an expression is a seeded address if it is a call to `Pubkey::create_with_seed`, or a
+    ///   local variable initialized with a seeded address, possibly through `?`, `unwrap()`,
+    ///   `expect(..)`, borrows and derefs
+    /// - unless a seeded address is passed to a function whose name ends with `_with_seed`, for
+    ///   each comparison (`==` or `!=`) of a seeded address with the key of an account, i.e.,
+    ///   `x.key` or `x.key()`, which is not an Anchor `Signer`, report the key if `x.is_signer`
+    ///   is not read in the function
+    /// - for each call passing a seeded address as a parameter with an authority name, e.g.,
+    ///   `authority_pubkey`, report the argument
+    /// - for each assignment of a seeded address to a field with an authority name, report the
+    ///   assignment
+    pub CREATE_WITH_SEED_AS_PDA,
+    Warn,
+    "addresses derived with `Pubkey::create_with_seed` used as if they were PDAs of the program"
+}
+
+impl<'tcx> LateLintPass<'tcx> for CreateWithSeedAsPda {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(CREATE_WITH_SEED_AS_PDA, span) {
+            return;
+        }
+        let manages_seeded_accounts = manages_seeded_accounts(cx, body.value);
+        visit_expr_no_bodies(body.value, |expr| {
+            match expr.kind {
+                ExprKind::Binary(op, lhs, rhs)
+                    if !manages_seeded_accounts
+                        && matches!(op.node, BinOpKind::Eq | BinOpKind::Ne) =>
+                {
+                    for (address, key) in [(lhs, rhs), (rhs, lhs)] {
+                        if_chain! {
+                            if is_seeded_address(cx, address);
+                            if let Some(account) = key_account(cx, key);
+                            if !is_anchor_signer(cx, account);
+                            if !reads_is_signer(cx, body.value, account);
+                            then {
+                                report_compared_key(cx, key);
+                            }
+                        }
+                    }
+                }
+                ExprKind::Call(_, args) => {
+                    if let Some(def_id) = fn_def_id(cx, expr).filter(|&def_id| {
+                        matches!(cx.tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+                    }) {
+                        for (param, arg) in cx.tcx.fn_arg_names(def_id).iter().zip(args) {
+                            let param = param.as_str();
+                            if is_authority_name(param.trim_end_matches("_pubkey"))
+                                && is_seeded_address(cx, arg)
+                            {
+                                report_authority(
+                                    cx,
+                                    arg.hir_id,
+                                    arg.span,
+                                    &format!("passed as `{param}`"),
+                                );
+                            }
+                        }
+                    }
+                }
+                ExprKind::Assign(lhs, value, _) => {
+                    if_chain! {
+                        if let ExprKind::Field(_, field) = lhs.kind;
+                        if is_authority_name(field.as_str());
+                        if is_seeded_address(cx, value);
+                        then {
+                            report_authority(
+                                cx,
+                                expr.hir_id,
+                                expr.span,
+                                &format!("stored as `{field}`"),
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[CREATE_WITH_SEED_AS_PDA]);
+    }
+}
+
+fn report_compared_key(cx: &LateContext<'_>, key: &Expr<'_>) {
+    span_lint_hir_and_then_with_confidence(
+        cx,
+        CREATE_WITH_SEED_AS_PDA,
+        key.hir_id,
+        key.span,
+        "the key of this account is checked against an address derived with `Pubkey::create_with_seed`, which is not a PDA",
+        Confidence::Medium,
+        |diag| {
+            diag.note("the holder of the base key controls the account, and the program cannot sign for it");
+            diag.help(
+                "require the account to sign, or derive a PDA with `Pubkey::find_program_address`",
+            );
+        },
+    );
+}
+
+fn report_authority(cx: &LateContext<'_>, hir_id: HirId, span: Span, usage: &str) {
+    span_lint_hir_and_then(
+        cx,
+        CREATE_WITH_SEED_AS_PDA,
+        hir_id,
+        span,
+        &format!(
+            "an address derived with `Pubkey::create_with_seed` is {usage}, as if the program controlled it"
+        ),
+        |diag| {
+            diag.note("the program cannot sign for the address, which is not a PDA");
+            diag.help(
+                "use a PDA derived with `Pubkey::find_program_address`, for which the program signs with `invoke_signed`",
+            );
+        },
+    );
+}
+
+/// Return true if a seeded address is passed to a `*_with_seed` function in `scope`, e.g.,
+/// `system_instruction::create_account_with_seed`, i.e., if the accounts at the seeded addresses
+/// are managed with the base key, as intended
+fn manages_seeded_accounts<'tcx>(cx: &LateContext<'tcx>, scope: &'tcx Expr<'tcx>) -> bool {
+    visit_expr_no_bodies(scope, |expr| {
+        if_chain! {
+            if let ExprKind::Call(_, args) = expr.kind;
+            if let Some(def_id) = fn_def_id(cx, expr);
+            if !match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_CREATE_WITH_SEED);
+            if cx.tcx.item_name(def_id).as_str().ends_with("_with_seed");
+            then {
+                args.iter().any(|arg| is_seeded_address(cx, arg))
+            } else {
+                false
+            }
+        }
+    })
+}
+
+/// Return true if `expr` is an address derived with `Pubkey::create_with_seed`, following `?`,
+/// `unwrap()`, `expect(..)`, borrows, derefs and the initializers of local variables
+fn is_seeded_address<'tcx>(cx: &LateContext<'tcx>, mut expr: &'tcx Expr<'tcx>) -> bool {
+    loop {
+        expr = match expr.kind {
+            ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) => inner,
+            // `x?` is desugared into `match Try::branch(x) { .. }`
+            ExprKind::Match(scrutinee, _, MatchSource::TryDesugar(_)) => match scrutinee.kind {
+                ExprKind::Call(_, [inner]) => inner,
+                _ => return false,
+            },
+            ExprKind::MethodCall(method, receiver, _, _)
+                if matches!(method.ident.as_str(), "unwrap" | "expect") =>
+            {
+                receiver
+            }
+            ExprKind::Call(..) => {
+                return fn_def_id(cx, expr).map_or(false, |def_id| {
+                    match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_CREATE_WITH_SEED)
+                });
+            }
+            _ => match path_to_local(expr).and_then(|local| local_init(cx, local)) {
+                Some(init) => init,
+                None => return false,
+            },
+        };
+    }
+}
+
+/// If `expr` is the key of an account, i.e., `x.key` or `x.key()`, possibly dereferenced or
+/// borrowed, return `x`
+fn key_account<'tcx>(
+    cx: &LateContext<'tcx>,
+    mut expr: &'tcx Expr<'tcx>,
+) -> Option<&'tcx Expr<'tcx>> {
+    while let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = expr.kind {
+        expr = inner;
+    }
+    if let Some(receiver) = is_expr_method_call(cx, expr, &paths::ANCHOR_LANG_KEY) {
+        return Some(receiver);
+    }
+    if_chain! {
+        if let ExprKind::Field(object, field) = expr.kind;
+        if field.as_str() == "key";
+        let ty = cx.typeck_results().expr_ty_adjusted(object).peel_refs();
+        if paths::is_account_info_ty(cx, ty);
+        then {
+            Some(object)
+        } else {
+            None
+        }
+    }
+}
+
+fn is_anchor_signer<'tcx>(cx: &LateContext<'tcx>, account: &'tcx Expr<'tcx>) -> bool {
+    let ty = cx.typeck_results().expr_ty(account).peel_refs();
+    anchor_wrapper(cx, ty) == Some(AnchorWrapper::Signer)
+}
+
+/// Return true if `account.is_signer` is read in `scope`
+fn reads_is_signer<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    account: &'tcx Expr<'tcx>,
+) -> bool {
+    visit_expr_no_bodies(scope, |expr| {
+        if_chain! {
+            if let ExprKind::Field(object, field) = expr.kind;
+            if field.as_str() == "is_signer";
+            then {
+                SpanlessEq::new(cx).eq_expr(object, account)
+            } else {
+                false
+            }
+        }
+    })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
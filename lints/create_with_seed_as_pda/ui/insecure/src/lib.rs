@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::account_info::next_account_info;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_spl::token::spl_token;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod create_with_seed_as_pda_insecure {
+    use super::*;
+
+    pub fn set_vault(ctx: Context<SetVault>) -> Result<()> {
+        let vault_authority =
+            Pubkey::create_with_seed(ctx.accounts.payer.key, "vault", ctx.program_id).unwrap();
+        ctx.accounts.config.vault_authority = vault_authority;
+        Ok(())
+    }
+}
+
+pub fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let base = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let vault_authority_info = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    let vault_authority = Pubkey::create_with_seed(base.key, "vault", program_id)?;
+    if vault_authority_info.key != &vault_authority {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let ix = spl_token::instruction::transfer(
+        token_program.key,
+        vault.key,
+        destination.key,
+        &vault_authority,
+        &[],
+        amount,
+    )?;
+    invoke(
+        &ix,
+        &[
+            vault.clone(),
+            destination.clone(),
+            vault_authority_info.clone(),
+        ],
+    )
+}
+
+#[derive(Accounts)]
+pub struct SetVault<'info> {
+    #[account(mut)]
+    config: Account<'info, Config>,
+    payer: Signer<'info>,
+}
+
+#[account]
+pub struct Config {
+    vault_authority: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
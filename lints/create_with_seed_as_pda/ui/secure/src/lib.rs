@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::account_info::next_account_info;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::system_instruction;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod create_with_seed_as_pda_secure {
+    use super::*;
+
+    pub fn set_vault(ctx: Context<SetVault>) -> Result<()> {
+        let (vault_authority, _) = Pubkey::find_program_address(
+            &[b"vault", ctx.accounts.payer.key.as_ref()],
+            ctx.program_id,
+        );
+        ctx.accounts.config.vault_authority = vault_authority;
+        Ok(())
+    }
+}
+
+// The account at the seeded address is created with the base key, which signs.
+pub fn create_vault(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer = next_account_info(accounts_iter)?;
+    let base = next_account_info(accounts_iter)?;
+    let vault = next_account_info(accounts_iter)?;
+    let vault_address = Pubkey::create_with_seed(base.key, "vault", program_id)?;
+    if vault.key != &vault_address {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let ix = system_instruction::create_account_with_seed(
+        payer.key,
+        &vault_address,
+        base.key,
+        "vault",
+        lamports,
+        165,
+        program_id,
+    );
+    invoke(&ix, &[payer.clone(), vault.clone(), base.clone()])
+}
+
+#[derive(Accounts)]
+pub struct SetVault<'info> {
+    #[account(mut)]
+    config: Account<'info, Config>,
+    payer: Signer<'info>,
+}
+
+#[account]
+pub struct Config {
+    vault_authority: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
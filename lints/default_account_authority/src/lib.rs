@@ -0,0 +1,191 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    get_trait_def_id,
+    ty::{implements_trait, match_type},
+};
+use if_chain::if_chain;
+use rustc_hir::{def_id::DefId, HirId, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::{sym, Span};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, paths, sarif,
+    utils::is_authority_name,
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports `#[derive(Default)]` on Anchor `#[account]` types with an authority field of type
+    /// `Pubkey`, i.e., a field named `authority`, `owner` or `admin`, or whose name ends with one
+    /// of them, e.g., `fee_authority`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The default authority is the zero key, `Pubkey::default()`, which is the key of the System
+    /// Program, and which no one can sign for. An account built with `Default::default()`, or
+    /// initialized field by field starting from it, e.g., in an instruction using
+    /// `init_if_needed` or `zero`, is stored with the zero key as authority whenever the code
+    /// setting the authority is skipped or forgotten. Depending on the checks of the other
+    /// instructions, such an account is either locked forever, or, if they compare the authority
+    /// with a key provided by the caller, e.g., `has_one = authority` with an `UncheckedAccount`
+    /// or a `SystemAccount`, anyone can act as its authority by passing the zero key.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The derive is reported even if `Default::default()` is only used in tests, or if every
+    /// instruction sets the authority.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[account]
+    /// #[derive(Default)]
+    /// pub struct Vault {
+    ///     pub authority: Pubkey,
+    ///     pub amount: u64,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[account]
+    /// pub struct Vault {
+    ///     pub authority: Pubkey,
+    ///     pub amount: u64,
+    /// }
+    ///
+    /// impl Vault {
+    ///     pub fn new(authority: Pubkey) -> Self {
+    ///         Self { authority, amount: 0 }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - for each implementation of `Default` generated by `#[derive(Default)]`
+    /// - if the type is a struct of the crate, not generated by a macro, which implements both
+    ///   `anchor_lang::Discriminator` and `anchor_lang::Owner`, i.e., an `#[account]` type
+    /// - if some of its fields have an authority name and the type `Pubkey`, report the derive,
+    ///   with a note for each of these fields
+    pub DEFAULT_ACCOUNT_AUTHORITY,
+    Warn,
+    "`Default` derived for an account type holding authority keys"
+}
+
+impl<'tcx> LateLintPass<'tcx> for DefaultAccountAuthority {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Impl(impl_) = item.kind;
+            if let Some(trait_ref) = impl_.of_trait;
+            if let Some(trait_def_id) = trait_ref.trait_def_id();
+            if cx.tcx.is_diagnostic_item(sym::Default, trait_def_id);
+            if cx.tcx.is_automatically_derived(item.owner_id.to_def_id());
+            let ty = cx.tcx.type_of(item.owner_id).instantiate_identity();
+            if let ty::Adt(adt_def, args) = ty.kind();
+            if adt_def.is_struct();
+            if let Some(local_def_id) = adt_def.did().as_local();
+            let account_span = cx.tcx.def_span(adt_def.did());
+            if !is_skipped_expansion(DEFAULT_ACCOUNT_AUTHORITY, account_span);
+            if is_account_type(cx, ty);
+            then {
+                let authorities: Vec<(String, Span)> = adt_def
+                    .all_fields()
+                    .filter(|field| {
+                        let field_ty = field.ty(cx.tcx, args);
+                        is_authority_name(field.name.as_str())
+                            && match_type(cx, field_ty, &paths::SOLANA_PROGRAM_PUBKEY)
+                    })
+                    .map(|field| (field.name.to_string(), cx.tcx.def_span(field.did)))
+                    .collect();
+                if authorities.is_empty() {
+                    return;
+                }
+                report(
+                    cx,
+                    adt_def.did(),
+                    cx.tcx.local_def_id_to_hir_id(local_def_id),
+                    item.span.source_callsite(),
+                    &authorities,
+                );
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[DEFAULT_ACCOUNT_AUTHORITY]);
+    }
+}
+
+/// Return true if `ty` is an `#[account]` type, i.e., it implements both
+/// `anchor_lang::Discriminator` and `anchor_lang::Owner`
+fn is_account_type<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>) -> bool {
+    let implements = |path: &[&str]| {
+        get_trait_def_id(cx, path).map_or(false, |trait_def_id| {
+            implements_trait(cx, ty, trait_def_id, &[])
+        })
+    };
+    implements(&paths::ANCHOR_LANG_DISCRIMINATOR) && implements(&paths::ANCHOR_LANG_OWNER)
+}
+
+fn report(
+    cx: &LateContext<'_>,
+    account_def_id: DefId,
+    hir_id: HirId,
+    derive_span: Span,
+    authorities: &[(String, Span)],
+) {
+    let account = cx.tcx.item_name(account_def_id);
+    span_lint_hir_and_then(
+        cx,
+        DEFAULT_ACCOUNT_AUTHORITY,
+        hir_id,
+        derive_span,
+        &format!(
+            "`Default` is derived for the account type `{account}`, which holds authority keys"
+        ),
+        |diag| {
+            for (name, span) in authorities {
+                diag.span_note(
+                    *span,
+                    format!("the default of the authority `{name}` is the zero key"),
+                );
+            }
+            diag.note(
+                "an account created from `Default::default()`, e.g., with `init_if_needed` or `zero`, has the zero key as authority if the instruction does not set it, and the zero key passes the checks comparing the authority with an unsigned account",
+            );
+            diag.help(format!(
+                "remove `Default` from the derive and build the account with a constructor taking its authorities, e.g., `{account}::new(..)`"
+            ));
+        },
+    );
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
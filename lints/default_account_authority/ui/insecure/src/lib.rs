@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod default_account_authority_insecure {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>, amount: u64) -> Result<()> {
+        // The authority is not set, so the vault keeps the zero key as authority
+        ctx.accounts.vault.set_inner(Vault {
+            amount,
+            ..Default::default()
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(zero)]
+    pub vault: Account<'info, Vault>,
+}
+
+#[account]
+#[derive(Default)]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+#[account]
+#[derive(Default)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub fee_authority: Pubkey,
+    pub fee: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod default_account_authority_secure {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>, amount: u64) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        ctx.accounts.vault.set_inner(Vault::new(authority, amount));
+        ctx.accounts.counter.set_inner(Counter::default());
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(zero)]
+    pub vault: Account<'info, Vault>,
+    #[account(zero)]
+    pub counter: Account<'info, Counter>,
+    pub authority: Signer<'info>,
+}
+
+// The authority is set by the constructor
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+impl Vault {
+    pub fn new(authority: Pubkey, amount: u64) -> Self {
+        Self { authority, amount }
+    }
+}
+
+// No authority
+#[account]
+#[derive(Default)]
+pub struct Counter {
+    pub count: u64,
+}
+
+// Not an account
+#[derive(AnchorSerialize, AnchorDeserialize, Default)]
+pub struct Params {
+    pub authority: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
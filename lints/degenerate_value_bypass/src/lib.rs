@@ -0,0 +1,344 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{
+    fn_def_id, is_res_lang_ctor, match_def_path, path_res, path_to_local, ty::match_type,
+};
+use if_chain::if_chain;
+use rustc_ast::LitKind;
+use rustc_hir::{
+    def_id::LocalDefId, intravisit::FnKind, Body, Expr, ExprKind, FnDecl, HirId, LangItem, Node,
+    PatKind, StmtKind, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::{Span, Symbol};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{is_expr_method_call, visit_expr_no_bodies},
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the checks of a function which are made only if an integer argument is not a
+    /// degenerate value, e.g., in `if amount > 0 { ... }`, while the state changes or CPIs after
+    /// the `if` are made for every value of the argument. A check is a return of an error, e.g.,
+    /// `require!(...)` or `return err!(...)`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The caller chooses the arguments of an instruction. By passing the degenerate value, e.g.,
+    /// an `amount` of `0`, the caller skips the signer, owner or price checks, but the state
+    /// changes and CPIs after the `if` are still made, e.g., a position is marked as closed or
+    /// a reward is claimed by an account which is not checked.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Only the conditions comparing an integer argument of the function with `0` are
+    /// considered, and only the statements after the `if` in the same block are regarded as its
+    /// effects. Any assignment through a reference, an argument or a field of one, and any call
+    /// to `add_lamports` or `sub_lamports`, is regarded as a state change.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    ///     if amount > 0 {
+    ///         require_keys_eq!(ctx.accounts.vault.authority, ctx.accounts.authority.key());
+    ///         ctx.accounts.vault.balance -= amount;
+    ///     }
+    ///     ctx.accounts.vault.withdrawn = true;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    ///     require_keys_eq!(ctx.accounts.vault.authority, ctx.accounts.authority.key());
+    ///     if amount > 0 {
+    ///         ctx.accounts.vault.balance -= amount;
+    ///     }
+    ///     ctx.accounts.vault.withdrawn = true;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - for each `if` whose condition compares an integer argument of the function with `0`
+    ///   - find the first return of an error, `return Err(...)`, in the `then` branch, else in
+    ///     the `else` branch
+    ///   - collect the effects of the statements after the `if` in the enclosing block:
+    ///     assignments through references and arguments, calls to `add_lamports` and
+    ///     `sub_lamports`, calls to `invoke` and `invoke_signed`, and calls taking a `CpiContext`
+    ///   - if the branch returns an error and there are effects, report the condition, and note
+    ///     the check and the effects
+    pub DEGENERATE_VALUE_BYPASS,
+    Warn,
+    "checks skipped for degenerate values of an argument, while the effects after them are not"
+}
+
+impl<'tcx> LateLintPass<'tcx> for DegenerateValueBypass {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(DEGENERATE_VALUE_BYPASS, span) {
+            return;
+        }
+        let params: Vec<HirId> = body
+            .params
+            .iter()
+            .filter_map(|param| match param.pat.kind {
+                PatKind::Binding(_, hir_id, _, _)
+                    if cx.typeck_results().node_type(hir_id).is_integral() =>
+                {
+                    Some(hir_id)
+                }
+                _ => None,
+            })
+            .collect();
+        if params.is_empty() {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if !is_skipped_expansion(DEGENERATE_VALUE_BYPASS, expr.span);
+                if let ExprKind::If(cond, then, els) = expr.kind;
+                let cond = peel_drop_temps(cond);
+                if let Some(param) = degenerate_guard(cx, cond, &params);
+                if let Some(check) = guarded_check(cx, then, els);
+                let effects = effects_after(cx, body, expr);
+                if !effects.is_empty();
+                then {
+                    report(cx, expr.hir_id, cond.span, param, check, &effects);
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[DEGENERATE_VALUE_BYPASS]);
+    }
+}
+
+/// The kind of an effect made after a guarded check
+#[derive(Clone, Copy)]
+enum Effect {
+    StateChange,
+    Cpi,
+}
+
+/// `if` conditions are wrapped in `DropTemps`
+fn peel_drop_temps<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    while let ExprKind::DropTemps(inner) = expr.kind {
+        expr = inner;
+    }
+    expr
+}
+
+/// If `cond` compares one of `params` with `0`, e.g., `amount > 0` or `0 == amount`, return the
+/// name of the argument
+fn degenerate_guard(cx: &LateContext<'_>, cond: &Expr<'_>, params: &[HirId]) -> Option<Symbol> {
+    if_chain! {
+        if let ExprKind::Binary(op, lhs, rhs) = cond.kind;
+        if op.node.is_comparison();
+        if let Some(param) = [(lhs, rhs), (rhs, lhs)].iter().find_map(|(param, zero)| {
+            path_to_local(param).filter(|hir_id| params.contains(hir_id) && is_zero(zero))
+        });
+        then {
+            Some(cx.tcx.hir().name(param))
+        } else {
+            None
+        }
+    }
+}
+
+/// Return true if `expr` is the integer literal `0`
+fn is_zero(expr: &Expr<'_>) -> bool {
+    matches!(expr.kind, ExprKind::Lit(lit) if matches!(lit.node, LitKind::Int(0, _)))
+}
+
+/// Return the span of the first return of an error in `then`, else in `els`
+fn guarded_check(cx: &LateContext<'_>, then: &Expr<'_>, els: Option<&Expr<'_>>) -> Option<Span> {
+    error_return(cx, then).or_else(|| els.and_then(|els| error_return(cx, els)))
+}
+
+/// Return the span of the first return of an error in `branch`, e.g., `return Err(...)`, or the
+/// call of the macro returning it, e.g., `require!(...)`
+fn error_return(cx: &LateContext<'_>, branch: &Expr<'_>) -> Option<Span> {
+    visit_expr_no_bodies(branch, |expr| {
+        if_chain! {
+            if let ExprKind::Ret(Some(value)) = expr.kind;
+            if let ExprKind::Call(ctor, _) = value.kind;
+            if is_res_lang_ctor(cx, path_res(cx, ctor), LangItem::ResultErr);
+            then {
+                Some(expr.span.source_callsite())
+            } else {
+                None
+            }
+        }
+    })
+}
+
+/// Return the effects of the statements after `if_expr` in its enclosing block
+fn effects_after<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx Body<'tcx>,
+    if_expr: &'tcx Expr<'tcx>,
+) -> Vec<(Span, Effect)> {
+    let mut parents = cx.tcx.hir().parent_iter(if_expr.hir_id);
+    let (stmt_id, block) = match (parents.next(), parents.next()) {
+        (Some((stmt_id, Node::Stmt(_))), Some((_, Node::Block(block)))) => (stmt_id, block),
+        _ => return Vec::new(),
+    };
+    let position = match block.stmts.iter().position(|stmt| stmt.hir_id == stmt_id) {
+        Some(position) => position,
+        None => return Vec::new(),
+    };
+    let exprs = block.stmts[position + 1..]
+        .iter()
+        .filter_map(|stmt| match stmt.kind {
+            StmtKind::Local(local) => local.init,
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => Some(expr),
+            StmtKind::Item(_) => None,
+        })
+        .chain(block.expr);
+    let mut effects = Vec::new();
+    for expr in exprs {
+        visit_expr_no_bodies(expr, |expr| {
+            if !is_skipped_expansion(DEGENERATE_VALUE_BYPASS, expr.span) {
+                if let Some(effect) = effect(cx, body, expr) {
+                    effects.push((expr.span, effect));
+                }
+            }
+            false
+        });
+    }
+    effects
+}
+
+/// Return the kind of the effect of `expr`, if it is one
+fn effect<'tcx>(cx: &LateContext<'tcx>, body: &Body<'tcx>, expr: &Expr<'tcx>) -> Option<Effect> {
+    match expr.kind {
+        ExprKind::Assign(lhs, _, _) | ExprKind::AssignOp(_, lhs, _)
+            if writes_through_reference(cx, body, lhs) =>
+        {
+            Some(Effect::StateChange)
+        }
+        ExprKind::Call(..) | ExprKind::MethodCall(..) => {
+            if is_expr_method_call(cx, expr, &paths::ANCHOR_LANG_ADD_LAMPORTS).is_some()
+                || is_expr_method_call(cx, expr, &paths::ANCHOR_LANG_SUB_LAMPORTS).is_some()
+            {
+                Some(Effect::StateChange)
+            } else if is_cpi(cx, expr) {
+                Some(Effect::Cpi)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Return true if `lhs` is not a local variable owned by the function or a part of one, e.g.,
+/// `ctx.accounts.vault.balance`, `vault.balance` where `vault` is a reference, or
+/// `**account.lamports.borrow_mut()`
+fn writes_through_reference(cx: &LateContext<'_>, body: &Body<'_>, mut lhs: &Expr<'_>) -> bool {
+    while let ExprKind::Field(inner, _)
+    | ExprKind::Index(inner, _, _)
+    | ExprKind::Unary(UnOp::Deref, inner) = lhs.kind
+    {
+        lhs = inner;
+    }
+    match path_to_local(lhs) {
+        Some(local) => {
+            body.params.iter().any(|param| param.pat.hir_id == local)
+                || cx.typeck_results().node_type(local).is_ref()
+        }
+        None => true,
+    }
+}
+
+/// Return true if `expr` calls `invoke` or `invoke_signed`, or passes a `CpiContext`
+fn is_cpi<'tcx>(cx: &LateContext<'tcx>, expr: &Expr<'tcx>) -> bool {
+    if fn_def_id(cx, expr).map_or(false, |def_id| {
+        match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_INVOKE)
+            || match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_INVOKE_SIGNED)
+    }) {
+        return true;
+    }
+    let args = match expr.kind {
+        ExprKind::Call(_, args) | ExprKind::MethodCall(_, _, args, _) => args,
+        _ => return false,
+    };
+    args.iter().any(|arg| {
+        let ty = cx.typeck_results().expr_ty(arg).peel_refs();
+        match_type(cx, ty, &paths::ANCHOR_LANG_CPI_CONTEXT)
+    })
+}
+
+fn report(
+    cx: &LateContext<'_>,
+    hir_id: HirId,
+    span: Span,
+    param: Symbol,
+    check: Span,
+    effects: &[(Span, Effect)],
+) {
+    let msg = format!(
+        "the checks guarded by this condition are skipped for some values of `{param}`, but the effects after them are not"
+    );
+    span_lint_hir_and_then(cx, DEGENERATE_VALUE_BYPASS, hir_id, span, &msg, |diag| {
+        diag.span_note(check, "the check is here");
+        for &(effect_span, effect) in effects {
+            let note = match effect {
+                Effect::StateChange => {
+                    format!("this state change is made for every value of `{param}`")
+                }
+                Effect::Cpi => format!("this CPI is made for every value of `{param}`"),
+            };
+            diag.span_note(effect_span, note);
+        }
+        diag.help(format!(
+            "make the checks before the `if`, so that they are made for every value of `{param}`"
+        ));
+    });
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
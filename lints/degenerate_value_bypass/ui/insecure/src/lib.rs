@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod degenerate_value_bypass_insecure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        if amount > 0 {
+            if ctx.accounts.vault.authority != ctx.accounts.authority.key() {
+                return err!(ErrorCode::Unauthorized);
+            }
+            ctx.accounts.vault.balance -= amount;
+        }
+        ctx.accounts.vault.withdrawn = true;
+        Ok(())
+    }
+
+    pub fn pay(ctx: Context<Pay>, amount: u64) -> Result<()> {
+        if amount != 0 {
+            require_keys_eq!(
+                ctx.accounts.payer.key(),
+                ctx.accounts.config.admin,
+                ErrorCode::Unauthorized
+            );
+        }
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.recipient.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_context, amount)
+    }
+
+    pub fn redeem(ctx: Context<Redeem>, shares: u64) -> Result<()> {
+        let position = &mut ctx.accounts.position;
+        if shares == 0 {
+            msg!("nothing to redeem");
+        } else {
+            require!(
+                position.owner == ctx.accounts.owner.key(),
+                ErrorCode::Unauthorized
+            );
+            position.shares -= shares;
+        }
+        position.closed = true;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Pay<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    config: Account<'info, Config>,
+    /// CHECK: only receives lamports
+    #[account(mut)]
+    recipient: AccountInfo<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Redeem<'info> {
+    #[account(mut)]
+    position: Account<'info, Position>,
+    owner: Signer<'info>,
+}
+
+#[account]
+pub struct Vault {
+    authority: Pubkey,
+    balance: u64,
+    withdrawn: bool,
+}
+
+#[account]
+pub struct Config {
+    admin: Pubkey,
+}
+
+#[account]
+pub struct Position {
+    owner: Pubkey,
+    shares: u64,
+    closed: bool,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    Unauthorized,
+}
+
+#[allow(dead_code)]
+fn main() {}
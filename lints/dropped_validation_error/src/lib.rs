@@ -0,0 +1,164 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{is_res_lang_ctor, path_res, ty::is_type_diagnostic_item};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::LocalDefId, intravisit::FnKind, Body, Expr, ExprKind, FnDecl, LangItem, StmtKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty;
+use rustc_span::{sym, Span};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, paths, sarif,
+    utils::visit_expr_no_bodies,
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the statements which construct an error, but neither return nor propagate it,
+    /// e.g., `Err::<(), _>(ErrorCode::Unauthorized);`, `error!(ErrorCode::Unauthorized);` or
+    /// `ProgramError::MissingRequiredSignature;`, and the calls of validation functions whose
+    /// `Result` is dropped instead of being propagated with `?`, e.g., `check_authority(&ctx);`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The statement is usually a validation which misses a `return`: the error is dropped, and
+    /// the function continues as if the validation succeeded, e.g., it transfers the funds to an
+    /// account which is not the authority.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Only `Err(...)`, `anchor_lang::error::Error` and `ProgramError` values, and `Result`s whose
+    /// error type is one of the latter two, are considered. A value of an `#[error_code]` enum
+    /// which is neither wrapped in `Err` nor converted into an error, e.g.,
+    /// `ErrorCode::Unauthorized;`, is not reported. Rust also warns about a dropped `Result` with
+    /// the `unused_must_use` lint.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// if ctx.accounts.vault.authority != ctx.accounts.authority.key() {
+    ///     error!(ErrorCode::Unauthorized);
+    /// }
+    /// check_amount(amount, &ctx.accounts.vault);
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// if ctx.accounts.vault.authority != ctx.accounts.authority.key() {
+    ///     return err!(ErrorCode::Unauthorized);
+    /// }
+    /// check_amount(amount, &ctx.accounts.vault)?;
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - for each statement `e;` of a block which is not generated by a macro
+    ///   - if `e` is `Err(...)`, or its type is `anchor_lang::error::Error`, `ProgramError` or a
+    ///     `Result` with one of them as error type, report the statement
+    pub DROPPED_VALIDATION_ERROR,
+    Warn,
+    "errors which are constructed but neither returned nor propagated"
+}
+
+impl<'tcx> LateLintPass<'tcx> for DroppedValidationError {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(DROPPED_VALIDATION_ERROR, span) {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if let ExprKind::Block(block, _) = expr.kind;
+                if !is_skipped_expansion(DROPPED_VALIDATION_ERROR, block.span);
+                then {
+                    for stmt in block.stmts {
+                        if_chain! {
+                            if let StmtKind::Semi(value) = stmt.kind;
+                            if is_error_value(cx, value);
+                            then {
+                                report(cx, value);
+                            }
+                        }
+                    }
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[DROPPED_VALIDATION_ERROR]);
+    }
+}
+
+/// Return true if `expr` is `Err(...)`, an `anchor_lang::error::Error` or a `ProgramError`, or a
+/// `Result` with one of the latter two as error type
+fn is_error_value<'tcx>(cx: &LateContext<'tcx>, expr: &Expr<'tcx>) -> bool {
+    if_chain! {
+        if let ExprKind::Call(ctor, _) = expr.kind;
+        if is_res_lang_ctor(cx, path_res(cx, ctor), LangItem::ResultErr);
+        then {
+            return true;
+        }
+    }
+    let ty = cx.typeck_results().expr_ty(expr);
+    match ty.kind() {
+        ty::Adt(_, args) if is_type_diagnostic_item(cx, ty, sym::Result) => {
+            paths::is_error_ty(cx, args.type_at(1))
+        }
+        _ => paths::is_error_ty(cx, ty),
+    }
+}
+
+fn report(cx: &LateContext<'_>, expr: &Expr<'_>) {
+    // e.g., the call of `err!(...)`
+    let span = expr.span.source_callsite();
+    span_lint_hir_and_then(
+        cx,
+        DROPPED_VALIDATION_ERROR,
+        expr.hir_id,
+        span,
+        "this error is neither returned nor propagated, so the function continues",
+        |diag| {
+            diag.help("return or propagate the error, e.g., `return Err(...)` or `...?`");
+        },
+    );
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
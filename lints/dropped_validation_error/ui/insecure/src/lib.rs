@@ -0,0 +1,60 @@
+// The statements below are also reported by rustc's lints, which are not under test
+#![allow(path_statements, unused_must_use)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_error::ProgramError;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod dropped_validation_error_insecure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        if ctx.accounts.vault.authority != ctx.accounts.authority.key() {
+            error!(ErrorCode::Unauthorized);
+        }
+        check_amount(&ctx.accounts.vault, amount);
+        ctx.accounts.vault.balance -= amount;
+        Ok(())
+    }
+
+    pub fn close(ctx: Context<Withdraw>) -> Result<()> {
+        if ctx.accounts.vault.balance != 0 {
+            msg!("The vault is not empty");
+            ProgramError::InvalidAccountData;
+        }
+        ctx.accounts.vault.closed = true;
+        Ok(())
+    }
+}
+
+fn check_amount(vault: &Vault, amount: u64) -> Result<()> {
+    if amount > vault.balance {
+        return err!(ErrorCode::InsufficientFunds);
+    }
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    authority: Signer<'info>,
+}
+
+#[account]
+pub struct Vault {
+    authority: Pubkey,
+    balance: u64,
+    closed: bool,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    Unauthorized,
+    InsufficientFunds,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,376 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use anchor_syn::AccountField;
+use clippy_utils::{
+    fn_def_id, get_trait_def_id, match_any_def_paths,
+    ty::{implements_trait, match_type},
+};
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    BinOpKind, Body, Expr, ExprKind, FnDecl, Item, ItemKind, VariantData,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty;
+use rustc_span::Span;
+use serde::Deserialize;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{account_data_type, get_anchor_accounts_struct, visit_expr_no_bodies},
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports Anchor account types which hold funds but which no instruction closes or withdraws
+    /// from.
+    ///
+    /// An `#[account]` type holds funds if one of its fields has a fund name, e.g., `vault`,
+    /// `escrow`, or `balance`. The lint inspects the instructions which use the account type and
+    /// reports the type if none of them:
+    ///
+    /// - closes an account of the type with the `close` constraint
+    /// - moves lamports out of an account, e.g., `**account.try_borrow_mut_lamports()? -= amount`
+    /// - decreases a field with a fund name, e.g., `state.balance -= amount`
+    /// - makes a CPI signed by the program, e.g., a token transfer from a vault owned by a PDA
+    ///
+    /// The lint is allowed by default. It is intended for reviews of programs which hold user funds
+    /// and can be enabled with `#![warn(funds_lockup)]`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// If no instruction closes an account holding funds, or withdraws from it, the funds
+    /// deposited into the account can never be recovered.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The lint recognizes funds by name and withdrawals by their usual forms. Funds held under
+    /// other names are missed, and any CPI signed by the program is regarded as a withdrawal. Only
+    /// the bodies of the instructions are inspected, not the functions they call.
+    ///
+    /// **Configuration:**
+    ///
+    /// The fund names can be configured in `dylint.toml`:
+    ///
+    /// ```toml
+    /// [funds_lockup]
+    /// fund_names = ["vault", "escrow", "treasury", "lamports", "balance", "deposit"]
+    /// ```
+    ///
+    /// A field has a fund name if its name, in lower case, contains one of the names.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    ///     pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    ///         ...
+    ///         ctx.accounts.escrow.balance += amount;
+    ///         Ok(())
+    ///     }
+    ///     // no instruction withdraws from or closes `Escrow`
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    ///     pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    ///         ...
+    ///         ctx.accounts.escrow.balance -= amount;
+    ///         Ok(())
+    ///     }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// - check_item:
+    ///   - If the item is a struct implementing `anchor_lang::Discriminator` and one of its fields has a
+    ///     fund name, store it as an account type holding funds.
+    ///   - If the item is an Anchor `Accounts` struct, store the account types of the fields with a
+    ///     `close` constraint as closed.
+    /// - check_fn: For every function with a `Context<T>` argument
+    ///   - Store the function name and the account types of the `Account` and `AccountLoader` fields of `T`.
+    ///   - Store whether the body of the function contains a withdrawal: an assignment to, or a subtraction
+    ///     from, a place accessing `lamports`; a `sub_lamports` call; a subtraction from a field with a fund
+    ///     name; a `CpiContext::new_with_signer` or `invoke_signed` call.
+    /// - check_crate_post: For every account type holding funds
+    ///   - If the type is not closed, at least one function uses it, and none of those functions
+    ///     contains a withdrawal, report the type and list the functions inspected.
+    pub FUNDS_LOCKUP,
+    Allow,
+    "account type holding funds which no instruction closes or withdraws from",
+    FundsLockup::new()
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct Config {
+    fund_names: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fund_names: vec![
+                "vault".to_string(),
+                "escrow".to_string(),
+                "treasury".to_string(),
+                "lamports".to_string(),
+                "balance".to_string(),
+                "deposit".to_string(),
+            ],
+        }
+    }
+}
+
+struct FundsLockup {
+    config: Config,
+    /// Account types holding funds, and the spans of their names
+    fund_accounts: Vec<(LocalDefId, Span)>,
+    /// Account types closed by a `close` constraint
+    closed_accounts: FxHashSet<DefId>,
+    instructions: Vec<Instruction>,
+}
+
+struct Instruction {
+    name: String,
+    /// Account types of the `Account` and `AccountLoader` fields of the instruction's `Accounts` struct
+    accounts: FxHashSet<DefId>,
+    withdraws: bool,
+}
+
+impl FundsLockup {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+            fund_accounts: Vec::new(),
+            closed_accounts: FxHashSet::default(),
+            instructions: Vec::new(),
+        }
+    }
+
+    fn is_fund_name(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.config
+            .fund_names
+            .iter()
+            .any(|fund_name| name.contains(fund_name.as_str()))
+    }
+
+    /// Return true if `item` implements `anchor_lang::Discriminator` and one of its fields has a fund name
+    fn holds_funds(
+        &self,
+        cx: &LateContext<'_>,
+        item: &Item<'_>,
+        variant: &VariantData<'_>,
+    ) -> bool {
+        if_chain! {
+            if let Some(trait_def_id) = get_trait_def_id(cx, &paths::ANCHOR_LANG_DISCRIMINATOR);
+            let ty = cx.tcx.type_of(item.owner_id).instantiate_identity();
+            if implements_trait(cx, ty, trait_def_id, &[]);
+            then {
+                variant
+                    .fields()
+                    .iter()
+                    .any(|field| self.is_fund_name(field.ident.as_str()))
+            } else {
+                false
+            }
+        }
+    }
+
+    /// Return true if `expr` contains a withdrawal
+    fn contains_withdrawal<'tcx>(&self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> bool {
+        visit_expr_no_bodies(expr, |expr| match expr.kind {
+            // `**account.try_borrow_mut_lamports()? = 0`
+            ExprKind::Assign(place, _, _) => accesses_lamports(place),
+            // `**account.try_borrow_mut_lamports()? -= amount` or `escrow.balance -= amount`
+            ExprKind::AssignOp(op, place, _) if op.node == BinOpKind::Sub => {
+                accesses_lamports(place)
+                    || matches!(
+                        place.kind,
+                        ExprKind::Field(_, field_name) if self.is_fund_name(field_name.as_str())
+                    )
+            }
+            // `account.sub_lamports(amount)`
+            ExprKind::MethodCall(method_name, _, _, _) => {
+                method_name.ident.as_str() == "sub_lamports"
+            }
+            // `CpiContext::new_with_signer(..)` or `invoke_signed(..)`
+            ExprKind::Call(..) => fn_def_id(cx, expr).map_or(false, |def_id| {
+                match_any_def_paths(
+                    cx,
+                    def_id,
+                    &[
+                        &paths::ANCHOR_CPI_CONTEXT_NEW_SIGNER,
+                        &paths::SOLANA_PROGRAM_INVOKE_SIGNED,
+                    ],
+                )
+                .is_some()
+            }),
+            _ => false,
+        })
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for FundsLockup {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if !item.span.from_expansion();
+            if let ItemKind::Struct(variant, _) = item.kind;
+            then {
+                if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+                    for (item_field, anchor_field) in
+                        variant.fields().iter().zip(accounts_struct.fields.iter())
+                    {
+                        if_chain! {
+                            if let AccountField::Field(field) = anchor_field;
+                            if field.constraints.close.is_some();
+                            let field_ty = cx.tcx.type_of(item_field.def_id).instantiate_identity();
+                            if let Some(account_def_id) = account_data_type(cx, field_ty);
+                            then {
+                                self.closed_accounts.insert(account_def_id);
+                            }
+                        }
+                    }
+                } else if self.holds_funds(cx, item, &variant) {
+                    self.fund_accounts.push((item.owner_id.def_id, item.ident.span));
+                }
+            }
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        fn_kind: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        if_chain! {
+            if !is_skipped_expansion(FUNDS_LOCKUP, span);
+            if let FnKind::ItemFn(ident, _, _) | FnKind::Method(ident, _) = fn_kind;
+            let accounts = context_account_types(cx, local_def_id);
+            if !accounts.is_empty();
+            then {
+                self.instructions.push(Instruction {
+                    name: ident.to_string(),
+                    accounts,
+                    withdraws: self.contains_withdrawal(cx, body.value),
+                });
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for (local_def_id, span) in &self.fund_accounts {
+            let def_id = local_def_id.to_def_id();
+            if self.closed_accounts.contains(&def_id) {
+                continue;
+            }
+            let instructions = self
+                .instructions
+                .iter()
+                .filter(|instruction| instruction.accounts.contains(&def_id))
+                .collect::<Vec<_>>();
+            if instructions.is_empty()
+                || instructions.iter().any(|instruction| instruction.withdraws)
+            {
+                continue;
+            }
+            let names = instructions
+                .iter()
+                .map(|instruction| format!("`{}`", instruction.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            span_lint_hir_and_then(
+                cx,
+                FUNDS_LOCKUP,
+                cx.tcx.local_def_id_to_hir_id(*local_def_id),
+                *span,
+                &format!(
+                    "`{}` holds funds, but no instruction closes it or withdraws from it",
+                    cx.tcx.item_name(def_id)
+                ),
+                |diag| {
+                    diag.note(format!("instructions inspected: {names}"));
+                    diag.help(
+                        "add an instruction which closes the account, e.g., with the `close` \
+                         constraint, or which withdraws the funds",
+                    );
+                },
+            );
+        }
+
+        sarif::write(cx, &[FUNDS_LOCKUP]);
+    }
+}
+
+/// Return the account types of the `Account` and `AccountLoader` fields of `T`, if the function takes a
+/// `Context<T>` argument
+fn context_account_types(cx: &LateContext<'_>, local_def_id: LocalDefId) -> FxHashSet<DefId> {
+    let fn_sig = cx
+        .tcx
+        .fn_sig(local_def_id.to_def_id())
+        .skip_binder()
+        .skip_binder();
+    if_chain! {
+        if let Some(context_ty) = fn_sig
+            .inputs()
+            .iter()
+            .find(|ty| match_type(cx, **ty, &paths::ANCHOR_LANG_CONTEXT));
+        if let ty::Adt(_, args) = context_ty.kind();
+        if let Some(accounts_ty) = args.types().next();
+        if let ty::Adt(accounts_adt_def, accounts_args) = accounts_ty.kind();
+        then {
+            accounts_adt_def
+                .all_fields()
+                .filter_map(|field| account_data_type(cx, field.ty(cx.tcx, accounts_args)))
+                .collect()
+        } else {
+            FxHashSet::default()
+        }
+    }
+}
+
+/// Return true if `place` accesses the lamports of an account: `account.lamports` or
+/// `account.try_borrow_mut_lamports()`
+fn accesses_lamports<'tcx>(place: &'tcx Expr<'tcx>) -> bool {
+    visit_expr_no_bodies(place, |expr| match expr.kind {
+        ExprKind::Field(_, field_name) => field_name.as_str() == "lamports",
+        ExprKind::MethodCall(method_name, _, _, _) => {
+            method_name.ident.as_str() == "try_borrow_mut_lamports"
+        }
+        _ => false,
+    })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,59 @@
+#![warn(funds_lockup)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod funds_lockup_insecure {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        ctx.accounts.escrow.owner = ctx.accounts.owner.key();
+        ctx.accounts.escrow.balance = 0;
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        ctx.accounts.escrow.balance += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = owner, space = 8 + 32 + 8)]
+    escrow: Account<'info, Escrow>,
+    #[account(mut)]
+    owner: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut, has_one = owner)]
+    escrow: Account<'info, Escrow>,
+    #[account(mut)]
+    owner: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct Escrow {
+    owner: Pubkey,
+    balance: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
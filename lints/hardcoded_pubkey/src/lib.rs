@@ -0,0 +1,375 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    fn_def_id, match_any_def_paths, match_def_path,
+    ty::{is_type_diagnostic_item, match_type},
+};
+use rustc_ast::LitKind;
+use rustc_hir::{
+    def_id::LocalDefId, intravisit::FnKind, BinOpKind, Body, Expr, ExprKind, FnDecl, MatchSource,
+    Node, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::{sym, Span};
+use serde::Deserialize;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    known_programs::known_program_by_id,
+    paths, sarif,
+    utils::{peel_addr_of, visit_expr_no_bodies},
+};
+use std::{convert::TryFrom, iter};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the keys written inline in the body of a function, i.e., `pubkey!("...")`,
+    /// `Pubkey::new_from_array([...])`, or a string literal parsed into a `Pubkey`, e.g.,
+    /// `Pubkey::from_str("...")` or `"...".parse::<Pubkey>()`, which are compared with another key
+    /// (`==` or `!=`, including `require_keys_eq!` and `require_keys_neq!`), or used as the program
+    /// of a CPI, i.e., the `program_id` of an `Instruction`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A key written inline has no name telling what it is, so an auditor must look it up to know
+    /// which account or program the code expects. When the key changes, e.g., when the program
+    /// called is redeployed at another address, each of its occurrences must be found and updated,
+    /// and one which is missed keeps accepting the old key. A named constant, the `ID` of a
+    /// `declare_id!` in a module for the program, or an `address = ...` constraint, is declared
+    /// once and documents the key.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Only the keys used directly are reported, not the keys stored in a local variable which is
+    /// then compared.
+    ///
+    /// **Configuration:**
+    ///
+    /// The IDs of the well-known programs (see `solana_lints::known_programs`), e.g., of the System
+    /// and Token programs, are not reported. Other keys can be allowed in `dylint.toml`:
+    ///
+    /// ```toml
+    /// [hardcoded_pubkey]
+    /// allowed_keys = ["whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"]
+    /// ```
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// require_keys_eq!(
+    ///     ctx.accounts.oracle.key(),
+    ///     pubkey!("GvDMxPzN1sCj7L26YDK2HnMRXEQmQ2aemov8YBtPS7vR")
+    /// );
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// pub const ORACLE: Pubkey = pubkey!("GvDMxPzN1sCj7L26YDK2HnMRXEQmQ2aemov8YBtPS7vR");
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct Update<'info> {
+    ///     /// CHECK: the oracle is checked by its address
+    ///     #[account(address = ORACLE)]
+    ///     oracle: UncheckedAccount<'info>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for every function, excluding the functions generated by macros
+    /// - for each key literal in the body of the function:
+    ///   - a call of `Pubkey::new_from_array` with an array of integer literals, e.g., generated by
+    ///     `pubkey!`
+    ///   - or a call with a string literal as its only argument, or a method call on a string
+    ///     literal, returning a `Pubkey` or a `Result<Pubkey, _>`
+    /// - ignore the key if it is the ID of a well-known program, or is allowed in the configuration
+    /// - follow the uses of the key through borrows, derefs, `unwrap`, `expect` and `?`
+    /// - if the key is then compared with `==` or `!=`, or is the `program_id` of an
+    ///   `Instruction`, whether built with a struct expression or with `Instruction::new_with_*`,
+    ///   report the key, at the call of `pubkey!` if it is generated by it
+    pub HARDCODED_PUBKEY,
+    Warn,
+    "a key written inline in a comparison or a CPI instead of a named constant",
+    HardcodedPubkey::new()
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Config {
+    /// The keys, in base58, which may be written inline, in addition to the IDs of
+    /// `solana_lints::known_programs`
+    allowed_keys: Vec<String>,
+}
+
+struct HardcodedPubkey {
+    config: Config,
+}
+
+impl HardcodedPubkey {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+        }
+    }
+}
+
+/// How a key literal is used
+enum KeyUse {
+    /// Compared with `==` or `!=`
+    Comparison,
+    /// The program of an `Instruction`
+    CpiTarget,
+}
+
+impl<'tcx> LateLintPass<'tcx> for HardcodedPubkey {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        if is_skipped_expansion(HARDCODED_PUBKEY, span) {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            let key = match key_literal(cx, expr) {
+                Some(key) => key,
+                None => return false,
+            };
+            if known_program_by_id(&key).is_some() || self.config.allowed_keys.contains(&key) {
+                return false;
+            }
+            if let Some(key_use) = key_use(cx, expr) {
+                report(cx, expr, &key, &key_use);
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[HARDCODED_PUBKEY]);
+    }
+}
+
+fn report(cx: &LateContext<'_>, expr: &Expr<'_>, key: &str, key_use: &KeyUse) {
+    // The key generated by `pubkey!("...")` is reported at the call of the macro. The arguments of
+    // the other macros, e.g., `require_keys_eq!`, keep their own spans.
+    let span = if expr.span.from_expansion() {
+        expr.span.source_callsite()
+    } else {
+        expr.span
+    };
+    let (msg, help) = match key_use {
+        KeyUse::Comparison => (
+            format!("the key `{key}` is written inline in a comparison"),
+            format!(
+                "declare the key as a named constant, e.g., `pub const EXPECTED: Pubkey = pubkey!(\"{key}\");`, or check the account with an `address = EXPECTED` constraint"
+            ),
+        ),
+        KeyUse::CpiTarget => (
+            format!("the program `{key}` of this CPI is written inline"),
+            format!(
+                "declare the ID of the program once, e.g., with `declare_id!(\"{key}\");` in a module for the program, and use its `ID`"
+            ),
+        ),
+    };
+    span_lint_hir_and_then(cx, HARDCODED_PUBKEY, expr.hir_id, span, &msg, |diag| {
+        diag.note(
+            "a key written inline does not tell which account or program it is, and each of its occurrences must be found and updated when it changes",
+        );
+        diag.help(help);
+    });
+}
+
+/// If `expr` is a key literal, return the key in base58:
+/// - `Pubkey::new_from_array([...])` with integer literals, which `pubkey!("...")` expands to
+/// - a call with a string literal as its only argument, or a method call on a string literal,
+///   returning a `Pubkey` or a `Result<Pubkey, _>`, e.g., `Pubkey::from_str("...")`,
+///   `Pubkey::try_from("...")` or `"...".parse::<Pubkey>()`
+fn key_literal(cx: &LateContext<'_>, expr: &Expr<'_>) -> Option<String> {
+    match expr.kind {
+        ExprKind::Call(_, [arg]) => {
+            if fn_def_id(cx, expr).map_or(false, |def_id| {
+                match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_PUBKEY_NEW_FROM_ARRAY)
+            }) {
+                let bytes = match arg.kind {
+                    ExprKind::Array(elems) => {
+                        elems.iter().map(int_literal).collect::<Option<Vec<u8>>>()?
+                    }
+                    _ => return None,
+                };
+                return Some(to_base58(&bytes));
+            }
+            string_key(cx, expr, arg)
+        }
+        ExprKind::MethodCall(_, receiver, [], _) => string_key(cx, expr, receiver),
+        _ => None,
+    }
+}
+
+/// If `string` is a string literal and `expr`, which parses it, returns a `Pubkey` or a
+/// `Result<Pubkey, _>`, return the string
+fn string_key(cx: &LateContext<'_>, expr: &Expr<'_>, string: &Expr<'_>) -> Option<String> {
+    let string = match peel_addr_of(string).kind {
+        ExprKind::Lit(lit) => match lit.node {
+            LitKind::Str(symbol, _) => symbol.to_string(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    if is_pubkey_or_result(cx, cx.typeck_results().expr_ty(expr)) {
+        Some(string)
+    } else {
+        None
+    }
+}
+
+fn is_pubkey_or_result<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>) -> bool {
+    let ty = if is_type_diagnostic_item(cx, ty, sym::Result) {
+        match ty.kind() {
+            ty::Adt(_, args) => args.type_at(0),
+            _ => return false,
+        }
+    } else {
+        ty
+    };
+    match_type(cx, ty, &paths::SOLANA_PROGRAM_PUBKEY)
+}
+
+fn int_literal(expr: &Expr<'_>) -> Option<u8> {
+    match expr.kind {
+        ExprKind::Lit(lit) => match lit.node {
+            LitKind::Int(value, _) => u8::try_from(value).ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Return how the key literal `expr` is used, following it through borrows, derefs, `unwrap`,
+/// `expect` and `?`, if it is compared or is the program of an `Instruction`
+fn key_use(cx: &LateContext<'_>, expr: &Expr<'_>) -> Option<KeyUse> {
+    let mut child_id = expr.hir_id;
+    for (parent_id, node) in cx.tcx.hir().parent_iter(expr.hir_id) {
+        match node {
+            Node::Expr(parent) => match parent.kind {
+                ExprKind::AddrOf(..)
+                | ExprKind::Unary(UnOp::Deref, _)
+                | ExprKind::Match(_, _, MatchSource::TryDesugar(_)) => {}
+                ExprKind::MethodCall(path, receiver, _, _)
+                    if receiver.hir_id == child_id
+                        && matches!(path.ident.as_str(), "unwrap" | "expect") => {}
+                ExprKind::Binary(op, _, _) if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne) => {
+                    return Some(KeyUse::Comparison);
+                }
+                ExprKind::Struct(..)
+                    if match_type(
+                        cx,
+                        cx.typeck_results().expr_ty(parent),
+                        &paths::SOLANA_PROGRAM_INSTRUCTION,
+                    ) =>
+                {
+                    return Some(KeyUse::CpiTarget);
+                }
+                ExprKind::Call(_, [arg, ..]) if arg.hir_id == child_id => {
+                    match fn_def_id(cx, parent).and_then(|def_id| {
+                        match_any_def_paths(
+                            cx,
+                            def_id,
+                            &[
+                                &paths::CORE_BRANCH,
+                                &paths::SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BINCODE,
+                                &paths::SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BORSH,
+                                &paths::SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BYTES,
+                            ],
+                        )
+                    }) {
+                        // `Try::branch`, generated by `?`
+                        Some(0) => {}
+                        Some(_) => return Some(KeyUse::CpiTarget),
+                        None => return None,
+                    }
+                }
+                _ => return None,
+            },
+            // Only the `program_id` field of a struct expression is followed, up to the struct.
+            Node::ExprField(field) if field.ident.as_str() == "program_id" => {}
+            _ => return None,
+        }
+        child_id = parent_id;
+    }
+    None
+}
+
+/// Encode `bytes` in base58, as the `Display` implementation of `Pubkey` does
+fn to_base58(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    // The base58 digits, the least significant first
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in bytes {
+        let mut carry = u32::from(byte);
+        for digit in &mut digits {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    // Each leading zero byte is encoded as a `1`
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    iter::repeat(b'1')
+        .take(zeros)
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&digit| ALPHABET[usize::from(digit)]),
+        )
+        .map(char::from)
+        .collect()
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
+
+#[test]
+fn secure_allowed_keys() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "secure-allowed-keys")
+        .dylint_toml(
+            "hardcoded_pubkey.allowed_keys = [\"GvDMxPzN1sCj7L26YDK2HnMRXEQmQ2aemov8YBtPS7vR\"]",
+        )
+        .run();
+}
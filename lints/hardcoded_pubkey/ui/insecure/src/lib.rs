@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    pubkey,
+};
+use std::str::FromStr;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod hardcoded_pubkey_insecure {
+    use super::*;
+
+    pub fn update(ctx: Context<Update>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.oracle.key(),
+            pubkey!("GvDMxPzN1sCj7L26YDK2HnMRXEQmQ2aemov8YBtPS7vR")
+        );
+        if ctx.accounts.admin.key()
+            != Pubkey::from_str("JUP6LkbZbjS1jKKwapdHNy74zoZ3EH4zBrX3bHjVD9v").unwrap()
+        {
+            return err!(ErrorCode::ConstraintAddress);
+        }
+        require!(
+            ctx.accounts.market.key()
+                == Pubkey::new_from_array([
+                    126, 140, 8, 135, 96, 191, 222, 29, 221, 207, 50, 193, 127, 32, 155, 130, 66,
+                    238, 82, 170, 241, 49, 250, 205, 136, 208, 234, 44, 109, 11, 6, 242,
+                ]),
+            ErrorCode::ConstraintAddress
+        );
+        Ok(())
+    }
+
+    pub fn swap(ctx: Context<Swap>) -> Result<()> {
+        let ix = Instruction {
+            program_id: "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"
+                .parse::<Pubkey>()
+                .unwrap(),
+            accounts: vec![AccountMeta::new(ctx.accounts.pool.key(), false)],
+            data: vec![],
+        };
+        invoke(&ix, &[ctx.accounts.pool.to_account_info()])?;
+        let ix = Instruction::new_with_bytes(
+            pubkey!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc"),
+            &[],
+            vec![AccountMeta::new(ctx.accounts.pool.key(), false)],
+        );
+        invoke(&ix, &[ctx.accounts.pool.to_account_info()])?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    /// CHECK: the oracle is checked by its key
+    oracle: UncheckedAccount<'info>,
+    admin: Signer<'info>,
+    /// CHECK: the market is checked by its key
+    market: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    /// CHECK: the pool is checked by the called program
+    #[account(mut)]
+    pool: UncheckedAccount<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+// The key of the oracle is allowed in `dylint.toml`
+#[program]
+pub mod hardcoded_pubkey_secure_allowed_keys {
+    use super::*;
+
+    pub fn update(ctx: Context<Update>) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.oracle.key(),
+            pubkey!("GvDMxPzN1sCj7L26YDK2HnMRXEQmQ2aemov8YBtPS7vR")
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    /// CHECK: the oracle is checked by its key
+    oracle: UncheckedAccount<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+    pubkey,
+};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+pub const ORACLE: Pubkey = pubkey!("GvDMxPzN1sCj7L26YDK2HnMRXEQmQ2aemov8YBtPS7vR");
+
+pub mod whirlpool {
+    use super::*;
+
+    declare_id!("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc");
+}
+
+#[program]
+pub mod hardcoded_pubkey_secure {
+    use super::*;
+
+    pub fn update(ctx: Context<Update>) -> Result<()> {
+        require_keys_eq!(ctx.accounts.oracle.key(), ORACLE);
+        // The IDs of the well-known programs are not reported
+        require_keys_eq!(
+            ctx.accounts.token_program.key(),
+            pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+        );
+        // The key is neither compared nor the program of a CPI
+        msg!("{}", pubkey!("JUP6LkbZbjS1jKKwapdHNy74zoZ3EH4zBrX3bHjVD9v"));
+        Ok(())
+    }
+
+    pub fn swap(ctx: Context<Swap>) -> Result<()> {
+        let ix = Instruction {
+            program_id: whirlpool::ID,
+            accounts: vec![AccountMeta::new(ctx.accounts.pool.key(), false)],
+            data: vec![],
+        };
+        invoke(&ix, &[ctx.accounts.pool.to_account_info()])?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    /// CHECK: the oracle is checked by its key
+    oracle: UncheckedAccount<'info>,
+    /// CHECK: the market is checked by its address
+    #[account(address = pubkey!("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"))]
+    market: UncheckedAccount<'info>,
+    /// CHECK: the token program is checked by its key
+    token_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    /// CHECK: the pool is checked by the called program
+    #[account(mut, owner = whirlpool::ID)]
+    pool: UncheckedAccount<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,241 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{fn_def_id, is_in_cfg_test, is_in_test_function};
+use if_chain::if_chain;
+use rustc_ast::NestedMetaItem;
+use rustc_hir::{def_id::DefId, Expr, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::sym;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, paths, sarif,
+    utils::is_anchor_program,
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports uses of APIs which are only available on the host in on-chain programs:
+    ///
+    /// - `std::time::SystemTime::now` and `std::time::Instant::now`
+    /// - `rand::thread_rng` and `rand::random`
+    /// - the functions of `std::fs`, `std::net`, and `std::thread`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// On-chain programs have no system clock, no source of randomness, no filesystem, no network,
+    /// and a single thread. The APIs either fail to build for the Solana target, which is often only
+    /// noticed when the program is deployed, or they panic at runtime. This happens easily when
+    /// code is shared between off-chain and on-chain crates.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// When the program is checked for the host, code is regarded as off-chain only if it is under a
+    /// `cfg` attribute mentioning the Solana target, e.g., `#[cfg(not(target_os = "solana"))]`. Code
+    /// gated by a feature, e.g., `#[cfg(feature = "client")]`, is reported.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    ///     let now = SystemTime::now()
+    ///         .duration_since(UNIX_EPOCH)
+    ///         .unwrap()
+    ///         .as_secs();
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    ///     let now = Clock::get()?.unix_timestamp;
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// - check_crate: Determine whether the crate is compiled for on-chain use:
+    ///   - The target is the Solana target (`target_os = "solana"` or `target_arch` is `bpf` or `sbf`)
+    ///   - Or the crate is an Anchor program or defines an `entrypoint` function, e.g., with
+    ///     `solana_program::entrypoint!`. This is the case when the lints are run for the host.
+    /// - check_expr: If the crate is compiled for on-chain use, for every function call
+    ///   - If the called function's path starts with the path of one of the APIs, e.g., `std::fs`
+    ///   - And the call is not in a test, nor in an item under a `cfg` attribute mentioning the Solana target
+    ///   - Report the call
+    pub HOST_ONLY_API,
+    Warn,
+    "use of host-only APIs in on-chain programs",
+    HostOnlyApi::default()
+}
+
+#[derive(Default)]
+struct HostOnlyApi {
+    on_chain: bool,
+}
+
+struct Api {
+    path: &'static [&'static str],
+    name: &'static str,
+    help: &'static str,
+}
+
+const CLOCK_HELP: &str = "use the `Clock` sysvar, e.g., `Clock::get()?.unix_timestamp`";
+const RANDOMNESS_HELP: &str =
+    "on-chain randomness must come from a randomness oracle or a commit-reveal scheme";
+
+const APIS: &[Api] = &[
+    Api {
+        path: &paths::STD_TIME_SYSTEM_TIME_NOW,
+        name: "SystemTime::now",
+        help: CLOCK_HELP,
+    },
+    Api {
+        path: &paths::STD_TIME_INSTANT_NOW,
+        name: "Instant::now",
+        help: CLOCK_HELP,
+    },
+    Api {
+        path: &paths::RAND_THREAD_RNG,
+        name: "rand::thread_rng",
+        help: RANDOMNESS_HELP,
+    },
+    Api {
+        path: &paths::RAND_RANDOM,
+        name: "rand::random",
+        help: RANDOMNESS_HELP,
+    },
+    Api {
+        path: &paths::STD_FS,
+        name: "std::fs",
+        help: "pass the data to the program in an account or in the instruction data",
+    },
+    Api {
+        path: &paths::STD_NET,
+        name: "std::net",
+        help: "pass the data to the program in an account or in the instruction data",
+    },
+    Api {
+        path: &paths::STD_THREAD,
+        name: "std::thread",
+        help: "on-chain programs run on a single thread",
+    },
+];
+
+impl<'tcx> LateLintPass<'tcx> for HostOnlyApi {
+    fn check_crate(&mut self, cx: &LateContext<'tcx>) {
+        self.on_chain = is_solana_target(cx) || is_anchor_program(cx) || defines_entrypoint(cx);
+    }
+
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if_chain! {
+            if self.on_chain;
+            if !is_skipped_expansion(HOST_ONLY_API, expr.span);
+            if let Some(def_id) = fn_def_id(cx, expr);
+            if let Some(api) = APIS.iter().find(|api| def_path_starts_with(cx, def_id, api.path));
+            if !is_in_test_function(cx.tcx, expr.hir_id);
+            if !is_in_cfg_test(cx.tcx, expr.hir_id);
+            if !is_under_solana_cfg(cx, expr);
+            then {
+                span_lint_hir_and_then(
+                    cx,
+                    HOST_ONLY_API,
+                    expr.hir_id,
+                    expr.span,
+                    &format!("`{}` is not available in on-chain programs", api.name),
+                    |diag| {
+                        diag.note("it fails to build for the Solana target or panics at runtime");
+                        diag.help(api.help);
+                    },
+                );
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[HOST_ONLY_API]);
+    }
+}
+
+/// Return true if the crate is compiled for the Solana target
+fn is_solana_target(cx: &LateContext<'_>) -> bool {
+    let target = &cx.tcx.sess.target;
+    target.os == "solana" || target.arch == "bpf" || target.arch == "sbf"
+}
+
+/// Return true if the crate defines a function named `entrypoint`, e.g., with
+/// `solana_program::entrypoint!`
+fn defines_entrypoint(cx: &LateContext<'_>) -> bool {
+    let hir_map = cx.tcx.hir();
+    hir_map.items().any(|item_id| {
+        let item = hir_map.item(item_id);
+        matches!(item.kind, ItemKind::Fn(..)) && item.ident.as_str() == "entrypoint"
+    })
+}
+
+/// Return true if the path of `def_id` starts with `path`, e.g., `std::fs::read` starts with `std::fs`
+fn def_path_starts_with(cx: &LateContext<'_>, def_id: DefId, path: &[&str]) -> bool {
+    let def_path = cx.get_def_path(def_id);
+    def_path.len() >= path.len()
+        && def_path
+            .iter()
+            .zip(path)
+            .all(|(segment, name)| segment.as_str() == *name)
+}
+
+/// Return true if `expr` is in an item under a `cfg` attribute which mentions the Solana target.
+/// When the crate is compiled for the host, such code is off-chain code, e.g., under
+/// `#[cfg(not(target_os = "solana"))]`.
+fn is_under_solana_cfg(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    let hir_map = cx.tcx.hir();
+    hir_map.parent_id_iter(expr.hir_id).any(|hir_id| {
+        hir_map.attrs(hir_id).iter().any(|attr| {
+            attr.has_name(sym::cfg)
+                && attr
+                    .meta_item_list()
+                    .map_or(false, |items| mentions_solana_target(&items))
+        })
+    })
+}
+
+/// Return true if the `cfg` predicate mentions `target_os = "solana"`, `target_arch = "bpf"`, or
+/// `target_arch = "sbf"`
+fn mentions_solana_target(items: &[NestedMetaItem]) -> bool {
+    items.iter().any(|item| {
+        item.meta_item().map_or(false, |meta| {
+            if let Some(items) = meta.meta_item_list() {
+                mentions_solana_target(items)
+            } else {
+                let value = meta.value_str();
+                (meta.has_name(sym::target_os)
+                    && value.map_or(false, |value| value.as_str() == "solana"))
+                    || (meta.has_name(sym::target_arch)
+                        && value.map_or(false, |value| matches!(value.as_str(), "bpf" | "sbf")))
+            }
+        })
+    })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn insecure_non_anchor() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-non-anchor");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
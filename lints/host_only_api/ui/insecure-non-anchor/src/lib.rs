@@ -0,0 +1,21 @@
+use solana_program::{
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg, pubkey::Pubkey,
+};
+use std::time::Instant;
+
+entrypoint!(process_instruction);
+fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let start = Instant::now();
+    if std::net::TcpStream::connect("127.0.0.1:8899").is_ok() {
+        msg!("connected");
+    }
+    msg!("took {:?}", start.elapsed());
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use rand::Rng;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod host_only_api_insecure {
+    use super::*;
+
+    pub fn start_round(ctx: Context<StartRound>) -> Result<()> {
+        let round = &mut ctx.accounts.round;
+        round.started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        round.seed = rand::random();
+        round.winner = rand::thread_rng().gen_range(0..round.players);
+        Ok(())
+    }
+
+    pub fn load_config(_ctx: Context<StartRound>) -> Result<()> {
+        let config = std::fs::read_to_string("config.toml").unwrap();
+        msg!("{}", config);
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct StartRound<'info> {
+    #[account(mut)]
+    round: Account<'info, Round>,
+}
+
+#[account]
+pub struct Round {
+    started_at: i64,
+    seed: u64,
+    players: u32,
+    winner: u32,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod host_only_api_secure {
+    use super::*;
+
+    pub fn start_round(ctx: Context<StartRound>, seed: u64) -> Result<()> {
+        let round = &mut ctx.accounts.round;
+        round.started_at = Clock::get()?.unix_timestamp;
+        round.seed = seed;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct StartRound<'info> {
+    #[account(mut)]
+    round: Account<'info, Round>,
+}
+
+#[account]
+pub struct Round {
+    started_at: i64,
+    seed: u64,
+}
+
+// Off-chain code shared with the client.
+#[cfg(not(target_os = "solana"))]
+pub mod client {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    pub fn new_seed() -> u64 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        now.as_nanos() as u64 ^ rand::random::<u64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn seeds_differ() {
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        assert_ne!(super::client::new_seed(), super::client::new_seed());
+    }
+}
+
+#[allow(dead_code)]
+fn main() {}
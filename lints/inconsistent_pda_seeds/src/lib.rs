@@ -0,0 +1,336 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::AccountField;
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, FnDecl, HirId, Item, ItemKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    sarif,
+    seeds::{display_seeds, field_seeds, find_program_address_seeds, Seed},
+    utils::{
+        account_data_type, context_accounts_struct, get_anchor_accounts_struct,
+        visit_expr_no_bodies,
+    },
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the seeds of a PDA which differ, in order or in content, from the seeds the account
+    /// is created with. The lint compares the seeds of every account type across the instructions:
+    ///
+    /// - An account is created with the seeds of an `#[account(init, seeds = [...], bump)]` field,
+    ///   or with the seeds passed to `Pubkey::find_program_address` by the instruction which
+    ///   initializes the account.
+    /// - The other instructions use the account with the seeds of an
+    ///   `#[account(seeds = [...], bump)]` field.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The address of a PDA is derived from its seeds, in order. If an instruction uses the seeds
+    /// in a different order, or with a different prefix, it derives a different address than the
+    /// one the account was created at. The instruction then fails for every existing account, or,
+    /// if the derived address can be created as well, operates on a different account than the
+    /// other instructions.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The seeds are compared by the accounts they name, e.g., `maker.key().as_ref()` is the seed
+    /// `maker`. Instructions may name the same account differently, so seeds which only differ by
+    /// the names of the accounts are regarded as the same. Seeds which differ in their byte string
+    /// literals, in their number, or in their order are reported.
+    ///
+    /// The accounts are grouped by their type, e.g., `Account<'info, Escrow>`. A program using
+    /// different seeds for two PDAs of the same type is reported as well.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Initialize<'info> {
+    ///     #[account(init, payer = maker, space = 8 + Escrow::INIT_SPACE, seeds = [b"escrow", maker.key().as_ref(), taker.key().as_ref()], bump)]
+    ///     escrow: Account<'info, Escrow>,
+    ///     ...
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct Exchange<'info> {
+    ///     #[account(seeds = [b"escrow", taker.key().as_ref(), maker.key().as_ref()], bump)]
+    ///     escrow: Account<'info, Escrow>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Exchange<'info> {
+    ///     #[account(seeds = [b"escrow", maker.key().as_ref(), taker.key().as_ref()], bump)]
+    ///     escrow: Account<'info, Escrow>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - For each Anchor `Accounts` struct
+    ///   - For each field of type `Account<'info, T>` or `AccountLoader<'info, T>`
+    ///     - If the field has the `init` constraint, record that the struct initializes `T`
+    ///     - If the field has the `seeds` constraint and the seeds are of the current program
+    ///       (no `seeds::program`), record the seeds of `T`. The seeds create `T` if the field has
+    ///       the `init` constraint.
+    ///
+    /// check_fn:
+    ///
+    /// - For every function which takes a `Context<S>` argument, excluding the functions generated by macros
+    ///   - record the seeds passed to each `Pubkey::find_program_address` call in the function
+    ///
+    /// check_crate_post:
+    ///
+    /// - For each `Pubkey::find_program_address` call, if its `S` initializes a single account type `T`,
+    ///   record the seeds as creating `T`
+    /// - For each account type `T`, compare the seeds of `T` with the first seeds which create `T`
+    ///   - Normalize the seeds: `&x`, `x.as_ref()`, `x.key()`, `x.key` and `ctx.accounts.x` are the seed `x`,
+    ///     `"x".as_bytes()` is the seed `b"x"`
+    ///   - If the seeds are the same seeds in a different order, report the seeds
+    ///   - Else if the number of the seeds or the byte string literals differ, report the seeds
+    pub INCONSISTENT_PDA_SEEDS,
+    Warn,
+    "PDA seeds which differ from the seeds the account is created with",
+    InconsistentPdaSeeds::default()
+}
+
+#[derive(Default)]
+struct InconsistentPdaSeeds {
+    /// The seeds of the PDAs, in the order they are found
+    pda_seeds: Vec<PdaSeeds>,
+    /// The account types initialized by each `Accounts` struct
+    initialized: FxHashMap<DefId, Vec<DefId>>,
+    /// `Pubkey::find_program_address` calls: (`Accounts` struct def id of the instruction, seeds, call hir id)
+    derivations: Vec<(DefId, Vec<Seed>, HirId)>,
+}
+
+/// The seeds of an account of type `account`
+struct PdaSeeds {
+    account: DefId,
+    seeds: Vec<Seed>,
+    /// The `Accounts` struct field or the `Pubkey::find_program_address` call
+    hir_id: HirId,
+    span: Span,
+    /// Whether the account is created with the seeds
+    creates: bool,
+}
+
+enum Mismatch {
+    Order,
+    Content,
+}
+
+impl<'tcx> LateLintPass<'tcx> for InconsistentPdaSeeds {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                for (item_field, anchor_field) in
+                    variant.fields().iter().zip(accounts_struct.fields.iter())
+                {
+                    // Composite fields are checked when their own struct is checked.
+                    if_chain! {
+                        if let AccountField::Field(field) = anchor_field;
+                        let field_ty = cx.tcx.type_of(item_field.def_id).skip_binder();
+                        if let Some(account) = account_data_type(cx, field_ty);
+                        then {
+                            if field.constraints.init.is_some() {
+                                self.initialized
+                                    .entry(item.owner_id.to_def_id())
+                                    .or_default()
+                                    .push(account);
+                            }
+                            if_chain! {
+                                if let Some(seeds) = field_seeds(field);
+                                if let Some(seeds_group) = &field.constraints.seeds;
+                                then {
+                                    self.pda_seeds.push(PdaSeeds {
+                                        account,
+                                        seeds,
+                                        hir_id: item_field.hir_id,
+                                        span: item_field.span,
+                                        creates: seeds_group.is_init,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        fn_kind: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        if_chain! {
+            if !is_skipped_expansion(INCONSISTENT_PDA_SEEDS, span);
+            if !matches!(fn_kind, FnKind::Closure);
+            if let Some(accounts_struct) = context_accounts_struct(cx, local_def_id);
+            then {
+                visit_expr_no_bodies(body.value, |expr| {
+                    if let Some(seeds) = find_program_address_seeds(cx, expr) {
+                        self.derivations.push((accounts_struct, seeds, expr.hir_id));
+                    }
+                    false
+                });
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for (accounts_struct, seeds, hir_id) in self.derivations.drain(..) {
+            // The seeds create an account only if the instruction initializes a single account type.
+            if let Some([account]) = self.initialized.get(&accounts_struct).map(Vec::as_slice) {
+                self.pda_seeds.push(PdaSeeds {
+                    account: *account,
+                    seeds,
+                    hir_id,
+                    span: cx.tcx.hir().span(hir_id),
+                    creates: true,
+                });
+            }
+        }
+
+        let mut created: FxHashMap<DefId, &PdaSeeds> = FxHashMap::default();
+        for pda_seeds in &self.pda_seeds {
+            if pda_seeds.creates {
+                created.entry(pda_seeds.account).or_insert(pda_seeds);
+            }
+        }
+
+        for pda_seeds in &self.pda_seeds {
+            if_chain! {
+                if let Some(creation) = created.get(&pda_seeds.account);
+                if !std::ptr::eq(*creation, pda_seeds);
+                if let Some(mismatch) = compare_seeds(&creation.seeds, &pda_seeds.seeds);
+                then {
+                    let account_name = cx.tcx.item_name(pda_seeds.account);
+                    let msg = match mismatch {
+                        Mismatch::Order => format!(
+                            "the seeds of `{account_name}` are in a different order than the seeds it is created with"
+                        ),
+                        Mismatch::Content => format!(
+                            "the seeds of `{account_name}` differ from the seeds it is created with"
+                        ),
+                    };
+                    span_lint_hir_and_then(
+                        cx,
+                        INCONSISTENT_PDA_SEEDS,
+                        pda_seeds.hir_id,
+                        pda_seeds.span,
+                        &msg,
+                        |diag| {
+                            diag.span_note(
+                                creation.span,
+                                format!(
+                                    "`{account_name}` is created with the seeds `{}`",
+                                    display_seeds(&creation.seeds)
+                                ),
+                            );
+                            diag.note(format!(
+                                "the seeds here are `{}`, which derive a different address",
+                                display_seeds(&pda_seeds.seeds)
+                            ));
+                        },
+                    );
+                }
+            }
+        }
+
+        sarif::write(cx, &[INCONSISTENT_PDA_SEEDS]);
+    }
+}
+
+/// Compare the seeds `seeds` of an account with the seeds `created` the account is created with
+/// - If the seeds are the same, return None
+/// - If the number of the seeds differ, return `Content`
+/// - If the seeds are the same seeds in a different order, return `Order`
+/// - If the byte string literals are the same at the same positions, the seeds only differ by the names of the
+///   accounts. Return None.
+/// - If the byte string literals are the same in a different order, return `Order`, else `Content`
+fn compare_seeds(created: &[Seed], seeds: &[Seed]) -> Option<Mismatch> {
+    if created == seeds {
+        return None;
+    }
+    if created.len() != seeds.len() {
+        return Some(Mismatch::Content);
+    }
+    if sorted(created.iter()) == sorted(seeds.iter()) {
+        return Some(Mismatch::Order);
+    }
+    let created_literals = literals(created);
+    let seeds_literals = literals(seeds);
+    if created_literals == seeds_literals {
+        None
+    } else if sorted(created_literals.iter().map(|(_, seed)| *seed))
+        == sorted(seeds_literals.iter().map(|(_, seed)| *seed))
+    {
+        Some(Mismatch::Order)
+    } else {
+        Some(Mismatch::Content)
+    }
+}
+
+/// Return the byte string literals of the seeds along with their positions
+fn literals(seeds: &[Seed]) -> Vec<(usize, &Seed)> {
+    seeds
+        .iter()
+        .enumerate()
+        .filter(|(_, seed)| matches!(seed, Seed::Bytes(_)))
+        .collect()
+}
+
+fn sorted<'a>(seeds: impl Iterator<Item = &'a Seed>) -> Vec<&'a Seed> {
+    let mut seeds = seeds.collect::<Vec<_>>();
+    seeds.sort();
+    seeds
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
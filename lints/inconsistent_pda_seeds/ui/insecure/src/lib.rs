@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod inconsistent_pda_seeds_insecure {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>, amount: u64) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.maker = ctx.accounts.maker.key();
+        escrow.taker = ctx.accounts.taker.key();
+        escrow.amount = amount;
+        Ok(())
+    }
+
+    pub fn exchange(_ctx: Context<Exchange>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn cancel(_ctx: Context<Cancel>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn open_vault(ctx: Context<OpenVault>) -> Result<()> {
+        let (_, bump) = Pubkey::find_program_address(
+            &[ctx.accounts.owner.key().as_ref(), b"vault"],
+            ctx.program_id,
+        );
+        ctx.accounts.vault.owner = ctx.accounts.owner.key();
+        ctx.accounts.vault.bump = bump;
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.balance += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [b"escrow", maker.key().as_ref(), taker.key().as_ref()],
+        bump
+    )]
+    escrow: Account<'info, Escrow>,
+    #[account(mut)]
+    maker: Signer<'info>,
+    taker: SystemAccount<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Exchange<'info> {
+    #[account(mut, seeds = [b"escrow", taker.key().as_ref(), maker.key().as_ref()], bump)]
+    escrow: Account<'info, Escrow>,
+    maker: SystemAccount<'info>,
+    taker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Cancel<'info> {
+    #[account(mut, seeds = [b"escrow_v2", maker.key().as_ref(), taker.key().as_ref()], bump)]
+    escrow: Account<'info, Escrow>,
+    maker: Signer<'info>,
+    taker: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenVault<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump
+    )]
+    vault: Account<'info, Vault>,
+    #[account(mut)]
+    owner: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut, seeds = [b"vault", owner.key().as_ref()], bump = vault.bump)]
+    vault: Account<'info, Vault>,
+    owner: Signer<'info>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Escrow {
+    maker: Pubkey,
+    taker: Pubkey,
+    amount: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Vault {
+    owner: Pubkey,
+    balance: u64,
+    bump: u8,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,255 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::AccountsStruct;
+use clippy_utils::{contains_return, ty::match_type};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, Expr, ExprKind, FnDecl, HirId, Item, Node,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{
+        anchor_constraints::field_constraint_summary, get_anchor_accounts_struct, local_field_def,
+        local_struct_field, visit_expr_no_bodies,
+    },
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the writes of the `Pubkey` fields, e.g., an authority or an admin, of an account
+    /// declared with `#[account(init_if_needed, ...)]`, which are made without first checking
+    /// whether the account was just initialized.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// With `init_if_needed`, the instruction accepts an account which already exists, and the
+    /// handler runs for it as for a new account. If the handler sets the authority of the account
+    /// unconditionally, anyone can call the instruction with an existing account, e.g., the
+    /// config of the program or the vault of another user, and make themselves its authority.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Only the assignments to a field of type `Pubkey` are reported. A write is regarded as
+    /// checked if it is in an `if` whose condition reads a field of the same account, or if it is
+    /// after an `if` which returns, e.g., `require!(...)`, whose condition reads a field of the
+    /// account. The lint does not check what the condition compares.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    ///     ctx.accounts.config.admin = ctx.accounts.payer.key();
+    ///     Ok(())
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct Initialize<'info> {
+    ///     #[account(init_if_needed, payer = payer, space = 8 + 32 + 1, seeds = [b"config"], bump)]
+    ///     config: Account<'info, Config>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    ///     require!(!ctx.accounts.config.initialized, ErrorCode::AlreadyInitialized);
+    ///     ctx.accounts.config.admin = ctx.accounts.payer.key();
+    ///     ctx.accounts.config.initialized = true;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item: Collect Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - for each assignment to a field of type `Pubkey` of an account, e.g.,
+    ///   `ctx.accounts.config.admin = ...`, or `config.admin = ...` where `config` is initialized
+    ///   with a reference to `ctx.accounts.config`
+    ///   - if an `if` whose condition reads a field of the account contains the assignment, or
+    ///     is before the assignment and returns, ignore the assignment
+    ///   - else record the assignment, and the field of the local struct holding the account
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each recorded assignment, if the field is a field of an Anchor `Accounts` struct with
+    ///   the `init_if_needed` constraint, report the assignment and note the field
+    pub INIT_IF_NEEDED_REINIT,
+    Warn,
+    "writes of the authority of `init_if_needed` accounts not checking that the account is new",
+    InitIfNeededReinit::default()
+}
+
+#[derive(Default)]
+struct InitIfNeededReinit {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    /// The unchecked writes of the `Pubkey` fields of accounts, in the order they are found
+    writes: Vec<FieldWrite>,
+}
+
+/// An assignment to a `Pubkey` field of an account
+struct FieldWrite {
+    hir_id: HirId,
+    span: Span,
+    /// The field of the local struct holding the account, e.g., `(Initialize, "config")` for
+    /// `ctx.accounts.config`
+    account: (DefId, String),
+}
+
+impl<'tcx> LateLintPass<'tcx> for InitIfNeededReinit {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(INIT_IF_NEEDED_REINIT, span) {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if !is_skipped_expansion(INIT_IF_NEEDED_REINIT, expr.span);
+                if let ExprKind::Assign(lhs, _, _) = expr.kind;
+                if let ExprKind::Field(object, _) = lhs.kind;
+                let lhs_ty = cx.typeck_results().expr_ty(lhs);
+                if match_type(cx, lhs_ty, &paths::SOLANA_PROGRAM_PUBKEY);
+                if let Some(account) = local_struct_field(cx, object);
+                if !is_write_checked(cx, body.value, expr, &account);
+                then {
+                    self.writes.push(FieldWrite {
+                        hir_id: expr.hir_id,
+                        span: expr.span,
+                        account,
+                    });
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for write in &self.writes {
+            let (def_id, field_name) = &write.account;
+            if !self
+                .anchor_accounts
+                .get(def_id)
+                .map_or(false, |accounts_struct| {
+                    field_constraint_summary(accounts_struct, field_name)
+                        .map_or(false, |constraints| constraints.is_init_if_needed)
+                })
+            {
+                continue;
+            }
+            let field_def = local_field_def(cx, *def_id, field_name);
+            span_lint_hir_and_then(
+                cx,
+                INIT_IF_NEEDED_REINIT,
+                write.hir_id,
+                write.span,
+                "this field of an `init_if_needed` account is written without checking whether the account was just initialized",
+                |diag| {
+                    if let Some(field_def) = field_def {
+                        diag.span_note(
+                            field_def.span,
+                            "the account is initialized only if it does not exist",
+                        );
+                    }
+                    diag.help(
+                        "check that the account was just initialized, e.g., with a flag set when it is initialized, before writing the field",
+                    );
+                },
+            );
+        }
+
+        sarif::write(cx, &[INIT_IF_NEEDED_REINIT]);
+    }
+}
+
+/// Return true if `write` is in an `if` whose condition reads a field of `account`, or is after
+/// such an `if` which returns, e.g., `require!(!config.initialized, ...)`
+fn is_write_checked<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    write: &Expr<'tcx>,
+    account: &(DefId, String),
+) -> bool {
+    let is_in_branch = cx
+        .tcx
+        .hir()
+        .parent_iter(write.hir_id)
+        .any(|(_, node)| match node {
+            Node::Expr(Expr {
+                kind: ExprKind::If(cond, ..),
+                ..
+            }) => reads_account(cx, cond, account),
+            _ => false,
+        });
+    is_in_branch
+        || visit_expr_no_bodies(scope, |expr| match expr.kind {
+            ExprKind::If(cond, then, els) => {
+                expr.span.source_callsite().hi() <= write.span.lo()
+                    && reads_account(cx, cond, account)
+                    && (contains_return(then) || els.map_or(false, contains_return))
+            }
+            _ => false,
+        })
+}
+
+/// Return true if `expr` reads a field of `account`, e.g., `config.initialized`
+fn reads_account<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    account: &(DefId, String),
+) -> bool {
+    visit_expr_no_bodies(expr, |expr| match expr.kind {
+        ExprKind::Field(object, _) => local_struct_field(cx, object).as_ref() == Some(account),
+        _ => false,
+    })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod init_if_needed_reinit_secure {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>, fee: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(!config.initialized, ErrorCode::AlreadyInitialized);
+        config.admin = ctx.accounts.payer.key();
+        config.fee = fee;
+        config.initialized = true;
+        Ok(())
+    }
+
+    pub fn open_vault(ctx: Context<OpenVault>) -> Result<()> {
+        // The owner is only set when the vault is new
+        if ctx.accounts.vault.owner == Pubkey::default() {
+            ctx.accounts.vault.owner = ctx.accounts.user.key();
+        }
+        ctx.accounts.vault.deposits += 1;
+        Ok(())
+    }
+
+    pub fn update_admin(ctx: Context<UpdateAdmin>, admin: Pubkey) -> Result<()> {
+        // The config is not declared with `init_if_needed`
+        ctx.accounts.config.admin = admin;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init_if_needed, payer = payer, space = 8 + 32 + 8 + 1, seeds = [b"config"], bump)]
+    config: Account<'info, Config>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenVault<'info> {
+    #[account(init_if_needed, payer = user, space = 8 + 32 + 8)]
+    vault: Account<'info, Vault>,
+    #[account(mut)]
+    user: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAdmin<'info> {
+    #[account(mut, has_one = admin)]
+    config: Account<'info, Config>,
+    admin: Signer<'info>,
+}
+
+#[account]
+pub struct Config {
+    admin: Pubkey,
+    fee: u64,
+    initialized: bool,
+}
+
+#[account]
+pub struct Vault {
+    owner: Pubkey,
+    deposits: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    AlreadyInitialized,
+}
+
+#[allow(dead_code)]
+fn main() {}
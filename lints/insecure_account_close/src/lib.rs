@@ -6,15 +6,15 @@ extern crate rustc_ast;
 extern crate rustc_hir;
 extern crate rustc_middle;
 
-use clippy_utils::{diagnostics::span_lint, higher};
+use clippy_utils::higher;
 use if_chain::if_chain;
 use rustc_ast::ast::{LitIntType, LitKind};
 use rustc_hir::{BinOpKind, Body, BorrowKind, Expr, ExprKind, LangItem, Mutability, QPath, UnOp};
 use rustc_lint::{LateContext, LateLintPass};
 use rustc_middle::ty::{TyKind, UintTy};
-use solana_lints::utils::visit_expr_no_bodies;
+use solana_lints::{diagnostics::span_lint_hir, sarif, utils::visit_expr_no_bodies};
 
-dylint_linting::declare_late_lint! {
+solana_lints::declare_late_lint! {
     /// **What it does:**
     ///
     /// Checks for attempts to close an account by setting its lamports to `0` but
@@ -31,6 +31,10 @@ dylint_linting::declare_late_lint! {
     /// - [x] Anchor
     /// - [x] Non Anchor
     ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
     /// **Known problems:**
     ///
     /// None
@@ -71,15 +75,20 @@ impl<'tcx> LateLintPass<'tcx> for InsecureAccountClose {
             // if the body does not contain a for loop with an expression assigning zero. (Assume clearing data)
             if !contains_manual_clear(body);
             then {
-                span_lint(
+                span_lint_hir(
                     cx,
                     INSECURE_ACCOUNT_CLOSE,
+                    expr.hir_id,
                     expr.span,
                     "attempt to close an account without also clearing its data",
                 )
             }
         }
     }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[INSECURE_ACCOUNT_CLOSE]);
+    }
 }
 
 // Return true if expr is `(*(*some_expr).lamports.borrow_mut()) = 0;`
@@ -0,0 +1,304 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{match_def_path, ty::match_type};
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, FnDecl, Item, ItemKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::{Span, Symbol};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, paths, sarif,
+    utils::context_accounts_struct,
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the `#[instruction(...)]` attributes of Anchor `Accounts` structs whose arguments
+    /// are not the first arguments of an instruction handler using the struct, after the context,
+    /// in the same order and with the same names and types.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Anchor deserializes the arguments of `#[instruction(...)]` from the start of the instruction
+    /// data, which holds the arguments of the handler in order. The names of the arguments are not
+    /// matched: if the attribute lists the arguments in another order, e.g.,
+    /// `#[instruction(bump: u8, amount: u64)]` for `deposit(ctx, amount: u64, bump: u8)`, the
+    /// constraints using `bump`, e.g., `seeds`, silently use the first byte of `amount`. If the
+    /// types differ, the constraints use wrong values, or the deserialization of the accounts
+    /// fails.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Every function of the program whose first argument is a `Context<T>` is regarded as an
+    /// instruction handler. The types of the arguments are compared after erasing lifetimes.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn deposit(ctx: Context<Deposit>, amount: u64, bump: u8) -> Result<()> {
+    ///     ...
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// #[instruction(bump: u8)]
+    /// pub struct Deposit<'info> {
+    ///     #[account(mut, seeds = [b"vault", owner.key().as_ref()], bump = bump)]
+    ///     vault: Account<'info, Vault>,
+    ///     owner: Signer<'info>,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// #[instruction(amount: u64, bump: u8)]
+    /// pub struct Deposit<'info> {
+    ///     #[account(mut, seeds = [b"vault", owner.key().as_ref()], bump = bump)]
+    ///     vault: Account<'info, Vault>,
+    ///     owner: Signer<'info>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - for each `__Args` struct generated by Anchor for an `#[instruction(...)]` attribute, in
+    ///   the implementation of `anchor_lang::Accounts` for an `Accounts` struct, record the
+    ///   `__Args` struct of the `Accounts` struct
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - if the first argument of the function is a `Context<T>`, record the function and `T`
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each recorded function, if its `T` has an `__Args` struct
+    ///   - compare the fields of `__Args` with the arguments of the function after the context,
+    ///     in order
+    ///   - report the first field without an argument at its position, or whose name or type
+    ///     differs from the argument at its position, and note the argument
+    pub INSTRUCTION_ATTRIBUTE_MISMATCH,
+    Warn,
+    "`#[instruction(...)]` arguments which do not match the arguments of the instruction handler",
+    InstructionAttributeMismatch::default()
+}
+
+#[derive(Default)]
+struct InstructionAttributeMismatch {
+    /// The `__Args` struct generated for the `#[instruction(...)]` attribute of each `Accounts`
+    /// struct
+    instruction_args: FxHashMap<DefId, LocalDefId>,
+    /// The instruction handlers and their `Accounts` structs, in the order they are found
+    handlers: Vec<(LocalDefId, DefId)>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for InstructionAttributeMismatch {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        // Anchor declares the arguments of `#[instruction(...)]` as the fields of a struct
+        // `__Args`, in the `try_accounts` method of the `Accounts` implementation
+        if_chain! {
+            if item.span.from_expansion();
+            if item.ident.as_str() == "__Args";
+            if let ItemKind::Struct(..) = item.kind;
+            if let Some(accounts) = accounts_impl_self(cx, item.owner_id.def_id);
+            then {
+                self.instruction_args.insert(accounts, item.owner_id.def_id);
+            }
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        _: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(INSTRUCTION_ATTRIBUTE_MISMATCH, span) {
+            return;
+        }
+        let fn_sig = cx
+            .tcx
+            .fn_sig(local_def_id.to_def_id())
+            .skip_binder()
+            .skip_binder();
+        if_chain! {
+            if let Some(context_ty) = fn_sig.inputs().first();
+            if match_type(cx, *context_ty, &paths::ANCHOR_LANG_CONTEXT);
+            if let Some(accounts) = context_accounts_struct(cx, local_def_id);
+            then {
+                self.handlers.push((local_def_id, accounts));
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for &(handler, accounts) in &self.handlers {
+            if let Some(&args_struct) = self.instruction_args.get(&accounts) {
+                check_handler(cx, handler, accounts, args_struct);
+            }
+        }
+
+        sarif::write(cx, &[INSTRUCTION_ATTRIBUTE_MISMATCH]);
+    }
+}
+
+/// If `def_id` is defined in a method of an implementation of `anchor_lang::Accounts`, return the
+/// def id of the implementing type
+fn accounts_impl_self(cx: &LateContext<'_>, def_id: LocalDefId) -> Option<DefId> {
+    let impl_def_id = cx
+        .tcx
+        .impl_of_method(cx.tcx.local_parent(def_id).to_def_id())?;
+    let trait_ref = cx.tcx.impl_trait_ref(impl_def_id)?.skip_binder();
+    if_chain! {
+        if match_def_path(cx, trait_ref.def_id, &paths::ANCHOR_LANG_ACCOUNTS);
+        if let ty::Adt(adt_def, _) = trait_ref.self_ty().kind();
+        then {
+            Some(adt_def.did())
+        } else {
+            None
+        }
+    }
+}
+
+/// Compare the fields of `args_struct`, the arguments of the `#[instruction(...)]` attribute of
+/// `accounts`, with the arguments of `handler` after the context, and report the first mismatch
+fn check_handler(
+    cx: &LateContext<'_>,
+    handler: LocalDefId,
+    accounts: DefId,
+    args_struct: LocalDefId,
+) {
+    let local_accounts = match accounts.as_local() {
+        Some(local_accounts) => local_accounts,
+        None => return,
+    };
+    let adt_def = cx.tcx.adt_def(args_struct);
+    let fn_sig = cx.tcx.fn_sig(handler).skip_binder().skip_binder();
+    let arg_names = cx.tcx.fn_arg_names(handler);
+    let params = cx
+        .tcx
+        .hir()
+        .body(cx.tcx.hir().body_owned_by(handler))
+        .params;
+    let handler_name = cx.tcx.item_name(handler.to_def_id());
+    for (i, field) in adt_def.non_enum_variant().fields.iter().enumerate() {
+        let field_ty = cx.tcx.type_of(field.did).instantiate_identity();
+        let mismatch = match fn_sig.inputs().get(i + 1) {
+            None => Mismatch::Missing,
+            Some(arg_ty)
+                if arg_names[i + 1].name != field.name
+                    || cx.tcx.erase_regions(*arg_ty) != cx.tcx.erase_regions(field_ty) =>
+            {
+                Mismatch::Different {
+                    name: arg_names[i + 1].name,
+                    ty: *arg_ty,
+                    span: params[i + 1].span,
+                }
+            }
+            Some(_) => continue,
+        };
+        // The field is declared with the tokens of the attribute
+        let mut span = cx.tcx.def_span(field.did);
+        if is_skipped_expansion(INSTRUCTION_ATTRIBUTE_MISMATCH, span) {
+            span = cx.tcx.def_span(accounts);
+        }
+        report(
+            cx,
+            local_accounts,
+            span,
+            &format!("{}: {field_ty}", field.name),
+            handler_name,
+            &mismatch,
+        );
+        return;
+    }
+}
+
+/// How an argument of `#[instruction(...)]` does not match the argument of the handler at the
+/// same position
+enum Mismatch<'tcx> {
+    /// The handler has no argument at the position
+    Missing,
+    /// The argument of the handler at the position has another name or another type
+    Different {
+        name: Symbol,
+        ty: Ty<'tcx>,
+        span: Span,
+    },
+}
+
+/// Report the argument `arg` of the `#[instruction(...)]` attribute of `accounts`, which does not
+/// match the argument of `handler_name` at the same position
+fn report(
+    cx: &LateContext<'_>,
+    accounts: LocalDefId,
+    span: Span,
+    arg: &str,
+    handler_name: Symbol,
+    mismatch: &Mismatch<'_>,
+) {
+    let msg = match mismatch {
+        Mismatch::Missing => format!(
+            "the instruction argument `{arg}` has no matching argument in `{handler_name}`"
+        ),
+        Mismatch::Different { name, ty, .. } => format!(
+            "the instruction argument `{arg}` does not match the argument `{name}: {ty}` of `{handler_name}`"
+        ),
+    };
+    span_lint_hir_and_then(
+        cx,
+        INSTRUCTION_ATTRIBUTE_MISMATCH,
+        cx.tcx.local_def_id_to_hir_id(accounts),
+        span,
+        &msg,
+        |diag| {
+            if let Mismatch::Different { span, .. } = mismatch {
+                diag.span_note(
+                    *span,
+                    "the argument of the handler at the same position is here",
+                );
+            }
+            diag.help(
+                "list the first arguments of the handler after the context, in the same order and with the same types",
+            );
+        },
+    );
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
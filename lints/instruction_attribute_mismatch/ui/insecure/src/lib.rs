@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod instruction_attribute_mismatch_insecure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64, bump: u8) -> Result<()> {
+        ctx.accounts.vault.balance += amount;
+        msg!("vault bump: {}", bump);
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.balance -= amount;
+        Ok(())
+    }
+
+    pub fn set_fee(ctx: Context<SetFee>, fee: u16) -> Result<()> {
+        ctx.accounts.vault.fee = fee;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct Deposit<'info> {
+    #[account(mut, seeds = [b"vault", owner.key().as_ref()], bump = bump)]
+    vault: Account<'info, Vault>,
+    owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, bump: u8)]
+pub struct Withdraw<'info> {
+    #[account(mut, seeds = [b"vault", owner.key().as_ref()], bump = bump, constraint = vault.balance >= amount)]
+    vault: Account<'info, Vault>,
+    owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(fee: u64)]
+pub struct SetFee<'info> {
+    #[account(mut, has_one = owner, constraint = fee <= 10_000)]
+    vault: Account<'info, Vault>,
+    owner: Signer<'info>,
+}
+
+#[account]
+pub struct Vault {
+    owner: Pubkey,
+    balance: u64,
+    fee: u16,
+}
+
+#[allow(dead_code)]
+fn main() {}
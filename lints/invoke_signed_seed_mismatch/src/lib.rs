@@ -0,0 +1,303 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::AccountField;
+use clippy_utils::{fn_def_id, higher::Range, match_def_path, path_to_local};
+use if_chain::if_chain;
+use rustc_hir::{Expr, ExprKind, HirId, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::{span_lint_hir_and_then, span_lint_hir_and_then_with_confidence, Confidence},
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    seeds::{bumped_seeds, display_seeds, field_seeds, find_program_address_seeds, Seed},
+    utils::{get_anchor_accounts_struct, local_init},
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the signer seeds passed to `invoke_signed` which are empty, e.g., `&[&[]]`, or which
+    /// do not correspond to the seeds of any PDA of the program, i.e., the seeds passed to
+    /// `Pubkey::find_program_address` or `Pubkey::create_program_address`, or the seeds of the
+    /// `seeds` constraints of Anchor accounts.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// `invoke_signed` signs the instruction for the PDA derived from each signer seeds and the
+    /// program id. Seeds which differ from the seeds the PDA is derived with, e.g., by a typo in a
+    /// byte string or a missing seed, derive another address, so that the PDA does not sign, and
+    /// the instruction fails. Empty seeds sign for no PDA of the program.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The seeds are compared as in `inconsistent_pda_seeds`: seeds which only differ by the names
+    /// of the accounts are regarded as the same, and seeds which differ in their number or in their
+    /// byte string literals are reported. Only the seeds written as arrays ending with the bump,
+    /// e.g., `&[b"vault", authority.key.as_ref(), &[bump]]`, either in the call or in a local
+    /// variable, are compared. The seeds passed to `CpiContext::new_with_signer` are not checked.
+    /// A PDA may be derived in another crate, so the findings on non-empty seeds have medium
+    /// confidence, and are not reported if the crate derives no PDA.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// let (vault_key, bump) =
+    ///     Pubkey::find_program_address(&[b"vault", authority.key.as_ref()], program_id);
+    /// ...
+    /// invoke_signed(&ix, &accounts, &[&[b"valut", authority.key.as_ref(), &[bump]]])?;
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// let (vault_key, bump) =
+    ///     Pubkey::find_program_address(&[b"vault", authority.key.as_ref()], program_id);
+    /// ...
+    /// invoke_signed(&ix, &accounts, &[&[b"vault", authority.key.as_ref(), &[bump]]])?;
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - for each Anchor `Accounts` struct, record the seeds of the fields with the `seeds`
+    ///   constraint (see `solana_lints::seeds`)
+    ///
+    /// check_expr:
+    ///
+    /// - record the seeds passed to `Pubkey::find_program_address`, and the seeds passed to
+    ///   `Pubkey::create_program_address` without the bump
+    /// - for each `invoke_signed` call, excluding the calls generated by macros, follow the signer
+    ///   seeds through borrows, full range slices, e.g., `&seeds[..]`, and local variables to an
+    ///   array, and record each of its elements, followed in the same way, which is empty or ends
+    ///   with a bump
+    ///
+    /// check_crate_post:
+    ///
+    /// - report the empty signer seeds
+    /// - if the crate derives PDAs, report the other signer seeds, without their bump, unless they
+    ///   have the same number of seeds as the seeds of a PDA, and the same byte string literals at
+    ///   the same positions
+    pub INVOKE_SIGNED_SEED_MISMATCH,
+    Warn,
+    "`invoke_signed` calls whose signer seeds are empty or match the seeds of no PDA of the program",
+    InvokeSignedSeedMismatch::default()
+}
+
+#[derive(Default)]
+struct InvokeSignedSeedMismatch {
+    /// The seeds the PDAs of the program are derived with, without the bumps
+    derived: Vec<Vec<Seed>>,
+    /// The signer seeds passed to `invoke_signed`, in the order they are found
+    signers: Vec<Signer>,
+}
+
+struct Signer {
+    /// The seeds without the bump, or None if the seeds are empty
+    seeds: Option<Vec<Seed>>,
+    hir_id: HirId,
+    span: Span,
+}
+
+impl<'tcx> LateLintPass<'tcx> for InvokeSignedSeedMismatch {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(..) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                for anchor_field in &accounts_struct.fields {
+                    if_chain! {
+                        if let AccountField::Field(field) = anchor_field;
+                        if let Some(seeds) = field_seeds(field);
+                        then {
+                            self.derived.push(seeds);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if is_skipped_expansion(INVOKE_SIGNED_SEED_MISMATCH, expr.span) {
+            return;
+        }
+        if let Some(seeds) = find_program_address_seeds(cx, expr) {
+            self.derived.push(seeds);
+        }
+        if_chain! {
+            if let ExprKind::Call(_, [seeds_arg, _]) = expr.kind;
+            if let Some(def_id) = fn_def_id(cx, expr);
+            if match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_CREATE_PROGRAM_ADDRESS);
+            if let ExprKind::Array(elements) = seeds_array(cx, seeds_arg).kind;
+            if let Some(seeds) = bumped_seeds(cx, elements);
+            then {
+                self.derived.push(seeds);
+            }
+        }
+        if_chain! {
+            if let ExprKind::Call(_, [_, _, signers_arg]) = expr.kind;
+            if let Some(def_id) = fn_def_id(cx, expr);
+            if match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_INVOKE_SIGNED);
+            then {
+                self.record_signers(cx, signers_arg);
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for signer in &self.signers {
+            match &signer.seeds {
+                None => {
+                    let help = "pass the seeds and the bump of the PDA which signs, or use `invoke` if no PDA signs";
+                    span_lint_hir_and_then(
+                        cx,
+                        INVOKE_SIGNED_SEED_MISMATCH,
+                        signer.hir_id,
+                        signer.span,
+                        "the signer seeds passed to `invoke_signed` are empty, so that no PDA of the program signs the instruction",
+                        |diag| {
+                            diag.help(help);
+                        },
+                    );
+                }
+                Some(seeds) => {
+                    if self.derived.is_empty()
+                        || self
+                            .derived
+                            .iter()
+                            .any(|derived| corresponds(derived, seeds))
+                    {
+                        continue;
+                    }
+                    let seeds_note = format!(
+                        "the signer seeds are `{}`, followed by the bump",
+                        display_seeds(seeds)
+                    );
+                    let mut derived = Vec::new();
+                    for seeds in &self.derived {
+                        let seeds = format!("`{}`", display_seeds(seeds));
+                        if !derived.contains(&seeds) {
+                            derived.push(seeds);
+                        }
+                    }
+                    let derived_note = format!(
+                        "the PDAs of the program are derived with the seeds {}",
+                        derived.join(", ")
+                    );
+                    span_lint_hir_and_then_with_confidence(
+                        cx,
+                        INVOKE_SIGNED_SEED_MISMATCH,
+                        signer.hir_id,
+                        signer.span,
+                        "the signer seeds passed to `invoke_signed` do not correspond to the seeds of any PDA of the program",
+                        Confidence::Medium,
+                        |diag| {
+                            diag.note(seeds_note);
+                            diag.note(derived_note);
+                            diag.help(
+                                "sign with the seeds the PDA is derived with, followed by its bump",
+                            );
+                        },
+                    );
+                }
+            }
+        }
+
+        sarif::write(cx, &[INVOKE_SIGNED_SEED_MISMATCH]);
+    }
+}
+
+impl InvokeSignedSeedMismatch {
+    /// Record the signer seeds of the `signers_arg` of an `invoke_signed` call, i.e., a slice of
+    /// signer seeds
+    fn record_signers<'tcx>(&mut self, cx: &LateContext<'tcx>, signers_arg: &'tcx Expr<'tcx>) {
+        if let ExprKind::Array(signers) = seeds_array(cx, signers_arg).kind {
+            if signers.is_empty() {
+                self.signers.push(Signer {
+                    seeds: None,
+                    hir_id: signers_arg.hir_id,
+                    span: signers_arg.span,
+                });
+            }
+            for signer in signers {
+                if let ExprKind::Array(elements) = seeds_array(cx, signer).kind {
+                    let seeds = if elements.is_empty() {
+                        None
+                    } else if let Some(seeds) = bumped_seeds(cx, elements) {
+                        Some(seeds)
+                    } else {
+                        continue;
+                    };
+                    self.signers.push(Signer {
+                        seeds,
+                        hir_id: signer.hir_id,
+                        span: signer.span,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Follow `expr` through borrows, full range slices, e.g., `&seeds[..]`, and local variables to
+/// the expression it is initialized with, e.g., an array of seeds
+fn seeds_array<'tcx>(cx: &LateContext<'tcx>, mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    loop {
+        expr = match expr.kind {
+            ExprKind::AddrOf(_, _, inner) => inner,
+            ExprKind::Index(base, index, _)
+                if Range::hir(index)
+                    .map_or(false, |range| range.start.is_none() && range.end.is_none()) =>
+            {
+                base
+            }
+            _ => match path_to_local(expr).and_then(|local| local_init(cx, local)) {
+                Some(init) => init,
+                None => return expr,
+            },
+        };
+    }
+}
+
+/// Return true if the signer `seeds` correspond to the `derived` seeds of a PDA, i.e., they have
+/// the same number of seeds and the same byte string literals at the same positions
+fn corresponds(derived: &[Seed], seeds: &[Seed]) -> bool {
+    derived.len() == seeds.len()
+        && derived
+            .iter()
+            .zip(seeds)
+            .all(|(derived, seed)| match (derived, seed) {
+                (Seed::Bytes(_), _) | (_, Seed::Bytes(_)) => derived == seed,
+                (Seed::Expr(_), Seed::Expr(_)) => true,
+            })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
+
+#[test]
+fn secure_2() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-2");
+}
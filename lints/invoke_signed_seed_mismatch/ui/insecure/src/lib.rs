@@ -0,0 +1,54 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+};
+
+pub fn withdraw(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let vault = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let (vault_key, bump) =
+        Pubkey::find_program_address(&[b"vault", authority.key.as_ref()], program_id);
+    if vault_key != *vault.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    invoke_signed(
+        &system_instruction::transfer(vault.key, destination.key, amount),
+        &[vault.clone(), destination.clone()],
+        &[&[b"valut", authority.key.as_ref(), &[bump]]],
+    )
+}
+
+pub fn close(accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let vault = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    invoke_signed(
+        &system_instruction::transfer(vault.key, destination.key, vault.lamports()),
+        &[vault.clone(), destination.clone()],
+        &[&[]],
+    )
+}
+
+pub fn sweep(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let vault = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let destination = next_account_info(account_info_iter)?;
+    let (vault_key, bump) =
+        Pubkey::find_program_address(&[b"vault", authority.key.as_ref()], program_id);
+    if vault_key != *vault.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let signer_seeds: &[&[u8]] = &[b"vault", &[bump]];
+    invoke_signed(
+        &system_instruction::transfer(vault.key, destination.key, vault.lamports()),
+        &[vault.clone(), destination.clone()],
+        &[signer_seeds],
+    )
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke_signed, system_instruction};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod invoke_signed_seed_mismatch_secure_2 {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+        let seeds: &[&[u8]] = &[b"vault", authority.as_ref(), &[ctx.bumps.vault]];
+        invoke_signed(
+            &system_instruction::transfer(
+                ctx.accounts.vault.key,
+                ctx.accounts.destination.key,
+                amount,
+            ),
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.destination.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, seeds = [b"vault", authority.key().as_ref()], bump)]
+    vault: SystemAccount<'info>,
+    authority: Signer<'info>,
+    /// CHECK: only receives lamports
+    #[account(mut)]
+    destination: UncheckedAccount<'info>,
+    system_program: Program<'info, System>,
+}
@@ -0,0 +1,346 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{
+    fn_def_id, match_any_def_paths, match_def_path, path_to_local, path_to_local_id,
+    ty::match_type, SpanlessEq,
+};
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::{
+    def_id::LocalDefId, intravisit::FnKind, Body, Expr, ExprKind, FnDecl, HirId, Mutability, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, paths, sarif,
+    utils::visit_expr_no_bodies,
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the reads of the lamports of an account, `account.lamports()`, and the borrows of
+    /// its data, `account.try_borrow_data()`, in a loop, when the account is the same in every
+    /// iteration and is not modified in the loop. For example, the lamports of a vault read while
+    /// iterating over `ctx.remaining_accounts`.
+    ///
+    /// The lint is allowed by default. It is intended for programs close to the compute budget and
+    /// can be enabled with `#![warn(loop_invariant_account_read)]`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Each read borrows the `RefCell` of the lamports or the data of the account, and checks the
+    /// borrow at runtime. In a loop over many accounts, e.g., the remaining accounts, the repeated
+    /// borrows of a value which does not change add a measurable compute cost. Reading the value
+    /// once before the loop avoids it.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The account is the same in every iteration if its expression only consists of local
+    /// variables declared before the loop and not assigned in it, fields, indexes, derefs, and
+    /// calls to `to_account_info`. Only the first read of an account in a loop is reported.
+    ///
+    /// The account is modified in the loop if the loop mutably borrows its lamports or its data,
+    /// reallocates its data, or makes a CPI (`invoke`, `invoke_signed`, or a call taking a
+    /// `CpiContext`). Two expressions of the same account, e.g., an `AccountInfo` and its clone,
+    /// are not recognized as the same account, and modifications through other functions are not
+    /// recognized either.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// for account in ctx.remaining_accounts.iter() {
+    ///     let share = ctx.accounts.vault.to_account_info().lamports() / count;
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// let share = ctx.accounts.vault.to_account_info().lamports() / count;
+    /// for account in ctx.remaining_accounts.iter() {
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - for each loop, outermost first, unless the loop makes a CPI
+    ///   - for each call to `AccountInfo::lamports` or `AccountInfo::try_borrow_data`, excluding
+    ///     the calls in macro expansions
+    ///     - if the account is not the same in every iteration, ignore the call
+    ///     - if the loop mutably borrows the lamports (resp. the data) of the account, or
+    ///       reallocates its data, ignore the call
+    ///     - if the call was reported for an enclosing loop, or follows a read of the same account
+    ///       in the loop, using `SpanlessEq`, do not report it
+    ///     - else, report the call
+    pub LOOP_INVARIANT_ACCOUNT_READ,
+    Allow,
+    "reads of the lamports or the data of an account which does not change in a loop"
+}
+
+impl<'tcx> LateLintPass<'tcx> for LoopInvariantAccountRead {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(LOOP_INVARIANT_ACCOUNT_READ, span) {
+            return;
+        }
+        let mut reported = FxHashSet::default();
+        // `for` and `while` loops are desugared to `loop`s.
+        visit_expr_no_bodies(body.value, |expr| {
+            if matches!(expr.kind, ExprKind::Loop(..)) {
+                check_loop(cx, expr, &mut reported);
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[LOOP_INVARIANT_ACCOUNT_READ]);
+    }
+}
+
+/// A read of the lamports or of the data of an account
+struct AccountRead<'tcx> {
+    /// The account, e.g., `ctx.accounts.vault.to_account_info()`
+    account: &'tcx Expr<'tcx>,
+    /// The call
+    expr: &'tcx Expr<'tcx>,
+    kind: ReadKind,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ReadKind {
+    Lamports,
+    Data,
+}
+
+/// Report the reads in `loop_expr` of accounts which do not change in the loop. `reported` holds
+/// the reads of the enclosing loops.
+fn check_loop<'tcx>(
+    cx: &LateContext<'tcx>,
+    loop_expr: &'tcx Expr<'tcx>,
+    reported: &mut FxHashSet<HirId>,
+) {
+    if makes_cpi(cx, loop_expr) {
+        return;
+    }
+    let mut reads: Vec<AccountRead<'tcx>> = Vec::new();
+    visit_expr_no_bodies(loop_expr, |expr| {
+        if_chain! {
+            if !is_skipped_expansion(LOOP_INVARIANT_ACCOUNT_READ, expr.span);
+            if let Some(read) = account_read(cx, expr);
+            if is_loop_invariant(cx, read.account, loop_expr);
+            if !modifies_account(cx, loop_expr, &read);
+            then {
+                reads.push(read);
+            }
+        }
+        false
+    });
+    for (i, read) in reads.iter().enumerate() {
+        let repeated = reads[..i].iter().any(|previous| {
+            previous.kind == read.kind
+                && SpanlessEq::new(cx).eq_expr(previous.account, read.account)
+        });
+        if reported.insert(read.expr.hir_id) && !repeated {
+            report(cx, read);
+        }
+    }
+}
+
+fn report(cx: &LateContext<'_>, read: &AccountRead<'_>) {
+    let (msg, help) = match read.kind {
+        ReadKind::Lamports => (
+            "the lamports of this account are read in every iteration of the loop",
+            "the account does not change in the loop: read its lamports once before the loop",
+        ),
+        ReadKind::Data => (
+            "the data of this account is borrowed in every iteration of the loop",
+            "the account does not change in the loop: borrow its data once before the loop",
+        ),
+    };
+    span_lint_hir_and_then(
+        cx,
+        LOOP_INVARIANT_ACCOUNT_READ,
+        read.expr.hir_id,
+        read.expr.span,
+        msg,
+        |diag| {
+            diag.help(help);
+        },
+    );
+}
+
+/// If `expr` is `account.lamports()` or `account.try_borrow_data()`, return the read
+fn account_read<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> Option<AccountRead<'tcx>> {
+    if let ExprKind::MethodCall(_, account, _, _) = expr.kind {
+        let def_id = cx.typeck_results().type_dependent_def_id(expr.hir_id)?;
+        let kind = if match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_LAMPORTS) {
+            ReadKind::Lamports
+        } else if match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_TRY_BORROW_DATA) {
+            ReadKind::Data
+        } else {
+            return None;
+        };
+        return Some(AccountRead {
+            account,
+            expr,
+            kind,
+        });
+    }
+    None
+}
+
+/// Return true if the value of `expr` is the same in every iteration of `loop_expr`
+fn is_loop_invariant<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    loop_expr: &'tcx Expr<'tcx>,
+) -> bool {
+    if let Some(local) = path_to_local(expr) {
+        return !loop_expr.span.contains(cx.tcx.hir().span(local))
+            && !assigns_local(loop_expr, local);
+    }
+    match expr.kind {
+        ExprKind::Path(_) | ExprKind::Lit(_) => true,
+        ExprKind::Field(base, _)
+        | ExprKind::AddrOf(_, _, base)
+        | ExprKind::Unary(UnOp::Deref, base) => is_loop_invariant(cx, base, loop_expr),
+        ExprKind::Index(base, index, _) => {
+            is_loop_invariant(cx, base, loop_expr) && is_loop_invariant(cx, index, loop_expr)
+        }
+        ExprKind::MethodCall(_, receiver, [], _) => {
+            cx.typeck_results()
+                .type_dependent_def_id(expr.hir_id)
+                .map_or(false, |def_id| {
+                    match_def_path(cx, def_id, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO)
+                })
+                && is_loop_invariant(cx, receiver, loop_expr)
+        }
+        _ => false,
+    }
+}
+
+/// Return true if `scope` assigns to `local`, or borrows it mutably
+fn assigns_local(scope: &Expr<'_>, local: HirId) -> bool {
+    visit_expr_no_bodies(scope, |expr| match expr.kind {
+        ExprKind::Assign(lhs, _, _)
+        | ExprKind::AssignOp(_, lhs, _)
+        | ExprKind::AddrOf(_, Mutability::Mut, lhs) => path_to_local_id(lhs, local),
+        _ => false,
+    })
+}
+
+/// Return true if `scope` mutably borrows the lamports (resp. the data) of the account of `read`,
+/// or reallocates its data
+fn modifies_account<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    read: &AccountRead<'tcx>,
+) -> bool {
+    visit_expr_no_bodies(scope, |expr| {
+        modified_account(cx, expr, read.kind).map_or(false, |account| {
+            SpanlessEq::new(cx).eq_expr(account, read.account)
+        })
+    })
+}
+
+/// If `expr` mutably borrows the lamports (resp. the data) of an account, or reallocates its data,
+/// return the account
+fn modified_account<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    kind: ReadKind,
+) -> Option<&'tcx Expr<'tcx>> {
+    let (try_borrow_mut, field) = match kind {
+        ReadKind::Lamports => (&paths::SOLANA_PROGRAM_TRY_BORROW_MUT_LAMPORTS, "lamports"),
+        ReadKind::Data => (&paths::SOLANA_PROGRAM_TRY_BORROW_MUT_DATA, "data"),
+    };
+    if let ExprKind::MethodCall(_, receiver, _, _) = expr.kind {
+        let def_id = cx.typeck_results().type_dependent_def_id(expr.hir_id)?;
+        if match_def_path(cx, def_id, try_borrow_mut)
+            || (kind == ReadKind::Data
+                && match_def_path(cx, def_id, &paths::SOLANA_PROGRAM_REALLOC))
+        {
+            return Some(receiver);
+        }
+        if_chain! {
+            if match_any_def_paths(
+                cx,
+                def_id,
+                &[&paths::CORE_CELL_BORROW_MUT, &paths::CORE_CELL_TRY_BORROW_MUT],
+            )
+            .is_some();
+            if let ExprKind::Field(account, ident) = receiver.kind;
+            if ident.as_str() == field;
+            then {
+                return Some(account);
+            }
+        }
+    }
+    None
+}
+
+/// Return true if `scope` calls `invoke` or `invoke_signed`, or a function taking a `CpiContext`
+fn makes_cpi<'tcx>(cx: &LateContext<'tcx>, scope: &'tcx Expr<'tcx>) -> bool {
+    visit_expr_no_bodies(scope, |expr| {
+        let args = match expr.kind {
+            ExprKind::Call(_, args) | ExprKind::MethodCall(_, _, args, _) => args,
+            _ => return false,
+        };
+        fn_def_id(cx, expr).map_or(false, |def_id| {
+            match_any_def_paths(
+                cx,
+                def_id,
+                &[
+                    &paths::SOLANA_PROGRAM_INVOKE,
+                    &paths::SOLANA_PROGRAM_INVOKE_SIGNED,
+                ],
+            )
+            .is_some()
+        }) || args.iter().any(|arg| {
+            match_type(
+                cx,
+                cx.typeck_results().expr_ty(arg),
+                &paths::ANCHOR_LANG_CPI_CONTEXT,
+            )
+        })
+    })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,55 @@
+#![warn(loop_invariant_account_read)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod loop_invariant_account_read_insecure {
+    use super::*;
+
+    pub fn distribute(ctx: Context<Distribute>) -> Result<()> {
+        let count = ctx.remaining_accounts.len() as u64;
+        for account in ctx.remaining_accounts.iter() {
+            let share = ctx.accounts.vault.to_account_info().lamports() / count;
+            let total = ctx.accounts.vault.to_account_info().lamports();
+            msg!("{}: {} of {}", account.key, share, total);
+        }
+        Ok(())
+    }
+
+    pub fn sum_balances(ctx: Context<SumBalances>) -> Result<()> {
+        let config = &ctx.remaining_accounts[0];
+        let mut total = 0;
+        for account in ctx.remaining_accounts[1..].iter() {
+            let data = config.try_borrow_data()?;
+            total += account.lamports().min(data[0] as u64);
+        }
+        msg!("total: {}", total);
+        Ok(())
+    }
+
+    pub fn scan(ctx: Context<SumBalances>) -> Result<()> {
+        let mut i = 1;
+        while i < ctx.remaining_accounts.len() {
+            let first = ctx.remaining_accounts[0].lamports();
+            let current = ctx.remaining_accounts[i].lamports();
+            msg!("{} {}", first, current);
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Distribute<'info> {
+    vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SumBalances<'info> {
+    payer: Signer<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
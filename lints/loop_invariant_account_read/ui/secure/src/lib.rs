@@ -0,0 +1,78 @@
+#![warn(loop_invariant_account_read)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{program::invoke, system_instruction};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod loop_invariant_account_read_secure {
+    use super::*;
+
+    pub fn distribute(ctx: Context<Distribute>) -> Result<()> {
+        let count = ctx.remaining_accounts.len() as u64;
+        let total = ctx.accounts.vault.to_account_info().lamports();
+        let share = total / count;
+        for account in ctx.remaining_accounts.iter() {
+            msg!("{}: {} of {}", account.key, share, total);
+        }
+        Ok(())
+    }
+
+    pub fn sum_balances(ctx: Context<SumBalances>) -> Result<()> {
+        let config = &ctx.remaining_accounts[0];
+        let limit = config.try_borrow_data()?[0] as u64;
+        let mut total = 0;
+        for account in ctx.remaining_accounts[1..].iter() {
+            total += account.lamports().min(limit);
+        }
+        msg!("total: {}", total);
+        Ok(())
+    }
+
+    pub fn drain(ctx: Context<Distribute>) -> Result<()> {
+        let vault = ctx.accounts.vault.to_account_info();
+        for account in ctx.remaining_accounts.iter() {
+            let amount = vault.lamports() / 2;
+            **vault.try_borrow_mut_lamports()? -= amount;
+            **account.try_borrow_mut_lamports()? += amount;
+        }
+        Ok(())
+    }
+
+    pub fn pay(ctx: Context<Pay>) -> Result<()> {
+        for account in ctx.remaining_accounts.iter() {
+            let amount = ctx.accounts.payer.lamports() / 100;
+            invoke(
+                &system_instruction::transfer(ctx.accounts.payer.key, account.key, amount),
+                &[
+                    ctx.accounts.payer.to_account_info(),
+                    account.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Distribute<'info> {
+    #[account(mut)]
+    vault: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SumBalances<'info> {
+    payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Pay<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,303 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    path_to_local_id,
+    ty::{is_type_diagnostic_item, match_type},
+};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::LocalDefId, intravisit::FnKind, Body, Expr, ExprKind, FnDecl, HirId, MatchSource,
+    PatKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::{sym, Span};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{context_accounts_struct, is_anchor_program, visit_expr_no_bodies},
+};
+use std::collections::HashSet;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports, in the modules of an Anchor program which declare instructions, e.g., the
+    /// `#[program]` module, the error conversions which lose the detail of the original error:
+    ///
+    /// - the conversions of an `anchor_lang::error::Error` into a `ProgramError`, with `?` in a
+    ///   function returning `ProgramResult`, `.into()`, `ProgramError::from` or
+    ///   `map_err(Into::into)`
+    /// - the calls of `map_err` on a `Result` with an `anchor_lang::error::Error` or a
+    ///   `ProgramError` as error type, whose closure ignores the original error, e.g.,
+    ///   `map_err(|_| ErrorCode::InvalidVault)`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Anchor logs an error returned by an instruction with its name, message and the file and
+    /// line it was raised at. A `ProgramError` converted from an Anchor error keeps only the error
+    /// code, and an error replaced in `map_err` is lost entirely, so the logs of a failed
+    /// transaction do not tell which validation failed. A generic error also hides a failed
+    /// validation, e.g., a failed owner check, behind an error which looks harmless, e.g., a
+    /// failed deserialization.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Only the functions defined in a module which also defines an instruction handler, i.e., a
+    /// function taking a `Context`, are checked. A `map_err` whose closure logs the original
+    /// error, e.g., with `msg!("{:?}", error)`, is not reported, as the closure uses the error.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> ProgramResult {
+    ///     validate(&ctx.accounts.vault)?;
+    ///     let data = Vault::try_deserialize(&mut &ctx.accounts.config.data.borrow()[..])
+    ///         .map_err(|_| ProgramError::InvalidAccountData)?;
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    ///     validate(&ctx.accounts.vault)?;
+    ///     let data = Vault::try_deserialize(&mut &ctx.accounts.config.data.borrow()[..])?;
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros,
+    ///   if the package is an Anchor program
+    /// - if the function takes a `Context`, record its module
+    /// - for each `?` on a `Result<_, anchor_lang::error::Error>` in a function returning a
+    ///   `Result<_, ProgramError>`, each `.into()` or `ProgramError::from` converting an
+    ///   `anchor_lang::error::Error` into a `ProgramError`, and each `map_err` either converting
+    ///   the error in the same way or ignoring it, record the conversion and the module of the
+    ///   function
+    ///
+    /// check_crate_post:
+    ///
+    /// - report the conversions recorded in the modules of the instruction handlers
+    pub LOSSY_ERROR_CONVERSION,
+    Warn,
+    "error conversions in Anchor programs which lose the detail of the original error",
+    LossyErrorConversion::default()
+}
+
+#[derive(Default)]
+struct LossyErrorConversion {
+    is_anchor: bool,
+    /// The modules defining an instruction handler
+    program_modules: HashSet<LocalDefId>,
+    conversions: Vec<Conversion>,
+}
+
+struct Conversion {
+    hir_id: HirId,
+    span: Span,
+    module: LocalDefId,
+    kind: ConversionKind,
+}
+
+#[derive(Clone, Copy)]
+enum ConversionKind {
+    /// An `anchor_lang::error::Error` is converted into a `ProgramError`
+    AnchorToProgramError,
+    /// The original error is ignored by the closure of a `map_err`
+    Discarded,
+}
+
+impl<'tcx> LateLintPass<'tcx> for LossyErrorConversion {
+    fn check_crate(&mut self, cx: &LateContext<'tcx>) {
+        self.is_anchor = is_anchor_program(cx);
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        fn_kind: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if !self.is_anchor || is_skipped_expansion(LOSSY_ERROR_CONVERSION, span) {
+            return;
+        }
+        let module = cx
+            .tcx
+            .parent_module_from_def_id(local_def_id)
+            .to_local_def_id();
+        let is_closure = matches!(fn_kind, FnKind::Closure);
+        if !is_closure && context_accounts_struct(cx, local_def_id).is_some() {
+            self.program_modules.insert(module);
+        }
+        // the error type `?` converts into, which is unknown for closures
+        let return_error_ty = if is_closure {
+            None
+        } else {
+            let output = cx
+                .tcx
+                .fn_sig(local_def_id)
+                .skip_binder()
+                .skip_binder()
+                .output();
+            result_error_ty(cx, output)
+        };
+        visit_expr_no_bodies(body.value, |expr| {
+            let kind = if_chain! {
+                if let ExprKind::Match(scrutinee, _, MatchSource::TryDesugar(_)) = expr.kind;
+                if let ExprKind::Call(_, [tried]) = scrutinee.kind;
+                then {
+                    let tried_error_ty = result_error_ty(cx, cx.typeck_results().expr_ty(tried));
+                    match (tried_error_ty, return_error_ty) {
+                        (Some(from), Some(to)) if is_anchor_to_program_error(cx, from, to) => {
+                            Some(ConversionKind::AnchorToProgramError)
+                        }
+                        _ => None,
+                    }
+                } else {
+                    conversion_kind(cx, expr)
+                }
+            };
+            if let Some(kind) = kind {
+                self.conversions.push(Conversion {
+                    hir_id: expr.hir_id,
+                    span: expr.span,
+                    module,
+                    kind,
+                });
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for conversion in &self.conversions {
+            if !self.program_modules.contains(&conversion.module) {
+                continue;
+            }
+            let (msg, help) = match conversion.kind {
+                ConversionKind::AnchorToProgramError => (
+                    "this converts an `anchor_lang::error::Error` into a `ProgramError`, which keeps only the error code",
+                    "return `anchor_lang::Result` instead of `ProgramResult`, so that the error is logged with its message and origin",
+                ),
+                ConversionKind::Discarded => (
+                    "this `map_err` discards the original error",
+                    "propagate the original error, or log it before replacing it",
+                ),
+            };
+            span_lint_hir_and_then(
+                cx,
+                LOSSY_ERROR_CONVERSION,
+                conversion.hir_id,
+                conversion.span,
+                msg,
+                |diag| {
+                    diag.help(help);
+                },
+            );
+        }
+
+        sarif::write(cx, &[LOSSY_ERROR_CONVERSION]);
+    }
+}
+
+/// Return the kind of the conversion `expr` makes, if it is `e.into()` or `ProgramError::from(e)`
+/// converting an `anchor_lang::error::Error` into a `ProgramError`, or a `map_err` converting the
+/// error in the same way or ignoring it
+fn conversion_kind<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> Option<ConversionKind> {
+    if is_skipped_expansion(LOSSY_ERROR_CONVERSION, expr.span) {
+        return None;
+    }
+    let typeck_results = cx.typeck_results();
+    let expr_ty = typeck_results.expr_ty(expr);
+    match expr.kind {
+        ExprKind::MethodCall(segment, receiver, [], _) if segment.ident.as_str() == "into" => {
+            let receiver_ty = typeck_results.expr_ty(receiver);
+            is_anchor_to_program_error(cx, receiver_ty, expr_ty)
+                .then_some(ConversionKind::AnchorToProgramError)
+        }
+        ExprKind::Call(callee, [arg]) if matches!(callee.kind, ExprKind::Path(_)) => {
+            let arg_ty = typeck_results.expr_ty(arg);
+            is_anchor_to_program_error(cx, arg_ty, expr_ty)
+                .then_some(ConversionKind::AnchorToProgramError)
+        }
+        ExprKind::MethodCall(segment, receiver, [op], _) if segment.ident.as_str() == "map_err" => {
+            let from = result_error_ty(cx, typeck_results.expr_ty(receiver))?;
+            let to = result_error_ty(cx, expr_ty)?;
+            if ignores_argument(cx, op) && paths::is_error_ty(cx, from) {
+                Some(ConversionKind::Discarded)
+            } else {
+                is_anchor_to_program_error(cx, from, to)
+                    .then_some(ConversionKind::AnchorToProgramError)
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Return true if `op` is a closure which does not use its argument, e.g., `|_| ...`
+fn ignores_argument<'tcx>(cx: &LateContext<'tcx>, op: &'tcx Expr<'tcx>) -> bool {
+    if_chain! {
+        if let ExprKind::Closure(closure) = op.kind;
+        let body = cx.tcx.hir().body(closure.body);
+        if let [param] = body.params;
+        then {
+            match param.pat.kind {
+                PatKind::Wild => true,
+                PatKind::Binding(_, hir_id, _, None) => {
+                    !visit_expr_no_bodies(body.value, |expr| path_to_local_id(expr, hir_id))
+                }
+                _ => false,
+            }
+        } else {
+            false
+        }
+    }
+}
+
+/// If `ty` is a `Result`, return its error type
+fn result_error_ty<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+    match ty.kind() {
+        ty::Adt(_, args) if is_type_diagnostic_item(cx, ty, sym::Result) => Some(args.type_at(1)),
+        _ => None,
+    }
+}
+
+fn is_anchor_to_program_error<'tcx>(cx: &LateContext<'tcx>, from: Ty<'tcx>, to: Ty<'tcx>) -> bool {
+    match_type(cx, from, &paths::ANCHOR_LANG_ERROR)
+        && match_type(cx, to, &paths::SOLANA_PROGRAM_PROGRAM_ERROR)
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
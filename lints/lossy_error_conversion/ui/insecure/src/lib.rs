@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod lossy_error_conversion_insecure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> ProgramResult {
+        validate(&ctx.accounts.vault)?;
+        let config = Config::try_deserialize(&mut &ctx.accounts.config.data.borrow()[..])
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        if config.authority != ctx.accounts.authority.key() {
+            return Err(error!(ErrorCode::Unauthorized).into());
+        }
+        ctx.accounts.vault.amount -= amount;
+        Ok(())
+    }
+
+    pub fn refresh(ctx: Context<Refresh>) -> ProgramResult {
+        ctx.accounts.vault.reload().map_err(Into::into)
+    }
+}
+
+fn validate(vault: &Vault) -> Result<()> {
+    require!(vault.amount > 0, ErrorCode::EmptyVault);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    config: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Refresh<'info> {
+    vault: Account<'info, Vault>,
+}
+
+#[account]
+pub struct Vault {
+    amount: u64,
+}
+
+#[account]
+pub struct Config {
+    authority: Pubkey,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    EmptyVault,
+    Unauthorized,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod lossy_error_conversion_secure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        validate(&ctx.accounts.vault)?;
+        let config = Config::try_deserialize(&mut &ctx.accounts.config.data.borrow()[..]).map_err(
+            |error| {
+                msg!("invalid config: {:?}", error);
+                ErrorCode::InvalidConfig
+            },
+        )?;
+        if config.authority != ctx.accounts.authority.key() {
+            return err!(ErrorCode::Unauthorized);
+        }
+        ctx.accounts.vault.amount -= amount;
+        Ok(())
+    }
+
+    pub fn refresh(ctx: Context<Refresh>) -> Result<()> {
+        ctx.accounts.vault.reload()
+    }
+}
+
+fn validate(vault: &Vault) -> Result<()> {
+    require!(vault.amount > 0, ErrorCode::EmptyVault);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    config: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Refresh<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+}
+
+#[account]
+pub struct Vault {
+    amount: u64,
+}
+
+#[account]
+pub struct Config {
+    authority: Pubkey,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    EmptyVault,
+    InvalidConfig,
+    Unauthorized,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,257 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    fn_def_id, match_any_def_paths, path_to_local, path_to_local_id, ty::is_type_diagnostic_item,
+};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::LocalDefId, intravisit::FnKind, Body, Expr, ExprKind, FnDecl, HirId, MatchSource,
+    PatKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::{sym, Span};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{context_accounts_struct, local_init, peel_addr_of, visit_expr_no_bodies},
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports Anchor instruction handlers which take the raw bytes of an instruction as an
+    /// argument, e.g., `data: Vec<u8>`, and dispatch on them by hand:
+    ///
+    /// - by deserializing the bytes into an enum and matching on it, or
+    /// - by matching on one of the bytes, e.g., `match data[0]`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Anchor validates the accounts of an instruction, and describes its arguments in the IDL,
+    /// per handler. A handler which dispatches on its own runs all of its operations with the same
+    /// `Accounts` struct, so the constraints cannot be specific to an operation, and clients cannot
+    /// know from the IDL how to encode the arguments. Each operation then has to validate its
+    /// accounts by hand, which is easy to get wrong.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The lint only recognizes the deserialization with `BorshDeserialize::try_from_slice` and
+    /// `BorshDeserialize::deserialize` (or their `AnchorDeserialize` aliases). The value matched on
+    /// must be the deserialized value, the indexed byte, or a local variable initialized with
+    /// either of them; the bytes passed to another function are not followed.
+    ///
+    /// A dispatch which is intended, e.g., to keep a stable custom discriminator, is reported as
+    /// well; allow the lint on the handler after documenting the encoding.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn execute(ctx: Context<Execute>, data: Vec<u8>) -> Result<()> {
+    ///     match Action::try_from_slice(&data)? {
+    ///         Action::Deposit { amount } => deposit(ctx, amount),
+    ///         Action::Withdraw { amount } => withdraw(ctx, amount),
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    ///     ...
+    /// }
+    ///
+    /// pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros and
+    ///   the closures
+    /// - if the first parameter of the function is not an Anchor `Context`, ignore the function
+    /// - for each parameter `data` of type `Vec<u8>` or `&[u8]`
+    ///   - find a `match` expression with at least two arms, whose scrutinee is
+    ///     - a call to `try_from_slice` or `deserialize` whose arguments use `data`, or
+    ///     - an index of `data`, e.g., `data[0]`, or
+    ///     - a local variable initialized with either of them
+    ///   - if there is such an expression, report `data`
+    pub MANUAL_INSTRUCTION_DISPATCH,
+    Warn,
+    "Anchor instructions which deserialize a raw byte argument and dispatch on it"
+}
+
+impl<'tcx> LateLintPass<'tcx> for ManualInstructionDispatch {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        fn_kind: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        if is_skipped_expansion(MANUAL_INSTRUCTION_DISPATCH, span)
+            || matches!(fn_kind, FnKind::Closure)
+            || context_accounts_struct(cx, local_def_id).is_none()
+        {
+            return;
+        }
+        for param in body.params {
+            if_chain! {
+                if let PatKind::Binding(_, hir_id, ident, None) = param.pat.kind;
+                if is_byte_buffer(cx, cx.typeck_results().node_type(param.pat.hir_id));
+                if let Some(dispatch) = find_dispatch(cx, body.value, hir_id);
+                then {
+                    span_lint_hir_and_then(
+                        cx,
+                        MANUAL_INSTRUCTION_DISPATCH,
+                        param.hir_id,
+                        param.pat.span,
+                        &format!("the instruction dispatches on the raw bytes of `{ident}`"),
+                        |diag| {
+                            if let Source::Deserialized(call) = dispatch.source {
+                                diag.span_note(
+                                    call.span,
+                                    format!("`{ident}` is deserialized here"),
+                                );
+                            }
+                            diag.span_note(
+                                dispatch.scrutinee.span,
+                                "the instruction is dispatched here",
+                            );
+                            diag.help(
+                                "declare a separate instruction for each case, so that Anchor validates its accounts and the IDL describes its arguments, or document the custom encoding",
+                            );
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[MANUAL_INSTRUCTION_DISPATCH]);
+    }
+}
+
+/// A `match` expression which dispatches on the bytes of a parameter
+struct Dispatch<'tcx> {
+    scrutinee: &'tcx Expr<'tcx>,
+    source: Source<'tcx>,
+}
+
+/// How the value matched on is obtained from the bytes
+#[derive(Clone, Copy)]
+enum Source<'tcx> {
+    /// The bytes are deserialized by the call
+    Deserialized(&'tcx Expr<'tcx>),
+    /// The value is one of the bytes, e.g., `data[0]`
+    Indexed,
+}
+
+/// Return true if `ty` is `Vec<u8>` or `&[u8]`
+fn is_byte_buffer<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>) -> bool {
+    match ty.kind() {
+        ty::Adt(_, args) if is_type_diagnostic_item(cx, ty, sym::Vec) => is_u8(args.type_at(0)),
+        ty::Ref(_, inner, _) => matches!(inner.kind(), ty::Slice(elem) if is_u8(*elem)),
+        _ => false,
+    }
+}
+
+fn is_u8(ty: Ty<'_>) -> bool {
+    matches!(ty.kind(), ty::Uint(ty::UintTy::U8))
+}
+
+/// Return the first `match` expression in `scope` with at least two arms, which dispatches on the
+/// bytes of `param`
+fn find_dispatch<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    param: HirId,
+) -> Option<Dispatch<'tcx>> {
+    visit_expr_no_bodies(scope, |expr| {
+        if_chain! {
+            if let ExprKind::Match(scrutinee, arms, MatchSource::Normal) = expr.kind;
+            if arms.len() > 1;
+            if let Some(source) = dispatch_source(cx, scrutinee, param);
+            then {
+                Some(Dispatch { scrutinee, source })
+            } else {
+                None
+            }
+        }
+    })
+}
+
+/// If the value of `scrutinee` is obtained from the bytes of `param`, directly or through a local
+/// variable, return how
+fn dispatch_source<'tcx>(
+    cx: &LateContext<'tcx>,
+    scrutinee: &'tcx Expr<'tcx>,
+    param: HirId,
+) -> Option<Source<'tcx>> {
+    let scrutinee = peel_addr_of(scrutinee);
+    byte_source(cx, scrutinee, param).or_else(|| {
+        let local = path_to_local(scrutinee)?;
+        let init = local_init(cx, local)?;
+        byte_source(cx, peel_addr_of(init), param)
+    })
+}
+
+/// If `expr` indexes `param`, or contains a call deserializing `param`, return how
+fn byte_source<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    param: HirId,
+) -> Option<Source<'tcx>> {
+    if let ExprKind::Index(base, _, _) = expr.kind {
+        return path_to_local_id(base, param).then_some(Source::Indexed);
+    }
+    visit_expr_no_bodies(expr, |expr| {
+        if_chain! {
+            if let Some(def_id) = fn_def_id(cx, expr);
+            if match_any_def_paths(
+                cx,
+                def_id,
+                &[&paths::BORSH_TRY_FROM_SLICE, &paths::BORSH_DESERIALIZE],
+            )
+            .is_some();
+            if visit_expr_no_bodies(expr, |arg| path_to_local_id(arg, param));
+            then {
+                Some(Source::Deserialized(expr))
+            } else {
+                None
+            }
+        }
+    })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod manual_instruction_dispatch_insecure {
+    use super::*;
+
+    pub fn execute(ctx: Context<Execute>, data: Vec<u8>) -> Result<()> {
+        match Action::try_from_slice(&data)? {
+            Action::Deposit { amount } => ctx.accounts.vault.balance += amount,
+            Action::Withdraw { amount } => ctx.accounts.vault.balance -= amount,
+        }
+        Ok(())
+    }
+
+    pub fn execute_deserialized(ctx: Context<Execute>, data: Vec<u8>) -> Result<()> {
+        let action = Action::deserialize(&mut data.as_slice())?;
+        match action {
+            Action::Deposit { amount } => ctx.accounts.vault.balance += amount,
+            Action::Withdraw { amount } => ctx.accounts.vault.balance -= amount,
+        }
+        Ok(())
+    }
+
+    pub fn execute_tagged(ctx: Context<Execute>, data: Vec<u8>) -> Result<()> {
+        let amount = u64::try_from_slice(&data[1..])?;
+        match data[0] {
+            0 => ctx.accounts.vault.balance += amount,
+            1 => ctx.accounts.vault.balance -= amount,
+            _ => return err!(ErrorCode::UnknownAction),
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub enum Action {
+    Deposit { amount: u64 },
+    Withdraw { amount: u64 },
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    #[account(mut, has_one = authority)]
+    vault: Account<'info, Vault>,
+    authority: Signer<'info>,
+}
+
+#[account]
+pub struct Vault {
+    authority: Pubkey,
+    balance: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    UnknownAction,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod manual_instruction_dispatch_secure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Execute>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.balance += amount;
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Execute>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.balance -= amount;
+        Ok(())
+    }
+
+    // The bytes are deserialized, but not dispatched on
+    pub fn configure(ctx: Context<Execute>, data: Vec<u8>) -> Result<()> {
+        let config = Config::try_from_slice(&data)?;
+        ctx.accounts.vault.limit = config.limit;
+        Ok(())
+    }
+
+    // The bytes are only stored
+    pub fn set_memo(ctx: Context<Execute>, memo: Vec<u8>) -> Result<()> {
+        if memo.len() > 32 {
+            return err!(ErrorCode::MemoTooLong);
+        }
+        ctx.accounts.vault.memo = memo;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct Config {
+    limit: u64,
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    #[account(mut, has_one = authority)]
+    vault: Account<'info, Vault>,
+    authority: Signer<'info>,
+}
+
+#[account]
+pub struct Vault {
+    authority: Pubkey,
+    balance: u64,
+    limit: u64,
+    memo: Vec<u8>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    MemoTooLong,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,425 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use anchor_syn::AccountField;
+use clippy_utils::{
+    consts::{constant, Constant},
+    match_def_path,
+};
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::{def_id::LocalDefId, ImplItem, ImplItemKind, Item, ItemKind, QPath, TyKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, layout::LayoutOf, Ty};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    paths, sarif,
+    utils::{anchor_constraints::init_space, borsh_size, get_anchor_accounts_struct},
+};
+use syn::visit::Visit;
+
+const DISCRIMINATOR_SIZE: u128 = 8;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports `space` (and `realloc`) constraints of `Account` and `AccountLoader` fields which do
+    /// not include the 8 bytes of the Anchor account discriminator, e.g., `space = Vault::INIT_SPACE`
+    /// or `space = std::mem::size_of::<Vault>()`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Anchor stores an 8-byte discriminator at the start of the data of every account it creates,
+    /// before the account itself. If the space allocated for the account does not include the
+    /// discriminator, the account cannot be serialized after the instruction, or, for zero-copy
+    /// accounts, cannot be loaded.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// A `space` expression which calls a function or uses a value that is not an integer constant
+    /// defined in the crate is only reported if it also uses `INIT_SPACE` or `size_of`. A `space`
+    /// expression which consists of a single number is only reported if the size of the account can
+    /// be estimated, i.e., if the account does not contain a `Vec`, a `String` or a `Box`.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Initialize<'info> {
+    ///     #[account(init, payer = payer, space = Vault::INIT_SPACE)]
+    ///     vault: Account<'info, Vault>,
+    ///     #[account(mut)]
+    ///     payer: Signer<'info>,
+    ///     system_program: Program<'info, System>,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Initialize<'info> {
+    ///     #[account(init, payer = payer, space = 8 + Vault::INIT_SPACE)]
+    ///     vault: Account<'info, Vault>,
+    ///     #[account(mut)]
+    ///     payer: Signer<'info>,
+    ///     system_program: Program<'info, System>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item, check_impl_item:
+    ///
+    /// - For each integer constant, record its value
+    /// - For each Anchor `Accounts` struct
+    ///   - For each field with `#[account(init, space = ...)]` or `#[account(realloc = ...)]` constraint
+    ///     - Split the expression into the terms of the sum and record them
+    ///
+    /// check_crate_post:
+    ///
+    /// - For each recorded `space` expression of an `Account<'info, T>` or `AccountLoader<'info, T>` field
+    ///   - Estimate the size of `T`: the size of `T` serialized with Borsh for `Account`, and the size of `T`
+    ///     in memory for `AccountLoader` (zero-copy)
+    ///   - If every term is a number and the size of `T` is known
+    ///     - Report the field if the sum is less than 8 + the size of `T`
+    ///   - Else if no term is `8`, a constant with the value `8` or a name containing `discriminator`
+    ///     - Report the field if the terms are numbers, `INIT_SPACE` or `size_of::<_>()`, and there
+    ///       are at least two terms or one of them is `INIT_SPACE` or `size_of::<_>()`
+    pub MISSING_DISCRIMINATOR_SPACE,
+    Warn,
+    "account `space` which does not include the 8-byte discriminator",
+    MissingDiscriminatorSpace::default()
+}
+
+#[derive(Default)]
+struct MissingDiscriminatorSpace {
+    /// Values of the integer constants, by name (`NAME` or `Type::NAME` for associated constants)
+    constants: FxHashMap<String, u128>,
+    /// `space` expressions of the fields of Anchor `Accounts` structs
+    spaces: Vec<Space>,
+}
+
+struct Space {
+    field_def_id: LocalDefId,
+    field_name: String,
+    span: Span,
+    terms: Vec<Term>,
+}
+
+/// A term of the sum in a `space` expression
+enum Term {
+    /// A value which accounts for the discriminator, e.g., `Vault::DISCRIMINATOR.len()`
+    Discriminator,
+    /// The size of an account without the discriminator: `T::INIT_SPACE` or `size_of::<T>()`
+    DataSize,
+    /// A number, possibly given by a constant
+    Value(Value),
+    /// Anything else, e.g., a function call
+    Opaque,
+}
+
+enum Value {
+    Int(u128),
+    Const(String),
+    Mul(Box<Value>, Box<Value>),
+}
+
+impl Value {
+    fn eval(&self, constants: &FxHashMap<String, u128>) -> Option<u128> {
+        match self {
+            Value::Int(value) => Some(*value),
+            Value::Const(name) => constants.get(name).copied(),
+            Value::Mul(lhs, rhs) => lhs.eval(constants)?.checked_mul(rhs.eval(constants)?),
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for MissingDiscriminatorSpace {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        match item.kind {
+            ItemKind::Const(_, _, body_id) => {
+                if let Some(value) = const_value(cx, body_id) {
+                    self.constants.insert(item.ident.to_string(), value);
+                }
+            }
+            ItemKind::Struct(variant, _) => {
+                if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+                    for (item_field, anchor_field) in
+                        variant.fields().iter().zip(accounts_struct.fields.iter())
+                    {
+                        if let AccountField::Field(field) = anchor_field {
+                            let spaces = field
+                                .constraints
+                                .init
+                                .as_ref()
+                                .and_then(init_space)
+                                .into_iter()
+                                .chain(
+                                    field
+                                        .constraints
+                                        .realloc
+                                        .as_ref()
+                                        .map(|realloc| &realloc.space),
+                                );
+                            for space in spaces {
+                                let mut terms = Vec::new();
+                                collect_terms(space, &mut terms);
+                                self.spaces.push(Space {
+                                    field_def_id: item_field.def_id,
+                                    field_name: field.ident.to_string(),
+                                    span: item_field.span,
+                                    terms,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_impl_item(&mut self, cx: &LateContext<'tcx>, impl_item: &'tcx ImplItem<'tcx>) {
+        if_chain! {
+            if let ImplItemKind::Const(_, body_id) = impl_item.kind;
+            let parent = cx.tcx.hir().get_parent_item(impl_item.hir_id());
+            if let ItemKind::Impl(impl_) = cx.tcx.hir().expect_item(parent.def_id).kind;
+            if let TyKind::Path(QPath::Resolved(None, path)) = impl_.self_ty.kind;
+            if let Some(segment) = path.segments.last();
+            if let Some(value) = const_value(cx, body_id);
+            then {
+                self.constants
+                    .insert(format!("{}::{}", segment.ident, impl_item.ident), value);
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for space in &self.spaces {
+            let field_ty = cx.tcx.type_of(space.field_def_id).instantiate_identity();
+            let (account_ty, data_size) = match account_data_size(cx, field_ty) {
+                Some(account_data_size) => account_data_size,
+                None => continue,
+            };
+            let values = space
+                .terms
+                .iter()
+                .map(|term| match term {
+                    Term::Value(value) => value.eval(&self.constants),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            // If `space` is a known number, compare it with the size of the account.
+            if values.iter().all(Option::is_some) {
+                if let Some(data_size) = data_size {
+                    let total = values.iter().flatten().sum::<u128>();
+                    if total < u128::from(data_size) + DISCRIMINATOR_SIZE {
+                        report(
+                            cx,
+                            space,
+                            Some(format!(
+                                "`{account_ty}` takes {data_size} bytes, but the `space` is {total} bytes"
+                            )),
+                        );
+                    }
+                    continue;
+                }
+            }
+
+            let includes_discriminator = space.terms.iter().zip(&values).any(|(term, value)| {
+                matches!(term, Term::Discriminator) || *value == Some(DISCRIMINATOR_SIZE)
+            });
+            let includes_unknown = space.terms.iter().zip(&values).any(|(term, value)| {
+                matches!(term, Term::Opaque) || (matches!(term, Term::Value(_)) && value.is_none())
+            });
+            let includes_data_size = space
+                .terms
+                .iter()
+                .any(|term| matches!(term, Term::DataSize));
+            if !includes_discriminator
+                && !includes_unknown
+                && (includes_data_size || space.terms.len() > 1)
+            {
+                report(cx, space, None);
+            }
+        }
+
+        sarif::write(cx, &[MISSING_DISCRIMINATOR_SPACE]);
+    }
+}
+
+/// Return the value of the constant if it is an integer
+fn const_value(cx: &LateContext<'_>, body_id: rustc_hir::BodyId) -> Option<u128> {
+    let body = cx.tcx.hir().body(body_id);
+    if let Some(Constant::Int(value)) = constant(cx, cx.tcx.typeck_body(body_id), body.value) {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+/// If `ty` is `Account<'info, T>` or `AccountLoader<'info, T>` (possibly boxed), return `T` and the size of
+/// its data, if known: the size of `T` serialized with Borsh for `Account`, the size of `T` in memory for
+/// `AccountLoader`
+fn account_data_size<'tcx>(
+    cx: &LateContext<'tcx>,
+    ty: Ty<'tcx>,
+) -> Option<(Ty<'tcx>, Option<u64>)> {
+    let ty = if ty.is_box() { ty.boxed_ty() } else { ty };
+    if_chain! {
+        if let ty::Adt(adt_def, args) = ty.kind();
+        if let Some(account_ty) = args.types().next();
+        then {
+            if match_def_path(cx, adt_def.did(), &paths::ANCHOR_LANG_ACCOUNT) {
+                Some((account_ty, borsh_size(cx, account_ty)))
+            } else if match_def_path(cx, adt_def.did(), &paths::ANCHOR_LANG_ACCOUNT_LOADER) {
+                // Zero-copy accounts are not serialized. Their data is the in-memory representation.
+                Some((
+                    account_ty,
+                    cx.layout_of(account_ty).ok().map(|layout| layout.size.bytes()),
+                ))
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Split the expression into the terms of the sum: `a + (b + c)` => `[a, b, c]`
+fn collect_terms(expr: &syn::Expr, terms: &mut Vec<Term>) {
+    match expr {
+        syn::Expr::Binary(binary) if matches!(binary.op, syn::BinOp::Add(_)) => {
+            collect_terms(&binary.left, terms);
+            collect_terms(&binary.right, terms);
+        }
+        syn::Expr::Paren(paren) => collect_terms(&paren.expr, terms),
+        _ => terms.push(term(expr)),
+    }
+}
+
+fn term(expr: &syn::Expr) -> Term {
+    let mut mentions_discriminator = MentionsDiscriminator::default();
+    mentions_discriminator.visit_expr(expr);
+    if mentions_discriminator.found {
+        Term::Discriminator
+    } else if is_data_size(expr) {
+        Term::DataSize
+    } else {
+        value(expr).map_or(Term::Opaque, Term::Value)
+    }
+}
+
+/// Return true if the expression is `T::INIT_SPACE` or `size_of::<T>()` (with any path to `size_of`)
+fn is_data_size(expr: &syn::Expr) -> bool {
+    match expr {
+        syn::Expr::Path(expr_path) => {
+            expr_path.path.segments.len() > 1
+                && expr_path
+                    .path
+                    .segments
+                    .last()
+                    .map_or(false, |segment| segment.ident == "INIT_SPACE")
+        }
+        syn::Expr::Call(call) => {
+            if let syn::Expr::Path(func) = &*call.func {
+                func.path
+                    .segments
+                    .last()
+                    .map_or(false, |segment| segment.ident == "size_of")
+            } else {
+                false
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Return the expression as a number, if it is made of integer literals, constants and multiplications
+fn value(expr: &syn::Expr) -> Option<Value> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit_int),
+            ..
+        }) => lit_int.base10_parse().ok().map(Value::Int),
+        // `NAME` or `Type::NAME`
+        syn::Expr::Path(expr_path) => {
+            let segments = &expr_path.path.segments;
+            let name = segments
+                .iter()
+                .skip(segments.len().saturating_sub(2))
+                .map(|segment| segment.ident.to_string())
+                .collect::<Vec<_>>()
+                .join("::");
+            Some(Value::Const(name))
+        }
+        syn::Expr::Binary(binary) if matches!(binary.op, syn::BinOp::Mul(_)) => Some(Value::Mul(
+            Box::new(value(&binary.left)?),
+            Box::new(value(&binary.right)?),
+        )),
+        syn::Expr::Paren(paren) => value(&paren.expr),
+        syn::Expr::Cast(cast) => value(&cast.expr),
+        _ => None,
+    }
+}
+
+/// Finds identifiers containing `discriminator` (case insensitive), e.g., `DISCRIMINATOR_LENGTH`
+#[derive(Default)]
+struct MentionsDiscriminator {
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for MentionsDiscriminator {
+    fn visit_ident(&mut self, ident: &'ast syn::Ident) {
+        if ident.to_string().to_lowercase().contains("discriminator") {
+            self.found = true;
+        }
+    }
+}
+
+fn report(cx: &LateContext<'_>, space: &Space, note: Option<String>) {
+    span_lint_hir_and_then(
+        cx,
+        MISSING_DISCRIMINATOR_SPACE,
+        cx.tcx.local_def_id_to_hir_id(space.field_def_id),
+        space.span,
+        &format!(
+            "the `space` of `{}` does not include the 8-byte account discriminator",
+            space.field_name
+        ),
+        |diag| {
+            if let Some(note) = note {
+                diag.note(note);
+            }
+            diag.help("add the size of the discriminator, e.g., `8 + ...`");
+        },
+    );
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod missing_discriminator_space_secure {
+    use super::*;
+
+    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn resize(_ctx: Context<Resize>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + Vault::INIT_SPACE)]
+    vault: Account<'info, Vault>,
+    #[account(init, payer = payer, space = Vault::DISCRIMINATOR.len() + Vault::INIT_SPACE)]
+    other_vault: Account<'info, Vault>,
+    #[account(init, payer = payer, space = ANCHOR_HEADER + std::mem::size_of::<Vault>())]
+    third_vault: Account<'info, Vault>,
+    #[account(init, payer = payer, space = Vault::LEN)]
+    fourth_vault: Account<'info, Vault>,
+    #[account(init, payer = payer, space = 48)]
+    fifth_vault: Account<'info, Vault>,
+    #[account(init, payer = payer, space = 8 + std::mem::size_of::<Orderbook>())]
+    orderbook: AccountLoader<'info, Orderbook>,
+    #[account(init, payer = payer, space = 1024)]
+    profile: Account<'info, Profile>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Resize<'info> {
+    #[account(
+        mut,
+        realloc = 8 + 4 + MAX_NAME_LEN,
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    profile: Account<'info, Profile>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+const ANCHOR_HEADER: usize = 8;
+
+const MAX_NAME_LEN: usize = 32;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Vault {
+    authority: Pubkey,
+    amount: u64,
+}
+
+impl Vault {
+    const LEN: usize = 8 + 32 + 8;
+}
+
+#[account(zero_copy)]
+pub struct Orderbook {
+    bids: [u64; 16],
+    asks: [u64; 16],
+}
+
+#[account]
+pub struct Profile {
+    name: String,
+}
+
+#[allow(dead_code)]
+fn main() {}
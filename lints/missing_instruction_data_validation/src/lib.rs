@@ -0,0 +1,497 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+
+use clippy_utils::{
+    consts::{constant, Constant},
+    higher::Range,
+    path_to_local, peel_hir_expr_refs,
+};
+use if_chain::if_chain;
+use rustc_ast::ast::RangeLimits;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_hir::{
+    def::{DefKind, Res},
+    def_id::{DefId, LocalDefId},
+    intravisit::{walk_expr, walk_local, Visitor},
+    BinOpKind, Expr, ExprKind, HirId, Local, PatKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty, TypeckResults};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, sarif,
+    utils::entrypoint_process_instruction,
+};
+use std::collections::VecDeque;
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports, in non-Anchor programs, the indexing and slicing of the instruction data, e.g.,
+    /// `instruction_data[0]` or `&instruction_data[1..33]`, which is not preceded by a check of
+    /// the length of the data.
+    ///
+    /// The instruction data is the `&[u8]` parameter of the function registered with
+    /// `entrypoint!`, and the `&[u8]` parameters of the functions of the crate it is passed to. The
+    /// slices of the instruction data, e.g., `let rest = &instruction_data[1..]`, are followed.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The caller chooses the instruction data. Indexing past its end makes the program panic,
+    /// so anyone can make the instruction fail with an unhelpful error, e.g., to grief the users
+    /// of a program which is called by other programs. Checking the length first returns a proper
+    /// error, e.g., `ProgramError::InvalidInstructionData`.
+    ///
+    /// **Works on:**
+    ///
+    /// - [ ] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The checks are not required to be on the path to the index: any comparison of the length
+    /// with a large enough constant, or with a value which is not a constant, which comes before
+    /// the index in the function counts, whichever branch it leads to. An index which is not a
+    /// constant is considered checked by any comparison of the length. The values obtained from
+    /// the instruction data other than by slicing, e.g., with `split_first`, are not followed.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// fn process_instruction(
+    ///     program_id: &Pubkey,
+    ///     accounts: &[AccountInfo],
+    ///     instruction_data: &[u8],
+    /// ) -> ProgramResult {
+    ///     let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// fn process_instruction(
+    ///     program_id: &Pubkey,
+    ///     accounts: &[AccountInfo],
+    ///     instruction_data: &[u8],
+    /// ) -> ProgramResult {
+    ///     if instruction_data.len() < 9 {
+    ///         return Err(ProgramError::InvalidInstructionData);
+    ///     }
+    ///     let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_crate_post:
+    ///
+    /// - find the function registered with `entrypoint!` (see
+    ///   `solana_lints::utils::entrypoint_process_instruction`), unless it is generated by a macro,
+    ///   e.g., by Anchor
+    /// - visit its body in order, starting with its `&[u8]` parameters as the instruction data:
+    ///   - record the local variables bound to the instruction data, or to a slice of it, with
+    ///     the offset of the slice when its start is a constant
+    ///   - record the length the data is checked to have at least: `len()` compared with a
+    ///     constant `N` checks `N` (`N + 1` for `>` and `<=`), `is_empty()` checks `1`, and `len()`
+    ///     compared with another value checks any length
+    ///   - for each index or range index of the data, compute the length it requires, e.g., `1`
+    ///     for `data[0]` and `33` for `data[1..33]`, and report it if the length is not checked to
+    ///     be at least the required length; then record the required length as checked, as the
+    ///     index panics otherwise
+    ///   - record the functions of the crate the data is passed to, with the length it is checked
+    ///     to have, and visit their bodies in turn
+    pub MISSING_INSTRUCTION_DATA_VALIDATION,
+    Warn,
+    "instruction data indexed or sliced without a preceding length check"
+}
+
+impl<'tcx> LateLintPass<'tcx> for MissingInstructionDataValidation {
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        if let Some(process_instruction) = entrypoint_process_instruction(cx) {
+            check_instruction_data(cx, process_instruction);
+        }
+
+        sarif::write(cx, &[MISSING_INSTRUCTION_DATA_VALIDATION]);
+    }
+}
+
+/// Visit the functions the instruction data is passed to, starting with `process_instruction`, and
+/// report the unchecked indexes of the data
+fn check_instruction_data(cx: &LateContext<'_>, process_instruction: LocalDefId) {
+    let hir_map = cx.tcx.hir();
+    let byte_params = |def_id: LocalDefId| {
+        let body = hir_map.body(hir_map.body_owned_by(def_id));
+        let typeck_results = cx.tcx.typeck(def_id);
+        body.params
+            .iter()
+            .map(|param| match param.pat.kind {
+                PatKind::Binding(_, hir_id, _, None)
+                    if is_byte_slice(typeck_results.pat_ty(param.pat)) =>
+                {
+                    Some(hir_id)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut visited = FxHashSet::default();
+    // the functions to visit, with their parameters holding the instruction data, and the length
+    // the data is checked to have at least
+    let mut worklist = byte_params(process_instruction)
+        .into_iter()
+        .flatten()
+        .map(|hir_id| (process_instruction, hir_id, None))
+        .collect::<VecDeque<_>>();
+    while let Some((def_id, param, checked_length)) = worklist.pop_front() {
+        if !visited.insert(param)
+            || is_skipped_expansion(MISSING_INSTRUCTION_DATA_VALIDATION, cx.tcx.def_span(def_id))
+        {
+            continue;
+        }
+        let mut visitor = DataVisitor {
+            cx,
+            typeck_results: cx.tcx.typeck(def_id),
+            slices: FxHashMap::default(),
+            checked_lengths: FxHashMap::default(),
+            callees: Vec::new(),
+        };
+        visitor.slices.insert(
+            param,
+            Slice {
+                data: param,
+                offset: 0,
+            },
+        );
+        if let Some(checked_length) = checked_length {
+            visitor.checked_lengths.insert(param, checked_length);
+        }
+        visitor.visit_expr(hir_map.body(hir_map.body_owned_by(def_id)).value);
+
+        for (callee, index, checked_length) in visitor.callees {
+            if_chain! {
+                if let Some(callee) = callee.as_local();
+                if cx.tcx.hir().maybe_body_owned_by(callee).is_some();
+                if let Some(Some(param)) = byte_params(callee).get(index);
+                then {
+                    worklist.push_back((callee, *param, checked_length));
+                }
+            }
+        }
+    }
+}
+
+/// A local variable holding the instruction data, or a slice of it
+#[derive(Clone, Copy)]
+struct Slice {
+    /// The local variable holding the data the variable is a slice of
+    data: HirId,
+    /// The index of the first byte of the slice in `data`
+    offset: u128,
+}
+
+/// The value of an expression which is the instruction data, or a slice of it
+enum SliceValue {
+    /// A slice starting at a known offset of `Slice::data`, and ending with it
+    Suffix(Slice),
+    /// A slice of known length
+    Fixed(u128),
+    /// A slice of unknown length
+    Unknown,
+}
+
+struct DataVisitor<'a, 'tcx> {
+    cx: &'a LateContext<'tcx>,
+    typeck_results: &'tcx TypeckResults<'tcx>,
+    /// The local variables holding the instruction data, or a slice of it
+    slices: FxHashMap<HirId, Slice>,
+    /// For each local variable which is the `data` of a `Slice`, the length it is checked to have
+    /// at least, `u128::MAX` if the length is compared with a value which is not a constant
+    checked_lengths: FxHashMap<HirId, u128>,
+    /// The functions the data is passed to, with the index of the argument and the length the
+    /// data is checked to have at least
+    callees: Vec<(DefId, usize, Option<u128>)>,
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for DataVisitor<'a, 'tcx> {
+    fn visit_expr(&mut self, expr: &'tcx Expr<'tcx>) {
+        match expr.kind {
+            ExprKind::Binary(op, left, right) => self.check_comparison(op.node, left, right),
+            ExprKind::MethodCall(segment, receiver, [], _)
+                if segment.ident.as_str() == "is_empty" =>
+            {
+                if let Some(slice) = self.local_slice(receiver) {
+                    self.check_length(slice, 1);
+                }
+            }
+            ExprKind::Index(base, index, _) => self.check_index(expr, base, index),
+            _ => {}
+        }
+        match expr.kind {
+            ExprKind::Call(callee, args) => {
+                if_chain! {
+                    if let ExprKind::Path(qpath) = &callee.kind;
+                    if let Res::Def(DefKind::Fn | DefKind::AssocFn, def_id) =
+                        self.typeck_results.qpath_res(qpath, callee.hir_id);
+                    then {
+                        self.record_callee(def_id, args.iter());
+                    }
+                }
+            }
+            ExprKind::MethodCall(_, receiver, args, _) => {
+                if let Some(def_id) = self.typeck_results.type_dependent_def_id(expr.hir_id) {
+                    self.record_callee(def_id, std::iter::once(receiver).chain(args));
+                }
+            }
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+
+    fn visit_local(&mut self, local: &'tcx Local<'tcx>) {
+        walk_local(self, local);
+        if_chain! {
+            if let PatKind::Binding(_, hir_id, _, None) = local.pat.kind;
+            if let Some(init) = local.init;
+            if let Some(value) = self.slice_value(init);
+            then {
+                let slice = match value {
+                    SliceValue::Suffix(slice) => slice,
+                    SliceValue::Fixed(length) => {
+                        self.checked_lengths.insert(hir_id, length);
+                        Slice {
+                            data: hir_id,
+                            offset: 0,
+                        }
+                    }
+                    SliceValue::Unknown => Slice {
+                        data: hir_id,
+                        offset: 0,
+                    },
+                };
+                self.slices.insert(hir_id, slice);
+            }
+        }
+    }
+}
+
+impl<'a, 'tcx> DataVisitor<'a, 'tcx> {
+    /// If `expr` is a local variable holding the instruction data, or a slice of it, return the
+    /// slice
+    fn local_slice(&self, expr: &Expr<'_>) -> Option<Slice> {
+        path_to_local(expr).and_then(|hir_id| self.slices.get(&hir_id).copied())
+    }
+
+    /// If `expr` is the instruction data, or a slice of it, e.g., `&data[1..]`, return what is
+    /// known of its length
+    fn slice_value(&self, expr: &Expr<'_>) -> Option<SliceValue> {
+        let (expr, _) = peel_hir_expr_refs(expr);
+        if let Some(slice) = self.local_slice(expr) {
+            return Some(SliceValue::Suffix(slice));
+        }
+        if_chain! {
+            if let ExprKind::Index(base, index, _) = expr.kind;
+            if let Some(slice) = self.local_slice(base);
+            if let Some(range) = Range::hir(index);
+            then {
+                let start = range.start.map_or(Some(0), |start| self.constant_int(start));
+                let end = range.end.map(|end| {
+                    self.constant_int(end)
+                        .map(|end| end + u128::from(range.limits == RangeLimits::Closed))
+                });
+                Some(match (start, end) {
+                    (Some(start), None) => SliceValue::Suffix(Slice {
+                        data: slice.data,
+                        offset: slice.offset + start,
+                    }),
+                    (Some(start), Some(Some(end))) => {
+                        SliceValue::Fixed(end.saturating_sub(start))
+                    }
+                    _ => SliceValue::Unknown,
+                })
+            } else {
+                None
+            }
+        }
+    }
+
+    /// If `expr` is `x.len()`, where `x` holds the instruction data or a slice of it, return the
+    /// slice
+    fn length_of(&self, expr: &Expr<'_>) -> Option<Slice> {
+        if_chain! {
+            if let ExprKind::MethodCall(segment, receiver, [], _) = expr.kind;
+            if segment.ident.as_str() == "len";
+            then {
+                self.local_slice(receiver)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Record the length checked by the comparison `left op right`, if it compares the length of
+    /// the data
+    fn check_comparison(&mut self, op: BinOpKind, left: &Expr<'_>, right: &Expr<'_>) {
+        let (op, slice, other) = if let Some(slice) = self.length_of(left) {
+            (op, slice, right)
+        } else if let Some(slice) = self.length_of(right) {
+            // `N < data.len()` is `data.len() > N`
+            let op = match op {
+                BinOpKind::Lt => BinOpKind::Gt,
+                BinOpKind::Le => BinOpKind::Ge,
+                BinOpKind::Gt => BinOpKind::Lt,
+                BinOpKind::Ge => BinOpKind::Le,
+                op => op,
+            };
+            (op, slice, left)
+        } else {
+            return;
+        };
+        // `data.len() > N` and `data.len() <= N` tell apart the lengths of at least `N + 1`
+        let excess = match op {
+            BinOpKind::Lt | BinOpKind::Ge | BinOpKind::Eq | BinOpKind::Ne => 0,
+            BinOpKind::Gt | BinOpKind::Le => 1,
+            _ => return,
+        };
+        let length = self
+            .constant_int(other)
+            .map_or(u128::MAX, |length| length.saturating_add(excess));
+        self.check_length(slice, length);
+    }
+
+    /// Record that the length of `slice` is checked to be at least `length`
+    fn check_length(&mut self, slice: Slice, length: u128) {
+        let checked_length = self.checked_lengths.entry(slice.data).or_default();
+        *checked_length = (*checked_length).max(length.saturating_add(slice.offset));
+    }
+
+    /// Report `expr`, i.e., `base[index]`, if `base` is the instruction data and its length is not
+    /// checked to be at least the length the index requires
+    fn check_index(&mut self, expr: &Expr<'_>, base: &Expr<'_>, index: &Expr<'_>) {
+        let (local, slice) = match path_to_local(base) {
+            Some(local) if self.slices.contains_key(&local) => (local, self.slices[&local]),
+            _ => return,
+        };
+        if is_skipped_expansion(MISSING_INSTRUCTION_DATA_VALIDATION, expr.span) {
+            return;
+        }
+        let required_length = if let Some(range) = Range::hir(index) {
+            match (range.start, range.end) {
+                (_, Some(end)) => self
+                    .constant_int(end)
+                    .map(|end| end + u128::from(range.limits == RangeLimits::Closed)),
+                (Some(start), None) => self.constant_int(start),
+                (None, None) => Some(0),
+            }
+        } else {
+            self.constant_int(index).map(|index| index + 1)
+        };
+        let checked_length = self.checked_lengths.get(&slice.data).copied();
+        let is_checked = match required_length {
+            // e.g., `data[..0]` cannot panic
+            Some(0) => true,
+            Some(required_length) => checked_length.map_or(false, |checked_length| {
+                checked_length >= required_length + slice.offset
+            }),
+            None => checked_length.is_some(),
+        };
+        // the index panics if the data is shorter, so the next indexes can rely on the length
+        if let Some(required_length) = required_length {
+            self.check_length(slice, required_length);
+        }
+        if is_checked {
+            return;
+        }
+
+        let name = self.cx.tcx.hir().name(local);
+        let msg = match required_length {
+            Some(required_length) => format!(
+                "this indexes the instruction data `{name}`, whose length is not checked to be at least {required_length}"
+            ),
+            None => format!(
+                "this indexes the instruction data `{name}`, whose length is not checked"
+            ),
+        };
+        // the length `name` is checked to have, if the data is checked to be longer than `offset`
+        let checked_length = checked_length
+            .filter(|checked_length| *checked_length > slice.offset)
+            .map(|checked_length| checked_length - slice.offset);
+        span_lint_hir_and_then(
+            self.cx,
+            MISSING_INSTRUCTION_DATA_VALIDATION,
+            expr.hir_id,
+            expr.span,
+            &msg,
+            |diag| {
+                if let Some(checked_length) = checked_length {
+                    diag.note(format!(
+                        "the length of `{name}` is only checked to be at least {checked_length}"
+                    ));
+                }
+                diag.help(format!(
+                    "return `ProgramError::InvalidInstructionData` if `{name}.len()` is too short before indexing it, or use `get`"
+                ));
+            },
+        );
+    }
+
+    /// Record the function `def_id` if one of the `args` it is called with is the instruction
+    /// data, or a slice of it
+    fn record_callee<'b>(&mut self, def_id: DefId, args: impl Iterator<Item = &'b Expr<'b>>) {
+        for (index, arg) in args.enumerate() {
+            let checked_length = match self.slice_value(arg) {
+                Some(SliceValue::Suffix(slice)) => self
+                    .checked_lengths
+                    .get(&slice.data)
+                    .filter(|checked_length| **checked_length > slice.offset)
+                    .map(|checked_length| checked_length - slice.offset),
+                Some(SliceValue::Fixed(length)) => Some(length),
+                Some(SliceValue::Unknown) => None,
+                None => continue,
+            };
+            self.callees.push((def_id, index, checked_length));
+        }
+    }
+
+    /// Return the value of `expr` if it is an integer constant
+    fn constant_int(&self, expr: &Expr<'_>) -> Option<u128> {
+        match constant(self.cx, self.typeck_results, expr) {
+            Some(Constant::Int(value)) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// Return true if `ty` is `&[u8]`
+fn is_byte_slice(ty: Ty<'_>) -> bool {
+    if_chain! {
+        if let ty::Ref(_, inner, _) = ty.kind();
+        if let ty::Slice(elem) = inner.kind();
+        then {
+            matches!(elem.kind(), ty::Uint(ty::UintTy::U8))
+        } else {
+            false
+        }
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,37 @@
+use solana_program::{
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+use std::convert::TryInto;
+
+entrypoint!(process_instruction);
+fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    match instruction_data[0] {
+        0 => deposit(accounts, &instruction_data[1..]),
+        1 => set_authority(accounts, instruction_data),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn deposit(_accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let amount = u64::from_le_bytes(data[..8].try_into().unwrap());
+    msg!("deposit {}", amount);
+    Ok(())
+}
+
+fn set_authority(_accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data.len() < 33 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let authority = Pubkey::new_from_array(data[1..33].try_into().unwrap());
+    let bump = data[33];
+    msg!("authority {} bump {}", authority, bump);
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,52 @@
+use solana_program::{
+    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, msg,
+    program_error::ProgramError, pubkey::Pubkey,
+};
+use std::convert::TryInto;
+
+entrypoint!(process_instruction);
+fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    match instruction_data[0] {
+        0 => deposit(accounts, &instruction_data[1..]),
+        1 => set_authority(accounts, instruction_data),
+        2 => withdraw(accounts, &instruction_data[1..]),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn deposit(_accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if 8 > data.len() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount = u64::from_le_bytes(data[..8].try_into().unwrap());
+    msg!("deposit {}", amount);
+    Ok(())
+}
+
+fn set_authority(_accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data.len() != 34 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let key = &data[1..33];
+    let authority = Pubkey::new_from_array(key.try_into().unwrap());
+    let bump = data[33];
+    msg!("authority {} ({}) bump {}", authority, key[0], bump);
+    Ok(())
+}
+
+fn withdraw(_accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let amount = data.get(..8).ok_or(ProgramError::InvalidInstructionData)?;
+    let amount = u64::from_le_bytes(amount.try_into().unwrap());
+    msg!("withdraw {}", amount);
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn main() {}
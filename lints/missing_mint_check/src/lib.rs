@@ -0,0 +1,325 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::{AccountsStruct, Field};
+use clippy_utils::{fn_def_id, match_any_def_paths, path_to_local};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    BinOpKind, Body, Expr, ExprKind, FnDecl, HirId, Item,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    seeds::Seed,
+    utils::{
+        account_struct_field,
+        anchor_constraints::{anchor_field, is_token_account, token_account_mints},
+        get_anchor_accounts_struct, local_init, visit_expr_no_bodies,
+    },
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports token transfers, with `anchor_spl::token::transfer` or
+    /// `spl_token::instruction::transfer`, between two `TokenAccount`s of an Anchor `Accounts`
+    /// struct which are not checked to have the same mint, neither by the constraints of the
+    /// accounts nor by a comparison in the function making the transfer.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A program which records a transfer, e.g., credits a deposit to a pool, assumes that the
+    /// tokens moved are of the mint it expects. If nothing ties the mint of the source to the mint
+    /// of the destination, the assumption rests on the token program rejecting the transfer, and
+    /// on the destination being checked elsewhere. Checking the mints where the accounts are
+    /// declared makes the assumption explicit, and fails the instruction before any state is
+    /// changed.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The SPL Token program itself rejects a transfer between token accounts of different mints,
+    /// so a finding is exploitable only if the program relies on the mints otherwise, e.g., before
+    /// the transfer, or if the token program is not checked.
+    ///
+    /// The mints are regarded as the same if:
+    ///
+    /// - both accounts have a `token::mint`, `associated_token::mint` or `has_one = mint`
+    ///   constraint with the same mint, or are initialized with the same mint
+    /// - a `constraint` of one of the accounts compares the mints, e.g.,
+    ///   `constraint = source.mint == destination.mint`, or compares the mint of each account with
+    ///   the same expression
+    /// - the function making the transfer compares the mints, e.g.,
+    ///   `require_keys_eq!(ctx.accounts.source.mint, ctx.accounts.destination.mint)`
+    ///
+    /// Only the transfers whose accounts are fields of `ctx.accounts`, possibly through local
+    /// variables, are checked. `transfer_checked` is not reported, as the token program checks the
+    /// mint of both accounts against the mint it is passed.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Deposit<'info> {
+    ///     #[account(mut)]
+    ///     source: Account<'info, TokenAccount>,
+    ///     #[account(mut, seeds = [b"vault"], bump)]
+    ///     vault: Account<'info, TokenAccount>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Deposit<'info> {
+    ///     #[account(mut, constraint = source.mint == vault.mint)]
+    ///     source: Account<'info, TokenAccount>,
+    ///     #[account(mut, seeds = [b"vault"], bump)]
+    ///     vault: Account<'info, TokenAccount>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item: Collect Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - for each call to `anchor_spl::token::transfer`, find the `Transfer { from, to, .. }` in
+    ///   its `CpiContext`; for each call to `spl_token::instruction::transfer`, take the source and
+    ///   the destination arguments
+    /// - if both accounts are fields of the same local struct, e.g., `ctx.accounts.source`
+    ///   - if the function compares `x.mint` with `y.mint`, where `x` and `y` are the two
+    ///     accounts, ignore the transfer
+    ///   - else record the transfer
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each recorded transfer, if the struct is an Anchor `Accounts` struct and both fields
+    ///   are `TokenAccount`s, report the transfer unless the constraints of the fields tie the two
+    ///   mints together
+    pub MISSING_MINT_CHECK,
+    Warn,
+    "token transfers between token accounts which are not checked to have the same mint",
+    MissingMintCheck::default()
+}
+
+#[derive(Default)]
+struct MissingMintCheck {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    /// The transfers whose mints are not compared in the function making them
+    transfers: Vec<Transfer>,
+}
+
+/// A transfer between two fields of a local struct
+struct Transfer {
+    hir_id: HirId,
+    span: Span,
+    accounts: DefId,
+    from: String,
+    to: String,
+}
+
+impl<'tcx> LateLintPass<'tcx> for MissingMintCheck {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(MISSING_MINT_CHECK, span) {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if let Some((from, to)) = transfer_accounts(cx, expr);
+                if let Some(from) = account_struct_field(cx, from);
+                if let Some(to) = account_struct_field(cx, to);
+                if from.0 == to.0;
+                if !compares_mints(cx, body.value, &from, &to);
+                then {
+                    self.transfers.push(Transfer {
+                        hir_id: expr.hir_id,
+                        span: expr.span,
+                        accounts: from.0,
+                        from: from.1,
+                        to: to.1,
+                    });
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for transfer in &self.transfers {
+            let accounts_struct = match self.anchor_accounts.get(&transfer.accounts) {
+                Some(accounts_struct) => accounts_struct,
+                None => continue,
+            };
+            let (from, to) = match (
+                anchor_field(accounts_struct, &transfer.from),
+                anchor_field(accounts_struct, &transfer.to),
+            ) {
+                (Some(from), Some(to)) => (from, to),
+                _ => continue,
+            };
+            if !is_token_account(from) || !is_token_account(to) || have_same_mint(from, to) {
+                continue;
+            }
+            span_lint_hir_and_then(
+                cx,
+                MISSING_MINT_CHECK,
+                transfer.hir_id,
+                transfer.span,
+                &format!(
+                    "the mints of `{}` and `{}` are not checked to be the same before this transfer",
+                    transfer.from, transfer.to
+                ),
+                |diag| {
+                    diag.help(format!(
+                        "add `constraint = {}.mint == {}.mint` to the accounts, or compare the mints before the transfer",
+                        transfer.from, transfer.to
+                    ));
+                },
+            );
+        }
+
+        sarif::write(cx, &[MISSING_MINT_CHECK]);
+    }
+}
+
+/// If `expr` is a call to `anchor_spl::token::transfer` or `spl_token::instruction::transfer`,
+/// return the expressions of the source and the destination accounts
+fn transfer_accounts<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<(&'tcx Expr<'tcx>, &'tcx Expr<'tcx>)> {
+    if_chain! {
+        if !is_skipped_expansion(MISSING_MINT_CHECK, expr.span);
+        if let ExprKind::Call(_, args) = expr.kind;
+        if let Some(def_id) = fn_def_id(cx, expr);
+        if let Some(index) = match_any_def_paths(
+            cx,
+            def_id,
+            &[&paths::ANCHOR_SPL_TRANSFER, &paths::SPL_TOKEN_TRANSFER],
+        );
+        then {
+            match (index, args) {
+                // `transfer(CpiContext::new(program, Transfer { from, to, authority }), amount)`
+                (0, [cpi_context, _]) => transfer_struct_accounts(cx, cpi_context, 2),
+                // `transfer(token_program_id, source, destination, authority, signers, amount)`
+                (1, [_, source, destination, ..]) => Some((source, destination)),
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
+
+/// Return the `from` and `to` fields of the `Transfer { .. }` struct expression in `expr`,
+/// following up to `depth` local variables, e.g., `cpi_accounts` in
+/// `CpiContext::new(program, cpi_accounts)`
+fn transfer_struct_accounts<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    depth: usize,
+) -> Option<(&'tcx Expr<'tcx>, &'tcx Expr<'tcx>)> {
+    let mut accounts = None;
+    visit_expr_no_bodies(expr, |expr| {
+        accounts = match expr.kind {
+            ExprKind::Struct(_, fields, _) => {
+                let field = |name: &str| {
+                    fields
+                        .iter()
+                        .find(|field| field.ident.as_str() == name)
+                        .map(|field| field.expr)
+                };
+                field("from").zip(field("to"))
+            }
+            ExprKind::Path(_) if depth > 0 => path_to_local(expr)
+                .and_then(|local| local_init(cx, local))
+                .and_then(|init| transfer_struct_accounts(cx, init, depth - 1)),
+            _ => None,
+        };
+        accounts.is_some()
+    });
+    accounts
+}
+
+/// Return true if `scope` compares the mints of the accounts `from` and `to`, e.g.,
+/// `ctx.accounts.source.mint != ctx.accounts.destination.mint`
+fn compares_mints<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    from: &(DefId, String),
+    to: &(DefId, String),
+) -> bool {
+    let reads_mint = |expr: &'tcx Expr<'tcx>, account: &(DefId, String)| {
+        visit_expr_no_bodies(expr, |expr| match expr.kind {
+            ExprKind::Field(object, ident) if ident.as_str() == "mint" => {
+                account_struct_field(cx, object).as_ref() == Some(account)
+            }
+            _ => false,
+        })
+    };
+    visit_expr_no_bodies(scope, |expr| match expr.kind {
+        ExprKind::Binary(op, lhs, rhs) if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne) => {
+            (reads_mint(lhs, from) && reads_mint(rhs, to))
+                || (reads_mint(lhs, to) && reads_mint(rhs, from))
+        }
+        _ => false,
+    })
+}
+
+/// Return true if the constraints of `from` and `to` tie their mints together
+fn have_same_mint(from: &Field, to: &Field) -> bool {
+    let from_mints = token_account_mints(from);
+    let to_mints = token_account_mints(to);
+    from_mints.iter().any(|mint| to_mints.contains(mint))
+        || from_mints.contains(&Seed::Expr(format!("{}.mint", to.ident)))
+        || to_mints.contains(&Seed::Expr(format!("{}.mint", from.ident)))
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
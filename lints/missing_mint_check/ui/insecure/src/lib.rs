@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program::invoke;
+use anchor_spl::token::{self, spl_token, Token, TokenAccount, Transfer};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod missing_mint_check_insecure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> ProgramResult {
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.source.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts);
+        token::transfer(cpi_ctx, amount)?;
+        ctx.accounts.pool.deposited += amount;
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> ProgramResult {
+        invoke(
+            &spl_token::instruction::transfer(
+                &ctx.accounts.token_program.key(),
+                &ctx.accounts.vault.key(),
+                &ctx.accounts.destination.key(),
+                &ctx.accounts.authority.key(),
+                &[],
+                amount,
+            )?,
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.destination.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+            ],
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pool: Account<'info, Pool>,
+    #[account(mut)]
+    source: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"vault", pool.key().as_ref()], bump)]
+    vault: Account<'info, TokenAccount>,
+    authority: Signer<'info>,
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, token::authority = authority)]
+    vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    destination: Account<'info, TokenAccount>,
+    authority: Signer<'info>,
+    token_program: Program<'info, Token>,
+}
+
+#[account]
+pub struct Pool {
+    deposited: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
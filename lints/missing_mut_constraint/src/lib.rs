@@ -0,0 +1,365 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use anchor_syn::AccountsStruct;
+use clippy_utils::{match_any_def_paths, path_to_local};
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, Expr, ExprKind, FieldDef, FnDecl, HirId, Item, Mutability, Node, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty;
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::{lint_level, span_lint_hir_and_then},
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{
+        anchor_constraints::field_constraint_summary, get_anchor_accounts_struct,
+        is_expr_method_call, local_field_def, visit_expr_no_bodies,
+    },
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the fields of Anchor `Accounts` structs which do not have the `mut` constraint,
+    /// although a function of the program writes the account:
+    ///
+    /// - assigns to its data, e.g., `ctx.accounts.vault.balance += amount`, directly or through a
+    ///   local variable, e.g., `let vault = &mut ctx.accounts.vault;`
+    /// - loads it with `AccountLoader::load_mut`
+    /// - borrows its lamports or its data mutably, e.g., `try_borrow_mut_lamports()`, reallocates
+    ///   its data, or calls `add_lamports` or `sub_lamports` on it
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Anchor only marks the accounts with the `mut` constraint as writable in the IDL, and only
+    /// checks that they are writable. The runtime rejects any change to an account which is not
+    /// writable, so the instruction fails; `AccountLoader::load_mut` fails as well. The writes to
+    /// an `Account<'info, T>` are only persisted when the instruction exits, so the failure is far
+    /// from the write.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The account is recognized from the expression of the write: a field of an `Accounts`
+    /// struct, e.g., `ctx.accounts.vault`, possibly through `to_account_info()` or a local variable
+    /// initialized with it. Writes through other functions, e.g., a CPI, are not recognized.
+    ///
+    /// The fields with the `init`, `zero`, `close` or `realloc` constraints are regarded as
+    /// mutable, since Anchor requires them to be.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+    ///     ctx.accounts.vault.balance += amount;
+    ///     Ok(())
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct Deposit<'info> {
+    ///     #[account(has_one = owner)]
+    ///     vault: Account<'info, Vault>,
+    ///     owner: Signer<'info>,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Deposit<'info> {
+    ///     #[account(mut, has_one = owner)]
+    ///     vault: Account<'info, Vault>,
+    ///     owner: Signer<'info>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item: Collect Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - for each write in the function: an assignment, a call to `AccountLoader::load_mut`,
+    ///   `try_borrow_mut_lamports`, `try_borrow_mut_data`, `realloc`, `add_lamports` or
+    ///   `sub_lamports`, or a call to `borrow_mut` on the `lamports` or the `data` of an
+    ///   `AccountInfo`
+    ///   - follow the written expression through fields, indexes, derefs, borrows, calls to
+    ///     `to_account_info`, and local variables initialized with a mutable borrow or an
+    ///     `AccountInfo`, and record the fields of local structs it accesses, e.g.,
+    ///     `Vault::balance` and `Deposit::vault` for `ctx.accounts.vault.balance`
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each write, find the first recorded field of an Anchor `Accounts` struct
+    /// - if the field does not have the `mut`, `init`, `zero`, `close` or `realloc` constraint,
+    ///   report the field once and note each of its writes, except the writes whose lint level
+    ///   differs from the one of the field, e.g., because of an `#[allow(missing_mut_constraint)]`
+    ///   on their function, which are reported on their own
+    pub MISSING_MUT_CONSTRAINT,
+    Warn,
+    "Anchor accounts which are written without the `mut` constraint",
+    MissingMutConstraint::default()
+}
+
+#[derive(Default)]
+struct MissingMutConstraint {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    writes: Vec<AccountWrite>,
+}
+
+/// A write to an account
+struct AccountWrite {
+    /// The assignment or the call
+    hir_id: HirId,
+    span: Span,
+    /// The fields of local structs accessed by the written expression, innermost first, e.g.,
+    /// `(Vault, "balance")` and `(Deposit, "vault")` for `ctx.accounts.vault.balance`
+    fields: Vec<(DefId, String)>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for MissingMutConstraint {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(MISSING_MUT_CONSTRAINT, span) {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if !is_skipped_expansion(MISSING_MUT_CONSTRAINT, expr.span);
+                if let Some(written) = written_expr(cx, expr);
+                let fields = accessed_fields(cx, written);
+                if !fields.is_empty();
+                then {
+                    self.writes.push(AccountWrite {
+                        hir_id: expr.hir_id,
+                        span: expr.span,
+                        fields,
+                    });
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        // The writes to each field, in the order they are found
+        let mut field_writes: FxIndexMap<(DefId, &str), (&FieldDef<'_>, Vec<Span>)> =
+            FxIndexMap::default();
+        for write in &self.writes {
+            if_chain! {
+                if let Some((def_id, field_name, constraints)) =
+                    write.fields.iter().find_map(|(def_id, field_name)| {
+                        let accounts_struct = self.anchor_accounts.get(def_id)?;
+                        let constraints = field_constraint_summary(accounts_struct, field_name)?;
+                        Some((*def_id, field_name.as_str(), constraints))
+                    });
+                if !constraints.is_writable();
+                if let Some(field_def) = local_field_def(cx, def_id, field_name);
+                then {
+                    // A write with its own lint level, e.g., `#[allow(missing_mut_constraint)]` on
+                    // its function, is reported on its own so that the level applies.
+                    if lint_level(cx, MISSING_MUT_CONSTRAINT, write.hir_id)
+                        == lint_level(cx, MISSING_MUT_CONSTRAINT, field_def.hir_id)
+                    {
+                        field_writes
+                            .entry((def_id, field_name))
+                            .or_insert_with(|| (field_def, Vec::new()))
+                            .1
+                            .push(write.span);
+                    } else {
+                        report_write(cx, write, field_def);
+                    }
+                }
+            }
+        }
+
+        for (field_def, writes) in field_writes.into_values() {
+            span_lint_hir_and_then(
+                cx,
+                MISSING_MUT_CONSTRAINT,
+                field_def.hir_id,
+                field_def.span,
+                "this account is written, but it does not have the `mut` constraint",
+                |diag| {
+                    for span in writes {
+                        diag.span_note(span, "the account is written here");
+                    }
+                    diag.help("add the `mut` constraint to the field: `#[account(mut)]`");
+                },
+            );
+        }
+
+        sarif::write(cx, &[MISSING_MUT_CONSTRAINT]);
+    }
+}
+
+fn report_write(cx: &LateContext<'_>, write: &AccountWrite, field_def: &FieldDef<'_>) {
+    span_lint_hir_and_then(
+        cx,
+        MISSING_MUT_CONSTRAINT,
+        write.hir_id,
+        write.span,
+        "this account is written, but it does not have the `mut` constraint",
+        |diag| {
+            diag.span_note(field_def.span, "the field of the account");
+            diag.help("add the `mut` constraint to the field: `#[account(mut)]`");
+        },
+    );
+}
+
+/// If `expr` writes to an account, return the written expression:
+/// - `x = ...` and `x += ...` => `x`
+/// - `x.load_mut()`, `x.try_borrow_mut_lamports()`, `x.try_borrow_mut_data()`, `x.realloc(..)`,
+///   `x.add_lamports(..)` and `x.sub_lamports(..)` => `x`
+/// - `x.lamports.borrow_mut()` and `x.data.borrow_mut()` on an `AccountInfo` `x` => `x`
+fn written_expr<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> Option<&'tcx Expr<'tcx>> {
+    match expr.kind {
+        ExprKind::Assign(lhs, _, _) | ExprKind::AssignOp(_, lhs, _) => Some(lhs),
+        ExprKind::MethodCall(_, receiver, _, _) => {
+            let def_id = cx.typeck_results().type_dependent_def_id(expr.hir_id)?;
+            if match_any_def_paths(
+                cx,
+                def_id,
+                &[
+                    &paths::ANCHOR_LANG_LOAD_MUT,
+                    &paths::ANCHOR_LANG_ADD_LAMPORTS,
+                    &paths::ANCHOR_LANG_SUB_LAMPORTS,
+                    &paths::SOLANA_PROGRAM_TRY_BORROW_MUT_LAMPORTS,
+                    &paths::SOLANA_PROGRAM_TRY_BORROW_MUT_DATA,
+                    &paths::SOLANA_PROGRAM_REALLOC,
+                ],
+            )
+            .is_some()
+            {
+                return Some(receiver);
+            }
+            if_chain! {
+                if match_any_def_paths(
+                    cx,
+                    def_id,
+                    &[&paths::CORE_CELL_BORROW_MUT, &paths::CORE_CELL_TRY_BORROW_MUT],
+                )
+                .is_some();
+                if let ExprKind::Field(account, ident) = receiver.kind;
+                if matches!(ident.as_str(), "lamports" | "data");
+                let ty = cx.typeck_results().expr_ty_adjusted(account).peel_refs();
+                if paths::is_account_info_ty(cx, ty);
+                then {
+                    Some(account)
+                } else {
+                    None
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Return the fields of local structs accessed by `expr`, innermost first, following fields,
+/// indexes, derefs, borrows, calls to `to_account_info` and the initializers of local variables
+/// which borrow an account (see [`borrowed_init`])
+fn accessed_fields<'tcx>(
+    cx: &LateContext<'tcx>,
+    mut expr: &'tcx Expr<'tcx>,
+) -> Vec<(DefId, String)> {
+    let mut fields = Vec::new();
+    loop {
+        expr = match expr.kind {
+            ExprKind::Field(receiver, ident) => {
+                if let ty::Adt(adt_def, _) = cx
+                    .typeck_results()
+                    .expr_ty_adjusted(receiver)
+                    .peel_refs()
+                    .kind()
+                {
+                    if adt_def.did().is_local() {
+                        fields.push((adt_def.did(), ident.to_string()));
+                    }
+                }
+                receiver
+            }
+            ExprKind::Index(base, _, _)
+            | ExprKind::Unary(UnOp::Deref, base)
+            | ExprKind::AddrOf(_, _, base) => base,
+            _ => {
+                if let Some(receiver) =
+                    is_expr_method_call(cx, expr, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO)
+                {
+                    receiver
+                } else if let Some(init) =
+                    path_to_local(expr).and_then(|local| borrowed_init(cx, local))
+                {
+                    init
+                } else {
+                    return fields;
+                }
+            }
+        };
+    }
+}
+
+/// Return the initializer of the local variable `local`, i.e., `init` in `let local = init;`, if
+/// writing through the variable writes the initializer: `init` is a mutable borrow, e.g.,
+/// `&mut ctx.accounts.vault`, or an `AccountInfo`
+fn borrowed_init<'tcx>(cx: &LateContext<'tcx>, local: HirId) -> Option<&'tcx Expr<'tcx>> {
+    let init = match cx.tcx.hir().parent_iter(local).next() {
+        Some((_, Node::Local(local))) => local.init?,
+        _ => return None,
+    };
+    let ty = cx.typeck_results().node_type(local).peel_refs();
+    (matches!(init.kind, ExprKind::AddrOf(_, Mutability::Mut, _))
+        || paths::is_account_info_ty(cx, ty))
+    .then_some(init)
+}
+
+#[test]
+fn allow() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "allow");
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
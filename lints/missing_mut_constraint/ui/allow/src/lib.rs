@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod missing_mut_constraint_allow {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.balance += amount;
+        Ok(())
+    }
+
+    // The write is allowed, and not noted at the field.
+    #[allow(missing_mut_constraint)]
+    pub fn count_deposit(ctx: Context<Deposit>) -> Result<()> {
+        ctx.accounts.vault.deposits += 1;
+        Ok(())
+    }
+
+    // The write has its own lint level, so it is reported on its own.
+    #[deny(missing_mut_constraint)]
+    pub fn reset(ctx: Context<Deposit>) -> Result<()> {
+        ctx.accounts.vault.balance = 0;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(has_one = owner)]
+    vault: Account<'info, Vault>,
+    owner: Signer<'info>,
+}
+
+#[account]
+pub struct Vault {
+    owner: Pubkey,
+    balance: u64,
+    deposits: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod missing_mut_constraint_insecure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.balance += amount;
+        let vault = &mut ctx.accounts.vault;
+        vault.deposits += 1;
+        Ok(())
+    }
+
+    pub fn set_price(ctx: Context<SetPrice>, price: u64) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+        orderbook.price = price;
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let recipient = ctx.accounts.recipient.to_account_info();
+        **recipient.try_borrow_mut_lamports()? += amount;
+        **ctx.accounts.treasury.lamports.borrow_mut() -= amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(has_one = owner)]
+    vault: Account<'info, Vault>,
+    owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    #[account(has_one = authority)]
+    orderbook: AccountLoader<'info, Orderbook>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: only lamports are transferred to the recipient
+    recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    authority: Signer<'info>,
+    /// CHECK: the treasury is owned by the program
+    #[account(owner = crate::ID)]
+    treasury: AccountInfo<'info>,
+}
+
+#[account]
+pub struct Vault {
+    owner: Pubkey,
+    balance: u64,
+    deposits: u64,
+}
+
+#[account(zero_copy)]
+pub struct Orderbook {
+    authority: Pubkey,
+    price: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
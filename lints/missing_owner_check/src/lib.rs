@@ -1,33 +1,53 @@
 #![feature(rustc_private)]
 #![warn(unused_extern_crates)]
 
+extern crate rustc_data_structures;
 extern crate rustc_hir;
 extern crate rustc_middle;
 extern crate rustc_span;
 
-use anchor_syn::{AccountField, AccountsStruct, ConstraintGroup};
-use clippy_utils::{
-    diagnostics::span_lint, match_any_def_paths, match_def_path, ty::match_type, SpanlessEq,
-};
+use anchor_syn::AccountsStruct;
+use clippy_utils::{fn_def_id, path_to_local, SpanlessEq};
 use if_chain::if_chain;
+use rustc_data_structures::fx::FxIndexMap;
 use rustc_hir::{
     def_id::{DefId, LocalDefId},
-    intravisit::{walk_expr, FnKind, Visitor},
-    BinOpKind, Body, Expr, ExprKind, FnDecl, Item, QPath,
+    intravisit::{walk_expr, walk_local, FnKind, Visitor},
+    Body, Expr, ExprKind, FieldDef, FnDecl, HirId, Item, Local, MatchSource, Node, PatKind, UnOp,
 };
 use rustc_lint::{LateContext, LateLintPass};
 use rustc_middle::ty;
 use rustc_span::Span;
-use solana_lints::{paths, utils::get_anchor_accounts_struct, utils::visit_expr_no_bodies};
-use std::collections::HashMap;
+use solana_lints::{
+    diagnostics::{lint_level, span_lint_hir, span_lint_hir_and_then},
+    expansion::{call_site, is_skipped_expansion},
+    paths::{self, anchor_wrapper, AnchorWrapper},
+    sarif,
+    suggestions::{expected_owner, AccountCheck, CheckSuggestion},
+    summaries::{self, Summaries},
+    utils::{
+        account_info_loops, anchor_constraints::field_constraint_summary, first_use_of_local,
+        get_anchor_accounts_struct, is_anchor_program, is_expr_method_call, local_field_def,
+        owners::is_owner_checked, remaining_account_bindings, uses_remaining_accounts,
+        visit_expr_no_bodies,
+    },
+};
+use std::{collections::HashMap, iter::once};
 
-dylint_linting::impl_late_lint! {
+solana_lints::impl_late_lint! {
     /// **What it does:**
     ///
     /// This lint checks that for each account referenced in a program, that there is a
     /// corresponding owner check on that account. Specifically, this means that the owner
     /// field is referenced on that account.
     ///
+    /// The accounts of `ctx.remaining_accounts` are reported with a dedicated message: Anchor does
+    /// not apply any constraint to them, so each of them must be checked explicitly, whether it is
+    /// accessed by indexing, by iterating, or with a method such as `get`.
+    ///
+    /// In a non Anchor program, each finding suggests the check of the owner to write, and the
+    /// Anchor constraint making the same check (see `solana_lints::suggestions`).
+    ///
     /// **Why is this bad?**
     ///
     /// The missing-owner-check vulnerability occurs when a program uses an account, but does
@@ -46,11 +66,36 @@ dylint_linting::impl_late_lint! {
     /// - [x] Anchor
     /// - [x] Non Anchor
     ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
     /// **Known problems:**
     ///
     /// Key checks can be strengthened. Currently, the lint only checks that the account's owner
     /// field is referenced somewhere, ie, `AccountInfo.owner`.
     ///
+    /// Each function is analyzed on its own. A function parameter of type `AccountInfo` is reported if
+    /// the function does not check its owner, even if every caller passes an account whose owner is
+    /// checked.
+    ///
+    /// Passing an account to a function of another crate which checks its owner, e.g., a validation
+    /// helper of a `utils` crate of the workspace, counts as a check only if the lint checked that
+    /// crate first and wrote its summary (see `solana_lints::summaries`), e.g., with
+    /// `cargo dylint --workspace`. The calls to the functions of the same crate are not followed.
+    ///
+    /// **Configuration:**
+    ///
+    /// The code generated by the function-like macros of the crate, e.g., instructions generated by
+    /// a `macro_rules!` macro, is analyzed if set in `dylint.toml`:
+    ///
+    /// ```toml
+    /// [missing_owner_check]
+    /// analyze_local_macro_expansions = true
+    /// ```
+    ///
+    /// The findings in the generated code are reported at the macro calls.
+    ///
     /// **Example:**
     ///
     /// See https://github.com/coral-xyz/sealevel-attacks/blob/master/programs/2-owner-checks/insecure/src/lib.rs
@@ -66,15 +111,18 @@ dylint_linting::impl_late_lint! {
     /// check_fn:
     ///
     /// - for every function defined in the package
-    /// - exclude functions generated from macro expansion.
+    /// - exclude functions generated from macro expansion, unless they are generated by local macros and
+    ///   `analyze_local_macro_expansions` is set (see `solana_lints::expansion`).
     /// - Get a list of unique and unsafe AccountInfo's referenced in the body
     ///   - for each expression in the function body
     ///   - Ignore `.clone()` expressions as the expression referencing original account will be checked
     ///   - Check if the expression's type is Solana's `AccountInfo` (`solana_program::account_info::AccountInfo`)
-    ///   - Ignore local variable expressions (`x` where x is defined in the function `let x = y`)
+    ///   - Ignore local variable expressions whose source is analyzed by the lint (`x` where x is defined in the
+    ///     function `let x = y`, or is the binding of a `for` loop)
     ///     - Removes duplcate warnings: both `x` and `y` are reported by the lint. reporting `y` is sufficient.
     ///     - Also the owner could be checked on `y`. reporting `x` which a copy/ref of `y` would be false-positive.
-    ///     - Determined using the expression kind (`.kind`): expr.kind = ExprKind::Path(QPath::Resolved(None, path)); path.segments.len() == 1
+    ///     - The other local variables, e.g., the function parameters of type `AccountInfo`, are not ignored:
+    ///       the lint does not see where their value comes from, so their uses are analyzed as accounts.
     ///   - Ignore safe `.to_account_info()` expressions
     ///     - `.to_account_info()` method can be called to convert different Anchor account types to `AccountInfo`
     ///     - The Anchor account types such as `Account` implement `Owner` trait: The owner of the account is checked during deserialization
@@ -86,19 +134,50 @@ dylint_linting::impl_late_lint! {
     ///       - `AccountLoader` requires its type argument to implement `anchor_lang::Owner`.
     ///       - `Signer` are mostly accounts with a private key and most of the times owned by System Program.
     ///       - `Sysvar` type arguments checks the account key.
+    ///       - `InterfaceAccount` requires its type argument to implement `anchor_lang::Owners`, and checks that
+    ///         the account is owned by one of them, e.g., the Token or the Token-2022 program.
+    ///       - `Interface`'s implementation of `try_from` checks that the account's program id is one of the ids
+    ///         of its type argument, e.g., `TokenInterface`.
     ///   - Ignore `x.to_account_info()` expressions called on Anchor `AccountInfo` to remove duplicates.
     ///     - the lint checks the original expression `x`; no need for checking both.
+    /// - If the function is public, record the indexes of its `AccountInfo` parameters whose `owner` is accessed
+    ///   or whose `key` is compared, as below, in the summary of the crate.
     /// - For each of the collected expressions, check if `owner` is accessed or if the `key` is compared
+    ///   - The checks on the aliases of the account are considered as well. An alias is a local variable
+    ///     initialized with the account, e.g., `let x = expr;`, `let x = &expr;` or `let x = expr.clone();`,
+    ///     or with another alias. The aliases of an expression are those of the account it refers to, so
+    ///     that the account may be checked through an alias and used through another, or the other way
+    ///     around.
     ///   - Ignore the `account_expr` if any of the expressions in the function is `{account_expr}.owner`
     ///   - Ignore the `account_expr` if `key` is compared
     ///     - if there is a comparison expression (`==` or `!=`) and one of the expressions being compared accesses key on `account_expr`:
     ///       - lhs or rhs of the comparison is `{account_expr}.key()`; The key for Anchor's `AccountInfo` is accessed using `.key()`
     ///       - Or lhs or rhs is `{account_expr}.key`; The key of Solana `AccountInfo` are accessed using `.key`
+    ///       - Or lhs or rhs is a local variable initialized with the key, directly or through other locals,
+    ///         e.g., `let key = {account_expr}.key(); require_keys_eq!(key, expected_pda)` where
+    ///         `expected_pda` is derived with `Pubkey::find_program_address`
+    ///     - Or if `account_expr` is compared directly with another `AccountInfo`; such comparisons are reported
+    ///       by the `account_info_comparison` lint.
+    ///   - Ignore the `account_expr` if it, or an alias, is passed to a function of another crate whose summary
+    ///     records that the function checks this parameter.
     ///   - Else
     ///     - If the expression is `.to_account_info()` and the receiver is a field access on a struct: `x.y.to_account_info()`
     ///     - Or If the expression is a field access on a struct `x.y`
     ///       - Then store the struct(x) def id and the accessed field name (y) in `MissingOwnerCheck.account_exprs`.
-    ///     - Else report the expression.
+    ///     - Else if the expression accesses `remaining_accounts`, e.g., `ctx.remaining_accounts[0]`, report the
+    ///       expression as an account of `remaining_accounts`.
+    ///     - Else report the expression. In a non Anchor program, suggest comparing its owner with the
+    ///       ID of the program whose state the account is unpacked into, if any, and else with `crate::ID`.
+    /// - For each `for` loop over `AccountInfo`s, e.g., `for account in ctx.remaining_accounts.iter()`
+    ///   - Each iteration accesses a different account. The loop binding (`account`) is treated as the account.
+    ///   - If the binding is used in the loop body and its `owner` is not accessed nor its `key` compared in the
+    ///     loop body, directly or through an alias, report the binding, as an account of `remaining_accounts` if
+    ///     the loop iterates over them.
+    /// - For each local variable bound to a reference to an account of `remaining_accounts` by a function or
+    ///   a method, e.g., `let account = ctx.remaining_accounts.get(0).unwrap();` or
+    ///   `if let Some(account) = ctx.remaining_accounts.first()`
+    ///   - If the variable is used and its `owner` is not accessed nor its `key` compared in the function,
+    ///     directly or through an alias, report the variable as an account of `remaining_accounts`.
     ///
     /// check_item: Collect Anchor `Accounts` structs
     ///
@@ -108,8 +187,11 @@ dylint_linting::impl_late_lint! {
     ///     - If parsing succeeds
     ///       - Then store the struct def id and the resultant AccountsStruct in `MissingOwnerCheck.anchor_accounts`
     ///
+    /// check_crate: read the summaries of the crates the crate depends on, written when the lint checked them
+    ///
     /// check_crate_post:
     ///
+    /// - write the summary of the crate, with the recorded parameters of its public functions
     /// - for each account expression in `MissingOwnerCheck.account_exprs`
     ///   - If the struct accessed in the expression is in `MissingOwnerCheck.anchor_accounts`
     ///     - find the `#[account(...)]` constraints applied on the accessed field
@@ -122,7 +204,14 @@ dylint_linting::impl_late_lint! {
     ///         - `#[account(address = ...)]` - Validates the key of the account.
     ///         - `#[account(owner = ...)]` - Checks the owner.
     ///         - `#[account(executable)]` - The account is an executable; All executables are owned by `BPFLoaders`.
-    ///       - Else report the expression.
+    ///         - `#[account(has_one = x)]` on another field - Anchor compares the key of `x` with the field `x` of the
+    ///           other account.
+    ///         - `#[account(constraint = ...)]` on any field, comparing the key or the owner of the account with `==`,
+    ///           e.g., `constraint = vault.authority == authority.key()` or `constraint = data.owner == &crate::ID`.
+    ///       - Else group the expression with the other expressions accessing the same field, unless the
+    ///         lint level of the expression differs from the one of the field, e.g., because of an
+    ///         `#[allow(missing_owner_check)]` on the statement. In that case, report the expression.
+    /// - for each field with grouped expressions, report the field once and note each expression
     pub MISSING_OWNER_CHECK,
     Warn,
     "using an account without checking if its owner is as expected",
@@ -131,7 +220,13 @@ dylint_linting::impl_late_lint! {
 
 struct MissingOwnerCheck {
     pub anchor_accounts: HashMap<DefId, AccountsStruct>,
-    pub account_exprs: Vec<(Span, DefId, String)>,
+    pub account_exprs: Vec<(HirId, DefId, String)>,
+    /// The summaries of the crates the crate depends on (see `solana_lints::summaries`)
+    pub summaries: Summaries,
+    /// The public functions of the crate, each with the indexes of its `AccountInfo` parameters
+    /// whose owner or key it checks, written to the summary of the crate
+    pub checked_params: Vec<(DefId, Vec<usize>)>,
+    pub is_anchor: bool,
 }
 
 impl MissingOwnerCheck {
@@ -139,11 +234,19 @@ impl MissingOwnerCheck {
         Self {
             anchor_accounts: HashMap::new(),
             account_exprs: Vec::new(),
+            summaries: Summaries::default(),
+            checked_params: Vec::new(),
+            is_anchor: false,
         }
     }
 }
 
 impl<'tcx> LateLintPass<'tcx> for MissingOwnerCheck {
+    fn check_crate(&mut self, cx: &LateContext<'tcx>) {
+        self.summaries = Summaries::read(cx, MISSING_OWNER_CHECK);
+        self.is_anchor = is_anchor_program(cx);
+    }
+
     fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
         if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
             // item is an anchor accounts struct
@@ -155,32 +258,71 @@ impl<'tcx> LateLintPass<'tcx> for MissingOwnerCheck {
     fn check_fn(
         &mut self,
         cx: &LateContext<'tcx>,
-        _: FnKind<'tcx>,
+        fn_kind: FnKind<'tcx>,
         _: &'tcx FnDecl<'tcx>,
         body: &'tcx Body<'tcx>,
         span: Span,
-        _: LocalDefId,
+        local_def_id: LocalDefId,
     ) {
         // exclude functions generated from macro expansions
-        if !span.from_expansion() {
+        if !is_skipped_expansion(MISSING_OWNER_CHECK, span) {
             // get unique and unsafe AccountInfo's referenced in the body
             let accounts = get_referenced_accounts(cx, body);
+            let aliases = Aliases::new(cx, body, &self.summaries);
+            // The crates depending on this one see the owner checks of its public functions
+            // through its summary.
+            if_chain! {
+                if !matches!(fn_kind, FnKind::Closure);
+                if cx.tcx.visibility(local_def_id).is_public();
+                let params = checked_params(cx, body, &aliases);
+                if !params.is_empty();
+                then {
+                    self.checked_params.push((local_def_id.to_def_id(), params));
+                }
+            }
             for account_expr in accounts {
                 // ignore the account_expr if `.owner` field is accessed in the function
-                // or key of account_expr is compared using `==` or `!=` in the function
-                if !contains_owner_use(cx, body, account_expr)
-                    && !contains_key_check(cx, body, account_expr)
-                {
+                // or key of account_expr is compared using `==` or `!=` in the function.
+                // The checks may be applied to the local variables aliasing the account.
+                if !aliases.is_owner_checked(body.value, account_expr) {
                     if let Some((def_id, field_name)) = accesses_anchor_account(cx, account_expr) {
                         self.account_exprs
-                            .push((account_expr.span, def_id, field_name));
+                            .push((account_expr.hir_id, def_id, field_name));
+                    } else if uses_remaining_accounts(cx, account_expr) {
+                        report_remaining_account(cx, account_expr.hir_id, account_expr.span);
                     } else {
-                        span_lint(
-                            cx,
-                            MISSING_OWNER_CHECK,
-                            account_expr.span,
-                            "this Account struct is used but there is no check on its owner field",
-                        );
+                        let suggestion = (!self.is_anchor)
+                            .then(|| owner_check_suggestion(cx, body.value, account_expr));
+                        report_account(cx, account_expr.hir_id, account_expr.span, suggestion);
+                    }
+                }
+            }
+            // Each iteration of a loop over `AccountInfo`s, e.g., `ctx.remaining_accounts`, accesses a
+            // different account. The checks must be applied to the loop binding within the loop body.
+            for (hir_id, iter, loop_body) in account_info_loops(cx, body.value) {
+                if_chain! {
+                    if let Some(account_use) = first_use_of_local(loop_body, hir_id);
+                    if !aliases.is_owner_checked(loop_body, account_use);
+                    then {
+                        if uses_remaining_accounts(cx, iter) {
+                            report_remaining_account(cx, hir_id, cx.tcx.hir().span(hir_id));
+                        } else {
+                            let suggestion = (!self.is_anchor).then(|| {
+                                owner_check_suggestion(cx, loop_body, account_use)
+                            });
+                            report_account(cx, hir_id, cx.tcx.hir().span(hir_id), suggestion);
+                        }
+                    }
+                }
+            }
+            // The accounts taken from `remaining_accounts` by a function or a method, e.g.,
+            // `ctx.remaining_accounts.get(0)`, are references which the visitor above does not collect.
+            for hir_id in remaining_account_bindings(cx, body.value) {
+                if_chain! {
+                    if let Some(account_use) = first_use_of_local(body.value, hir_id);
+                    if !aliases.is_owner_checked(body.value, account_use);
+                    then {
+                        report_remaining_account(cx, hir_id, cx.tcx.hir().span(hir_id));
                     }
                 }
             }
@@ -188,34 +330,117 @@ impl<'tcx> LateLintPass<'tcx> for MissingOwnerCheck {
     }
 
     fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
-        for (span, def_id, field_name) in &self.account_exprs {
+        // The unchecked uses of each field of an Anchor `Accounts` struct, in the order they are
+        // found. A field is fixed once, with a constraint, so its uses are reported together.
+        let mut field_uses: FxIndexMap<(DefId, &str), (&FieldDef<'_>, Vec<HirId>)> =
+            FxIndexMap::default();
+        for (hir_id, def_id, field_name) in &self.account_exprs {
             if let Some(accounts_struct) = self.anchor_accounts.get(def_id) {
-                if let Some((_, constraints)) = accounts_struct
-                    .fields
-                    .iter()
-                    .map(|account_field| match account_field {
-                        AccountField::Field(field) => (field.ident.to_string(), &field.constraints),
-                        AccountField::CompositeField(field) => {
-                            (field.ident.to_string(), &field.constraints)
-                        }
-                    })
-                    .find(|(anchor_field_name, _)| anchor_field_name == field_name)
+                if field_constraint_summary(accounts_struct, field_name)
+                    .map_or(false, |constraints| constraints.exempts_owner_check())
                 {
-                    if is_safe_constraint_for_owner(constraints) {
+                    continue;
+                }
+                // A use with its own lint level, e.g., `#[allow(missing_owner_check)]` on the
+                // statement, is reported on its own so that the level applies.
+                if_chain! {
+                    if let Some(field_def) = local_field_def(cx, *def_id, field_name);
+                    if lint_level(cx, MISSING_OWNER_CHECK, *hir_id)
+                        == lint_level(cx, MISSING_OWNER_CHECK, field_def.hir_id);
+                    then {
+                        field_uses
+                            .entry((*def_id, field_name))
+                            .or_insert_with(|| (field_def, Vec::new()))
+                            .1
+                            .push(*hir_id);
                         continue;
                     }
                 }
             }
-            span_lint(
+            span_lint_hir(
                 cx,
                 MISSING_OWNER_CHECK,
-                *span,
+                *hir_id,
+                cx.tcx.hir().span(*hir_id),
                 "this Account struct is used but there is no check on its owner field",
             );
         }
+
+        for (field_def, uses) in field_uses.into_values() {
+            span_lint_hir_and_then(
+                cx,
+                MISSING_OWNER_CHECK,
+                field_def.hir_id,
+                field_def.span,
+                "this Account struct is used but there is no check on its owner field",
+                |diag| {
+                    for hir_id in uses {
+                        diag.span_note(
+                            call_site(cx.tcx.hir().span(hir_id)),
+                            "the account is used here",
+                        );
+                    }
+                    diag.help(
+                        "add an `owner` constraint to the field, or use a type which checks the owner, e.g., `Account<'info, T>`",
+                    );
+                },
+            );
+        }
+
+        summaries::write(cx, MISSING_OWNER_CHECK, &self.checked_params);
+        sarif::write(cx, &[MISSING_OWNER_CHECK]);
     }
 }
 
+/// Report an account used without a check of its owner. The findings of a non Anchor program come
+/// with the check to write, and the equivalent Anchor constraint (see `solana_lints::suggestions`).
+fn report_account(
+    cx: &LateContext<'_>,
+    hir_id: HirId,
+    span: Span,
+    suggestion: Option<CheckSuggestion>,
+) {
+    span_lint_hir_and_then(
+        cx,
+        MISSING_OWNER_CHECK,
+        hir_id,
+        span,
+        "this Account struct is used but there is no check on its owner field",
+        |diag| {
+            if let Some(suggestion) = suggestion {
+                suggestion.add_to(diag);
+            }
+        },
+    );
+}
+
+/// Return the suggested check of the owner of `account`, used in `body`
+fn owner_check_suggestion<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx Expr<'tcx>,
+    account: &Expr<'_>,
+) -> CheckSuggestion {
+    let owner = expected_owner(cx, body, account);
+    CheckSuggestion::for_expr(cx, account, AccountCheck::Owner(owner))
+}
+
+/// Report an account of `remaining_accounts`. Anchor does not validate these accounts, so the
+/// finding cannot be fixed with a constraint.
+fn report_remaining_account(cx: &LateContext<'_>, hir_id: HirId, span: Span) {
+    span_lint_hir_and_then(
+        cx,
+        MISSING_OWNER_CHECK,
+        hir_id,
+        span,
+        "this account of `remaining_accounts` is used but there is no check on its owner or key",
+        |diag| {
+            diag.help(
+                "Anchor does not validate the accounts of `remaining_accounts`; check the `owner` or the `key` of each account explicitly",
+            );
+        },
+    );
+}
+
 struct AccountUses<'cx, 'tcx> {
     cx: &'cx LateContext<'tcx>,
     uses: Vec<&'tcx Expr<'tcx>>,
@@ -243,9 +468,9 @@ impl<'cx, 'tcx> Visitor<'tcx> for AccountUses<'cx, 'tcx> {
             if is_expr_method_call(self.cx, expr, &paths::CORE_CLONE).is_none();
             // type of the expression must be Solana's AccountInfo.
             let ty = self.cx.typeck_results().expr_ty(expr);
-            if match_type(self.cx, ty, &paths::SOLANA_PROGRAM_ACCOUNT_INFO);
+            if paths::is_account_info_ty(self.cx, ty);
             // ignore expressions which are local variables
-            if !is_expr_local_variable(expr);
+            if !is_expr_local_variable(self.cx, expr);
             // `to_account_info()` returns AccountInfo. look for expressions calling `to_account_info` and ignore safe expressions
             // expression is safe if `to_account_info` is called on Anchor "Owner" types such as Account, which check the owner during deserialization
             if !is_safe_to_account_info(self.cx, expr);
@@ -266,20 +491,33 @@ impl<'cx, 'tcx> Visitor<'tcx> for AccountUses<'cx, 'tcx> {
 // This removes the false positives of following pattern:
 // `let x = {Account, Program, ...verified structs}.to_account_info()`,
 // the lint reports uses of `x`. Having this check would remove such false positives.
-fn is_expr_local_variable<'tcx>(expr: &'tcx Expr<'tcx>) -> bool {
-    if_chain! {
-        // The expressions accessing simple local variables have expr.kind = ExprKind::Path(QPath::Resolved(None, path))
-        // where path only has one segment.
-        // Note: The check could be improved by including more checks on path/expr or following a different approach which uses Res.
-        // matches!(tcx.hir().qpath_res(qpath, expr.hir_id), Res::Local(_))
-        if let ExprKind::Path(QPath::Resolved(None, path)) = expr.kind;
-        if path.segments.len() == 1;
-        then {
-            true
-        } else {
-            false
+// Only the local variables whose source is analyzed are ignored. The uses of the other local variables, e.g.,
+// function parameters, are the only expressions through which the lint sees the account.
+fn is_expr_local_variable<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> bool {
+    path_to_local(expr).map_or(false, |hir_id| is_source_analyzed(cx, hir_id))
+}
+
+/// Return true if the value of the local variable `hir_id` comes from an expression analyzed by the lint:
+/// - `let x = init;`: `init` is an `AccountInfo` expression, analyzed as any other
+/// - `for x in accounts`: the loops over `AccountInfo`s are analyzed separately, see `account_info_loops`
+///
+/// The function parameters, the bindings of `match` arms and the `let` statements without an initializer
+/// are not analyzed.
+fn is_source_analyzed(cx: &LateContext<'_>, hir_id: HirId) -> bool {
+    for (_, node) in cx.tcx.hir().parent_iter(hir_id) {
+        match node {
+            Node::Pat(_) | Node::PatField(_) | Node::Arm(_) => {}
+            Node::Local(local) => return local.init.is_some(),
+            Node::Expr(expr) => {
+                return matches!(
+                    expr.kind,
+                    ExprKind::Match(_, _, MatchSource::ForLoopDesugar)
+                );
+            }
+            _ => return false,
         }
     }
+    false
 }
 
 // smoelius: See: https://github.com/crytic/solana-lints/issues/31
@@ -289,31 +527,12 @@ fn is_safe_to_account_info<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>)
         if let Some(recv) = is_expr_method_call(cx, expr, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO);
         // expr_ty_adjusted removes wrappers such as Box, any other implicit conversions and gives the base type
         if let ty::Ref(_, recv_ty, _) = cx.typeck_results().expr_ty_adjusted(recv).kind();
-        if let ty::Adt(adt_def, _) = recv_ty.kind();
-        // smoelius:
-        // - `Account` requires its type argument to implement `anchor_lang::Owner`.
-        // - `Program`'s implementation of `try_from` checks the account's program id. So there is
-        //   no ambiguity in regard to the account's owner.
-        // - `SystemAccount`'s implementation of `try_from` checks that the account's owner is the
-        //   System Program.
-        // - `AccountLoader` requires its type argument to implement `anchor_lang::Owner`.
-        // - `Signer` are mostly accounts with a private key and most of the times owned by System Program.
-        // - `Sysvar` type arguments checks the account key.
-        if match_any_def_paths(
-            cx,
-            adt_def.did(),
-            &[
-                &paths::ANCHOR_LANG_ACCOUNT,
-                &paths::ANCHOR_LANG_PROGRAM,
-                &paths::ANCHOR_LANG_SYSTEM_ACCOUNT,
-                &paths::ANCHOR_LANG_ACCOUNT_LOADER,
-                &paths::ANCHOR_LANG_SIGNER,
-                &paths::ANCHOR_LANG_SYSVAR,
-                // s3v3ru5: The following line will remove duplicate warnings where lint reports both `x` and `x.to_account_info()` when x is of type Anchor's AccountInfo.
-                &paths::SOLANA_PROGRAM_ACCOUNT_INFO,
-            ],
-        )
-        .is_some();
+        // smoelius: See `AnchorWrapper::checks_owner` for why the owner of these types is
+        // unambiguous.
+        // s3v3ru5: The `AccountInfo` case removes duplicate warnings where lint reports both `x`
+        // and `x.to_account_info()` when x is of type Anchor's AccountInfo.
+        if anchor_wrapper(cx, *recv_ty).map_or(false, AnchorWrapper::checks_owner)
+            || paths::is_account_info_ty(cx, *recv_ty);
         then {
             true
         } else {
@@ -348,140 +567,126 @@ fn accesses_anchor_account<'tcx>(
     }
 }
 
-/// Given an Anchor `ConstraintGroup`, check if the constraints warrant the exemption of the owner check
-/// - if any of the following constraints are applied on the account return true
-///     - Constraints:
-///     - `#[account(signer)]` - Signer accounts are assumed to be EOA accounts and are ignored.
-///         See comment in fn `is_safe_to_account_info`.
-///     - `#[account(init, ...)]` - init creates a new account and sets its owner to current program or the given program.
-///     - `#[account(seeds = ..., ...)]` - Anchor derives a PDA using the seeds. This is essentially a `key` check and we ignore
-///         if the key of the account is validated.
-///     - `#[account(address = ...)]` - Validates the key of the account.
-///     - `#[account(owner = ...)]` - Checks the owner.
-///     - `#[account(executable)]` - The account is an executable; All executables are owned by `BPFLoaders` and these
-///         accounts are considered to be exempt from owner check.
-/// - else return false
-fn is_safe_constraint_for_owner(constraints: &ConstraintGroup) -> bool {
-    constraints.signer.is_some()
-        || constraints
-            .init
-            .as_ref()
-            .map_or(false, |init_constraint| init_constraint.if_needed)
-        || constraints.seeds.is_some()
-        || constraints.address.is_some()
-        || constraints.owner.is_some()
-        || constraints.executable.is_some()
-}
-
-/// Check if any of the expressions in the body is `{account_expr}.owner`
-fn contains_owner_use<'tcx>(
+/// Return the indexes of the parameters of `body` of type `AccountInfo`, or a reference to it,
+/// whose owner is accessed or whose key is compared in the function
+fn checked_params<'tcx>(
     cx: &LateContext<'tcx>,
     body: &'tcx Body<'tcx>,
-    account_expr: &Expr<'tcx>,
-) -> bool {
-    visit_expr_no_bodies(body.value, |expr| {
-        uses_given_field(cx, expr, account_expr, "owner")
-    })
+    aliases: &Aliases<'_, 'tcx>,
+) -> Vec<usize> {
+    body.params
+        .iter()
+        .enumerate()
+        .filter_map(|(index, param)| {
+            if_chain! {
+                if let PatKind::Binding(_, hir_id, _, None) = param.pat.kind;
+                let ty = cx.typeck_results().node_type(param.hir_id).peel_refs();
+                if paths::is_account_info_ty(cx, ty);
+                if let Some(param_use) = first_use_of_local(body.value, hir_id);
+                if aliases.is_owner_checked(body.value, param_use);
+                then {
+                    Some(index)
+                } else {
+                    None
+                }
+            }
+        })
+        .collect()
 }
 
-/// Check if the key of account returned by `account_expr` is compared
-fn contains_key_check<'tcx>(
-    cx: &LateContext<'tcx>,
-    body: &'tcx Body<'tcx>,
-    account_expr: &Expr<'tcx>,
-) -> bool {
-    visit_expr_no_bodies(body.value, |expr| compares_key(cx, expr, account_expr))
+/// The local variables of a function aliasing accounts, e.g., `x` in `let x = &ctx.accounts.foo;`,
+/// each with the expression of the account it aliases, e.g., `ctx.accounts.foo`. The aliases are
+/// tracked through their `HirId`s, i.e., the `Res::Local`s of the paths using them.
+struct Aliases<'cx, 'tcx> {
+    cx: &'cx LateContext<'tcx>,
+    summaries: &'cx Summaries,
+    bindings: Vec<(HirId, &'tcx Expr<'tcx>)>,
 }
 
-/// Check if expr is a comparison expression and one of expressions being compared accesses key on `account_expr`
-fn compares_key<'tcx>(
-    cx: &LateContext<'tcx>,
-    expr: &Expr<'tcx>,
-    account_expr: &Expr<'tcx>,
-) -> bool {
-    if_chain! {
-        // check if the expr is comparison expression
-        if let ExprKind::Binary(op, lhs, rhs) = expr.kind;
-        // == or !=
-        if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne);
-        // check if lhs or rhs accesses key of `account_expr`
-        if expr_accesses_key(cx, lhs, account_expr) || expr_accesses_key(cx, rhs, account_expr);
-        then {
-            true
-        } else {
-            false
-        }
+impl<'cx, 'tcx> Aliases<'cx, 'tcx> {
+    fn new(cx: &'cx LateContext<'tcx>, body: &'tcx Body<'tcx>, summaries: &'cx Summaries) -> Self {
+        let mut aliases = Self {
+            cx,
+            summaries,
+            bindings: Vec::new(),
+        };
+        aliases.visit_expr(body.value);
+        aliases
     }
-}
 
-// Return true if the expr access key of account_expr(AccountInfo)
-fn expr_accesses_key<'tcx>(
-    cx: &LateContext<'tcx>,
-    expr: &Expr<'tcx>,
-    account_expr: &Expr<'tcx>,
-) -> bool {
-    // Anchor AccountInfo: `.key()` and Solana AccountInfo: `.key` field.
-    calls_method_on_expr(cx, expr, account_expr, &paths::ANCHOR_LANG_KEY)
-        || uses_given_field(cx, expr, account_expr, "key")
-}
-
-/// Checks if `expr` is a method call of `path` on `account_expr`
-fn calls_method_on_expr<'tcx>(
-    cx: &LateContext<'tcx>,
-    expr: &Expr<'tcx>,
-    account_expr: &Expr<'tcx>,
-    def_path: &[&str],
-) -> bool {
-    if_chain! {
-        // check if expr is a method call
-        if let Some(recv) = is_expr_method_call(cx, expr, def_path);
-        // check if recv is same expression as account_expr
-        let mut spanless_eq = SpanlessEq::new(cx);
-        if spanless_eq.eq_expr(account_expr, recv);
-        then {
-            true
-        } else {
-            false
+    /// Return the expression of the account `expr` refers to, following borrows, derefs, `clone()`
+    /// and `to_account_info()` calls, and the aliases, e.g., `ctx.accounts.foo` for `y.clone()`
+    /// where `let x = &ctx.accounts.foo; let y = x;`
+    fn account_of(&self, mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+        loop {
+            if let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = expr.kind {
+                expr = inner;
+            } else if let Some(receiver) = is_expr_method_call(self.cx, expr, &paths::CORE_CLONE)
+                .or_else(|| is_expr_method_call(self.cx, expr, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO))
+            {
+                expr = receiver;
+            } else if let Some((_, account)) = path_to_local(expr)
+                .and_then(|local| self.bindings.iter().find(|(hir_id, _)| *hir_id == local))
+            {
+                // the account of an alias is resolved when the alias is bound
+                return account;
+            } else {
+                return expr;
+            }
         }
     }
-}
 
-/// Checks if `expr` is references `field` on `account_expr`
-fn uses_given_field<'tcx>(
-    cx: &LateContext<'tcx>,
-    expr: &Expr<'tcx>,
-    account_expr: &Expr<'tcx>,
-    field: &str,
-) -> bool {
-    if_chain! {
-        if let ExprKind::Field(object, field_name) = expr.kind;
-        // TODO: add check for key, is_signer
-        if field_name.as_str() == field;
-        let mut spanless_eq = SpanlessEq::new(cx);
-        if spanless_eq.eq_expr(account_expr, object);
-        then {
-            true
-        } else {
-            false
-        }
+    /// Check if `owner` is accessed or the key is compared in `scope` on `account_expr`, on the
+    /// account it refers to, or on any alias of this account, or if one of them is passed to a
+    /// function of another crate checking it
+    fn is_owner_checked(&self, scope: &'tcx Expr<'tcx>, account_expr: &'tcx Expr<'tcx>) -> bool {
+        let account = self.account_of(account_expr);
+        let mut spanless_eq = SpanlessEq::new(self.cx);
+        is_owner_checked(self.cx, scope, account_expr)
+            || is_owner_checked(self.cx, scope, account)
+            || self
+                .bindings
+                .iter()
+                .filter(|(_, aliased)| spanless_eq.eq_expr(aliased, account))
+                .filter_map(|(hir_id, _)| first_use_of_local(scope, *hir_id))
+                .any(|alias_use| is_owner_checked(self.cx, scope, alias_use))
+            || self.is_passed_to_checking_fn(scope, account)
+    }
+
+    /// Check if `account`, directly or through an alias, is passed in `scope` to a function of
+    /// another crate whose summary records that it checks the owner or the key of the parameter,
+    /// e.g., `utils::assert_token_owner(&ctx.accounts.token)?` where `utils` is another crate of
+    /// the workspace
+    fn is_passed_to_checking_fn(&self, scope: &'tcx Expr<'tcx>, account: &'tcx Expr<'tcx>) -> bool {
+        let mut spanless_eq = SpanlessEq::new(self.cx);
+        visit_expr_no_bodies(scope, |expr| {
+            let args: Vec<&Expr<'_>> = match expr.kind {
+                ExprKind::Call(_, args) => args.iter().collect(),
+                ExprKind::MethodCall(_, receiver, args, _) => once(receiver).chain(args).collect(),
+                _ => return false,
+            };
+            fn_def_id(self.cx, expr).map_or(false, |def_id| {
+                self.summaries
+                    .validated_params(self.cx, def_id)
+                    .iter()
+                    .filter_map(|&index| args.get(index))
+                    .any(|arg| spanless_eq.eq_expr(self.account_of(arg), account))
+            })
+        })
     }
 }
 
-/// if `expr` is a method call of `def_path` return the receiver else None
-fn is_expr_method_call<'tcx>(
-    cx: &LateContext<'tcx>,
-    expr: &Expr<'tcx>,
-    def_path: &[&str],
-) -> Option<&'tcx Expr<'tcx>> {
-    if_chain! {
-        if let ExprKind::MethodCall(_, recv, _, _) = expr.kind;
-        if let Some(def_id) = cx.typeck_results().type_dependent_def_id(expr.hir_id);
-        if match_def_path(cx, def_id, def_path);
-        then {
-            Some(recv)
-        } else {
-            None
+impl<'cx, 'tcx> Visitor<'tcx> for Aliases<'cx, 'tcx> {
+    fn visit_local(&mut self, local: &'tcx Local<'tcx>) {
+        if_chain! {
+            if let Some(init) = local.init;
+            if let PatKind::Binding(_, hir_id, _, None) = local.pat.kind;
+            then {
+                let account = self.account_of(init);
+                self.bindings.push((hir_id, account));
+            }
         }
+        walk_local(self, local);
     }
 }
 
@@ -519,3 +724,102 @@ fn secure_programn_id() {
 fn secure_anchor_constraints() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-anchor-constraints");
 }
+
+#[test]
+fn secure_has_one() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-has-one");
+}
+
+#[test]
+fn insecure_multiple_uses() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-multiple-uses");
+}
+
+#[test]
+fn insecure_remaining_accounts() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-remaining-accounts");
+}
+
+#[test]
+fn insecure_remaining_accounts_2() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-remaining-accounts-2");
+}
+
+#[test]
+fn insecure_non_anchor() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-non-anchor");
+}
+
+#[test]
+fn insecure_parameter() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-parameter");
+}
+
+#[test]
+fn secure_parameter() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-parameter");
+}
+
+#[test]
+fn secure_key_comparisons() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-key-comparisons");
+}
+
+#[test]
+fn insecure_account_info_comparisons() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-account-info-comparisons");
+}
+
+#[test]
+fn secure_remaining_accounts() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-remaining-accounts");
+}
+
+#[test]
+fn expect() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "expect");
+}
+
+#[test]
+fn secure_pda_derivation() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-pda-derivation");
+}
+
+#[test]
+fn secure_token_2022() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-token-2022");
+}
+
+#[test]
+fn insecure_aliases() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-aliases");
+}
+
+#[test]
+fn secure_aliases() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-aliases");
+}
+
+#[test]
+fn insecure_local_macro() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "insecure-local-macro")
+        .dylint_toml("missing_owner_check.analyze_local_macro_expansions = true")
+        .run();
+}
+
+#[test]
+fn secure_local_macro() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-local-macro");
+}
+
+// The summary of `owner-checks-utils` must be written before `secure-workspace` and
+// `insecure-workspace`, which call its functions, are checked, and in a directory shared by the
+// three runs.
+#[test]
+fn workspace() {
+    let tempdir = tempfile::tempdir().unwrap();
+    std::env::set_var(summaries::SUMMARIES_VAR, tempdir.path());
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "owner-checks-utils");
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-workspace");
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-workspace");
+}
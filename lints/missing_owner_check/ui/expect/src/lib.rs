@@ -0,0 +1,49 @@
+#![feature(lint_reasons)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_expect {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>) -> ProgramResult {
+        // The expectation is fulfilled: the owner of `token` is not checked.
+        #[expect(missing_owner_check)]
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        if ctx.accounts.authority.key != &token.owner {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    // The expectation is unfulfilled: the owner of `token` is checked.
+    #[expect(missing_owner_check)]
+    pub fn log_message_checked(ctx: Context<LogMessage>) -> ProgramResult {
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        if ctx.accounts.token.owner != &spl_token::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if ctx.accounts.authority.key != &token.owner {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    token: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_insecure_account_info_comparisons {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>) -> ProgramResult {
+        let token = ctx.accounts.token.to_account_info();
+        let vault = ctx.accounts.vault.to_account_info();
+        log_balance_of_same_struct(token.clone(), vault.clone())?;
+        log_balance_of_same_lamports(token.clone(), vault.clone())?;
+        log_balance_of_same_data(token, vault)
+    }
+}
+
+// Comparing the `AccountInfo`s themselves, rather than their keys, checks neither account
+fn log_balance_of_same_struct(token: AccountInfo, vault: AccountInfo) -> ProgramResult {
+    if !std::ptr::eq(&token, &vault) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+fn log_balance_of_same_lamports(token: AccountInfo, vault: AccountInfo) -> ProgramResult {
+    if token.lamports != vault.lamports {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+fn log_balance_of_same_data(token: AccountInfo, vault: AccountInfo) -> ProgramResult {
+    if token.data != vault.data {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    #[account(owner = spl_token::ID)]
+    token: AccountInfo<'info>,
+    #[account(owner = spl_token::ID)]
+    vault: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_insecure_aliases {
+    use super::*;
+
+    pub fn log_balances(ctx: Context<LogBalances>) -> ProgramResult {
+        // The aliases of different accounts are not merged: only `source` is checked.
+        let source = &ctx.accounts.source;
+        let destination = &ctx.accounts.destination;
+        if source.owner != &spl_token::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let source_token = SplTokenAccount::unpack(&source.data.borrow())?;
+        let destination_token = SplTokenAccount::unpack(&destination.data.borrow())?;
+        msg!(
+            "Your account balances are: {} and {}",
+            source_token.amount,
+            destination_token.amount
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogBalances<'info> {
+    source: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+// The test analyzes the expansions of the local macros, so the function generated by the macro is
+// analyzed, and its finding noted at the macro call.
+macro_rules! log_instruction {
+    ($name:ident, $field:ident) => {
+        fn $name(ctx: &Context<LogMessage>) -> ProgramResult {
+            let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+            msg!("Your account {} is: {:?}", stringify!($field), token.$field);
+            Ok(())
+        }
+    };
+}
+
+log_instruction!(log_balance, amount);
+
+#[program]
+pub mod owner_checks_insecure_local_macro {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>) -> ProgramResult {
+        log_balance(&ctx)
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    token: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
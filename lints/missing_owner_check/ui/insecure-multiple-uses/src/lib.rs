@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_insecure_multiple_uses {
+    use super::*;
+
+    pub fn log_balance(ctx: Context<LogMessage>) -> ProgramResult {
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    pub fn log_mint(ctx: Context<LogMessage>) -> ProgramResult {
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        msg!("Your account mint is: {}", token.mint);
+        Ok(())
+    }
+
+    pub fn log_owner(ctx: Context<LogMessage>) -> ProgramResult {
+        // The owner of `token` is checked, so this use is not noted.
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        if ctx.accounts.token.owner != &spl_token::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("Your account owner is: {}", token.owner);
+        Ok(())
+    }
+
+    pub fn log_delegate(ctx: Context<LogMessage>) -> ProgramResult {
+        // The use has its own lint level, so it is not noted.
+        #[allow(missing_owner_check)]
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        msg!("Your account delegate is: {:?}", token.delegate);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    token: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,36 @@
+use anchor_lang::solana_program::{
+    account_info::AccountInfo, declare_id, entrypoint, entrypoint::ProgramResult, msg,
+    program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+entrypoint!(process_instruction);
+fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    match instruction_data.first() {
+        Some(0) => log_balance(accounts),
+        Some(1) => log_sizes(accounts),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+pub fn log_balance(accounts: &[AccountInfo]) -> ProgramResult {
+    let token = SplTokenAccount::unpack(&accounts[0].data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+pub fn log_sizes(accounts: &[AccountInfo]) -> ProgramResult {
+    for account in accounts.iter() {
+        msg!("{} bytes", account.data_len());
+    }
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_insecure_parameter {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>) -> ProgramResult {
+        log_balance(ctx.accounts.token.clone())?;
+        log_balance_of_copy(ctx.accounts.token.clone())
+    }
+}
+
+// The lint does not see where `token` comes from, so its uses are analyzed
+fn log_balance(token: AccountInfo) -> ProgramResult {
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+// `account` is a copy of `token`; only `token` is reported
+fn log_balance_of_copy(token: AccountInfo) -> ProgramResult {
+    let account = token;
+    let token = SplTokenAccount::unpack(&account.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    token: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
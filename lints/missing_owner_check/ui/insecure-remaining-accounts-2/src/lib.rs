@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::account_info::next_account_info;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_insecure_remaining_accounts_2 {
+    use super::*;
+
+    pub fn log_first_balance(ctx: Context<LogBalances>) -> ProgramResult {
+        let token = SplTokenAccount::unpack(&ctx.remaining_accounts[0].data.borrow())?;
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    pub fn log_balance_at(ctx: Context<LogBalances>, index: u8) -> ProgramResult {
+        let account = ctx
+            .remaining_accounts
+            .get(index as usize)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let token = SplTokenAccount::unpack(&account.data.borrow())?;
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    pub fn log_last_balance(ctx: Context<LogBalances>) -> ProgramResult {
+        if let Some(account) = ctx.remaining_accounts.last() {
+            let token = SplTokenAccount::unpack(&account.data.borrow())?;
+            msg!("Your account balance is: {}", token.amount);
+        }
+        Ok(())
+    }
+
+    pub fn log_next_balance(ctx: Context<LogBalances>) -> ProgramResult {
+        let account = next_account_info(&mut ctx.remaining_accounts.iter())?;
+        let token = SplTokenAccount::unpack(&account.data.borrow())?;
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogBalances<'info> {
+    authority: Signer<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_insecure_remaining_accounts {
+    use super::*;
+
+    pub fn log_balances(ctx: Context<LogBalances>) -> ProgramResult {
+        for account in ctx.remaining_accounts {
+            let token = SplTokenAccount::unpack(&account.data.borrow())?;
+            msg!("Your account balance is: {}", token.amount);
+        }
+        Ok(())
+    }
+
+    pub fn log_indexed_balances(ctx: Context<LogBalances>) -> ProgramResult {
+        for (i, account) in ctx.remaining_accounts.iter().enumerate() {
+            let token = SplTokenAccount::unpack(&account.data.borrow())?;
+            msg!("The balance of account {} is: {}", i, token.amount);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogBalances<'info> {
+    authority: Signer<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+// `owner_checks_utils::token_balance` does not check the owner of the account it reads
+#[program]
+pub mod owner_checks_insecure_workspace {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>) -> ProgramResult {
+        let amount = owner_checks_utils::token_balance(&ctx.accounts.token)?;
+        msg!("Your account balance is: {}", amount);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    token: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
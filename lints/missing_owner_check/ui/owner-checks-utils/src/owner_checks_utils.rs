@@ -0,0 +1,26 @@
+// The validation helpers of a workspace, in a crate of their own. The lint records in the summary
+// of this crate that `assert_token_owner` checks the owner of its parameter, so that the crates
+// calling it see the check.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+pub fn assert_token_owner(token: &AccountInfo) -> ProgramResult {
+    if token.owner != &spl_token::ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+// The owner of `token` is not checked, so calling this function is not a check
+pub fn token_balance(token: &AccountInfo) -> std::result::Result<u64, ProgramError> {
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    Ok(token.amount)
+}
+
+#[allow(dead_code)]
+fn main() {}
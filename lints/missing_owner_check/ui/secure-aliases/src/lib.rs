@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_secure_aliases {
+    use super::*;
+
+    pub fn check_alias_use_field(ctx: Context<LogMessage>) -> ProgramResult {
+        // The account is used through the field, and checked through an alias.
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        let token_info = &ctx.accounts.token;
+        if token_info.owner != &spl_token::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    pub fn check_field_use_alias(ctx: Context<LogMessage>) -> ProgramResult {
+        let token_info = &ctx.accounts.token;
+        let token = SplTokenAccount::unpack(&token_info.data.borrow())?;
+        if ctx.accounts.token.owner != &spl_token::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    pub fn check_other_alias(ctx: Context<LogMessage>) -> ProgramResult {
+        let token_info = &ctx.accounts.token;
+        let token = SplTokenAccount::unpack(&token_info.data.borrow())?;
+        let checked_info = ctx.accounts.token.clone();
+        if checked_info.owner != &spl_token::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    pub fn check_alias_of_alias(ctx: Context<LogMessage>) -> ProgramResult {
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        let token_info = ctx.accounts.token.clone();
+        let checked_info = &token_info;
+        if checked_info.owner != &spl_token::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    pub fn check_parameter_alias(ctx: Context<LogOwnedMessage>) -> ProgramResult {
+        log_balance(ctx.accounts.token.to_account_info())
+    }
+}
+
+// The parameter is used, and checked through an alias
+fn log_balance(token_info: AccountInfo) -> ProgramResult {
+    let token = SplTokenAccount::unpack(&token_info.data.borrow())?;
+    let checked_info = &token_info;
+    if checked_info.owner != &spl_token::ID {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    token: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LogOwnedMessage<'info> {
+    #[account(owner = spl_token::ID)]
+    token: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
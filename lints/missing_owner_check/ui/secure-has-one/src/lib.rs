@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_secure {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>) -> ProgramResult {
+        msg!("has_one: {}", ctx.accounts.authority.key());
+        msg!("constraint on the key: {}", ctx.accounts.mint.key());
+        msg!("constraint on the owner: {}", ctx.accounts.data.key());
+        msg!(
+            "constraint on the keys: {} {}",
+            ctx.accounts.source.key(),
+            ctx.accounts.destination.key()
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    #[account(has_one = authority, constraint = vault.mint == mint.key())]
+    pub vault: Account<'info, Vault>,
+    pub authority: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    #[account(constraint = data.owner == &crate::ID)]
+    pub data: AccountInfo<'info>,
+    #[account(
+        constraint = source.key() == vault.source && (*destination.key == vault.destination)
+    )]
+    pub source: AccountInfo<'info>,
+    pub destination: AccountInfo<'info>,
+}
+
+#[account]
+pub struct Vault {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_secure_key_comparisons {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>, expected: Pubkey) -> ProgramResult {
+        let token = ctx.accounts.token.to_account_info();
+        let vault = ctx.accounts.vault.to_account_info();
+        log_balance_key_method(token.clone(), expected)?;
+        log_balance_key_field(token.clone(), expected)?;
+        log_balance_deref_key(token.clone(), expected)?;
+        log_balance_of_vault(token.clone(), vault.clone())?;
+        log_balance_of_vault_deref(token, vault)
+    }
+}
+
+fn log_balance_key_method(token: AccountInfo, expected: Pubkey) -> ProgramResult {
+    if token.key() != expected {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+fn log_balance_key_field(token: AccountInfo, expected: Pubkey) -> ProgramResult {
+    if token.key != &expected {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+fn log_balance_deref_key(token: AccountInfo, expected: Pubkey) -> ProgramResult {
+    if *token.key != expected {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+// The key of `token` is compared with the key of another account, which checks both
+fn log_balance_of_vault(token: AccountInfo, vault: AccountInfo) -> ProgramResult {
+    if token.key != vault.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+fn log_balance_of_vault_deref(token: AccountInfo, vault: AccountInfo) -> ProgramResult {
+    if *token.key != vault.key() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    #[account(owner = spl_token::ID)]
+    token: AccountInfo<'info>,
+    #[account(owner = spl_token::ID)]
+    vault: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_secure_parameter {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>) -> ProgramResult {
+        log_balance(ctx.accounts.token.to_account_info())?;
+        log_balance_of_copy(ctx.accounts.token.to_account_info())
+    }
+}
+
+fn log_balance(token: AccountInfo) -> ProgramResult {
+    if token.owner != &spl_token::ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let token = SplTokenAccount::unpack(&token.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+// The owner is checked on the copy of `token`
+fn log_balance_of_copy(token: AccountInfo) -> ProgramResult {
+    let account = token;
+    if account.owner != &spl_token::ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let token = SplTokenAccount::unpack(&account.data.borrow())?;
+    msg!("Your account balance is: {}", token.amount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    #[account(owner = spl_token::ID)]
+    token: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
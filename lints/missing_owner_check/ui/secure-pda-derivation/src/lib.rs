@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_secure {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>) -> ProgramResult {
+        // The key of the token account is compared with the derived address through a local
+        let (expected_token, _bump) = Pubkey::find_program_address(
+            &[b"token", ctx.accounts.authority.key.as_ref()],
+            ctx.program_id,
+        );
+        let token_key = ctx.accounts.token.key();
+        if token_key != expected_token {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    pub fn log_message_2(ctx: Context<LogMessage>) -> ProgramResult {
+        let (expected_token, _bump) = Pubkey::find_program_address(
+            &[b"token", ctx.accounts.authority.key.as_ref()],
+            ctx.program_id,
+        );
+        let token_key = ctx.accounts.token.key;
+        let actual_token = token_key;
+        if expected_token != *actual_token {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    token: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod owner_checks_secure_remaining_accounts {
+    use super::*;
+
+    pub fn log_balances(ctx: Context<LogBalances>) -> ProgramResult {
+        for account in ctx.remaining_accounts {
+            if account.owner != &spl_token::ID {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let token = SplTokenAccount::unpack(&account.data.borrow())?;
+            msg!("Your account balance is: {}", token.amount);
+        }
+        Ok(())
+    }
+
+    pub fn log_indexed_balances(ctx: Context<LogBalances>) -> ProgramResult {
+        for i in 0..ctx.remaining_accounts.len() {
+            let account = &ctx.remaining_accounts[i];
+            if account.owner != &spl_token::ID {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let token = SplTokenAccount::unpack(&account.data.borrow())?;
+            msg!("The balance of account {} is: {}", i, token.amount);
+        }
+        Ok(())
+    }
+
+    pub fn log_first_balance(ctx: Context<LogBalances>) -> ProgramResult {
+        if ctx.remaining_accounts[0].owner != &spl_token::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let token = SplTokenAccount::unpack(&ctx.remaining_accounts[0].data.borrow())?;
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    pub fn log_vault_balance(ctx: Context<LogBalances>, vault: Pubkey) -> ProgramResult {
+        let account = ctx
+            .remaining_accounts
+            .get(0)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if account.key() != vault {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let token = SplTokenAccount::unpack(&account.data.borrow())?;
+        msg!("The vault balance is: {}", token.amount);
+        Ok(())
+    }
+
+    pub fn log_last_balance(ctx: Context<LogBalances>) -> ProgramResult {
+        if let Some(account) = ctx.remaining_accounts.last() {
+            if account.owner != &spl_token::ID {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let token = SplTokenAccount::unpack(&account.data.borrow())?;
+            msg!("Your account balance is: {}", token.amount);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogBalances<'info> {
+    authority: Signer<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::Account as SplTokenAccount;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+// The owner of the token accounts is checked by `owner_checks_utils::assert_token_owner`, in
+// another crate of the workspace
+#[program]
+pub mod owner_checks_secure_workspace {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>) -> ProgramResult {
+        owner_checks_utils::assert_token_owner(&ctx.accounts.token)?;
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+
+    // The owner is checked through an alias of the account
+    pub fn log_message_of_alias(ctx: Context<LogMessage>) -> ProgramResult {
+        let account = &ctx.accounts.token;
+        owner_checks_utils::assert_token_owner(account)?;
+        let token = SplTokenAccount::unpack(&ctx.accounts.token.data.borrow())?;
+        msg!("Your account balance is: {}", token.amount);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogMessage<'info> {
+    token: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,261 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{fn_def_id, match_any_def_paths, match_def_path};
+use if_chain::if_chain;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap};
+use rustc_hir::{
+    def::DefKind,
+    def_id::LocalDefId,
+    intravisit::{walk_expr, FnKind, Visitor},
+    Body, Expr, ExprKind, FnDecl, HirId,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, paths, sarif,
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports accounts which are created without checking that their balance is rent-exempt for
+    /// the allocated size. An account is created by `system_instruction::create_account` or
+    /// `create_account_with_seed`, or funded manually and then allocated by
+    /// `system_instruction::allocate` or `allocate_with_seed`. The balance is checked if the
+    /// function, a function it calls, or a function calling it uses `Rent::minimum_balance` or
+    /// `Rent::is_exempt`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// An account with a balance below the rent-exempt minimum for its size cannot be created:
+    /// the System Program rejects it. If the lamports come from the caller, e.g., from instruction
+    /// data, the caller can make the instruction fail, and an account which the program expects to
+    /// exist, e.g., a user's PDA, can never be initialized.
+    ///
+    /// **Works on:**
+    ///
+    /// - [ ] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The lint does not check that `Rent::minimum_balance` or `Rent::is_exempt` is called with
+    /// the allocated size, nor that its result is the balance of the created account. A check in a
+    /// function which is neither a caller nor a callee of the creating function, e.g., a sibling
+    /// computing the lamports, is not recognized.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// let lamports = u64::from_le_bytes(instruction_data[..8].try_into().unwrap());
+    /// invoke(
+    ///     &system_instruction::create_account(payer.key, vault.key, lamports, 64, program_id),
+    ///     &[payer.clone(), vault.clone()],
+    /// )?;
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// let lamports = Rent::get()?.minimum_balance(64);
+    /// invoke(
+    ///     &system_instruction::create_account(payer.key, vault.key, lamports, 64, program_id),
+    ///     &[payer.clone(), vault.clone()],
+    /// )?;
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - For every function defined in the package, excluding functions generated from macro expansion
+    ///   - Collect the calls to `system_instruction::{create_account, create_account_with_seed, allocate, allocate_with_seed}`
+    ///   - Record whether the function calls `Rent::minimum_balance` or `Rent::is_exempt`
+    ///   - Collect the functions of the package which the function calls
+    ///
+    /// check_crate_post:
+    ///
+    /// - For each function with a collected call
+    ///   - If neither the function, nor a function it calls transitively, nor a function calling it
+    ///     transitively, calls `Rent::minimum_balance` or `Rent::is_exempt`, report the calls
+    pub MISSING_RENT_EXEMPT_CHECK,
+    Warn,
+    "accounts created without checking that their balance is rent-exempt",
+    MissingRentExemptCheck::default()
+}
+
+#[derive(Default)]
+struct MissingRentExemptCheck {
+    /// The functions of the package, in the order they are checked
+    fns: FxIndexMap<LocalDefId, FnInfo>,
+}
+
+#[derive(Default)]
+struct FnInfo {
+    /// The calls creating an account, with the name of the called function
+    creations: Vec<(HirId, Span, &'static str)>,
+    /// Whether the function calls `Rent::minimum_balance` or `Rent::is_exempt`
+    checks_rent: bool,
+    /// The functions of the package called by the function
+    callees: FxHashSet<LocalDefId>,
+}
+
+/// The functions creating accounts, with their names, e.g., `create_account`
+const CREATE_FNS: &[(&[&str], &str)] = &[
+    (&paths::SOLANA_PROGRAM_CREATE_ACCOUNT, "create_account"),
+    (
+        &paths::SOLANA_PROGRAM_CREATE_ACCOUNT_WITH_SEED,
+        "create_account_with_seed",
+    ),
+    (&paths::SOLANA_PROGRAM_ALLOCATE, "allocate"),
+    (
+        &paths::SOLANA_PROGRAM_ALLOCATE_WITH_SEED,
+        "allocate_with_seed",
+    ),
+];
+
+impl<'tcx> LateLintPass<'tcx> for MissingRentExemptCheck {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if !is_skipped_expansion(MISSING_RENT_EXEMPT_CHECK, span) {
+            let mut visitor = FnVisitor {
+                cx,
+                info: FnInfo::default(),
+            };
+            visitor.visit_expr(body.value);
+            self.fns.insert(local_def_id, visitor.info);
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        let mut callees: FxHashMap<LocalDefId, Vec<LocalDefId>> = FxHashMap::default();
+        let mut callers: FxHashMap<LocalDefId, Vec<LocalDefId>> = FxHashMap::default();
+        for (&caller, info) in &self.fns {
+            for &callee in &info.callees {
+                callees.entry(caller).or_default().push(callee);
+                callers.entry(callee).or_default().push(caller);
+            }
+        }
+
+        for (&local_def_id, info) in &self.fns {
+            if info.creations.is_empty()
+                || self.reaches_rent_check(local_def_id, &callees)
+                || self.reaches_rent_check(local_def_id, &callers)
+            {
+                continue;
+            }
+            for &(hir_id, span, name) in &info.creations {
+                span_lint_hir_and_then(
+                    cx,
+                    MISSING_RENT_EXEMPT_CHECK,
+                    hir_id,
+                    span,
+                    &format!("the account created with `{name}` is not checked to be rent-exempt"),
+                    |diag| {
+                        diag.help(
+                            "compute the lamports with `Rent::minimum_balance` for the allocated size, or check them with `Rent::is_exempt`",
+                        );
+                    },
+                );
+            }
+        }
+
+        sarif::write(cx, &[MISSING_RENT_EXEMPT_CHECK]);
+    }
+}
+
+impl MissingRentExemptCheck {
+    /// Return true if the function, or a function reachable from it in `graph`, calls
+    /// `Rent::minimum_balance` or `Rent::is_exempt`
+    fn reaches_rent_check(
+        &self,
+        local_def_id: LocalDefId,
+        graph: &FxHashMap<LocalDefId, Vec<LocalDefId>>,
+    ) -> bool {
+        let mut visited = FxHashSet::default();
+        let mut stack = vec![local_def_id];
+        while let Some(local_def_id) = stack.pop() {
+            if !visited.insert(local_def_id) {
+                continue;
+            }
+            if self
+                .fns
+                .get(&local_def_id)
+                .map_or(false, |info| info.checks_rent)
+            {
+                return true;
+            }
+            stack.extend(graph.get(&local_def_id).into_iter().flatten());
+        }
+        false
+    }
+}
+
+struct FnVisitor<'cx, 'tcx> {
+    cx: &'cx LateContext<'tcx>,
+    info: FnInfo,
+}
+
+impl<'cx, 'tcx> Visitor<'tcx> for FnVisitor<'cx, 'tcx> {
+    fn visit_expr(&mut self, expr: &'tcx Expr<'tcx>) {
+        // A closure is checked as a function of its own, called by the enclosing function.
+        if let ExprKind::Closure(closure) = expr.kind {
+            self.info.callees.insert(closure.def_id);
+        }
+        if_chain! {
+            if matches!(expr.kind, ExprKind::Call(..) | ExprKind::MethodCall(..));
+            if let Some(def_id) = fn_def_id(self.cx, expr);
+            then {
+                if let Some((_, name)) = CREATE_FNS
+                    .iter()
+                    .find(|(path, _)| match_def_path(self.cx, def_id, path))
+                {
+                    self.info.creations.push((expr.hir_id, expr.span, name));
+                } else if match_any_def_paths(
+                    self.cx,
+                    def_id,
+                    &[
+                        &paths::SOLANA_PROGRAM_MINIMUM_BALANCE,
+                        &paths::SOLANA_PROGRAM_IS_EXEMPT,
+                    ],
+                )
+                .is_some()
+                {
+                    self.info.checks_rent = true;
+                } else if let Some(local_def_id) = def_id.as_local() {
+                    if matches!(self.cx.tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn) {
+                        self.info.callees.insert(local_def_id);
+                    }
+                }
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
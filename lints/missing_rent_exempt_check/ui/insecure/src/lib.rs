@@ -0,0 +1,67 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+};
+use std::convert::TryInto;
+
+const VAULT_SIZE: u64 = 64;
+
+entrypoint!(process_instruction);
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (tag, rest) = instruction_data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let lamports = rest
+        .get(..8)
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    match tag {
+        0 => create_vault(program_id, accounts, lamports),
+        _ => create_pda(program_id, accounts, lamports),
+    }
+}
+
+fn create_vault(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+    let vault = next_account_info(account_info_iter)?;
+    invoke(
+        &system_instruction::create_account(payer.key, vault.key, lamports, VAULT_SIZE, program_id),
+        &[payer.clone(), vault.clone()],
+    )
+}
+
+fn create_pda(program_id: &Pubkey, accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?;
+    let pda = next_account_info(account_info_iter)?;
+    let (_, bump) = Pubkey::find_program_address(&[payer.key.as_ref()], program_id);
+    let signer_seeds: &[&[u8]] = &[payer.key.as_ref(), &[bump]];
+    invoke(
+        &system_instruction::transfer(payer.key, pda.key, lamports),
+        &[payer.clone(), pda.clone()],
+    )?;
+    invoke_signed(
+        &system_instruction::allocate(pda.key, VAULT_SIZE),
+        &[pda.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(pda.key, program_id),
+        &[pda.clone()],
+        &[signer_seeds],
+    )
+}
+
+#[allow(dead_code)]
+fn main() {}
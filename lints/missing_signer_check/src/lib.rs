@@ -2,28 +2,43 @@
 #![warn(unused_extern_crates)]
 
 extern crate rustc_hir;
-extern crate rustc_middle;
 extern crate rustc_span;
 
 use anchor_syn::{AccountField, Ty as FieldTy};
-use clippy_utils::{diagnostics::span_lint, diagnostics::span_lint_and_then, ty::match_type};
+use clippy_utils::{fn_def_id, match_any_def_paths, path_to_local};
 use if_chain::if_chain;
 use rustc_hir::{
-    def_id::LocalDefId, intravisit::FnKind, Body, Expr, ExprKind, FnDecl, Item, ItemKind,
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, Expr, ExprKind, FnDecl, Item, ItemKind,
 };
 use rustc_lint::{LateContext, LateLintPass};
-use rustc_middle::ty::{self, GenericArg, GenericArgKind};
 use rustc_span::Span;
+use serde::Deserialize;
 use solana_lints::{
-    paths,
-    utils::{get_anchor_accounts_struct, is_anchor_program, visit_expr_no_bodies},
+    diagnostics::span_lint_hir_and_then,
+    paths, sarif,
+    suggestions::{AccountCheck, CheckSuggestion},
+    utils::{
+        anchor_constraints::ConstraintSummary,
+        get_anchor_accounts_struct, is_anchor_program, is_authority_name,
+        signers::{contains_is_signer_use, context_contains_signer_field},
+        visit_expr_no_bodies,
+    },
 };
 
-dylint_linting::impl_late_lint! {
+solana_lints::impl_late_lint! {
     /// **What it does:**
     ///
     /// This lint reports functions which use `AccountInfo` type and have zero signer checks.
     ///
+    /// In a non Anchor program, only the functions performing a privileged operation, i.e., writing
+    /// the data or the lamports of an account, resizing an account, changing its owner, or making a
+    /// CPI, are reported, unless the lint is configured to be strict (see below). The functions
+    /// which only read accounts, e.g., queries and helpers, need no signer. Each finding suggests the
+    /// check of the signer to write, and the Anchor constraint making the same check (see
+    /// `solana_lints::suggestions`).
+    ///
     /// **Why is this bad?**
     ///
     /// The missing-signer-check vulnerability occurs when a program does not check that all the authorative
@@ -39,8 +54,25 @@ dylint_linting::impl_late_lint! {
     /// - [x] Anchor
     /// - [x] Non Anchor
     ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
     /// **Known problems:**
-    /// None.
+    ///
+    /// In a non Anchor program, the privileged operations are looked for in the body of the
+    /// function only, not in the functions it calls, so a function checking the signer and then
+    /// calling a helper which performs the operation reports the helper.
+    ///
+    /// **Configuration:**
+    ///
+    /// Every function using `AccountInfo` without a signer check is reported, whether it performs
+    /// a privileged operation or not, e.g., for an audit, if set in `dylint.toml`:
+    ///
+    /// ```toml
+    /// [missing_signer_check]
+    /// strict = true
+    /// ```
     ///
     /// **Example:**
     ///
@@ -57,20 +89,41 @@ dylint_linting::impl_late_lint! {
     /// - If the function has an expression of type `AccountInfo` AND
     /// - If the function does **not** take a `Context<T>` type argument where `T` has a `Signer` type field AND
     /// - If the function does **not** has an expression `x.is_signer` where the expression `x` is of type `AccountInfo`.
-    ///   - Report the function
+    ///   - The expressions in the body of a `for` loop and in the bodies of closures are included. The checks on the
+    ///     accounts of `ctx.remaining_accounts` are written as `for account in ctx.remaining_accounts { .. }` or
+    ///     using iterator adapters, e.g., `ctx.remaining_accounts.iter().filter(|account| account.is_signer)`.
+    /// - If the lint is not strict, if the function performs a privileged operation: a call of
+    ///   `try_borrow_mut_data`, `try_borrow_mut_lamports`, `realloc` or `assign` on an
+    ///   `AccountInfo`, a mutable borrow of its `data` or `lamports`, or a call of `invoke` or
+    ///   `invoke_signed`
+    ///   - Report the function, and note the privileged operation
+    ///   - Suggest checking that the authority of the function signed, i.e., the first account of the
+    ///     function with a name such as `authority`, `owner` or `admin`, if any, and else an account
+    ///     named `authority`
     pub MISSING_SIGNER_CHECK,
     Warn,
     "description goes here",
     MissingSignerCheck::new()
 }
 
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Config {
+    /// Report the functions of non Anchor programs which perform no privileged operation
+    strict: bool,
+}
+
 struct MissingSignerCheck {
+    config: Config,
     is_anchor: bool,
 }
 
 impl MissingSignerCheck {
     pub fn new() -> Self {
-        Self { is_anchor: false }
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+            is_anchor: false,
+        }
     }
 }
 
@@ -107,91 +160,131 @@ impl<'tcx> LateLintPass<'tcx> for MissingSignerCheck {
             if !context_contains_signer_field(cx, local_def_id);
             // The function does not have an expression `x.is_signer` where `x` has AccountInfo type.
             if !body_contains_is_signer_use(cx, body);
+            let operation = privileged_operation(cx, body);
+            // Unless the lint is strict, the functions which only read accounts are not reported.
+            if operation.is_some() || self.config.strict;
             then {
-                span_lint(
+                span_lint_hir_and_then(
                     cx,
                     MISSING_SIGNER_CHECK,
+                    cx.tcx.local_def_id_to_hir_id(local_def_id),
                     span,
                     "this function lacks a use of `is_signer`",
+                    |diag| {
+                        if let Some((operation_span, operation)) = operation {
+                            diag.span_note(operation_span, format!("the function {operation} here"));
+                        }
+                        CheckSuggestion::new(authority_name(cx, body), AccountCheck::Signer)
+                            .add_to(diag);
+                    },
                 )
             }
         }
     }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[MISSING_SIGNER_CHECK]);
+    }
 }
 
 /// Return true if any of the expression in body has type `AccountInfo` (`solana_program::account_info::AccountInfo`)
 fn body_uses_account_info<'tcx>(cx: &LateContext<'tcx>, body: &'tcx Body<'tcx>) -> bool {
     visit_expr_no_bodies(body.value, |expr| {
         let ty = cx.typeck_results().expr_ty(expr).peel_refs();
-        match_type(cx, ty, &paths::SOLANA_PROGRAM_ACCOUNT_INFO)
+        paths::is_account_info_ty(cx, ty)
     })
 }
 
-/// Given def id of a function return true if
-/// - function takes a Context<T> type argument and
-/// - T has only one variant (T is a struct) and
-/// - T has a Signer type field
-fn context_contains_signer_field(cx: &LateContext<'_>, local_def_id: LocalDefId) -> bool {
-    let fn_sig = cx
-        .tcx
-        .fn_sig(local_def_id.to_def_id())
-        .skip_binder()
-        .skip_binder();
-    if_chain! {
-        // iterate over the arguments and find Context<> type argument
-        if let Some(ty) = fn_sig
-            .inputs()
-            .iter()
-            .find(|ty| match_type(cx, **ty, &paths::ANCHOR_LANG_CONTEXT));
-        if let ty::Adt(_, substs) = ty.kind();
-        // Context takes T as generic arg. iterate over the type arguments and
-        // check any of them is a type arg and has `Signer` type field.
-        if substs.iter().any(|arg| arg_contains_signer_field(cx, arg));
-        then {
-            true
-        } else {
-            false
-        }
-    }
+/// Return true if any of expressions in `body` are `x.is_signer` where `x`'s type is `AccountInfo`
+fn body_contains_is_signer_use<'tcx>(cx: &LateContext<'tcx>, body: &'tcx Body<'tcx>) -> bool {
+    contains_is_signer_use(cx, body.value)
 }
 
-/// Given a generic type argument, return true if its a struct that contains `Signer` type field.
-fn arg_contains_signer_field<'tcx>(cx: &LateContext<'tcx>, arg: GenericArg<'tcx>) -> bool {
-    if_chain! {
-        // GenericArg is a type argument (not lifetime)
-        if let GenericArgKind::Type(ty) = arg.unpack();
-        if let ty::Adt(adt_def, substs) = ty.kind();
-        if let [variant] = adt_def.variants().iter().collect::<Vec<_>>().as_slice();
-        // iterate over the fields and check if any of the field's type is `Signer`
-        if variant.fields.iter().any(|field_def| {
-            match_type(cx, field_def.ty(cx.tcx, substs), &paths::ANCHOR_LANG_SIGNER)
-        });
-        then {
-            true
-        } else {
-            false
+/// Return the name of the first account of `body` with an authority name (see
+/// `solana_lints::utils::is_authority_name`), e.g., `vault_authority`, and else `authority`
+fn authority_name<'tcx>(cx: &LateContext<'tcx>, body: &'tcx Body<'tcx>) -> String {
+    visit_expr_no_bodies(body.value, |expr| {
+        if_chain! {
+            if let Some(hir_id) = path_to_local(expr);
+            let name = cx.tcx.hir().name(hir_id);
+            if is_authority_name(name.as_str());
+            if paths::is_account_info_ty(cx, cx.typeck_results().expr_ty(expr).peel_refs());
+            then {
+                Some(name.to_string())
+            } else {
+                None
+            }
         }
-    }
+    })
+    .unwrap_or_else(|| String::from("authority"))
 }
 
-/// Return true if any of expressions in `body` are `x.is_signer` where `x`'s type is `AccountInfo`
-fn body_contains_is_signer_use<'tcx>(cx: &LateContext<'tcx>, body: &'tcx Body<'tcx>) -> bool {
-    visit_expr_no_bodies(body.value, |expr| is_is_signer_use(cx, expr))
+/// Return the first privileged operation of `body`, and its description:
+/// - `x.try_borrow_mut_data()` and `x.data.borrow_mut()`: writes the data of `x`
+/// - `x.try_borrow_mut_lamports()` and `x.lamports.borrow_mut()`: moves the lamports of `x`
+/// - `x.realloc(..)` and `x.assign(..)`: resizes `x`, or changes its owner
+/// - `invoke(..)` and `invoke_signed(..)`: makes a CPI
+fn privileged_operation<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx Body<'tcx>,
+) -> Option<(Span, &'static str)> {
+    visit_expr_no_bodies(body.value, |expr| {
+        let (def_id, receiver) = match expr.kind {
+            ExprKind::MethodCall(_, receiver, _, _) => (
+                cx.typeck_results().type_dependent_def_id(expr.hir_id)?,
+                Some(receiver),
+            ),
+            ExprKind::Call(..) => (fn_def_id(cx, expr)?, None),
+            _ => return None,
+        };
+        let operation = match match_any_def_paths(
+            cx,
+            def_id,
+            &[
+                &paths::SOLANA_PROGRAM_TRY_BORROW_MUT_DATA,
+                &paths::SOLANA_PROGRAM_TRY_BORROW_MUT_LAMPORTS,
+                &paths::SOLANA_PROGRAM_REALLOC,
+                &paths::SOLANA_PROGRAM_ASSIGN,
+                &paths::SOLANA_PROGRAM_INVOKE,
+                &paths::SOLANA_PROGRAM_INVOKE_SIGNED,
+            ],
+        ) {
+            Some(0) => "writes the data of an account",
+            Some(1) => "moves lamports",
+            Some(2) => "resizes an account",
+            Some(3) => "changes the owner of an account",
+            Some(_) => "makes a CPI",
+            None => borrowed_mut_account_field(cx, def_id, receiver?)?,
+        };
+        Some((expr.span, operation))
+    })
 }
 
-/// Return true if the `expr` is `x.is_signer` where `x`'s type is `AccountInfo`.
-fn is_is_signer_use<'tcx>(cx: &LateContext<'tcx>, expr: &Expr<'tcx>) -> bool {
+/// If the method `def_id` called on `receiver` mutably borrows the `data` or the `lamports` of an
+/// `AccountInfo`, e.g., `x.lamports.borrow_mut()`, return the description of the operation
+fn borrowed_mut_account_field<'tcx>(
+    cx: &LateContext<'tcx>,
+    def_id: DefId,
+    receiver: &'tcx Expr<'tcx>,
+) -> Option<&'static str> {
     if_chain! {
-        // `expr` is `x.{field_name}`
-        if let ExprKind::Field(object, field_name) = expr.kind;
-        if field_name.as_str() == "is_signer";
-        // type of `x` is AccountInfo
-        let ty = cx.typeck_results().expr_ty(object).peel_refs();
-        if match_type(cx, ty, &paths::SOLANA_PROGRAM_ACCOUNT_INFO);
+        if match_any_def_paths(
+            cx,
+            def_id,
+            &[&paths::CORE_CELL_BORROW_MUT, &paths::CORE_CELL_TRY_BORROW_MUT],
+        )
+        .is_some();
+        if let ExprKind::Field(account, ident) = receiver.kind;
+        let ty = cx.typeck_results().expr_ty_adjusted(account).peel_refs();
+        if paths::is_account_info_ty(cx, ty);
         then {
-            true
+            match ident.as_str() {
+                "data" => Some("writes the data of an account"),
+                "lamports" => Some("moves lamports"),
+                _ => None,
+            }
         } else {
-            false
+            None
         }
     }
 }
@@ -255,7 +348,7 @@ fn anchor_missing_signer<'tcx>(cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
                     if matches!(
                         field.ty,
                         FieldTy::AccountInfo | FieldTy::UncheckedAccount | FieldTy::SystemAccount
-                    ) && !field.constraints.is_signer()
+                    ) && !ConstraintSummary::new(&field.constraints).is_signer
                     {
                         reported_fields.push(item_field);
                     }
@@ -282,9 +375,10 @@ fn anchor_missing_signer<'tcx>(cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
                 )
             };
 
-            span_lint_and_then(
+            span_lint_hir_and_then(
                 cx,
                 MISSING_SIGNER_CHECK,
+                item.hir_id(),
                 reported_fields
                     .iter()
                     .map(|field| field.span)
@@ -315,10 +409,27 @@ fn secure() {
 
 #[test]
 fn insecure_non_anchor() {
-    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-non-anchor");
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "insecure-non-anchor")
+        .dylint_toml("missing_signer_check.strict = true")
+        .run();
+}
+
+#[test]
+fn insecure_non_anchor_privileged() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-non-anchor-privileged");
 }
 
 #[test]
 fn secure_non_anchor() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-non-anchor");
 }
+
+#[test]
+fn secure_remaining_accounts() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-remaining-accounts");
+}
+
+#[test]
+fn renamed() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "renamed");
+}
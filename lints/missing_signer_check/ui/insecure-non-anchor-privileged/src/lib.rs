@@ -0,0 +1,47 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    match instruction_data.first() {
+        Some(0) => withdraw(accounts, 1_000_000),
+        Some(1) => set_admin(accounts, Pubkey::new_unique()),
+        Some(2) => log_balance(accounts),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+pub fn withdraw(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+    let vault = next_account_info(account_iter)?;
+    let recipient = next_account_info(account_iter)?;
+    **vault.try_borrow_mut_lamports()? -= amount;
+    **recipient.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+pub fn set_admin(accounts: &[AccountInfo], admin: Pubkey) -> ProgramResult {
+    let config = next_account_info(&mut accounts.iter())?;
+    config.try_borrow_mut_data()?[..32].copy_from_slice(admin.as_ref());
+    Ok(())
+}
+
+// `log_balance` only reads the account, so that it requires no signer
+pub fn log_balance(accounts: &[AccountInfo]) -> ProgramResult {
+    let vault = next_account_info(&mut accounts.iter())?;
+    msg!("{} lamports", vault.lamports());
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn main() {}
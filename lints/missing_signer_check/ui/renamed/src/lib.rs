@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod signer_authorization_renamed {
+    use super::*;
+
+    pub fn log_message(ctx: Context<LogMessage>) -> ProgramResult {
+        msg!("GM {}", ctx.accounts.authority.key().to_string());
+        Ok(())
+    }
+}
+
+// `signer_authorization` is an old name of `missing_signer_check`, so the finding is allowed, and
+// the old name is reported
+#[derive(Accounts)]
+#[allow(signer_authorization)]
+pub struct LogMessage<'info> {
+    authority: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
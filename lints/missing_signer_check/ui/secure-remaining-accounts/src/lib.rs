@@ -0,0 +1,53 @@
+use solana_program::msg;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+const THRESHOLD: usize = 2;
+
+entrypoint!(process_instruction);
+fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    match instruction_data.first() {
+        Some(0) => approve(accounts),
+        Some(1) => approve_loop(accounts),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+pub fn approve(accounts: &[AccountInfo]) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+    let proposal = next_account_info(account_iter)?;
+    let signers = account_iter.filter(|account| account.is_signer).count();
+    if signers < THRESHOLD {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    msg!("{:?} approved by {} signers", proposal.key, signers);
+    Ok(())
+}
+
+pub fn approve_loop(accounts: &[AccountInfo]) -> ProgramResult {
+    let account_iter = &mut accounts.iter();
+    let proposal = next_account_info(account_iter)?;
+    let mut signers = 0;
+    for account in account_iter {
+        if account.is_signer {
+            signers += 1;
+        }
+    }
+    if signers < THRESHOLD {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    msg!("{:?} approved by {} signers", proposal.key, signers);
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,280 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+
+use anchor_syn::{AccountField, Field};
+use if_chain::if_chain;
+use rustc_hir::{FieldDef, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    sarif,
+    seeds::Seed,
+    utils::{
+        anchor_constraints::{
+            is_token_account, token_account_authorities, token_account_mints, ConstraintSummary,
+        },
+        get_anchor_accounts_struct,
+    },
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports, in the Anchor `Accounts` structs with two or more `TokenAccount`s, e.g., a source,
+    /// a destination and a fee vault, the token accounts whose mint, or whose owner, is not
+    /// constrained while the mints, or the owners, of the other token accounts are.
+    ///
+    /// The mint of a token account is constrained by `token::mint`, `associated_token::mint`,
+    /// `has_one = mint`, `init` or a `constraint` comparing it, e.g.,
+    /// `constraint = fee_vault.mint == mint.key()`. Its owner is constrained by
+    /// `token::authority`, `associated_token::authority`, `has_one = owner`, `init` or a
+    /// `constraint` comparing it. A token account whose mint is compared with the mint of a
+    /// constrained token account, e.g., `constraint = source.mint == fee_vault.mint`, is
+    /// constrained as well, as is a token account whose address is checked by `seeds` or
+    /// `address`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Constraining some of the token accounts of an instruction shows that the program relies
+    /// on their mints and owners. A token account left out, typically the last one added, e.g., a
+    /// fee vault, can be replaced by a token account of another mint, e.g., a worthless one, or
+    /// owned by the caller, e.g., to redirect the fees.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The structs in which no token account is constrained are not reported, nor are the mints
+    /// or owners compared in the instruction handlers. The lint does not check that the
+    /// constrained mints are the same, as an instruction can handle several mints, e.g., a swap.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Swap<'info> {
+    ///     #[account(mut, token::mint = mint, token::authority = user)]
+    ///     source: Account<'info, TokenAccount>,
+    ///     #[account(mut, token::mint = mint, token::authority = pool)]
+    ///     destination: Account<'info, TokenAccount>,
+    ///     #[account(mut)]
+    ///     fee_vault: Account<'info, TokenAccount>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Swap<'info> {
+    ///     #[account(mut, token::mint = mint, token::authority = user)]
+    ///     source: Account<'info, TokenAccount>,
+    ///     #[account(mut, token::mint = mint, token::authority = pool)]
+    ///     destination: Account<'info, TokenAccount>,
+    ///     #[account(mut, token::mint = mint, token::authority = pool)]
+    ///     fee_vault: Account<'info, TokenAccount>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - for each Anchor `Accounts` struct with two or more `TokenAccount` fields
+    /// - for the mints, then for the owners:
+    ///   - mark the token accounts whose constraints compare the mint (owner) with an expression
+    ///     which is not the mint (owner) of another token account, or check their address
+    ///   - until no token account is marked, mark the token accounts whose mint (owner) is
+    ///     compared with the mint (owner) of a marked token account
+    ///   - if some, but not all, token accounts are marked, report the others
+    pub PARTIAL_TOKEN_ACCOUNT_CONSTRAINTS,
+    Warn,
+    "token accounts whose mint or owner is not constrained, while those of the other token accounts are"
+}
+
+impl<'tcx> LateLintPass<'tcx> for PartialTokenAccountConstraints {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                let token_accounts = variant
+                    .fields()
+                    .iter()
+                    .zip(accounts_struct.fields.iter())
+                    .filter_map(|(item_field, anchor_field)| match anchor_field {
+                        AccountField::Field(field) if is_token_account(field) => {
+                            Some((item_field, field))
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                if token_accounts.len() < 2 {
+                    return;
+                }
+                for relation in [Relation::Mint, Relation::Owner] {
+                    check_relation(cx, &token_accounts, relation);
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[PARTIAL_TOKEN_ACCOUNT_CONSTRAINTS]);
+    }
+}
+
+/// A relation between a token account and another account, checked by the constraints
+#[derive(Clone, Copy)]
+enum Relation {
+    /// The `mint` of the token account
+    Mint,
+    /// The `owner` of the token account
+    Owner,
+}
+
+impl Relation {
+    /// The field of `TokenAccount` holding the related account
+    fn member(self) -> &'static str {
+        match self {
+            Relation::Mint => "mint",
+            Relation::Owner => "owner",
+        }
+    }
+
+    /// The expressions which the constraints of the token account compare the member with
+    fn values(self, field: &Field) -> Vec<Seed> {
+        match self {
+            Relation::Mint => token_account_mints(field),
+            Relation::Owner => token_account_authorities(field),
+        }
+    }
+}
+
+/// Report the token accounts whose `relation` is not constrained, if the relation of another
+/// token account is
+fn check_relation(
+    cx: &LateContext<'_>,
+    token_accounts: &[(&FieldDef<'_>, &Field)],
+    relation: Relation,
+) {
+    let constrained = constrained_token_accounts(token_accounts, relation);
+    if constrained.iter().all(|constrained| *constrained)
+        || constrained.iter().all(|constrained| !*constrained)
+    {
+        return;
+    }
+    let constrained_names = token_accounts
+        .iter()
+        .zip(&constrained)
+        .filter(|(_, constrained)| **constrained)
+        .map(|((_, field), _)| field.ident.to_string())
+        .collect::<Vec<_>>();
+    let member = relation.member();
+    let others = if let [name] = constrained_names.as_slice() {
+        format!("the {member} of `{name}` is")
+    } else {
+        format!("the {member}s of {} are", list(&constrained_names))
+    };
+    for ((item_field, field), _) in token_accounts
+        .iter()
+        .zip(&constrained)
+        .filter(|(_, constrained)| !**constrained)
+    {
+        let name = field.ident.to_string();
+        span_lint_hir_and_then(
+            cx,
+            PARTIAL_TOKEN_ACCOUNT_CONSTRAINTS,
+            item_field.hir_id,
+            item_field.span,
+            &format!("the {member} of `{name}` is not constrained, while {others}"),
+            |diag| {
+                diag.help(match relation {
+                    Relation::Mint => format!(
+                        "tie the mint of `{name}` to the mint of the other token accounts, e.g., with `constraint = {name}.mint == {}.mint`",
+                        constrained_names[0]
+                    ),
+                    Relation::Owner => format!(
+                        "constrain the owner of `{name}` to the expected authority, e.g., with `token::authority = ...`"
+                    ),
+                });
+            },
+        );
+    }
+}
+
+/// Return, for each token account, whether its `relation` is constrained, directly or through the
+/// relation of another token account
+fn constrained_token_accounts(
+    token_accounts: &[(&FieldDef<'_>, &Field)],
+    relation: Relation,
+) -> Vec<bool> {
+    // `{field}.mint` or `{field}.owner`, as the expressions are normalized
+    let members = token_accounts
+        .iter()
+        .map(|(_, field)| Seed::Expr(format!("{}.{}", field.ident, relation.member())))
+        .collect::<Vec<_>>();
+    let values = token_accounts
+        .iter()
+        .map(|(_, field)| relation.values(field))
+        .collect::<Vec<_>>();
+    let mut constrained = token_accounts
+        .iter()
+        .zip(&values)
+        .map(|((_, field), values)| {
+            ConstraintSummary::new(&field.constraints).has_key_check
+                || values.iter().any(|value| !members.contains(value))
+        })
+        .collect::<Vec<_>>();
+    // e.g., `constraint = source.mint == fee_vault.mint` ties `fee_vault` to `source`
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..token_accounts.len() {
+            for j in 0..token_accounts.len() {
+                if constrained[i] || !constrained[j] {
+                    continue;
+                }
+                if values[i].contains(&members[j]) || values[j].contains(&members[i]) {
+                    constrained[i] = true;
+                    changed = true;
+                }
+            }
+        }
+    }
+    constrained
+}
+
+/// Format the names, e.g., "`a`, `b` and `c`"
+fn list(names: &[String]) -> String {
+    let names = names
+        .iter()
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>();
+    match names.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {last}", rest.join(", ")),
+        None => String::new(),
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
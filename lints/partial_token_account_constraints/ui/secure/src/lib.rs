@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod partial_token_account_constraints_secure {
+    use super::*;
+
+    pub fn swap(_ctx: Context<Swap>, _amount: u64) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn withdraw(_ctx: Context<Withdraw>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(mut, token::mint = mint, token::authority = user)]
+    source: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::authority = pool)]
+    destination: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [b"fee_vault", mint.key().as_ref()], bump)]
+    fee_vault: Account<'info, TokenAccount>,
+    mint: Account<'info, Mint>,
+    user: Signer<'info>,
+    /// CHECK: the authority of the token accounts of the pool
+    pool: AccountInfo<'info>,
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(
+        mut,
+        constraint = vault.mint == mint.key(),
+        constraint = vault.owner == pool.key(),
+    )]
+    vault: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = destination.mint == vault.mint,
+        constraint = destination.owner == user.key(),
+    )]
+    destination: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, constraint = treasury.owner == pool.key())]
+    treasury: Account<'info, TokenAccount>,
+    mint: Account<'info, Mint>,
+    user: Signer<'info>,
+    /// CHECK: the authority of the token accounts of the pool
+    pool: AccountInfo<'info>,
+    token_program: Program<'info, Token>,
+}
+
+#[allow(dead_code)]
+fn main() {}
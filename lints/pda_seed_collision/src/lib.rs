@@ -0,0 +1,247 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use anchor_syn::AccountField;
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::{def_id::DefId, HirId, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    paths::{anchor_wrapper, AnchorWrapper},
+    sarif,
+    seeds::{display_seeds, field_seeds, Seed},
+    utils::get_anchor_accounts_struct,
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports two account types of a program whose PDAs are derived from seeds which can
+    /// collide, i.e., the `seeds` constraints of the two types have the same byte string literals
+    /// at the same positions, and the same number of other seeds. For example,
+    /// `seeds = [b"state", user.key().as_ref()]` for an `Account<'info, Escrow>` and for an
+    /// `Account<'info, Vault>` derive the same address for the same `user`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// An address can only hold one account. Once an account of one type is created at the
+    /// address, the account of the other type cannot be created there anymore: anyone who can
+    /// create the first account can prevent the creation of the second one, e.g., a user's vault
+    /// by creating an escrow with the same seeds. If the seeds of the two types name different
+    /// accounts, e.g., a mint and a user, an attacker may also choose the accounts so that the
+    /// seeds of one type are the seeds of the other.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The seeds are normalized as in `inconsistent_pda_seeds`, and seeds other than byte string
+    /// literals are assumed to have the same length, e.g., a key and a `u64` seed are regarded as
+    /// possibly colliding. Seeds are not concatenated, so `[b"ab", x]` and `[b"a", b"b", x]` are
+    /// not reported, although they derive the same address.
+    ///
+    /// Only fields of type `Account<'info, T>` or `AccountLoader<'info, T>` are checked, and
+    /// fields of the same type `T` are assumed to be the same account.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct OpenEscrow<'info> {
+    ///     #[account(init, payer = user, space = 8 + Escrow::INIT_SPACE, seeds = [b"state", user.key().as_ref()], bump)]
+    ///     escrow: Account<'info, Escrow>,
+    ///     ...
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct OpenVault<'info> {
+    ///     #[account(init, payer = user, space = 8 + Vault::INIT_SPACE, seeds = [b"state", user.key().as_ref()], bump)]
+    ///     vault: Account<'info, Vault>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct OpenVault<'info> {
+    ///     #[account(init, payer = user, space = 8 + Vault::INIT_SPACE, seeds = [b"vault", user.key().as_ref()], bump)]
+    ///     vault: Account<'info, Vault>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - For each Anchor `Accounts` struct
+    ///   - For each field of type `Account<'info, T>` or `AccountLoader<'info, T>` with the `seeds`
+    ///     constraint, where the seeds are of the current program (no `seeds::program`), record the
+    ///     seeds of `T`
+    ///
+    /// check_crate_post:
+    ///
+    /// - For each recorded seeds of a type `U`, find the first recorded seeds of a type `T`, other
+    ///   than `U`, such that
+    ///   - the number of the seeds is the same, and
+    ///   - the byte string literals of the seeds are the same, at the same positions
+    /// - If there are such seeds, and the pair `T` and `U` has not been reported yet, report the
+    ///   seeds of `U` and note the seeds of `T`
+    pub PDA_SEED_COLLISION,
+    Warn,
+    "account types whose PDA seeds can derive the same address",
+    PdaSeedCollision::default()
+}
+
+#[derive(Default)]
+struct PdaSeedCollision {
+    /// The seeds of the PDAs, in the order they are found
+    pda_seeds: Vec<PdaSeeds>,
+}
+
+/// The seeds of an account of type `account`
+struct PdaSeeds {
+    account: DefId,
+    seeds: Vec<Seed>,
+    /// The `Accounts` struct field
+    hir_id: HirId,
+    span: Span,
+}
+
+impl<'tcx> LateLintPass<'tcx> for PdaSeedCollision {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                for (item_field, anchor_field) in
+                    variant.fields().iter().zip(accounts_struct.fields.iter())
+                {
+                    // Composite fields are checked when their own struct is checked.
+                    if_chain! {
+                        if let AccountField::Field(field) = anchor_field;
+                        if let Some(seeds) = field_seeds(field);
+                        let field_ty = cx.tcx.type_of(item_field.def_id).skip_binder();
+                        if let Some(account) = account_data_type(cx, field_ty);
+                        then {
+                            self.pda_seeds.push(PdaSeeds {
+                                account,
+                                seeds,
+                                hir_id: item_field.hir_id,
+                                span: item_field.span,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        let mut reported = FxHashSet::default();
+        for (i, pda_seeds) in self.pda_seeds.iter().enumerate() {
+            let other = match self.pda_seeds[..i].iter().find(|other| {
+                other.account != pda_seeds.account && can_collide(&other.seeds, &pda_seeds.seeds)
+            }) {
+                Some(other) => other,
+                None => continue,
+            };
+            if reported.insert((other.account, pda_seeds.account)) {
+                report(cx, other, pda_seeds);
+            }
+        }
+
+        sarif::write(cx, &[PDA_SEED_COLLISION]);
+    }
+}
+
+/// Report the seeds `pda_seeds`, which can collide with the seeds `other` of another account type
+fn report(cx: &LateContext<'_>, other: &PdaSeeds, pda_seeds: &PdaSeeds) {
+    let account_name = cx.tcx.item_name(pda_seeds.account);
+    let other_name = cx.tcx.item_name(other.account);
+    let msg = format!(
+        "the seeds of `{account_name}` can derive the same address as the seeds of `{other_name}`"
+    );
+    span_lint_hir_and_then(
+        cx,
+        PDA_SEED_COLLISION,
+        pda_seeds.hir_id,
+        pda_seeds.span,
+        &msg,
+        |diag| {
+            diag.span_note(
+                other.span,
+                format!(
+                    "`{other_name}` is derived from the seeds `{}`",
+                    display_seeds(&other.seeds)
+                ),
+            );
+            diag.note(format!(
+                "the seeds here are `{}`, with the same byte string literals and the same number of seeds",
+                display_seeds(&pda_seeds.seeds)
+            ));
+            diag.help(format!(
+                "add a byte string literal specific to `{account_name}` to its seeds"
+            ));
+        },
+    );
+}
+
+/// If `ty` is `Account<'info, T>` or `AccountLoader<'info, T>`, or a `Box` of them, return the def id of `T`
+fn account_data_type<'tcx>(cx: &LateContext<'tcx>, mut ty: Ty<'tcx>) -> Option<DefId> {
+    if ty.is_box() {
+        ty = ty.boxed_ty();
+    }
+    if_chain! {
+        if matches!(
+            anchor_wrapper(cx, ty),
+            Some(AnchorWrapper::Account | AnchorWrapper::AccountLoader)
+        );
+        if let ty::Adt(_, args) = ty.kind();
+        if let Some(data_ty) = args.types().next();
+        if let ty::Adt(data_adt_def, _) = data_ty.kind();
+        then {
+            Some(data_adt_def.did())
+        } else {
+            None
+        }
+    }
+}
+
+/// Return true if the seeds have the same number of seeds, and the same byte string literals at
+/// the same positions
+fn can_collide(seeds: &[Seed], other: &[Seed]) -> bool {
+    seeds.len() == other.len()
+        && seeds.iter().zip(other).all(|pair| match pair {
+            (Seed::Bytes(bytes), Seed::Bytes(other_bytes)) => bytes == other_bytes,
+            (Seed::Expr(_), Seed::Expr(_)) => true,
+            _ => false,
+        })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
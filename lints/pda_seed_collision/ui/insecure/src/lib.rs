@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod pda_seed_collision_insecure {
+    use super::*;
+
+    pub fn open_escrow(ctx: Context<OpenEscrow>, amount: u64) -> Result<()> {
+        ctx.accounts.escrow.user = ctx.accounts.user.key();
+        ctx.accounts.escrow.amount = amount;
+        Ok(())
+    }
+
+    pub fn open_vault(ctx: Context<OpenVault>) -> Result<()> {
+        ctx.accounts.vault.user = ctx.accounts.user.key();
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.balance += amount;
+        Ok(())
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>) -> Result<()> {
+        ctx.accounts.position.owner = ctx.accounts.owner.key();
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct OpenEscrow<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [b"state", user.key().as_ref()],
+        bump
+    )]
+    escrow: Account<'info, Escrow>,
+    #[account(mut)]
+    user: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenVault<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Vault::INIT_SPACE,
+        seeds = [b"state", user.key().as_ref()],
+        bump
+    )]
+    vault: Account<'info, Vault>,
+    #[account(mut)]
+    user: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut, seeds = [b"state", user.key().as_ref()], bump)]
+    vault: Account<'info, Vault>,
+    user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OpenPosition<'info> {
+    #[account(seeds = [b"pool", pool.mint.as_ref(), pool.authority.as_ref()], bump)]
+    pool: Account<'info, Pool>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Position::INIT_SPACE,
+        seeds = [b"pool", owner.key().as_ref(), pool.key().as_ref()],
+        bump
+    )]
+    position: Account<'info, Position>,
+    #[account(mut)]
+    owner: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Escrow {
+    user: Pubkey,
+    amount: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Vault {
+    user: Pubkey,
+    balance: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {
+    mint: Pubkey,
+    authority: Pubkey,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Position {
+    owner: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
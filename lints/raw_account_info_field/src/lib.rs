@@ -0,0 +1,198 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_errors;
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::{AccountField, Ty as FieldTy};
+use if_chain::if_chain;
+use rustc_errors::Applicability;
+use rustc_hir::{FieldDef, Item, ItemKind, QPath, TyKind};
+use rustc_lint::{LateContext, LateLintPass, LintContext};
+use rustc_span::{BytePos, Span};
+use solana_lints::{diagnostics::span_lint_hir_and_then, sarif, utils::get_anchor_accounts_struct};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the `AccountInfo` fields of Anchor `Accounts` structs, and suggests declaring them
+    /// as `UncheckedAccount`s, with a `/// CHECK:` comment if they do not have one.
+    ///
+    /// The lint is allowed by default. It is a stylistic lint, which can be enabled with
+    /// `#![warn(raw_account_info_field)]`, and whose suggestions can be applied with `cargo fix`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Anchor checks neither an `AccountInfo` nor an `UncheckedAccount`. `UncheckedAccount` states
+    /// it in the type, and `anchor build` requires a `/// CHECK:` comment explaining why the
+    /// account is safe to use unchecked, so the reviewers of the struct see which accounts the
+    /// instruction must check itself, and why.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The suggestion assumes that `UncheckedAccount` is in scope, e.g., imported with
+    /// `anchor_lang::prelude::*`, and is `MaybeIncorrect` for the fields whose type is written
+    /// with a path, e.g., `solana_program::account_info::AccountInfo<'info>`. The inserted comment
+    /// is a placeholder, to be replaced with the actual explanation.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Withdraw<'info> {
+    ///     #[account(mut)]
+    ///     destination: AccountInfo<'info>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Withdraw<'info> {
+    ///     /// CHECK: only receives lamports
+    ///     #[account(mut)]
+    ///     destination: UncheckedAccount<'info>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - for each Anchor `Accounts` struct, for each field whose Anchor type is `AccountInfo`,
+    ///   excluding the fields generated by macros
+    /// - report the field, and suggest replacing the path of its type with `UncheckedAccount`
+    /// - if none of the doc comments of the field starts with `CHECK`, suggest inserting
+    ///   `/// CHECK: TODO ...` before its attributes other than doc comments
+    pub RAW_ACCOUNT_INFO_FIELD,
+    Allow,
+    "Anchor `AccountInfo` fields which could be declared as `UncheckedAccount`s"
+}
+
+impl<'tcx> LateLintPass<'tcx> for RawAccountInfoField {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                for (item_field, anchor_field) in
+                    variant.fields().iter().zip(accounts_struct.fields.iter())
+                {
+                    if_chain! {
+                        if let AccountField::Field(field) = anchor_field;
+                        if matches!(field.ty, FieldTy::AccountInfo);
+                        if !item_field.span.from_expansion();
+                        then {
+                            report_field(cx, item_field);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[RAW_ACCOUNT_INFO_FIELD]);
+    }
+}
+
+fn report_field(cx: &LateContext<'_>, item_field: &FieldDef<'_>) {
+    // the path of the type up to `AccountInfo`, leaving out its generic arguments
+    let (path_span, applicability) = match item_field.ty.kind {
+        TyKind::Path(QPath::Resolved(None, path)) => match path.segments {
+            [segment] => (
+                path.span.with_hi(segment.ident.span.hi()),
+                Applicability::MachineApplicable,
+            ),
+            [.., segment] => (
+                path.span.with_hi(segment.ident.span.hi()),
+                Applicability::MaybeIncorrect,
+            ),
+            [] => return,
+        },
+        _ => return,
+    };
+    let attrs = cx.tcx.hir().attrs(item_field.hir_id);
+    let has_check_comment = attrs.iter().any(|attr| {
+        attr.doc_str()
+            .map_or(false, |doc| doc.as_str().trim_start().starts_with("CHECK"))
+    });
+    // the comment goes after the doc comments, before the other attributes
+    let check_comment = attrs
+        .iter()
+        .filter(|attr| !attr.is_doc_comment())
+        .map(|attr| attr.span)
+        .chain(std::iter::once(item_field.span))
+        .min_by_key(|span| span.lo())
+        .filter(|_| !has_check_comment)
+        .and_then(|span| line_start(cx, span))
+        .map(|(line_start, indent)| {
+            (
+                line_start,
+                format!(
+                    "{indent}/// CHECK: TODO: explain why this account does not need to be checked\n"
+                ),
+            )
+        });
+    span_lint_hir_and_then(
+        cx,
+        RAW_ACCOUNT_INFO_FIELD,
+        item_field.hir_id,
+        item_field.span,
+        &format!(
+            "`{}` is declared as an `AccountInfo` rather than an `UncheckedAccount`",
+            item_field.ident
+        ),
+        |diag| {
+            diag.span_suggestion_verbose(
+                path_span,
+                "use `UncheckedAccount`, whose type states that Anchor does not check the account",
+                "UncheckedAccount",
+                applicability,
+            );
+            if let Some((line_start, comment)) = check_comment {
+                diag.span_suggestion_verbose(
+                    line_start,
+                    "explain why the account does not need to be checked, as `anchor build` requires",
+                    comment,
+                    Applicability::MachineApplicable,
+                );
+            }
+        },
+    );
+}
+
+/// If `span` is the first code on its line, return the empty span at the start of the line, and
+/// the indentation of the line
+fn line_start(cx: &LateContext<'_>, span: Span) -> Option<(Span, String)> {
+    let prev_source = cx.sess().source_map().span_to_prev_source(span).ok()?;
+    let indent = prev_source
+        .rsplit('\n')
+        .next()
+        .filter(|indent| indent.chars().all(char::is_whitespace))?;
+    let lo = span.lo() - BytePos(indent.len() as u32);
+    Some((span.with_lo(lo).shrink_to_lo(), indent.to_owned()))
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
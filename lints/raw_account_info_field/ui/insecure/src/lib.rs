@@ -0,0 +1,28 @@
+#![warn(raw_account_info_field)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod raw_account_info_field_insecure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.destination.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, seeds = [b"vault", authority.key().as_ref()], bump)]
+    vault: AccountInfo<'info>,
+    /// The account receiving the lamports
+    #[account(mut)]
+    destination: AccountInfo<'info>,
+    /// CHECK: only used as a seed of the vault
+    authority: AccountInfo<'info>,
+    payer: Signer<'info>,
+}
@@ -0,0 +1,30 @@
+#![warn(raw_account_info_field)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod raw_account_info_field_secure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.destination.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: a PDA of the program, whose address is checked by the seeds
+    #[account(mut, seeds = [b"vault", authority.key().as_ref()], bump)]
+    vault: UncheckedAccount<'info>,
+    /// The account receiving the lamports
+    /// CHECK: only receives lamports
+    #[account(mut)]
+    destination: UncheckedAccount<'info>,
+    /// CHECK: only used as a seed of the vault
+    authority: UncheckedAccount<'info>,
+    payer: Signer<'info>,
+}
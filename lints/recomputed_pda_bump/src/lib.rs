@@ -0,0 +1,202 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use anchor_syn::AccountField;
+use if_chain::if_chain;
+use rustc_hir::{def_id::DefId, FieldDef, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, UintTy};
+use rustc_span::{Span, Symbol};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    paths::{anchor_wrapper, AnchorWrapper},
+    sarif,
+    utils::{account_data_type, get_anchor_accounts_struct},
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the fields of Anchor `Accounts` structs with the constraints `seeds = [...]` and a
+    /// bare `bump`, i.e., without `bump = ...`, whose account type stores a bump, i.e., has a `u8`
+    /// field named `bump`, `bump_seed`, or whose name ends with `_bump`, e.g., `vault_bump`. The
+    /// fields with the `init` or `init_if_needed` constraints are not reported, as the account may
+    /// not exist yet.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// With a bare `bump`, Anchor derives the address of the account with
+    /// `Pubkey::find_program_address`, which searches for the canonical bump from 255 downwards,
+    /// hashing the seeds and checking that the result is off the curve at each attempt. The search
+    /// costs thousands of compute units, and it is made in every call of the instruction. With
+    /// `bump = vault.bump`, Anchor derives the address once, with the stored bump and
+    /// `Pubkey::create_program_address`.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The lint does not know how often an instruction is called: every instruction using an
+    /// existing account is assumed to be called repeatedly. The lint does not check that the
+    /// stored bump is set to the canonical bump when the account is initialized, e.g., from
+    /// `ctx.bumps`.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Deposit<'info> {
+    ///     #[account(mut, seeds = [b"vault", authority.key().as_ref()], bump)]
+    ///     vault: Account<'info, Vault>,
+    ///     ...
+    /// }
+    ///
+    /// #[account]
+    /// pub struct Vault {
+    ///     pub authority: Pubkey,
+    ///     pub bump: u8,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Deposit<'info> {
+    ///     #[account(mut, seeds = [b"vault", authority.key().as_ref()], bump = vault.bump)]
+    ///     vault: Account<'info, Vault>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - for each field of an Anchor `Accounts` struct, excluding the composite fields
+    /// - if the field has the `seeds` constraint, for the current program (no `seeds::program`),
+    ///   with a bare `bump`, and has neither `init` nor `init_if_needed`
+    /// - if the field is an `Account<'info, T>` or an `AccountLoader<'info, T>`, and `T` has a `u8`
+    ///   field with a bump name, report the field, suggesting to use the stored bump
+    pub RECOMPUTED_PDA_BUMP,
+    Warn,
+    "a PDA bump recomputed with `find_program_address` although the account stores its bump"
+}
+
+impl<'tcx> LateLintPass<'tcx> for RecomputedPdaBump {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                for (item_field, anchor_field) in
+                    variant.fields().iter().zip(accounts_struct.fields.iter())
+                {
+                    if_chain! {
+                        if let AccountField::Field(field) = anchor_field;
+                        if field.constraints.init.is_none();
+                        if let Some(seeds_group) = &field.constraints.seeds;
+                        if seeds_group.bump.is_none() && seeds_group.program_seed.is_none();
+                        let mut field_ty = cx.tcx.type_of(item_field.def_id).skip_binder();
+                        if let Some(account) = account_data_type(cx, field_ty);
+                        if let Some((bump_name, bump_span)) = stored_bump(cx, account);
+                        then {
+                            if field_ty.is_box() {
+                                field_ty = field_ty.boxed_ty();
+                            }
+                            let stored_bump = match anchor_wrapper(cx, field_ty) {
+                                Some(AnchorWrapper::AccountLoader) => {
+                                    format!("{}.load()?.{bump_name}", item_field.ident)
+                                }
+                                _ => format!("{}.{bump_name}", item_field.ident),
+                            };
+                            report(cx, item_field, account, bump_span, &stored_bump);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[RECOMPUTED_PDA_BUMP]);
+    }
+}
+
+fn report(
+    cx: &LateContext<'_>,
+    item_field: &FieldDef<'_>,
+    account: DefId,
+    bump_span: Span,
+    stored_bump: &str,
+) {
+    span_lint_hir_and_then(
+        cx,
+        RECOMPUTED_PDA_BUMP,
+        item_field.hir_id,
+        item_field.span,
+        &format!(
+            "the bump of `{}` is searched with `find_program_address` in every call of the instruction",
+            item_field.ident
+        ),
+        |diag| {
+            diag.span_note(
+                bump_span,
+                format!(
+                    "`{}` stores the bump of the account",
+                    cx.tcx.item_name(account)
+                ),
+            );
+            diag.note(
+                "a bare `bump` makes Anchor search for the canonical bump, hashing the seeds up to 255 times, whereas a stored bump is checked with a single hash",
+            );
+            diag.help(format!("use the stored bump: `bump = {stored_bump}`"));
+        },
+    );
+}
+
+/// Return the name and the span of the first `u8` field of the struct `account` with a bump name,
+/// if any
+fn stored_bump(cx: &LateContext<'_>, account: DefId) -> Option<(Symbol, Span)> {
+    let adt_def = cx.tcx.adt_def(account);
+    if !adt_def.is_struct() {
+        return None;
+    }
+    adt_def
+        .all_fields()
+        .find(|field| {
+            is_bump_name(field.name.as_str())
+                && matches!(
+                    cx.tcx.type_of(field.did).skip_binder().kind(),
+                    ty::Uint(UintTy::U8)
+                )
+        })
+        .map(|field| (field.name, cx.tcx.def_span(field.did)))
+}
+
+/// Return true if `name` is the name of a field holding a bump, i.e., `bump`, `bump_seed`, or a
+/// name ending with `_bump`, e.g., `vault_bump`
+fn is_bump_name(name: &str) -> bool {
+    name == "bump" || name == "bump_seed" || name.ends_with("_bump")
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
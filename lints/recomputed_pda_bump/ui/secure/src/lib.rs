@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod recomputed_pda_bump_secure {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        vault.authority = ctx.accounts.authority.key();
+        vault.bump = ctx.bumps.vault;
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.amount += amount;
+        Ok(())
+    }
+
+    pub fn swap(ctx: Context<Swap>, amount: u64) -> Result<()> {
+        ctx.accounts.pool.load_mut()?.volume += amount;
+        Ok(())
+    }
+
+    pub fn log(ctx: Context<Log>) -> Result<()> {
+        msg!("{}", ctx.accounts.config.fee);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = authority, space = 8 + 32 + 8 + 1, seeds = [b"vault", authority.key().as_ref()], bump)]
+    vault: Account<'info, Vault>,
+    #[account(mut)]
+    authority: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut, seeds = [b"vault", authority.key().as_ref()], bump = vault.bump)]
+    vault: Account<'info, Vault>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    #[account(mut, seeds = [b"pool", authority.key().as_ref()], bump = pool.load()?.pool_bump)]
+    pool: AccountLoader<'info, Pool>,
+    authority: Signer<'info>,
+}
+
+// `Config` does not store its bump
+#[derive(Accounts)]
+pub struct Log<'info> {
+    #[account(seeds = [b"config"], bump)]
+    config: Account<'info, Config>,
+}
+
+#[account]
+pub struct Vault {
+    authority: Pubkey,
+    amount: u64,
+    bump: u8,
+}
+
+#[account(zero_copy)]
+pub struct Pool {
+    authority: Pubkey,
+    volume: u64,
+    pool_bump: u8,
+    _padding: [u8; 7],
+}
+
+#[account]
+pub struct Config {
+    fee: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,340 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{higher, is_trait_method, path_to_local, source::snippet};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::LocalDefId, intravisit::FnKind, BinOpKind, Body, Expr, ExprKind, FnDecl, HirId, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty;
+use rustc_span::{sym, Span};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    sarif,
+    utils::{
+        context_accounts_struct, is_remaining_accounts, local_init, uses_remaining_accounts,
+        visit_expr_no_bodies,
+    },
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports, in Anchor instruction handlers, the iterations over `ctx.remaining_accounts`
+    /// together with a list supplied by the caller, i.e., derived from an instruction argument,
+    /// when the number of accounts is not compared with the length of the list beforehand:
+    ///
+    /// - `zip` of the two, e.g., `ctx.remaining_accounts.iter().zip(amounts.iter())`
+    /// - a loop over the indexes of one of them which indexes the other, e.g.,
+    ///   `for i in 0..amounts.len() { let account = &ctx.remaining_accounts[i]; .. }`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// `zip` stops at the end of the shorter of the two. A loop validating each account against
+    /// the value of the caller, e.g., its owner or its key, silently skips the accounts past the
+    /// end of the list, which the program may use afterwards without their checks. A loop over the
+    /// indexes of the list likewise skips the extra accounts, or panics when there are fewer
+    /// accounts than values.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The instruction arguments are followed through the initializers of local variables only,
+    /// and a list computed from them is regarded as chosen by the caller, unless it is an array.
+    /// Any comparison between the two, e.g., with `require_eq!` or `if`, before the iteration
+    /// counts as a check of their lengths; `assert_eq!` does not, as it compares references to
+    /// them. The remaining accounts are only recognized as `ctx.remaining_accounts`, not through a
+    /// local variable.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn distribute(ctx: Context<Distribute>, amounts: Vec<u64>) -> Result<()> {
+    ///     for (account, amount) in ctx.remaining_accounts.iter().zip(amounts.iter()) {
+    ///         ..
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// pub fn distribute(ctx: Context<Distribute>, amounts: Vec<u64>) -> Result<()> {
+    ///     require_eq!(ctx.remaining_accounts.len(), amounts.len());
+    ///     for (account, amount) in ctx.remaining_accounts.iter().zip(amounts.iter()) {
+    ///         ..
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for every Anchor instruction handler, excluding the functions generated by macros, take
+    ///   the arguments after the `Context`
+    /// - an expression is derived from the arguments if it uses one of them, or a local variable
+    ///   initialized with an expression derived from them
+    /// - find the calls of `Iterator::zip` whose receiver uses `ctx.remaining_accounts` and whose
+    ///   argument is derived from the arguments, or the other way around
+    /// - find the `for` loops over a range ending at `x.len()`, whose body indexes `y` with the
+    ///   loop variable, where `x` and `y` are `ctx.remaining_accounts` and an expression derived
+    ///   from the arguments, in either order
+    /// - report them, unless the list is an array, or an expression before them compares (`==`, `!=`, `<`, `<=`, `>` or
+    ///   `>=`) an expression using `ctx.remaining_accounts` with one derived from the arguments
+    pub REMAINING_ACCOUNTS_LENGTH_MISMATCH,
+    Warn,
+    "remaining accounts iterated with an instruction argument whose length is not checked to match"
+}
+
+impl<'tcx> LateLintPass<'tcx> for RemainingAccountsLengthMismatch {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        if is_skipped_expansion(REMAINING_ACCOUNTS_LENGTH_MISMATCH, span)
+            || context_accounts_struct(cx, local_def_id).is_none()
+        {
+            return;
+        }
+        let mut args = Vec::new();
+        for param in body.params.iter().skip(1) {
+            param.pat.each_binding(|_, hir_id, _, _| args.push(hir_id));
+        }
+        if args.is_empty() {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if let Some(iteration) = zipped_lists(cx, expr, &args)
+                    .or_else(|| indexed_loop_lists(cx, expr, &args));
+                if !is_skipped_expansion(REMAINING_ACCOUNTS_LENGTH_MISMATCH, iteration.span);
+                if !has_fixed_length(cx, iteration.list);
+                if !compares_lengths(cx, body.value, &args, iteration.span);
+                then {
+                    report(cx, &iteration);
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[REMAINING_ACCOUNTS_LENGTH_MISMATCH]);
+    }
+}
+
+/// An iteration over the remaining accounts together with a list derived from the instruction
+/// arguments
+struct Iteration<'tcx> {
+    hir_id: HirId,
+    span: Span,
+    kind: IterationKind,
+    /// The list derived from the instruction arguments, e.g., `amounts`
+    list: &'tcx Expr<'tcx>,
+}
+
+#[derive(Clone, Copy)]
+enum IterationKind {
+    /// `a.zip(b)`
+    Zip,
+    /// `for i in 0..a.len() { .. b[i] .. }`
+    IndexedLoop,
+}
+
+fn report(cx: &LateContext<'_>, iteration: &Iteration<'_>) {
+    let list = snippet(cx, iteration.list.span, "..");
+    let (msg, note) = match iteration.kind {
+        IterationKind::Zip => (
+            format!("`ctx.remaining_accounts` is zipped with `{list}`, whose length is not checked to match"),
+            "`zip` stops at the end of the shorter of the two, and silently skips the accounts or the values past it",
+        ),
+        IterationKind::IndexedLoop => (
+            format!("the lengths of `ctx.remaining_accounts` and `{list}`, indexed in the same loop, are not checked to match"),
+            "the loop skips the accounts or the values past the end of the shorter of the two, or panics",
+        ),
+    };
+    span_lint_hir_and_then(
+        cx,
+        REMAINING_ACCOUNTS_LENGTH_MISMATCH,
+        iteration.hir_id,
+        iteration.span,
+        &msg,
+        |diag| {
+            diag.note(note);
+            diag.help(format!(
+                "compare `ctx.remaining_accounts.len()` with `{list}.len()` before the loop, e.g., with `require_eq!`"
+            ));
+        },
+    );
+}
+
+/// If `expr` is `a.zip(b)`, where one of `a` and `b` uses `ctx.remaining_accounts` and the other
+/// is derived from `args`, return the iteration
+fn zipped_lists<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    args: &[HirId],
+) -> Option<Iteration<'tcx>> {
+    if_chain! {
+        if let ExprKind::MethodCall(segment, receiver, [other], _) = expr.kind;
+        if segment.ident.as_str() == "zip";
+        if is_trait_method(cx, expr, sym::Iterator);
+        then {
+            let list = if uses_remaining_accounts(cx, receiver) && is_derived_from(cx, other, args)
+            {
+                other
+            } else if uses_remaining_accounts(cx, other) && is_derived_from(cx, receiver, args) {
+                receiver
+            } else {
+                return None;
+            };
+            Some(Iteration {
+                hir_id: expr.hir_id,
+                span: expr.span,
+                kind: IterationKind::Zip,
+                list: peel_iter(list),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// If `expr` is a `for` loop over `0..a.len()` whose body indexes `b` with the loop variable,
+/// where one of `a` and `b` is `ctx.remaining_accounts` and the other is derived from `args`,
+/// return the iteration
+fn indexed_loop_lists<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    args: &[HirId],
+) -> Option<Iteration<'tcx>> {
+    let for_loop = higher::ForLoop::hir(expr)?;
+    let bound = match higher::Range::hir(for_loop.arg)?.end?.kind {
+        ExprKind::MethodCall(segment, receiver, [], _) if segment.ident.as_str() == "len" => {
+            receiver
+        }
+        _ => return None,
+    };
+    let mut index = None;
+    for_loop
+        .pat
+        .each_binding(|_, hir_id, _, _| index = Some(hir_id));
+    let index = index?;
+    let bound_is_accounts = is_remaining_accounts(cx, peel_iter(bound));
+    if !bound_is_accounts && !is_derived_from(cx, bound, args) {
+        return None;
+    }
+    let indexed = visit_expr_no_bodies(for_loop.body, |expr| match expr.kind {
+        ExprKind::Index(base, idx, _) if path_to_local(idx) == Some(index) => Some(base),
+        _ => None,
+    })?;
+    let list = if bound_is_accounts && is_derived_from(cx, indexed, args) {
+        indexed
+    } else if !bound_is_accounts && is_remaining_accounts(cx, peel_iter(indexed)) {
+        bound
+    } else {
+        return None;
+    };
+    Some(Iteration {
+        hir_id: for_loop.arg.hir_id,
+        span: for_loop.arg.span,
+        kind: IterationKind::IndexedLoop,
+        list: peel_iter(list),
+    })
+}
+
+/// Return true if `expr` uses one of `args`, or a local variable initialized with an expression
+/// which does
+fn is_derived_from<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>, args: &[HirId]) -> bool {
+    visit_expr_no_bodies(expr, |expr| {
+        path_to_local(expr).map_or(false, |local| {
+            args.contains(&local)
+                || local_init(cx, local).map_or(false, |init| is_derived_from(cx, init, args))
+        })
+    })
+}
+
+/// Return true if `list` is an array, or a reference to one, whose length the caller cannot choose
+fn has_fixed_length(cx: &LateContext<'_>, list: &Expr<'_>) -> bool {
+    matches!(
+        cx.typeck_results().expr_ty(list).peel_refs().kind(),
+        ty::Array(..)
+    )
+}
+
+/// Return true if an expression of `scope` before `span` compares an expression using
+/// `ctx.remaining_accounts` with one derived from `args`, e.g.,
+/// `ctx.remaining_accounts.len() != amounts.len()`
+fn compares_lengths<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    args: &[HirId],
+    span: Span,
+) -> bool {
+    visit_expr_no_bodies(scope, |expr| match expr.kind {
+        ExprKind::Binary(op, lhs, rhs)
+            if matches!(
+                op.node,
+                BinOpKind::Eq
+                    | BinOpKind::Ne
+                    | BinOpKind::Lt
+                    | BinOpKind::Le
+                    | BinOpKind::Gt
+                    | BinOpKind::Ge
+            ) =>
+        {
+            expr.span.source_callsite().lo() < span.source_callsite().lo()
+                && ((uses_remaining_accounts(cx, lhs) && is_derived_from(cx, rhs, args))
+                    || (uses_remaining_accounts(cx, rhs) && is_derived_from(cx, lhs, args)))
+        }
+        _ => false,
+    })
+}
+
+/// Remove the borrows, dereferences and calls of `iter`, `iter_mut` and `into_iter` around `expr`,
+/// e.g., `amounts` for `amounts.iter()`
+fn peel_iter<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    loop {
+        expr = match expr.kind {
+            ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) => inner,
+            ExprKind::MethodCall(segment, receiver, [], _)
+                if matches!(segment.ident.as_str(), "iter" | "iter_mut" | "into_iter") =>
+            {
+                receiver
+            }
+            _ => return expr,
+        };
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod remaining_accounts_length_mismatch_insecure {
+    use super::*;
+
+    pub fn distribute(ctx: Context<Distribute>, amounts: Vec<u64>) -> Result<()> {
+        for (account, amount) in ctx.remaining_accounts.iter().zip(amounts.iter()) {
+            require_keys_eq!(*account.owner, crate::ID);
+            **account.try_borrow_mut_lamports()? += *amount;
+        }
+        Ok(())
+    }
+
+    pub fn distribute_reversed(ctx: Context<Distribute>, amounts: Vec<u64>) -> Result<()> {
+        let amounts = amounts.into_iter().filter(|amount| *amount > 0).collect::<Vec<_>>();
+        for (amount, account) in amounts.iter().zip(ctx.remaining_accounts) {
+            **account.try_borrow_mut_lamports()? += *amount;
+        }
+        Ok(())
+    }
+
+    pub fn verify_recipients(ctx: Context<Distribute>, recipients: Vec<Pubkey>) -> Result<()> {
+        for i in 0..recipients.len() {
+            let account = &ctx.remaining_accounts[i];
+            require_keys_eq!(account.key(), recipients[i]);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Distribute<'info> {
+    pub authority: Signer<'info>,
+}
+
+fn main() {}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod remaining_accounts_length_mismatch_secure {
+    use super::*;
+
+    pub fn distribute(ctx: Context<Distribute>, amounts: Vec<u64>) -> Result<()> {
+        require_eq!(ctx.remaining_accounts.len(), amounts.len());
+        for (account, amount) in ctx.remaining_accounts.iter().zip(amounts.iter()) {
+            require_keys_eq!(*account.owner, crate::ID);
+            **account.try_borrow_mut_lamports()? += *amount;
+        }
+        Ok(())
+    }
+
+    pub fn verify_recipients(ctx: Context<Distribute>, recipients: Vec<Pubkey>) -> Result<()> {
+        if ctx.remaining_accounts.len() != recipients.len() {
+            return Err(ProgramError::NotEnoughAccountKeys.into());
+        }
+        for i in 0..recipients.len() {
+            let account = &ctx.remaining_accounts[i];
+            require_keys_eq!(account.key(), recipients[i]);
+        }
+        Ok(())
+    }
+
+    pub fn distribute_evenly(ctx: Context<Distribute>, amount: u64) -> Result<()> {
+        let shares = [amount / 2, amount / 2];
+        for (account, share) in ctx.remaining_accounts.iter().zip(shares.iter()) {
+            **account.try_borrow_mut_lamports()? += *share;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Distribute<'info> {
+    pub authority: Signer<'info>,
+}
+
+fn main() {}
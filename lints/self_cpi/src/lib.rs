@@ -0,0 +1,469 @@
+#![feature(rustc_private)]
+#![feature(box_patterns)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{match_any_def_paths, match_def_path, ty::match_type};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, CRATE_DEF_ID},
+    Body,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::{
+    mir::{
+        self, BasicBlock, Operand, Place, ProjectionElem, Rvalue, StatementKind, TerminatorKind,
+    },
+    ty::{self, TyKind},
+};
+use rustc_span::Span;
+use serde::Deserialize;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    mir_cache::{BodyAnalysis, MirCache},
+    paths, sarif,
+    utils::{
+        cpi::{instruction_invoke_blocks, is_instruction_init_stmt, is_same_place},
+        is_self_program_account_ty, mir_lint_root,
+    },
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the CPIs into the current program:
+    ///
+    /// - the calls of `invoke` or `invoke_signed` with an `Instruction` whose program ID is
+    ///   `ctx.program_id`, or the ID declared with `declare_id!`, i.e., `crate::ID` or
+    ///   `crate::id()`
+    /// - the calls of `CpiContext::new` or `CpiContext::new_with_signer` with the account of the
+    ///   current program, i.e., a `Program<'info, T>` where `T` is the type generated by
+    ///   `#[program]` in `crate::program`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A program calling itself through a CPI usually means to call one of its functions, which it
+    /// can do directly. The CPI costs compute units and a level of the CPI depth, and it makes the
+    /// program reentrant: the instruction called runs while the accounts of the caller are
+    /// borrowed and partially updated, so that the checks and the invariants of both instructions
+    /// have to hold in the middle of the other.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The program ID is only traced within the function: an `Instruction` built in a function and
+    /// invoked in another one, or a program ID passed as an argument, e.g., the `program_id` of a
+    /// non-Anchor entrypoint, is not reported.
+    ///
+    /// The CPIs generated by macros, e.g., by `emit_cpi!` of Anchor's `event-cpi` feature, are not
+    /// reported.
+    ///
+    /// **Configuration:**
+    ///
+    /// The functions making intended self-CPIs can be allowed in `dylint.toml`, by their path:
+    ///
+    /// ```toml
+    /// [self_cpi]
+    /// allowed_functions = ["my_program::emit_through_cpi"]
+    /// ```
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// let ix = Instruction {
+    ///     program_id: *ctx.program_id,
+    ///     accounts: vec![AccountMeta::new(ctx.accounts.vault.key(), false)],
+    ///     data: instruction::Settle {}.data(),
+    /// };
+    /// invoke(&ix, &[ctx.accounts.vault.to_account_info()])?;
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// settle_vault(&mut ctx.accounts.vault)?;
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_body:
+    ///
+    /// - for every function, excluding the functions allowed in the configuration
+    /// - for every `Instruction` built in the function, with `Instruction {..}` or
+    ///   `Instruction::new_with_*`, excluding the code generated by macros
+    ///   - find the aliases of its program ID (see `solana_lints::utils::cpi`)
+    ///   - if one of them is the `program_id` field of an Anchor `Context`, is assigned `ID`,
+    ///     `ID_CONST` or the result of calling `id` or `id_const` at the root of the crate, report
+    ///     the calls of `invoke` or `invoke_signed` with the `Instruction` which its construction
+    ///     dominates
+    /// - for every call of `CpiContext::new` or `CpiContext::new_with_signer`
+    ///   - find the aliases of its program account
+    ///   - if the account is the result of calling `to_account_info` on a `Program<'info, T>`,
+    ///     where `T` is a struct of the module `program` at the root of the crate, report the call
+    pub SELF_CPI,
+    Warn,
+    "a CPI into the current program, which can call the function directly",
+    SelfCpi::new()
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Config {
+    /// The paths of the functions whose self-CPIs are intended, e.g., `my_program::emit_event`
+    allowed_functions: Vec<String>,
+}
+
+struct SelfCpi {
+    config: Config,
+    mir_cache: MirCache,
+}
+
+impl SelfCpi {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+            mir_cache: MirCache::default(),
+        }
+    }
+
+    fn is_allowed_function(&self, cx: &LateContext<'_>, def_id: DefId) -> bool {
+        self.config.allowed_functions.iter().any(|path| {
+            let path = path.split("::").collect::<Vec<_>>();
+            match_def_path(cx, def_id, &path)
+        })
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for SelfCpi {
+    fn check_body(&mut self, cx: &LateContext<'tcx>, body: &'tcx Body<'tcx>) {
+        if is_skipped_expansion(SELF_CPI, body.value.span) {
+            return;
+        }
+        let body_did = cx.tcx.hir().body_owner_def_id(body.id()).to_def_id();
+        if !cx.tcx.def_kind(body_did).is_fn_like()
+            || !cx.tcx.is_mir_available(body_did)
+            || self.is_allowed_function(cx, body_did)
+        {
+            return;
+        }
+        let body_mir = cx.tcx.optimized_mir(body_did);
+        let analysis = self.mir_cache.analysis(cx, body_did);
+        for (block_id, block_data) in body_mir.basic_blocks.iter_enumerated() {
+            // `_x = Instruction { program_id: _y, .. }`
+            for stmt in &block_data.statements {
+                if_chain! {
+                    if !is_skipped_expansion(SELF_CPI, stmt.source_info.span);
+                    if let Some((instruction_place, program_id_place)) =
+                        is_instruction_init_stmt(cx, stmt);
+                    if is_self_program_id_place(
+                        cx,
+                        body_mir,
+                        analysis,
+                        block_id,
+                        &program_id_place,
+                    );
+                    then {
+                        report_invokes(
+                            cx,
+                            body_mir,
+                            analysis,
+                            block_id,
+                            &instruction_place,
+                            stmt.source_info.span,
+                        );
+                    }
+                }
+            }
+            let terminator = block_data.terminator();
+            if is_skipped_expansion(SELF_CPI, terminator.source_info.span) {
+                continue;
+            }
+            if_chain! {
+                if let TerminatorKind::Call {
+                    func: Operand::Constant(box func),
+                    args,
+                    destination,
+                    ..
+                } = &terminator.kind;
+                if let TyKind::FnDef(def_id, _) = func.const_.ty().kind();
+                if let Some(first_arg) = args.first();
+                then {
+                    // `_x = Instruction::new_with_bytes(_y, ..)`
+                    if match_any_def_paths(
+                        cx,
+                        *def_id,
+                        &[
+                            &paths::SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BINCODE,
+                            &paths::SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BORSH,
+                            &paths::SOLANA_PROGRAM_INSTRUCTION_NEW_WITH_BYTES,
+                        ],
+                    )
+                    .is_some()
+                        && is_self_program_id_operand(cx, body_mir, analysis, block_id, first_arg)
+                    {
+                        report_invokes(
+                            cx,
+                            body_mir,
+                            analysis,
+                            block_id,
+                            destination,
+                            terminator.source_info.span,
+                        );
+                    }
+                    // `CpiContext::new(_y, ..)`
+                    if_chain! {
+                        if match_any_def_paths(
+                            cx,
+                            *def_id,
+                            &[
+                                &paths::ANCHOR_CPI_CONTEXT_NEW,
+                                &paths::ANCHOR_CPI_CONTEXT_NEW_SIGNER,
+                            ],
+                        )
+                        .is_some();
+                        if let Operand::Copy(program_place) | Operand::Move(program_place) =
+                            first_arg;
+                        if is_self_program_account_info(
+                            cx,
+                            body_mir,
+                            analysis,
+                            block_id,
+                            program_place,
+                        );
+                        then {
+                            span_lint_hir_and_then(
+                                cx,
+                                SELF_CPI,
+                                mir_lint_root(body_mir, terminator.source_info),
+                                terminator.source_info.span,
+                                "this `CpiContext` calls the current program",
+                                |diag| {
+                                    diag.note(REENTRANCY_NOTE);
+                                    diag.help(allow_help(cx, body_did));
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[SELF_CPI]);
+    }
+}
+
+const REENTRANCY_NOTE: &str = "the instruction called runs while the accounts of the caller are borrowed, which makes the program reentrant";
+
+fn allow_help(cx: &LateContext<'_>, def_id: DefId) -> String {
+    format!(
+        "call the function of the instruction directly, or, if the CPI is intended, add `{}{}` to `allowed_functions` in the `self_cpi` table of `dylint.toml`",
+        cx.tcx.crate_name(def_id.krate),
+        cx.tcx.def_path(def_id).to_string_no_crate_verbose()
+    )
+}
+
+/// Report the calls of `invoke` or `invoke_signed` with the `Instruction` at `instruction_place`,
+/// built with the ID of the current program at `construction_span`, which `block` dominates
+fn report_invokes<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    analysis: &BodyAnalysis,
+    block: BasicBlock,
+    instruction_place: &Place<'tcx>,
+    construction_span: Span,
+) {
+    if !instruction_place.projection.is_empty() {
+        return;
+    }
+    let instruction_locals = analysis.reference_locals(instruction_place.local);
+    let dominators = body.basic_blocks.dominators();
+    for invoke_block in instruction_invoke_blocks(cx, body, &instruction_locals) {
+        let source_info = body.basic_blocks[invoke_block].terminator().source_info;
+        if dominators.dominates(block, invoke_block)
+            && !is_skipped_expansion(SELF_CPI, source_info.span)
+        {
+            span_lint_hir_and_then(
+                cx,
+                SELF_CPI,
+                mir_lint_root(body, source_info),
+                source_info.span,
+                "this CPI calls the current program",
+                |diag| {
+                    diag.span_note(
+                        construction_span,
+                        "the `Instruction` is built with the ID of the current program here",
+                    );
+                    diag.note(REENTRANCY_NOTE);
+                    diag.help(allow_help(cx, body.source.def_id()));
+                },
+            );
+        }
+    }
+}
+
+/// Return true if `operand`, the program ID of an `Instruction` built in `block`, is the ID of the
+/// current program
+fn is_self_program_id_operand<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    analysis: &BodyAnalysis,
+    block: BasicBlock,
+    operand: &Operand<'tcx>,
+) -> bool {
+    match operand {
+        Operand::Copy(place) | Operand::Move(place) => {
+            is_self_program_id_place(cx, body, analysis, block, place)
+        }
+        Operand::Constant(_) => is_self_id_operand(cx, operand),
+    }
+}
+
+/// Return true if `place`, the program ID of an `Instruction` built in `block`, is assigned the ID
+/// of the current program, i.e., if one of its aliases is `ctx.program_id`, is assigned the ID
+/// declared with `declare_id!`, or the result of calling its `id` function
+fn is_self_program_id_place<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    analysis: &BodyAnalysis,
+    block: BasicBlock,
+    place: &Place<'tcx>,
+) -> bool {
+    let aliases = analysis.find_place_aliases(cx, body, block, place);
+    if aliases
+        .iter()
+        .any(|alias| is_context_program_id(cx, body, alias))
+    {
+        return true;
+    }
+    let is_alias = |place: &Place<'tcx>| aliases.iter().any(|alias| is_same_place(place, alias));
+    body.basic_blocks.iter().any(|block_data| {
+        block_data.statements.iter().any(|stmt| {
+            matches!(
+                &stmt.kind,
+                StatementKind::Assign(box (place, Rvalue::Use(operand)))
+                    if is_alias(place) && is_self_id_operand(cx, operand)
+            )
+        }) || match &block_data.terminator().kind {
+            TerminatorKind::Call {
+                func: Operand::Constant(box func),
+                destination,
+                ..
+            } if is_alias(destination) => match func.const_.ty().kind() {
+                TyKind::FnDef(def_id, _) => is_self_id(cx, *def_id),
+                _ => false,
+            },
+            _ => false,
+        }
+    })
+}
+
+/// Return true if `place` is the `program_id` field of an Anchor `Context`, e.g., `ctx.program_id`
+fn is_context_program_id<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    place: &Place<'tcx>,
+) -> bool {
+    if_chain! {
+        if let Some((base, ProjectionElem::Field(field, _))) =
+            place.iter_projections().last();
+        let base_ty = base.ty(body, cx.tcx).ty.peel_refs();
+        if match_type(cx, base_ty, &paths::ANCHOR_LANG_CONTEXT);
+        if let ty::Adt(adt_def, _) = base_ty.kind();
+        then {
+            adt_def.non_enum_variant().fields[field].name.as_str() == "program_id"
+        } else {
+            false
+        }
+    }
+}
+
+/// Return true if `operand` is the ID of the current program, i.e., the `ID` static or the
+/// `ID_CONST` constant declared with `declare_id!`, before it is evaluated
+fn is_self_id_operand(cx: &LateContext<'_>, operand: &Operand<'_>) -> bool {
+    match operand {
+        Operand::Constant(constant) => match constant.const_ {
+            mir::Const::Unevaluated(unevaluated, _) => is_self_id(cx, unevaluated.def),
+            _ => constant
+                .check_static_ptr(cx.tcx)
+                .map_or(false, |def_id| is_self_id(cx, def_id)),
+        },
+        _ => false,
+    }
+}
+
+/// Return true if `def_id` is one of the items declared by `declare_id!` at the root of the crate:
+/// `ID`, `ID_CONST`, `id` or `id_const`
+fn is_self_id(cx: &LateContext<'_>, def_id: DefId) -> bool {
+    def_id.is_local()
+        && cx.tcx.opt_parent(def_id) == Some(CRATE_DEF_ID.to_def_id())
+        && cx.tcx.opt_item_name(def_id).map_or(false, |name| {
+            matches!(name.as_str(), "ID" | "ID_CONST" | "id" | "id_const")
+        })
+}
+
+/// Return true if `program_place`, the program account of a `CpiContext` built in `block`, is the
+/// result of calling `to_account_info` on the `Program` account of the current program
+fn is_self_program_account_info<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    analysis: &BodyAnalysis,
+    block: BasicBlock,
+    program_place: &Place<'tcx>,
+) -> bool {
+    let program_aliases = analysis.find_place_aliases(cx, body, block, program_place);
+    // As in `arbitrary_cpi`, the call of `to_account_info` assigns to the last alias
+    let program = match program_aliases.last() {
+        Some(program) => program,
+        None => return false,
+    };
+    body.basic_blocks.iter().any(|block_data| {
+        if_chain! {
+            if let TerminatorKind::Call {
+                func: Operand::Constant(box func),
+                destination,
+                args,
+                ..
+            } = &block_data.terminator().kind;
+            if is_same_place(destination, program);
+            if let TyKind::FnDef(def_id, _) = func.const_.ty().kind();
+            if match_def_path(cx, *def_id, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO);
+            if let Some(Operand::Copy(arg0_pl) | Operand::Move(arg0_pl)) = args.first();
+            then {
+                is_self_program_account_ty(cx, arg0_pl.ty(body, cx.tcx).ty.peel_refs())
+            } else {
+                false
+            }
+        }
+    })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
+
+#[test]
+fn secure_allowed() {
+    dylint_testing::ui::Test::example(env!("CARGO_PKG_NAME"), "secure-allowed")
+        .dylint_toml("self_cpi.allowed_functions = [\"secure_allowed::emit_through_cpi\"]")
+        .run();
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use anchor_lang::InstructionData;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod self_cpi_insecure {
+    use super::*;
+
+    pub fn settle(ctx: Context<Settle>) -> Result<()> {
+        let ix = Instruction {
+            program_id: *ctx.program_id,
+            accounts: vec![AccountMeta::new(ctx.accounts.vault.key(), false)],
+            data: crate::instruction::Finalize {}.data(),
+        };
+        invoke(&ix, &[ctx.accounts.vault.to_account_info()])?;
+        Ok(())
+    }
+
+    pub fn settle_with_id(ctx: Context<Settle>) -> Result<()> {
+        let ix = Instruction::new_with_bytes(
+            crate::id(),
+            &crate::instruction::Finalize {}.data(),
+            vec![AccountMeta::new(ctx.accounts.vault.key(), false)],
+        );
+        invoke(&ix, &[ctx.accounts.vault.to_account_info()])?;
+        Ok(())
+    }
+
+    pub fn settle_through_program(ctx: Context<SettleThroughProgram>) -> Result<()> {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.this_program.to_account_info(),
+            Finalize {
+                vault: ctx.accounts.vault.clone(),
+            },
+        );
+        let ix = Instruction {
+            program_id: cpi_ctx.program.key(),
+            accounts: cpi_ctx.to_account_metas(None),
+            data: crate::instruction::Finalize {}.data(),
+        };
+        invoke(&ix, &cpi_ctx.to_account_infos())?;
+        Ok(())
+    }
+
+    pub fn finalize(_ctx: Context<Finalize>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    /// CHECK: the vault is only passed to `finalize`
+    #[account(mut)]
+    vault: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleThroughProgram<'info> {
+    /// CHECK: the vault is only passed to `finalize`
+    #[account(mut)]
+    vault: AccountInfo<'info>,
+    this_program: Program<'info, program::SelfCpiInsecure>,
+}
+
+#[derive(Accounts)]
+pub struct Finalize<'info> {
+    /// CHECK: the vault is not read
+    #[account(mut)]
+    vault: AccountInfo<'info>,
+}
+
+fn main() {}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use anchor_lang::InstructionData;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod self_cpi_secure_allowed {
+    use super::*;
+
+    pub fn settle(ctx: Context<Settle>) -> Result<()> {
+        emit_through_cpi(&ctx.accounts.vault)
+    }
+
+    pub fn finalize(_ctx: Context<Settle>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Call `finalize` through a CPI, so that it appears as an inner instruction of the transaction
+pub fn emit_through_cpi(vault: &AccountInfo) -> Result<()> {
+    let ix = Instruction {
+        program_id: crate::id(),
+        accounts: vec![AccountMeta::new(vault.key(), false)],
+        data: crate::instruction::Finalize {}.data(),
+    };
+    invoke(&ix, &[vault.clone()])?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    /// CHECK: the vault is not read
+    #[account(mut)]
+    vault: AccountInfo<'info>,
+}
+
+fn main() {}
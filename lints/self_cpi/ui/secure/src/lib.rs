@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use anchor_lang::InstructionData;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod self_cpi_secure {
+    use super::*;
+
+    pub fn settle(ctx: Context<Settle>) -> Result<()> {
+        finalize_vault(&ctx.accounts.vault)?;
+        emit_cpi!(Settled {
+            vault: ctx.accounts.vault.key(),
+        });
+        Ok(())
+    }
+
+    pub fn forward(ctx: Context<Forward>, data: Vec<u8>) -> Result<()> {
+        let ix = Instruction {
+            program_id: ctx.accounts.target_program.key(),
+            accounts: vec![AccountMeta::new(ctx.accounts.vault.key(), false)],
+            data,
+        };
+        invoke(
+            &ix,
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.target_program.to_account_info(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+        finalize_vault(&ctx.accounts.vault)
+    }
+}
+
+fn finalize_vault(_vault: &AccountInfo) -> Result<()> {
+    Ok(())
+}
+
+/// Build the `finalize` instruction, for the clients of the program
+pub fn finalize_instruction(vault: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![AccountMeta::new(vault, false)],
+        data: crate::instruction::Finalize {}.data(),
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    /// CHECK: the vault is not read
+    #[account(mut)]
+    vault: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Forward<'info> {
+    /// CHECK: the vault is not read
+    #[account(mut)]
+    vault: AccountInfo<'info>,
+    target_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Finalize<'info> {
+    /// CHECK: the vault is not read
+    #[account(mut)]
+    vault: AccountInfo<'info>,
+}
+
+#[event]
+pub struct Settled {
+    vault: Pubkey,
+}
+
+fn main() {}
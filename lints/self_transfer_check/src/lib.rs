@@ -0,0 +1,318 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::AccountsStruct;
+use clippy_utils::{source::snippet, SpanlessEq};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    BinOpKind, Body, Expr, ExprKind, FnDecl, HirId, Item,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{
+        anchor_constraints::compares_field_keys, compares_keys_of, followed_accounts,
+        get_anchor_accounts_struct, is_expr_method_call, lamports_account, local_struct_field,
+        visit_expr_no_bodies,
+    },
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the transfers of lamports written by hand, i.e., a decrease of the lamports of an
+    /// account followed by an increase of the lamports of another account by the same amount,
+    /// where nothing checks that the two accounts are different:
+    ///
+    /// - `**from.lamports.borrow_mut() -= amount; **to.lamports.borrow_mut() += amount`, or the
+    ///   same with `try_borrow_mut_lamports()?`
+    /// - `from.sub_lamports(amount)?; to.add_lamports(amount)?`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A caller may pass the same account as the source and the destination. The transfer then
+    /// moves no lamports, while the instruction proceeds as if it did, e.g., it credits the
+    /// caller with a deposit, or records a payment, which duplicates value or breaks the
+    /// accounting of the program. When the balances are read before being written, the second
+    /// write also overwrites the first.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The decrease and the increase are paired by their amounts, so a transfer whose amount is
+    /// split, e.g., between the destination and a fee, is not reported, nor is a transfer written
+    /// as assignments of the new balances. Accounts which are different by construction, e.g.,
+    /// the PDAs of different seeds, are reported unless their keys are compared.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn transfer(ctx: Context<Transfer>, amount: u64) -> Result<()> {
+    ///     **ctx.accounts.source.try_borrow_mut_lamports()? -= amount;
+    ///     **ctx.accounts.destination.try_borrow_mut_lamports()? += amount;
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// pub fn transfer(ctx: Context<Transfer>, amount: u64) -> Result<()> {
+    ///     require_keys_neq!(ctx.accounts.source.key(), ctx.accounts.destination.key());
+    ///     **ctx.accounts.source.try_borrow_mut_lamports()? -= amount;
+    ///     **ctx.accounts.destination.try_borrow_mut_lamports()? += amount;
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// or add the constraint `constraint = source.key() != destination.key()` to the field
+    /// `source` of the `Accounts` struct.
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item: Collect Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - collect the decreases and the increases of the lamports of accounts, with their amounts
+    /// - for each decrease, find the first increase of the same amount of another account
+    ///   - follow both accounts through borrows, derefs, calls to `to_account_info` and the
+    ///     initializers of local `AccountInfo` variables
+    ///   - if the function compares the key of one of the accounts followed from the source with
+    ///     the key of one of the accounts followed from the destination, ignore the transfer
+    ///   - else record the transfer, and the fields of the last accounts if they are fields of
+    ///     the same local struct
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each recorded transfer
+    ///   - if its fields are fields of an Anchor `Accounts` struct, one of whose `constraint`s
+    ///     compares their keys, ignore the transfer
+    ///   - else report the decrease, noting the increase
+    pub SELF_TRANSFER_CHECK,
+    Warn,
+    "transfers of lamports between two accounts which are not checked to be different",
+    SelfTransferCheck::default()
+}
+
+#[derive(Default)]
+struct SelfTransferCheck {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    /// The transfers whose accounts are not compared by their functions, in the order they are
+    /// found
+    transfers: Vec<LamportTransfer>,
+}
+
+/// A decrease of the lamports of an account and the increase by the same amount of the lamports
+/// of another account
+struct LamportTransfer {
+    hir_id: HirId,
+    span: Span,
+    credit_span: Span,
+    /// The source code of the two accounts, e.g., `ctx.accounts.source`
+    from: String,
+    to: String,
+    /// The struct and the names of the fields holding the two accounts, if they are fields of the
+    /// same local struct, e.g., `(Transfer, "source", "destination")`
+    fields: Option<(DefId, String, String)>,
+}
+
+/// A change of the lamports of an account
+struct LamportChange<'tcx> {
+    expr: &'tcx Expr<'tcx>,
+    account: &'tcx Expr<'tcx>,
+    amount: &'tcx Expr<'tcx>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for SelfTransferCheck {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(SELF_TRANSFER_CHECK, span) {
+            return;
+        }
+        let mut debits = Vec::new();
+        let mut credits = Vec::new();
+        visit_expr_no_bodies(body.value, |expr| {
+            if !is_skipped_expansion(SELF_TRANSFER_CHECK, expr.span) {
+                if let Some(debit) = lamport_change(cx, expr, BinOpKind::Sub) {
+                    debits.push(debit);
+                } else if let Some(credit) = lamport_change(cx, expr, BinOpKind::Add) {
+                    credits.push(credit);
+                }
+            }
+            false
+        });
+        for debit in &debits {
+            let mut spanless_eq = SpanlessEq::new(cx);
+            let credit = match credits.iter().find(|credit| {
+                spanless_eq.eq_expr(debit.amount, credit.amount)
+                    && !spanless_eq.eq_expr(debit.account, credit.account)
+            }) {
+                Some(credit) => credit,
+                None => continue,
+            };
+            let from_accounts = followed_accounts(cx, debit.account);
+            let to_accounts = followed_accounts(cx, credit.account);
+            let compares_keys = visit_expr_no_bodies(body.value, |expr| {
+                from_accounts.iter().any(|from| {
+                    to_accounts
+                        .iter()
+                        .any(|to| compares_keys_of(cx, expr, from, to))
+                })
+            });
+            if compares_keys {
+                continue;
+            }
+            let fields = match (
+                from_accounts
+                    .last()
+                    .and_then(|from| local_struct_field(cx, from)),
+                to_accounts.last().and_then(|to| local_struct_field(cx, to)),
+            ) {
+                (Some((def_id, from_field)), Some((to_def_id, to_field)))
+                    if def_id == to_def_id =>
+                {
+                    Some((def_id, from_field, to_field))
+                }
+                _ => None,
+            };
+            self.transfers.push(LamportTransfer {
+                hir_id: debit.expr.hir_id,
+                span: debit.expr.span,
+                credit_span: credit.expr.span,
+                from: snippet(cx, debit.account.span, "..").into_owned(),
+                to: snippet(cx, credit.account.span, "..").into_owned(),
+                fields,
+            });
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for transfer in &self.transfers {
+            if let Some((def_id, from_field, to_field)) = &transfer.fields {
+                if self
+                    .anchor_accounts
+                    .get(def_id)
+                    .map_or(false, |accounts_struct| {
+                        compares_field_keys(accounts_struct, from_field, to_field)
+                    })
+                {
+                    continue;
+                }
+            }
+            span_lint_hir_and_then(
+                cx,
+                SELF_TRANSFER_CHECK,
+                transfer.hir_id,
+                transfer.span,
+                &format!(
+                    "lamports are transferred from `{}` to `{}`, which are not checked to be different accounts",
+                    transfer.from, transfer.to
+                ),
+                |diag| {
+                    diag.span_note(transfer.credit_span, "the lamports are credited here");
+                    diag.note(
+                        "if the same account is passed for both, the transfer moves no lamports, while the instruction proceeds as if it did",
+                    );
+                    match &transfer.fields {
+                        Some((def_id, from_field, to_field))
+                            if self.anchor_accounts.contains_key(def_id) =>
+                        {
+                            diag.help(format!(
+                                "add the constraint `constraint = {from_field}.key() != {to_field}.key()` to the field `{from_field}`, or compare the keys with `require_keys_neq!`"
+                            ));
+                        }
+                        _ => {
+                            diag.help("check that the keys of the two accounts are different before the transfer");
+                        }
+                    }
+                },
+            );
+        }
+
+        sarif::write(cx, &[SELF_TRANSFER_CHECK]);
+    }
+}
+
+/// If `expr` changes the lamports of an account, decreasing them if `op` is `Sub` and increasing
+/// them if `op` is `Add`, return the account and the amount:
+/// - `**x.lamports.borrow_mut() -= a` and `**x.try_borrow_mut_lamports()? -= a`, or `+=`
+/// - `x.sub_lamports(a)` or `x.add_lamports(a)`
+fn lamport_change<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    op: BinOpKind,
+) -> Option<LamportChange<'tcx>> {
+    match expr.kind {
+        ExprKind::AssignOp(assign_op, lhs, amount) if assign_op.node == op => {
+            lamports_account(cx, lhs).map(|account| LamportChange {
+                expr,
+                account,
+                amount,
+            })
+        }
+        ExprKind::MethodCall(_, _, [amount], _) => {
+            let path = if op == BinOpKind::Sub {
+                &paths::ANCHOR_LANG_SUB_LAMPORTS
+            } else {
+                &paths::ANCHOR_LANG_ADD_LAMPORTS
+            };
+            if_chain! {
+                if let Some(account) = is_expr_method_call(cx, expr, path);
+                then {
+                    Some(LamportChange {
+                        expr,
+                        account,
+                        amount,
+                    })
+                } else {
+                    None
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
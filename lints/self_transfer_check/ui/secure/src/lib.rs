@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::account_info::next_account_info;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod self_transfer_check_secure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        require_keys_neq!(ctx.accounts.vault.key(), ctx.accounts.recipient.key());
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
+        ctx.accounts.state.withdrawn += amount;
+        Ok(())
+    }
+
+    pub fn sweep(ctx: Context<Sweep>, amount: u64) -> Result<()> {
+        ctx.accounts.source.sub_lamports(amount)?;
+        ctx.accounts.treasury.add_lamports(amount)?;
+        Ok(())
+    }
+}
+
+pub fn transfer_lamports(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let source = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    if source.key == destination.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    **source.lamports.borrow_mut() -= amount;
+    **destination.lamports.borrow_mut() += amount;
+    Ok(())
+}
+
+#[account]
+pub struct State {
+    withdrawn: u64,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    state: Account<'info, State>,
+    /// CHECK: a PDA of the program holding the lamports
+    #[account(mut, seeds = [b"vault"], bump)]
+    vault: AccountInfo<'info>,
+    /// CHECK: only lamports are transferred to the recipient
+    #[account(mut)]
+    recipient: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Sweep<'info> {
+    #[account(mut, owner = crate::ID, constraint = source.key() != treasury.key())]
+    source: Account<'info, State>,
+    #[account(mut, owner = crate::ID)]
+    treasury: Account<'info, State>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,288 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::{AccountField, Field, InitKind};
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::{HirId, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    sarif,
+    seeds::{display_seeds, field_seeds, seed, Seed},
+    utils::get_anchor_accounts_struct,
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports PDAs which are used both as the authority of user vaults and for protocol fees. A
+    /// PDA is identified by its seeds, so the lint reports the seeds which are:
+    ///
+    /// - the `token::authority` or `associated_token::authority` of a token account, e.g., a vault
+    ///   holding user deposits
+    /// - and the authority of a fee account, or a fee account themselves. Fee accounts are the
+    ///   accounts whose names contain `fee`, `fees` or `treasury`, e.g., `fee_vault`.
+    ///
+    /// The lint reports each seed tuple once, with one of the vaults and one of the fee accounts.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A single authority couples the user funds and the protocol funds: every instruction which
+    /// signs for the fee accounts can move the user funds as well, and a bug in the fee collection
+    /// puts the deposits at risk. Nor can the funds be moved to separate authorities, e.g., during
+    /// an upgrade, without migrating all of the vaults.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The fee accounts are recognized by their names only. The seeds are compared by the accounts
+    /// they name, e.g., `pool.key().as_ref()` is the seed `pool`, so a PDA which two instructions
+    /// derive from differently named accounts is regarded as two PDAs.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Deposit<'info> {
+    ///     #[account(seeds = [b"authority", pool.key().as_ref()], bump)]
+    ///     authority: SystemAccount<'info>,
+    ///     #[account(mut, token::authority = authority)]
+    ///     vault: Account<'info, TokenAccount>,
+    ///     ...
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct CollectFees<'info> {
+    ///     #[account(seeds = [b"authority", pool.key().as_ref()], bump)]
+    ///     authority: SystemAccount<'info>,
+    ///     #[account(mut, token::authority = authority)]
+    ///     fee_vault: Account<'info, TokenAccount>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct CollectFees<'info> {
+    ///     #[account(seeds = [b"fee_authority", pool.key().as_ref()], bump)]
+    ///     fee_authority: SystemAccount<'info>,
+    ///     #[account(mut, token::authority = fee_authority)]
+    ///     fee_vault: Account<'info, TokenAccount>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - For each Anchor `Accounts` struct
+    ///   - Collect the seeds of the fields with the `seeds` constraint (see `solana_lints::seeds`)
+    ///   - For each field with a `token::authority` or `associated_token::authority` constraint, including
+    ///     the ones of `init`, naming a field with seeds
+    ///     - If the field is a fee account, record the seeds as the authority of a fee account
+    ///     - Else record the seeds as the authority of a vault
+    ///   - For each field with seeds which is a fee account, record the seeds as a fee account
+    ///
+    /// check_crate_post:
+    ///
+    /// - For each seed tuple, in the order they are found
+    ///   - If the seeds are the authority of a vault, and the authority of a fee account or a fee
+    ///     account themselves, report the first vault and note the first fee account
+    pub SHARED_VAULT_FEE_AUTHORITY,
+    Warn,
+    "PDAs which are the authority of both user vaults and protocol fee accounts",
+    SharedVaultFeeAuthority::default()
+}
+
+#[derive(Default)]
+struct SharedVaultFeeAuthority {
+    /// The uses of the PDAs, in the order they are found
+    uses: Vec<PdaUse>,
+}
+
+/// A use of the PDA with the seeds `seeds` by the `Accounts` struct field `account`
+struct PdaUse {
+    seeds: Vec<Seed>,
+    kind: UseKind,
+    account: String,
+    hir_id: HirId,
+    span: Span,
+}
+
+enum UseKind {
+    /// The PDA is the authority of the token account
+    VaultAuthority,
+    /// The PDA is the authority of the fee account
+    FeeAuthority,
+    /// The PDA is the fee account
+    FeeAccount,
+}
+
+impl<'tcx> LateLintPass<'tcx> for SharedVaultFeeAuthority {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                // Composite fields are checked when their own struct is checked.
+                let fields = variant
+                    .fields()
+                    .iter()
+                    .zip(accounts_struct.fields.iter())
+                    .filter_map(|(item_field, anchor_field)| match anchor_field {
+                        AccountField::Field(field) => Some((item_field, field)),
+                        AccountField::CompositeField(_) => None,
+                    })
+                    .collect::<Vec<_>>();
+
+                let pda_seeds = fields
+                    .iter()
+                    .filter_map(|(_, field)| Some((field.ident.to_string(), field_seeds(field)?)))
+                    .collect::<FxHashMap<_, _>>();
+
+                for (item_field, field) in fields {
+                    let account = field.ident.to_string();
+                    let is_fee_account = is_fee_account(&account);
+                    if_chain! {
+                        if let Some(authority) = token_authority(field);
+                        if let Some(Seed::Expr(authority)) = seed(authority);
+                        if let Some(seeds) = pda_seeds.get(&authority);
+                        then {
+                            self.uses.push(PdaUse {
+                                seeds: seeds.clone(),
+                                kind: if is_fee_account {
+                                    UseKind::FeeAuthority
+                                } else {
+                                    UseKind::VaultAuthority
+                                },
+                                account: account.clone(),
+                                hir_id: item_field.hir_id,
+                                span: item_field.span,
+                            });
+                        }
+                    }
+                    if_chain! {
+                        if is_fee_account;
+                        if let Some(seeds) = pda_seeds.get(&account);
+                        then {
+                            self.uses.push(PdaUse {
+                                seeds: seeds.clone(),
+                                kind: UseKind::FeeAccount,
+                                account,
+                                hir_id: item_field.hir_id,
+                                span: item_field.span,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        let mut seed_tuples: Vec<&[Seed]> = Vec::new();
+        for pda_use in &self.uses {
+            if !seed_tuples.contains(&pda_use.seeds.as_slice()) {
+                seed_tuples.push(&pda_use.seeds);
+            }
+        }
+
+        for seeds in seed_tuples {
+            let mut uses = self.uses.iter().filter(|pda_use| pda_use.seeds == seeds);
+            if_chain! {
+                if let Some(vault_use) = uses
+                    .clone()
+                    .find(|pda_use| matches!(pda_use.kind, UseKind::VaultAuthority));
+                if let Some(fee_use) =
+                    uses.find(|pda_use| !matches!(pda_use.kind, UseKind::VaultAuthority));
+                then {
+                    span_lint_hir_and_then(
+                        cx,
+                        SHARED_VAULT_FEE_AUTHORITY,
+                        vault_use.hir_id,
+                        vault_use.span,
+                        &format!(
+                            "the PDA with the seeds `{}` is the authority of `{}` and is used for protocol fees",
+                            display_seeds(seeds),
+                            vault_use.account
+                        ),
+                        |diag| {
+                            let note = match fee_use.kind {
+                                UseKind::FeeAccount => {
+                                    format!("the PDA is the fee account `{}` here", fee_use.account)
+                                }
+                                UseKind::FeeAuthority | UseKind::VaultAuthority => format!(
+                                    "the PDA is the authority of the fee account `{}` here",
+                                    fee_use.account
+                                ),
+                            };
+                            diag.span_note(fee_use.span, note);
+                            diag.help(
+                                "derive the authority of the fee accounts from different seeds than the authority of the vaults",
+                            );
+                        },
+                    );
+                }
+            }
+        }
+
+        sarif::write(cx, &[SHARED_VAULT_FEE_AUTHORITY]);
+    }
+}
+
+/// Return the expression of the `token::authority` or `associated_token::authority` constraint of
+/// the field, including the one of `init`
+fn token_authority(field: &Field) -> Option<&syn::Expr> {
+    if let Some(init) = &field.constraints.init {
+        if let InitKind::Token { owner, .. } | InitKind::AssociatedToken { owner, .. } = &init.kind
+        {
+            return Some(owner);
+        }
+    }
+    field
+        .constraints
+        .token_account
+        .as_ref()
+        .and_then(|token_account| token_account.authority.as_ref())
+        .or_else(|| {
+            field
+                .constraints
+                .associated_token
+                .as_ref()
+                .map(|associated_token| &associated_token.wallet)
+        })
+}
+
+/// Return true if the name of the account contains `fee`, `fees` or `treasury`, e.g., `fee_vault`
+fn is_fee_account(name: &str) -> bool {
+    name.to_lowercase()
+        .split('_')
+        .any(|word| matches!(word, "fee" | "fees" | "treasury"))
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod shared_vault_fee_authority_secure {
+    use super::*;
+
+    pub fn deposit(_ctx: Context<Deposit>, _amount: u64) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn init_fee_vault(_ctx: Context<InitFeeVault>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn lock(_ctx: Context<Lock>, _amount: u64) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn pay_fee(_ctx: Context<PayFee>, _amount: u64) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    pool: Account<'info, Pool>,
+    #[account(seeds = [b"authority", pool.key().as_ref()], bump)]
+    authority: SystemAccount<'info>,
+    #[account(mut, token::authority = authority)]
+    vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    user_token: Account<'info, TokenAccount>,
+    user: Signer<'info>,
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitFeeVault<'info> {
+    pool: Account<'info, Pool>,
+    #[account(seeds = [b"fee_authority", pool.key().as_ref()], bump)]
+    fee_authority: SystemAccount<'info>,
+    mint: Account<'info, Mint>,
+    #[account(init, payer = admin, token::mint = mint, token::authority = fee_authority)]
+    fee_vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    admin: Signer<'info>,
+    token_program: Program<'info, Token>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Lock<'info> {
+    #[account(seeds = [b"escrow"], bump)]
+    escrow_authority: SystemAccount<'info>,
+    #[account(mut, associated_token::mint = mint, associated_token::authority = escrow_authority)]
+    escrow: Account<'info, TokenAccount>,
+    mint: Account<'info, Mint>,
+    user: Signer<'info>,
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct PayFee<'info> {
+    #[account(mut, seeds = [b"treasury"], bump)]
+    treasury: SystemAccount<'info>,
+    #[account(mut)]
+    user: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct Pool {
+    mint: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
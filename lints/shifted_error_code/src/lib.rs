@@ -0,0 +1,290 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+
+use rustc_hir::{def_id::DefId, Item};
+use rustc_lint::{LateContext, LateLintPass};
+use solana_lints::{
+    bless::{is_blessing, write_lock_or_warn},
+    diagnostics::span_lint_hir_and_then,
+    error_codes::{
+        compare, error_code_enum, error_codes, lock_path, read_lock, write_lock, Change,
+        ErrorCodes, LOCK_FILE,
+    },
+    sarif,
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the variants of the Anchor `#[error_code]` enums of the crate whose error codes
+    /// changed since they were recorded in the `error_codes.lock` file of the package (see
+    /// `solana_lints::error_codes`), i.e., the variants which were moved, e.g., by inserting or
+    /// removing a variant before them, or whose enum has another offset, and the variants which
+    /// were removed. Also reports the variants with the same error code as a variant of another
+    /// enum of the crate, e.g., of two enums without an offset.
+    ///
+    /// If `SOLANA_LINTS_BLESS` is set (see `solana_lints::bless`), the lint records the error codes
+    /// of the enums of the crate in `error_codes.lock` instead, so that they are compared with the
+    /// codes of the next runs. The file should be committed, and updated the same way with the new
+    /// variants once they are released. A package without the file is only checked for collisions.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A transaction failing with an error of an `#[error_code]` enum returns the code of the
+    /// error, i.e., the position of the variant in the enum plus the offset of the enum, which is
+    /// 6000 by default. The clients, the IDLs and the monitors of the program identify the errors
+    /// by these codes. After an upgrade moving a variant, they report the wrong errors, e.g., a
+    /// slippage failure as an unauthorized access, and the alerts watching for an error miss it.
+    /// The errors of two enums with the same codes cannot be told apart.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The enums and their variants are recorded by name, so a renamed enum is treated as a new
+    /// enum, and a renamed variant as a removed variant. The codes of the enums of the
+    /// dependencies, e.g., of a shared errors crate, are not compared with the codes of the crate.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// // error_codes.lock records `ErrorCode` with `6000 Unauthorized` and `6001 SlippageExceeded`
+    /// #[error_code]
+    /// pub enum ErrorCode {
+    ///     InvalidAmount,
+    ///     Unauthorized,
+    ///     SlippageExceeded,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[error_code]
+    /// pub enum ErrorCode {
+    ///     Unauthorized,
+    ///     SlippageExceeded,
+    ///     InvalidAmount,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - record the enums for which `#[error_code]` generates an `impl From<E> for u32`, and their
+    ///   offsets, i.e., the constants the `from` functions add to the discriminants
+    ///
+    /// check_crate_post:
+    ///
+    /// - compute the error code of each variant of the recorded enums
+    /// - for each enum, report the first variant whose code is also the code of a variant of an
+    ///   enum recorded before it
+    /// - if `SOLANA_LINTS_BLESS` is set, write the codes to `error_codes.lock`
+    /// - else, if `error_codes.lock` exists, for each enum recorded in it, report the recorded
+    ///   variants whose codes changed, and those which were removed
+    pub SHIFTED_ERROR_CODE,
+    Warn,
+    "Anchor error enums whose error codes collide, or changed since `error_codes.lock`",
+    ShiftedErrorCode::default()
+}
+
+#[derive(Default)]
+struct ShiftedErrorCode {
+    /// The `#[error_code]` enums of the crate and their offsets, in the order they are found
+    error_enums: Vec<(DefId, u32)>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for ShiftedErrorCode {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some((def_id, offset)) = error_code_enum(cx, item) {
+            if def_id.is_local() {
+                self.error_enums.push((def_id, offset));
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        let error_enums = self
+            .error_enums
+            .iter()
+            .map(|&(def_id, offset)| (def_id, error_codes(cx, def_id, offset)))
+            .collect::<Vec<_>>();
+
+        check_collisions(cx, &error_enums);
+
+        if let Some(path) = lock_path(cx).filter(|_| !error_enums.is_empty()) {
+            if is_blessing() {
+                let entries = error_enums
+                    .iter()
+                    .map(|(def_id, codes)| (cx.tcx.item_name(*def_id).to_string(), codes.clone()))
+                    .collect::<Vec<_>>();
+                write_lock_or_warn(cx, "error codes", &path, |path| write_lock(path, &entries));
+            } else if path.exists() {
+                match read_lock(&path) {
+                    Ok(entries) => check_lock(cx, &error_enums, &entries),
+                    Err(error) => cx.tcx.sess.dcx().warn(format!(
+                        "could not read the error codes file `{}`: {error}",
+                        path.display()
+                    )),
+                }
+            }
+        }
+
+        sarif::write(cx, &[SHIFTED_ERROR_CODE]);
+    }
+}
+
+/// Report, for each enum, the first variant whose code is also the code of a variant of an enum
+/// found before it
+fn check_collisions(cx: &LateContext<'_>, error_enums: &[(DefId, ErrorCodes)]) {
+    for (i, (def_id, codes)) in error_enums.iter().enumerate() {
+        for (other_def_id, other_codes) in &error_enums[..i] {
+            let collision = codes.iter().find_map(|(code, variant)| {
+                other_codes
+                    .iter()
+                    .find(|(other_code, _)| other_code == code)
+                    .map(|(_, other_variant)| (code, variant, other_variant))
+            });
+            let (code, variant, other_variant) = match collision {
+                Some(collision) => collision,
+                None => continue,
+            };
+            let variant_def_id = match find_variant(cx, *def_id, variant) {
+                Some(variant_def_id) => variant_def_id,
+                None => continue,
+            };
+            let name = cx.tcx.item_name(*def_id);
+            let other_name = cx.tcx.item_name(*other_def_id);
+            let other_span = find_variant(cx, *other_def_id, other_variant)
+                .map(|other_variant_def_id| cx.tcx.def_span(other_variant_def_id));
+            span_lint_hir_and_then(
+                cx,
+                SHIFTED_ERROR_CODE,
+                cx.tcx.local_def_id_to_hir_id(variant_def_id.expect_local()),
+                cx.tcx.def_span(variant_def_id),
+                &format!(
+                    "the error code {code} of `{name}::{variant}` is also the code of `{other_name}::{other_variant}`"
+                ),
+                |diag| {
+                    if let Some(other_span) = other_span {
+                        diag.span_note(
+                            other_span,
+                            format!("`{other_name}::{other_variant}` is defined here"),
+                        );
+                    }
+                    diag.help(format!(
+                        "give `{name}` an offset past the codes of `{other_name}`, with `#[error_code(offset = ...)]`, so that their errors can be told apart"
+                    ));
+                },
+            );
+        }
+    }
+}
+
+/// Report the variants of the enums recorded in the lock file whose codes changed since, or which
+/// were removed
+fn check_lock(
+    cx: &LateContext<'_>,
+    error_enums: &[(DefId, ErrorCodes)],
+    entries: &[(String, ErrorCodes)],
+) {
+    for (def_id, codes) in error_enums {
+        let name = cx.tcx.item_name(*def_id);
+        let recorded = match entries
+            .iter()
+            .find(|(recorded_name, _)| recorded_name == name.as_str())
+        {
+            Some((_, recorded)) => recorded,
+            // a new enum, whose errors were not released yet
+            None => continue,
+        };
+        let reused = |code: u32| {
+            codes
+                .iter()
+                .find(|(current_code, _)| *current_code == code)
+                .map(|(_, variant)| format!("{code} is now the code of `{name}::{variant}`"))
+        };
+        for change in compare(recorded, codes) {
+            match change {
+                Change::Shifted {
+                    variant,
+                    recorded,
+                    current,
+                } => {
+                    let variant_def_id = match find_variant(cx, *def_id, &variant) {
+                        Some(variant_def_id) => variant_def_id,
+                        None => continue,
+                    };
+                    let note = reused(recorded);
+                    span_lint_hir_and_then(
+                        cx,
+                        SHIFTED_ERROR_CODE,
+                        cx.tcx.local_def_id_to_hir_id(variant_def_id.expect_local()),
+                        cx.tcx.def_span(variant_def_id),
+                        &format!(
+                            "the error code of `{name}::{variant}` changed from {recorded} to {current} since `{LOCK_FILE}`"
+                        ),
+                        |diag| {
+                            if let Some(note) = note {
+                                diag.note(note);
+                            }
+                            diag.help(format!(
+                                "keep the variants recorded in `{LOCK_FILE}` in order, with their offset, and add the new variants after them"
+                            ));
+                        },
+                    );
+                }
+                Change::Removed { variant, code } => {
+                    let note = reused(code);
+                    span_lint_hir_and_then(
+                        cx,
+                        SHIFTED_ERROR_CODE,
+                        cx.tcx.local_def_id_to_hir_id(def_id.expect_local()),
+                        cx.tcx.def_span(*def_id),
+                        &format!(
+                            "`{name}::{variant}`, recorded in `{LOCK_FILE}` with the error code {code}, was removed"
+                        ),
+                        |diag| {
+                            if let Some(note) = note {
+                                diag.note(note);
+                            }
+                            diag.help(format!(
+                                "keep the variant, e.g., with a message saying it is deprecated, so that its code is not reused; if it was renamed, update `{LOCK_FILE}`"
+                            ));
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Return the variant named `name` of the enum `def_id`
+fn find_variant(cx: &LateContext<'_>, def_id: DefId, name: &str) -> Option<DefId> {
+    cx.tcx
+        .adt_def(def_id)
+        .variants()
+        .iter()
+        .find(|variant| variant.name.as_str() == name)
+        .map(|variant| variant.def_id)
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
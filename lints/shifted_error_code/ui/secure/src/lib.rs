@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod shifted_error_code_secure {
+    use super::*;
+
+    pub fn swap(ctx: Context<Swap>, amount: u64, min_out: u64) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.pool.authority,
+            ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(!ctx.accounts.pool.paused, PoolError::Paused);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(amount >= min_out, ErrorCode::SlippageExceeded);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Swap<'info> {
+    pool: Account<'info, Pool>,
+    authority: Signer<'info>,
+}
+
+#[account]
+pub struct Pool {
+    pub authority: Pubkey,
+    pub paused: bool,
+}
+
+// `InvalidAmount` is added after the variants recorded in `error_codes.lock`
+#[error_code]
+pub enum ErrorCode {
+    #[msg("The signer is not the authority of the pool")]
+    Unauthorized,
+    #[msg("The output amount is below the minimum")]
+    SlippageExceeded,
+    #[msg("The amount must be positive")]
+    InvalidAmount,
+}
+
+// the codes of `PoolError` start at 6100, past those of `ErrorCode`
+#[error_code(offset = 6100)]
+pub enum PoolError {
+    #[msg("The pool is paused")]
+    Paused,
+}
+
+#[allow(dead_code)]
+fn main() {}
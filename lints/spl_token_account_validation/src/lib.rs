@@ -0,0 +1,333 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{match_any_def_paths, path_to_local_id, ty::match_type};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::{walk_expr, walk_local, FnKind, Visitor},
+    Body, Expr, ExprKind, FnDecl, HirId, Local, PatKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::{self, Ty};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, paths, sarif,
+    utils::visit_expr_no_bodies,
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports an `spl_token::state::Account` or `Mint` which is unpacked from the data of an
+    /// account, when the function transfers tokens afterwards but does not read the authorities of
+    /// the unpacked state:
+    ///
+    /// - the `owner`, `delegate` and `close_authority` fields of an `Account`
+    /// - the `freeze_authority` field of a `Mint`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Unpacking a token account only checks that its data is well-formed. The authorities of the
+    /// account are set by whoever created it. If a program transfers tokens to or from a token
+    /// account supplied by the caller without checking them, the caller can:
+    ///
+    /// - pass a token account owned by someone else, if the `owner` is not checked
+    /// - set itself as the `delegate` or the `close_authority` of a vault, and later take the
+    ///   tokens transferred to it, or close it
+    /// - keep the `freeze_authority` of the mint, and freeze the accounts of the program
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// A field is regarded as checked if it is read anywhere in the function, e.g.,
+    /// `token.delegate.is_none()`. The lint does not check how the field is compared. Fields read
+    /// by destructuring the unpacked state, or in another function, are not recognized.
+    ///
+    /// Only the states bound to a local variable, e.g.,
+    /// `let token = Account::unpack(&data)?;`, are checked.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// let vault = Account::unpack(&vault_info.data.borrow())?;
+    /// if vault.owner != *authority_info.key {
+    ///     return Err(ProgramError::InvalidAccountData);
+    /// }
+    /// invoke(
+    ///     &spl_token::instruction::transfer(token_program.key, source.key, vault_info.key, authority.key, &[], amount)?,
+    ///     &[source, vault_info, authority],
+    /// )?;
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// let vault = Account::unpack(&vault_info.data.borrow())?;
+    /// if vault.owner != *authority_info.key
+    ///     || vault.delegate.is_some()
+    ///     || vault.close_authority.is_some()
+    /// {
+    ///     return Err(ProgramError::InvalidAccountData);
+    /// }
+    /// invoke(
+    ///     &spl_token::instruction::transfer(token_program.key, source.key, vault_info.key, authority.key, &[], amount)?,
+    ///     &[source, vault_info, authority],
+    /// )?;
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - collect the local variables of type `spl_token::state::Account` or `Mint` which are
+    ///   initialized with a call to `Pack::unpack`, `Pack::unpack_unchecked` or
+    ///   `Pack::unpack_from_slice`, e.g., `let x = Account::unpack(&data)?;`
+    /// - collect the calls to `spl_token::instruction::transfer`, `transfer_checked`, and their
+    ///   Anchor counterparts `anchor_spl::token::transfer` and `transfer_checked`
+    /// - for each collected local variable `x`
+    ///   - if no transfer call comes after the initialization of `x`, ignore `x`
+    ///   - find the authorities of `x` which are not read, i.e., no expression in the function is
+    ///     `x.{field}`
+    ///   - if there are such fields, report the unpack call and note the first transfer after it
+    pub SPL_TOKEN_ACCOUNT_VALIDATION,
+    Warn,
+    "token accounts or mints used in a transfer without checking their authorities"
+}
+
+/// The authorities which must be checked on an unpacked `spl_token::state::Account`
+const ACCOUNT_AUTHORITIES: &[&str] = &["owner", "delegate", "close_authority"];
+
+/// The authorities which must be checked on an unpacked `spl_token::state::Mint`
+const MINT_AUTHORITIES: &[&str] = &["freeze_authority"];
+
+impl<'tcx> LateLintPass<'tcx> for SplTokenAccountValidation {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(SPL_TOKEN_ACCOUNT_VALIDATION, span) {
+            return;
+        }
+        let mut visitor = TokenStateUses {
+            cx,
+            unpacked: Vec::new(),
+            transfers: Vec::new(),
+        };
+        visitor.visit_expr(body.value);
+
+        for unpacked in &visitor.unpacked {
+            let transfer = match visitor
+                .transfers
+                .iter()
+                .find(|transfer| transfer.lo() > unpacked.span.lo())
+            {
+                Some(transfer) => *transfer,
+                None => continue,
+            };
+            let unchecked = unpacked
+                .authorities
+                .iter()
+                .filter(|field| !reads_field(body.value, unpacked.hir_id, field))
+                .collect::<Vec<_>>();
+            if unchecked.is_empty() {
+                continue;
+            }
+            let msg = format!(
+                "the {} of this {} {} not checked before the transfer",
+                display_fields(&unchecked),
+                unpacked.kind,
+                if unchecked.len() == 1 { "is" } else { "are" },
+            );
+            span_lint_hir_and_then(
+                cx,
+                SPL_TOKEN_ACCOUNT_VALIDATION,
+                unpacked.call_hir_id,
+                unpacked.span,
+                &msg,
+                |diag| {
+                    diag.span_note(transfer, "the tokens are transferred here");
+                    diag.help(
+                        "compare the fields with the expected authorities, or check that the optional ones are `COption::None`",
+                    );
+                },
+            );
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[SPL_TOKEN_ACCOUNT_VALIDATION]);
+    }
+}
+
+/// A local variable holding an unpacked token account or mint
+struct Unpacked {
+    /// The `HirId` of the local variable
+    hir_id: HirId,
+    /// The unpack call
+    call_hir_id: HirId,
+    span: Span,
+    /// "token account" or "mint"
+    kind: &'static str,
+    authorities: &'static [&'static str],
+}
+
+struct TokenStateUses<'cx, 'tcx> {
+    cx: &'cx LateContext<'tcx>,
+    unpacked: Vec<Unpacked>,
+    /// The spans of the transfer calls, in the order they are found
+    transfers: Vec<Span>,
+}
+
+impl<'cx, 'tcx> Visitor<'tcx> for TokenStateUses<'cx, 'tcx> {
+    fn visit_local(&mut self, local: &'tcx Local<'tcx>) {
+        if_chain! {
+            if let PatKind::Binding(_, hir_id, _, None) = local.pat.kind;
+            if let Some(init) = local.init;
+            let ty = self.cx.typeck_results().node_type(local.pat.hir_id);
+            if let Some((kind, authorities)) = token_state(self.cx, ty);
+            if let Some(call) = unpack_call(self.cx, init);
+            then {
+                self.unpacked.push(Unpacked {
+                    hir_id,
+                    call_hir_id: call.hir_id,
+                    span: call.span,
+                    kind,
+                    authorities,
+                });
+            }
+        }
+        walk_local(self, local);
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx Expr<'tcx>) {
+        if_chain! {
+            if let ExprKind::Call(callee, _) = expr.kind;
+            if let Some(def_id) = fn_def_id(self.cx, callee);
+            if match_any_def_paths(
+                self.cx,
+                def_id,
+                &[
+                    &paths::SPL_TOKEN_TRANSFER,
+                    &paths::SPL_TOKEN_TRANSFER_CHECKED,
+                    &paths::ANCHOR_SPL_TRANSFER,
+                    &paths::ANCHOR_SPL_TRANSFER_CHECKED,
+                ],
+            )
+            .is_some();
+            then {
+                self.transfers.push(expr.span);
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// If `ty` is `spl_token::state::Account` or `Mint`, return its description and the authorities
+/// which must be checked
+fn token_state<'tcx>(
+    cx: &LateContext<'tcx>,
+    ty: Ty<'tcx>,
+) -> Option<(&'static str, &'static [&'static str])> {
+    if match_type(cx, ty, &paths::SPL_TOKEN_ACCOUNT) {
+        Some(("token account", ACCOUNT_AUTHORITIES))
+    } else if match_type(cx, ty, &paths::SPL_TOKEN_MINT) {
+        Some(("mint", MINT_AUTHORITIES))
+    } else {
+        None
+    }
+}
+
+/// Return the first call to `Pack::unpack`, `Pack::unpack_unchecked` or `Pack::unpack_from_slice`
+/// in `init`, e.g., `Account::unpack(&data)` in `Account::unpack(&data)?`
+fn unpack_call<'tcx>(cx: &LateContext<'tcx>, init: &'tcx Expr<'tcx>) -> Option<&'tcx Expr<'tcx>> {
+    let mut call = None;
+    visit_expr_no_bodies(init, |expr| {
+        if_chain! {
+            if let ExprKind::Call(callee, _) = expr.kind;
+            if let Some(def_id) = fn_def_id(cx, callee);
+            if match_any_def_paths(
+                cx,
+                def_id,
+                &[
+                    &paths::SOLANA_PROGRAM_UNPACK,
+                    &paths::SOLANA_PROGRAM_UNPACK_UNCHECKED,
+                    &paths::SOLANA_PROGRAM_UNPACK_FROM_SLICE,
+                ],
+            )
+            .is_some();
+            then {
+                call = Some(expr);
+                true
+            } else {
+                false
+            }
+        }
+    });
+    call
+}
+
+fn fn_def_id(cx: &LateContext<'_>, callee: &Expr<'_>) -> Option<DefId> {
+    match cx.typeck_results().expr_ty(callee).kind() {
+        ty::FnDef(def_id, _) => Some(*def_id),
+        _ => None,
+    }
+}
+
+/// Return true if any expression in `scope` is `{local}.{field}`
+fn reads_field(scope: &Expr<'_>, local: HirId, field: &str) -> bool {
+    visit_expr_no_bodies(scope, |expr| {
+        if_chain! {
+            if let ExprKind::Field(object, ident) = expr.kind;
+            if ident.as_str() == field;
+            if path_to_local_id(object, local);
+            then {
+                true
+            } else {
+                false
+            }
+        }
+    })
+}
+
+/// Display the fields as "`a`", "`a` and `b`" or "`a`, `b` and `c`"
+fn display_fields(fields: &[&&str]) -> String {
+    let fields = fields
+        .iter()
+        .map(|field| format!("`{field}`"))
+        .collect::<Vec<_>>();
+    match fields.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {last}", rest.join(", ")),
+        None => String::new(),
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::state::{Account as SplTokenAccount, Mint};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod spl_token_account_validation_secure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> ProgramResult {
+        let vault = SplTokenAccount::unpack(&ctx.accounts.vault.data.borrow())?;
+        if vault.owner != ctx.accounts.vault_authority.key()
+            || vault.delegate.is_some()
+            || vault.close_authority.is_some()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mint = Mint::unpack(&ctx.accounts.mint.data.borrow())?;
+        if mint.freeze_authority.is_some() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("Depositing {} of {} tokens", amount, mint.supply);
+        invoke(
+            &spl_token::instruction::transfer(
+                ctx.accounts.token_program.key,
+                ctx.accounts.source.key,
+                ctx.accounts.vault.key,
+                ctx.accounts.authority.key,
+                &[],
+                amount,
+            )?,
+            &[
+                ctx.accounts.source.clone(),
+                ctx.accounts.vault.clone(),
+                ctx.accounts.authority.to_account_info(),
+            ],
+        )
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> ProgramResult {
+        let source = SplTokenAccount::unpack(&ctx.accounts.source.data.borrow())?;
+        if source.owner != ctx.accounts.authority.key()
+            || source.delegate.is_some()
+            || source.close_authority.is_some()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if source.amount < amount {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        invoke(
+            &spl_token::instruction::transfer(
+                ctx.accounts.token_program.key,
+                ctx.accounts.source.key,
+                ctx.accounts.destination.key,
+                ctx.accounts.authority.key,
+                &[],
+                amount,
+            )?,
+            &[
+                ctx.accounts.source.clone(),
+                ctx.accounts.destination.clone(),
+                ctx.accounts.authority.to_account_info(),
+            ],
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    source: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    vault_authority: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    authority: Signer<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    source: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    authority: Signer<'info>,
+    token_program: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,267 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{fn_def_id, match_def_path, ty::match_type};
+use if_chain::if_chain;
+use rustc_hir::{def_id::LocalDefId, intravisit::FnKind, Body, Expr, ExprKind, FnDecl, UnOp};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths::{self, anchor_wrapper, AnchorWrapper},
+    sarif,
+    utils::visit_expr_no_bodies,
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the native stake instructions built with
+    /// `solana_program::stake::instruction::delegate_stake` or `withdraw` on a stake account
+    /// accepted as an `AccountInfo` (or an Anchor `UncheckedAccount`), when the function does not
+    /// check the state of the stake account:
+    ///
+    /// - the data of the account is not deserialized into a `StakeStateV2` (or a `StakeState`), or
+    /// - the authority of the instruction is not read from the state: the authorized `staker` for
+    ///   `delegate_stake`, and the authorized `withdrawer` for `withdraw`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The stake program only checks that the authority of the instruction signs it. If the
+    /// program signs for the authority, e.g., with a PDA, and does not check which stake account
+    /// is passed, a caller can make the program delegate or withdraw from any stake account the
+    /// PDA is an authority of, e.g., the stake account of another user of the program. A stake
+    /// account accepted as an `AccountInfo` can also be an uninitialized account, or an account
+    /// which is not a stake account at all.
+    ///
+    /// The vote account of `delegate_stake` is checked by the stake program, and is not reported.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The checks are not tied to the stake account of the instruction: a function which
+    /// deserializes the state of one stake account and reads its authorities, and builds an
+    /// instruction on another stake account, is not reported. The lint does not check how the
+    /// authority is compared either.
+    ///
+    /// The stake account is only recognized if the first argument of the instruction is its key,
+    /// e.g., `stake.key` or `&stake.key()`.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// invoke_signed(
+    ///     &stake::instruction::withdraw(stake.key, pool.key, recipient.key, amount, None),
+    ///     &[stake.clone(), recipient.clone(), clock.clone(), stake_history.clone(), pool.clone()],
+    ///     &[&[b"pool", &[bump]]],
+    /// )?;
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// let state = StakeStateV2::deserialize(&mut &stake.data.borrow()[..])?;
+    /// match state.authorized() {
+    ///     Some(authorized) if authorized.withdrawer == *pool.key => {}
+    ///     _ => return Err(ProgramError::InvalidAccountData),
+    /// }
+    /// invoke_signed(
+    ///     &stake::instruction::withdraw(stake.key, pool.key, recipient.key, amount, None),
+    ///     &[stake.clone(), recipient.clone(), clock.clone(), stake_history.clone(), pool.clone()],
+    ///     &[&[b"pool", &[bump]]],
+    /// )?;
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - for each call to `stake::instruction::delegate_stake` or `stake::instruction::withdraw`
+    ///   - if the first argument is not the key of an `AccountInfo` or an `UncheckedAccount`, e.g.,
+    ///     `stake.key`, `stake.key()` or `&stake.key()`, ignore the call
+    ///   - if no expression in the function has type `StakeStateV2` or `StakeState`, report the
+    ///     call
+    ///   - else, if no expression in the function reads the authority of the instruction from an
+    ///     `Authorized`, i.e., `x.staker` for `delegate_stake` and `x.withdrawer` for `withdraw`,
+    ///     report the call and note the first expression of type `StakeStateV2` or `StakeState`
+    pub STAKE_ACCOUNT_VALIDATION,
+    Warn,
+    "stake instructions on stake accounts whose state and authorities are not checked"
+}
+
+/// The stake instructions, and the authority which must be checked for each of them
+const STAKE_INSTRUCTIONS: &[(&[&str], &str)] = &[
+    (&paths::SOLANA_PROGRAM_STAKE_DELEGATE_STAKE, "staker"),
+    (&paths::SOLANA_PROGRAM_STAKE_WITHDRAW, "withdrawer"),
+];
+
+impl<'tcx> LateLintPass<'tcx> for StakeAccountValidation {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(STAKE_ACCOUNT_VALIDATION, span) {
+            return;
+        }
+        let state = stake_state(cx, body.value);
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if let ExprKind::Call(_, args) = expr.kind;
+                if let Some((path, authority)) = stake_instruction(cx, expr);
+                if let Some(stake_key) = args.first();
+                if is_account_info_key(cx, stake_key);
+                then {
+                    check_stake_instruction(cx, body.value, state, expr, path, authority);
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[STAKE_ACCOUNT_VALIDATION]);
+    }
+}
+
+/// Report the stake instruction `call`, unless the function reads the stake state and its
+/// `authority`
+fn check_stake_instruction<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    state: Option<&'tcx Expr<'tcx>>,
+    call: &'tcx Expr<'tcx>,
+    path: &[&str],
+    authority: &str,
+) {
+    let name = path[path.len() - 1];
+    match state {
+        None => span_lint_hir_and_then(
+            cx,
+            STAKE_ACCOUNT_VALIDATION,
+            call.hir_id,
+            call.span,
+            &format!("the state of the stake account is not checked before `{name}`"),
+            |diag| {
+                diag.help(format!(
+                    "deserialize the data of the account into a `StakeStateV2`, and compare its authorized `{authority}` with the expected authority"
+                ));
+            },
+        ),
+        Some(state) if !reads_authority(cx, scope, authority) => span_lint_hir_and_then(
+            cx,
+            STAKE_ACCOUNT_VALIDATION,
+            call.hir_id,
+            call.span,
+            &format!(
+                "the authorized `{authority}` of the stake account is not checked before `{name}`"
+            ),
+            |diag| {
+                diag.span_note(state.span, "the stake state is deserialized here");
+                diag.help(format!(
+                    "compare the authorized `{authority}` of the state with the expected authority"
+                ));
+            },
+        ),
+        Some(_) => {}
+    }
+}
+
+/// If `expr` is a call to a stake instruction, return its path and the authority which must be
+/// checked
+fn stake_instruction(
+    cx: &LateContext<'_>,
+    expr: &Expr<'_>,
+) -> Option<(&'static [&'static str], &'static str)> {
+    let def_id = fn_def_id(cx, expr)?;
+    STAKE_INSTRUCTIONS
+        .iter()
+        .find(|(path, _)| match_def_path(cx, def_id, path))
+        .copied()
+}
+
+/// Return true if `expr` is the key of an `AccountInfo` or an `UncheckedAccount`, e.g.,
+/// `stake.key`, `stake.key()` or `&stake.key()`
+fn is_account_info_key<'tcx>(cx: &LateContext<'tcx>, mut expr: &'tcx Expr<'tcx>) -> bool {
+    while let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = expr.kind {
+        expr = inner;
+    }
+    let account = match expr.kind {
+        ExprKind::Field(account, ident) if ident.as_str() == "key" => account,
+        ExprKind::MethodCall(_, account, _, _)
+            if cx
+                .typeck_results()
+                .type_dependent_def_id(expr.hir_id)
+                .map_or(false, |def_id| {
+                    match_def_path(cx, def_id, &paths::ANCHOR_LANG_KEY)
+                }) =>
+        {
+            account
+        }
+        _ => return false,
+    };
+    let ty = cx.typeck_results().expr_ty_adjusted(account).peel_refs();
+    paths::is_account_info_ty(cx, ty)
+        || anchor_wrapper(cx, ty) == Some(AnchorWrapper::UncheckedAccount)
+}
+
+/// Return the first expression in `scope` of type `StakeStateV2` or `StakeState`, which is not a
+/// local variable, e.g., `StakeStateV2::deserialize(&mut data)?`
+fn stake_state<'tcx>(cx: &LateContext<'tcx>, scope: &'tcx Expr<'tcx>) -> Option<&'tcx Expr<'tcx>> {
+    visit_expr_no_bodies(scope, |expr| {
+        let ty = cx.typeck_results().expr_ty(expr);
+        if !matches!(expr.kind, ExprKind::Path(_))
+            && (match_type(cx, ty, &paths::SOLANA_PROGRAM_STAKE_STATE_V2)
+                || match_type(cx, ty, &paths::SOLANA_PROGRAM_STAKE_STATE))
+        {
+            Some(expr)
+        } else {
+            None
+        }
+    })
+}
+
+/// Return true if any expression in `scope` is `x.{authority}`, where `x` is an `Authorized`
+fn reads_authority(cx: &LateContext<'_>, scope: &Expr<'_>, authority: &str) -> bool {
+    visit_expr_no_bodies(scope, |expr| {
+        if_chain! {
+            if let ExprKind::Field(object, ident) = expr.kind;
+            if ident.as_str() == authority;
+            let ty = cx.typeck_results().expr_ty_adjusted(object).peel_refs();
+            if match_type(cx, ty, &paths::SOLANA_PROGRAM_STAKE_AUTHORIZED);
+            then {
+                true
+            } else {
+                false
+            }
+        }
+    })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
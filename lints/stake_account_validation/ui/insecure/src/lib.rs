@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::stake;
+use anchor_lang::solana_program::stake::state::StakeStateV2;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod stake_account_validation_insecure {
+    use super::*;
+
+    pub fn delegate(ctx: Context<Delegate>, bump: u8) -> Result<()> {
+        let accounts = &ctx.accounts;
+        let ix = stake::instruction::delegate_stake(
+            accounts.stake.key,
+            accounts.pool.key,
+            accounts.vote.key,
+        );
+        invoke_signed(
+            &ix,
+            &[
+                accounts.stake.clone(),
+                accounts.vote.clone(),
+                accounts.clock.to_account_info(),
+                accounts.stake_history.clone(),
+                accounts.stake_config.clone(),
+                accounts.pool.clone(),
+            ],
+            &[&[b"pool", &[bump]]],
+        )?;
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64, bump: u8) -> Result<()> {
+        let accounts = &ctx.accounts;
+        let state = deserialize_stake(&accounts.stake)?;
+        // The staker is checked instead of the withdrawer
+        match state.authorized() {
+            Some(authorized) if authorized.staker == accounts.pool.key() => {}
+            _ => return err!(ErrorCode::InvalidStakeAccount),
+        }
+        let ix = stake::instruction::withdraw(
+            &accounts.stake.key(),
+            accounts.pool.key,
+            accounts.recipient.key,
+            amount,
+            None,
+        );
+        invoke_signed(
+            &ix,
+            &[
+                accounts.stake.clone(),
+                accounts.recipient.clone(),
+                accounts.clock.to_account_info(),
+                accounts.stake_history.clone(),
+                accounts.pool.clone(),
+            ],
+            &[&[b"pool", &[bump]]],
+        )?;
+        Ok(())
+    }
+}
+
+fn deserialize_stake(stake: &AccountInfo) -> Result<StakeStateV2> {
+    StakeStateV2::deserialize(&mut &stake.data.borrow()[..])
+        .map_err(|_| error!(ErrorCode::InvalidStakeAccount))
+}
+
+#[derive(Accounts)]
+pub struct Delegate<'info> {
+    /// CHECK: the stake account of the pool
+    #[account(mut)]
+    stake: AccountInfo<'info>,
+    /// CHECK: checked by the stake program
+    vote: AccountInfo<'info>,
+    /// CHECK: the authority of the stake accounts of the pool
+    #[account(seeds = [b"pool"], bump)]
+    pool: AccountInfo<'info>,
+    clock: Sysvar<'info, Clock>,
+    /// CHECK: checked by the stake program
+    stake_history: AccountInfo<'info>,
+    /// CHECK: checked by the stake program
+    stake_config: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: the stake account of the pool
+    #[account(mut)]
+    stake: AccountInfo<'info>,
+    /// CHECK: the authority of the stake accounts of the pool
+    #[account(seeds = [b"pool"], bump)]
+    pool: AccountInfo<'info>,
+    /// CHECK: any account
+    #[account(mut)]
+    recipient: AccountInfo<'info>,
+    clock: Sysvar<'info, Clock>,
+    /// CHECK: checked by the stake program
+    stake_history: AccountInfo<'info>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    InvalidStakeAccount,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,209 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::{
+    fn_def_id, match_def_path,
+    ty::{get_associated_type, match_type},
+};
+use if_chain::if_chain;
+use rustc_ast::Mutability;
+use rustc_hir::{Expr, ExprKind, HirId, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty::Ty;
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, paths, sarif,
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports state which does not behave in an on-chain program as it does in a native program:
+    ///
+    /// - `static mut` items
+    /// - `static` items with interior mutability, e.g., `static COUNTER: AtomicU64`, including the
+    ///   statics declared with `lazy_static!`, e.g., `lazy_static! { static ref CACHE: Mutex<..> }`
+    /// - `std::mem::forget` of a `Ref` or `RefMut`, e.g., the guard returned by
+    ///   `account.try_borrow_mut_data()`
+    /// - `Box::leak`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Every invocation of a program starts with a fresh copy of the program's memory. A value
+    /// stored in a static, or in leaked memory, is not persisted between instructions, and it is not
+    /// shared with the other invocations of the program in the same transaction, including the
+    /// reentrant invocations through CPI. Code which relies on such a value, e.g., a counter or a
+    /// cache, silently starts over in every invocation.
+    ///
+    /// The data and lamports of an account are borrowed through a `RefCell`. Forgetting the `Ref` or
+    /// `RefMut` leaves the `RefCell` borrowed until the end of the instruction. The following
+    /// borrows of the account fail, including the borrows made by `invoke` when the account is
+    /// passed to a CPI.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// A static with interior mutability which is only used within a single invocation, e.g., a
+    /// lazily computed constant, is reported as well.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    ///     static mut TOTAL_DEPOSITS: u64 = 0;
+    ///     ...
+    ///     unsafe { TOTAL_DEPOSITS += amount };
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    ///     let vault = &mut ctx.accounts.vault;
+    ///     vault.total_deposits += amount;
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// - For every `static` item whose name is not from a macro expansion
+    ///   - If the item is `static mut`, report the item
+    ///   - Else if the type of the item is not `Freeze`, i.e., it contains an `UnsafeCell`, report the item
+    ///   - Else if the type of the item implements `Deref` and the `Deref::Target` is not `Freeze`,
+    ///     report the name of the item. This is how the statics declared with `lazy_static!` are recognized.
+    ///   - The first two checks are skipped if the item is from a macro expansion.
+    /// - For every call expression, excluding the expressions in macro expansions
+    ///   - If the call is `std::mem::forget(x)` and `x` is a `Ref` or a `RefMut`, report the call
+    ///   - If the call is `Box::leak(x)`, report the call
+    pub STATIC_OR_LEAKED_STATE,
+    Warn,
+    "state which is not persisted in on-chain programs or borrows which are never released"
+}
+
+impl<'tcx> LateLintPass<'tcx> for StaticOrLeakedState {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Static(_, mutability, _) = item.kind;
+            if !item.ident.span.from_expansion();
+            then {
+                // `lazy_static!` expands to a static whose name is the only part written by the user
+                let from_expansion = item.span.from_expansion();
+                let ty = cx.tcx.type_of(item.owner_id).instantiate_identity();
+                if !from_expansion && mutability == Mutability::Mut {
+                    report_static(
+                        cx,
+                        item.hir_id(),
+                        item.span,
+                        "mutable static in an on-chain program",
+                    );
+                } else if !from_expansion && !ty.is_freeze(cx.tcx, cx.param_env) {
+                    report_static(
+                        cx,
+                        item.hir_id(),
+                        item.span,
+                        "static with interior mutability in an on-chain program",
+                    );
+                } else if let Some(target) = deref_target(cx, ty) {
+                    if !target.is_freeze(cx.tcx, cx.param_env) {
+                        report_static(
+                            cx,
+                            item.hir_id(),
+                            item.ident.span,
+                            "lazily initialized static with interior mutability in an on-chain program",
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if_chain! {
+            if !is_skipped_expansion(STATIC_OR_LEAKED_STATE, expr.span);
+            if let ExprKind::Call(_, args) = expr.kind;
+            if let Some(def_id) = fn_def_id(cx, expr);
+            then {
+                if match_def_path(cx, def_id, &paths::CORE_MEM_FORGET) {
+                    let arg_ty = cx.typeck_results().expr_ty(&args[0]);
+                    if match_type(cx, arg_ty, &paths::CORE_CELL_REF_MUT) {
+                        report_forget(cx, expr.hir_id, expr.span, "RefMut");
+                    } else if match_type(cx, arg_ty, &paths::CORE_CELL_REF) {
+                        report_forget(cx, expr.hir_id, expr.span, "Ref");
+                    }
+                } else if match_def_path(cx, def_id, &paths::ALLOC_BOXED_BOX_LEAK) {
+                    span_lint_hir_and_then(
+                        cx,
+                        STATIC_OR_LEAKED_STATE,
+                        expr.hir_id,
+                        expr.span,
+                        "`Box::leak` in an on-chain program",
+                        |diag| {
+                            diag.note(
+                                "the leaked value is not persisted between instructions, even though \
+                                 the reference has a `'static` lifetime",
+                            );
+                            diag.help("store the values which outlive the instruction in an account");
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[STATIC_OR_LEAKED_STATE]);
+    }
+}
+
+/// Return the `Deref::Target` of `ty` if `ty` implements `Deref`
+fn deref_target<'tcx>(cx: &LateContext<'tcx>, ty: Ty<'tcx>) -> Option<Ty<'tcx>> {
+    let deref_trait = cx.tcx.lang_items().deref_trait()?;
+    get_associated_type(cx, ty, deref_trait, "Target")
+}
+
+fn report_static(cx: &LateContext<'_>, hir_id: HirId, span: Span, msg: &str) {
+    span_lint_hir_and_then(cx, STATIC_OR_LEAKED_STATE, hir_id, span, msg, |diag| {
+        diag.note(
+            "the value of a static is not persisted between instructions, nor shared with the other \
+             invocations of the program, including the reentrant invocations through CPI",
+        );
+        diag.help("store the state in an account");
+    });
+}
+
+fn report_forget(cx: &LateContext<'_>, hir_id: HirId, span: Span, guard: &str) {
+    span_lint_hir_and_then(
+        cx,
+        STATIC_OR_LEAKED_STATE,
+        hir_id,
+        span,
+        &format!("forgetting a `{guard}` leaves the `RefCell` borrowed"),
+        |diag| {
+            diag.note(
+                "the borrow is not released until the end of the instruction; the following borrows of \
+                 the account, including the borrows made by `invoke` for a CPI, fail",
+            );
+            diag.help(format!("drop the `{guard}` instead, e.g., with `drop(..)`"));
+        },
+    );
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+static mut TOTAL_DEPOSITS: u64 = 0;
+
+static DEPOSIT_COUNT: AtomicU64 = AtomicU64::new(0);
+
+lazy_static! {
+    static ref DEPOSITORS: Mutex<Vec<Pubkey>> = Mutex::new(Vec::new());
+}
+
+#[program]
+pub mod static_or_leaked_state_insecure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        unsafe { TOTAL_DEPOSITS += amount };
+        DEPOSIT_COUNT.fetch_add(1, Ordering::Relaxed);
+        DEPOSITORS.lock().unwrap().push(ctx.accounts.depositor.key());
+        let memo: &'static mut Vec<u8> = Box::leak(Box::new(Vec::new()));
+        memo.extend_from_slice(&amount.to_le_bytes());
+        Ok(())
+    }
+
+    pub fn write(ctx: Context<Write>, data: Vec<u8>) -> Result<()> {
+        let mut account_data = ctx.accounts.buffer.try_borrow_mut_data()?;
+        account_data[..data.len()].copy_from_slice(&data);
+        std::mem::forget(account_data);
+        let lamports = ctx.accounts.buffer.try_borrow_lamports()?;
+        msg!("lamports: {}", lamports);
+        std::mem::forget(lamports);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    depositor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Write<'info> {
+    /// CHECK: The data is written as is
+    #[account(mut)]
+    buffer: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use lazy_static::lazy_static;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+static MAX_DEPOSIT: u64 = 1_000_000;
+
+lazy_static! {
+    static ref FEE_RECEIVERS: Vec<Pubkey> = vec![Pubkey::default()];
+}
+
+#[program]
+pub mod static_or_leaked_state_secure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        if amount > MAX_DEPOSIT || FEE_RECEIVERS.contains(&ctx.accounts.depositor.key()) {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let vault = &mut ctx.accounts.vault;
+        vault.total_deposits += amount;
+        vault.deposit_count += 1;
+        let memo = Box::new(amount.to_le_bytes());
+        msg!("memo: {:?}", memo);
+        Ok(())
+    }
+
+    pub fn write(ctx: Context<Write>, data: Vec<u8>) -> Result<()> {
+        let mut account_data = ctx.accounts.buffer.try_borrow_mut_data()?;
+        account_data[..data.len()].copy_from_slice(&data);
+        drop(account_data);
+        let lamports = ctx.accounts.buffer.try_borrow_lamports()?;
+        msg!("lamports: {}", lamports);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    depositor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Write<'info> {
+    /// CHECK: The data is written as is
+    #[account(mut)]
+    buffer: AccountInfo<'info>,
+}
+
+#[account]
+pub struct Vault {
+    total_deposits: u64,
+    deposit_count: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,326 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use anchor_syn::{AccountField, Ty as FieldTy};
+use clippy_utils::match_def_path;
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::{walk_expr, FnKind, Visitor},
+    BinOpKind, Body, Expr, ExprKind, FnDecl, HirId, Item, ItemKind, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty;
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{anchor_constraints::ConstraintSummary, get_anchor_accounts_struct},
+};
+use syn::visit::{self, Visit};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports `SystemAccount` fields of Anchor `Accounts` structs which are used as authorities, i.e.,
+    /// their key is compared against an expected authority, but which are not required to be signers.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// `SystemAccount` only checks that the account is owned by the System Program. Every wallet is owned
+    /// by the System Program, so the check says nothing about who approved the instruction. If the program
+    /// compares the key of such an account with a stored authority, e.g., using `has_one = authority`,
+    /// then anyone can pass the address of the real authority without its signature and pass the check.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Only `has_one` constraints, `constraint = ...` expressions and `==`/`!=` comparisons in functions
+    /// are considered as authorization checks. Comparisons performed by helper functions that take the key
+    /// as an argument are not recognized.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Withdraw<'info> {
+    ///     #[account(mut, has_one = authority)]
+    ///     vault: Account<'info, Vault>,
+    ///     authority: SystemAccount<'info>,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Withdraw<'info> {
+    ///     #[account(mut, has_one = authority)]
+    ///     vault: Account<'info, Vault>,
+    ///     authority: Signer<'info>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - For each Anchor `Accounts` struct
+    ///   - Collect the `SystemAccount` fields which do not have the `#[account(signer)]` constraint
+    ///   - For each field with `#[account(has_one = x)]` constraint
+    ///     - record `x` as used as an authority
+    ///   - For each field with `#[account(constraint = ...)]` constraint
+    ///     - record every field `x` whose key is accessed in the expression (`x.key()` or `x.key`) as used as an authority
+    ///
+    /// check_fn:
+    ///
+    /// - For every function defined in the package, excluding the functions generated by macros
+    ///   - For each comparison (`==` or `!=`) in the function body
+    ///     - If either side of the comparison accesses the key of an account stored in a struct field
+    ///       (`x.y.key()` or `x.y.key`)
+    ///       - record the field `y` of the struct as used as an authority
+    ///
+    /// check_crate_post:
+    ///
+    /// - For each collected `SystemAccount` field
+    ///   - If the field is used as an authority
+    ///     - Report the field along with the locations where it is used as an authority
+    pub SYSTEM_ACCOUNT_AUTHORITY,
+    Warn,
+    "`SystemAccount` used as an authority without being a signer",
+    SystemAccountAuthority::default()
+}
+
+#[derive(Default)]
+struct SystemAccountAuthority {
+    /// `SystemAccount` fields without `signer` constraint: (struct def id, field name, field hir id)
+    unsigned_system_accounts: Vec<(DefId, String, HirId)>,
+    /// Fields whose key is compared against an authority: (struct def id, field name, comparison span)
+    authority_uses: Vec<(DefId, String, Span)>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for SystemAccountAuthority {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                let def_id = item.owner_id.to_def_id();
+                for (item_field, anchor_field) in
+                    variant.fields().iter().zip(accounts_struct.fields.iter())
+                {
+                    // Composite fields are reported when their own struct is checked.
+                    if let AccountField::Field(field) = anchor_field {
+                        if matches!(field.ty, FieldTy::SystemAccount)
+                            && !ConstraintSummary::new(&field.constraints).is_signer
+                        {
+                            self.unsigned_system_accounts.push((
+                                def_id,
+                                field.ident.to_string(),
+                                item_field.hir_id,
+                            ));
+                        }
+                        // `has_one = x` compares the key of `x` with the field `x` stored in this account.
+                        for has_one in &field.constraints.has_one {
+                            if let syn::Expr::Path(expr_path) = &has_one.join_target {
+                                if let Some(ident) = expr_path.path.get_ident() {
+                                    self.authority_uses.push((
+                                        def_id,
+                                        ident.to_string(),
+                                        item_field.span,
+                                    ));
+                                }
+                            }
+                        }
+                        // `constraint = ...` expressions may compare keys of the other fields.
+                        for raw in &field.constraints.raw {
+                            let mut key_accesses = KeyAccesses::default();
+                            key_accesses.visit_expr(&raw.raw);
+                            for field_name in key_accesses.fields {
+                                self.authority_uses
+                                    .push((def_id, field_name, item_field.span));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if !is_skipped_expansion(SYSTEM_ACCOUNT_AUTHORITY, span) {
+            let mut comparisons = KeyComparisons {
+                cx,
+                uses: Vec::new(),
+            };
+            comparisons.visit_expr(body.value);
+            self.authority_uses.extend(comparisons.uses);
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for (def_id, field_name, field_hir_id) in &self.unsigned_system_accounts {
+            let mut uses = Vec::new();
+            for (use_def_id, use_field_name, use_span) in &self.authority_uses {
+                if use_def_id == def_id && use_field_name == field_name && !uses.contains(use_span)
+                {
+                    uses.push(*use_span);
+                }
+            }
+            if uses.is_empty() {
+                continue;
+            }
+            span_lint_hir_and_then(
+                cx,
+                SYSTEM_ACCOUNT_AUTHORITY,
+                *field_hir_id,
+                cx.tcx.hir().span(*field_hir_id),
+                &format!(
+                    "`SystemAccount` `{field_name}` is used as an authority but is not required to be a signer"
+                ),
+                |diag| {
+                    for span in uses {
+                        diag.span_note(
+                            span,
+                            "the key of the account is checked against an authority here",
+                        );
+                    }
+                    diag.help("use `Signer<'info>` or add the `#[account(signer)]` constraint");
+                },
+            );
+        }
+
+        sarif::write(cx, &[SYSTEM_ACCOUNT_AUTHORITY]);
+    }
+}
+
+/// Collects the fields whose key is accessed (`x.key()` or `x.key`) in an Anchor constraint expression
+#[derive(Default)]
+struct KeyAccesses {
+    fields: Vec<String>,
+}
+
+impl<'ast> Visit<'ast> for KeyAccesses {
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        if method_call.method == "key" && method_call.args.is_empty() {
+            if let Some(ident) = expr_ident(&method_call.receiver) {
+                self.fields.push(ident);
+            }
+        }
+        visit::visit_expr_method_call(self, method_call);
+    }
+
+    fn visit_expr_field(&mut self, expr_field: &'ast syn::ExprField) {
+        if_chain! {
+            if let syn::Member::Named(member) = &expr_field.member;
+            if member == "key";
+            if let Some(ident) = expr_ident(&expr_field.base);
+            then {
+                self.fields.push(ident);
+            }
+        }
+        visit::visit_expr_field(self, expr_field);
+    }
+}
+
+/// Return the identifier if the expression is a single identifier path, e.g., `authority`
+fn expr_ident(expr: &syn::Expr) -> Option<String> {
+    if let syn::Expr::Path(expr_path) = expr {
+        expr_path.path.get_ident().map(ToString::to_string)
+    } else {
+        None
+    }
+}
+
+/// Collects the struct fields whose key is compared using `==` or `!=` in a function body
+struct KeyComparisons<'cx, 'tcx> {
+    cx: &'cx LateContext<'tcx>,
+    uses: Vec<(DefId, String, Span)>,
+}
+
+impl<'cx, 'tcx> Visitor<'tcx> for KeyComparisons<'cx, 'tcx> {
+    fn visit_expr(&mut self, expr: &'tcx Expr<'tcx>) {
+        if_chain! {
+            if let ExprKind::Binary(op, lhs, rhs) = expr.kind;
+            if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne);
+            then {
+                for operand in [lhs, rhs] {
+                    if let Some((def_id, field_name)) = accessed_field_key(self.cx, operand) {
+                        self.uses.push((def_id, field_name, expr.span));
+                    }
+                }
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// If `expr` accesses the key of an account stored in a struct field, i.e., `x.y.key()` or `x.y.key`,
+/// return the def id of the struct `x` and the field name `y`.
+fn accessed_field_key<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<(DefId, String)> {
+    // remove the `*` and `&` around the key: `*x.y.key`, `&x.y.key()`
+    let mut expr = expr;
+    while let ExprKind::Unary(UnOp::Deref, inner) | ExprKind::AddrOf(_, _, inner) = expr.kind {
+        expr = inner;
+    }
+    let account = match expr.kind {
+        // Anchor account types: `x.y.key()`
+        ExprKind::MethodCall(_, recv, [], _)
+            if cx
+                .typeck_results()
+                .type_dependent_def_id(expr.hir_id)
+                .map_or(false, |def_id| {
+                    match_def_path(cx, def_id, &paths::ANCHOR_LANG_KEY)
+                }) =>
+        {
+            recv
+        }
+        // `AccountInfo` (or types dereferencing to it): `x.y.key`
+        ExprKind::Field(recv, field_name) if field_name.as_str() == "key" => recv,
+        _ => return None,
+    };
+    if_chain! {
+        if let ExprKind::Field(object, field_name) = account.kind;
+        if let ty::Adt(adt_def, _) = cx.typeck_results().expr_ty_adjusted(object).kind();
+        then {
+            Some((adt_def.did(), field_name.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
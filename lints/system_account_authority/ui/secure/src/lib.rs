@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_error::ProgramError;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod system_account_authority_secure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> ProgramResult {
+        **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.authority.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+
+    pub fn set_config(ctx: Context<SetConfig>, value: u64) -> ProgramResult {
+        if ctx.accounts.authority.key() != ctx.accounts.config.authority {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        ctx.accounts.config.value = value;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut, has_one = authority)]
+    vault: Account<'info, Vault>,
+    #[account(mut)]
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetConfig<'info> {
+    #[account(mut)]
+    config: Account<'info, Config>,
+    authority: Signer<'info>,
+}
+
+#[account]
+pub struct Vault {
+    authority: Pubkey,
+}
+
+#[account]
+pub struct Config {
+    authority: Pubkey,
+    value: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
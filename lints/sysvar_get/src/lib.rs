@@ -1,25 +1,30 @@
 #![feature(rustc_private)]
 #![warn(unused_extern_crates)]
 
+extern crate rustc_errors;
 extern crate rustc_hir;
 extern crate rustc_span;
 
 use anchor_syn::{AccountField, SysvarTy, Ty as FieldTy};
-use clippy_utils::{
-    diagnostics::span_lint, diagnostics::span_lint_and_then, match_any_def_paths, match_def_path,
-};
+use clippy_utils::{match_def_path, source::snippet_with_applicability};
 use if_chain::if_chain;
+use rustc_errors::Applicability;
 use rustc_hir::{
     def::Res,
-    def_id::LocalDefId,
+    def_id::{DefId, LocalDefId},
     intravisit::{walk_expr, FnKind, Visitor},
-    Body, Expr, ExprKind, FnDecl, Item, ItemKind, QPath, TyKind,
+    Body, Expr, ExprKind, FnDecl, HirId, Item, ItemKind, QPath, TyKind, UnOp,
 };
 use rustc_lint::{LateContext, LateLintPass};
 use rustc_span::Span;
-use solana_lints::{paths, utils::get_anchor_accounts_struct};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{context_accounts_struct, get_anchor_accounts_struct},
+};
 
-dylint_linting::declare_late_lint! {
+solana_lints::impl_late_lint! {
     /// **What it does:**
     ///
     /// Lint warns uses of `Sysvar::from_account_info` and suggests to use `Sysvar::get` instead for
@@ -32,6 +37,11 @@ dylint_linting::declare_late_lint! {
     /// - LastRestartSlot
     /// - Rent
     ///
+    /// The other sysvars, e.g., `RecentBlockhashes`, `SlotHashes` and `StakeHistory`, do not
+    /// implement `Sysvar::get`: they can only be read from their account, and their
+    /// `from_account_info` is not reported. The lint reports their `get()` calls instead, which
+    /// always fail with `UnsupportedSysvar`.
+    ///
     /// **Why is this bad?**
     ///
     /// The `Sysvar::from_account_info` is less efficient than `Sysvar::get` because:
@@ -53,6 +63,10 @@ dylint_linting::declare_late_lint! {
     /// - [x] Anchor
     /// - [x] Non Anchor
     ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
     /// **Known problems:**
     ///
     /// None
@@ -75,63 +89,270 @@ dylint_linting::declare_late_lint! {
     /// - For every item
     ///   - If item is a struct and has `#[derive(Accounts)]` macro
     ///   - For each field in the struct
-    ///     - If field is of type Ty::Sysvar(T) and T is one of `Clock`, `EpochRewards`, `EpochSchedule`, `Fees`, `LastRestartSlot`, `Rent`
-    ///       - Then report the field and suggest to T::get().
+    ///     - If field is of type Ty::Sysvar(T) and T is one of `Clock`, `EpochSchedule`, `Fees`, `Rent`
+    ///       - Then record the field for the instruction (the `Accounts` struct) and T.
     /// - For every function
     ///   - If an expr in function calls T::x() where x is `solana_program::Sysvar::from_account_info` and
     ///     T is one of sysvars that implements `Sysvar::get()` method.
-    ///     - report the call expr and suggest to use T::get().
+    ///     - record the call expr for the instruction and T. The instruction is the `Accounts` struct `S` if the
+    ///       function takes a `Context<S>` argument, and the function otherwise.
+    ///   - If an expr in function calls T::get() where T is one of `RecentBlockhashes`, `SlotHashes`,
+    ///     `StakeHistory`, the sysvars not implementing `Sysvar::get()`, report the call.
+    /// - After the crate is checked, for every instruction and sysvar T recorded
+    ///   - report the fields and the call exprs together and suggest to use T::get().
+    ///   - suggest replacing each call expr with `T::get()`. The suggestion drops the argument of
+    ///     `from_account_info`, so it is machine applicable only if the argument is a place
+    ///     expression, e.g., `&ctx.accounts.clock`, whose evaluation has no side effects.
+    ///
+    /// As a result, an Anchor instruction which passes a sysvar account and also calls `from_account_info` on it
+    /// is reported once for that sysvar.
     pub SYSVAR_GET,
     Warn,
-    "Using `Sysvar::from_account_info` instead of `Sysvar::get`"
+    "Using `Sysvar::from_account_info` instead of `Sysvar::get`",
+    SysvarGet::default()
+}
+
+#[derive(Default)]
+struct SysvarGet {
+    uses: Vec<SysvarUses>,
+}
+
+/// The instruction in which a sysvar is used
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    /// An Anchor instruction, identified by its `Accounts` struct
+    Accounts(DefId),
+    /// A function which does not take a `Context`
+    Fn(LocalDefId),
+}
+
+/// The uses of a sysvar in an instruction
+struct SysvarUses {
+    instruction: Instruction,
+    sysvar: String,
+    /// The node the uses are reported at: the `Accounts` struct if the sysvar account is passed to the
+    /// instruction, else the function calling `from_account_info`
+    hir_id: HirId,
+    /// The span of the `Accounts` struct's name, if the sysvar account is passed to the instruction
+    accounts_struct: Option<Span>,
+    /// `Sysvar<'info, T>` fields of the `Accounts` struct
+    fields: Vec<Span>,
+    /// `T::from_account_info(...)` calls
+    calls: Vec<FromAccountInfoCall>,
+}
+
+/// A `T::from_account_info(...)` call, and its replacement `T::get()`
+struct FromAccountInfoCall {
+    span: Span,
+    sugg: Option<(String, Applicability)>,
+}
+
+impl SysvarGet {
+    fn uses_mut(
+        &mut self,
+        instruction: Instruction,
+        sysvar: &str,
+        hir_id: HirId,
+    ) -> &mut SysvarUses {
+        if let Some(index) = self
+            .uses
+            .iter()
+            .position(|uses| uses.instruction == instruction && uses.sysvar == sysvar)
+        {
+            &mut self.uses[index]
+        } else {
+            self.uses.push(SysvarUses {
+                instruction,
+                sysvar: sysvar.to_string(),
+                hir_id,
+                accounts_struct: None,
+                fields: Vec::new(),
+                calls: Vec::new(),
+            });
+            self.uses.last_mut().unwrap()
+        }
+    }
 }
 
 impl<'tcx> LateLintPass<'tcx> for SysvarGet {
     fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
-        anchor_sysvar_get(cx, item);
+        for (field_span, sysvar) in anchor_sysvar_fields(cx, item) {
+            let uses = self.uses_mut(
+                Instruction::Accounts(item.owner_id.to_def_id()),
+                &sysvar,
+                item.hir_id(),
+            );
+            uses.hir_id = item.hir_id();
+            uses.accounts_struct = Some(item.ident.span);
+            uses.fields.push(field_span);
+        }
     }
 
     fn check_fn(
         &mut self,
         cx: &LateContext<'tcx>,
-        _: FnKind<'tcx>,
+        fn_kind: FnKind<'tcx>,
         _: &'tcx FnDecl<'tcx>,
         body: &'tcx Body<'tcx>,
         span: Span,
-        _: LocalDefId,
+        local_def_id: LocalDefId,
     ) {
-        if !span.from_expansion() {
-            let uses = find_from_account_info_exprs(cx, body);
+        if !is_skipped_expansion(SYSVAR_GET, span) {
+            let FromAccountInfoUses {
+                uses,
+                unsupported_gets,
+                ..
+            } = find_from_account_info_exprs(cx, body);
+            for (expr, sysvar) in unsupported_gets {
+                report_unsupported_get(cx, expr, sysvar);
+            }
+            if uses.is_empty() {
+                return;
+            }
+            let instruction = if matches!(fn_kind, FnKind::Closure) {
+                None
+            } else {
+                context_accounts_struct(cx, local_def_id)
+            }
+            .map_or(Instruction::Fn(local_def_id), Instruction::Accounts);
+            let hir_id = cx.tcx.local_def_id_to_hir_id(local_def_id);
             for (expr, sysvar) in &uses {
-                span_lint(
-                    cx,
-                    SYSVAR_GET,
-                    expr.span,
-                    &format!(
-                        "Use `{0}::get()` instead of `{0}::from_account_info(...)`",
-                        &sysvar
-                    ),
-                );
+                self.uses_mut(instruction, sysvar, hir_id)
+                    .calls
+                    .push(FromAccountInfoCall {
+                        span: expr.span,
+                        sugg: get_suggestion(cx, expr),
+                    });
             }
         }
     }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for uses in &self.uses {
+            let spans = uses
+                .fields
+                .iter()
+                .copied()
+                .chain(uses.calls.iter().map(|call| call.span))
+                .collect::<Vec<_>>();
+            let msg = if uses.fields.is_empty() {
+                format!(
+                    "Use `{0}::get()` instead of `{0}::from_account_info(...)`",
+                    uses.sysvar
+                )
+            } else {
+                format!("Use `{}::get` instead of passing the account", uses.sysvar)
+            };
+            span_lint_hir_and_then(cx, SYSVAR_GET, uses.hir_id, spans, &msg, |diag| {
+                if let Some(accounts_struct) = uses.accounts_struct {
+                    diag.span_label(
+                        accounts_struct,
+                        "Sysvar accounts passed in this instruction",
+                    );
+                    for call in &uses.calls {
+                        diag.span_label(
+                            call.span,
+                            format!(
+                                "`{}` is also deserialized from the account here",
+                                uses.sysvar
+                            ),
+                        );
+                    }
+                }
+                for call in &uses.calls {
+                    if let Some((sugg, applicability)) = &call.sugg {
+                        diag.span_suggestion_verbose(
+                            call.span,
+                            format!("use `{}::get()` instead", uses.sysvar),
+                            sugg,
+                            *applicability,
+                        );
+                    }
+                }
+            });
+        }
+
+        sarif::write(cx, &[SYSVAR_GET]);
+    }
 }
 
+/// A sysvar, and whether it implements `Sysvar::get`
+struct SysvarInfo {
+    name: &'static str,
+    path: &'static [&'static str],
+    /// False if `Sysvar::get` always fails with `UnsupportedSysvar`, e.g., for `SlotHashes`, which
+    /// can only be read from its account
+    implements_get: bool,
+}
+
+const SYSVARS: [SysvarInfo; 9] = [
+    SysvarInfo {
+        name: "Clock",
+        path: &paths::SYSVAR_CLOCK,
+        implements_get: true,
+    },
+    SysvarInfo {
+        name: "EpochRewards",
+        path: &paths::SYSVAR_EPOCH_REWARDS,
+        implements_get: true,
+    },
+    SysvarInfo {
+        name: "EpochSchedule",
+        path: &paths::SYSVAR_EPOCH_SCHEDULE,
+        implements_get: true,
+    },
+    SysvarInfo {
+        name: "Fees",
+        path: &paths::SYSVAR_FEES,
+        implements_get: true,
+    },
+    SysvarInfo {
+        name: "LastRestartSlot",
+        path: &paths::SYSVAR_LAST_RESTART_SLOT,
+        implements_get: true,
+    },
+    SysvarInfo {
+        name: "RecentBlockhashes",
+        path: &paths::SYSVAR_RECENT_BLOCKHASHES,
+        implements_get: false,
+    },
+    SysvarInfo {
+        name: "Rent",
+        path: &paths::SYSVAR_RENT,
+        implements_get: true,
+    },
+    SysvarInfo {
+        name: "SlotHashes",
+        path: &paths::SYSVAR_SLOT_HASHES,
+        implements_get: false,
+    },
+    SysvarInfo {
+        name: "StakeHistory",
+        path: &paths::SYSVAR_STAKE_HISTORY,
+        implements_get: false,
+    },
+];
+
 struct FromAccountInfoUses<'cx, 'tcx> {
     cx: &'cx LateContext<'tcx>,
     uses: Vec<(&'tcx Expr<'tcx>, String)>,
+    /// `T::get()` calls of sysvars not implementing `Sysvar::get`
+    unsupported_gets: Vec<(&'tcx Expr<'tcx>, &'static str)>,
 }
 
-fn find_from_account_info_exprs<'tcx>(
-    cx: &LateContext<'tcx>,
+/// Find the `T::from_account_info(...)` calls of `body` of sysvars implementing `Sysvar::get`, and
+/// the `T::get()` calls of sysvars not implementing it
+fn find_from_account_info_exprs<'cx, 'tcx>(
+    cx: &'cx LateContext<'tcx>,
     body: &'tcx Body<'tcx>,
-) -> Vec<(&'tcx Expr<'tcx>, String)> {
+) -> FromAccountInfoUses<'cx, 'tcx> {
     let mut f = FromAccountInfoUses {
         cx,
         uses: Vec::new(),
+        unsupported_gets: Vec::new(),
     };
     f.visit_expr(body.value);
-    f.uses
+    f
 }
 
 impl<'cx, 'tcx> Visitor<'tcx> for FromAccountInfoUses<'cx, 'tcx> {
@@ -140,48 +361,88 @@ impl<'cx, 'tcx> Visitor<'tcx> for FromAccountInfoUses<'cx, 'tcx> {
             if let ExprKind::Call(func, _) = expr.kind;
             // T::x()
             if let ExprKind::Path(QPath::TypeRelative(ty_t, _)) = func.kind;
-            // T::from_account_info()
+            // T::from_account_info() or T::get()
             if let Some(def_id) = self.cx.typeck_results().type_dependent_def_id(func.hir_id);
-            if match_def_path(self.cx, def_id, &paths::SYSVAR_FROM_ACCOUNT_INFO);
-            // T is either CLOCK, EpochRewards, EpochSchedule, Fees, LastRestartSlot, Rent
+            let is_get = match_def_path(self.cx, def_id, &paths::SYSVAR_GET);
+            if is_get || match_def_path(self.cx, def_id, &paths::SYSVAR_FROM_ACCOUNT_INFO);
             if let TyKind::Path(ty_qpath) = &ty_t.kind;
             let res = self.cx.typeck_results().qpath_res(ty_qpath, ty_t.hir_id);
             if let Res::Def(_, t_def_id) = res;
-            if let Some(ind) = match_any_def_paths(
-                self.cx,
-                t_def_id,
-                &[
-                    &paths::SYSVAR_CLOCK,
-                    &paths::SYSVAR_EPOCH_REWARDS,
-                    &paths::SYSVAR_EPOCH_SCHEDULE,
-                    &paths::SYSVAR_FEES,
-                    &paths::SYSVAR_LAST_RESTART_SLOT,
-                    &paths::SYSVAR_RENT,
-                ],
-            );
+            if let Some(sysvar) = SYSVARS
+                .iter()
+                .find(|sysvar| match_def_path(self.cx, t_def_id, sysvar.path));
             then {
-                self.uses.push((
-                    expr,
-                    [
-                        "Clock",
-                        "EpochRewards",
-                        "EpochSchedule",
-                        "Fees",
-                        "LastRestartSlot",
-                        "Rent",
-                    ][ind]
-                        .to_string(),
-                ));
+                // `from_account_info` is the only way to read the sysvars not implementing
+                // `Sysvar::get`, so it is reported only for the others
+                if is_get && !sysvar.implements_get {
+                    self.unsupported_gets.push((expr, sysvar.name));
+                } else if !is_get && sysvar.implements_get {
+                    self.uses.push((expr, sysvar.name.to_string()));
+                }
             }
         }
         walk_expr(self, expr);
     }
 }
 
-fn anchor_sysvar_get<'tcx>(cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+fn report_unsupported_get(cx: &LateContext<'_>, expr: &Expr<'_>, sysvar: &str) {
+    span_lint_hir_and_then(
+        cx,
+        SYSVAR_GET,
+        expr.hir_id,
+        expr.span,
+        &format!("`{sysvar}::get()` always fails, as `{sysvar}` does not implement `Sysvar::get`"),
+        |diag| {
+            diag.help(format!(
+                "pass the `{sysvar}` sysvar account and read it with `{sysvar}::from_account_info`"
+            ));
+        },
+    );
+}
+
+/// Return the replacement `T::get()` for the call `T::from_account_info(arg)`. The replacement drops
+/// `arg`, so it is machine applicable only if `arg` is a place expression.
+fn get_suggestion(cx: &LateContext<'_>, expr: &Expr<'_>) -> Option<(String, Applicability)> {
+    if_chain! {
+        if let ExprKind::Call(func, args) = expr.kind;
+        if let ExprKind::Path(QPath::TypeRelative(ty_t, _)) = func.kind;
+        then {
+            let mut applicability = if args.iter().all(is_place_expr) {
+                Applicability::MachineApplicable
+            } else {
+                // e.g., `Clock::from_account_info(next_account_info(account_info_iter)?)`, where
+                // dropping the argument would change which accounts are read next
+                Applicability::MaybeIncorrect
+            };
+            let ty_snippet = snippet_with_applicability(cx, ty_t.span, "..", &mut applicability);
+            Some((format!("{ty_snippet}::get()"), applicability))
+        } else {
+            None
+        }
+    }
+}
+
+/// Return true if `expr` is a path, possibly with fields, borrows and derefs, e.g.,
+/// `&ctx.accounts.clock`
+fn is_place_expr(expr: &Expr<'_>) -> bool {
+    match expr.kind {
+        ExprKind::Path(_) => true,
+        ExprKind::Field(inner, _)
+        | ExprKind::AddrOf(_, _, inner)
+        | ExprKind::Unary(UnOp::Deref, inner) => is_place_expr(inner),
+        _ => false,
+    }
+}
+
+/// Return the spans of the fields of an Anchor `Accounts` struct which are sysvar accounts implementing
+/// `Sysvar::get`, and the names of the sysvars
+fn anchor_sysvar_fields<'tcx>(
+    cx: &LateContext<'tcx>,
+    item: &'tcx Item<'tcx>,
+) -> Vec<(Span, String)> {
+    let mut sysvar_fields = Vec::new();
     if let ItemKind::Struct(variant, _) = item.kind {
         if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
-            let mut reported_fields = Vec::new();
             for (item_field, anchor_field) in
                 variant.fields().iter().zip(accounts_struct.fields.iter())
             {
@@ -189,56 +450,36 @@ fn anchor_sysvar_get<'tcx>(cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
                 // The field represents multiple accounts. As this function will report that struct, Composite
                 // fields are ignored here.
                 // TODO: Confirm above statement.
-                if let AccountField::Field(field) = anchor_field {
-                    if let FieldTy::Sysvar(sysvar_ty) = &field.ty {
-                        if matches!(
-                            sysvar_ty,
-                            SysvarTy::Clock
-                                | SysvarTy::Rewards
-                                | SysvarTy::EpochSchedule
-                                | SysvarTy::Fees
-                                | SysvarTy::Rent
-                        ) {
-                            reported_fields.push((item_field, format!("{sysvar_ty:?}")));
-                        }
+                if_chain! {
+                    if let AccountField::Field(field) = anchor_field;
+                    if let FieldTy::Sysvar(sysvar_ty) = &field.ty;
+                    if let Some(sysvar) = anchor_sysvar_with_get(sysvar_ty);
+                    then {
+                        sysvar_fields.push((item_field.span, sysvar.to_string()));
                     }
                 }
             }
-            if reported_fields.is_empty() {
-                return;
-            }
-            let warn_message = if reported_fields.len() == 1 {
-                format!(
-                    "Use `{}::get` instead of passing the account",
-                    reported_fields[0].1.as_str()
-                )
-            } else {
-                let (last_field, fields) = reported_fields.split_last().unwrap();
-                format!(
-                    "Use `Sysvar::get` instead of passing the accounts for `{}`, and `{}`.",
-                    fields
-                        .iter()
-                        .map(|f| f.1.as_str())
-                        .collect::<Vec<&str>>()
-                        .join("`, `"),
-                    last_field.1.as_str()
-                )
-            };
-
-            span_lint_and_then(
-                cx,
-                SYSVAR_GET,
-                reported_fields.iter().map(|f| f.0.span).collect::<Vec<_>>(),
-                &warn_message,
-                |diag| {
-                    diag.span_label(
-                        item.ident.span,
-                        "Sysvar accounts passed in this instruction",
-                    );
-                },
-            );
         }
     }
+    sysvar_fields
+}
+
+/// Return the name of the sysvar of a `Sysvar<'info, T>` field, if it implements `Sysvar::get`
+fn anchor_sysvar_with_get(sysvar_ty: &SysvarTy) -> Option<&'static str> {
+    match sysvar_ty {
+        SysvarTy::Clock => Some("Clock"),
+        SysvarTy::EpochSchedule => Some("EpochSchedule"),
+        SysvarTy::Fees => Some("Fees"),
+        SysvarTy::Rent => Some("Rent"),
+        // The other sysvars, e.g., `Rewards`, `SlotHashes` and `StakeHistory`, can only be read
+        // from their account
+        SysvarTy::RecentBlockhashes
+        | SysvarTy::SlotHashes
+        | SysvarTy::SlotHistory
+        | SysvarTy::StakeHistory
+        | SysvarTy::Instructions
+        | SysvarTy::Rewards => None,
+    }
 }
 
 #[test]
@@ -246,6 +487,16 @@ fn insecure() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
 }
 
+#[test]
+fn insecure_grouped() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-grouped");
+}
+
+#[test]
+fn insecure_unsupported_get() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-unsupported-get");
+}
+
 #[test]
 fn secure() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod sysvar_get_insecure_grouped {
+    use super::*;
+
+    pub fn check_expiry(ctx: Context<CheckExpiry>, expiry: i64) -> ProgramResult {
+        let clock = Clock::from_account_info(&ctx.accounts.clock.to_account_info())?;
+        if clock.unix_timestamp > expiry {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CheckExpiry<'info> {
+    authority: Signer<'info>,
+    clock: Sysvar<'info, Clock>,
+}
+
+#[allow(dead_code)]
+fn main() {}
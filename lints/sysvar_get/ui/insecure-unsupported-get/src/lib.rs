@@ -0,0 +1,35 @@
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::sysvar::{
+    recent_blockhashes::RecentBlockhashes, slot_hashes::SlotHashes, stake_history::StakeHistory,
+};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod sysvar_get_insecure_unsupported_get {
+    use super::*;
+
+    pub fn log_history(_ctx: Context<LogHistory>) -> ProgramResult {
+        let slot_hashes = SlotHashes::get()?;
+        let stake_history = StakeHistory::get()?;
+        let recent_blockhashes = RecentBlockhashes::get()?;
+        msg!(
+            "{} {} {}",
+            slot_hashes.len(),
+            stake_history.len(),
+            recent_blockhashes.len()
+        );
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct LogHistory<'info> {
+    authority: Signer<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
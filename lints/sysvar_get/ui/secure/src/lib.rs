@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::sysvar::{slot_hashes::SlotHashes, stake_history::StakeHistory};
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
@@ -16,6 +17,12 @@ pub mod sysvar_get_secure {
         );
         Ok(())
     }
+
+    pub fn log_history(ctx: Context<LogHistory>) -> ProgramResult {
+        let slot_hashes = SlotHashes::from_account_info(&ctx.accounts.slot_hashes)?;
+        msg!("{} {}", slot_hashes.len(), ctx.accounts.stake_history.len());
+        Ok(())
+    }
 }
 
 #[derive(Accounts)]
@@ -23,5 +30,12 @@ pub struct LogMessage<'info> {
     authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct LogHistory<'info> {
+    /// CHECK: read with `SlotHashes::from_account_info`, which checks the key
+    slot_hashes: AccountInfo<'info>,
+    stake_history: Sysvar<'info, StakeHistory>,
+}
+
 #[allow(dead_code)]
 fn main() {}
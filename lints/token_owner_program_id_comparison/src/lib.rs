@@ -0,0 +1,180 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{fn_def_id, ty::match_type};
+use rustc_hir::{
+    def::{DefKind, Res},
+    BinOpKind, Expr, ExprKind, QPath,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, paths, sarif,
+    utils::peel_derefs_and_borrows,
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports comparisons (`==` or `!=`, including `require_keys_eq!` and `require_keys_neq!`) of
+    /// the `owner` field of an SPL token account with the id of the program, i.e.,
+    /// `ctx.program_id`, the `program_id` parameter of a non-Anchor instruction, or the `ID` and
+    /// `id()` generated by `declare_id!`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The `owner` field of a token account, e.g., of an `Account<'info, TokenAccount>`, is the
+    /// authority of the account, i.e., the wallet or the PDA which can transfer its tokens. It is
+    /// not the program owning the account, which is always the token program. No token account
+    /// has the program id as its authority, since a program cannot sign for its own id, so a
+    /// check requiring it always fails, and a check rejecting it always passes, without checking
+    /// the expected authority.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The program id is only recognized through the expressions listed above, not through a local
+    /// variable or a constant holding it.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// require_keys_eq!(ctx.accounts.vault.owner, *ctx.program_id);
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// require_keys_eq!(ctx.accounts.vault.owner, ctx.accounts.vault_authority.key());
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_expr:
+    ///
+    /// - for each comparison (`==` or `!=`), ignoring the borrows and the derefs of the operands
+    /// - if one operand is the `owner` field of `spl_token::state::Account` or
+    ///   `spl_token_2022::state::Account`, which the Anchor `TokenAccount` types deref to, and the
+    ///   other is the program id, report the comparison, at the call of the macro generating it, if
+    ///   any
+    pub TOKEN_OWNER_PROGRAM_ID_COMPARISON,
+    Warn,
+    "the owner field of a token account, i.e., its authority, compared with the program id"
+}
+
+impl<'tcx> LateLintPass<'tcx> for TokenOwnerProgramIdComparison {
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        let (lhs, rhs) = match expr.kind {
+            ExprKind::Binary(op, lhs, rhs) if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne) => {
+                (lhs, rhs)
+            }
+            _ => return,
+        };
+        let token_owner = if is_token_owner(cx, lhs) {
+            lhs
+        } else if is_token_owner(cx, rhs) {
+            rhs
+        } else {
+            return;
+        };
+        // The comparisons generated by macros, e.g., `require_keys_eq!`, are reported if the token
+        // account comes from the code of the crate.
+        if is_skipped_expansion(TOKEN_OWNER_PROGRAM_ID_COMPARISON, token_owner.span)
+            || !(is_program_id(cx, lhs) || is_program_id(cx, rhs))
+        {
+            return;
+        }
+        span_lint_hir_and_then(
+            cx,
+            TOKEN_OWNER_PROGRAM_ID_COMPARISON,
+            expr.hir_id,
+            comparison_span(expr),
+            "the `owner` field of a token account is compared with the program id",
+            |diag| {
+                diag.note(
+                    "the `owner` field of a token account is its authority, i.e., the wallet or the PDA which can transfer its tokens, not the program owning the account, which is the token program",
+                );
+                diag.help(
+                    "compare the `owner` field with the expected authority, e.g., a PDA of the program, or, to check the program owning the account, compare the `owner` of its `AccountInfo` with the token program id",
+                );
+            },
+        );
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[TOKEN_OWNER_PROGRAM_ID_COMPARISON]);
+    }
+}
+
+/// Return true if `expr` is the `owner` field of an SPL token account, ignoring the borrows and
+/// the derefs, e.g., `ctx.accounts.vault.owner` where `vault` is an `Account<'info, TokenAccount>`
+fn is_token_owner(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    match peel_derefs_and_borrows(expr).kind {
+        ExprKind::Field(object, field_name) if field_name.as_str() == "owner" => {
+            let ty = cx.typeck_results().expr_ty_adjusted(object).peel_refs();
+            match_type(cx, ty, &paths::SPL_TOKEN_ACCOUNT)
+                || match_type(cx, ty, &paths::SPL_TOKEN_2022_ACCOUNT)
+        }
+        _ => false,
+    }
+}
+
+/// Return true if `expr` is the id of the program, ignoring the borrows and the derefs:
+/// - `ctx.program_id`, where `ctx` is an Anchor `Context`
+/// - a local variable named `program_id`, e.g., the parameter of a non-Anchor instruction
+/// - the `ID` constant or the `id()` function of the crate, generated by `declare_id!`
+fn is_program_id(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
+    let expr = peel_derefs_and_borrows(expr);
+    match expr.kind {
+        ExprKind::Field(object, field_name) => {
+            field_name.as_str() == "program_id"
+                && match_type(
+                    cx,
+                    cx.typeck_results().expr_ty_adjusted(object).peel_refs(),
+                    &paths::ANCHOR_LANG_CONTEXT,
+                )
+        }
+        ExprKind::Path(QPath::Resolved(_, path)) => match path.res {
+            Res::Local(hir_id) => cx.tcx.hir().name(hir_id).as_str() == "program_id",
+            Res::Def(DefKind::Const | DefKind::Static(_), def_id) => {
+                def_id.is_local() && cx.tcx.item_name(def_id).as_str() == "ID"
+            }
+            _ => false,
+        },
+        ExprKind::Call(_, []) => fn_def_id(cx, expr).map_or(false, |def_id| {
+            def_id.is_local() && cx.tcx.item_name(def_id).as_str() == "id"
+        }),
+        _ => false,
+    }
+}
+
+/// Return the span at which to report the comparison `expr`: the comparison itself, or the call of
+/// the macro generating it, e.g., `require_keys_eq!(vault.owner, *ctx.program_id)`
+fn comparison_span(expr: &Expr<'_>) -> Span {
+    if expr.span.from_expansion() {
+        expr.span.source_callsite()
+    } else {
+        expr.span
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
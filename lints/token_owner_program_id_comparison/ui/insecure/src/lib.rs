@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod token_owner_program_id_comparison_insecure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        if ctx.accounts.vault.owner != *ctx.program_id {
+            return Err(ProgramError::IllegalOwner.into());
+        }
+        msg!("withdraw {}", amount);
+        Ok(())
+    }
+
+    pub fn report(ctx: Context<Report>) -> Result<()> {
+        if ctx.accounts.vault.owner == crate::ID {
+            msg!("the vault belongs to the program");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    vault: Account<'info, TokenAccount>,
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Report<'info> {
+    vault: Account<'info, TokenAccount>,
+}
+
+pub fn is_program_vault(program_id: &Pubkey, vault: &TokenAccount) -> bool {
+    &vault.owner == program_id
+}
+
+#[allow(dead_code)]
+fn main() {}
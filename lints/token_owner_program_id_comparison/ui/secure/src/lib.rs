@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod token_owner_program_id_comparison_secure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        if ctx.accounts.vault.owner != ctx.accounts.vault_authority.key() {
+            return Err(ProgramError::IllegalOwner.into());
+        }
+        if ctx.accounts.vault.to_account_info().owner != &anchor_spl::token::ID {
+            return Err(ProgramError::IllegalOwner.into());
+        }
+        if ctx.accounts.config.owner != ctx.program_id {
+            return Err(ProgramError::IllegalOwner.into());
+        }
+        msg!("withdraw {}", amount);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    vault: Account<'info, TokenAccount>,
+    /// CHECK: the PDA owning the vault
+    #[account(seeds = [b"vault"], bump)]
+    vault_authority: UncheckedAccount<'info>,
+    /// CHECK: the configuration of the program
+    config: AccountInfo<'info>,
+    token_program: Program<'info, Token>,
+}
+
+#[allow(dead_code)]
+fn main() {}
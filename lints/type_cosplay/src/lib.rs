@@ -3,6 +3,7 @@
 #![recursion_limit = "256"]
 
 extern crate rustc_data_structures;
+extern crate rustc_errors;
 extern crate rustc_hir;
 extern crate rustc_index;
 extern crate rustc_middle;
@@ -10,34 +11,45 @@ extern crate rustc_span;
 extern crate rustc_target;
 
 use clippy_utils::{
-    diagnostics::span_lint_and_help,
-    get_trait_def_id, match_def_path,
-    ty::{implements_trait, match_type},
+    fn_def_id, get_trait_def_id, match_any_def_paths, match_def_path,
+    source::snippet_with_applicability, ty::implements_trait,
 };
 use if_chain::if_chain;
-use rustc_data_structures::fx::FxHashMap;
-use rustc_hir::{def::Res, Expr, ExprKind, QPath, TyKind};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_errors::Applicability;
+use rustc_hir::{def::Res, BorrowKind, Expr, ExprKind, HirId, Mutability, QPath, TyKind};
 use rustc_index::Idx;
 use rustc_lint::{LateContext, LateLintPass};
 use rustc_middle::ty::{AdtDef, TyKind as MiddleTyKind};
-use rustc_session::DataTypeKind;
 use rustc_span::{def_id::DefId, Span};
 use rustc_target::abi::FieldIdx;
-use solana_lints::{paths, utils::visit_expr_no_bodies};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    discriminators::{account_types, type_path},
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{
+        borsh_layout::{borsh_layout, Atom, Layout},
+        visit_expr_no_bodies,
+    },
+};
 
-dylint_linting::impl_late_lint! {
+solana_lints::impl_late_lint! {
     /// **What it does:**
     ///
-    /// Checks that all deserialized types have a proper discriminant so that
-    /// all types are guaranteed to deserialize differently.
+    /// Checks that the deserialized types cannot be confused with other types of the program, i.e.,
+    /// that no other type has an equivalent serialization layout, unless both types have a proper
+    /// discriminant.
     ///
-    /// Instead of searching for equivalent types and checking to make sure those specific
-    /// types have a discriminant, this lint takes a more strict approach and instead enforces
-    /// all deserialized types it collects, to have a discriminant, regardless of whether the
-    /// types are equivalent or not.
+    /// The lint computes the Borsh layouts of the deserialized types and of the other types of the
+    /// crate implementing `BorshDeserialize` (see `solana_lints::utils::borsh_layout`), flattening
+    /// the newtypes, the tuples and the nested structs, so that, e.g., `struct A(Pubkey, u64)` and
+    /// `struct B { key: Key, amount: (u32, u32) }` are equivalent. Two layouts are equivalent if
+    /// some data may be deserialized as both types. The types with a unique layout are not
+    /// reported.
     ///
     /// We define a proper discriminant as an enum with as many variants as there are struct
-    /// types in the program. Further, the discriminant should be the first field of every
+    /// types starting with it. Further, the discriminant should be the first field of every
     /// struct in order to avoid overwrite by arbitrary length fields, like vectors.
     ///
     /// A second case of a proper discriminant is when a single enum contains as variants all the struct
@@ -45,6 +57,13 @@ dylint_linting::impl_late_lint! {
     /// discriminant. If it is the only type that is deserialized, then all struct types
     /// are guaranteed to be unique since the program will have to match a specific variant.
     ///
+    /// The lint also checks the types deserialized in place (zero-copy) with `bytemuck`, e.g.,
+    /// `bytemuck::from_bytes::<T>(&data[..])`. Such a type cannot contain an enum, so its proper
+    /// discriminant is a first field whose name contains `discriminator` or `discriminant`, and
+    /// whose type is an unsigned integer or a byte array. A type with an Anchor discriminator,
+    /// i.e., an `#[account(zero_copy)]` type, should be loaded with `AccountLoader::load`, which
+    /// checks the discriminator, rather than with `bytemuck`.
+    ///
     /// **Why is this bad?**
     ///
     /// The type cosplay issue is when one account type can be substituted for another account type.
@@ -58,15 +77,32 @@ dylint_linting::impl_late_lint! {
     /// - [ ] Anchor
     /// - [x] Non Anchor
     ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
     /// **Known problems:**
     ///
-    /// In the case when only one enum is deserialized, this lint by default
-    /// regards that as secure. However, this is not always the case. For example, if the program
-    /// defines another enum and serializes, but never deserializes it, a user could create this enum,
-    /// and, if it deserializes the same as the first enum, then this may be a possible vulnerability.
+    /// The deserialized enums are only compared with the other deserialized types, so in the case
+    /// when only one enum is deserialized, this lint regards that as secure. However, this is not
+    /// always the case. For example, if the program defines another enum and serializes, but
+    /// never deserializes it, a user could create this enum, and, if it deserializes the same as
+    /// the first enum, then this may be a possible vulnerability.
     ///
     /// Furthermore, one may have alternative definitions of a discriminant, such as using a bool,
-    /// or u8, and not an enum. This will flag a false positive.
+    /// or u8, and not an enum. This will flag a false positive if another type has an equivalent
+    /// layout.
+    ///
+    /// The values of variable length, e.g., `Vec` and `String`, and the types of other crates are
+    /// compared by type only, and the layouts of the generic types are not computed, so such types
+    /// may be missed. Only the types of the crate are considered, not those of other programs.
+    ///
+    /// For the zero-copy types, the lint only checks that the discriminator field exists, not that
+    /// the program compares it with the expected value. The types deserialized with `bytemuck` and
+    /// the types deserialized with Borsh are checked separately.
+    ///
+    /// `AccountLoader::load` is not reported: `AccountLoader` only accepts types with an Anchor
+    /// discriminator, and `load` checks it.
     ///
     /// ## Note on Tests
     ///
@@ -146,6 +182,35 @@ dylint_linting::impl_late_lint! {
     /// from a single enum, and that enum encapsulates all of the user-defined types. Since enums contain
     /// an implicit discriminant, this program will always be secure as long as all types are defined under the enum.
     ///
+    /// ### insecure-nested
+    ///
+    /// Insecure because `Vault { owner: Pubkey, amount: u64 }` and
+    /// `Position { market: Key, sizes: (u32, u32) }`, where `Key` is a newtype of `Pubkey`, are
+    /// both serialized as 40 bytes, so a `Position` account can be passed as a `Vault` account.
+    ///
+    /// ### secure-unique-layouts
+    ///
+    /// Secure although the deserialized types have no discriminant, because no two types of the
+    /// program have equivalent layouts.
+    ///
+    /// ### insecure-zero-copy
+    ///
+    /// Insecure because the program deserializes the `User` and `Metadata` types in place with
+    /// `bytemuck`, and neither type has a discriminator field. The types have the same layout, so a
+    /// `Metadata` account can be passed as a `User` account.
+    ///
+    /// ### insecure-zero-copy-anchor
+    ///
+    /// Insecure because the program skips the discriminator of the `#[account(zero_copy)]` type
+    /// `Vault` and deserializes the rest of the data with `bytemuck`, without checking the
+    /// discriminator.
+    ///
+    /// ### secure-zero-copy
+    ///
+    /// Secure because the types deserialized with `bytemuck` start with a `discriminator` field,
+    /// which the program checks, and the `#[account(zero_copy)]` type is loaded with
+    /// `AccountLoader::load`.
+    ///
     /// **How the lint is implemented:**
     ///
     /// - Find call expressions which has an arg that accesses account data
@@ -153,21 +218,27 @@ dylint_linting::impl_late_lint! {
     /// - Get the type that the function was called on, ie X in X::call()
     /// - if `X` implements `anchor_lang::Discriminator` trait but the function called is not `try_deserialize`
     ///   - warn to use `try_deserialize` or to account for type's discriminator
+    ///   - if the function called is Borsh `try_from_slice`, suggest rewriting the call
+    ///     `X::try_from_slice(data)` as `X::try_deserialize(&mut &data[..])`
     /// - else if the function called is Borsh `try_from_slice`, collect the deserialized type
+    /// - if the function called is `bytemuck::from_bytes`, `bytemuck::try_from_bytes`, or one of
+    ///   their `_mut` variants, get the type `X` it is instantiated with
+    ///   - if `X` implements `anchor_lang::Discriminator`, warn to use `AccountLoader::load`
+    ///   - else collect the deserialized type as a zero-copy type
     /// - Repeat the above for all call expressions and collect all deserialized types; `X` from `X::try_from_slice()` expressions.
-    /// - If number of different kinds of types deserialized is more than `1`, i.e the
-    ///   code deserializes `Enum` type as well as a `Struct` type, etc.
+    /// - Check the Borsh types and the zero-copy types separately, as follows:
+    /// - Compute the Borsh layouts of the deserialized types and of the other types of the crate
+    ///   implementing `BorshDeserialize`, preceded by 8 bytes for the Anchor `#[account]` types.
+    ///   Two distinct types are equivalent if their layouts overlap, unless they are structs whose
+    ///   first field is the same enum, with at least as many variants as there are types starting
+    ///   with it.
+    /// - If a deserialized struct and a deserialized enum are equivalent
     ///   - warn to either deserialize from only structs or only an enum
-    /// - Else If the deserialized types are all enum
-    ///   - If number of deserialized enums are more than `1`
-    ///     - warn to use single enum that contains all type definitions
-    ///   - Else assume that the single enum is safe and do not warn
-    /// - Else the deserialized types are structs
-    ///   - For each deserialized type
-    ///     - If the struct has first field of type enum and number of variants of the enum are more than the
-    ///       number of deserialized types
-    ///       - The struct has proper discriminant; do not warn.
-    ///     - Else warn to add an enum with at least as many variants as there are deserialized types.
+    /// - If two deserialized enums are equivalent
+    ///   - warn to use single enum that contains all type definitions
+    /// - For each other deserialized struct, if it is equivalent to another type
+    ///   - warn to add an enum with at least as many variants as there are deserialized types.
+    /// - For a zero-copy type, check that the first field is a discriminator field instead.
     pub TYPE_COSPLAY,
     Warn,
     "type is equivalent to another type",
@@ -176,13 +247,23 @@ dylint_linting::impl_late_lint! {
 
 #[derive(Default)]
 struct TypeCosplay {
-    deser_types: FxHashMap<DataTypeKind, Vec<(DefId, Span)>>,
+    /// The deserialized types, in the order they are found
+    deser_types: FxHashMap<Deserializer, Vec<(DefId, HirId, Span)>>,
+}
+
+/// The kinds of functions deserializing account data
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Deserializer {
+    /// Borsh `try_from_slice`
+    Borsh,
+    /// `bytemuck::from_bytes` and the like, which reinterpret the data in place
+    ZeroCopy,
 }
 
 impl<'tcx> LateLintPass<'tcx> for TypeCosplay {
     fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
         if_chain! {
-            if !expr.span.from_expansion();
+            if !is_skipped_expansion(TYPE_COSPLAY, expr.span);
             if let ExprKind::Call(fnc_expr, args_exprs) = expr.kind;
             // TODO: recommended-2 case will exit early since it contains a reference to AccountInfo.data,
             // not a direct argument. In general, any references will fail
@@ -190,9 +271,7 @@ impl<'tcx> LateLintPass<'tcx> for TypeCosplay {
             // `AccountInfo.data`. But @victor-wei126's comment is still relevant in that we need a
             // more general solution for finding references to `AccountInfo.data`.
             // do any of the args access `x.data` where x is of type `AccountInfo`
-            if args_exprs.iter().any(|arg| {
-                visit_expr_no_bodies(arg, |expr| contains_data_field_reference(cx, expr))
-            });
+            if accesses_account_data(cx, args_exprs);
             // get the type that the function was called on, ie X in X::call()
             if let ExprKind::Path(qpath) = &fnc_expr.kind;
             if let QPath::TypeRelative(ty, _) = qpath;
@@ -210,62 +289,303 @@ impl<'tcx> LateLintPass<'tcx> for TypeCosplay {
                     if !match_def_path(cx, def_id, &paths::ANCHOR_LANG_TRY_DESERIALIZE);
                     then {
                         // warn to use `try_deserialize`
-                        span_lint_and_help(
+                        span_lint_hir_and_then(
                             cx,
                             TYPE_COSPLAY,
+                            expr.hir_id,
                             fnc_expr.span,
                             &format!("`{middle_ty}` type implements the `Discriminator` trait. If you are attempting to deserialize\n here, you probably want try_deserialize() instead."),
-                            None,
-                            "otherwise, make sure you are accounting for this type's discriminator in your deserialization function"
+                            |diag| {
+                                diag.help("otherwise, make sure you are accounting for this type's discriminator in your deserialization function");
+                                // `T::try_from_slice(data)` can be rewritten as `T::try_deserialize(&mut &data[..])`
+                                if_chain! {
+                                    if is_deserialize_function(cx, fnc_expr);
+                                    if let [arg] = args_exprs;
+                                    then {
+                                        let (sugg, applicability) = try_deserialize_suggestion(cx, ty.span, arg);
+                                        diag.span_suggestion_verbose(
+                                            expr.span,
+                                            "use `try_deserialize`, which checks the discriminator",
+                                            sugg,
+                                            applicability,
+                                        );
+                                    }
+                                }
+                            },
                         );
                     } else {
                         // currently only checks borsh::try_from_slice()
                         if is_deserialize_function(cx, fnc_expr) {
                             if let MiddleTyKind::Adt(adt_def, _) = middle_ty.kind() {
-                                let adt_kind = adt_def.adt_kind();
                                 let def_id = adt_def.did();
                                 // store the deserialized type
-                                if let Some(vec) = self.deser_types.get_mut(&adt_kind.into()) {
-                                    vec.push((def_id, ty.span));
-                                } else {
-                                    self.deser_types.insert(adt_kind.into(), vec![(def_id, ty.span)]);
-                                }
+                                self.deser_types
+                                    .entry(Deserializer::Borsh)
+                                    .or_default()
+                                    .push((def_id, expr.hir_id, ty.span));
                             }
                         }
                     }
                 }
             }
         }
+
+        if_chain! {
+            if !is_skipped_expansion(TYPE_COSPLAY, expr.span);
+            if let ExprKind::Call(fnc_expr, args_exprs) = expr.kind;
+            if accesses_account_data(cx, args_exprs);
+            if let Some(def_id) = fn_def_id(cx, expr);
+            if match_any_def_paths(cx, def_id, &[
+                &paths::BYTEMUCK_FROM_BYTES,
+                &paths::BYTEMUCK_FROM_BYTES_MUT,
+                &paths::BYTEMUCK_TRY_FROM_BYTES,
+                &paths::BYTEMUCK_TRY_FROM_BYTES_MUT,
+            ]).is_some();
+            // get the type that the function is instantiated with, ie X in from_bytes::<X>()
+            let middle_ty = cx.typeck_results().node_args(fnc_expr.hir_id).type_at(0);
+            if let MiddleTyKind::Adt(adt_def, _) = middle_ty.kind();
+            then {
+                if_chain! {
+                    if let Some(trait_did) = get_trait_def_id(cx, &paths::ANCHOR_LANG_DISCRIMINATOR);
+                    if implements_trait(cx, middle_ty, trait_did, &[]);
+                    then {
+                        // warn to use `AccountLoader::load`
+                        span_lint_hir_and_then(
+                            cx,
+                            TYPE_COSPLAY,
+                            expr.hir_id,
+                            fnc_expr.span,
+                            &format!("`{middle_ty}` type implements the `Discriminator` trait. If you are attempting to deserialize\n here, you probably want AccountLoader::load() instead."),
+                            |diag| {
+                                diag.help("otherwise, make sure you are accounting for this type's discriminator in your deserialization function");
+                            },
+                        );
+                    } else {
+                        // store the deserialized type
+                        self.deser_types
+                            .entry(Deserializer::ZeroCopy)
+                            .or_default()
+                            .push((adt_def.did(), expr.hir_id, fnc_expr.span));
+                    }
+                }
+            }
+        }
     }
 
     fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
-        // NOTE: the case where len == 0 does nothing, since no types are deserialized
-        #[allow(clippy::comparison_chain)]
-        if self.deser_types.len() == 1 {
-            let (k, v) = self.deser_types.iter().next().unwrap();
-            match k {
-                DataTypeKind::Enum => check_enums(cx, v),
-                _ => check_structs_have_discriminant(cx, v), // NOTE: also catches unions
+        for (&deserializer, deser_types) in &self.deser_types {
+            check_deser_types(cx, deserializer, deser_types);
+        }
+
+        sarif::write(cx, &[TYPE_COSPLAY]);
+    }
+}
+
+/// Check the types deserialized with `deserializer`
+fn check_deser_types(
+    cx: &LateContext<'_>,
+    deserializer: Deserializer,
+    deser_types: &[(DefId, HirId, Span)],
+) {
+    match deserializer {
+        Deserializer::Borsh => check_borsh_types(cx, deser_types),
+        Deserializer::ZeroCopy => {
+            for &(def_id, hir_id, span) in deser_types {
+                has_discriminator_field(cx, cx.tcx.adt_def(def_id), hir_id, span);
             }
-        } else if self.deser_types.len() > 1 {
-            // Number of AdtKind's of different deserialization is > 1
-            // Retrieve spans: iter through map, grab first elem of each key-pair, then get span
-            let mut spans = vec![];
-            self.deser_types.iter().for_each(|(_, v)| {
-                spans.push(v[0].1);
-            });
-            span_lint_and_help(
-                cx,
-                TYPE_COSPLAY,
-                spans[0],
-                "Deserializing from different ADT types.",
-                Some(spans[1]),
-                "deserialize from only structs with a discriminant, or an enum encapsulating all structs."
-            );
         }
     }
 }
 
+/// Check the types deserialized with Borsh: report the pairs of distinct types, at least one of
+/// which is deserialized, whose layouts are equivalent and which do not both have a proper
+/// discriminant
+fn check_borsh_types(cx: &LateContext<'_>, deser_types: &[(DefId, HirId, Span)]) {
+    let types = borsh_types(cx, deser_types);
+    let account_types = account_types(cx);
+    let layouts = types
+        .iter()
+        .filter_map(|&def_id| Some((def_id, stored_layout(cx, def_id, &account_types)?)))
+        .collect::<FxHashMap<_, _>>();
+    let equivalent = |def_id: DefId, other: DefId| {
+        def_id != other
+            && match (layouts.get(&def_id), layouts.get(&other)) {
+                (Some(layout), Some(other_layout)) => layout.overlaps(other_layout),
+                _ => false,
+            }
+            && !have_proper_discriminants(cx, &types, def_id, other)
+    };
+    let is_enum = |def_id: DefId| cx.tcx.adt_def(def_id).is_enum();
+    let mut reported = FxHashSet::default();
+
+    // a struct and an enum are deserialized
+    if let Some((first, second)) = find_pair(deser_types, |def_id, other| {
+        is_enum(def_id) != is_enum(other) && equivalent(def_id, other)
+    }) {
+        span_lint_hir_and_then(
+            cx,
+            TYPE_COSPLAY,
+            first.1,
+            first.2,
+            "Deserializing from different ADT types.",
+            |diag| {
+                diag.span_help(
+                    second.2,
+                    "deserialize from only structs with a discriminant, or an enum encapsulating all structs.",
+                );
+            },
+        );
+        reported.extend([first.0, second.0]);
+    }
+
+    // several enums are deserialized
+    if let Some((first, second)) = find_pair(deser_types, |def_id, other| {
+        is_enum(def_id) && is_enum(other) && equivalent(def_id, other)
+    }) {
+        span_lint_hir_and_then(
+            cx,
+            TYPE_COSPLAY,
+            first.1,
+            first.2,
+            "multiple enum types deserialized. Should only have one enum type to avoid possible equivalent types",
+            |diag| {
+                diag.span_help(
+                    second.2,
+                    "consider constructing a single enum that contains all type definitions as variants",
+                );
+            },
+        );
+        reported.extend([first.0, second.0]);
+    }
+
+    // the deserialized structs, compared with all the types
+    for &(def_id, hir_id, span) in deser_types {
+        if is_enum(def_id) || reported.contains(&def_id) {
+            continue;
+        }
+        let other = match types
+            .iter()
+            .copied()
+            .find(|&other| equivalent(def_id, other))
+        {
+            Some(other) => other,
+            None => continue,
+        };
+        let name = cx.tcx.item_name(def_id);
+        let other_name = cx.tcx.item_name(other);
+        span_lint_hir_and_then(
+            cx,
+            TYPE_COSPLAY,
+            hir_id,
+            span,
+            "type does not have a proper discriminant. It may be indistinguishable when deserialized.",
+            |diag| {
+                diag.span_note(
+                    cx.tcx.def_span(other),
+                    format!("the data of a `{other_name}` may be deserialized as a `{name}`"),
+                );
+                diag.help("add an enum with at least as many variants as there are struct definitions");
+            },
+        );
+    }
+}
+
+/// Return the first two deserialized types, in the order they are found, satisfying `predicate`
+fn find_pair(
+    deser_types: &[(DefId, HirId, Span)],
+    predicate: impl Fn(DefId, DefId) -> bool,
+) -> Option<((DefId, HirId, Span), (DefId, HirId, Span))> {
+    deser_types.iter().enumerate().find_map(|(i, &first)| {
+        deser_types[i + 1..]
+            .iter()
+            .find(|second| predicate(first.0, second.0))
+            .map(|&second| (first, second))
+    })
+}
+
+/// Return the deserialized types, followed by the other types of the crate implementing
+/// `BorshDeserialize`, i.e., the types whose data may be stored in an account
+fn borsh_types(cx: &LateContext<'_>, deser_types: &[(DefId, HirId, Span)]) -> Vec<DefId> {
+    let mut types = Vec::<DefId>::new();
+    for &(def_id, _, _) in deser_types {
+        if !types.contains(&def_id) {
+            types.push(def_id);
+        }
+    }
+    let mut other_types = get_trait_def_id(cx, &paths::BORSH_DESERIALIZE_TRAIT)
+        .map(|trait_id| {
+            cx.tcx
+                .all_impls(trait_id)
+                .filter_map(
+                    |impl_id| match cx.tcx.type_of(impl_id).skip_binder().kind() {
+                        MiddleTyKind::Adt(adt_def, _) if adt_def.did().is_local() => {
+                            Some(adt_def.did())
+                        }
+                        _ => None,
+                    },
+                )
+                .filter(|def_id| !types.contains(def_id))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    // the order of the impls is not stable
+    other_types.sort_by_key(|def_id| type_path(cx, *def_id));
+    other_types.dedup();
+    types.extend(other_types);
+    types
+}
+
+/// Return the layout of the data of an account holding a value of the type `def_id`, i.e., the
+/// Borsh layout of the type, preceded by the 8 bytes of the discriminator for an Anchor
+/// `#[account]` type
+fn stored_layout(cx: &LateContext<'_>, def_id: DefId, account_types: &[DefId]) -> Option<Layout> {
+    let layout = borsh_layout(cx, cx.tcx.type_of(def_id).instantiate_identity())?;
+    if !account_types.contains(&def_id) {
+        return Some(layout);
+    }
+    Some(Layout {
+        shapes: layout
+            .shapes
+            .into_iter()
+            .map(|shape| std::iter::once(Atom::Bytes(8)).chain(shape).collect())
+            .collect(),
+    })
+}
+
+/// Return true if `def_id` and `other` are structs with a proper discriminant, i.e., whose first
+/// field is of the same enum, which has at least as many variants as there are `types` whose first
+/// field is of this enum
+fn have_proper_discriminants(
+    cx: &LateContext<'_>,
+    types: &[DefId],
+    def_id: DefId,
+    other: DefId,
+) -> bool {
+    match (discriminant(cx, def_id), discriminant(cx, other)) {
+        (Some(enum_def_id), Some(other_enum_def_id)) if enum_def_id == other_enum_def_id => {
+            let num_types = types
+                .iter()
+                .filter(|&&def_id| discriminant(cx, def_id) == Some(enum_def_id))
+                .count();
+            cx.tcx.adt_def(enum_def_id).variants().len() >= num_types
+        }
+        _ => false,
+    }
+}
+
+/// Return the enum which is the type of the first field of the struct `def_id`, if any
+fn discriminant(cx: &LateContext<'_>, def_id: DefId) -> Option<DefId> {
+    let adt_def = cx.tcx.adt_def(def_id);
+    if !adt_def.is_struct() {
+        return None;
+    }
+    let first_field_def = adt_def.non_enum_variant().fields.iter().next()?;
+    match cx.tcx.type_of(first_field_def.did).skip_binder().kind() {
+        MiddleTyKind::Adt(field_adt_def, _) if field_adt_def.is_enum() => Some(field_adt_def.did()),
+        _ => None,
+    }
+}
+
 /// Return true if the expr is Borsh `try_from_slice` else false
 fn is_deserialize_function(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
     match cx.typeck_results().type_dependent_def_id(expr.hir_id) {
@@ -274,13 +594,43 @@ fn is_deserialize_function(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
     }
 }
 
+/// Return the replacement `T::try_deserialize(&mut &data[..])` for the call `T::try_from_slice(arg)`,
+/// where `ty_span` is the span of `T` and `data` is `arg` without the leading `&`, if any.
+///
+/// `try_deserialize` takes a `&mut &[u8]` and advances it past the bytes it reads, so the suggestion
+/// reborrows the data as a slice. The caller may still need to rework how the data is borrowed, hence
+/// the suggestion is `MaybeIncorrect`.
+fn try_deserialize_suggestion(
+    cx: &LateContext<'_>,
+    ty_span: Span,
+    arg: &Expr<'_>,
+) -> (String, Applicability) {
+    let mut applicability = Applicability::MaybeIncorrect;
+    let data = match arg.kind {
+        ExprKind::AddrOf(BorrowKind::Ref, Mutability::Not, inner) => inner,
+        _ => arg,
+    };
+    let ty_snippet = snippet_with_applicability(cx, ty_span, "..", &mut applicability);
+    let data_snippet = snippet_with_applicability(cx, data.span, "..", &mut applicability);
+    (
+        format!("{ty_snippet}::try_deserialize(&mut &{data_snippet}[..])"),
+        applicability,
+    )
+}
+
+/// Return true if any of `args` accesses `x.data` where x is of type `AccountInfo`
+fn accesses_account_data(cx: &LateContext<'_>, args: &[Expr<'_>]) -> bool {
+    args.iter()
+        .any(|arg| visit_expr_no_bodies(arg, |expr| contains_data_field_reference(cx, expr)))
+}
+
 /// Return true if the `expr` accesses `.data` on a value whose type is `solana_program::account_info::AccountInfo`
 fn contains_data_field_reference(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool {
     if_chain! {
         if let ExprKind::Field(obj_expr, ident) = expr.kind;
         if ident.as_str() == "data";
         let ty = cx.typeck_results().expr_ty(obj_expr).peel_refs();
-        if match_type(cx, ty, &paths::SOLANA_PROGRAM_ACCOUNT_INFO);
+        if paths::is_account_info_ty(cx, ty);
         then {
             true
         } else {
@@ -289,60 +639,39 @@ fn contains_data_field_reference(cx: &LateContext<'_>, expr: &Expr<'_>) -> bool
     }
 }
 
-// if number of enums are 1 then ignore otherwise warn the first two instances found
-fn check_enums(cx: &LateContext<'_>, enums: &[(DefId, Span)]) {
-    #[allow(clippy::comparison_chain)]
-    if enums.len() > 1 {
-        // TODO: can implement loop to print all spans if > 2 enums
-        let first_span = enums[0].1;
-        let second_span = enums[1].1;
-        span_lint_and_help(
+/// Checks if the zero-copy type `adt` has a discriminator field, i.e., a first field whose name
+/// contains `discriminator` or `discriminant`, and whose type is an unsigned integer or a byte
+/// array.
+fn has_discriminator_field(cx: &LateContext, adt: AdtDef, hir_id: HirId, span: Span) {
+    // an enum without variants has no values to deserialize
+    let variant = match adt.variants().iter().next() {
+        Some(variant) => variant,
+        None => return,
+    };
+    let is_discriminator = variant
+        .fields
+        .get(FieldIdx::new(0))
+        .map_or(false, |field_def| {
+            let name = field_def.name.as_str();
+            let ty = cx.tcx.type_of(field_def.did).skip_binder();
+            (name.contains("discriminator") || name.contains("discriminant"))
+                && match ty.kind() {
+                    MiddleTyKind::Uint(_) => true,
+                    MiddleTyKind::Array(elem_ty, _) => *elem_ty == cx.tcx.types.u8,
+                    _ => false,
+                }
+        });
+    if !is_discriminator {
+        span_lint_hir_and_then(
             cx,
             TYPE_COSPLAY,
-            first_span,
-            "multiple enum types deserialized. Should only have one enum type to avoid possible equivalent types",
-            Some(second_span),
-            "consider constructing a single enum that contains all type definitions as variants"
+            hir_id,
+            span,
+            "type does not have a discriminator field. It may be indistinguishable when deserialized.",
+            |diag| {
+                diag.help("add a discriminator field, e.g., `discriminator: [u8; 8]`, as the first field and check it, or use an `#[account(zero_copy)]` type with `AccountLoader`");
+            },
         );
-    } else if enums.len() == 1 {
-        // future check - check that single enum is safe
-        // check serialization
-    }
-}
-
-/// Check each of the struct has first field of type enum with number of variants > `types.len()`
-fn check_structs_have_discriminant(cx: &LateContext<'_>, types: &[(DefId, Span)]) {
-    let num_structs = types.len();
-    types
-        .iter()
-        .for_each(|t| has_discriminant(cx, cx.tcx.adt_def(t.0), num_structs, t.1));
-}
-
-/// Checks if `adt` has a proper discriminant. We define a proper discriminant as being an enum with
-/// the number of variants at least the number of deserialized structs. Further the discriminant should
-/// be the first field in the adt.
-fn has_discriminant(cx: &LateContext, adt: AdtDef, num_struct_types: usize, span: Span) {
-    // get the type of the first field
-    let variant = adt.variants().get(Idx::new(0)).unwrap();
-    let first_field_def = &variant.fields[FieldIdx::new(0)];
-    let ty = cx.tcx.type_of(first_field_def.did).skip_binder();
-    if_chain! {
-        // the type is an enum with variants > `num_struct_types``.
-        if let MiddleTyKind::Adt(adt_def, _) = ty.kind();
-        if adt_def.is_enum();
-        if adt_def.variants().len() >= num_struct_types;
-        then {
-            // struct has a proper discriminant
-        } else {
-            span_lint_and_help(
-                cx,
-                TYPE_COSPLAY,
-                span,
-                "type does not have a proper discriminant. It may be indistinguishable when deserialized.",
-                None,
-                "add an enum with at least as many variants as there are struct definitions"
-            );
-        }
     }
 }
 
@@ -376,6 +705,31 @@ fn secure_two() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-2");
 }
 
+#[test]
+fn insecure_nested() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-nested");
+}
+
+#[test]
+fn secure_unique_layouts() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-unique-layouts");
+}
+
+#[test]
+fn insecure_zero_copy() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-zero-copy");
+}
+
+#[test]
+fn insecure_zero_copy_anchor() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-zero-copy-anchor");
+}
+
+#[test]
+fn secure_zero_copy() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-zero-copy");
+}
+
 #[test]
 fn recommended() {
     dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "recommended");
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod type_cosplay_insecure_nested {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> ProgramResult {
+        let vault = Vault::try_from_slice(&ctx.accounts.vault.data.borrow()).unwrap();
+        if ctx.accounts.vault.owner != ctx.program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if vault.owner != ctx.accounts.owner.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if vault.amount < amount {
+            return Err(ProgramError::InsufficientFunds);
+        }
+        msg!("withdraw {} from {}", amount, vault.owner);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    vault: AccountInfo<'info>,
+    owner: Signer<'info>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Vault {
+    owner: Pubkey,
+    amount: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Key(Pubkey);
+
+// A `Position` is serialized as 40 bytes, like a `Vault`
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Position {
+    market: Key,
+    sizes: (u32, u32),
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod type_cosplay_insecure_zero_copy_anchor {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>) -> ProgramResult {
+        // The first 8 bytes are the discriminator, which is skipped without being checked
+        let vault = *bytemuck::from_bytes::<Vault>(&ctx.accounts.vault.data.borrow()[8..]);
+        if ctx.accounts.vault.owner != ctx.program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if vault.authority != ctx.accounts.authority.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("GM {}", vault.authority);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    vault: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+#[account(zero_copy)]
+pub struct Vault {
+    authority: Pubkey,
+    amount: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use bytemuck::{Pod, Zeroable};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod type_cosplay_insecure_zero_copy {
+    use super::*;
+
+    pub fn update_user(ctx: Context<UpdateUser>) -> ProgramResult {
+        let user = *bytemuck::from_bytes::<User>(&ctx.accounts.user.data.borrow());
+        if ctx.accounts.user.owner != ctx.program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if user.authority != ctx.accounts.authority.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("GM {}", user.authority);
+        Ok(())
+    }
+
+    pub fn update_metadata(ctx: Context<UpdateMetadata>) -> ProgramResult {
+        let metadata = *bytemuck::try_from_bytes::<Metadata>(&ctx.accounts.metadata.data.borrow())
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        if ctx.accounts.metadata.owner != ctx.program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        msg!("GM {}", metadata.account);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateUser<'info> {
+    user: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadata<'info> {
+    metadata: AccountInfo<'info>,
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct User {
+    authority: Pubkey,
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct Metadata {
+    account: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
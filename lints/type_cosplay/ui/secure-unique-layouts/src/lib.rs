@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use borsh::{BorshDeserialize, BorshSerialize};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod type_cosplay_secure_unique_layouts {
+    use super::*;
+
+    pub fn update_user(ctx: Context<UpdateUser>) -> ProgramResult {
+        let config = Config::try_from_slice(&ctx.accounts.config.data.borrow()).unwrap();
+        if config.paused {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let user = User::try_from_slice(&ctx.accounts.user.data.borrow()).unwrap();
+        if ctx.accounts.user.owner != ctx.program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if user.authority != ctx.accounts.authority.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("GM {} with {} lamports", user.authority, user.balance);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateUser<'info> {
+    config: AccountInfo<'info>,
+    user: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+// No discriminant is needed: no other type is serialized as 40 bytes
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct User {
+    authority: Pubkey,
+    balance: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Config {
+    admin: Pubkey,
+    fee_bps: u16,
+    paused: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Profile {
+    owner: Pubkey,
+    name: String,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use bytemuck::{Pod, Zeroable};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const USER_DISCRIMINATOR: [u8; 8] = [1, 0, 0, 0, 0, 0, 0, 0];
+const METADATA_DISCRIMINATOR: [u8; 8] = [2, 0, 0, 0, 0, 0, 0, 0];
+
+#[program]
+pub mod type_cosplay_secure_zero_copy {
+    use super::*;
+
+    pub fn update_user(ctx: Context<UpdateUser>) -> ProgramResult {
+        let user = *bytemuck::from_bytes::<User>(&ctx.accounts.user.data.borrow());
+        if ctx.accounts.user.owner != ctx.program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if user.discriminator != USER_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if user.authority != ctx.accounts.authority.key() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("GM {}", user.authority);
+        Ok(())
+    }
+
+    pub fn update_metadata(ctx: Context<UpdateMetadata>) -> ProgramResult {
+        let metadata = *bytemuck::try_from_bytes::<Metadata>(&ctx.accounts.metadata.data.borrow())
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        if ctx.accounts.metadata.owner != ctx.program_id {
+            return Err(ProgramError::IllegalOwner);
+        }
+        if metadata.discriminator != METADATA_DISCRIMINATOR {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("GM {}", metadata.account);
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        let vault = ctx.accounts.vault.load()?;
+        msg!("GM {}", vault.authority);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateUser<'info> {
+    user: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadata<'info> {
+    metadata: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    vault: AccountLoader<'info, Vault>,
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct User {
+    discriminator: [u8; 8],
+    authority: Pubkey,
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct Metadata {
+    discriminator: [u8; 8],
+    account: Pubkey,
+}
+
+#[account(zero_copy)]
+pub struct Vault {
+    authority: Pubkey,
+    amount: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,292 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::{AccountField, Ty as FieldTy};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, FnDecl, HirId, Item, ItemKind,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    sarif,
+    utils::{
+        anchor_constraints::ConstraintSummary,
+        closed_account, context_accounts_struct, get_anchor_accounts_struct, is_anchor_program,
+        signers::{contains_is_signer_use, context_contains_signer_field},
+        visit_expr_no_bodies,
+    },
+};
+use std::collections::HashSet;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the accounts closed by an instruction which does not check that any account signs
+    /// it:
+    ///
+    /// - the fields of Anchor `Accounts` structs with the `close = destination` constraint, if the
+    ///   struct has neither a `Signer` field nor a field with the `signer` constraint
+    /// - the assignments of `0` to the lamports of an account, i.e.,
+    ///   `**account.lamports.borrow_mut() = 0` and `**account.try_borrow_mut_lamports()? = 0`, in
+    ///   a function which neither takes a `Context` with a signer nor reads `is_signer`
+    ///
+    /// The lint complements `insecure_account_close`, which checks that the data of the closed
+    /// account is cleared.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Closing an account transfers its lamports to the destination and deletes it. If the
+    /// authority of the account is not checked to sign the instruction, anyone can close the
+    /// account, e.g., the account of another user, and choose the destination of its lamports.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The lint does not check that the signer is the authority of the closed account, e.g., with
+    /// `has_one = authority`. The `Accounts` structs with a composite field are assumed to have a
+    /// signer. In Anchor programs, only the functions taking a `Context` are checked; in other
+    /// programs, a helper closing an account is reported even if its callers check the signer.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Close<'info> {
+    ///     #[account(mut, close = destination)]
+    ///     vault: Account<'info, Vault>,
+    ///     /// CHECK: receives the lamports of the vault
+    ///     #[account(mut)]
+    ///     destination: AccountInfo<'info>,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Close<'info> {
+    ///     #[account(mut, close = destination, has_one = authority)]
+    ///     vault: Account<'info, Vault>,
+    ///     authority: Signer<'info>,
+    ///     /// CHECK: receives the lamports of the vault
+    ///     #[account(mut)]
+    ///     destination: AccountInfo<'info>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - for each Anchor `Accounts` struct, record whether it has a signer: a `Signer` field, a
+    ///   field with the `signer` constraint, or a composite field
+    /// - record the fields with the `close` constraint
+    ///
+    /// check_fn:
+    ///
+    /// - for every free function defined in the package, excluding the functions generated by
+    ///   macros, and, in Anchor programs, excluding the functions which do not take a `Context`
+    /// - if the function takes a `Context<T>` where `T` has a `Signer` field, or reads
+    ///   `x.is_signer` for an `AccountInfo` `x` (see `missing_signer_check`), record that `T`
+    ///   has a signer
+    /// - else record each assignment of `0` to the lamports of an account
+    ///
+    /// check_crate_post:
+    ///
+    /// - report the recorded assignments, unless the `Accounts` struct of the function has a
+    ///   signer
+    /// - report the fields with the `close` constraint of the `Accounts` structs which do not
+    ///   have a signer
+    pub UNAUTHORIZED_ACCOUNT_CLOSE,
+    Warn,
+    "accounts closed by instructions which no account signs",
+    UnauthorizedAccountClose::default()
+}
+
+#[derive(Default)]
+struct UnauthorizedAccountClose {
+    is_anchor: bool,
+    /// The Anchor `Accounts` structs which have a signer, or whose handlers read `is_signer`
+    signer_structs: HashSet<DefId>,
+    /// The fields with the `close` constraint, in the order they are found
+    closed_fields: Vec<ClosedField>,
+    /// The assignments of `0` to lamports, in functions which do not check a signer
+    closes: Vec<AccountClose>,
+}
+
+/// A field of an Anchor `Accounts` struct with the `close` constraint
+struct ClosedField {
+    accounts_struct: DefId,
+    hir_id: HirId,
+    span: Span,
+}
+
+/// An assignment of `0` to the lamports of an account
+struct AccountClose {
+    hir_id: HirId,
+    span: Span,
+    /// The `Accounts` struct of the `Context` the function takes
+    accounts_struct: Option<DefId>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UnauthorizedAccountClose {
+    fn check_crate(&mut self, cx: &LateContext<'tcx>) {
+        self.is_anchor = is_anchor_program(cx);
+    }
+
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                let def_id = item.owner_id.to_def_id();
+                let mut has_signer = false;
+                for (item_field, anchor_field) in
+                    variant.fields().iter().zip(accounts_struct.fields.iter())
+                {
+                    let field = match anchor_field {
+                        AccountField::Field(field) => field,
+                        // the signer may be a field of the composite field
+                        AccountField::CompositeField(_) => {
+                            has_signer = true;
+                            continue;
+                        }
+                    };
+                    let constraints = ConstraintSummary::new(&field.constraints);
+                    if matches!(field.ty, FieldTy::Signer) || constraints.is_signer {
+                        has_signer = true;
+                    }
+                    if constraints.is_closed {
+                        self.closed_fields.push(ClosedField {
+                            accounts_struct: def_id,
+                            hir_id: item_field.hir_id,
+                            span: item_field.span,
+                        });
+                    }
+                }
+                if has_signer {
+                    self.signer_structs.insert(def_id);
+                }
+            }
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        fn_kind: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(UNAUTHORIZED_ACCOUNT_CLOSE, span)
+            || !matches!(fn_kind, FnKind::ItemFn(..))
+        {
+            return;
+        }
+        let accounts_struct = context_accounts_struct(cx, local_def_id);
+        if self.is_anchor && accounts_struct.is_none() {
+            return;
+        }
+        if context_contains_signer_field(cx, local_def_id) || contains_is_signer_use(cx, body.value)
+        {
+            if let Some(accounts_struct) = accounts_struct {
+                self.signer_structs.insert(accounts_struct);
+            }
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if !is_skipped_expansion(UNAUTHORIZED_ACCOUNT_CLOSE, expr.span)
+                && closed_account(cx, expr).is_some()
+            {
+                self.closes.push(AccountClose {
+                    hir_id: expr.hir_id,
+                    span: expr.span,
+                    accounts_struct,
+                });
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for close in &self.closes {
+            if close
+                .accounts_struct
+                .map_or(false, |def_id| self.signer_structs.contains(&def_id))
+            {
+                continue;
+            }
+            span_lint_hir_and_then(
+                cx,
+                UNAUTHORIZED_ACCOUNT_CLOSE,
+                close.hir_id,
+                close.span,
+                "this closes an account, but no account is checked to sign the instruction",
+                |diag| {
+                    diag.help(
+                        "check that the authority of the account signs the instruction, e.g., with `is_signer`",
+                    );
+                },
+            );
+        }
+
+        for field in &self.closed_fields {
+            if self.signer_structs.contains(&field.accounts_struct) {
+                continue;
+            }
+            span_lint_hir_and_then(
+                cx,
+                UNAUTHORIZED_ACCOUNT_CLOSE,
+                field.hir_id,
+                field.span,
+                "this account is closed, but no account is checked to sign the instruction",
+                |diag| {
+                    diag.help(
+                        "add the authority of the account as a `Signer`, and check that it is the authority, e.g., with `has_one = authority`",
+                    );
+                },
+            );
+        }
+
+        sarif::write(cx, &[UNAUTHORIZED_ACCOUNT_CLOSE]);
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
+
+#[test]
+fn insecure_non_anchor() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-non-anchor");
+}
+
+#[test]
+fn secure_non_anchor() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure-non-anchor");
+}
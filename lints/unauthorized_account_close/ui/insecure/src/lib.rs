@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unauthorized_account_close_insecure {
+    use super::*;
+
+    pub fn close(_ctx: Context<Close>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn drain(ctx: Context<Drain>) -> Result<()> {
+        let vault = ctx.accounts.vault.to_account_info();
+        let destination = &ctx.accounts.destination;
+        **destination.lamports.borrow_mut() += vault.lamports();
+        **vault.lamports.borrow_mut() = 0;
+        Ok(())
+    }
+
+    pub fn drain_unchecked(ctx: Context<DrainUnchecked>) -> Result<()> {
+        let amount = ctx.accounts.vault.lamports();
+        **ctx.accounts.destination.try_borrow_mut_lamports()? += amount;
+        **ctx.accounts.vault.try_borrow_mut_lamports()? = 0;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Close<'info> {
+    #[account(mut, close = destination, has_one = authority)]
+    vault: Account<'info, Vault>,
+    /// CHECK: the authority of the vault, which does not sign
+    authority: AccountInfo<'info>,
+    /// CHECK: receives the lamports of the vault
+    #[account(mut)]
+    destination: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Drain<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    /// CHECK: receives the lamports of the vault
+    #[account(mut)]
+    destination: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DrainUnchecked<'info> {
+    /// CHECK: only lamports are transferred from the vault
+    #[account(mut)]
+    vault: UncheckedAccount<'info>,
+    /// CHECK: receives the lamports of the vault
+    #[account(mut)]
+    destination: AccountInfo<'info>,
+}
+
+#[account]
+pub struct Vault {
+    authority: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,39 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+entrypoint!(process_instruction);
+fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if !instruction_data.is_empty() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    close_vault(accounts)
+}
+
+pub fn close_vault(accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let vault = next_account_info(accounts_iter)?;
+    let authority = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    if !authority.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if vault.data.borrow()[..32] != authority.key.to_bytes() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    **destination.lamports.borrow_mut() += vault.lamports();
+    **vault.lamports.borrow_mut() = 0;
+    vault.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn main() {}
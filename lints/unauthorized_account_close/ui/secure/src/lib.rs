@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unauthorized_account_close_secure {
+    use super::*;
+
+    pub fn close(_ctx: Context<Close>) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn drain(ctx: Context<Drain>) -> Result<()> {
+        let vault = ctx.accounts.vault.to_account_info();
+        let destination = &ctx.accounts.destination;
+        **destination.lamports.borrow_mut() += vault.lamports();
+        **vault.lamports.borrow_mut() = 0;
+        Ok(())
+    }
+
+    pub fn drain_unchecked(ctx: Context<DrainUnchecked>) -> Result<()> {
+        if !ctx.accounts.authority.is_signer {
+            return Err(ProgramError::MissingRequiredSignature.into());
+        }
+        require_keys_eq!(ctx.accounts.authority.key(), ctx.accounts.vault.authority);
+        let vault = ctx.accounts.vault.to_account_info();
+        let amount = vault.lamports();
+        **ctx.accounts.destination.try_borrow_mut_lamports()? += amount;
+        **vault.try_borrow_mut_lamports()? = 0;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Close<'info> {
+    #[account(mut, close = destination, has_one = authority)]
+    vault: Account<'info, Vault>,
+    authority: Signer<'info>,
+    /// CHECK: receives the lamports of the vault
+    #[account(mut)]
+    destination: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Drain<'info> {
+    #[account(mut, has_one = authority)]
+    vault: Account<'info, Vault>,
+    /// CHECK: the authority of the vault, which signs
+    #[account(signer)]
+    authority: AccountInfo<'info>,
+    /// CHECK: receives the lamports of the vault
+    #[account(mut)]
+    destination: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DrainUnchecked<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    /// CHECK: checked to sign in the instruction
+    authority: AccountInfo<'info>,
+    /// CHECK: receives the lamports of the vault
+    #[account(mut)]
+    destination: AccountInfo<'info>,
+}
+
+#[account]
+pub struct Vault {
+    authority: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,306 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::{AccountField, AccountsStruct, Ty as FieldTy};
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, Expr, ExprKind, FnDecl, HirId, Item,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::{
+    hygiene::{ExpnKind, MacroKind},
+    Span,
+};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    sarif,
+    utils::{
+        anchor_constraints::{field_constraint_summary, ConstraintSummary},
+        context_accounts_struct, get_anchor_accounts_struct, local_struct_field,
+        signers::contains_is_signer_use,
+        visit_expr_no_bodies,
+    },
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the events emitted with `emit!` or `emit_cpi!` by Anchor instructions which mutate
+    /// the accounts of their `Accounts` struct, but whose `Accounts` struct has no signer, i.e.,
+    /// neither a `Signer` field nor a field with the `signer` constraint, and whose mutated
+    /// accounts have neither a `has_one` constraint nor a `constraint`, tying the mutation to an
+    /// authority.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The indexers, the frontends and the monitors of a program trust its events as the record
+    /// of the actions of its users, e.g., a deposit or a change of parameters. An instruction
+    /// which anyone can call both lets anyone change the state of the program, and makes the
+    /// change look legitimate to the off-chain services consuming the events, e.g., a forged
+    /// `ConfigUpdated` event, which may even trigger their own actions.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Only the assignments to the accounts of the `Accounts` struct, e.g.,
+    /// `ctx.accounts.config.fee = fee`, are considered mutations, not the CPIs, e.g., token
+    /// transfers. The checks in the body of the instruction, other than reading `is_signer`, are
+    /// not taken into account, e.g., a `require_keys_eq!` against a key stored in the program.
+    /// The instructions whose `Accounts` struct has a composite field are not reported, as the
+    /// composite field may hold a signer.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn set_fee(ctx: Context<SetFee>, fee: u64) -> Result<()> {
+    ///     ctx.accounts.config.fee = fee;
+    ///     emit!(FeeUpdated { fee });
+    ///     Ok(())
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct SetFee<'info> {
+    ///     #[account(mut)]
+    ///     config: Account<'info, Config>,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct SetFee<'info> {
+    ///     #[account(mut, has_one = admin)]
+    ///     config: Account<'info, Config>,
+    ///     admin: Signer<'info>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - record the Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for each function taking a `Context<T>`, excluding the functions generated by macros and
+    ///   those reading `is_signer`
+    /// - collect the assignments to the fields of `T`, e.g., `ctx.accounts.config.fee = fee`, and
+    ///   the calls of `emit!` and `emit_cpi!`, i.e., the expressions generated by them
+    /// - if there are both, record the calls and the mutated fields
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each recorded call, if `T` has neither a signer nor a composite field, and none of the
+    ///   mutated fields has a `has_one` constraint or a `constraint`, report the call
+    pub UNAUTHORIZED_EVENT_EMISSION,
+    Warn,
+    "events emitted by instructions mutating accounts without the check of an authority",
+    UnauthorizedEventEmission::default()
+}
+
+#[derive(Default)]
+struct UnauthorizedEventEmission {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    /// The events emitted by the instructions mutating accounts, in the order they are found
+    emissions: Vec<Emission>,
+}
+
+/// A call of `emit!` or `emit_cpi!` in an instruction mutating the accounts of its `Accounts`
+/// struct
+struct Emission {
+    hir_id: HirId,
+    /// The span of the macro call
+    span: Span,
+    /// The name of the macro, i.e., `emit` or `emit_cpi`
+    macro_name: String,
+    /// The `Accounts` struct
+    accounts_struct: DefId,
+    /// The mutated fields of the `Accounts` struct, and the first assignment to each of them
+    mutations: Vec<(String, Span)>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UnauthorizedEventEmission {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(UNAUTHORIZED_EVENT_EMISSION, span) {
+            return;
+        }
+        let accounts_struct = match context_accounts_struct(cx, local_def_id) {
+            Some(accounts_struct) => accounts_struct,
+            None => return,
+        };
+        if contains_is_signer_use(cx, body.value) {
+            return;
+        }
+        let mut mutations = Vec::<(String, Span)>::new();
+        let mut emit_calls = Vec::<(HirId, Span, String)>::new();
+        visit_expr_no_bodies(body.value, |expr| {
+            if let Some((call_site, macro_name)) = emit_call(expr.span) {
+                if !emit_calls.iter().any(|(_, span, _)| *span == call_site) {
+                    emit_calls.push((expr.hir_id, call_site, macro_name));
+                }
+                return false;
+            }
+            if is_skipped_expansion(UNAUTHORIZED_EVENT_EMISSION, expr.span) {
+                return false;
+            }
+            if let Some(account) = mutated_account(cx, expr, accounts_struct) {
+                if !mutations.iter().any(|(mutated, _)| *mutated == account) {
+                    mutations.push((account, expr.span));
+                }
+            }
+            false
+        });
+        if mutations.is_empty() {
+            return;
+        }
+        for (hir_id, span, macro_name) in emit_calls {
+            self.emissions.push(Emission {
+                hir_id,
+                span,
+                macro_name,
+                accounts_struct,
+                mutations: mutations.clone(),
+            });
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for emission in &self.emissions {
+            let accounts_struct = match self.anchor_accounts.get(&emission.accounts_struct) {
+                Some(accounts_struct) => accounts_struct,
+                None => continue,
+            };
+            if has_signer_or_composite_field(accounts_struct)
+                || emission.mutations.iter().any(|(account, _)| {
+                    field_constraint_summary(accounts_struct, account)
+                        .map_or(true, |constraints| constraints.has_relation_check)
+                })
+            {
+                continue;
+            }
+            let accounts = emission
+                .mutations
+                .iter()
+                .map(|(account, _)| format!("`{account}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            span_lint_hir_and_then(
+                cx,
+                UNAUTHORIZED_EVENT_EMISSION,
+                emission.hir_id,
+                emission.span,
+                &format!(
+                    "`{}!` is called by an instruction which mutates {accounts} without checking an authority",
+                    emission.macro_name
+                ),
+                |diag| {
+                    for (account, span) in &emission.mutations {
+                        diag.span_note(*span, format!("`{account}` is mutated here"));
+                    }
+                    diag.help(
+                        "require the signature of the authority of the mutated accounts, e.g., with a `Signer` field which the accounts are checked against with `has_one`",
+                    );
+                },
+            );
+        }
+
+        sarif::write(cx, &[UNAUTHORIZED_EVENT_EMISSION]);
+    }
+}
+
+/// If the code at `span` is generated by a call of `emit!` or `emit_cpi!`, return the span of the
+/// call and the name of the macro
+fn emit_call(span: Span) -> Option<(Span, String)> {
+    let expn_data = span.ctxt().outer_expn_data();
+    match expn_data.kind {
+        ExpnKind::Macro(MacroKind::Bang, name) => {
+            let macro_name = name.as_str().rsplit("::").next()?;
+            if matches!(macro_name, "emit" | "emit_cpi") {
+                Some((expn_data.call_site, macro_name.to_owned()))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// If `expr` is an assignment to (a part of) a field of the local struct `accounts_struct`, e.g.,
+/// `ctx.accounts.config.fee = fee` or `config.fees[0] += fee`, return the name of the field
+fn mutated_account<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    accounts_struct: DefId,
+) -> Option<String> {
+    let mut place = match expr.kind {
+        ExprKind::Assign(lhs, _, _) | ExprKind::AssignOp(_, lhs, _) => lhs,
+        _ => return None,
+    };
+    loop {
+        match local_struct_field(cx, place) {
+            Some((def_id, account)) if def_id == accounts_struct => return Some(account),
+            _ => {}
+        }
+        match place.kind {
+            ExprKind::Field(object, _) | ExprKind::Index(object, _, _) => place = object,
+            _ => return None,
+        }
+    }
+}
+
+/// Return true if the `Accounts` struct has a signer, i.e., a `Signer` field or a field with the
+/// `signer` constraint, or a composite field, which may hold one
+fn has_signer_or_composite_field(accounts_struct: &AccountsStruct) -> bool {
+    accounts_struct
+        .fields
+        .iter()
+        .any(|account_field| match account_field {
+            AccountField::Field(field) => {
+                matches!(field.ty, FieldTy::Signer)
+                    || ConstraintSummary::new(&field.constraints).is_signer
+            }
+            AccountField::CompositeField(_) => true,
+        })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
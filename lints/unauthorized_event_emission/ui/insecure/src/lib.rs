@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unauthorized_event_emission_insecure {
+    use super::*;
+
+    pub fn set_fee(ctx: Context<SetFee>, fee_bps: u16) -> Result<()> {
+        ctx.accounts.config.fee_bps = fee_bps;
+        emit!(FeeUpdated { fee_bps });
+        Ok(())
+    }
+
+    pub fn pause(ctx: Context<Pause>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.paused = true;
+        emit!(PoolPaused { pool: pool.key() });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetFee<'info> {
+    #[account(mut)]
+    config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(mut)]
+    pool: Account<'info, Pool>,
+    /// CHECK: the admin of the pool, which is neither checked nor required to sign
+    admin: UncheckedAccount<'info>,
+}
+
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+}
+
+#[account]
+pub struct Pool {
+    pub admin: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct FeeUpdated {
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct PoolPaused {
+    pub pool: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
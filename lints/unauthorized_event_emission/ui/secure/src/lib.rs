@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unauthorized_event_emission_secure {
+    use super::*;
+
+    pub fn set_fee(ctx: Context<SetFee>, fee_bps: u16) -> Result<()> {
+        ctx.accounts.config.fee_bps = fee_bps;
+        emit!(FeeUpdated { fee_bps });
+        Ok(())
+    }
+
+    pub fn pause(ctx: Context<Pause>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.paused = true;
+        emit!(PoolPaused { pool: pool.key() });
+        Ok(())
+    }
+
+    // the event records no change of state
+    pub fn announce_fee(ctx: Context<AnnounceFee>) -> Result<()> {
+        emit!(FeeUpdated {
+            fee_bps: ctx.accounts.config.fee_bps
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetFee<'info> {
+    #[account(mut, has_one = admin)]
+    config: Account<'info, Config>,
+    admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Pause<'info> {
+    #[account(mut, has_one = admin)]
+    pool: Account<'info, Pool>,
+    admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AnnounceFee<'info> {
+    config: Account<'info, Config>,
+}
+
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub fee_bps: u16,
+}
+
+#[account]
+pub struct Pool {
+    pub admin: Pubkey,
+    pub paused: bool,
+}
+
+#[event]
+pub struct FeeUpdated {
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct PoolPaused {
+    pub pool: Pubkey,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,326 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use anchor_syn::AccountsStruct;
+use clippy_utils::SpanlessEq;
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    BinOpKind, Body, Expr, ExprKind, FnDecl, HirId, Item, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::ty;
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths::{self, anchor_wrapper, AnchorWrapper},
+    sarif,
+    utils::{
+        anchor_constraints::field_constraint_summary, compares_key, followed_accounts,
+        get_anchor_accounts_struct, is_expr_method_call, lamports_account, local_field_def,
+        visit_expr_no_bodies,
+    },
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the decreases of the lamports of an account which is neither a signer nor an
+    /// account of the program, i.e., an `AccountInfo`, an `UncheckedAccount` or a `SystemAccount`
+    /// which is not checked to sign the instruction, to be owned by the program, or to be a PDA.
+    /// The decreases are:
+    ///
+    /// - `**account.lamports.borrow_mut() -= amount` and
+    ///   `**account.try_borrow_mut_lamports()? -= amount`
+    /// - an assignment to the lamports computing a subtraction, e.g.,
+    ///   `**account.lamports.borrow_mut() = balance.checked_sub(amount).unwrap()`
+    /// - `account.sub_lamports(amount)`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The program debits an account which the caller chooses freely, e.g., to pay a fee or to
+    /// fund a withdrawal. If the program owns the account, a caller can drain the lamports of any
+    /// account of the program, e.g., the vault of another user, by passing it. Otherwise the
+    /// runtime rejects the instruction, so the debit is a bug either way.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The fields of Anchor `Accounts` structs are checked by the `signer`, `owner`, `seeds` and
+    /// `address` constraints. Any other account is checked if the function reads its `is_signer`,
+    /// compares its `owner`, or compares its key; the lint does not check what it is compared
+    /// with.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    ///     **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+    ///     **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
+    ///     Ok(())
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct Withdraw<'info> {
+    ///     /// CHECK: only lamports are transferred
+    ///     #[account(mut)]
+    ///     vault: AccountInfo<'info>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Withdraw<'info> {
+    ///     /// CHECK: only lamports are transferred
+    ///     #[account(mut, seeds = [b"vault", authority.key().as_ref()], bump)]
+    ///     vault: AccountInfo<'info>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item: Collect Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for every function defined in the package, excluding the functions generated by macros
+    /// - for each decrease of the lamports of an account
+    ///   - follow the account through borrows, derefs, calls to `to_account_info` and the
+    ///     initializers of local `AccountInfo` variables, e.g., from `source` to
+    ///     `ctx.accounts.source.to_account_info()` and `ctx.accounts.source`
+    ///   - if the last account is not an `AccountInfo`, an `UncheckedAccount` or a
+    ///     `SystemAccount`, e.g., a `Signer`, ignore the decrease
+    ///   - if the function reads `x.is_signer`, compares `x.owner` or compares the key of `x`,
+    ///     where `x` is one of the accounts followed, ignore the decrease
+    ///   - else record the decrease, and the field of the last account if it is a field of a
+    ///     local struct
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each recorded decrease
+    ///   - if its field is a field of an Anchor `Accounts` struct with the `signer`, `owner`,
+    ///     `seeds` or `address` constraint, ignore the decrease
+    ///   - else report the decrease, and note the field of the Anchor `Accounts` struct
+    pub UNAUTHORIZED_LAMPORT_DEBIT,
+    Warn,
+    "decreases of the lamports of accounts which are neither signers nor accounts of the program",
+    UnauthorizedLamportDebit::default()
+}
+
+#[derive(Default)]
+struct UnauthorizedLamportDebit {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    /// The decreases of lamports which are not checked by the functions, in the order they are
+    /// found
+    debits: Vec<LamportDebit>,
+}
+
+/// A decrease of the lamports of an account
+struct LamportDebit {
+    hir_id: HirId,
+    span: Span,
+    /// The field of a local struct holding the account, e.g., `(Withdraw, "vault")` for
+    /// `ctx.accounts.vault`
+    field: Option<(DefId, String)>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UnauthorizedLamportDebit {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(UNAUTHORIZED_LAMPORT_DEBIT, span) {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if !is_skipped_expansion(UNAUTHORIZED_LAMPORT_DEBIT, expr.span);
+                if let Some(account) = debited_account(cx, expr);
+                let accounts = followed_accounts(cx, account);
+                if let Some(last) = accounts.last();
+                if is_unchecked_account_type(cx, last);
+                if !accounts
+                    .iter()
+                    .any(|account| is_account_checked(cx, body.value, account));
+                then {
+                    self.debits.push(LamportDebit {
+                        hir_id: expr.hir_id,
+                        span: expr.span,
+                        field: local_struct_field(cx, last),
+                    });
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for debit in &self.debits {
+            let anchor_field = debit.field.as_ref().and_then(|(def_id, field_name)| {
+                let accounts_struct = self.anchor_accounts.get(def_id)?;
+                let constraints = field_constraint_summary(accounts_struct, field_name)?;
+                Some((*def_id, field_name, constraints))
+            });
+            if anchor_field.map_or(false, |(_, _, constraints)| {
+                constraints.is_signer || constraints.has_owner_check || constraints.has_key_check
+            }) {
+                continue;
+            }
+            let field_def = anchor_field
+                .and_then(|(def_id, field_name, _)| local_field_def(cx, def_id, field_name));
+            span_lint_hir_and_then(
+                cx,
+                UNAUTHORIZED_LAMPORT_DEBIT,
+                debit.hir_id,
+                debit.span,
+                "the lamports of this account are decreased, but the account is neither a signer nor an account of the program",
+                |diag| {
+                    if let Some(field_def) = field_def {
+                        diag.span_note(
+                            field_def.span,
+                            "the account does not have the `signer`, `owner`, `seeds` or `address` constraint",
+                        );
+                    }
+                    diag.help(
+                        "check that the account signs the instruction, that it is owned by the program, or that it is a PDA of the program",
+                    );
+                },
+            );
+        }
+
+        sarif::write(cx, &[UNAUTHORIZED_LAMPORT_DEBIT]);
+    }
+}
+
+/// If `expr` decreases the lamports of an account, return the account:
+/// - `**x.lamports.borrow_mut() -= a` and `**x.try_borrow_mut_lamports()? -= a` => `x`
+/// - `**x.lamports.borrow_mut() = e` where `e` computes a subtraction => `x`
+/// - `x.sub_lamports(a)` => `x`
+fn debited_account<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<&'tcx Expr<'tcx>> {
+    match expr.kind {
+        ExprKind::AssignOp(op, lhs, _) if op.node == BinOpKind::Sub => lamports_account(cx, lhs),
+        ExprKind::Assign(lhs, rhs, _) if contains_subtraction(rhs) => lamports_account(cx, lhs),
+        _ => is_expr_method_call(cx, expr, &paths::ANCHOR_LANG_SUB_LAMPORTS),
+    }
+}
+
+/// Return true if `expr` computes a subtraction: `a - b`, or a call to `checked_sub`,
+/// `saturating_sub` or `wrapping_sub`
+fn contains_subtraction<'tcx>(expr: &'tcx Expr<'tcx>) -> bool {
+    visit_expr_no_bodies(expr, |expr| match expr.kind {
+        ExprKind::Binary(op, _, _) => op.node == BinOpKind::Sub,
+        ExprKind::MethodCall(method_name, _, _, _) => matches!(
+            method_name.ident.as_str(),
+            "checked_sub" | "saturating_sub" | "wrapping_sub"
+        ),
+        _ => false,
+    })
+}
+
+/// Return true if `account` is an `AccountInfo`, an `UncheckedAccount` or a `SystemAccount`, i.e.,
+/// neither its signature nor its owner is checked by its type
+fn is_unchecked_account_type(cx: &LateContext<'_>, account: &Expr<'_>) -> bool {
+    let ty = cx.typeck_results().expr_ty(account).peel_refs();
+    paths::is_account_info_ty(cx, ty)
+        || matches!(
+            anchor_wrapper(cx, ty),
+            Some(AnchorWrapper::UncheckedAccount | AnchorWrapper::SystemAccount)
+        )
+}
+
+/// Return true if `scope` reads `account.is_signer`, compares `account.owner` or compares the key
+/// of `account`
+fn is_account_checked<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    account: &Expr<'tcx>,
+) -> bool {
+    visit_expr_no_bodies(scope, |expr| match expr.kind {
+        ExprKind::Field(object, ident) if ident.as_str() == "is_signer" => {
+            SpanlessEq::new(cx).eq_expr(object, account)
+        }
+        ExprKind::Binary(op, lhs, rhs) if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne) => {
+            is_owner_of(cx, lhs, account)
+                || is_owner_of(cx, rhs, account)
+                || compares_key(cx, expr, account)
+        }
+        _ => false,
+    })
+}
+
+/// Return true if `expr` is the owner of `account`, e.g., `account.owner` or `*account.owner`
+fn is_owner_of<'tcx>(cx: &LateContext<'tcx>, mut expr: &Expr<'tcx>, account: &Expr<'tcx>) -> bool {
+    while let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = expr.kind {
+        expr = inner;
+    }
+    if_chain! {
+        if let ExprKind::Field(object, ident) = expr.kind;
+        if ident.as_str() == "owner";
+        then {
+            SpanlessEq::new(cx).eq_expr(object, account)
+        } else {
+            false
+        }
+    }
+}
+
+/// If `account` is a field of a local struct, e.g., `ctx.accounts.vault`, return the struct and
+/// the name of the field
+fn local_struct_field(cx: &LateContext<'_>, account: &Expr<'_>) -> Option<(DefId, String)> {
+    if_chain! {
+        if let ExprKind::Field(object, ident) = account.kind;
+        let object_ty = cx.typeck_results().expr_ty_adjusted(object).peel_refs();
+        if let ty::Adt(adt_def, _) = object_ty.kind();
+        if adt_def.did().is_local();
+        then {
+            Some((adt_def.did(), ident.to_string()))
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
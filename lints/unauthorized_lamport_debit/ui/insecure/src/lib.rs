@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::account_info::next_account_info;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unauthorized_lamport_debit_insecure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+
+    pub fn pay_fee(ctx: Context<PayFee>, fee: u64) -> Result<()> {
+        let payer = ctx.accounts.payer.to_account_info();
+        let balance = payer.lamports();
+        **payer.lamports.borrow_mut() = balance.checked_sub(fee).unwrap();
+        **ctx.accounts.treasury.lamports.borrow_mut() += fee;
+        Ok(())
+    }
+
+    pub fn sweep(ctx: Context<Sweep>, amount: u64) -> Result<()> {
+        ctx.accounts.source.sub_lamports(amount)?;
+        ctx.accounts.treasury.add_lamports(amount)?;
+        Ok(())
+    }
+}
+
+pub fn transfer_lamports(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let source = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    **source.lamports.borrow_mut() -= amount;
+    **destination.lamports.borrow_mut() += amount;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: only lamports are transferred from the vault
+    #[account(mut)]
+    vault: AccountInfo<'info>,
+    /// CHECK: only lamports are transferred to the recipient
+    #[account(mut)]
+    recipient: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PayFee<'info> {
+    /// CHECK: only lamports are transferred from the payer
+    #[account(mut)]
+    payer: UncheckedAccount<'info>,
+    /// CHECK: only lamports are transferred to the treasury
+    #[account(mut)]
+    treasury: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Sweep<'info> {
+    /// CHECK: only lamports are transferred from the source
+    #[account(mut)]
+    source: AccountInfo<'info>,
+    /// CHECK: only lamports are transferred to the treasury
+    #[account(mut)]
+    treasury: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
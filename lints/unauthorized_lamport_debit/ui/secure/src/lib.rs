@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::account_info::next_account_info;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unauthorized_lamport_debit_secure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.recipient.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+
+    pub fn pay_fee(ctx: Context<PayFee>, fee: u64) -> Result<()> {
+        let payer = ctx.accounts.payer.to_account_info();
+        let balance = payer.lamports();
+        **payer.lamports.borrow_mut() = balance.checked_sub(fee).unwrap();
+        **ctx.accounts.treasury.lamports.borrow_mut() += fee;
+        Ok(())
+    }
+
+    pub fn sweep(ctx: Context<Sweep>, amount: u64) -> Result<()> {
+        ctx.accounts.source.sub_lamports(amount)?;
+        ctx.accounts.treasury.add_lamports(amount)?;
+        Ok(())
+    }
+}
+
+pub fn transfer_lamports(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let source = next_account_info(accounts_iter)?;
+    let destination = next_account_info(accounts_iter)?;
+    if !source.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    **source.lamports.borrow_mut() -= amount;
+    **destination.lamports.borrow_mut() += amount;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: only lamports are transferred from the vault, a PDA of the program
+    #[account(mut, seeds = [b"vault", authority.key().as_ref()], bump)]
+    vault: AccountInfo<'info>,
+    /// CHECK: only lamports are transferred to the recipient
+    #[account(mut)]
+    recipient: AccountInfo<'info>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PayFee<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    /// CHECK: only lamports are transferred to the treasury
+    #[account(mut)]
+    treasury: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Sweep<'info> {
+    /// CHECK: the source is owned by the program
+    #[account(mut, owner = crate::ID)]
+    source: AccountInfo<'info>,
+    /// CHECK: only lamports are transferred to the treasury
+    #[account(mut)]
+    treasury: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,194 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+
+use anchor_syn::Field;
+use if_chain::if_chain;
+use rustc_hir::{FieldDef, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    sarif,
+    seeds::{display_seeds, field_seeds},
+    utils::{
+        anchor_constraints::{
+            anchor_field, has_event_authority_seeds, EVENT_AUTHORITY_FIELD, EVENT_CPI_PROGRAM_FIELD,
+        },
+        get_anchor_accounts_struct, is_self_program_account_ty,
+    },
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the fields `event_authority` and `program` of Anchor `Accounts` structs written by
+    /// hand for `emit_cpi!`, instead of being added by `#[event_cpi]`, whose constraints do not
+    /// check the accounts:
+    ///
+    /// - `event_authority` has neither the constraints `seeds = [b"__event_authority"], bump`,
+    ///   which derive the event authority of the program, nor an `address` constraint
+    /// - `program` is neither a `Program<'info, T>` of the current program, where `T` is the type
+    ///   generated by `#[program]`, nor has an `address` constraint
+    ///
+    /// **Why is this bad?**
+    ///
+    /// `emit_cpi!` emits an event as the data of a CPI of the program into itself, signed by the
+    /// event authority, a PDA of the program. Indexers trust the events of the CPIs signed by the
+    /// event authority. When the field is not derived from the seeds of the event authority, any
+    /// account can be passed as the event authority, and the events of the program can be spoofed
+    /// by a caller, or are not emitted, as the signature of the CPI fails.
+    ///
+    /// `#[event_cpi]` adds both fields to the struct with the expected constraints.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![warn(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The fields are recognized by their names, `event_authority` and `program`, which `emit_cpi!`
+    /// reads. The value of an `address` constraint is not checked.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Deposit<'info> {
+    ///     /// CHECK: the event authority
+    ///     event_authority: UncheckedAccount<'info>,
+    ///     /// CHECK: the current program
+    ///     program: UncheckedAccount<'info>,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[event_cpi]
+    /// #[derive(Accounts)]
+    /// pub struct Deposit<'info> {}
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - for each Anchor `Accounts` struct with a field `event_authority`, written in the source of
+    ///   the struct rather than added by `#[event_cpi]`
+    /// - if the field has no `address` constraint, and its seeds are not `[b"__event_authority"]`
+    ///   of the current program, report the field, noting its seeds, if any
+    /// - if the struct has a field `program` without `address` constraint, whose type is not
+    ///   `Program<'info, T>` with `T` in the module `program` of the crate, report the field
+    pub UNCHECKED_EVENT_AUTHORITY,
+    Warn,
+    "an event authority or a program of `emit_cpi!` whose key is not checked"
+}
+
+impl<'tcx> LateLintPass<'tcx> for UncheckedEventAuthority {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            if let Some(authority) = anchor_field(&accounts_struct, EVENT_AUTHORITY_FIELD);
+            then {
+                let item_field = |name: &str| {
+                    variant
+                        .fields()
+                        .iter()
+                        .find(|item_field| item_field.ident.as_str() == name)
+                };
+                if_chain! {
+                    if authority.constraints.address.is_none();
+                    if !has_event_authority_seeds(authority);
+                    if let Some(authority_def) = item_field(EVENT_AUTHORITY_FIELD);
+                    if !authority_def.span.from_expansion();
+                    then {
+                        report_event_authority(cx, authority_def, authority);
+                    }
+                }
+                if_chain! {
+                    if let Some(program) = anchor_field(&accounts_struct, EVENT_CPI_PROGRAM_FIELD);
+                    if program.constraints.address.is_none();
+                    if let Some(program_def) = item_field(EVENT_CPI_PROGRAM_FIELD);
+                    if !program_def.span.from_expansion();
+                    let program_ty = cx.tcx.type_of(program_def.def_id).skip_binder();
+                    if !is_self_program_account_ty(cx, program_ty);
+                    then {
+                        report_program(cx, program_def);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[UNCHECKED_EVENT_AUTHORITY]);
+    }
+}
+
+fn report_event_authority(cx: &LateContext<'_>, authority_def: &FieldDef<'_>, authority: &Field) {
+    let msg = if authority.constraints.seeds.is_some() {
+        "`event_authority` is not derived from the seeds of the event authority"
+    } else {
+        "`event_authority` is not checked to be the event authority of the program"
+    };
+    span_lint_hir_and_then(
+        cx,
+        UNCHECKED_EVENT_AUTHORITY,
+        authority_def.hir_id,
+        authority_def.span,
+        msg,
+        |diag| {
+            if let Some(seeds_group) = &authority.constraints.seeds {
+                if seeds_group.program_seed.is_some() {
+                    diag.note(
+                        "the seeds of the field are seeds of another program, `seeds::program`",
+                    );
+                } else if let Some(seeds) = field_seeds(authority) {
+                    diag.note(format!(
+                        "the seeds of the field are `{}`",
+                        display_seeds(&seeds)
+                    ));
+                }
+            }
+            diag.note(
+                "the event authority is the PDA of the seeds `[b\"__event_authority\"]` of the current program, which signs the CPIs of `emit_cpi!`",
+            );
+            diag.help(
+                "add `#[event_cpi]` to the struct, which adds the field with the constraints `seeds = [b\"__event_authority\"], bump`",
+            );
+        },
+    );
+}
+
+fn report_program(cx: &LateContext<'_>, program_def: &FieldDef<'_>) {
+    span_lint_hir_and_then(
+        cx,
+        UNCHECKED_EVENT_AUTHORITY,
+        program_def.hir_id,
+        program_def.span,
+        "`program` is not checked to be the current program",
+        |diag| {
+            diag.note("`emit_cpi!` invokes the current program with the event");
+            diag.help(
+                "add `#[event_cpi]` to the struct, or make the field a `Program<'info, T>`, where `T` is the type generated by `#[program]`",
+            );
+        },
+    );
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
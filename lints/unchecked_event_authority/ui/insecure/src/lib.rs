@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unchecked_event_authority_insecure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        emit_cpi!(Deposited { amount });
+        Ok(())
+    }
+
+    pub fn withdraw(_ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        msg!("withdrawn {}", amount);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    /// CHECK: the event authority
+    #[account(seeds = [b"event_authority"], bump)]
+    event_authority: UncheckedAccount<'info>,
+    /// CHECK: the current program
+    program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: the event authority
+    event_authority: UncheckedAccount<'info>,
+    program: Program<'info, System>,
+}
+
+#[event]
+pub struct Deposited {
+    amount: u64,
+}
+
+fn main() {}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unchecked_event_authority_secure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        emit_cpi!(Deposited { amount });
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        emit_cpi!(Withdrawn { amount });
+        Ok(())
+    }
+
+    pub fn close(_ctx: Context<Close>) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// CHECK: the event authority
+    #[account(seeds = [b"__event_authority"], bump)]
+    event_authority: UncheckedAccount<'info>,
+    program: Program<'info, crate::program::UncheckedEventAuthoritySecure>,
+}
+
+#[derive(Accounts)]
+pub struct Close<'info> {
+    /// CHECK: the event authority, whose address is computed by the client
+    #[account(address = event_authority_address())]
+    event_authority: UncheckedAccount<'info>,
+    /// CHECK: the current program
+    #[account(address = crate::ID)]
+    program: UncheckedAccount<'info>,
+}
+
+fn event_authority_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"__event_authority"], &crate::ID).0
+}
+
+#[event]
+pub struct Deposited {
+    amount: u64,
+}
+
+#[event]
+pub struct Withdrawn {
+    amount: u64,
+}
+
+fn main() {}
@@ -0,0 +1,364 @@
+#![feature(rustc_private)]
+#![feature(box_patterns)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_index;
+extern crate rustc_middle;
+extern crate rustc_span;
+
+use clippy_utils::ty::match_type;
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::{def_id::DefId, Body, LanguageItems};
+use rustc_index::bit_set::BitSet;
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::{
+    mir::{
+        self,
+        visit::{PlaceContext, Visitor},
+        BinOp, Local, Location, Operand, Place, ProjectionElem, Rvalue, StatementKind,
+        TerminatorKind,
+    },
+    ty::{self, Ty, TyKind},
+};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    mir_cache::{BodyAnalysis, MirCache},
+    paths, sarif,
+    taint::{instruction_argument_locals, tainted_locals},
+    utils::mir_lint_root,
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports indexing of account data, or of a slice of accounts, e.g.,
+    /// `ctx.remaining_accounts`, with an index derived from an instruction argument, when the
+    /// index is not compared to a bound beforehand.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The caller chooses the index. An index past the end of the slice makes the program panic,
+    /// so the caller can make the instruction fail whenever it likes. An index which is in bounds
+    /// but not the one the program expects may make the program read or write the wrong part of
+    /// an account, or use the wrong account.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The instruction arguments are tracked within a function only, so an index passed to a
+    /// helper function is not reported. Any comparison of the index, or of a value computed from
+    /// it, counts as a bounds check, whatever the bound. An index which is bounded by other means,
+    /// e.g., `index % data.len()` or `index.min(data.len() - 1)`, is reported.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn read_entry(ctx: Context<ReadEntry>, index: u64) -> Result<()> {
+    ///     let data = ctx.accounts.table.try_borrow_data()?;
+    ///     msg!("entry: {}", data[index as usize]);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// pub fn read_entry(ctx: Context<ReadEntry>, index: u64) -> Result<()> {
+    ///     let data = ctx.accounts.table.try_borrow_data()?;
+    ///     let entry = data
+    ///         .get(index as usize)
+    ///         .ok_or(ProgramError::InvalidArgument)?;
+    ///     msg!("entry: {}", entry);
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// - For every function whose MIR is available, excluding functions generated from macro expansion
+    /// - Find the instruction arguments (see `solana_lints::taint`): the arguments after the
+    ///   `Context` of an Anchor instruction handler, or the `&[u8]` arguments of other functions
+    /// - Compute the locals which are tainted by the instruction arguments
+    /// - Find the index operations with a tainted index, i.e., `Index` projections and calls to
+    ///   `Index::index` or `IndexMut::index_mut`, into
+    ///   - slices of `AccountInfo`s
+    ///   - slices of `u8` dereferenced from a `Ref` or `RefMut`, i.e., the data of an account
+    /// - An index operation is checked if a block which dominates it ends in a `SwitchInt` and
+    ///   compares (`<`, `<=`, `>` or `>=`) a value computed from the same tainted locals as the index
+    /// - Report the index operations which are not checked
+    pub UNCHECKED_INDEX,
+    Warn,
+    "indexing of account data or accounts with an unchecked instruction argument",
+    UncheckedIndex::default()
+}
+
+#[derive(Default)]
+struct UncheckedIndex {
+    mir_cache: MirCache,
+}
+
+/// What an index operation indexes
+#[derive(Clone, Copy)]
+enum Indexed {
+    AccountData,
+    Accounts,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UncheckedIndex {
+    fn check_body(&mut self, cx: &LateContext<'tcx>, body: &'tcx Body<'tcx>) {
+        let hir_map = cx.tcx.hir();
+        let body_did = hir_map.body_owner_def_id(body.id()).to_def_id();
+        if !cx.tcx.def_kind(body_did).is_fn_like()
+            || !cx.tcx.is_mir_available(body_did)
+            || is_skipped_expansion(UNCHECKED_INDEX, cx.tcx.def_span(body_did))
+        {
+            return;
+        }
+        let body_mir = cx.tcx.optimized_mir(body_did);
+
+        let sources = instruction_argument_locals(cx, body_mir);
+        if sources.is_empty() {
+            return;
+        }
+        let tainted = tainted_locals(body_mir, &sources);
+
+        let mut visitor = IndexVisitor {
+            cx,
+            body: body_mir,
+            tainted: &tainted,
+            indexes: Vec::new(),
+        };
+        visitor.visit_body(body_mir);
+
+        let analysis = self.mir_cache.analysis(cx, body_did);
+        let mut reported = FxHashSet::<Span>::default();
+        for (location, index, indexed) in visitor.indexes {
+            let source_info = body_mir.source_info(location);
+            if is_checked(body_mir, analysis, &tainted, location, index)
+                || !reported.insert(source_info.span)
+            {
+                continue;
+            }
+            let (what, bound) = match indexed {
+                Indexed::AccountData => ("account data", "the length of the data"),
+                Indexed::Accounts => ("accounts", "the number of accounts"),
+            };
+            span_lint_hir_and_then(
+                cx,
+                UNCHECKED_INDEX,
+                mir_lint_root(body_mir, *source_info),
+                source_info.span,
+                &format!("{what} indexed with an instruction argument which is not bounds-checked"),
+                |diag| {
+                    diag.help(format!(
+                        "compare the index to {bound} and return an error if it is out of bounds, or use `get`"
+                    ));
+                },
+            );
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[UNCHECKED_INDEX]);
+    }
+}
+
+/// Collects the index operations with a tainted index, with the local holding the index
+struct IndexVisitor<'a, 'tcx> {
+    cx: &'a LateContext<'tcx>,
+    body: &'a mir::Body<'tcx>,
+    tainted: &'a BitSet<Local>,
+    indexes: Vec<(Location, Local, Indexed)>,
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for IndexVisitor<'a, 'tcx> {
+    fn visit_place(&mut self, place: &Place<'tcx>, _: PlaceContext, location: Location) {
+        for (base, elem) in place.iter_projections() {
+            if_chain! {
+                if let ProjectionElem::Index(index) = elem;
+                if self.tainted.contains(index);
+                let pointer = match base.projection {
+                    [ProjectionElem::Deref] => Some(base.local),
+                    _ => None,
+                };
+                if let Some(indexed) = self.indexed(base.ty(self.body, self.cx.tcx).ty, pointer);
+                then {
+                    self.indexes.push((location, index, indexed));
+                }
+            }
+        }
+    }
+
+    fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Location) {
+        if_chain! {
+            if let TerminatorKind::Call {
+                func: Operand::Constant(box func),
+                args,
+                ..
+            } = &terminator.kind;
+            if let TyKind::FnDef(def_id, _) = func.const_.ty().kind();
+            if self.is_trait_method(*def_id, |lang_items| {
+                [lang_items.index_trait(), lang_items.index_mut_trait()]
+            });
+            if let [Operand::Copy(base) | Operand::Move(base), Operand::Copy(index) | Operand::Move(index)] =
+                args.as_slice();
+            if self.tainted.contains(index.local);
+            if base.projection.is_empty();
+            if let ty::Ref(_, slice_ty, _) = self.body.local_decls[base.local].ty.kind();
+            if let Some(indexed) = self.indexed(*slice_ty, Some(base.local));
+            then {
+                self.indexes.push((location, index.local, indexed));
+            }
+        }
+        self.super_terminator(terminator, location);
+    }
+}
+
+impl<'a, 'tcx> IndexVisitor<'a, 'tcx> {
+    /// Return what a slice of type `slice_ty` is if it is account data or a slice of accounts.
+    /// `pointer` is the local holding the reference to the slice, if any.
+    fn indexed(&self, slice_ty: Ty<'tcx>, pointer: Option<Local>) -> Option<Indexed> {
+        if let ty::Slice(elem) = slice_ty.kind() {
+            if paths::is_account_info_ty(self.cx, *elem) {
+                return Some(Indexed::Accounts);
+            }
+            if_chain! {
+                if matches!(elem.kind(), ty::Uint(ty::UintTy::U8));
+                if let Some(pointer) = pointer;
+                if self.derefs_ref_cell(pointer);
+                then {
+                    return Some(Indexed::AccountData);
+                }
+            }
+        }
+        None
+    }
+
+    /// Return true if the reference held in `local` is obtained from a `Ref` or `RefMut` by
+    /// `Deref::deref` or `DerefMut::deref_mut`, following copies, reborrows and dereferences
+    fn derefs_ref_cell(&self, mut local: Local) -> bool {
+        let mut visited = FxHashSet::default();
+        while visited.insert(local) {
+            let mut next = None;
+            for block_data in self.body.basic_blocks.iter() {
+                for stmt in &block_data.statements {
+                    if_chain! {
+                        if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind;
+                        if place.local == local && place.projection.is_empty();
+                        if let Rvalue::Use(Operand::Copy(source) | Operand::Move(source))
+                            | Rvalue::CopyForDeref(source)
+                            | Rvalue::Ref(_, _, source) = rvalue;
+                        then {
+                            next = Some(source.local);
+                        }
+                    }
+                }
+                if_chain! {
+                    if let TerminatorKind::Call {
+                        func: Operand::Constant(box func),
+                        destination,
+                        ..
+                    } = &block_data.terminator().kind;
+                    if destination.local == local;
+                    if let TyKind::FnDef(def_id, args) = func.const_.ty().kind();
+                    then {
+                        return self.is_ref_cell_deref(*def_id, args.type_at(0));
+                    }
+                }
+            }
+            match next {
+                Some(source) => local = source,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    fn is_ref_cell_deref(&self, def_id: DefId, self_ty: Ty<'tcx>) -> bool {
+        self.is_trait_method(def_id, |lang_items| {
+            [lang_items.deref_trait(), lang_items.deref_mut_trait()]
+        }) && (match_type(self.cx, self_ty, &paths::CORE_CELL_REF)
+            || match_type(self.cx, self_ty, &paths::CORE_CELL_REF_MUT))
+    }
+
+    /// Return true if `def_id` is a method of one of the lang item traits returned by `traits`
+    fn is_trait_method(
+        &self,
+        def_id: DefId,
+        traits: impl FnOnce(&LanguageItems) -> [Option<DefId>; 2],
+    ) -> bool {
+        self.cx
+            .tcx
+            .trait_of_item(def_id)
+            .map_or(false, |trait_def_id| {
+                traits(self.cx.tcx.lang_items()).contains(&Some(trait_def_id))
+            })
+    }
+}
+
+/// Return true if a block which dominates `location` ends in a `SwitchInt`, and compares a value
+/// computed from the same tainted locals as `index`
+fn is_checked(
+    body: &mir::Body<'_>,
+    analysis: &BodyAnalysis,
+    tainted: &BitSet<Local>,
+    location: Location,
+    index: Local,
+) -> bool {
+    let mut index_sources = analysis.value_sources(index);
+    index_sources.intersect(tainted);
+    let dominators = body.basic_blocks.dominators();
+    body.basic_blocks
+        .iter_enumerated()
+        .any(|(block, block_data)| {
+            block != location.block
+                && matches!(
+                    block_data.terminator().kind,
+                    TerminatorKind::SwitchInt { .. }
+                )
+                && dominators.dominates(block, location.block)
+                && block_data.statements.iter().any(|stmt| {
+                    compared_locals(stmt).iter().flatten().any(|&local| {
+                        let mut sources = analysis.value_sources(local);
+                        sources.intersect(&index_sources);
+                        !sources.is_empty()
+                    })
+                })
+        })
+}
+
+/// Return the locals compared by `stmt` if it assigns `<`, `<=`, `>` or `>=`
+fn compared_locals(stmt: &mir::Statement<'_>) -> [Option<Local>; 2] {
+    if let StatementKind::Assign(box (
+        _,
+        Rvalue::BinaryOp(BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge, box (left, right)),
+    )) = &stmt.kind
+    {
+        [left.place(), right.place()].map(|place| place.map(|place| place.local))
+    } else {
+        [None, None]
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unchecked_index_insecure {
+    use super::*;
+
+    pub fn read_entry(ctx: Context<ReadTable>, index: u64) -> Result<()> {
+        let data = ctx.accounts.table.try_borrow_data()?;
+        msg!("entry: {}", data[index as usize]);
+        Ok(())
+    }
+
+    pub fn read_range(ctx: Context<ReadTable>, offset: u32) -> Result<()> {
+        let data = ctx.accounts.table.try_borrow_data()?;
+        let start = offset as usize;
+        let entry = &data[start..start + 8];
+        msg!("entry: {:?}", entry);
+        Ok(())
+    }
+
+    pub fn forward(ctx: Context<Forward>, account_index: u8) -> Result<()> {
+        let target = &ctx.remaining_accounts[account_index as usize];
+        msg!("target: {}", target.key);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ReadTable<'info> {
+    table: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Forward<'info> {
+    authority: Signer<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unchecked_index_secure {
+    use super::*;
+
+    pub fn read_entry(ctx: Context<ReadTable>, index: u64) -> Result<()> {
+        let data = ctx.accounts.table.try_borrow_data()?;
+        let index = index as usize;
+        if index >= data.len() {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        msg!("entry: {}", data[index]);
+        Ok(())
+    }
+
+    pub fn read_range(ctx: Context<ReadTable>, offset: u32) -> Result<()> {
+        let data = ctx.accounts.table.try_borrow_data()?;
+        let start = offset as usize;
+        let end = start
+            .checked_add(8)
+            .ok_or(ProgramError::InvalidArgument)?;
+        if end > data.len() {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let entry = &data[start..end];
+        msg!("entry: {:?}", entry);
+        Ok(())
+    }
+
+    pub fn forward(ctx: Context<Forward>, account_index: u8) -> Result<()> {
+        let target = ctx
+            .remaining_accounts
+            .get(account_index as usize)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        msg!("target: {}", target.key);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ReadTable<'info> {
+    table: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Forward<'info> {
+    authority: Signer<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,308 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::AccountsStruct;
+use clippy_utils::{fn_def_id, match_any_def_paths, match_def_path, source::snippet};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, Expr, ExprKind, FnDecl, HirId, Item, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{
+        accesses_key, anchor_constraints::field_constraint_summary, compares_key,
+        get_anchor_accounts_struct, is_expr_method_call, local_field_def, local_struct_field,
+        visit_expr_no_bodies,
+    },
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the accounts passed to the functions reading the instructions sysvar, i.e.,
+    /// `load_instruction_at_checked`, `get_instruction_relative` and `load_current_index_checked`
+    /// of `sysvar::instructions`, whose key is not checked to be `sysvar::instructions::ID`:
+    ///
+    /// - in an Anchor program, by the constraint `address = sysvar::instructions::ID` of the field
+    ///   of the account
+    /// - in the function calling them, by a comparison of the key of the account, e.g., with
+    ///   `require_keys_eq!`, or with `sysvar::instructions::check_id`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A program inspecting the other instructions of the transaction, e.g., to require that an
+    /// instruction of the Ed25519 program verified a signature before it, trusts the account it is
+    /// passed to be the instructions sysvar. A program reading the sysvar from an account whose key
+    /// it does not check may read instructions forged by the caller, as in the Wormhole exploit,
+    /// whose program read the sysvar with the unchecked `load_instruction_at`.
+    ///
+    /// The functions of `sysvar::instructions` ending with `_checked` reject another account, but
+    /// only when they are called: the program which checks the key itself rejects the account
+    /// before doing anything else, and, with Anchor, documents the account in its IDL.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// A key checked in another function, e.g., in a validation helper, is not seen, and the
+    /// account is reported.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct VerifySignature<'info> {
+    ///     /// CHECK: the instructions sysvar
+    ///     instructions: UncheckedAccount<'info>,
+    /// }
+    ///
+    /// let ix = load_instruction_at_checked(0, &ctx.accounts.instructions)?;
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct VerifySignature<'info> {
+    ///     /// CHECK: the instructions sysvar
+    ///     #[account(address = sysvar::instructions::ID)]
+    ///     instructions: UncheckedAccount<'info>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item: collect the Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for every function, excluding the functions generated by macros
+    /// - for each call of `load_instruction_at_checked`, `get_instruction_relative` or
+    ///   `load_current_index_checked`, get the account argument, without its borrows and calls of
+    ///   `to_account_info`
+    /// - if the function compares the key of the account, or passes it to
+    ///   `sysvar::instructions::check_id`, ignore the call
+    /// - else record the account, with the field of the local struct holding it, if any, e.g.,
+    ///   `ctx.accounts.instructions`
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each recorded account, if it is a field of an Anchor `Accounts` struct with the
+    ///   `address` or `seeds` constraints, or whose key is compared by a constraint of the struct,
+    ///   ignore the account
+    /// - else report the account, and note the field, if any
+    pub UNCHECKED_INSTRUCTIONS_SYSVAR,
+    Warn,
+    "an account read as the instructions sysvar whose key is not checked",
+    UncheckedInstructionsSysvar::default()
+}
+
+#[derive(Default)]
+struct UncheckedInstructionsSysvar {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    /// The accounts read as the instructions sysvar without a check of their key in the function
+    /// reading them, in the order they are found
+    reads: Vec<SysvarRead>,
+}
+
+/// An account passed to a function reading the instructions sysvar
+struct SysvarRead {
+    hir_id: HirId,
+    span: Span,
+    /// The name of the function reading the sysvar, e.g., `load_instruction_at_checked`
+    function: &'static str,
+    /// The field of the local struct holding the account, if any, e.g.,
+    /// `(VerifySignature, "instructions")` for `ctx.accounts.instructions`
+    field: Option<(DefId, String)>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UncheckedInstructionsSysvar {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        if is_skipped_expansion(UNCHECKED_INSTRUCTIONS_SYSVAR, span) {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if !is_skipped_expansion(UNCHECKED_INSTRUCTIONS_SYSVAR, expr.span);
+                if let Some((function, account)) = sysvar_read(cx, expr);
+                if !is_key_checked(cx, body.value, account);
+                then {
+                    self.reads.push(SysvarRead {
+                        hir_id: account.hir_id,
+                        span: account.span,
+                        function,
+                        field: local_struct_field(cx, account),
+                    });
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for read in &self.reads {
+            if let Some((def_id, field_name)) = &read.field {
+                if self
+                    .anchor_accounts
+                    .get(def_id)
+                    .and_then(|accounts_struct| {
+                        field_constraint_summary(accounts_struct, field_name)
+                    })
+                    .map_or(false, |constraints| {
+                        constraints.has_key_check || constraints.is_key_compared
+                    })
+                {
+                    continue;
+                }
+            }
+            let anchor_field = read.field.as_ref().and_then(|(def_id, field_name)| {
+                if self.anchor_accounts.contains_key(def_id) {
+                    local_field_def(cx, *def_id, field_name)
+                } else {
+                    None
+                }
+            });
+            span_lint_hir_and_then(
+                cx,
+                UNCHECKED_INSTRUCTIONS_SYSVAR,
+                read.hir_id,
+                read.span,
+                &format!(
+                    "`{}` reads the instructions sysvar from `{}`, whose key is not checked",
+                    read.function,
+                    snippet(cx, read.span, "..")
+                ),
+                |diag| {
+                    if let Some(field_def) = anchor_field {
+                        diag.span_note(
+                            field_def.span,
+                            "the field has no `address = sysvar::instructions::ID` constraint",
+                        );
+                        diag.help(
+                            "add the constraint `#[account(address = sysvar::instructions::ID)]` to the field",
+                        );
+                    } else {
+                        diag.help(
+                            "check that the key of the account is `sysvar::instructions::ID`, e.g., with `sysvar::instructions::check_id`",
+                        );
+                    }
+                },
+            );
+        }
+        sarif::write(cx, &[UNCHECKED_INSTRUCTIONS_SYSVAR]);
+    }
+}
+
+/// If `expr` is a call of a function reading the instructions sysvar, return the name of the
+/// function and its account argument, without its borrows and calls of `to_account_info`, e.g.,
+/// `ctx.accounts.instructions` for
+/// `load_instruction_at_checked(0, &ctx.accounts.instructions.to_account_info())`
+fn sysvar_read<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<(&'static str, &'tcx Expr<'tcx>)> {
+    let args = match expr.kind {
+        ExprKind::Call(_, args) => args,
+        _ => return None,
+    };
+    let (function, mut account) = match match_any_def_paths(
+        cx,
+        fn_def_id(cx, expr)?,
+        &[
+            &paths::SYSVAR_INSTRUCTIONS_LOAD_INSTRUCTION_AT_CHECKED,
+            &paths::SYSVAR_INSTRUCTIONS_GET_INSTRUCTION_RELATIVE,
+            &paths::SYSVAR_INSTRUCTIONS_LOAD_CURRENT_INDEX_CHECKED,
+        ],
+    )? {
+        0 => ("load_instruction_at_checked", args.get(1)?),
+        1 => ("get_instruction_relative", args.get(1)?),
+        _ => ("load_current_index_checked", args.first()?),
+    };
+    loop {
+        if let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = account.kind {
+            account = inner;
+        } else if let Some(receiver) =
+            is_expr_method_call(cx, account, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO)
+        {
+            account = receiver;
+        } else {
+            return Some((function, account));
+        }
+    }
+}
+
+/// Return true if `scope` compares the key of `account`, e.g., with `require_keys_eq!`, or passes
+/// it to `sysvar::instructions::check_id`
+fn is_key_checked<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    account: &Expr<'tcx>,
+) -> bool {
+    visit_expr_no_bodies(scope, |expr| {
+        compares_key(cx, expr, account) || is_check_id_call(cx, expr, account)
+    })
+}
+
+/// Return true if `expr` is `check_id(account.key)` or `check_id(&account.key())`
+fn is_check_id_call<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    account: &Expr<'tcx>,
+) -> bool {
+    if_chain! {
+        if let ExprKind::Call(_, [key]) = expr.kind;
+        if let Some(def_id) = fn_def_id(cx, expr);
+        if match_def_path(cx, def_id, &paths::SYSVAR_INSTRUCTIONS_CHECK_ID);
+        then {
+            let mut key = key;
+            while let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = key.kind {
+                key = inner;
+            }
+            accesses_key(cx, key, account)
+        } else {
+            false
+        }
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    get_instruction_relative, load_instruction_at_checked,
+};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unchecked_instructions_sysvar_insecure {
+    use super::*;
+
+    pub fn verify_signature(ctx: Context<VerifySignature>) -> Result<()> {
+        let ix = load_instruction_at_checked(0, &ctx.accounts.instructions)?;
+        msg!("{}", ix.program_id);
+        Ok(())
+    }
+
+    pub fn verify_previous(ctx: Context<VerifySignature>) -> Result<()> {
+        check_previous_instruction(&ctx.accounts.instructions.to_account_info())
+    }
+}
+
+fn check_previous_instruction(ix_sysvar: &AccountInfo) -> Result<()> {
+    let ix = get_instruction_relative(-1, ix_sysvar)?;
+    msg!("{}", ix.program_id);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct VerifySignature<'info> {
+    /// CHECK: the instructions sysvar
+    instructions: UncheckedAccount<'info>,
+}
+
+fn main() {}
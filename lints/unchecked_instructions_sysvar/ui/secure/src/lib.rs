@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::{
+    self,
+    instructions::{
+        get_instruction_relative, load_current_index_checked, load_instruction_at_checked,
+    },
+};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unchecked_instructions_sysvar_secure {
+    use super::*;
+
+    pub fn verify_signature(ctx: Context<VerifySignature>) -> Result<()> {
+        let ix = load_instruction_at_checked(0, &ctx.accounts.instructions)?;
+        msg!("{}", ix.program_id);
+        Ok(())
+    }
+
+    pub fn verify_current(ctx: Context<VerifyCurrent>) -> Result<()> {
+        let instructions = ctx.accounts.instructions.to_account_info();
+        require_keys_eq!(instructions.key(), sysvar::instructions::ID);
+        let index = load_current_index_checked(&instructions)?;
+        msg!("{}", index);
+        Ok(())
+    }
+
+    pub fn verify_previous(ctx: Context<VerifyCurrent>) -> Result<()> {
+        check_previous_instruction(&ctx.accounts.instructions.to_account_info())
+    }
+}
+
+fn check_previous_instruction(ix_sysvar: &AccountInfo) -> Result<()> {
+    if !sysvar::instructions::check_id(ix_sysvar.key) {
+        return Err(ProgramError::UnsupportedSysvar.into());
+    }
+    let ix = get_instruction_relative(-1, ix_sysvar)?;
+    msg!("{}", ix.program_id);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct VerifySignature<'info> {
+    /// CHECK: the instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyCurrent<'info> {
+    /// CHECK: checked in the handler
+    instructions: UncheckedAccount<'info>,
+}
+
+fn main() {}
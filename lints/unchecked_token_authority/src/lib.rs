@@ -0,0 +1,368 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::{AccountsStruct, Field, Ty as FieldTy};
+use clippy_utils::{fn_def_id, match_any_def_paths, path_to_local};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    BinOpKind, Body, Expr, ExprKind, FnDecl, HirId, Item,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    seeds::Seed,
+    utils::{
+        account_struct_field,
+        anchor_constraints::{
+            anchor_field, field_constraint_summary, is_token_account, token_account_authorities,
+        },
+        get_anchor_accounts_struct, local_field_def, local_init, visit_expr_no_bodies,
+    },
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the transfers and burns, with `anchor_spl::token` or `spl_token::instruction`, of
+    /// the tokens of a `TokenAccount` of an Anchor `Accounts` struct whose owner is not checked to
+    /// be a signer:
+    ///
+    /// - the field has none of the constraints `token::authority`, `associated_token::authority`,
+    ///   `has_one = owner`, or a `constraint` comparing its owner, e.g.,
+    ///   `constraint = vault.owner == user.key()`, and the function making the transfer does not
+    ///   compare its owner either
+    /// - or the constraints compare its owner only with fields which are neither signers nor PDAs,
+    ///   e.g., `token::authority = owner`, where `owner` is an `UncheckedAccount`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The token program only checks that the authority of a transfer signed it. A program whose
+    /// PDA owns the token accounts of several users, e.g., their vaults, signs the transfers from
+    /// any of them. If the owner of the vault is not tied to the user calling the instruction, a
+    /// user can pass the vault of another user and withdraw its tokens. This is the account data
+    /// matching vulnerability of the Sealevel attacks.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// A token account whose key is checked, by `seeds`, `address` or a `has_one` constraint of
+    /// another field, is not reported, as its owner is set by the program. The owner compared with
+    /// an expression which is not a field of the struct, e.g., `config.authority`, is regarded as
+    /// checked.
+    ///
+    /// Only the transfers and burns whose accounts are fields of `ctx.accounts`, possibly through
+    /// local variables, are checked.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Withdraw<'info> {
+    ///     #[account(mut)]
+    ///     vault: Account<'info, TokenAccount>,
+    ///     user: Signer<'info>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Withdraw<'info> {
+    ///     #[account(mut, constraint = vault.owner == user.key())]
+    ///     vault: Account<'info, TokenAccount>,
+    ///     user: Signer<'info>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item: collect the Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for every function, excluding the functions generated by macros
+    /// - for each call of `transfer`, `transfer_checked` or `burn` of `anchor_spl::token`, take the
+    ///   `from` account of its `CpiContext`; for each call of `transfer`, `transfer_checked` or
+    ///   `burn` of `spl_token::instruction`, take its source account
+    /// - if the account is a field of a local struct, e.g., `ctx.accounts.vault`, and the function
+    ///   does not compare its owner, record the call
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each recorded call, if the struct is an Anchor `Accounts` struct, and the field is a
+    ///   `TokenAccount` whose key is not checked
+    /// - if the constraints of the field do not compare its owner, report the call, with a note at
+    ///   the field
+    /// - else if its owner is only compared with fields of the struct which are neither signers
+    ///   nor PDAs, report the call, with a note at each of them
+    pub UNCHECKED_TOKEN_AUTHORITY,
+    Warn,
+    "tokens moved out of a token account whose owner is not checked to be a signer",
+    UncheckedTokenAuthority::default()
+}
+
+#[derive(Default)]
+struct UncheckedTokenAuthority {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    /// The transfers and burns whose owner is not compared in the function making them
+    debits: Vec<Debit>,
+}
+
+/// A transfer or a burn of the tokens of a field of a local struct
+struct Debit {
+    hir_id: HirId,
+    span: Span,
+    /// `transfer` or `burn`
+    operation: &'static str,
+    accounts: DefId,
+    field: String,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UncheckedTokenAuthority {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        if is_skipped_expansion(UNCHECKED_TOKEN_AUTHORITY, span) {
+            return;
+        }
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if !is_skipped_expansion(UNCHECKED_TOKEN_AUTHORITY, expr.span);
+                if let Some((operation, account)) = debited_account(cx, expr);
+                if let Some((accounts, field)) = account_struct_field(cx, account);
+                if !compares_owner(cx, body.value, &(accounts, field.clone()));
+                then {
+                    self.debits.push(Debit {
+                        hir_id: expr.hir_id,
+                        span: expr.span,
+                        operation,
+                        accounts,
+                        field,
+                    });
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for debit in &self.debits {
+            let accounts_struct = match self.anchor_accounts.get(&debit.accounts) {
+                Some(accounts_struct) => accounts_struct,
+                None => continue,
+            };
+            let field = match anchor_field(accounts_struct, &debit.field) {
+                Some(field) if is_token_account(field) => field,
+                _ => continue,
+            };
+            if field_constraint_summary(accounts_struct, &debit.field).map_or(true, |constraints| {
+                constraints.has_key_check || constraints.is_key_compared
+            }) {
+                continue;
+            }
+            let authorities = token_account_authorities(field);
+            if authorities.is_empty() {
+                report_unconstrained_owner(cx, debit);
+                continue;
+            }
+            let unchecked_authorities = authorities
+                .iter()
+                .filter_map(|authority| match authority {
+                    Seed::Expr(name) => anchor_field(accounts_struct, name)
+                        .filter(|authority| !is_signer_or_pda(authority))
+                        .map(|_| name.as_str()),
+                    Seed::Bytes(_) => None,
+                })
+                .collect::<Vec<_>>();
+            if unchecked_authorities.len() == authorities.len() {
+                report_unchecked_authorities(cx, debit, &unchecked_authorities);
+            }
+        }
+        sarif::write(cx, &[UNCHECKED_TOKEN_AUTHORITY]);
+    }
+}
+
+fn report_unconstrained_owner(cx: &LateContext<'_>, debit: &Debit) {
+    span_lint_hir_and_then(
+        cx,
+        UNCHECKED_TOKEN_AUTHORITY,
+        debit.hir_id,
+        debit.span,
+        &format!(
+            "the owner of `{}` is not checked before this {}",
+            debit.field, debit.operation
+        ),
+        |diag| {
+            if let Some(field_def) = local_field_def(cx, debit.accounts, &debit.field) {
+                diag.span_note(
+                    field_def.span,
+                    "the field has no `token::authority`, `has_one = owner` or `constraint` on its owner",
+                );
+            }
+            diag.help(format!(
+                "add `constraint = {}.owner == <signer>.key()` to the field, where `<signer>` is the account allowed to move its tokens",
+                debit.field
+            ));
+        },
+    );
+}
+
+fn report_unchecked_authorities(cx: &LateContext<'_>, debit: &Debit, authorities: &[&str]) {
+    span_lint_hir_and_then(
+        cx,
+        UNCHECKED_TOKEN_AUTHORITY,
+        debit.hir_id,
+        debit.span,
+        &format!(
+            "the owner of `{}` is not checked to be a signer before this {}",
+            debit.field, debit.operation
+        ),
+        |diag| {
+            for authority in authorities {
+                if let Some(field_def) = local_field_def(cx, debit.accounts, authority) {
+                    diag.span_note(
+                        field_def.span,
+                        format!(
+                            "the owner is compared with `{authority}`, which is neither a signer nor a PDA"
+                        ),
+                    );
+                }
+            }
+            diag.help("make the owner a `Signer`, or derive it with `seeds`");
+        },
+    );
+}
+
+/// If `expr` is a call of `transfer`, `transfer_checked` or `burn` of `anchor_spl::token` or of
+/// `spl_token::instruction`, return the operation and the expression of the debited account
+fn debited_account<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+) -> Option<(&'static str, &'tcx Expr<'tcx>)> {
+    let args = match expr.kind {
+        ExprKind::Call(_, args) => args,
+        _ => return None,
+    };
+    match match_any_def_paths(
+        cx,
+        fn_def_id(cx, expr)?,
+        &[
+            &paths::ANCHOR_SPL_TRANSFER,
+            &paths::ANCHOR_SPL_TRANSFER_CHECKED,
+            &paths::ANCHOR_SPL_BURN,
+            &paths::SPL_TOKEN_TRANSFER,
+            &paths::SPL_TOKEN_TRANSFER_CHECKED,
+            &paths::SPL_TOKEN_BURN,
+        ],
+    )? {
+        // `transfer(CpiContext::new(program, Transfer { from, to, authority }), amount)`
+        0 | 1 => Some((
+            "transfer",
+            cpi_accounts_field(cx, args.first()?, "from", 2)?,
+        )),
+        2 => Some(("burn", cpi_accounts_field(cx, args.first()?, "from", 2)?)),
+        // `transfer(token_program_id, source, destination, authority, signers, amount)`
+        3 | 4 => Some(("transfer", args.get(1)?)),
+        // `burn(token_program_id, account, mint, authority, signers, amount)`
+        _ => Some(("burn", args.get(1)?)),
+    }
+}
+
+/// Return the field `name` of the struct expression of the accounts of a CPI, e.g.,
+/// `Transfer { .. }`, in `expr`, following up to `depth` local variables, e.g., `cpi_accounts` in
+/// `CpiContext::new(program, cpi_accounts)`
+fn cpi_accounts_field<'tcx>(
+    cx: &LateContext<'tcx>,
+    expr: &'tcx Expr<'tcx>,
+    name: &str,
+    depth: usize,
+) -> Option<&'tcx Expr<'tcx>> {
+    let mut field = None;
+    visit_expr_no_bodies(expr, |expr| {
+        field = match expr.kind {
+            ExprKind::Struct(_, fields, _) => fields
+                .iter()
+                .find(|field| field.ident.as_str() == name)
+                .map(|field| field.expr),
+            ExprKind::Path(_) if depth > 0 => path_to_local(expr)
+                .and_then(|local| local_init(cx, local))
+                .and_then(|init| cpi_accounts_field(cx, init, name, depth - 1)),
+            _ => None,
+        };
+        field.is_some()
+    });
+    field
+}
+
+/// Return true if `scope` compares the owner of `account`, e.g.,
+/// `ctx.accounts.vault.owner != ctx.accounts.user.key()`
+fn compares_owner<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    account: &(DefId, String),
+) -> bool {
+    let reads_owner = |expr: &'tcx Expr<'tcx>| {
+        visit_expr_no_bodies(expr, |expr| match expr.kind {
+            ExprKind::Field(object, ident) if ident.as_str() == "owner" => {
+                account_struct_field(cx, object).as_ref() == Some(account)
+            }
+            _ => false,
+        })
+    };
+    visit_expr_no_bodies(scope, |expr| match expr.kind {
+        ExprKind::Binary(op, lhs, rhs) if matches!(op.node, BinOpKind::Eq | BinOpKind::Ne) => {
+            reads_owner(lhs) || reads_owner(rhs)
+        }
+        _ => false,
+    })
+}
+
+/// Return true if the field is a `Signer`, has the `signer` constraint, or is a PDA of the program
+fn is_signer_or_pda(field: &Field) -> bool {
+    matches!(field.ty, FieldTy::Signer)
+        || field.constraints.signer.is_some()
+        || field.constraints.seeds.is_some()
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
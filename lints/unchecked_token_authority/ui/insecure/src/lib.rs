@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token::{self, spl_token, Burn, Mint, Token, TokenAccount, Transfer};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unchecked_token_authority_insecure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        let seeds: &[&[u8]] = &[b"pool_authority", &[ctx.bumps.pool_authority]];
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.destination.to_account_info(),
+            authority: ctx.accounts.pool_authority.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            cpi_accounts,
+            &[seeds],
+        );
+        token::transfer(cpi_ctx, amount)
+    }
+
+    pub fn withdraw_all(ctx: Context<Withdraw>) -> Result<()> {
+        let seeds: &[&[u8]] = &[b"pool_authority", &[ctx.bumps.pool_authority]];
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                &ctx.accounts.token_program.key(),
+                &ctx.accounts.vault.key(),
+                &ctx.accounts.destination.key(),
+                &ctx.accounts.pool_authority.key(),
+                &[],
+                ctx.accounts.vault.amount,
+            )?,
+            &[
+                ctx.accounts.vault.to_account_info(),
+                ctx.accounts.destination.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+            ],
+            &[seeds],
+        )?;
+        Ok(())
+    }
+
+    pub fn burn_tokens(ctx: Context<BurnTokens>, amount: u64) -> Result<()> {
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    from: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+    pub user: Signer<'info>,
+    /// CHECK: the PDA owning the vaults of the users
+    #[account(seeds = [b"pool_authority"], bump)]
+    pub pool_authority: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BurnTokens<'info> {
+    #[account(mut, token::authority = owner)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: the owner of the vault
+    pub owner: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+fn main() {}
@@ -0,0 +1,285 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+
+use anchor_syn::AccountField;
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::{def_id::DefId, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use solana_lints::{
+    bless::{is_blessing, write_lock_or_warn},
+    diagnostics::span_lint_hir_and_then,
+    discriminators::{account_types, type_path},
+    layouts::{
+        account_layout, compare, lock_path, read_lock, write_lock, Change, Layout, LOCK_FILE,
+    },
+    sarif,
+    utils::{account_data_type, anchor_constraints::ConstraintSummary, get_anchor_accounts_struct},
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the `#[account]` types of the crate whose fields changed since they were recorded
+    /// in the `layouts.lock` file of the package (see `solana_lints::layouts`), and whose
+    /// existing accounts cannot be read with the new fields:
+    ///
+    /// - the types which gained fields, unless an `Accounts` struct reallocates an account of the
+    ///   type with the `realloc` constraint, and the type has a version field, i.e., a field named
+    ///   `version` or ending with `_version`
+    /// - the types whose recorded fields were removed, moved, renamed or changed type
+    ///
+    /// The types are recorded by path, e.g., `staking::Vault`. If `SOLANA_LINTS_BLESS` is set
+    /// (see `solana_lints::bless`), the lint records the layouts of the account types of the crate
+    /// in `layouts.lock` instead, so that they are compared with the layouts of the next runs. The
+    /// file should be committed, and updated the same way once the accounts on chain are migrated.
+    /// A package without the file is not checked.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// Anchor serializes the fields of an account type in order, with neither their names nor a
+    /// version. After an upgrade adding a field, the accounts created before the upgrade are too
+    /// short to be deserialized, so that every instruction using them fails, unless an instruction
+    /// reallocates them and initializes the new fields. A version field tells the migrated accounts
+    /// apart from the others.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The accounts reallocated with `AccountInfo::realloc` rather than with the `realloc`
+    /// constraint are not recognized, and the instructions reallocating the accounts are not
+    /// checked to initialize the new fields or the version. The types are recorded by path, so a
+    /// renamed or moved type is treated as a new type (see `account_discriminator_collision`).
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// // layouts.lock records `Vault` with the field `balance: u64`
+    /// #[account]
+    /// pub struct Vault {
+    ///     pub balance: u64,
+    ///     pub fee: u64,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[account]
+    /// pub struct Vault {
+    ///     pub balance: u64,
+    ///     pub layout_version: u8,
+    ///     pub fee: u64,
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct MigrateVault<'info> {
+    ///     #[account(mut, realloc = 8 + 8 + 1 + 8, realloc::payer = payer, realloc::zero = false)]
+    ///     vault: Account<'info, Vault>,
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - for each Anchor `Accounts` struct, record the account types of the `Account` and
+    ///   `AccountLoader` fields with the `realloc` constraint
+    ///
+    /// check_crate_post:
+    ///
+    /// - collect the account types of the crate, i.e., the types implementing both
+    ///   `anchor_lang::Discriminator` and `anchor_lang::Owner`, and their fields
+    /// - if `SOLANA_LINTS_BLESS` is set, write the layouts to `layouts.lock`
+    /// - else, if `layouts.lock` exists, for each type recorded in it:
+    ///   - if the recorded fields are the first fields of the type, and the type has other fields,
+    ///     report the type unless it is recorded as reallocated and has a version field
+    ///   - if the recorded fields are not the first fields of the type, report the type
+    pub UNMIGRATED_ACCOUNT_LAYOUT,
+    Warn,
+    "Anchor account types whose fields changed since `layouts.lock` without a migration",
+    UnmigratedAccountLayout::default()
+}
+
+#[derive(Default)]
+struct UnmigratedAccountLayout {
+    /// The account types of the `Account` and `AccountLoader` fields with the `realloc` constraint
+    reallocated: FxHashSet<DefId>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UnmigratedAccountLayout {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                for (item_field, anchor_field) in
+                    variant.fields().iter().zip(accounts_struct.fields.iter())
+                {
+                    if_chain! {
+                        if let AccountField::Field(field) = anchor_field;
+                        if ConstraintSummary::new(&field.constraints).is_reallocated;
+                        let field_ty = cx.tcx.type_of(item_field.def_id).instantiate_identity();
+                        if let Some(account_type) = account_data_type(cx, field_ty);
+                        then {
+                            self.reallocated.insert(account_type);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        let layouts = account_types(cx)
+            .into_iter()
+            .filter(|def_id| def_id.is_local())
+            .map(|def_id| (def_id, account_layout(cx, def_id)))
+            .collect::<Vec<_>>();
+
+        if let Some(path) = lock_path(cx).filter(|_| !layouts.is_empty()) {
+            if is_blessing() {
+                let entries = layouts
+                    .iter()
+                    .map(|(def_id, layout)| (type_path(cx, *def_id), layout.clone()))
+                    .collect::<Vec<_>>();
+                write_lock_or_warn(cx, "layouts", &path, |path| write_lock(path, &entries));
+            } else if path.exists() {
+                match read_lock(&path) {
+                    Ok(entries) => self.check_lock(cx, &layouts, &entries),
+                    Err(error) => cx.tcx.sess.dcx().warn(format!(
+                        "could not read the layouts file `{}`: {error}",
+                        path.display()
+                    )),
+                }
+            }
+        }
+
+        sarif::write(cx, &[UNMIGRATED_ACCOUNT_LAYOUT]);
+    }
+}
+
+impl UnmigratedAccountLayout {
+    /// Report the account types whose layout changed since it was recorded in the lock file, and
+    /// whose accounts are not migrated
+    fn check_lock(
+        &self,
+        cx: &LateContext<'_>,
+        layouts: &[(DefId, Layout)],
+        entries: &[(String, Layout)],
+    ) {
+        for (def_id, layout) in layouts {
+            let name = type_path(cx, *def_id);
+            let recorded = match entries
+                .iter()
+                .find(|(recorded_path, _)| *recorded_path == name)
+            {
+                Some((_, recorded)) => recorded,
+                // a new account type, which has no accounts on chain yet
+                None => continue,
+            };
+            let hir_id = cx.tcx.local_def_id_to_hir_id(def_id.expect_local());
+            let span = cx.tcx.def_span(def_id);
+            match compare(recorded, layout) {
+                Change::Unchanged => {}
+                Change::Grown(added) => {
+                    let is_reallocated = self.reallocated.contains(def_id);
+                    if is_reallocated && is_versioned(layout) {
+                        continue;
+                    }
+                    let added = fields(&added);
+                    let msg = if is_reallocated {
+                        format!("the account type `{name}` has new fields, {added}, but no version field")
+                    } else {
+                        format!("the account type `{name}` has new fields, {added}, but no instruction reallocates its accounts")
+                    };
+                    let note = format!(
+                        "`{LOCK_FILE}` records `{name}` with the fields {}, so that the accounts created before the new fields were added are too short to be deserialized",
+                        fields(recorded)
+                    );
+                    let help = format!(
+                        "add an instruction reallocating the accounts of `{name}` with `realloc = ...` and initializing the new fields, and a `version` field telling the migrated accounts apart; once the accounts are migrated, update `{LOCK_FILE}`"
+                    );
+                    span_lint_hir_and_then(
+                        cx,
+                        UNMIGRATED_ACCOUNT_LAYOUT,
+                        hir_id,
+                        span,
+                        &msg,
+                        |diag| {
+                            diag.note(note);
+                            diag.help(help);
+                        },
+                    );
+                }
+                Change::Incompatible => {
+                    let msg = format!(
+                        "the fields of the account type `{name}` differ from the fields recorded in `{LOCK_FILE}`"
+                    );
+                    let recorded_note = format!(
+                        "`{LOCK_FILE}` records `{name}` with the fields {}",
+                        fields(recorded)
+                    );
+                    let current_note = format!("the fields of `{name}` are {}", fields(layout));
+                    span_lint_hir_and_then(
+                        cx,
+                        UNMIGRATED_ACCOUNT_LAYOUT,
+                        hir_id,
+                        span,
+                        &msg,
+                        |diag| {
+                            diag.note(recorded_note);
+                            diag.note(current_note);
+                            diag.help(
+                                "keep the recorded fields, in order and with their types, so that the existing accounts can be deserialized, and add the new fields after them",
+                            );
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Return true if the layout has a version field, i.e., a field named `version` or ending with
+/// `_version`
+fn is_versioned(layout: &Layout) -> bool {
+    layout
+        .iter()
+        .any(|(name, _)| name == "version" || name.ends_with("_version"))
+}
+
+/// Format the fields, e.g., "`balance: u64` and `bump: u8`"
+fn fields(layout: &Layout) -> String {
+    let fields = layout
+        .iter()
+        .map(|(name, ty)| format!("`{name}: {ty}`"))
+        .collect::<Vec<_>>();
+    match fields.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {last}", rest.join(", ")),
+        None => String::from("none"),
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
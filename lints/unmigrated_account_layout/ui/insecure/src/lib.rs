@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unmigrated_account_layout_insecure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.fee = amount / 100;
+        ctx.accounts.vault.balance += amount - ctx.accounts.vault.fee;
+        Ok(())
+    }
+
+    pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+        ctx.accounts.pool.rewards = 0;
+        Ok(())
+    }
+
+    pub fn open_position(ctx: Context<OpenPosition>, amount: u64) -> Result<()> {
+        require!(ctx.accounts.config.fee_bps < 10_000, ErrorCode::InvalidFee);
+        ctx.accounts.position.amount = amount;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    #[account(mut, realloc = 8 + 8 + 8, realloc::payer = payer, realloc::zero = false)]
+    pool: Account<'info, Pool>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenPosition<'info> {
+    config: Account<'info, Config>,
+    #[account(init, payer = payer, space = 8 + 8)]
+    position: Account<'info, Position>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+// `layouts.lock` records `admin_count` before `fee_bps`
+#[account]
+pub struct Config {
+    pub fee_bps: u16,
+    pub admin_count: u8,
+}
+
+// `rewards` is added, and the accounts are reallocated, but they cannot be told apart from the
+// accounts which are not migrated yet
+#[account]
+pub struct Pool {
+    pub liquidity: u64,
+    pub rewards: u64,
+}
+
+// `Position` is not recorded in `layouts.lock`, as it has no accounts on chain yet
+#[account]
+pub struct Position {
+    pub amount: u64,
+}
+
+// `fee` is added, but the accounts are not reallocated
+#[account]
+pub struct Vault {
+    pub balance: u64,
+    pub fee: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    InvalidFee,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+pub const POOL_VERSION: u8 = 1;
+
+#[program]
+pub mod unmigrated_account_layout_secure {
+    use super::*;
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
+        ctx.accounts.vault.balance += amount;
+        Ok(())
+    }
+
+    pub fn migrate_pool(ctx: Context<MigratePool>) -> Result<()> {
+        if ctx.accounts.pool.pool_version < POOL_VERSION {
+            ctx.accounts.pool.rewards = 0;
+            ctx.accounts.pool.pool_version = POOL_VERSION;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePool<'info> {
+    #[account(mut, realloc = 8 + 8 + 1 + 8, realloc::payer = payer, realloc::zero = false)]
+    pool: Account<'info, Pool>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+// `rewards` is added, and the accounts are reallocated and versioned
+#[account]
+pub struct Pool {
+    pub liquidity: u64,
+    pub pool_version: u8,
+    pub rewards: u64,
+}
+
+// the fields recorded in `layouts.lock`
+#[account]
+pub struct Vault {
+    pub balance: u64,
+}
+
+pub mod staking {
+    use super::*;
+
+    // recorded as `staking::Vault`, apart from `Vault`
+    #[account]
+    pub struct Vault {
+        pub staked: u64,
+        pub unlock_slot: u64,
+    }
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,379 @@
+#![feature(rustc_private)]
+#![feature(box_patterns)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_data_structures;
+extern crate rustc_hir;
+extern crate rustc_middle;
+
+use clippy_utils::{match_any_def_paths, match_def_path};
+use if_chain::if_chain;
+use rustc_data_structures::fx::FxHashSet;
+use rustc_hir::Body;
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_middle::{
+    mir::{
+        self, BasicBlock, BinOp, Local, Location, Operand, Place, ProjectionElem, Rvalue,
+        StatementKind, TerminatorKind,
+    },
+    ty::{self, TyKind},
+};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    mir_cache::{BodyAnalysis, MirCache},
+    paths, sarif,
+    utils::mir_lint_root,
+};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports `+`, `-` and `*` whose results become lamport balances or token amounts, i.e.,
+    /// values which are:
+    ///
+    /// - written to the lamports of an account, e.g., `**account.lamports.borrow_mut() -= amount`
+    /// - written to the `amount` field of an `spl_token::state::Account`
+    /// - passed as the amount to `anchor_spl::token::{transfer, mint_to, burn}` or
+    ///   `spl_token::instruction::{transfer, mint_to, burn}`
+    ///
+    /// **Why is this bad?**
+    ///
+    /// If overflow checks are disabled, which is the default for release builds, the arithmetic
+    /// wraps: a withdrawal larger than the balance leaves the account with almost `u64::MAX`
+    /// lamports or tokens. If overflow checks are enabled, the program panics, and the error does
+    /// not say which amount was invalid. `checked_add`, `checked_sub` and `checked_mul` make the
+    /// program handle the overflow, e.g., by returning an error.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The lint traces the values within a function only, so an operation whose result is passed
+    /// to another function is not reported. An operation whose result is bounded by an earlier
+    /// check, e.g., `if amount <= balance { balance - amount }`, is reported.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// let vault_lamports = ctx.accounts.vault.lamports();
+    /// **ctx.accounts.vault.try_borrow_mut_lamports()? = vault_lamports
+    ///     .checked_sub(amount)
+    ///     .ok_or(ProgramError::InsufficientFunds)?;
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// - For every function whose MIR is available, excluding the functions generated by macros
+    /// - Find the sinks:
+    ///   - assignments through `&mut u64`s which are dereferenced from `&mut &mut u64`s, i.e., the
+    ///     `RefMut<&mut u64>`s returned by `AccountInfo::{lamports.borrow_mut, try_borrow_mut_lamports}`
+    ///   - assignments to the `amount` field of `spl_token::state::Account`
+    ///   - the last argument, the amount, of calls to `anchor_spl::token::{transfer, mint_to, burn}` and
+    ///     `spl_token::instruction::{transfer, mint_to, burn}`
+    /// - For each sink, trace the assigned value backward (similar to `bump_seed_canonicalization`)
+    ///   - through copies, moves and casts, following every predecessor of each basic block, except
+    ///     the cleanup blocks, the early returns of `?` and the back edges of loops (see
+    ///     `solana_lints::utils::dataflow::backward_predecessors`)
+    ///   - If the value is the result of `Add`, `Sub` or `Mul`, checked or not, report the operation,
+    ///     unless it is generated by a macro
+    ///   - If the value is the result of any other rvalue or a call, e.g., `checked_sub`, stop
+    ///     following the path
+    pub UNSAFE_ARITHMETIC,
+    Warn,
+    "unchecked arithmetic on lamport balances and token amounts",
+    UnsafeArithmetic::default()
+}
+
+#[derive(Default)]
+struct UnsafeArithmetic {
+    mir_cache: MirCache,
+}
+
+/// What the value of a sink is
+#[derive(Clone, Copy)]
+enum SinkKind {
+    Lamports,
+    TokenAmount,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UnsafeArithmetic {
+    fn check_body(&mut self, cx: &LateContext<'tcx>, body: &'tcx Body<'tcx>) {
+        let hir_map = cx.tcx.hir();
+        let body_did = hir_map.body_owner_def_id(body.id()).to_def_id();
+        if !cx.tcx.def_kind(body_did).is_fn_like()
+            || !cx.tcx.is_mir_available(body_did)
+            || is_skipped_expansion(UNSAFE_ARITHMETIC, body.value.span)
+        {
+            return;
+        }
+        let body_mir = cx.tcx.optimized_mir(body_did);
+
+        // (location of the sink, local holding the value, kind of value)
+        let mut sinks = Vec::new();
+        for (block, block_data) in body_mir.basic_blocks.iter_enumerated() {
+            for (statement_index, stmt) in block_data.statements.iter().enumerate() {
+                if_chain! {
+                    if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind;
+                    if let Some(kind) = sink_kind(cx, body_mir, place);
+                    then {
+                        let location = Location {
+                            block,
+                            statement_index,
+                        };
+                        // The operation may be the assigned rvalue, e.g., without overflow checks.
+                        if let Some(op) = arithmetic_op(rvalue) {
+                            report(cx, body_mir, location, op, kind);
+                        } else if let Some(local) = copied_local(rvalue) {
+                            sinks.push((location, local, kind));
+                        }
+                    }
+                }
+            }
+            if_chain! {
+                if let TerminatorKind::Call {
+                    func: Operand::Constant(box func),
+                    args,
+                    ..
+                } = &block_data.terminator().kind;
+                if let TyKind::FnDef(def_id, _) = func.const_.ty().kind();
+                if match_any_def_paths(
+                    cx,
+                    *def_id,
+                    &[
+                        &paths::ANCHOR_SPL_TRANSFER,
+                        &paths::ANCHOR_SPL_MINT_TO,
+                        &paths::ANCHOR_SPL_BURN,
+                        &paths::SPL_TOKEN_TRANSFER,
+                        &paths::SPL_TOKEN_MINT_TO,
+                        &paths::SPL_TOKEN_BURN,
+                    ],
+                )
+                .is_some();
+                if let Some(Operand::Copy(amount) | Operand::Move(amount)) = args.last();
+                then {
+                    let location = Location {
+                        block,
+                        statement_index: block_data.statements.len(),
+                    };
+                    sinks.push((location, amount.local, SinkKind::TokenAmount));
+                }
+            }
+        }
+
+        // An operation may reach several sinks, e.g., `let total = amount + fee;` used twice.
+        let analysis = self.mir_cache.analysis(cx, body_did);
+        let mut reported = FxHashSet::default();
+        for (location, local, kind) in sinks {
+            for (op_location, op) in arithmetic_origins(body_mir, analysis, location, local) {
+                if reported.insert(op_location) {
+                    report(cx, body_mir, op_location, op, kind);
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[UNSAFE_ARITHMETIC]);
+    }
+}
+
+fn report(
+    cx: &LateContext<'_>,
+    body: &mir::Body<'_>,
+    location: Location,
+    op: BinOp,
+    kind: SinkKind,
+) {
+    let source_info = body.source_info(location);
+    // the operations generated by macros, e.g., of another crate
+    if is_skipped_expansion(UNSAFE_ARITHMETIC, source_info.span) {
+        return;
+    }
+    let (symbol, method) = match op {
+        BinOp::Add => ("+", "checked_add"),
+        BinOp::Sub => ("-", "checked_sub"),
+        _ => ("*", "checked_mul"),
+    };
+    let value = match kind {
+        SinkKind::Lamports => "a lamport balance",
+        SinkKind::TokenAmount => "a token amount",
+    };
+    span_lint_hir_and_then(
+        cx,
+        UNSAFE_ARITHMETIC,
+        mir_lint_root(body, *source_info),
+        source_info.span,
+        &format!("unchecked `{symbol}` on {value}"),
+        |diag| {
+            diag.help(format!(
+                "use `{method}` and return an error if it returns `None`"
+            ));
+        },
+    );
+}
+
+/// Return the kind of value assigned to `place` if it is a sink
+/// - `*x`, where `x: &mut u64` is `*y` and `y: &mut &mut u64`: the lamports of an account
+/// - `x.amount`, where `x` is an `spl_token::state::Account`: a token amount
+fn sink_kind<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx mir::Body<'tcx>,
+    place: &Place<'tcx>,
+) -> Option<SinkKind> {
+    let (base, elem) = place.iter_projections().last()?;
+    match elem {
+        ProjectionElem::Deref => {
+            if_chain! {
+                if base.projection.is_empty();
+                if is_mut_ref_to_u64(body.local_decls[base.local].ty);
+                if let Some(Rvalue::CopyForDeref(source) | Rvalue::Use(Operand::Copy(source))) =
+                    assigned_rvalue(body, base.local);
+                if let [ProjectionElem::Deref] = source.projection.as_slice();
+                if let ty::Ref(_, inner, mir::Mutability::Mut) =
+                    body.local_decls[source.local].ty.kind();
+                if is_mut_ref_to_u64(*inner);
+                then {
+                    Some(SinkKind::Lamports)
+                } else {
+                    None
+                }
+            }
+        }
+        ProjectionElem::Field(field, _) => {
+            if_chain! {
+                if let ty::Adt(adt_def, _) = base.ty(body, cx.tcx).ty.kind();
+                if match_def_path(cx, adt_def.did(), &paths::SPL_TOKEN_ACCOUNT);
+                if adt_def.non_enum_variant().fields[field].name.as_str() == "amount";
+                then {
+                    Some(SinkKind::TokenAmount)
+                } else {
+                    None
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+fn is_mut_ref_to_u64(ty: ty::Ty<'_>) -> bool {
+    matches!(
+        ty.kind(),
+        ty::Ref(_, inner, mir::Mutability::Mut) if matches!(inner.kind(), ty::Uint(ty::UintTy::U64))
+    )
+}
+
+/// Return the rvalue of the first assignment to the whole of `local` in `body`
+fn assigned_rvalue<'tcx>(body: &'tcx mir::Body<'tcx>, local: Local) -> Option<&'tcx Rvalue<'tcx>> {
+    body.basic_blocks
+        .iter()
+        .flat_map(|block_data| &block_data.statements)
+        .find_map(|stmt| match &stmt.kind {
+            StatementKind::Assign(box (place, rvalue))
+                if place.local == local && place.projection.is_empty() =>
+            {
+                Some(rvalue)
+            }
+            _ => None,
+        })
+}
+
+/// Return the operation if `rvalue` is `Add`, `Sub` or `Mul`, with or without overflow checks
+fn arithmetic_op(rvalue: &Rvalue<'_>) -> Option<BinOp> {
+    match rvalue {
+        Rvalue::BinaryOp(op @ (BinOp::Add | BinOp::Sub | BinOp::Mul), _)
+        | Rvalue::CheckedBinaryOp(op @ (BinOp::Add | BinOp::Sub | BinOp::Mul), _) => Some(*op),
+        _ => None,
+    }
+}
+
+/// Return the local whose value `rvalue` copies, moves or casts, e.g., `_5` for `move (_5.0)`
+fn copied_local(rvalue: &Rvalue<'_>) -> Option<Local> {
+    match rvalue {
+        Rvalue::Use(Operand::Copy(place) | Operand::Move(place))
+        | Rvalue::Cast(_, Operand::Copy(place) | Operand::Move(place), _) => Some(place.local),
+        _ => None,
+    }
+}
+
+/// Trace the value of `local` at `location` backward, and return the locations of the `Add`,
+/// `Sub` and `Mul` which compute it, on any of the paths to `location`
+/// - The predecessors returned by `dataflow::backward_predecessors`, cached by `analysis`, are
+///   followed
+/// - The trace of a path stops at an assignment of any other rvalue, or at a call assigning `local`
+fn arithmetic_origins(
+    body: &mir::Body<'_>,
+    analysis: &BodyAnalysis,
+    location: Location,
+    local: Local,
+) -> Vec<(Location, BinOp)> {
+    let mut origins = Vec::new();
+    let mut worklist = vec![(location.block, location.statement_index, local)];
+    let mut visited = FxHashSet::<(BasicBlock, Local)>::default();
+    'paths: while let Some((block, end, mut local)) = worklist.pop() {
+        for (statement_index, stmt) in body.basic_blocks[block].statements[..end]
+            .iter()
+            .enumerate()
+            .rev()
+        {
+            if let StatementKind::Assign(box (place, rvalue)) = &stmt.kind {
+                // Assignments through a reference held in `local` do not change `local`.
+                if place.local != local || place.is_indirect() {
+                    continue;
+                }
+                if let Some(op) = arithmetic_op(rvalue) {
+                    origins.push((
+                        Location {
+                            block,
+                            statement_index,
+                        },
+                        op,
+                    ));
+                    continue 'paths;
+                }
+                local = match copied_local(rvalue) {
+                    Some(local) => local,
+                    None => continue 'paths,
+                };
+            }
+        }
+        for &predecessor in analysis.backward_predecessors(block) {
+            if !visited.insert((predecessor, local)) {
+                continue;
+            }
+            let block_data = &body.basic_blocks[predecessor];
+            // The value is the result of a call, e.g., `checked_sub`.
+            if let TerminatorKind::Call { destination, .. } = &block_data.terminator().kind {
+                if destination.local == local {
+                    continue;
+                }
+            }
+            worklist.push((predecessor, block_data.statements.len(), local));
+        }
+    }
+    origins.sort_by_key(|&(location, _)| location);
+    origins
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::{self, spl_token::state::Account as SplTokenAccount, Token, Transfer};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unsafe_arithmetic_insecure {
+    use super::*;
+
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        **ctx.accounts.vault.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.user.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+
+    pub fn deposit(ctx: Context<Deposit>, amount: u64, fee: u64) -> Result<()> {
+        let total = amount + fee;
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.source.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, total)
+    }
+
+    pub fn burn_raw(ctx: Context<BurnRaw>, amount: u64) -> Result<()> {
+        let mut data = ctx.accounts.token.try_borrow_mut_data()?;
+        let mut token = SplTokenAccount::unpack(&data)?;
+        token.amount = token.amount - amount;
+        SplTokenAccount::pack(token, &mut data)?;
+        Ok(())
+    }
+
+    pub fn deposit_with_fee(ctx: Context<Deposit>, amount: u64, fee: u64) -> Result<()> {
+        // The fee is checked on one of the paths to the transfer only.
+        let total = if fee < amount {
+            amount
+                .checked_add(fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?
+        } else {
+            amount + fee
+        };
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.source.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, total)
+    }
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    vault: AccountInfo<'info>,
+    #[account(mut)]
+    user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    source: AccountInfo<'info>,
+    #[account(mut)]
+    destination: AccountInfo<'info>,
+    authority: Signer<'info>,
+    token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BurnRaw<'info> {
+    #[account(mut)]
+    token: AccountInfo<'info>,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,272 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+
+use anchor_syn::{AccountField, AccountsStruct, Ty as FieldTy};
+use clippy_utils::{
+    consts::{constant, Constant},
+    source::snippet,
+};
+use if_chain::if_chain;
+use rustc_hir::{Expr, ExprKind, FieldDef, Item, ItemKind};
+use rustc_lint::{LateContext, LateLintPass};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    utils::{
+        anchor_constraints::field_constraint_summary, get_anchor_accounts_struct,
+        is_expr_method_call,
+    },
+};
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the reallocations of accounts which do not zero the bytes the account grows by:
+    ///
+    /// - the fields of Anchor `Accounts` structs with the constraint `realloc::zero = false`
+    /// - the calls `AccountInfo::realloc(new_len, false)`
+    ///
+    /// The lint also reports the fields with the `realloc` constraint whose `realloc::payer` is not
+    /// checked, i.e., is not a `Signer`, has neither the `signer`, `address` nor `seeds`
+    /// constraints, and is not the target of a `has_one` constraint.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// An account which shrinks and grows again in the same call gets back the bytes it had before
+    /// shrinking, unless they are zeroed. The program may then read stale data as the new fields
+    /// of the account, e.g., an authority which was removed.
+    ///
+    /// When an account with the `realloc` constraint shrinks, Anchor sends the lamports it no
+    /// longer needs for rent exemption to `realloc::payer`. If any account can be passed as the
+    /// payer, the caller of the instruction can take these lamports.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![warn(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The runtime zeroes the memory an account may grow into at the start of each instruction, so
+    /// that an account which never shrinks in the same call is safely reallocated without zeroing,
+    /// and zeroing then only costs compute units. The lint does not know whether an account
+    /// shrinks, and reports every reallocation without zeroing.
+    ///
+    /// A payer checked in the handler, e.g., with `require_keys_eq!`, is reported.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Resize<'info> {
+    ///     #[account(mut, realloc = 8 + len, realloc::payer = payer, realloc::zero = false)]
+    ///     pub profile: Account<'info, Profile>,
+    ///     /// CHECK: receives the lamports of the profile when it shrinks
+    ///     #[account(mut)]
+    ///     pub payer: UncheckedAccount<'info>,
+    ///     pub system_program: Program<'info, System>,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct Resize<'info> {
+    ///     #[account(mut, realloc = 8 + len, realloc::payer = payer, realloc::zero = true)]
+    ///     pub profile: Account<'info, Profile>,
+    ///     #[account(mut)]
+    ///     pub payer: Signer<'info>,
+    ///     pub system_program: Program<'info, System>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - for each field of an Anchor `Accounts` struct with the `realloc` constraint
+    /// - if `realloc::zero` is the literal `false`, report the field
+    /// - if `realloc::payer` names a field of the struct, and the field is neither a `Signer` nor
+    ///   checked by its constraints or by those of the other fields, report the field, with a note
+    ///   at the payer
+    ///
+    /// check_expr:
+    ///
+    /// - for each call of `AccountInfo::realloc`, excluding the calls generated by macros, e.g.,
+    ///   by the `realloc` constraint
+    /// - if its `zero_init` argument evaluates to `false`, report the call
+    pub UNSAFE_REALLOC,
+    Warn,
+    "a reallocation which does not zero the new bytes, or whose payer is not checked"
+}
+
+impl<'tcx> LateLintPass<'tcx> for UnsafeRealloc {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if let ItemKind::Struct(variant, _) = item.kind;
+            if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item);
+            then {
+                let item_fields = variant.fields();
+                for (item_field, anchor_field) in
+                    item_fields.iter().zip(accounts_struct.fields.iter())
+                {
+                    let realloc = match anchor_field {
+                        AccountField::Field(field) => match &field.constraints.realloc {
+                            Some(realloc) => realloc,
+                            None => continue,
+                        },
+                        AccountField::CompositeField(_) => continue,
+                    };
+                    if is_false(&realloc.zero) {
+                        report_unzeroed_field(cx, item_field);
+                    }
+                    if_chain! {
+                        if let syn::Expr::Path(expr_path) = &realloc.payer;
+                        if let Some(payer) = expr_path.path.get_ident();
+                        let payer = payer.to_string();
+                        if !is_checked_payer(&accounts_struct, &payer);
+                        if let Some(payer_field) =
+                            item_fields.iter().find(|field| field.ident.as_str() == payer);
+                        then {
+                            report_unchecked_payer(cx, item_field, payer_field);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if_chain! {
+            if !is_skipped_expansion(UNSAFE_REALLOC, expr.span);
+            if let Some(account) = is_expr_method_call(cx, expr, &paths::SOLANA_PROGRAM_REALLOC);
+            if let ExprKind::MethodCall(_, _, [_, zero_init], _) = expr.kind;
+            if let Some(Constant::Bool(false)) = constant(cx, cx.typeck_results(), zero_init);
+            then {
+                span_lint_hir_and_then(
+                    cx,
+                    UNSAFE_REALLOC,
+                    expr.hir_id,
+                    expr.span,
+                    &format!(
+                        "`{}` is reallocated without zeroing the bytes it grows by",
+                        snippet(cx, account.span, "..")
+                    ),
+                    |diag| {
+                        diag.note(STALE_DATA_NOTE);
+                        diag.help(
+                            "pass `true` as `zero_init`, unless the account never shrinks in the same call",
+                        );
+                    },
+                );
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[UNSAFE_REALLOC]);
+    }
+}
+
+const STALE_DATA_NOTE: &str = "if the account shrank earlier in the same call, the bytes it grows by still hold its previous data";
+
+fn report_unzeroed_field(cx: &LateContext<'_>, item_field: &FieldDef<'_>) {
+    span_lint_hir_and_then(
+        cx,
+        UNSAFE_REALLOC,
+        item_field.hir_id,
+        item_field.span,
+        &format!(
+            "`{}` is reallocated without zeroing the bytes it grows by",
+            item_field.ident
+        ),
+        |diag| {
+            diag.note(STALE_DATA_NOTE);
+            diag.help(
+                "use `realloc::zero = true`, unless the account never shrinks in the same instruction",
+            );
+        },
+    );
+}
+
+fn report_unchecked_payer(
+    cx: &LateContext<'_>,
+    item_field: &FieldDef<'_>,
+    payer_field: &FieldDef<'_>,
+) {
+    span_lint_hir_and_then(
+        cx,
+        UNSAFE_REALLOC,
+        item_field.hir_id,
+        item_field.span,
+        &format!(
+            "the payer of the reallocation of `{}`, `{}`, is not checked",
+            item_field.ident, payer_field.ident
+        ),
+        |diag| {
+            diag.span_note(
+                payer_field.span,
+                format!("`{}` can be any account", payer_field.ident),
+            );
+            diag.note(format!(
+                "when `{}` shrinks, the lamports it no longer needs are sent to `{}`",
+                item_field.ident, payer_field.ident
+            ));
+            diag.help(format!(
+                "make `{}` a `Signer`, or add the `signer` constraint to it",
+                payer_field.ident
+            ));
+        },
+    );
+}
+
+/// Return true if the field `payer` of `accounts_struct` is a `Signer`, or its key is checked by
+/// the constraints of the struct
+fn is_checked_payer(accounts_struct: &AccountsStruct, payer: &str) -> bool {
+    let is_signer_field = accounts_struct
+        .fields
+        .iter()
+        .any(|account_field| match account_field {
+            AccountField::Field(field) => {
+                field.ident == payer && matches!(field.ty, FieldTy::Signer)
+            }
+            AccountField::CompositeField(_) => false,
+        });
+    is_signer_field
+        || field_constraint_summary(accounts_struct, payer).map_or(true, |summary| {
+            summary.is_signer || summary.has_key_check || summary.is_key_compared
+        })
+}
+
+fn is_false(expr: &syn::Expr) -> bool {
+    matches!(
+        expr,
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Bool(lit_bool),
+            ..
+        }) if !lit_bool.value
+    )
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn insecure_non_anchor() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure-non-anchor");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,25 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint,
+    entrypoint::ProgramResult,
+    pubkey::Pubkey,
+};
+
+const ZERO_INIT: bool = false;
+
+entrypoint!(process_instruction);
+fn process_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let record = next_account_info(account_info_iter)?;
+    let new_len = usize::from(instruction_data[0]) * 32;
+    record.realloc(new_len, false)?;
+    record.realloc(new_len + 32, ZERO_INIT)?;
+    Ok(())
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unsafe_realloc_insecure {
+    use super::*;
+
+    pub fn resize_profile(ctx: Context<ResizeProfile>, len: u32) -> Result<()> {
+        ctx.accounts.profile.name_len = len;
+        Ok(())
+    }
+
+    pub fn resize_log(ctx: Context<ResizeLog>, len: u32) -> Result<()> {
+        ctx.accounts.log.len = len;
+        Ok(())
+    }
+
+    pub fn resize_data(ctx: Context<ResizeData>, len: u32) -> Result<()> {
+        ctx.accounts.data.realloc(len as usize, false)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(len: u32)]
+pub struct ResizeProfile<'info> {
+    #[account(
+        mut,
+        realloc = 8 + 4 + len as usize,
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub profile: Account<'info, Profile>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(len: u32)]
+pub struct ResizeLog<'info> {
+    #[account(
+        mut,
+        realloc = 8 + 4 + len as usize,
+        realloc::payer = payer,
+        realloc::zero = true
+    )]
+    pub log: Account<'info, Log>,
+    /// CHECK: receives the lamports of the log when it shrinks
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResizeData<'info> {
+    /// CHECK: the data is reallocated without any layout
+    #[account(mut, owner = crate::ID)]
+    pub data: UncheckedAccount<'info>,
+}
+
+#[account]
+pub struct Profile {
+    pub name_len: u32,
+}
+
+#[account]
+pub struct Log {
+    pub len: u32,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,330 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::{AccountsStruct, Field, Ty as FieldTy};
+use clippy_utils::ty::match_type;
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, Expr, ExprKind, FnDecl, HirId, Item, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths, sarif,
+    seeds::Seed,
+    utils::{
+        anchor_constraints::{
+            anchor_field, field_constraint_summary, member_targets, ConstraintSummary,
+        },
+        context_accounts_struct, get_anchor_accounts_struct, is_authority_name, local_field_def,
+        local_struct_field, visit_expr_no_bodies,
+    },
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the instructions which overwrite the authority of an account, i.e., a `Pubkey` field
+    /// named `authority`, `owner` or `admin`, or ending with `_authority`, `_owner` or `_admin`,
+    /// either without the signature of the current authority, or, when the new authority signs,
+    /// without checking the current authority at all.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The authority of an account is the key allowed to administer it. If the instruction changing
+    /// it does not require the signature of the current authority, anyone can make themselves the
+    /// authority, e.g., by signing as the new authority, and then take over the account.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [ ] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The current authority is the account of the `Accounts` struct which a `has_one` constraint
+    /// or a `constraint` of the account checks the field against, e.g., `has_one = authority`, or
+    /// otherwise the account named as the field. Checks in the body of the instruction, e.g.,
+    /// `require_keys_eq!`, are not taken into account. The writes of accounts created by the
+    /// instruction, i.e., with the `init` constraint, are not reported, nor are the instructions
+    /// in which the current authority signs but the new one does not, which is a common way of
+    /// transferring an authority to a key which accepts it later.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn set_authority(ctx: Context<SetAuthority>) -> Result<()> {
+    ///     ctx.accounts.config.authority = ctx.accounts.new_authority.key();
+    ///     Ok(())
+    /// }
+    ///
+    /// #[derive(Accounts)]
+    /// pub struct SetAuthority<'info> {
+    ///     #[account(mut, has_one = authority)]
+    ///     config: Account<'info, Config>,
+    ///     /// CHECK: the current authority of the config
+    ///     authority: UncheckedAccount<'info>,
+    ///     new_authority: Signer<'info>,
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// #[derive(Accounts)]
+    /// pub struct SetAuthority<'info> {
+    ///     #[account(mut, has_one = authority)]
+    ///     config: Account<'info, Config>,
+    ///     authority: Signer<'info>,
+    ///     new_authority: Signer<'info>,
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - record the Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for each function taking a `Context<T>`, excluding the functions generated by macros
+    /// - for each assignment to a `Pubkey` field with an authority name of a field of `T`, e.g.,
+    ///   `ctx.accounts.config.authority = ...`, record the assignment and, if the assigned value is
+    ///   the key of a field of `T`, e.g., `ctx.accounts.new_authority.key()`, the new authority
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each recorded assignment, unless the account is initialized by the instruction
+    /// - find the current authorities, i.e., the fields of `T` which the `has_one` constraints and
+    ///   the `constraint`s of the account compare the field with, or else the field of `T` named as
+    ///   the field
+    /// - if there are current authorities, report the assignment if none of them is a signer, and
+    ///   note them
+    /// - otherwise, report the assignment if the new authority is a signer
+    pub UNSIGNED_AUTHORITY_CHANGE,
+    Warn,
+    "writes of the authority of an account which the current authority does not sign",
+    UnsignedAuthorityChange::default()
+}
+
+#[derive(Default)]
+struct UnsignedAuthorityChange {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    /// The writes of the authority fields of accounts, in the order they are found
+    writes: Vec<AuthorityWrite>,
+}
+
+/// An assignment to the authority field of an account of an `Accounts` struct
+struct AuthorityWrite {
+    hir_id: HirId,
+    span: Span,
+    /// The `Accounts` struct
+    accounts_struct: DefId,
+    /// The field of the `Accounts` struct holding the account, e.g., `config`
+    account: String,
+    /// The authority field of the account, e.g., `authority`
+    member: String,
+    /// The field of the `Accounts` struct whose key is assigned, e.g., `new_authority`
+    new_authority: Option<String>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UnsignedAuthorityChange {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        local_def_id: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(UNSIGNED_AUTHORITY_CHANGE, span) {
+            return;
+        }
+        let accounts_struct = match context_accounts_struct(cx, local_def_id) {
+            Some(accounts_struct) => accounts_struct,
+            None => return,
+        };
+        visit_expr_no_bodies(body.value, |expr| {
+            if_chain! {
+                if !is_skipped_expansion(UNSIGNED_AUTHORITY_CHANGE, expr.span);
+                if let ExprKind::Assign(lhs, value, _) = expr.kind;
+                if let ExprKind::Field(object, member) = lhs.kind;
+                if is_authority_name(member.as_str());
+                let lhs_ty = cx.typeck_results().expr_ty(lhs);
+                if match_type(cx, lhs_ty, &paths::SOLANA_PROGRAM_PUBKEY);
+                if let Some((def_id, account)) = local_struct_field(cx, object);
+                if def_id == accounts_struct;
+                then {
+                    self.writes.push(AuthorityWrite {
+                        hir_id: expr.hir_id,
+                        span: expr.span,
+                        accounts_struct,
+                        account,
+                        member: member.to_string(),
+                        new_authority: key_of_field(cx, value, accounts_struct),
+                    });
+                }
+            }
+            false
+        });
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for write in &self.writes {
+            let accounts_struct = match self.anchor_accounts.get(&write.accounts_struct) {
+                Some(accounts_struct) => accounts_struct,
+                None => continue,
+            };
+            if field_constraint_summary(accounts_struct, &write.account)
+                .map_or(true, |constraints| constraints.is_initialized)
+            {
+                continue;
+            }
+            let current_authorities = current_authorities(accounts_struct, write);
+            let account_member = format!("{}.{}", write.account, write.member);
+            if !current_authorities.is_empty() {
+                if current_authorities.iter().any(|field| is_signer(field)) {
+                    continue;
+                }
+                let notes = current_authorities
+                    .iter()
+                    .filter_map(|field| {
+                        local_field_def(cx, write.accounts_struct, &field.ident.to_string())
+                    })
+                    .collect::<Vec<_>>();
+                let help = format!(
+                    "declare the current `{}` as a `Signer`, or add the `signer` constraint",
+                    write.member
+                );
+                span_lint_hir_and_then(
+                    cx,
+                    UNSIGNED_AUTHORITY_CHANGE,
+                    write.hir_id,
+                    write.span,
+                    &format!(
+                        "`{account_member}` is overwritten without the signature of the current `{}`",
+                        write.member
+                    ),
+                    |diag| {
+                        for field_def in notes {
+                            diag.span_note(
+                                field_def.span,
+                                "the current authority is not required to sign",
+                            );
+                        }
+                        diag.help(help);
+                    },
+                );
+            } else if write
+                .new_authority
+                .as_ref()
+                .and_then(|new_authority| anchor_field(accounts_struct, new_authority))
+                .map_or(false, is_signer)
+            {
+                let help = format!(
+                    "add the current `{}` as a `Signer`, checked with `has_one = {}`",
+                    write.member, write.member
+                );
+                span_lint_hir_and_then(
+                    cx,
+                    UNSIGNED_AUTHORITY_CHANGE,
+                    write.hir_id,
+                    write.span,
+                    &format!(
+                        "`{account_member}` is overwritten by the new `{}` without checking the current `{}`",
+                        write.member, write.member
+                    ),
+                    |diag| {
+                        diag.note("only the new authority is required to sign");
+                        diag.help(help);
+                    },
+                );
+            }
+        }
+
+        sarif::write(cx, &[UNSIGNED_AUTHORITY_CHANGE]);
+    }
+}
+
+/// If `value` is the key of a field of the local struct `accounts_struct`, e.g.,
+/// `ctx.accounts.new_authority.key()` or `*ctx.accounts.new_authority.key`, return the name of
+/// the field
+fn key_of_field<'tcx>(
+    cx: &LateContext<'tcx>,
+    mut value: &'tcx Expr<'tcx>,
+    accounts_struct: DefId,
+) -> Option<String> {
+    while let ExprKind::Unary(UnOp::Deref, inner) = value.kind {
+        value = inner;
+    }
+    let account = match value.kind {
+        ExprKind::MethodCall(method, receiver, [], _) if method.ident.as_str() == "key" => receiver,
+        ExprKind::Field(object, ident) if ident.as_str() == "key" => object,
+        _ => return None,
+    };
+    local_struct_field(cx, account)
+        .filter(|(def_id, _)| *def_id == accounts_struct)
+        .map(|(_, name)| name)
+}
+
+/// Return the fields of `accounts_struct` holding the current authority of `write`, i.e., those
+/// which the account is checked against, or else the field named as the authority field
+fn current_authorities<'a>(
+    accounts_struct: &'a AccountsStruct,
+    write: &AuthorityWrite,
+) -> Vec<&'a Field> {
+    let account = match anchor_field(accounts_struct, &write.account) {
+        Some(account) => account,
+        None => return Vec::new(),
+    };
+    let checked = member_targets(account, &write.member)
+        .into_iter()
+        .filter_map(|target| match target {
+            Seed::Expr(name) => anchor_field(accounts_struct, &name),
+            Seed::Bytes(_) => None,
+        })
+        .collect::<Vec<_>>();
+    if checked.is_empty() {
+        anchor_field(accounts_struct, &write.member)
+            .into_iter()
+            .collect()
+    } else {
+        checked
+    }
+}
+
+fn is_signer(field: &Field) -> bool {
+    matches!(field.ty, FieldTy::Signer) || ConstraintSummary::new(&field.constraints).is_signer
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unsigned_authority_change_insecure {
+    use super::*;
+
+    pub fn set_authority(ctx: Context<SetAuthority>) -> Result<()> {
+        ctx.accounts.config.authority = ctx.accounts.new_authority.key();
+        Ok(())
+    }
+
+    pub fn set_admin(ctx: Context<SetAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.new_admin.key();
+        Ok(())
+    }
+
+    pub fn transfer_vault(ctx: Context<TransferVault>) -> Result<()> {
+        ctx.accounts.vault.owner = *ctx.accounts.new_owner.key;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    config: Account<'info, Config>,
+    /// CHECK: the current authority of the config
+    authority: UncheckedAccount<'info>,
+    new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAdmin<'info> {
+    #[account(mut)]
+    config: Account<'info, Config>,
+    new_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferVault<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    /// CHECK: the current owner of the vault
+    owner: AccountInfo<'info>,
+    new_owner: Signer<'info>,
+}
+
+#[account]
+pub struct Config {
+    authority: Pubkey,
+    admin: Pubkey,
+}
+
+#[account]
+pub struct Vault {
+    owner: Pubkey,
+    balance: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod unsigned_authority_change_secure {
+    use super::*;
+
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+        ctx.accounts.config.authority = ctx.accounts.payer.key();
+        Ok(())
+    }
+
+    pub fn set_authority(ctx: Context<SetAuthority>) -> Result<()> {
+        ctx.accounts.config.authority = ctx.accounts.new_authority.key();
+        Ok(())
+    }
+
+    pub fn set_admin(ctx: Context<SetAdmin>, new_admin: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = new_admin;
+        Ok(())
+    }
+
+    pub fn transfer_vault(ctx: Context<TransferVault>) -> Result<()> {
+        ctx.accounts.vault.owner = *ctx.accounts.new_owner.key;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = 8 + 32 + 32)]
+    config: Account<'info, Config>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAuthority<'info> {
+    #[account(mut, has_one = authority)]
+    config: Account<'info, Config>,
+    authority: Signer<'info>,
+    /// CHECK: the new authority is only stored
+    new_authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAdmin<'info> {
+    #[account(mut, constraint = config.admin == admin.key())]
+    config: Account<'info, Config>,
+    admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferVault<'info> {
+    #[account(mut, has_one = owner)]
+    vault: Account<'info, Vault>,
+    /// CHECK: the current owner of the vault, which signs
+    #[account(signer)]
+    owner: AccountInfo<'info>,
+    new_owner: Signer<'info>,
+}
+
+#[account]
+pub struct Config {
+    authority: Pubkey,
+    admin: Pubkey,
+}
+
+#[account]
+pub struct Vault {
+    owner: Pubkey,
+    balance: u64,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,292 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use anchor_syn::AccountsStruct;
+use clippy_utils::{fn_def_id, match_any_def_paths, SpanlessEq};
+use rustc_hir::{
+    def_id::{DefId, LocalDefId},
+    intravisit::FnKind,
+    Body, Expr, ExprKind, FnDecl, HirId, Item, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths::{self, anchor_wrapper, AnchorWrapper},
+    sarif,
+    utils::{
+        anchor_constraints::field_constraint_summary,
+        get_anchor_accounts_struct, is_expr_method_call, local_struct_field,
+        owners::{is_owner_checked, uses_given_field},
+        visit_expr_no_bodies,
+    },
+};
+use std::collections::HashMap;
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the accounts forwarded in the `accounts` slice of `invoke` and `invoke_signed`
+    /// which the caller never validated, i.e., whose owner is not accessed, whose key is not
+    /// compared, and whose `is_signer` is not read, and which are neither of an Anchor type
+    /// validating them, e.g., `Account<'info, T>` or `Signer<'info>`, nor fields with a constraint
+    /// validating them, e.g., `owner`, `address`, `seeds` or `signer`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// The callee of a CPI trusts the accounts it receives as much as the accounts of any other
+    /// instruction, but the caller may sign for them, e.g., with `invoke_signed`, or may pass
+    /// accounts it holds the authority of. `arbitrary_cpi` checks that the callee is the expected
+    /// program, but an attacker passing, e.g., their own token account or vault in place of the
+    /// expected one, makes the callee act on it with the authority of the caller.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Only the accounts listed in an array literal, e.g., `&[vault.clone(), user.clone()]`, are
+    /// considered, not those of a vector or of `remaining_accounts`. As for `missing_owner_check`,
+    /// each function is analyzed on its own, so an account checked by the caller of the function
+    /// is reported. The accounts which may legitimately be arbitrary, e.g., the recipient of a
+    /// transfer, are reported as well.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// pub fn withdraw(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    ///     let accounts_iter = &mut accounts.iter();
+    ///     let vault = next_account_info(accounts_iter)?;
+    ///     let destination = next_account_info(accounts_iter)?;
+    ///     let authority = next_account_info(accounts_iter)?;
+    ///     let ix = spl_token::instruction::transfer(
+    ///         &spl_token::ID, vault.key, destination.key, authority.key, &[], amount,
+    ///     )?;
+    ///     invoke_signed(&ix, &[vault.clone(), destination.clone(), authority.clone()], &[SEEDS])
+    /// }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// pub fn withdraw(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    ///     ...
+    ///     if *vault.key != expected_vault || vault.owner != &spl_token::ID {
+    ///         return Err(ProgramError::InvalidAccountData);
+    ///     }
+    ///     ...
+    /// }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_item:
+    ///
+    /// - record the Anchor `Accounts` structs
+    ///
+    /// check_fn:
+    ///
+    /// - for each call of `invoke` or `invoke_signed` whose `accounts` argument is an array literal,
+    ///   excluding the functions generated by macros
+    /// - for each element of the array, get the account, ignoring the borrows, the derefs, and the
+    ///   calls of `clone` and `to_account_info`, e.g., `vault` for `vault.clone()`
+    /// - ignore the accounts of an Anchor type which checks the owner or the key, or a signer
+    ///   (see `AnchorWrapper::checks_owner`)
+    /// - ignore the accounts whose `owner` is accessed, whose key is compared, or whose `is_signer`
+    ///   is read in the function (see `solana_lints::utils::owners`)
+    /// - if the account is a field of a struct, e.g., `ctx.accounts.vault`, record it, else report
+    ///   it
+    ///
+    /// check_crate_post:
+    ///
+    /// - for each recorded field of an Anchor `Accounts` struct, report it unless it has a
+    ///   constraint checking its owner or its key, or the `signer` constraint
+    pub UNVALIDATED_CPI_ACCOUNTS,
+    Warn,
+    "accounts forwarded to a CPI without a check of their owner, key or signature",
+    UnvalidatedCpiAccounts::default()
+}
+
+#[derive(Default)]
+struct UnvalidatedCpiAccounts {
+    anchor_accounts: HashMap<DefId, AccountsStruct>,
+    /// The unchecked fields forwarded to CPIs: the element of the `accounts` slice, the name of the
+    /// CPI function, the struct, and the name of the field
+    field_accounts: Vec<(HirId, Span, &'static str, DefId, String)>,
+}
+
+impl<'tcx> LateLintPass<'tcx> for UnvalidatedCpiAccounts {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if let Some(accounts_struct) = get_anchor_accounts_struct(cx, item) {
+            self.anchor_accounts
+                .insert(item.owner_id.to_def_id(), accounts_struct);
+        }
+    }
+
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(UNVALIDATED_CPI_ACCOUNTS, span) {
+            return;
+        }
+        let mut seen = Vec::<&Expr<'_>>::new();
+        for (invoke, elems) in cpi_account_lists(cx, body.value) {
+            for elem in elems {
+                let account = account_of(cx, elem);
+                let mut spanless_eq = SpanlessEq::new(cx);
+                if seen.iter().any(|seen| spanless_eq.eq_expr(seen, account))
+                    || is_validated(cx, body.value, account)
+                {
+                    continue;
+                }
+                seen.push(account);
+                if let Some((def_id, field_name)) = local_struct_field(cx, account) {
+                    self.field_accounts
+                        .push((elem.hir_id, elem.span, invoke, def_id, field_name));
+                } else {
+                    report(cx, elem.hir_id, elem.span, invoke, false);
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        for (hir_id, span, invoke, def_id, field_name) in &self.field_accounts {
+            let accounts_struct = self.anchor_accounts.get(def_id);
+            if accounts_struct
+                .and_then(|accounts_struct| field_constraint_summary(accounts_struct, field_name))
+                .map_or(false, |constraints| constraints.exempts_owner_check())
+            {
+                continue;
+            }
+            report(cx, *hir_id, *span, invoke, accounts_struct.is_some());
+        }
+
+        sarif::write(cx, &[UNVALIDATED_CPI_ACCOUNTS]);
+    }
+}
+
+fn report(cx: &LateContext<'_>, hir_id: HirId, span: Span, invoke: &str, is_anchor_field: bool) {
+    span_lint_hir_and_then(
+        cx,
+        UNVALIDATED_CPI_ACCOUNTS,
+        hir_id,
+        span,
+        &format!(
+            "this account is passed to `{invoke}` but there is no check on its owner, its key or its signature"
+        ),
+        |diag| {
+            if is_anchor_field {
+                diag.help(
+                    "add an `owner`, `address` or `seeds` constraint to the field, or use a type which validates the account, e.g., `Account<'info, T>`",
+                );
+            } else {
+                diag.help(
+                    "check the `owner` or the `key` of the account before the CPI, as the callee trusts the accounts it receives",
+                );
+            }
+        },
+    );
+}
+
+/// Return the calls of `invoke` and `invoke_signed` in `body` whose `accounts` argument is an
+/// array literal, with the name of the function and the elements of the array
+fn cpi_account_lists<'tcx>(
+    cx: &LateContext<'tcx>,
+    body: &'tcx Expr<'tcx>,
+) -> Vec<(&'static str, &'tcx [Expr<'tcx>])> {
+    let mut account_lists = Vec::new();
+    visit_expr_no_bodies(body, |expr| {
+        if is_skipped_expansion(UNVALIDATED_CPI_ACCOUNTS, expr.span) {
+            return false;
+        }
+        let args = match expr.kind {
+            ExprKind::Call(_, args) => args,
+            _ => return false,
+        };
+        let invoke = match fn_def_id(cx, expr).and_then(|def_id| {
+            match_any_def_paths(
+                cx,
+                def_id,
+                &[
+                    &paths::SOLANA_PROGRAM_INVOKE,
+                    &paths::SOLANA_PROGRAM_INVOKE_SIGNED,
+                ],
+            )
+        }) {
+            Some(0) => "invoke",
+            Some(_) => "invoke_signed",
+            None => return false,
+        };
+        let mut accounts = match args.get(1) {
+            Some(accounts) => accounts,
+            None => return false,
+        };
+        while let ExprKind::AddrOf(_, _, inner) = accounts.kind {
+            accounts = inner;
+        }
+        if let ExprKind::Array(elems) = accounts.kind {
+            account_lists.push((invoke, elems));
+        }
+        false
+    });
+    account_lists
+}
+
+/// Return the account `expr` forwards, ignoring the borrows, the derefs, and the calls of `clone`
+/// and `to_account_info`, e.g., `ctx.accounts.vault` for `ctx.accounts.vault.to_account_info()`
+fn account_of<'tcx>(cx: &LateContext<'tcx>, mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    loop {
+        if let ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) = expr.kind {
+            expr = inner;
+        } else if let Some(receiver) = is_expr_method_call(cx, expr, &paths::CORE_CLONE)
+            .or_else(|| is_expr_method_call(cx, expr, &paths::ANCHOR_LANG_TO_ACCOUNT_INFO))
+        {
+            expr = receiver;
+        } else {
+            return expr;
+        }
+    }
+}
+
+/// Return true if `account` is of an Anchor type which validates it, or if its owner, its key or
+/// its signature is checked in `scope`
+fn is_validated<'tcx>(
+    cx: &LateContext<'tcx>,
+    scope: &'tcx Expr<'tcx>,
+    account: &'tcx Expr<'tcx>,
+) -> bool {
+    let ty = cx.typeck_results().expr_ty(account).peel_refs();
+    anchor_wrapper(cx, ty).map_or(false, AnchorWrapper::checks_owner)
+        || is_owner_checked(cx, scope, account)
+        || visit_expr_no_bodies(scope, |expr| {
+            uses_given_field(cx, expr, account, "is_signer")
+        })
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::{invoke, invoke_signed},
+    pubkey,
+};
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+pub const STAKING_PROGRAM_ID: Pubkey = pubkey!("Stake11111111111111111111111111111111111111");
+
+#[program]
+pub mod unvalidated_cpi_accounts_insecure {
+    use super::*;
+
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        let ix = Instruction::new_with_bytes(
+            ctx.accounts.staking_program.key(),
+            &amount.to_le_bytes(),
+            vec![
+                AccountMeta::new(ctx.accounts.pool.key(), false),
+                AccountMeta::new(ctx.accounts.user.key(), true),
+            ],
+        );
+        invoke(
+            &ix,
+            &[
+                ctx.accounts.pool.to_account_info(),
+                ctx.accounts.user.to_account_info(),
+                ctx.accounts.staking_program.to_account_info(),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    /// CHECK: forwarded to the staking program
+    #[account(mut)]
+    pool: UncheckedAccount<'info>,
+    user: Signer<'info>,
+    /// CHECK: the staking program
+    #[account(address = STAKING_PROGRAM_ID)]
+    staking_program: UncheckedAccount<'info>,
+}
+
+pub fn release<'info>(
+    vault: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    staking_program: &AccountInfo<'info>,
+    bump: u8,
+) -> ProgramResult {
+    if staking_program.key != &STAKING_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let ix = Instruction::new_with_bytes(
+        *staking_program.key,
+        &[1],
+        vec![
+            AccountMeta::new(*vault.key, false),
+            AccountMeta::new(*destination.key, false),
+        ],
+    );
+    invoke_signed(
+        &ix,
+        &[vault.clone(), destination.clone(), staking_program.clone()],
+        &[&[b"vault", &[bump]]],
+    )
+}
+
+#[allow(dead_code)]
+fn main() {}
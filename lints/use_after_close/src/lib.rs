@@ -0,0 +1,369 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use clippy_utils::{path_to_local, source::snippet};
+use if_chain::if_chain;
+use rustc_hir::{
+    def_id::LocalDefId,
+    intravisit::{walk_block, walk_expr, FnKind, Visitor},
+    Block, Body, Expr, ExprKind, FnDecl, HirId, StmtKind, UnOp,
+};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::{Span, Symbol};
+use solana_lints::{
+    diagnostics::span_lint_hir_and_then,
+    expansion::is_skipped_expansion,
+    paths::{self, anchor_wrapper, AnchorWrapper},
+    sarif,
+    utils::{closed_account, is_expr_method_call, local_init, visit_expr_no_bodies},
+};
+use std::collections::HashSet;
+
+solana_lints::declare_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports the uses of the data or the lamports of an account which follow, in the same
+    /// block, a statement closing the account, i.e.:
+    ///
+    /// - assigning `0` to its lamports, e.g., `**account.lamports.borrow_mut() = 0`
+    /// - calling Anchor's `close` method, e.g., `ctx.accounts.vault.close(destination)`
+    ///
+    /// The uses are the reads and writes of its lamports, e.g., `account.lamports()` or
+    /// `**account.try_borrow_mut_lamports()? += fee`, the immutable borrows of its data, e.g.,
+    /// `account.try_borrow_data()`, the fields of an Anchor `Account`, e.g., `vault.amount`, and
+    /// the `load` and `load_mut` calls of an `AccountLoader`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A closed account is only garbage collected at the end of the transaction. An instruction
+    /// which goes on using the account after closing it acts on a state which should no longer
+    /// exist: crediting lamports back to the account makes it rent exempt again, so that it
+    /// survives with its data (a revival attack), and the values read from its data, e.g., a
+    /// balance, may be used once more.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_security_critical`, e.g., `#![deny(solana_security_critical)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// Only the closes which a statement performs unconditionally are considered, i.e., not those
+    /// in an `if`, a `match`, a loop or a closure, nor those performed by called functions or by
+    /// CPIs. The uses are looked for in the following statements of the same block, including
+    /// their nested blocks. An account is identified by a local variable followed by fields, e.g.,
+    /// `ctx.accounts.vault`, through borrows, `to_account_info()`, `clone()` and the local
+    /// variables initialized with them. The mutable borrows of the data are not reported, as
+    /// clearing the data is part of closing an account (see `insecure_account_close`). Anchor's
+    /// `close` constraint closes the account once the handler returns, so that the uses of such
+    /// an account in the handler are not reported.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    /// **destination.lamports.borrow_mut() += vault.lamports();
+    /// **vault.lamports.borrow_mut() = 0;
+    /// msg!("closed a vault of {} lamports", vault.lamports());
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    /// let lamports = vault.lamports();
+    /// **destination.lamports.borrow_mut() += lamports;
+    /// **vault.lamports.borrow_mut() = 0;
+    /// msg!("closed a vault of {} lamports", lamports);
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// check_fn:
+    ///
+    /// - for each function, excluding the functions generated by macros
+    /// - for each block of the function and each of its statements, find the accounts which the
+    ///   statement closes outside of conditionals, loops and closures
+    /// - for each such account, report its first use in the following statements of the block,
+    ///   and note the close
+    pub USE_AFTER_CLOSE,
+    Warn,
+    "uses of the data or the lamports of an account after it is closed"
+}
+
+impl<'tcx> LateLintPass<'tcx> for UseAfterClose {
+    fn check_fn(
+        &mut self,
+        cx: &LateContext<'tcx>,
+        _: FnKind<'tcx>,
+        _: &'tcx FnDecl<'tcx>,
+        body: &'tcx Body<'tcx>,
+        span: Span,
+        _: LocalDefId,
+    ) {
+        // exclude functions generated from macro expansions
+        if is_skipped_expansion(USE_AFTER_CLOSE, span) {
+            return;
+        }
+        let mut blocks = Blocks(Vec::new());
+        blocks.visit_expr(body.value);
+        let mut reported = HashSet::new();
+        for block in blocks.0 {
+            let statements = statement_exprs(block);
+            for (i, statement) in statements.iter().enumerate() {
+                for (account, close) in unconditional_closes(cx, statement) {
+                    if_chain! {
+                        if let Some(use_expr) = first_use(cx, &statements[i + 1..], &account);
+                        if reported.insert(use_expr.hir_id);
+                        then {
+                            report(cx, use_expr, close);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[USE_AFTER_CLOSE]);
+    }
+}
+
+fn report<'tcx>(cx: &LateContext<'tcx>, use_expr: &'tcx Expr<'tcx>, close: &Expr<'_>) {
+    let account = match used_account(cx, use_expr) {
+        Some(account) => account,
+        None => return,
+    };
+    span_lint_hir_and_then(
+        cx,
+        USE_AFTER_CLOSE,
+        use_expr.hir_id,
+        use_expr.span,
+        &format!(
+            "`{}` is used after it is closed",
+            snippet(cx, account.span, "..")
+        ),
+        |diag| {
+            diag.span_note(close.span, "the account is closed here");
+            diag.help("move the uses of the account before it is closed");
+        },
+    );
+}
+
+/// An account, as a local variable followed by fields, e.g., `ctx.accounts.vault`
+#[derive(PartialEq, Eq)]
+struct AccountPlace {
+    local: HirId,
+    fields: Vec<Symbol>,
+}
+
+/// The blocks of a body, excluding those of nested bodies, e.g., of closures
+struct Blocks<'tcx>(Vec<&'tcx Block<'tcx>>);
+
+impl<'tcx> Visitor<'tcx> for Blocks<'tcx> {
+    fn visit_block(&mut self, block: &'tcx Block<'tcx>) {
+        self.0.push(block);
+        walk_block(self, block);
+    }
+}
+
+/// Return the expressions of the statements of `block`, in order, including its trailing
+/// expression
+fn statement_exprs<'tcx>(block: &'tcx Block<'tcx>) -> Vec<&'tcx Expr<'tcx>> {
+    block
+        .stmts
+        .iter()
+        .filter_map(|stmt| match stmt.kind {
+            StmtKind::Local(local) => local.init,
+            StmtKind::Expr(expr) | StmtKind::Semi(expr) => Some(expr),
+            StmtKind::Item(_) => None,
+        })
+        .chain(block.expr)
+        .collect()
+}
+
+/// Return the accounts which `statement` closes outside of conditionals, loops and closures,
+/// along with the closing expressions
+fn unconditional_closes<'tcx>(
+    cx: &LateContext<'tcx>,
+    statement: &'tcx Expr<'tcx>,
+) -> Vec<(AccountPlace, &'tcx Expr<'tcx>)> {
+    let mut closes = Closes {
+        cx,
+        closes: Vec::new(),
+    };
+    closes.visit_expr(statement);
+    closes.closes
+}
+
+struct Closes<'cx, 'tcx> {
+    cx: &'cx LateContext<'tcx>,
+    closes: Vec<(AccountPlace, &'tcx Expr<'tcx>)>,
+}
+
+impl<'cx, 'tcx> Visitor<'tcx> for Closes<'cx, 'tcx> {
+    fn visit_expr(&mut self, expr: &'tcx Expr<'tcx>) {
+        match expr.kind {
+            // only the condition and the scrutinee are evaluated unconditionally, e.g., the
+            // scrutinee of the `match` which `x?` is desugared into
+            ExprKind::If(condition, _, _) => self.visit_expr(condition),
+            ExprKind::Match(scrutinee, _, _) => self.visit_expr(scrutinee),
+            ExprKind::Loop(..) | ExprKind::Closure(..) => {}
+            _ => {
+                if_chain! {
+                    if !is_skipped_expansion(USE_AFTER_CLOSE, expr.span);
+                    if let Some(account) = closed_account(self.cx, expr).or_else(|| {
+                        is_expr_method_call(self.cx, expr, &paths::ANCHOR_LANG_ACCOUNTS_CLOSE)
+                    });
+                    if let Some(place) = account_place(self.cx, account);
+                    then {
+                        self.closes.push((place, expr));
+                    }
+                }
+                walk_expr(self, expr);
+            }
+        }
+    }
+}
+
+/// Return the first use of the data or the lamports of `account` in `statements`
+fn first_use<'tcx>(
+    cx: &LateContext<'tcx>,
+    statements: &[&'tcx Expr<'tcx>],
+    account: &AccountPlace,
+) -> Option<&'tcx Expr<'tcx>> {
+    statements.iter().find_map(|statement| {
+        visit_expr_no_bodies(statement, |expr| {
+            if_chain! {
+                if !is_skipped_expansion(USE_AFTER_CLOSE, expr.span);
+                if let Some(used) = used_account(cx, expr);
+                if account_place(cx, used).as_ref() == Some(account);
+                then {
+                    Some(expr)
+                } else {
+                    None
+                }
+            }
+        })
+    })
+}
+
+/// If `expr` uses the data or the lamports of an account, return the account:
+///
+/// - `x.lamports()`, `x.try_lamports()`, `x.try_borrow_lamports()`, `x.try_borrow_mut_lamports()`,
+///   `x.lamports.borrow()`, `x.lamports.borrow_mut()`, `x.try_borrow_data()` or `x.data.borrow()`
+///   for an `AccountInfo` `x`
+/// - `x.get_lamports()`, `x.add_lamports(..)` or `x.sub_lamports(..)` for an Anchor account `x`
+/// - `x.field` for an Anchor `Account` `x`
+/// - `x.load()` or `x.load_mut()` for an `AccountLoader` `x`
+fn used_account<'tcx>(cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) -> Option<&'tcx Expr<'tcx>> {
+    match expr.kind {
+        ExprKind::MethodCall(method, receiver, _, _) => {
+            let receiver_ty = cx.typeck_results().expr_ty_adjusted(receiver).peel_refs();
+            if paths::is_account_info_ty(cx, receiver_ty)
+                && [
+                    "lamports",
+                    "try_lamports",
+                    "try_borrow_lamports",
+                    "try_borrow_mut_lamports",
+                    "try_borrow_data",
+                ]
+                .contains(&method.ident.as_str())
+            {
+                return Some(receiver);
+            }
+            let anchor_uses: [&[&str]; 5] = [
+                &paths::ANCHOR_LANG_GET_LAMPORTS,
+                &paths::ANCHOR_LANG_ADD_LAMPORTS,
+                &paths::ANCHOR_LANG_SUB_LAMPORTS,
+                &paths::ANCHOR_LANG_LOAD,
+                &paths::ANCHOR_LANG_LOAD_MUT,
+            ];
+            if anchor_uses
+                .iter()
+                .any(|path| is_expr_method_call(cx, expr, path).is_some())
+            {
+                return Some(receiver);
+            }
+            if_chain! {
+                if let ExprKind::Field(account, field) = receiver.kind;
+                let borrows = is_expr_method_call(cx, expr, &paths::CORE_CELL_BORROW).is_some();
+                let borrows_mut =
+                    is_expr_method_call(cx, expr, &paths::CORE_CELL_BORROW_MUT).is_some();
+                // the mutable borrows of the data are how the data of a closed account is cleared
+                if match field.as_str() {
+                    "lamports" => borrows || borrows_mut,
+                    "data" => borrows,
+                    _ => false,
+                };
+                let account_ty = cx.typeck_results().expr_ty_adjusted(account).peel_refs();
+                if paths::is_account_info_ty(cx, account_ty);
+                then {
+                    Some(account)
+                } else {
+                    None
+                }
+            }
+        }
+        ExprKind::Field(object, _) => {
+            let object_ty = cx.typeck_results().expr_ty(object).peel_refs();
+            if anchor_wrapper(cx, object_ty) == Some(AnchorWrapper::Account) {
+                Some(object)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Return the place of `account`, following borrows, derefs, `to_account_info()`, `clone()` and
+/// the local variables initialized with accounts
+fn account_place<'tcx>(
+    cx: &LateContext<'tcx>,
+    mut account: &'tcx Expr<'tcx>,
+) -> Option<AccountPlace> {
+    let mut fields = Vec::new();
+    loop {
+        account = match account.kind {
+            ExprKind::AddrOf(_, _, inner) | ExprKind::Unary(UnOp::Deref, inner) => inner,
+            ExprKind::MethodCall(method, receiver, [], _)
+                if ["to_account_info", "clone"].contains(&method.ident.as_str()) =>
+            {
+                receiver
+            }
+            ExprKind::Field(object, field) => {
+                fields.push(field.name);
+                object
+            }
+            _ => {
+                let local = path_to_local(account)?;
+                match local_init(cx, local).and_then(|init| account_place(cx, init)) {
+                    Some(mut place) => {
+                        place.fields.extend(fields.into_iter().rev());
+                        return Some(place);
+                    }
+                    None => {
+                        fields.reverse();
+                        return Some(AccountPlace { local, fields });
+                    }
+                }
+            }
+        };
+    }
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
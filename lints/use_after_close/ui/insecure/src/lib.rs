@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod use_after_close_insecure {
+    use super::*;
+
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        let vault = ctx.accounts.vault.to_account_info();
+        let destination = ctx.accounts.destination.to_account_info();
+        **destination.lamports.borrow_mut() += vault.lamports();
+        **vault.lamports.borrow_mut() = 0;
+        let remaining = vault.lamports();
+        msg!("closed a vault of {} lamports", remaining);
+        Ok(())
+    }
+
+    pub fn close_and_record(ctx: Context<CloseVault>) -> Result<()> {
+        let destination = ctx.accounts.destination.to_account_info();
+        ctx.accounts.vault.close(destination)?;
+        ctx.accounts.vault.closed = true;
+        Ok(())
+    }
+}
+
+pub fn close_with_fee(account: &AccountInfo, destination: &AccountInfo, fee: u64) -> ProgramResult {
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **destination.try_borrow_mut_lamports()? += lamports - fee;
+    **account.try_borrow_mut_lamports()? += fee;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    #[account(mut)]
+    destination: SystemAccount<'info>,
+}
+
+#[account]
+pub struct Vault {
+    amount: u64,
+    closed: bool,
+}
+
+#[allow(dead_code)]
+fn main() {}
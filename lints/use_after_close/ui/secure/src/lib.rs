@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[program]
+pub mod use_after_close_secure {
+    use super::*;
+
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        let vault = ctx.accounts.vault.to_account_info();
+        let destination = ctx.accounts.destination.to_account_info();
+        let remaining = vault.lamports();
+        **destination.lamports.borrow_mut() += remaining;
+        **vault.lamports.borrow_mut() = 0;
+        let mut data = vault.try_borrow_mut_data()?;
+        data.fill(0);
+        msg!("closed a vault of {} lamports", remaining);
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<CloseVault>, amount: u64) -> Result<()> {
+        if ctx.accounts.vault.amount == amount {
+            let destination = ctx.accounts.destination.to_account_info();
+            ctx.accounts.vault.close(destination)?;
+            return Ok(());
+        }
+        ctx.accounts.vault.amount -= amount;
+        Ok(())
+    }
+
+    pub fn close_with_constraint(ctx: Context<CloseWithConstraint>) -> Result<()> {
+        ctx.accounts.vault.closed = true;
+        Ok(())
+    }
+}
+
+pub fn close_with_fee(account: &AccountInfo, destination: &AccountInfo, fee: u64) -> ProgramResult {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? += lamports - fee;
+    **account.try_borrow_mut_lamports()? = fee;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut)]
+    vault: Account<'info, Vault>,
+    #[account(mut)]
+    destination: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseWithConstraint<'info> {
+    #[account(mut, close = destination)]
+    vault: Account<'info, Vault>,
+    #[account(mut)]
+    destination: SystemAccount<'info>,
+}
+
+#[account]
+pub struct Vault {
+    amount: u64,
+    closed: bool,
+}
+
+#[allow(dead_code)]
+fn main() {}
@@ -0,0 +1,319 @@
+#![feature(rustc_private)]
+#![warn(unused_extern_crates)]
+
+extern crate rustc_ast;
+extern crate rustc_hir;
+extern crate rustc_span;
+
+use if_chain::if_chain;
+use rustc_ast::ast::LitKind;
+use rustc_hir::{BinOpKind, Expr, ExprKind, HirId, Item, ItemKind, Local, PatKind, QPath};
+use rustc_lint::{LateContext, LateLintPass};
+use rustc_span::Span;
+use serde::Deserialize;
+use solana_lints::{diagnostics::span_lint_hir_and_then, expansion::is_skipped_expansion, sarif};
+
+solana_lints::impl_late_lint! {
+    /// **What it does:**
+    ///
+    /// Reports multisig-style thresholds which are ineffective:
+    ///
+    /// - a threshold set to the literal `0` or `1`, e.g., `multisig.threshold = 1;`,
+    ///   `const THRESHOLD: u8 = 1;`, or `Multisig { threshold: 0, .. }`
+    /// - a threshold compared with the size of the configured signer set, e.g.,
+    ///   `multisig.owners.len() >= multisig.threshold`, instead of the number of valid signatures
+    ///
+    /// The lint is allowed by default. It is intended for reviews of governance and multisig programs
+    /// and can be enabled with `#![warn(weak_multisig_threshold)]`.
+    ///
+    /// **Why is this bad?**
+    ///
+    /// A threshold of `1` lets any single signer approve an action, and a threshold of `0` does not
+    /// require any signature. Comparing the threshold with the number of configured signers instead of
+    /// the number of signatures is satisfied by every valid configuration. In all of these cases, the
+    /// multisig does not provide the protection it appears to provide.
+    ///
+    /// **Works on:**
+    ///
+    /// - [x] Anchor
+    /// - [x] Non Anchor
+    ///
+    /// **Group:**
+    ///
+    /// `solana_best_practices`, e.g., `#![warn(solana_best_practices)]`
+    ///
+    /// **Known problems:**
+    ///
+    /// The lint recognizes thresholds and signer sets by name. Thresholds computed at runtime are not
+    /// checked, and a threshold of `1` may be intended, e.g., for a multisig with a single owner used
+    /// during testing.
+    ///
+    /// **Configuration:**
+    ///
+    /// The names can be configured in `dylint.toml`:
+    ///
+    /// ```toml
+    /// [weak_multisig_threshold]
+    /// threshold_names = ["threshold", "min_signers", "required_signers"]
+    /// signer_set_names = ["signers", "owners", "members"]
+    /// ```
+    ///
+    /// A name matches an identifier if the identifier, in lower case, is equal to the name or ends with
+    /// `_` followed by the name, e.g., `threshold` matches `MULTISIG_THRESHOLD`.
+    ///
+    /// **Example:**
+    ///
+    /// ```rust
+    ///     multisig.threshold = 1;
+    ///     ...
+    ///     if multisig.owners.len() < multisig.threshold as usize {
+    ///         return Err(ProgramError::MissingRequiredSignature);
+    ///     }
+    /// ```
+    ///
+    /// Use instead:
+    ///
+    /// ```rust
+    ///     multisig.threshold = threshold; // validated to be greater than 1
+    ///     ...
+    ///     if signatures < multisig.threshold as usize {
+    ///         return Err(ProgramError::MissingRequiredSignature);
+    ///     }
+    /// ```
+    ///
+    /// **How the lint is implemented:**
+    ///
+    /// - For every `const`/`static` item and `let` statement whose name is a threshold name
+    ///   - If the value is the literal `0` or `1`, report the value
+    /// - For every expression, excluding the expressions in macro expansions
+    ///   - If the expression is a struct expression and a field with a threshold name is
+    ///     initialized with the literal `0` or `1`, report the field value
+    ///   - If the expression assigns the literal `0` or `1` to a field with a threshold name,
+    ///     report the assignment
+    ///   - If the expression is a comparison (`<`, `<=`, `>`, `>=`) of a value with a threshold name
+    ///     and `x.len()` where `x` has a signer set name, report the comparison
+    pub WEAK_MULTISIG_THRESHOLD,
+    Allow,
+    "multisig threshold which a single signer can satisfy",
+    WeakMultisigThreshold::new()
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+struct Config {
+    threshold_names: Vec<String>,
+    signer_set_names: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            threshold_names: vec![
+                "threshold".to_string(),
+                "min_signers".to_string(),
+                "required_signers".to_string(),
+            ],
+            signer_set_names: vec![
+                "signers".to_string(),
+                "owners".to_string(),
+                "members".to_string(),
+            ],
+        }
+    }
+}
+
+struct WeakMultisigThreshold {
+    config: Config,
+}
+
+impl WeakMultisigThreshold {
+    pub fn new() -> Self {
+        Self {
+            config: dylint_linting::config_or_default(env!("CARGO_PKG_NAME")),
+        }
+    }
+
+    fn is_threshold_name(&self, name: &str) -> bool {
+        matches_any_name(name, &self.config.threshold_names)
+    }
+
+    fn is_signer_set_name(&self, name: &str) -> bool {
+        matches_any_name(name, &self.config.signer_set_names)
+    }
+
+    /// Return true if `expr` accesses a value with a threshold name, ignoring casts: `x.threshold as usize`
+    fn threshold_access(&self, expr: &Expr<'_>) -> bool {
+        let expr = peel_casts(expr);
+        match expr.kind {
+            ExprKind::Field(_, field_name) => self.is_threshold_name(field_name.as_str()),
+            ExprKind::Path(QPath::Resolved(None, path)) => {
+                path.segments.last().map_or(false, |segment| {
+                    self.is_threshold_name(segment.ident.as_str())
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// Return true if `expr` is `x.len()` where `x` is a field or a variable with a signer set name
+    fn is_signer_set_len(&self, expr: &Expr<'_>) -> bool {
+        let expr = peel_casts(expr);
+        if_chain! {
+            if let ExprKind::MethodCall(method_name, recv, [], _) = expr.kind;
+            if method_name.ident.as_str() == "len";
+            then {
+                match recv.kind {
+                    ExprKind::Field(_, field_name) => self.is_signer_set_name(field_name.as_str()),
+                    ExprKind::Path(QPath::Resolved(None, path)) => path
+                        .segments
+                        .last()
+                        .map_or(false, |segment| self.is_signer_set_name(segment.ident.as_str())),
+                    _ => false,
+                }
+            } else {
+                false
+            }
+        }
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for WeakMultisigThreshold {
+    fn check_item(&mut self, cx: &LateContext<'tcx>, item: &'tcx Item<'tcx>) {
+        if_chain! {
+            if !item.span.from_expansion();
+            if let ItemKind::Const(_, _, body_id) | ItemKind::Static(_, _, body_id) = item.kind;
+            if self.is_threshold_name(item.ident.as_str());
+            let value = cx.tcx.hir().body(body_id).value;
+            if let Some(threshold) = small_int_literal(value);
+            then {
+                report_threshold(cx, item.hir_id(), value.span, threshold);
+            }
+        }
+    }
+
+    fn check_local(&mut self, cx: &LateContext<'tcx>, local: &'tcx Local<'tcx>) {
+        if_chain! {
+            if !is_skipped_expansion(WEAK_MULTISIG_THRESHOLD, local.span);
+            if let PatKind::Binding(_, _, ident, None) = local.pat.kind;
+            if self.is_threshold_name(ident.as_str());
+            if let Some(init) = local.init;
+            if let Some(threshold) = small_int_literal(init);
+            then {
+                report_threshold(cx, local.hir_id, init.span, threshold);
+            }
+        }
+    }
+
+    fn check_expr(&mut self, cx: &LateContext<'tcx>, expr: &'tcx Expr<'tcx>) {
+        if is_skipped_expansion(WEAK_MULTISIG_THRESHOLD, expr.span) {
+            return;
+        }
+        match expr.kind {
+            // `Multisig { threshold: 1, .. }`
+            ExprKind::Struct(_, fields, _) => {
+                for field in fields {
+                    if_chain! {
+                        if self.is_threshold_name(field.ident.as_str());
+                        if let Some(threshold) = small_int_literal(field.expr);
+                        then {
+                            report_threshold(cx, field.expr.hir_id, field.expr.span, threshold);
+                        }
+                    }
+                }
+            }
+            // `multisig.threshold = 1`
+            ExprKind::Assign(place, value, _) => {
+                if_chain! {
+                    if let ExprKind::Field(_, field_name) = place.kind;
+                    if self.is_threshold_name(field_name.as_str());
+                    if let Some(threshold) = small_int_literal(value);
+                    then {
+                        report_threshold(cx, expr.hir_id, expr.span, threshold);
+                    }
+                }
+            }
+            // `multisig.owners.len() >= multisig.threshold`
+            ExprKind::Binary(op, lhs, rhs) => {
+                if_chain! {
+                    if matches!(
+                        op.node,
+                        BinOpKind::Lt | BinOpKind::Le | BinOpKind::Gt | BinOpKind::Ge
+                    );
+                    if (self.threshold_access(lhs) && self.is_signer_set_len(rhs))
+                        || (self.is_signer_set_len(lhs) && self.threshold_access(rhs));
+                    then {
+                        span_lint_hir_and_then(
+                            cx,
+                            WEAK_MULTISIG_THRESHOLD,
+                            expr.hir_id,
+                            expr.span,
+                            "the multisig threshold is compared with the number of configured signers",
+                            |diag| {
+                                diag.help("compare the threshold with the number of valid signatures instead");
+                            },
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_crate_post(&mut self, cx: &LateContext<'tcx>) {
+        sarif::write(cx, &[WEAK_MULTISIG_THRESHOLD]);
+    }
+}
+
+/// Return true if `name`, in lower case, is equal to one of `names` or ends with `_` followed by one of `names`
+fn matches_any_name(name: &str, names: &[String]) -> bool {
+    let name = name.to_lowercase();
+    names.iter().any(|candidate| {
+        name == *candidate
+            || name
+                .strip_suffix(candidate.as_str())
+                .map_or(false, |prefix| prefix.ends_with('_'))
+    })
+}
+
+/// Return the value if `expr` is the integer literal `0` or `1`, ignoring casts
+fn small_int_literal(expr: &Expr<'_>) -> Option<u128> {
+    if_chain! {
+        if let ExprKind::Lit(lit) = peel_casts(expr).kind;
+        if let LitKind::Int(value, _) = lit.node;
+        if value <= 1;
+        then {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+/// Remove the casts around the expression: `x as usize` => `x`
+fn peel_casts<'tcx>(mut expr: &'tcx Expr<'tcx>) -> &'tcx Expr<'tcx> {
+    while let ExprKind::Cast(inner, _) = expr.kind {
+        expr = inner;
+    }
+    expr
+}
+
+fn report_threshold(cx: &LateContext<'_>, hir_id: HirId, span: Span, threshold: u128) {
+    let msg = if threshold == 0 {
+        "multisig threshold of `0` does not require any signature"
+    } else {
+        "multisig threshold of `1` lets a single signer approve"
+    };
+    span_lint_hir_and_then(cx, WEAK_MULTISIG_THRESHOLD, hir_id, span, msg, |diag| {
+        diag.help("require the signatures of more than one signer");
+    });
+}
+
+#[test]
+fn insecure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "insecure");
+}
+
+#[test]
+fn secure() {
+    dylint_testing::ui_test_example(env!("CARGO_PKG_NAME"), "secure");
+}
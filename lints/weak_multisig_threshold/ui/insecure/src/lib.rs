@@ -0,0 +1,60 @@
+#![warn(weak_multisig_threshold)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const DEFAULT_THRESHOLD: u8 = 1;
+
+#[program]
+pub mod weak_multisig_threshold_insecure {
+    use super::*;
+
+    pub fn create_multisig(ctx: Context<CreateMultisig>, owners: Vec<Pubkey>) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.owners = owners;
+        multisig.threshold = 1;
+        Ok(())
+    }
+
+    pub fn reset_multisig(ctx: Context<CreateMultisig>) -> Result<()> {
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.set_inner(Multisig {
+            owners: vec![ctx.accounts.payer.key()],
+            threshold: 0,
+        });
+        Ok(())
+    }
+
+    pub fn execute(ctx: Context<Execute>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        if multisig.owners.len() < multisig.threshold as usize {
+            return Err(ProgramError::MissingRequiredSignature.into());
+        }
+        msg!("executed with default threshold {}", DEFAULT_THRESHOLD);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(init, payer = payer, space = 8 + 4 + 32 * 10 + 1)]
+    multisig: Account<'info, Multisig>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    multisig: Account<'info, Multisig>,
+}
+
+#[account]
+pub struct Multisig {
+    owners: Vec<Pubkey>,
+    threshold: u8,
+}
+
+#[allow(dead_code)]
+fn main() {}
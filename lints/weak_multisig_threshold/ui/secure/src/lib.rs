@@ -0,0 +1,62 @@
+#![warn(weak_multisig_threshold)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+const MIN_THRESHOLD: u8 = 2;
+
+#[program]
+pub mod weak_multisig_threshold_secure {
+    use super::*;
+
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        if threshold < MIN_THRESHOLD || usize::from(threshold) > owners.len() {
+            return Err(ProgramError::InvalidArgument.into());
+        }
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.owners = owners;
+        multisig.threshold = threshold;
+        Ok(())
+    }
+
+    pub fn execute(ctx: Context<Execute>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let signatures = ctx
+            .remaining_accounts
+            .iter()
+            .filter(|account| account.is_signer && multisig.owners.contains(account.key))
+            .count();
+        if signatures < multisig.threshold as usize {
+            return Err(ProgramError::MissingRequiredSignature.into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CreateMultisig<'info> {
+    #[account(init, payer = payer, space = 8 + 4 + 32 * 10 + 1)]
+    multisig: Account<'info, Multisig>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    multisig: Account<'info, Multisig>,
+}
+
+#[account]
+pub struct Multisig {
+    owners: Vec<Pubkey>,
+    threshold: u8,
+}
+
+#[allow(dead_code)]
+fn main() {}
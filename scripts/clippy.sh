@@ -13,7 +13,7 @@ WORKSPACE="$(realpath "$SCRIPTS"/..)"
 
 cd "$WORKSPACE"
 
-for X in . lints/*; do
+for X in . crate/mir_probe crate/paths_probe lints/*; do
     pushd "$X"
     # smoelius: `--all-targets` can't be used here because the lint examples would fail.
     cargo clippy --workspace --tests -- \
@@ -27,6 +27,23 @@ for LIBRARY in *; do
         sed -n 's,^[[:space:]]*///\([[:space:]]\(.*\)\)\?$,\2,;T;p'
     ) > README.md
 
+    # The references of the lint, from the table its diagnostics link to as well
+    REFERENCES="$(
+        awk -v lint="$LIBRARY" -F '"' '
+            /^ *lint: "/ { current = $2 }
+            /^ *title: "/ { title = $2 }
+            /^ *url: "/ { if (current == lint) print "- [" title "](" $2 ")" }
+        ' "$WORKSPACE"/crate/src/references.rs
+    )"
+    if [[ -n "$REFERENCES" ]]; then
+        (
+            echo
+            echo '**References:**'
+            echo
+            echo "$REFERENCES"
+        ) >> README.md
+    fi
+
     # prettier --write README.md
 
     popd >/dev/null
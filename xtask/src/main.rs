@@ -0,0 +1,537 @@
+//! Development tasks for the repository. Run with `cargo xtask <task>`.
+//!
+//! `cargo xtask test [--jobs N] [LIBRARY]...` runs the tests of the libraries in `lints` (all of
+//! them if none are named):
+//!
+//! 1. The libraries' tests are built one library at a time, using a target directory shared by all
+//!    of the libraries (`target/lints`). The dependencies that the libraries have in common, e.g.,
+//!    `anchor-lang` and `solana-program`, are built only once.
+//! 2. Each test, e.g., a ui test, is run in its own `cargo test` process, and up to `N` of those
+//!    processes run in parallel. Each process gets its own temporary directory, so that the ui
+//!    tests do not share their outputs.
+//!
+//! `cargo xtask e2e [--bless]` checks the fixture in `crate/e2e` with all of the libraries loaded at
+//! once, as `cargo dylint --all` does for users:
+//!
+//! 1. Each library is built with `cargo build`, in the same target directory as above.
+//! 2. The fixture is checked with `cargo dylint`, passing every library with `--lib-path`.
+//! 3. The findings are compared with `crate/e2e/expected.txt`, one finding per line, in the form
+//!    `path:line:column: lint: message`. `--bless` overwrites the file with the findings instead.
+//!
+//! The check fails if `cargo dylint` fails, e.g., because a lint panics, or if the findings differ,
+//! e.g., because a library suppresses the findings of another one. It requires `cargo-dylint`.
+//!
+//! `cargo xtask metamorphic [--cases N] [--seed S] [LIBRARY]...` checks `N` random programs and
+//! variants of them with the libraries named (some of the libraries reporting missing checks if
+//! none are named), and fails if adding a constraint introduces a finding, or if removing a check
+//! removes one. See `xtask/src/metamorphic.rs`.
+//!
+//! The ui tests are built against the versions of `anchor-lang`, `anchor-spl` and `solana-program`
+//! in the libraries' manifests. To build them against other versions, set
+//! `SOLANA_LINTS_FIXTURE_VERSIONS`, e.g., `SOLANA_LINTS_FIXTURE_VERSIONS=anchor=0.31,solana=2.1`.
+//! `cargo xtask test` then copies each library to `target/lints/versions/{label}/{library}`, where
+//! `label` names the version set, e.g., `anchor-0.31-solana-2.1`, and in the copy:
+//!
+//! - replaces the versions of the fixtures' dependencies, i.e., the `[dev-dependencies]` of the
+//!   library and the `[dependencies]` of each ui directory;
+//! - replaces `src/lib.stderr` of each ui directory with `src/lib.{label}.stderr`, if it exists.
+//!   Such a file is only needed when the diagnostics differ with the version set.
+//!
+//! The libraries' lock files are not copied, so that the versions are resolved anew.
+
+mod metamorphic;
+
+use serde_json::Value;
+use std::{
+    env,
+    fs::{copy, create_dir_all, read_dir, read_to_string, remove_dir_all, write},
+    path::{Path, PathBuf},
+    process::{exit, Command, Output},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+const USAGE: &str = "usage: cargo xtask test [--jobs N] [LIBRARY]...
+       cargo xtask e2e [--bless]
+       cargo xtask metamorphic [--cases N] [--seed S] [LIBRARY]...";
+
+/// The environment variable selecting the versions of the fixtures' dependencies
+const FIXTURE_VERSIONS: &str = "SOLANA_LINTS_FIXTURE_VERSIONS";
+
+/// The fixtures' dependencies whose versions are selected by each framework's version
+const FRAMEWORKS: &[(&str, &[&str])] = &[
+    ("anchor", &["anchor-lang", "anchor-spl"]),
+    ("solana", &["solana-program"]),
+];
+
+struct Test {
+    library: String,
+    path: PathBuf,
+    name: String,
+}
+
+/// The versions of the fixtures' dependencies selected with `SOLANA_LINTS_FIXTURE_VERSIONS`
+struct FixtureVersions {
+    /// The name of the version set, e.g., `anchor-0.31-solana-2.1`
+    label: String,
+    /// The version of each dependency, e.g., `("anchor-lang", "0.31")`
+    dependencies: Vec<(&'static str, String)>,
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+
+    match args.next().as_deref() {
+        Some("test") => test(args),
+        Some("e2e") => e2e(args),
+        Some("metamorphic") => metamorphic::metamorphic(args),
+        _ => fail(USAGE),
+    }
+}
+
+fn test(mut args: impl Iterator<Item = String>) {
+    let mut jobs = thread::available_parallelism().map_or(1, usize::from);
+    let mut libraries = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--jobs" || arg == "-j" {
+            jobs = args
+                .next()
+                .and_then(|value| value.parse().ok())
+                .filter(|&value| value > 0)
+                .unwrap_or_else(|| fail(USAGE));
+        } else if arg.starts_with('-') {
+            fail(USAGE);
+        } else {
+            libraries.push(arg);
+        }
+    }
+
+    let root = root();
+    let target_dir = root.join("target").join("lints");
+
+    let mut paths = library_paths(&root, &libraries);
+
+    if let Some(versions) = fixture_versions() {
+        eprintln!("Using the fixture versions {}", versions.label);
+        paths = paths
+            .iter()
+            .map(|path| stage_library(path, &root, &target_dir, &versions))
+            .collect();
+    }
+
+    for path in &paths {
+        eprintln!("Building tests of {}", library_name(path));
+        let status = cargo(path, &target_dir)
+            .args(["test", "--no-run"])
+            .status()
+            .unwrap();
+        if !status.success() {
+            fail(&format!("failed to build tests of {}", library_name(path)));
+        }
+    }
+
+    let tests = paths
+        .iter()
+        .flat_map(|path| list_tests(path, &target_dir))
+        .collect::<Vec<_>>();
+
+    let failures = run_tests(&tests, &target_dir, jobs);
+
+    for (test, output) in &failures {
+        eprintln!("\n---- {}::{} ----", test.library, test.name);
+        eprintln!("{}", String::from_utf8_lossy(&output.stdout));
+        eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    eprintln!(
+        "\n{} passed; {} failed",
+        tests.len() - failures.len(),
+        failures.len()
+    );
+
+    if !failures.is_empty() {
+        exit(1);
+    }
+}
+
+fn e2e(mut args: impl Iterator<Item = String>) {
+    let bless = match args.next().as_deref() {
+        None => false,
+        Some("--bless") => true,
+        Some(_) => fail(USAGE),
+    };
+    if args.next().is_some() {
+        fail(USAGE);
+    }
+
+    let root = root();
+    let target_dir = root.join("target").join("lints");
+    let fixture = root.join("crate").join("e2e");
+    let expected_path = fixture.join("expected.txt");
+
+    let paths = library_paths(&root, &[]);
+
+    let mut command = Command::new("cargo");
+    command
+        .current_dir(&fixture)
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .arg("dylint");
+    for file in build_libraries(&paths, &target_dir) {
+        command.arg("--lib-path").arg(file);
+    }
+
+    eprintln!("Checking {}", fixture.display());
+    let output = command
+        .args(["--", "--message-format=json"])
+        .output()
+        .unwrap();
+    if !output.status.success() {
+        fail(&format!(
+            "failed to check {}:\n{}",
+            fixture.display(),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let findings = findings(&String::from_utf8_lossy(&output.stdout));
+
+    if bless {
+        write(&expected_path, findings).unwrap();
+        return;
+    }
+
+    let expected = read_to_string(&expected_path).unwrap();
+    if findings != expected {
+        fail(&format!(
+            "the findings differ from {}:\n--- expected\n{expected}--- found\n{findings}",
+            expected_path.display()
+        ));
+    }
+}
+
+/// Build each library at `paths` with `cargo build`, and return the paths of the built libraries
+fn build_libraries(paths: &[PathBuf], target_dir: &Path) -> Vec<PathBuf> {
+    paths
+        .iter()
+        .map(|path| {
+            eprintln!("Building {}", library_name(path));
+            let status = cargo(path, target_dir).arg("build").status().unwrap();
+            if !status.success() {
+                fail(&format!("failed to build {}", library_name(path)));
+            }
+            library_file(path, target_dir)
+        })
+        .collect()
+}
+
+/// Return the findings in the JSON messages output by `cargo check --message-format=json`, one per
+/// line, sorted
+fn findings(stdout: &str) -> String {
+    let mut findings = stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|message| message["reason"] == "compiler-message")
+        .filter_map(|message| {
+            let message = &message["message"];
+            let level = message["level"].as_str()?;
+            let code = message["code"]["code"].as_str().unwrap_or(level);
+            let span = message["spans"]
+                .as_array()?
+                .iter()
+                .find(|span| span["is_primary"] == true)?;
+            Some(format!(
+                "{}:{}:{}: {code}: {}\n",
+                span["file_name"].as_str()?,
+                span["line_start"],
+                span["column_start"],
+                message["message"].as_str()?
+            ))
+        })
+        .collect::<Vec<_>>();
+    findings.sort();
+    findings.concat()
+}
+
+/// Return the path of the library built from `path`. `dylint-link` copies the library to a file
+/// whose name includes the toolchain, e.g., `libmissing_owner_check@nightly-2023-12-28.so`.
+fn library_file(path: &Path, target_dir: &Path) -> PathBuf {
+    let prefix = format!("{}{}@", env::consts::DLL_PREFIX, library_name(path));
+    read_dir(target_dir.join("debug"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|file| {
+            file.file_name().map_or(false, |name| {
+                let name = name.to_string_lossy();
+                name.starts_with(&prefix) && name.ends_with(env::consts::DLL_SUFFIX)
+            })
+        })
+        .unwrap_or_else(|| {
+            fail(&format!(
+                "cannot find the library of {}",
+                library_name(path)
+            ))
+        })
+}
+
+/// Parse `SOLANA_LINTS_FIXTURE_VERSIONS`, e.g., `anchor=0.31,solana=2.1`, if it is set
+fn fixture_versions() -> Option<FixtureVersions> {
+    let value = env::var(FIXTURE_VERSIONS)
+        .ok()
+        .filter(|value| !value.is_empty())?;
+    let mut frameworks = value
+        .split(',')
+        .map(|item| {
+            let (framework, version) = item
+                .split_once('=')
+                .map(|(framework, version)| (framework.trim(), version.trim()))
+                .filter(|(_, version)| !version.is_empty())
+                .unwrap_or_else(|| fail(&format!("invalid {FIXTURE_VERSIONS} item: {item}")));
+            let (framework, dependencies) = FRAMEWORKS
+                .iter()
+                .find(|(name, _)| *name == framework)
+                .unwrap_or_else(|| {
+                    fail(&format!(
+                        "unknown framework in {FIXTURE_VERSIONS}: {framework}"
+                    ))
+                });
+            (*framework, version, *dependencies)
+        })
+        .collect::<Vec<_>>();
+    frameworks.sort_by_key(|(framework, _, _)| *framework);
+
+    let label = frameworks
+        .iter()
+        .map(|(framework, version, _)| format!("{framework}-{version}"))
+        .collect::<Vec<_>>()
+        .join("-");
+    let dependencies = frameworks
+        .iter()
+        .flat_map(|(_, version, dependencies)| {
+            dependencies
+                .iter()
+                .map(move |dependency| (*dependency, (*version).to_owned()))
+        })
+        .collect();
+    Some(FixtureVersions {
+        label,
+        dependencies,
+    })
+}
+
+/// Copy the library at `path` to `target_dir/versions/{label}`, with the versions of its fixtures'
+/// dependencies and its ui tests' expected outputs replaced. Return the path of the copy.
+fn stage_library(
+    path: &Path,
+    root: &Path,
+    target_dir: &Path,
+    versions: &FixtureVersions,
+) -> PathBuf {
+    let staged = target_dir
+        .join("versions")
+        .join(&versions.label)
+        .join(library_name(path));
+    if staged.exists() {
+        remove_dir_all(&staged).unwrap();
+    }
+    copy_dir(path, &staged);
+
+    // The copy is not next to `crate` anymore.
+    let manifest = rewrite_dependencies(
+        &read_to_string(path.join("Cargo.toml")).unwrap(),
+        "[dev-dependencies]",
+        versions,
+    )
+    .replace(
+        "path = \"../../crate\"",
+        &format!("path = \"{}\"", root.join("crate").display()),
+    );
+    write(staged.join("Cargo.toml"), manifest).unwrap();
+
+    let ui = staged.join("ui");
+    if ui.exists() {
+        for entry in read_dir(&ui).unwrap() {
+            let dir = entry.unwrap().path();
+            let manifest = dir.join("Cargo.toml");
+            if manifest.exists() {
+                let contents = read_to_string(&manifest).unwrap();
+                write(
+                    &manifest,
+                    rewrite_dependencies(&contents, "[dependencies]", versions),
+                )
+                .unwrap();
+            }
+            let stderr = dir
+                .join("src")
+                .join(format!("lib.{}.stderr", versions.label));
+            if stderr.exists() {
+                copy(&stderr, dir.join("src").join("lib.stderr")).unwrap();
+            }
+        }
+    }
+
+    staged
+}
+
+/// Copy the directory `from` to `to`, excluding the build outputs and the lock file
+fn copy_dir(from: &Path, to: &Path) {
+    create_dir_all(to).unwrap();
+    for entry in read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let name = entry.file_name();
+        if name == "target" || name == "Cargo.lock" {
+            continue;
+        }
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &to.join(&name));
+        } else {
+            copy(entry.path(), to.join(&name)).unwrap();
+        }
+    }
+}
+
+/// Replace the versions of the dependencies in `section` of `manifest`, e.g., `anchor-lang =
+/// "0.29.0"`. Only the dependencies given as a version requirement are replaced.
+fn rewrite_dependencies(manifest: &str, section: &str, versions: &FixtureVersions) -> String {
+    let mut in_section = false;
+    manifest
+        .lines()
+        .map(|line| {
+            if line.starts_with('[') {
+                in_section = line.trim() == section;
+            }
+            let version = line
+                .split_once('=')
+                .filter(|(_, value)| in_section && value.trim().starts_with('"'))
+                .and_then(|(name, _)| {
+                    versions
+                        .dependencies
+                        .iter()
+                        .find(|(dependency, _)| *dependency == name.trim())
+                });
+            if let Some((dependency, version)) = version {
+                format!("{dependency} = \"{version}\"\n")
+            } else {
+                format!("{line}\n")
+            }
+        })
+        .collect()
+}
+
+/// Return the paths of the libraries in `lints` named in `libraries`, or of all of them if
+/// `libraries` is empty
+fn library_paths(root: &Path, libraries: &[String]) -> Vec<PathBuf> {
+    let mut paths = read_dir(root.join("lints"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.join("Cargo.toml").exists())
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for library in libraries {
+        if !paths.iter().any(|path| library_name(path) == *library) {
+            fail(&format!("unknown library: {library}"));
+        }
+    }
+
+    paths.retain(|path| libraries.is_empty() || libraries.contains(&library_name(path)));
+    paths
+}
+
+/// List the tests of the library at `path` with `cargo test -- --list`
+fn list_tests(path: &Path, target_dir: &Path) -> Vec<Test> {
+    let output = cargo(path, target_dir)
+        .args(["test", "--quiet", "--", "--list"])
+        .output()
+        .unwrap();
+    if !output.status.success() {
+        fail(&format!(
+            "failed to list tests of {}:\n{}",
+            library_name(path),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_suffix(": test"))
+        .map(|name| Test {
+            library: library_name(path),
+            path: path.to_path_buf(),
+            name: name.to_owned(),
+        })
+        .collect()
+}
+
+/// Run each test in its own process, with up to `jobs` processes running at the same time. Return
+/// the failed tests and their outputs.
+fn run_tests<'a>(tests: &'a [Test], target_dir: &Path, jobs: usize) -> Vec<(&'a Test, Output)> {
+    let next = AtomicUsize::new(0);
+    let failures = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| {
+                while let Some(test) = tests.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    let output = run_test(test, target_dir);
+                    let result = if output.status.success() {
+                        "ok"
+                    } else {
+                        "FAILED"
+                    };
+                    eprintln!("test {}::{} ... {result}", test.library, test.name);
+                    if !output.status.success() {
+                        failures.lock().unwrap().push((test, output));
+                    }
+                }
+            });
+        }
+    });
+
+    let mut failures = failures.into_inner().unwrap();
+    failures.sort_by(|(x, _), (y, _)| (&x.library, &x.name).cmp(&(&y.library, &y.name)));
+    failures
+}
+
+fn run_test(test: &Test, target_dir: &Path) -> Output {
+    // The ui tests of different libraries have sources with the same names, e.g.,
+    // `ui/insecure/src/lib.rs`. Giving each test its own temporary directory keeps their build
+    // outputs apart.
+    let tmp_dir = target_dir.join("tmp").join(&test.library).join(&test.name);
+    if tmp_dir.exists() {
+        remove_dir_all(&tmp_dir).unwrap();
+    }
+    create_dir_all(&tmp_dir).unwrap();
+
+    cargo(&test.path, target_dir)
+        .env("TMPDIR", &tmp_dir)
+        .args(["test", "--quiet", "--", "--exact", &test.name])
+        .output()
+        .unwrap()
+}
+
+fn cargo(path: &Path, target_dir: &Path) -> Command {
+    let mut command = Command::new("cargo");
+    command
+        .current_dir(path)
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .env("CARGO_TARGET_DIR", target_dir);
+    command
+}
+
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+fn library_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().into_owned()
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{msg}");
+    exit(1)
+}
@@ -0,0 +1,611 @@
+//! `cargo xtask metamorphic`: checks random Anchor programs and variants of them with the lint
+//! libraries, and asserts that the findings change in the expected direction:
+//!
+//! - adding a constraint to a field of an `Accounts` struct, e.g., `signer` or `owner = crate::ID`,
+//!   never introduces a finding;
+//! - removing a check from a handler, e.g., `require_keys_eq!(*account.owner, crate::ID)`, never
+//!   removes a finding.
+//!
+//! Each case is a program with one to three instructions, whose `Accounts` structs and handlers are
+//! assembled from the templates below, and two variants of it, each with one mutation. The cases
+//! are written to `target/lints/metamorphic`, one package per program, and are checked at once with
+//! `cargo dylint --workspace`. The findings are compared as multisets of `lint: message`, as their
+//! locations move with the mutations.
+//!
+//! The programs are generated from a seed, which is printed, so that a run is reproduced with
+//! `--seed S` and the same `--cases N`. The cases of the last run are kept on disk, so that a
+//! failing case can be inspected and turned into a ui test.
+
+use super::{build_libraries, fail, findings, library_name, library_paths, root};
+use std::{
+    fmt::Write as _,
+    fs::{create_dir_all, read_dir, remove_dir_all, write},
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const USAGE: &str = "usage: cargo xtask metamorphic [--cases N] [--seed S] [LIBRARY]...";
+
+/// The libraries checked when none are named. Their findings are missing checks, so adding a
+/// constraint or removing a check must not make them disappear or appear, respectively.
+const DEFAULT_LIBRARIES: &[&str] = &[
+    "missing_owner_check",
+    "missing_signer_check",
+    "unauthorized_lamport_debit",
+    "unsigned_authority_change",
+];
+
+const DEFAULT_CASES: usize = 16;
+
+/// The names of the fields other than `vault`
+const NAMES: &[&str] = &["authority", "payer", "recipient", "user"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    AccountInfo,
+    Unchecked,
+    Signer,
+    /// `Account<'info, Vault>`, whose field is always named `vault`
+    Vault,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Constraint {
+    Signer,
+    Owner,
+    HasOneAuthority,
+}
+
+#[derive(Clone)]
+struct Field {
+    name: &'static str,
+    kind: Kind,
+    constraints: Vec<Constraint>,
+}
+
+/// A statement of a handler, on the field at the given index
+#[derive(Clone, Copy, PartialEq)]
+enum Stmt {
+    OwnerCheck(usize),
+    SignerCheck(usize),
+    AuthorityCheck(usize),
+    ReadData(usize),
+    Debit(usize),
+    Credit(usize),
+    SetAuthority(usize),
+    SetAmount,
+}
+
+#[derive(Clone)]
+struct Instruction {
+    fields: Vec<Field>,
+    /// The checks, then the uses
+    body: Vec<Stmt>,
+}
+
+#[derive(Clone)]
+struct Program {
+    instructions: Vec<Instruction>,
+}
+
+/// A program and its variants, each with the description of its mutation
+struct Case {
+    base: Program,
+    constrained: (Program, String),
+    unchecked: (Program, String),
+}
+
+/// A SplitMix64 generator, so that a run is reproduced from its seed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        usize::try_from(self.next() % n as u64).unwrap()
+    }
+
+    fn chance(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+pub fn metamorphic(mut args: impl Iterator<Item = String>) {
+    let mut cases = DEFAULT_CASES;
+    let mut seed = None;
+    let mut libraries = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--cases" {
+            cases = args
+                .next()
+                .and_then(|value| value.parse().ok())
+                .filter(|&value| value > 0)
+                .unwrap_or_else(|| fail(USAGE));
+        } else if arg == "--seed" {
+            seed = Some(
+                args.next()
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or_else(|| fail(USAGE)),
+            );
+        } else if arg.starts_with('-') {
+            fail(USAGE);
+        } else {
+            libraries.push(arg);
+        }
+    }
+    if libraries.is_empty() {
+        libraries = DEFAULT_LIBRARIES.iter().map(ToString::to_string).collect();
+    }
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    });
+
+    let root = root();
+    let target_dir = root.join("target").join("lints");
+    let dir = target_dir.join("metamorphic");
+
+    eprintln!("Using the seed {seed}");
+    let mut rng = Rng(seed);
+    let cases = (0..cases)
+        .map(|_| Case::generate(&mut rng))
+        .collect::<Vec<_>>();
+    write_cases(&dir, &cases);
+
+    let paths = library_paths(&root, &libraries);
+    let library_files = build_libraries(&paths, &target_dir);
+
+    eprintln!("Checking {} cases in {}", cases.len(), dir.display());
+    let output = Command::new("cargo")
+        .current_dir(&dir)
+        .env_remove("RUSTUP_TOOLCHAIN")
+        .arg("dylint")
+        .args(
+            library_files
+                .iter()
+                .flat_map(|file| [Path::new("--lib-path"), file.as_path()]),
+        )
+        .args(["--workspace", "--", "--message-format=json"])
+        .output()
+        .unwrap();
+    if !output.status.success() {
+        fail(&format!(
+            "failed to check {}:\n{}",
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    let names = paths
+        .iter()
+        .map(|path| library_name(path))
+        .collect::<Vec<_>>();
+    let findings = findings(&String::from_utf8_lossy(&output.stdout));
+    let findings_of = |package: &str| -> Vec<String> {
+        findings
+            .lines()
+            .filter_map(|line| line.split_once(": "))
+            .filter(|(location, _)| location.starts_with(&format!("{package}/")))
+            .map(|(_, finding)| finding.to_owned())
+            .filter(|finding| {
+                names
+                    .iter()
+                    .any(|name| finding.starts_with(&format!("{name}: ")))
+            })
+            .collect()
+    };
+
+    let mut violations = String::new();
+    for (n, case) in cases.iter().enumerate() {
+        let base = findings_of(&format!("{n}/base"));
+        let (_, constraint) = &case.constrained;
+        for finding in difference(&findings_of(&format!("{n}/constrained")), &base) {
+            writeln!(violations, "case {n}: {constraint} introduced `{finding}`").unwrap();
+        }
+        let (_, check) = &case.unchecked;
+        for finding in difference(&base, &findings_of(&format!("{n}/unchecked"))) {
+            writeln!(violations, "case {n}: {check} removed `{finding}`").unwrap();
+        }
+    }
+
+    if !violations.is_empty() {
+        fail(&format!(
+            "{violations}\nThe cases are in {}. Rerun them with `--seed {seed}`.",
+            dir.display()
+        ));
+    }
+    eprintln!("{} cases passed", cases.len());
+}
+
+/// Return the findings of `after` which are not in `before`, counting the repeated findings
+fn difference(after: &[String], before: &[String]) -> Vec<String> {
+    let mut before = before.to_vec();
+    let mut new = Vec::new();
+    for finding in after {
+        if let Some(i) = before.iter().position(|other| other == finding) {
+            before.swap_remove(i);
+        } else {
+            new.push(finding.clone());
+        }
+    }
+    new
+}
+
+/// Write the cases as the packages of a workspace in `dir`, replacing those of the previous run.
+/// The target directory of the workspace is kept, so that `anchor-lang` is built only once.
+fn write_cases(dir: &Path, cases: &[Case]) {
+    create_dir_all(dir).unwrap();
+    for entry in read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() && path.file_name().map_or(false, |name| name != "target") {
+            remove_dir_all(path).unwrap();
+        }
+    }
+
+    let mut members = Vec::new();
+    for (n, case) in cases.iter().enumerate() {
+        for (variant, program) in [
+            ("base", &case.base),
+            ("constrained", &case.constrained.0),
+            ("unchecked", &case.unchecked.0),
+        ] {
+            let package = dir.join(n.to_string()).join(variant);
+            create_dir_all(package.join("src")).unwrap();
+            write(package.join("Cargo.toml"), manifest(n, variant)).unwrap();
+            write(package.join("src").join("lib.rs"), program.render()).unwrap();
+            members.push(format!("    \"{n}/{variant}\",\n"));
+        }
+    }
+    write(
+        dir.join("Cargo.toml"),
+        format!("[workspace]\nmembers = [\n{}]\n", members.concat()),
+    )
+    .unwrap();
+}
+
+fn manifest(n: usize, variant: &str) -> String {
+    format!(
+        "[package]
+name = \"case-{n}-{variant}\"
+version = \"0.1.0\"
+edition = \"2018\"
+publish = false
+
+[lib]
+crate-type = [\"cdylib\", \"lib\"]
+
+[features]
+no-entrypoint = []
+no-idl = []
+no-log-ix-name = []
+cpi = [\"no-entrypoint\"]
+default = []
+
+[dependencies]
+anchor-lang = \"0.29.0\"
+"
+    )
+}
+
+impl Case {
+    /// Generate a program with at least one field to constrain and one check to remove, and its
+    /// variants
+    fn generate(rng: &mut Rng) -> Self {
+        loop {
+            let base = Program::generate(rng);
+
+            let constraints = base
+                .instructions
+                .iter()
+                .enumerate()
+                .flat_map(|(i, instruction)| {
+                    (0..instruction.fields.len()).flat_map(move |j| {
+                        instruction
+                            .missing_constraints(j)
+                            .into_iter()
+                            .map(move |constraint| (i, j, constraint))
+                    })
+                })
+                .collect::<Vec<_>>();
+            let checks = base
+                .instructions
+                .iter()
+                .enumerate()
+                .flat_map(|(i, instruction)| {
+                    instruction
+                        .body
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, stmt)| stmt.is_check())
+                        .map(move |(k, _)| (i, k))
+                })
+                .collect::<Vec<_>>();
+            if constraints.is_empty() || checks.is_empty() {
+                continue;
+            }
+
+            let (i, j, constraint) = constraints[rng.below(constraints.len())];
+            let mut constrained = base.clone();
+            constrained.instructions[i].fields[j]
+                .constraints
+                .push(constraint);
+            let constraint = format!(
+                "adding `{}` to `Instruction{i}::{}`",
+                constraint.render(),
+                base.instructions[i].fields[j].name
+            );
+
+            let (i, k) = checks[rng.below(checks.len())];
+            let mut unchecked = base.clone();
+            let stmt = unchecked.instructions[i].body.remove(k);
+            let check = format!(
+                "removing `{}` from `instruction_{i}`",
+                stmt.render(&base.instructions[i].fields)
+            );
+
+            return Self {
+                base,
+                constrained: (constrained, constraint),
+                unchecked: (unchecked, check),
+            };
+        }
+    }
+}
+
+impl Program {
+    fn generate(rng: &mut Rng) -> Self {
+        let instructions = (0..1 + rng.below(3))
+            .map(|_| Instruction::generate(rng))
+            .collect();
+        Self { instructions }
+    }
+
+    fn render(&self) -> String {
+        let mut handlers = String::new();
+        let mut structs = String::new();
+        for (i, instruction) in self.instructions.iter().enumerate() {
+            write!(
+                handlers,
+                "
+    pub fn instruction_{i}(ctx: Context<Instruction{i}>, amount: u64) -> Result<()> {{
+"
+            )
+            .unwrap();
+            for stmt in &instruction.body {
+                writeln!(handlers, "        {}", stmt.render(&instruction.fields)).unwrap();
+            }
+            handlers.push_str("        Ok(())\n    }\n");
+
+            write!(
+                structs,
+                "
+#[derive(Accounts)]
+pub struct Instruction{i}<'info> {{
+"
+            )
+            .unwrap();
+            for j in 0..instruction.fields.len() {
+                structs.push_str(&instruction.render_field(j));
+            }
+            structs.push_str("}\n");
+        }
+
+        format!(
+            "#![allow(unused_variables)]
+
+use anchor_lang::prelude::*;
+
+declare_id!(\"Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS\");
+
+#[program]
+pub mod metamorphic {{
+    use super::*;
+{handlers}}}
+{structs}
+#[account]
+pub struct Vault {{
+    pub authority: Pubkey,
+    pub amount: u64,
+}}
+"
+        )
+    }
+}
+
+impl Instruction {
+    fn generate(rng: &mut Rng) -> Self {
+        let mut fields = Vec::new();
+        if rng.chance(2) {
+            fields.push(Field {
+                name: "vault",
+                kind: Kind::Vault,
+                constraints: Vec::new(),
+            });
+        }
+        let mut names = NAMES.to_vec();
+        rng.shuffle(&mut names);
+        for name in names.into_iter().take(1 + rng.below(3)) {
+            let kind = [Kind::AccountInfo, Kind::Unchecked, Kind::Signer][rng.below(3)];
+            fields.push(Field {
+                name,
+                kind,
+                constraints: Vec::new(),
+            });
+        }
+
+        let mut instruction = Self {
+            fields,
+            body: Vec::new(),
+        };
+        for j in 0..instruction.fields.len() {
+            for constraint in instruction.missing_constraints(j) {
+                if rng.chance(4) {
+                    instruction.fields[j].constraints.push(constraint);
+                }
+            }
+        }
+
+        let (mut checks, mut uses) = (Vec::new(), Vec::new());
+        for j in 0..instruction.fields.len() {
+            checks.extend(instruction.checks(j).into_iter().filter(|_| rng.chance(3)));
+            uses.extend(instruction.uses(j).into_iter().filter(|_| rng.chance(3)));
+        }
+        rng.shuffle(&mut checks);
+        rng.shuffle(&mut uses);
+        instruction.body = checks.into_iter().chain(uses).collect();
+        instruction
+    }
+
+    fn has_vault(&self) -> bool {
+        self.fields.iter().any(|field| field.kind == Kind::Vault)
+    }
+
+    /// The constraints which may be added to the field `j`
+    fn missing_constraints(&self, j: usize) -> Vec<Constraint> {
+        let field = &self.fields[j];
+        let constraints = match field.kind {
+            Kind::AccountInfo | Kind::Unchecked => vec![Constraint::Signer, Constraint::Owner],
+            Kind::Vault if self.fields.iter().any(|field| field.name == "authority") => {
+                vec![Constraint::HasOneAuthority]
+            }
+            _ => Vec::new(),
+        };
+        constraints
+            .into_iter()
+            .filter(|constraint| !field.constraints.contains(constraint))
+            .collect()
+    }
+
+    /// The checks which may be made on the field `j`
+    fn checks(&self, j: usize) -> Vec<Stmt> {
+        let mut checks = Vec::new();
+        if matches!(self.fields[j].kind, Kind::AccountInfo | Kind::Unchecked) {
+            checks.extend([Stmt::OwnerCheck(j), Stmt::SignerCheck(j)]);
+        }
+        if self.fields[j].kind != Kind::Vault && self.has_vault() {
+            checks.push(Stmt::AuthorityCheck(j));
+        }
+        checks
+    }
+
+    /// The uses which may be made of the field `j`
+    fn uses(&self, j: usize) -> Vec<Stmt> {
+        let mut uses = Vec::new();
+        match self.fields[j].kind {
+            Kind::AccountInfo | Kind::Unchecked => {
+                uses.extend([Stmt::ReadData(j), Stmt::Debit(j), Stmt::Credit(j)]);
+            }
+            Kind::Signer => {}
+            Kind::Vault => uses.push(Stmt::SetAmount),
+        }
+        if self.fields[j].kind != Kind::Vault && self.has_vault() {
+            uses.push(Stmt::SetAuthority(j));
+        }
+        uses
+    }
+
+    fn render_field(&self, j: usize) -> String {
+        let field = &self.fields[j];
+        let writes_lamports = self
+            .body
+            .iter()
+            .any(|stmt| matches!(stmt, Stmt::Debit(k) | Stmt::Credit(k) if *k == j));
+        let mut constraints = Vec::new();
+        if field.kind == Kind::Vault || writes_lamports {
+            constraints.push("mut");
+        }
+        constraints.extend(
+            field
+                .constraints
+                .iter()
+                .map(|constraint| constraint.render()),
+        );
+
+        let mut rendered = String::new();
+        if matches!(field.kind, Kind::AccountInfo | Kind::Unchecked) {
+            rendered.push_str("    /// CHECK: generated\n");
+        }
+        if !constraints.is_empty() {
+            writeln!(rendered, "    #[account({})]", constraints.join(", ")).unwrap();
+        }
+        let ty = match field.kind {
+            Kind::AccountInfo => "AccountInfo<'info>",
+            Kind::Unchecked => "UncheckedAccount<'info>",
+            Kind::Signer => "Signer<'info>",
+            Kind::Vault => "Account<'info, Vault>",
+        };
+        writeln!(rendered, "    pub {}: {ty},", field.name).unwrap();
+        rendered
+    }
+}
+
+impl Constraint {
+    fn render(self) -> &'static str {
+        match self {
+            Self::Signer => "signer",
+            Self::Owner => "owner = crate::ID",
+            Self::HasOneAuthority => "has_one = authority",
+        }
+    }
+}
+
+impl Stmt {
+    fn is_check(self) -> bool {
+        matches!(
+            self,
+            Self::OwnerCheck(_) | Self::SignerCheck(_) | Self::AuthorityCheck(_)
+        )
+    }
+
+    fn render(self, fields: &[Field]) -> String {
+        let name = |j: usize| fields[j].name;
+        match self {
+            Self::OwnerCheck(j) => {
+                format!(
+                    "require_keys_eq!(*ctx.accounts.{}.owner, crate::ID);",
+                    name(j)
+                )
+            }
+            Self::SignerCheck(j) => format!(
+                "require!(ctx.accounts.{}.is_signer, ErrorCode::AccountNotSigner);",
+                name(j)
+            ),
+            Self::AuthorityCheck(j) => format!(
+                "require_keys_eq!(ctx.accounts.vault.authority, ctx.accounts.{}.key());",
+                name(j)
+            ),
+            Self::ReadData(j) => format!(
+                "msg!(\"{{}}\", ctx.accounts.{}.try_borrow_data()?.len());",
+                name(j)
+            ),
+            Self::Debit(j) => format!(
+                "**ctx.accounts.{}.try_borrow_mut_lamports()? -= amount;",
+                name(j)
+            ),
+            Self::Credit(j) => format!(
+                "**ctx.accounts.{}.try_borrow_mut_lamports()? += amount;",
+                name(j)
+            ),
+            Self::SetAuthority(j) => format!(
+                "ctx.accounts.vault.authority = ctx.accounts.{}.key();",
+                name(j)
+            ),
+            Self::SetAmount => "ctx.accounts.vault.amount = amount;".to_owned(),
+        }
+    }
+}